@@ -0,0 +1,58 @@
+//! Per-call latency of the motor controller API.
+//!
+//! Off the roboRIO this measures the wrappers over the simulated CCI, which is
+//! enough to catch overhead added by new wrapper layers.  Cross-compiled for
+//! the roboRIO, it measures a real Talon SRX; set `CTRE_BENCH_TALON` to its
+//! device number (default 0).
+
+#[macro_use]
+extern crate criterion;
+extern crate ctre;
+
+use std::convert::TryFrom;
+use std::env;
+
+use criterion::Criterion;
+
+use ctre::control::VelocityRequest;
+use ctre::motor_control::{BaseMotorController, ControlMode, DemandType, Slot, TalonSRX};
+use ctre::DeviceId;
+
+fn talon() -> TalonSRX {
+    let id = env::var("CTRE_BENCH_TALON")
+        .ok()
+        .and_then(|id| id.parse::<i32>().ok())
+        .and_then(|id| DeviceId::try_from(id).ok());
+    TalonSRX::new(id.unwrap_or(DeviceId::new(0)))
+}
+
+fn bench_set(c: &mut Criterion) {
+    let talon = talon();
+    c.bench_function("set", |b| {
+        b.iter(|| talon.set(ControlMode::PercentOutput, 0., DemandType::Neutral, 0.))
+    });
+    let request = VelocityRequest::new(0.).with_slot(Slot::S0);
+    c.bench_function("apply", |b| b.iter(|| talon.apply(&request)));
+}
+
+fn bench_getters(c: &mut Criterion) {
+    let talon = talon();
+    c.bench_function("get_selected_sensor_position", |b| {
+        b.iter(|| talon.get_selected_sensor_position(0))
+    });
+    c.bench_function("get_motor_output_percent", |b| {
+        b.iter(|| talon.get_motor_output_percent())
+    });
+    c.bench_function("get_faults", |b| b.iter(|| talon.get_faults()));
+}
+
+fn bench_configs(c: &mut Criterion) {
+    let talon = talon();
+    c.bench_function("config_kp", |b| b.iter(|| talon.config_kp(0, 0., 10)));
+    c.bench_function("config_get_parameter", |b| {
+        b.iter(|| talon.config_get_parameter(ctre::ParamEnum::ProfileParamSlot_P, 0, 10))
+    });
+}
+
+criterion_group!(benches, bench_set, bench_getters, bench_configs);
+criterion_main!(benches);
@@ -1,4 +1,4 @@
-// use std::env;
+use std::env;
 
 const LIB_LIST: &[&str] = &[
     "FRC_NetworkCommunication",
@@ -9,12 +9,126 @@ const LIB_LIST: &[&str] = &[
     "NiRioSrv",
     "RoboRIO_FRC_ChipObject",
     "visa",
-//    "./lib/libCTRE_Phoenix.so",
+    //    "./lib/libCTRE_Phoenix.so",
+];
+
+/// The roboRIO's target, as `cfg` facts.  This must match the `cfg` the `ctre`
+/// crate's manifest uses to enable `no-link` off the roboRIO.
+const ROBORIO_CFG: &[(&str, &str)] = &[
+    ("CARGO_CFG_TARGET_ARCH", "arm"),
+    ("CARGO_CFG_TARGET_OS", "linux"),
+    ("CARGO_CFG_TARGET_ENV", "gnu"),
+    ("CARGO_CFG_TARGET_ABI", "eabi"),
 ];
 
 fn main() {
-    for lib in LIB_LIST {
-        println!("cargo:rustc-link-lib=dylib={}", lib);
+    let target = env::var("TARGET").unwrap();
+    let roborio = ROBORIO_CFG
+        .iter()
+        .all(|&(key, value)| env::var(key).is_ok_and(|actual| actual == value));
+
+    // With `dlopen`, the Phoenix library (and through it the NI libraries)
+    // is loaded at runtime instead.  This wins over `no-link`, which the
+    // `ctre` crate enables on every target other than the roboRIO.
+    let dlopen = env::var_os("CARGO_FEATURE_DLOPEN").is_some();
+
+    // With `no-link`, the CCI is provided by the `sim` module instead.
+    let no_link = env::var_os("CARGO_FEATURE_NO_LINK").is_some() && !dlopen;
+
+    // The NI and Phoenix libraries only exist for the roboRIO.
+    if !roborio && !no_link && !dlopen {
+        panic!(
+            "the Phoenix libraries can't be linked for {}, only for the roboRIO \
+             (target_arch \"arm\", target_os \"linux\", target_env \"gnu\", \
+             target_abi \"eabi\"); enable the `no-link` feature to use the \
+             in-process stand-in, or `dlopen` to load Phoenix at runtime",
+            target
+        );
+    }
+
+    println!("cargo:rustc-check-cfg=cfg(ctre_no_link)");
+    if no_link {
+        println!("cargo:rustc-cfg=ctre_no_link");
+    } else if !dlopen {
+        for lib in LIB_LIST {
+            println!("cargo:rustc-link-lib=dylib={}", lib);
+        }
+        println!("cargo:rustc-link-lib=stdc++");
+    }
+
+    #[cfg(feature = "regen-bindings")]
+    regen::generate_all();
+}
+
+/// Regenerates the CCI function declarations with bindgen.
+///
+/// The headers are taken from the Phoenix-api submodule (whose commit pins the
+/// Phoenix version these bindings target), or from `$PHOENIX_ROOT` if set.
+/// Only functions are generated; the enums and structs in this crate are
+/// hand-tidied and stay as they are.
+#[cfg(feature = "regen-bindings")]
+mod regen {
+    extern crate bindgen;
+
+    use std::env;
+    use std::path::PathBuf;
+
+    /// (module, wrapper header, function prefix, whether it has device handles)
+    const MODULES: &[(&str, &str, &str, bool)] = &[
+        ("mot", "gen/mot_wrapper.hpp", "c_MotController_.*", true),
+        ("pigeon", "gen/pigeon_wrapper.hpp", "c_PigeonIMU_.*", true),
+        (
+            "canifier",
+            "gen/canifier_wrapper.hpp",
+            "c_CANifier_.*",
+            true,
+        ),
+        ("logger", "gen/logger_wrapper.hpp", "c_Logger_.*", false),
+    ];
+
+    /// C types for the generated declarations.  The CCI passes device handles
+    /// around as `void *`, so in modules with handles `c_void` is the module's
+    /// `_Handle`, making `void *` its `Handle`.
+    const CTYPES: &str = "mod ctypes { pub use std::os::raw::*; }";
+    const CTYPES_WITH_HANDLES: &str =
+        "mod ctypes { pub use std::os::raw::*; pub type c_void = super::_Handle; }";
+
+    pub fn generate_all() {
+        let manifest_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+        let phoenix_root = match env::var_os("PHOENIX_ROOT") {
+            Some(root) => PathBuf::from(root),
+            None => manifest_dir.join("../Phoenix-api"),
+        };
+        let include_dir = phoenix_root.join("libraries/driver/include");
+        if !include_dir.is_dir() {
+            panic!(
+                "Phoenix headers not found at {}; run `make reload-phoenix` or set PHOENIX_ROOT",
+                include_dir.display()
+            );
+        }
+        println!("cargo:rerun-if-env-changed=PHOENIX_ROOT");
+
+        let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+        for &(module, wrapper, functions, handles) in MODULES {
+            let wrapper = manifest_dir.join(wrapper);
+            println!("cargo:rerun-if-changed={}", wrapper.display());
+
+            let bindings = bindgen::Builder::default()
+                .header(wrapper.to_str().unwrap())
+                .clang_args(&["-x", "c++", "-std=c++11"])
+                .clang_arg(format!("-I{}", include_dir.display()))
+                .disable_name_namespacing()
+                .allowlist_function(functions)
+                .blocklist_type(".*")
+                .ctypes_prefix("ctypes")
+                .raw_line(if handles { CTYPES_WITH_HANDLES } else { CTYPES })
+                .layout_tests(false)
+                .generate()
+                .unwrap_or_else(|_| panic!("unable to generate bindings for {}", module));
+
+            bindings
+                .write_to_file(out_dir.join(format!("{}.rs", module)))
+                .expect("couldn't write bindings");
+        }
     }
-    println!("cargo:rustc-link-lib=stdc++");
 }
@@ -0,0 +1 @@
+#include "ctre/phoenix/CCI/Logger_CCI.h"
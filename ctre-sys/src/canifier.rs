@@ -1,6 +1,7 @@
 //! Enums and functions related to the CANifier.
 #![allow(non_camel_case_types)]
 
+#[cfg_attr(feature = "regen-bindings", allow(unused_imports))]
 use std::os::raw::{c_char, c_int};
 use ErrorCode;
 
@@ -16,6 +17,10 @@ pub enum CANifierControlFrame {
     Control_1_General = 50593792,
     Control_2_PwmOutput = 50593856,
 }
+enum_names!(CANifierControlFrame {
+    Control_1_General,
+    Control_2_PwmOutput,
+});
 #[repr(i32)]
 /// Enumerated type for status frame types.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
@@ -28,6 +33,15 @@ pub enum CANifierStatusFrame {
     Status_6_PwmInputs3 = 267584,
     Status_8_Misc = 267712,
 }
+enum_names!(CANifierStatusFrame {
+    Status_1_General,
+    Status_2_General,
+    Status_3_PwmInputs0,
+    Status_4_PwmInputs1,
+    Status_5_PwmInputs2,
+    Status_6_PwmInputs3,
+    Status_8_Misc,
+});
 
 #[repr(i32)]
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
@@ -41,6 +55,16 @@ pub enum CANifierVelocityMeasPeriod {
     Period_50Ms = 50,
     Period_100Ms = 100,
 }
+enum_names!(CANifierVelocityMeasPeriod {
+    Period_1Ms,
+    Period_2Ms,
+    Period_5Ms,
+    Period_10Ms,
+    Period_20Ms,
+    Period_25Ms,
+    Period_50Ms,
+    Period_100Ms,
+});
 
 #[repr(u32)]
 /// General IO Pins on the CANifier
@@ -58,147 +82,186 @@ pub enum GeneralPin {
     SPI_MOSI_PWM1P = 9,
     SPI_CLK_PWM0P = 10,
 }
+enum_names!(GeneralPin {
+    QUAD_IDX,
+    QUAD_B,
+    QUAD_A,
+    LIMR,
+    LIMF,
+    SDA,
+    SCL,
+    SPI_CS,
+    SPI_MISO_PWM2P,
+    SPI_MOSI_PWM1P,
+    SPI_CLK_PWM0P,
+});
+
+#[cfg(feature = "regen-bindings")]
+include!(concat!(env!("OUT_DIR"), "/canifier.rs"));
+
+#[cfg(not(feature = "regen-bindings"))]
+cci! {
+    extern "C" {
+        pub fn c_CANifier_Create1(deviceNumber: c_int) -> Handle;
+
+        pub fn c_CANifier_GetDescription(
+            handle: Handle,
+            toFill: *mut c_char,
+            toFillByteSz: c_int,
+            numBytesFilled: *mut c_int,
+        ) -> ErrorCode;
+
+        pub fn c_CANifier_SetLEDOutput(
+            handle: Handle,
+            dutyCycle: u32,
+            ledChannel: u32,
+        ) -> ErrorCode;
+
+        pub fn c_CANifier_SetGeneralOutputs(
+            handle: Handle,
+            outputsBits: u32,
+            isOutputBits: u32,
+        ) -> ErrorCode;
+
+        pub fn c_CANifier_SetGeneralOutput(
+            handle: Handle,
+            outputPin: u32,
+            outputValue: bool,
+            outputEnable: bool,
+        ) -> ErrorCode;
+
+        pub fn c_CANifier_SetPWMOutput(
+            handle: Handle,
+            pwmChannel: u32,
+            dutyCycle: u32,
+        ) -> ErrorCode;
+
+        pub fn c_CANifier_EnablePWMOutput(
+            handle: Handle,
+            pwmChannel: u32,
+            bEnable: bool,
+        ) -> ErrorCode;
+
+        pub fn c_CANifier_GetGeneralInputs(
+            handle: Handle,
+            allPins: *mut bool,
+            capacity: u32,
+        ) -> ErrorCode;
+
+        pub fn c_CANifier_GetGeneralInput(
+            handle: Handle,
+            inputPin: u32,
+            measuredInput: *mut bool,
+        ) -> ErrorCode;
+
+        /// dutyCycleAndPeriod takes an array of length 2
+        pub fn c_CANifier_GetPWMInput(
+            handle: Handle,
+            pwmChannel: u32,
+            dutyCycleAndPeriod: *mut f64,
+        ) -> ErrorCode;
+
+        pub fn c_CANifier_GetLastError(handle: Handle) -> ErrorCode;
+
+        pub fn c_CANifier_GetBusVoltage(handle: Handle, batteryVoltage: *mut f64) -> ErrorCode;
+
+        pub fn c_CANifier_GetQuadraturePosition(handle: Handle, pos: *mut c_int) -> ErrorCode;
+
+        pub fn c_CANifier_SetQuadraturePosition(
+            handle: Handle,
+            pos: c_int,
+            timeoutMs: c_int,
+        ) -> ErrorCode;
+
+        pub fn c_CANifier_GetQuadratureVelocity(handle: Handle, vel: *mut c_int) -> ErrorCode;
+
+        pub fn c_CANifier_GetQuadratureSensor(
+            handle: Handle,
+            pos: *mut c_int,
+            vel: *mut c_int,
+        ) -> ErrorCode;
+
+        pub fn c_CANifier_ConfigVelocityMeasurementPeriod(
+            handle: Handle,
+            period: c_int,
+            timeoutMs: c_int,
+        ) -> ErrorCode;
+
+        pub fn c_CANifier_ConfigVelocityMeasurementWindow(
+            handle: Handle,
+            window: c_int,
+            timeoutMs: c_int,
+        ) -> ErrorCode;
+
+        pub fn c_CANifier_SetLastError(handle: Handle, error: c_int);
+
+        pub fn c_CANifier_ConfigSetParameter(
+            handle: Handle,
+            param: c_int,
+            value: f64,
+            subValue: c_int,
+            ordinal: c_int,
+            timeoutMs: c_int,
+        ) -> ErrorCode;
+
+        pub fn c_CANifier_ConfigGetParameter(
+            handle: Handle,
+            param: c_int,
+            value: *mut f64,
+            ordinal: c_int,
+            timeoutMs: c_int,
+        ) -> ErrorCode;
+
+        pub fn c_CANifier_ConfigSetCustomParam(
+            handle: Handle,
+            newValue: c_int,
+            paramIndex: c_int,
+            timeoutMs: c_int,
+        ) -> ErrorCode;
+
+        pub fn c_CANifier_ConfigGetCustomParam(
+            handle: Handle,
+            readValue: *mut c_int,
+            paramIndex: c_int,
+            timoutMs: c_int,
+        ) -> ErrorCode;
+
+        pub fn c_CANifier_GetFaults(handle: Handle, param: *mut c_int) -> ErrorCode;
+
+        pub fn c_CANifier_GetStickyFaults(handle: Handle, param: *mut c_int) -> ErrorCode;
+
+        pub fn c_CANifier_ClearStickyFaults(handle: Handle, timeoutMs: c_int) -> ErrorCode;
+
+        pub fn c_CANifier_GetFirmwareVersion(handle: Handle, firmwareVers: *mut c_int) -> ErrorCode;
+
+        pub fn c_CANifier_HasResetOccurred(handle: Handle, hasReset: *mut bool) -> ErrorCode;
+
+        pub fn c_CANifier_SetStatusFramePeriod(
+            handle: Handle,
+            frame: c_int,
+            periodMs: c_int,
+            timeoutMs: c_int,
+        ) -> ErrorCode;
+
+        pub fn c_CANifier_GetStatusFramePeriod(
+            handle: Handle,
+            frame: c_int,
+            periodMs: *mut c_int,
+            timeoutMs: c_int,
+        ) -> ErrorCode;
+
+        pub fn c_CANifier_SetControlFramePeriod(
+            handle: Handle,
+            frame: c_int,
+            periodMs: c_int,
+        ) -> ErrorCode;
+    }
+}
 
-extern "C" {
-    pub fn c_CANifier_Create1(deviceNumber: c_int) -> Handle;
-
-    pub fn c_CANifier_GetDescription(
-        handle: Handle,
-        toFill: *mut c_char,
-        toFillByteSz: c_int,
-        numBytesFilled: *mut c_int,
-    ) -> ErrorCode;
-
-    pub fn c_CANifier_SetLEDOutput(handle: Handle, dutyCycle: u32, ledChannel: u32) -> ErrorCode;
-
-    pub fn c_CANifier_SetGeneralOutputs(
-        handle: Handle,
-        outputsBits: u32,
-        isOutputBits: u32,
-    ) -> ErrorCode;
-
-    pub fn c_CANifier_SetGeneralOutput(
-        handle: Handle,
-        outputPin: u32,
-        outputValue: bool,
-        outputEnable: bool,
-    ) -> ErrorCode;
-
-    pub fn c_CANifier_SetPWMOutput(handle: Handle, pwmChannel: u32, dutyCycle: u32) -> ErrorCode;
-
-    pub fn c_CANifier_EnablePWMOutput(handle: Handle, pwmChannel: u32, bEnable: bool) -> ErrorCode;
-
-    pub fn c_CANifier_GetGeneralInputs(
-        handle: Handle,
-        allPins: *mut bool,
-        capacity: u32,
-    ) -> ErrorCode;
-
-    pub fn c_CANifier_GetGeneralInput(
-        handle: Handle,
-        inputPin: u32,
-        measuredInput: *mut bool,
-    ) -> ErrorCode;
-
-    /// dutyCycleAndPeriod takes an array of length 2
-    pub fn c_CANifier_GetPWMInput(
-        handle: Handle,
-        pwmChannel: u32,
-        dutyCycleAndPeriod: *mut f64,
-    ) -> ErrorCode;
-
-    pub fn c_CANifier_GetLastError(handle: Handle) -> ErrorCode;
-
-    pub fn c_CANifier_GetBusVoltage(handle: Handle, batteryVoltage: *mut f64) -> ErrorCode;
-
-    pub fn c_CANifier_GetQuadraturePosition(handle: Handle, pos: *mut c_int) -> ErrorCode;
-
-    pub fn c_CANifier_SetQuadraturePosition(
-        handle: Handle,
-        pos: c_int,
-        timeoutMs: c_int,
-    ) -> ErrorCode;
-
-    pub fn c_CANifier_GetQuadratureVelocity(handle: Handle, vel: *mut c_int) -> ErrorCode;
-
-    pub fn c_CANifier_GetQuadratureSensor(
-        handle: Handle,
-        pos: *mut c_int,
-        vel: *mut c_int,
-    ) -> ErrorCode;
-
-    pub fn c_CANifier_ConfigVelocityMeasurementPeriod(
-        handle: Handle,
-        period: c_int,
-        timeoutMs: c_int,
-    ) -> ErrorCode;
-
-    pub fn c_CANifier_ConfigVelocityMeasurementWindow(
-        handle: Handle,
-        window: c_int,
-        timeoutMs: c_int,
-    ) -> ErrorCode;
-
-    pub fn c_CANifier_SetLastError(handle: Handle, error: c_int);
-
-    pub fn c_CANifier_ConfigSetParameter(
-        handle: Handle,
-        param: c_int,
-        value: f64,
-        subValue: c_int,
-        ordinal: c_int,
-        timeoutMs: c_int,
-    ) -> ErrorCode;
-
-    pub fn c_CANifier_ConfigGetParameter(
-        handle: Handle,
-        param: c_int,
-        value: *mut f64,
-        ordinal: c_int,
-        timeoutMs: c_int,
-    ) -> ErrorCode;
-
-    pub fn c_CANifier_ConfigSetCustomParam(
-        handle: Handle,
-        newValue: c_int,
-        paramIndex: c_int,
-        timeoutMs: c_int,
-    ) -> ErrorCode;
-
-    pub fn c_CANifier_ConfigGetCustomParam(
-        handle: Handle,
-        readValue: *mut c_int,
-        paramIndex: c_int,
-        timoutMs: c_int,
-    ) -> ErrorCode;
-
-    pub fn c_CANifier_GetFaults(handle: Handle, param: *mut c_int) -> ErrorCode;
-
-    pub fn c_CANifier_GetStickyFaults(handle: Handle, param: *mut c_int) -> ErrorCode;
-
-    pub fn c_CANifier_ClearStickyFaults(handle: Handle, timeoutMs: c_int) -> ErrorCode;
-
-    pub fn c_CANifier_GetFirmwareVersion(handle: Handle, firmwareVers: *mut c_int) -> ErrorCode;
-
-    pub fn c_CANifier_HasResetOccurred(handle: Handle, hasReset: *mut bool) -> ErrorCode;
-
-    pub fn c_CANifier_SetStatusFramePeriod(
-        handle: Handle,
-        frame: c_int,
-        periodMs: c_int,
-        timeoutMs: c_int,
-    ) -> ErrorCode;
-
-    pub fn c_CANifier_GetStatusFramePeriod(
-        handle: Handle,
-        frame: c_int,
-        periodMs: *mut c_int,
-        timeoutMs: c_int,
-    ) -> ErrorCode;
-
-    pub fn c_CANifier_SetControlFramePeriod(
-        handle: Handle,
-        frame: c_int,
-        periodMs: c_int,
-    ) -> ErrorCode;
+// Functions added in Phoenix 5.12 (2019 season).
+#[cfg(all(feature = "phoenix-2019", not(feature = "regen-bindings")))]
+cci! {
+    extern "C" {
+        pub fn c_CANifier_ConfigFactoryDefault(handle: Handle, timeoutMs: c_int) -> ErrorCode;
+    }
 }
@@ -0,0 +1,99 @@
+//! The time devices run on.
+//!
+//! On the roboRIO this is real time.  When the simulation stands in for the
+//! CCI it can be made to run faster or slower than real time with
+//! `sim::set_time_scale`, e.g. ten times faster to get through many autonomous
+//! runs in CI.  Anything that waits on devices or measures how they change
+//! over time should use [`now`] and [`sleep`] rather than `Instant::now` and
+//! `thread::sleep`, so it keeps pace with the simulated devices.
+//!
+//! [`now`]: fn.now.html
+//! [`sleep`]: fn.sleep.html
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[cfg(ctre_no_link)]
+use std::sync::Mutex;
+
+/// Simulated time, running `scale` times as fast as real time since `real_base`.
+#[cfg(ctre_no_link)]
+#[derive(Debug, Copy, Clone)]
+struct Scaled {
+    real_base: Instant,
+    base: Instant,
+    scale: f64,
+}
+
+#[cfg(ctre_no_link)]
+impl Scaled {
+    fn now(&self) -> Instant {
+        self.base + self.real_base.elapsed().mul_f64(self.scale)
+    }
+}
+
+/// `None` until the time scale is first set, running at real time.
+#[cfg(ctre_no_link)]
+static SCALED: Mutex<Option<Scaled>> = Mutex::new(None);
+
+#[cfg(ctre_no_link)]
+fn scaled() -> Option<Scaled> {
+    *SCALED.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/**
+ * Sets how many times faster than real time the simulation runs.  Time carries
+ * on from where it was, so `now` never goes backwards.
+ *
+ * # Panics
+ *
+ * If `scale` isn't positive and finite.
+ */
+#[cfg(ctre_no_link)]
+pub(crate) fn set_scale(scale: f64) {
+    assert!(
+        scale > 0. && scale.is_finite(),
+        "time scale must be positive, not {}",
+        scale
+    );
+    let mut state = SCALED.lock().unwrap_or_else(|e| e.into_inner());
+    let real_base = Instant::now();
+    let base = state.map_or(real_base, |s| s.now());
+    *state = Some(Scaled {
+        real_base,
+        base,
+        scale,
+    });
+}
+
+/// How many times faster than real time devices run; always 1 on the roboRIO.
+pub fn time_scale() -> f64 {
+    #[cfg(ctre_no_link)]
+    {
+        if let Some(s) = scaled() {
+            return s.scale;
+        }
+    }
+    1.
+}
+
+/// The current device time.  Only comparable with other `Instant`s from here.
+pub fn now() -> Instant {
+    #[cfg(ctre_no_link)]
+    {
+        if let Some(s) = scaled() {
+            return s.now();
+        }
+    }
+    Instant::now()
+}
+
+/// How long `device_time` takes in real time, e.g. for a `Condvar` timeout.
+pub fn real_duration(device_time: Duration) -> Duration {
+    device_time.div_f64(time_scale())
+}
+
+/// Sleeps the current thread for `dur` of device time.
+pub fn sleep(dur: Duration) {
+    thread::sleep(real_duration(dur))
+}
@@ -0,0 +1,126 @@
+//! Runtime resolution of the Phoenix CCI.
+//!
+//! With the `dlopen` feature, the Phoenix library isn't linked against.
+//! Instead it is loaded the first time a CCI function is called, from
+//! `$CTRE_PHOENIX_LIB` if set, or `libCTRE_PhoenixCCI.so` otherwise.
+//! Functions missing from the installed library (or every function, if the
+//! library can't be loaded) return `ErrorCode::FeatureNotSupported` rather than
+//! stopping the robot program from starting.  Nothing is printed; see
+//! [`load_error`] and [`missing_functions`] for why a call wasn't supported.
+//!
+//! [`load_error`]: fn.load_error.html
+//! [`missing_functions`]: fn.missing_functions.html
+
+extern crate libloading;
+
+use std::env;
+use std::ffi::OsString;
+use std::ptr;
+use std::sync::{Mutex, OnceLock};
+
+use self::libloading::Library;
+use ErrorCode;
+
+const DEFAULT_LIBRARY: &str = "libCTRE_PhoenixCCI.so";
+
+/// The loaded library, or why it couldn't be loaded.
+fn load() -> &'static Result<Library, String> {
+    static LIBRARY: OnceLock<Result<Library, String>> = OnceLock::new();
+    LIBRARY.get_or_init(|| {
+        let path =
+            env::var_os("CTRE_PHOENIX_LIB").unwrap_or_else(|| OsString::from(DEFAULT_LIBRARY));
+        unsafe { Library::new(&path) }
+            .map_err(|e| format!("could not load {}: {}", path.to_string_lossy(), e))
+    })
+}
+
+fn library() -> Option<&'static Library> {
+    load().as_ref().ok()
+}
+
+/// Whether the Phoenix library was found and loaded.
+pub fn is_loaded() -> bool {
+    library().is_some()
+}
+
+/// Why the Phoenix library couldn't be loaded, if it couldn't.  Every CCI
+/// function then returns `ErrorCode::FeatureNotSupported`.
+pub fn load_error() -> Option<&'static str> {
+    load().as_ref().err().map(String::as_str)
+}
+
+/// Functions called so far that the loaded library doesn't have, in the order
+/// they were first called.  Each returned `ErrorCode::FeatureNotSupported`.
+pub fn missing_functions() -> Vec<&'static str> {
+    missing_lock().clone()
+}
+
+static MISSING: Mutex<Vec<&'static str>> = Mutex::new(Vec::new());
+
+fn missing_lock() -> ::std::sync::MutexGuard<'static, Vec<&'static str>> {
+    MISSING.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// A lazily resolved CCI function.
+#[doc(hidden)]
+pub struct Symbol {
+    /// Nul-terminated symbol name.
+    name: &'static str,
+    addr: OnceLock<Option<usize>>,
+}
+
+impl Symbol {
+    pub const fn new(name: &'static str) -> Self {
+        Symbol {
+            name,
+            addr: OnceLock::new(),
+        }
+    }
+
+    /// The address of the function, or `None` if it isn't in the loaded library.
+    pub fn get(&self) -> Option<usize> {
+        *self.addr.get_or_init(|| {
+            let lib = library()?;
+            match unsafe { lib.get::<unsafe extern "C" fn()>(self.name.as_bytes()) } {
+                Ok(f) => Some(*f as usize),
+                Err(_) => {
+                    missing_lock().push(self.name.trim_end_matches('\0'));
+                    None
+                }
+            }
+        })
+    }
+}
+
+/// The value a CCI function returns when it isn't available.
+#[doc(hidden)]
+pub trait Missing {
+    fn missing() -> Self;
+}
+impl Missing for () {
+    fn missing() {}
+}
+impl Missing for ErrorCode {
+    fn missing() -> Self {
+        ErrorCode::FeatureNotSupported
+    }
+}
+impl<T> Missing for *mut T {
+    fn missing() -> Self {
+        ptr::null_mut()
+    }
+}
+
+#[test]
+fn unloadable_library_is_reported() {
+    env::set_var("CTRE_PHOENIX_LIB", "/nonexistent/libCTRE_PhoenixCCI.so");
+    assert!(!is_loaded());
+    assert!(load_error()
+        .unwrap()
+        .starts_with("could not load /nonexistent/libCTRE_PhoenixCCI.so: "));
+    let mut version = 0;
+    let code = unsafe { ::mot::c_MotController_GetFirmwareVersion(ptr::null_mut(), &mut version) };
+    assert_eq!(code, ErrorCode::FeatureNotSupported);
+    // Not missing from a library, as none was loaded.
+    assert!(missing_functions().is_empty());
+}
@@ -82,6 +82,10 @@ pub enum ErrorCode {
     RemoteSensorsNotSupportedYet = 108,
     MotProfFirmThreshold = 109,
     MotProfFirmThreshold2 = 110,
+
+    /// Not from Phoenix: the `ctre` crate's soft error for a call on an
+    /// optional device that isn't on the bus
+    DeviceMissing = 200,
 }
 
 #[repr(u32)]
@@ -159,6 +163,8 @@ pub enum ParamEnum {
 
     MotMag_Accel = 410,
     MotMag_VelCruise = 411,
+    #[cfg(feature = "phoenix-2020")]
+    MotMag_SCurveLevel = 412,
 
     /// ordinal (fwd=0,reverse=1), @see LimitSwitchSource_t
     LimitSwitchSource = 421,
@@ -191,3 +197,89 @@ pub enum ParamEnum {
     MotionMeas_shake_reject_time = 179,
     MotionMeas_shake_reject_timeout = 180,
 }
+enum_names!(ParamEnum {
+    OnBoot_BrakeMode,
+    QuadFilterEn,
+    QuadIdxPolarity,
+    ClearPositionOnIdx,
+    MotionProfileHasUnderrunErr,
+    MotionProfileTrajectoryPointDurationMs,
+    ClearPosOnLimitF,
+    ClearPosOnLimitR,
+    StatusFramePeriod,
+    OpenloopRamp,
+    ClosedloopRamp,
+    NeutralDeadband,
+    PeakPosOutput,
+    NominalPosOutput,
+    PeakNegOutput,
+    NominalNegOutput,
+    ProfileParamSlot_P,
+    ProfileParamSlot_I,
+    ProfileParamSlot_D,
+    ProfileParamSlot_F,
+    ProfileParamSlot_IZone,
+    ProfileParamSlot_AllowableErr,
+    ProfileParamSlot_MaxIAccum,
+    ProfileParamSlot_PeakOutput,
+    ClearPositionOnLimitF,
+    ClearPositionOnLimitR,
+    ClearPositionOnQuadIdx,
+    SampleVelocityPeriod,
+    SampleVelocityWindow,
+    FeedbackSensorType,
+    SelectedSensorPosition,
+    FeedbackNotContinuous,
+    RemoteSensorSource,
+    RemoteSensorDeviceID,
+    SensorTerm,
+    RemoteSensorClosedLoopDisableNeutralOnLOS,
+    PIDLoopPolarity,
+    PIDLoopPeriod,
+    SelectedSensorCoefficient,
+    ForwardSoftLimitThreshold,
+    ReverseSoftLimitThreshold,
+    ForwardSoftLimitEnable,
+    ReverseSoftLimitEnable,
+    NominalBatteryVoltage,
+    BatteryVoltageFilterSize,
+    ContinuousCurrentLimitAmps,
+    PeakCurrentLimitMs,
+    PeakCurrentLimitAmps,
+    ClosedLoopIAccum,
+    CustomParam,
+    StickyFaults,
+    AnalogPosition,
+    QuadraturePosition,
+    PulseWidthPosition,
+    MotMag_Accel,
+    MotMag_VelCruise,
+    #[cfg(feature = "phoenix-2020")]
+    MotMag_SCurveLevel,
+    LimitSwitchSource,
+    LimitSwitchNormClosedAndDis,
+    LimitSwitchDisableNeutralOnLOS,
+    LimitSwitchRemoteDevID,
+    SoftLimitDisableNeutralOnLOS,
+    PulseWidthPeriod_EdgesPerRot,
+    PulseWidthPeriod_FilterWindowSz,
+    YawOffset,
+    CompassOffset,
+    BetaGain,
+    EnableCompassFusion,
+    GyroNoMotionCal,
+    EnterCalibration,
+    FusedHeadingOffset,
+    StatusFrameRate,
+    AccumZ,
+    TempCompDisable,
+    MotionMeas_tap_threshX,
+    MotionMeas_tap_threshY,
+    MotionMeas_tap_threshZ,
+    MotionMeas_tap_count,
+    MotionMeas_tap_time,
+    MotionMeas_tap_time_multi,
+    MotionMeas_shake_reject_thresh,
+    MotionMeas_shake_reject_time,
+    MotionMeas_shake_reject_timeout,
+});
@@ -4,16 +4,38 @@
 #[macro_use]
 extern crate serde;
 
+#[cfg(all(feature = "dlopen", feature = "regen-bindings"))]
+compile_error!("`dlopen` needs the checked-in declarations, not `regen-bindings`");
+
+#[macro_use]
+mod macros;
+
 mod enums;
 pub use enums::*;
 
 pub mod canifier;
+pub mod clock;
+#[cfg(feature = "dlopen")]
+pub mod dlopen;
 pub mod logger;
 pub mod mot;
 pub mod pigeon;
+#[cfg(ctre_no_link)]
+pub mod sim;
 
 use std::fmt;
 
+/// How serious an `ErrorCode` is.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// The call succeeded.
+    Ok,
+    /// The call went through, but something may be off (positive codes).
+    Warning,
+    /// The call failed (negative codes).
+    Error,
+}
+
 impl ErrorCode {
     /// Returns `true` if the error code is `OK`.
     #[inline]
@@ -43,6 +65,46 @@ impl ErrorCode {
             _ => Err(self),
         }
     }
+
+    /// Returns how serious the error code is.
+    /// Phoenix uses negative codes for errors and positive codes for warnings.
+    pub fn severity(self) -> Severity {
+        match self as i32 {
+            0 => Severity::Ok,
+            code if code > 0 => Severity::Warning,
+            _ => Severity::Error,
+        }
+    }
+
+    /// Returns `true` if the error code is a warning rather than an error.
+    #[inline]
+    pub fn is_warning(self) -> bool {
+        self.severity() == Severity::Warning
+    }
+
+    /// Returns `true` if a frame or signal was not received in time.
+    /// These are usually transient, so the call may be worth retrying.
+    pub fn is_timeout(self) -> bool {
+        matches!(
+            self,
+            ErrorCode::RxTimeout | ErrorCode::TxTimeout | ErrorCode::SigNotUpdated
+        )
+    }
+
+    /// Returns `true` if the error code comes from the CAN bus itself,
+    /// e.g. a failed transmit or a stale or missing frame.
+    pub fn is_can_error(self) -> bool {
+        matches!(
+            self,
+            ErrorCode::CAN_MSG_STALE
+                | ErrorCode::TxFailed
+                | ErrorCode::RxTimeout
+                | ErrorCode::TxTimeout
+                | ErrorCode::UnexpectedArbId
+                | ErrorCode::BufferFull
+                | ErrorCode::CAN_OVERFLOW
+        )
+    }
 }
 
 impl std::error::Error for ErrorCode {
@@ -58,6 +120,37 @@ impl fmt::Display for ErrorCode {
     }
 }
 
+/// An enum name which couldn't be parsed, e.g. with `ParamEnum::from_str`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseEnumError {
+    type_name: &'static str,
+    input: String,
+}
+
+impl ParseEnumError {
+    fn new(type_name: &'static str, input: &str) -> Self {
+        ParseEnumError {
+            type_name,
+            input: input.to_owned(),
+        }
+    }
+    /// The name of the enum that was being parsed.
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+    pub fn input(&self) -> &str {
+        &self.input
+    }
+}
+
+impl fmt::Display for ParseEnumError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "unknown {} {:?}", self.type_name, self.input)
+    }
+}
+
+impl std::error::Error for ParseEnumError {}
+
 #[cfg(feature = "try_trait")]
 impl std::ops::Try for ErrorCode {
     type Ok = ();
@@ -77,3 +170,60 @@ impl std::ops::Try for ErrorCode {
         ErrorCode::OK
     }
 }
+
+#[test]
+fn error_code_categories() {
+    assert_eq!(ErrorCode::OK.severity(), Severity::Ok);
+    assert_eq!(ErrorCode::CAN_MSG_STALE.severity(), Severity::Warning);
+    assert_eq!(ErrorCode::InvalidHandle.severity(), Severity::Error);
+    assert!(ErrorCode::FeatureNotSupported.is_warning());
+    assert!(ErrorCode::RxTimeout.is_timeout() && ErrorCode::RxTimeout.is_can_error());
+    assert!(!ErrorCode::SensorNotPresent.is_can_error());
+}
+
+#[test]
+fn enum_names_round_trip() {
+    use mot::StatusFrameEnhanced;
+    assert_eq!(
+        ParamEnum::ProfileParamSlot_P.to_string(),
+        "ProfileParamSlot_P"
+    );
+    assert_eq!(
+        "profileparamslot_p".parse(),
+        Ok(ParamEnum::ProfileParamSlot_P)
+    );
+    assert_eq!(
+        "Status_10_Targets".parse(),
+        Ok(StatusFrameEnhanced::Status_10_Targets)
+    );
+    let err = "kP".parse::<ParamEnum>().unwrap_err();
+    assert_eq!(err.to_string(), "unknown ParamEnum \"kP\"");
+}
+
+#[test]
+fn enum_names_accept_aliases() {
+    use mot::{FeedbackDevice, NeutralMode};
+    assert_eq!("brake".parse(), Ok(NeutralMode::Brake));
+    assert_eq!(
+        "CTRE_MagEncoder_Relative".parse(),
+        Ok(FeedbackDevice::QuadEncoder)
+    );
+    assert_eq!(
+        FeedbackDevice::CTRE_MagEncoder_Relative.to_string(),
+        "QuadEncoder"
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn enums_deserialize_from_names() {
+    use mot::LimitSwitchNormal;
+    use serde::de::value::{Error, StrDeserializer};
+    use serde::Deserialize;
+    let name = StrDeserializer::<Error>::new("NormallyClosed");
+    assert_eq!(
+        LimitSwitchNormal::deserialize(name),
+        Ok(LimitSwitchNormal::NormallyClosed)
+    );
+    assert!(LimitSwitchNormal::deserialize(StrDeserializer::<Error>::new("closed")).is_err());
+}
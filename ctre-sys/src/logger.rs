@@ -1,13 +1,20 @@
+#[cfg_attr(feature = "regen-bindings", allow(unused_imports))]
 use std::os::raw::{c_char, c_int};
 use ErrorCode;
 
-extern "C" {
-    pub fn c_Logger_Close();
-    pub fn c_Logger_Open(language: c_int, logDriverStation: bool);
-    pub fn c_Logger_Log(
-        code: ErrorCode,
-        origin: *const c_char,
-        hierarchy: c_int,
-        stacktrace: *const c_char,
-    ) -> ErrorCode;
+#[cfg(feature = "regen-bindings")]
+include!(concat!(env!("OUT_DIR"), "/logger.rs"));
+
+#[cfg(not(feature = "regen-bindings"))]
+cci! {
+    extern "C" {
+        pub fn c_Logger_Close();
+        pub fn c_Logger_Open(language: c_int, logDriverStation: bool);
+        pub fn c_Logger_Log(
+            code: ErrorCode,
+            origin: *const c_char,
+            hierarchy: c_int,
+            stacktrace: *const c_char,
+        ) -> ErrorCode;
+    }
 }
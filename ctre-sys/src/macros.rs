@@ -0,0 +1,103 @@
+/// Declares CCI functions.
+///
+/// Normally this is just the extern block.  With the `dlopen` feature, each
+/// function is instead resolved from the Phoenix library on first use, and
+/// returns a "not supported" value if the installed library lacks it.
+macro_rules! cci {
+    (
+        extern "C" {
+            $(
+                $(#[$attr:meta])*
+                pub fn $name:ident($($arg:ident: $ty:ty),* $(,)?) $(-> $ret:ty)?;
+            )*
+        }
+    ) => {
+        #[cfg(not(feature = "dlopen"))]
+        extern "C" {
+            $(
+                $(#[$attr])*
+                pub fn $name($($arg: $ty),*) $(-> $ret)?;
+            )*
+        }
+
+        $(
+            $(#[$attr])*
+            #[cfg(feature = "dlopen")]
+            #[allow(non_snake_case, clippy::missing_safety_doc, clippy::too_many_arguments)]
+            pub unsafe extern "C" fn $name($($arg: $ty),*) $(-> $ret)? {
+                static SYMBOL: $crate::dlopen::Symbol =
+                    $crate::dlopen::Symbol::new(concat!(stringify!($name), "\0"));
+                match SYMBOL.get() {
+                    Some(addr) => {
+                        let f: unsafe extern "C" fn($($ty),*) $(-> $ret)? =
+                            ::std::mem::transmute(addr);
+                        f($($arg),*)
+                    }
+                    None => $crate::dlopen::Missing::missing(),
+                }
+            }
+        )*
+    };
+}
+
+/// Implements `Display` and `FromStr` for a C-like enum using its variants' names,
+/// so they can be shown in logs and read from config files.  With the `serde`
+/// feature, it is also (de)serialized as its name.
+///
+/// Parsing ignores ASCII case, and also accepts the names of any associated
+/// constants listed as aliases.  The variants must all be listed, with any `cfg`.
+macro_rules! enum_names {
+    (
+        $ty:ident { $($(#[$attr:meta])* $variant:ident),* $(,)? }
+        $(aliases { $($alias:ident),* $(,)? })?
+    ) => {
+        impl $ty {
+            /// The variant's name, as written in Rust.
+            pub fn name(self) -> &'static str {
+                match self {
+                    $($(#[$attr])* $ty::$variant => stringify!($variant),)*
+                }
+            }
+        }
+
+        impl ::std::fmt::Display for $ty {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                f.write_str(self.name())
+            }
+        }
+
+        impl ::std::str::FromStr for $ty {
+            type Err = $crate::ParseEnumError;
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                match s {
+                    $(
+                        $(#[$attr])*
+                        s if s.eq_ignore_ascii_case(stringify!($variant)) => Ok($ty::$variant),
+                    )*
+                    $($(
+                        s if s.eq_ignore_ascii_case(stringify!($alias)) => Ok($ty::$alias),
+                    )*)?
+                    _ => Err($crate::ParseEnumError::new(stringify!($ty), s)),
+                }
+            }
+        }
+
+        #[cfg(feature = "serde")]
+        impl ::serde::Serialize for $ty {
+            fn serialize<S: ::serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(self.name())
+            }
+        }
+
+        #[cfg(feature = "serde")]
+        impl<'de> ::serde::Deserialize<'de> for $ty {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: ::serde::Deserializer<'de>,
+            {
+                let name = <::std::borrow::Cow<str>>::deserialize(deserializer)?;
+                name.parse().map_err(::serde::de::Error::custom)
+            }
+        }
+    };
+}
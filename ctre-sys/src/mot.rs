@@ -1,6 +1,7 @@
 //! Enums, structs, and functions related to motor controllers.
-#![allow(non_camel_case_types, non_upper_case_globals)]
+#![allow(non_camel_case_types, non_snake_case, non_upper_case_globals)]
 
+#[cfg_attr(feature = "regen-bindings", allow(unused_imports))]
 use std::os::raw::{c_char, c_int};
 use ErrorCode;
 
@@ -16,6 +17,11 @@ pub enum ControlFrame {
     Control_4_Advanced = 262336,
     Control_6_MotProfAddTrajPoint = 262464,
 }
+enum_names!(ControlFrame {
+    Control_3_General,
+    Control_4_Advanced,
+    Control_6_MotProfAddTrajPoint,
+});
 #[repr(i32)]
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum ControlFrameEnhanced {
@@ -24,6 +30,12 @@ pub enum ControlFrameEnhanced {
     Control_5_FeedbackOutputOverride = 262400,
     Control_6_MotProfAddTrajPoint = 262464,
 }
+enum_names!(ControlFrameEnhanced {
+    Control_3_General,
+    Control_4_Advanced,
+    Control_5_FeedbackOutputOverride,
+    Control_6_MotProfAddTrajPoint,
+});
 
 #[repr(i32)]
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
@@ -38,6 +50,17 @@ pub enum ControlMode {
     MotionProfileArc = 10,
     Disabled = 15,
 }
+enum_names!(ControlMode {
+    PercentOutput,
+    Position,
+    Velocity,
+    Current,
+    Follower,
+    MotionProfile,
+    MotionMagic,
+    MotionProfileArc,
+    Disabled,
+});
 #[repr(i32)]
 /// How to interpret a demand value.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
@@ -54,6 +77,11 @@ pub enum DemandType {
     /// Simply add to the output
     ArbitraryFeedForward = 2,
 }
+enum_names!(DemandType {
+    Neutral,
+    AuxPID,
+    ArbitraryFeedForward,
+});
 impl Default for DemandType {
     #[inline]
     fn default() -> DemandType {
@@ -64,6 +92,21 @@ impl Default for DemandType {
 impl FeedbackDevice {
     pub const CTRE_MagEncoder_Absolute: FeedbackDevice = FeedbackDevice::PulseWidthEncodedPosition;
     pub const CTRE_MagEncoder_Relative: FeedbackDevice = FeedbackDevice::QuadEncoder;
+
+    /**
+     * Sensor units per rotation of the sensor, for sensors with a fixed resolution.
+     *
+     * `QuadEncoder` is taken to be a CTRE Mag Encoder (1024 CPR, read as 4096
+     * units).  Other quadrature encoders read 4 units per cycle.  Sum, difference
+     * and remote sensors depend on their sources, and a tachometer has no position.
+     */
+    pub fn units_per_rotation(self) -> Option<f64> {
+        match self {
+            FeedbackDevice::QuadEncoder | FeedbackDevice::PulseWidthEncodedPosition => Some(4096.),
+            FeedbackDevice::Analog => Some(1024.),
+            _ => None,
+        }
+    }
 }
 #[repr(i32)]
 /// Motor controller with gadgeteer connector.
@@ -80,6 +123,21 @@ pub enum FeedbackDevice {
     RemoteSensor1 = 12,
     SoftwareEmulatedSensor = 15,
 }
+enum_names!(FeedbackDevice {
+    None,
+    QuadEncoder,
+    Analog,
+    Tachometer,
+    PulseWidthEncodedPosition,
+    SensorSum,
+    SensorDifference,
+    RemoteSensor0,
+    RemoteSensor1,
+    SoftwareEmulatedSensor,
+} aliases {
+    CTRE_MagEncoder_Absolute,
+    CTRE_MagEncoder_Relative,
+});
 #[repr(i32)]
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum RemoteFeedbackDevice {
@@ -90,6 +148,14 @@ pub enum RemoteFeedbackDevice {
     RemoteSensor1 = 12,
     SoftwareEmulatedSensor = 15,
 }
+enum_names!(RemoteFeedbackDevice {
+    None,
+    SensorSum,
+    SensorDifference,
+    RemoteSensor0,
+    RemoteSensor1,
+    SoftwareEmulatedSensor,
+});
 
 #[repr(u32)]
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
@@ -97,6 +163,10 @@ pub enum FollowerType {
     PercentOutput = 0,
     AuxOutput1 = 1,
 }
+enum_names!(FollowerType {
+    PercentOutput,
+    AuxOutput1,
+});
 impl Default for FollowerType {
     #[inline]
     fn default() -> FollowerType {
@@ -112,6 +182,12 @@ pub enum LimitSwitchSource {
     RemoteCANifier = 2,
     Deactivated = 3,
 }
+enum_names!(LimitSwitchSource {
+    FeedbackConnector,
+    RemoteTalonSRX,
+    RemoteCANifier,
+    Deactivated,
+});
 #[repr(i32)]
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum RemoteLimitSwitchSource {
@@ -119,6 +195,11 @@ pub enum RemoteLimitSwitchSource {
     RemoteCANifier = 2,
     Deactivated = 3,
 }
+enum_names!(RemoteLimitSwitchSource {
+    RemoteTalonSRX,
+    RemoteCANifier,
+    Deactivated,
+});
 #[repr(i32)]
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum LimitSwitchNormal {
@@ -126,6 +207,11 @@ pub enum LimitSwitchNormal {
     NormallyClosed = 1,
     Disabled = 2,
 }
+enum_names!(LimitSwitchNormal {
+    NormallyOpen,
+    NormallyClosed,
+    Disabled,
+});
 
 #[repr(i32)]
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
@@ -137,6 +223,11 @@ pub enum NeutralMode {
     /// Stop the motor's rotation by applying a force.
     Brake = 2,
 }
+enum_names!(NeutralMode {
+    EEPROMSetting,
+    Coast,
+    Brake,
+});
 
 #[repr(i32)]
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
@@ -155,6 +246,21 @@ pub enum RemoteSensorSource {
     GadgeteerPigeon_Pitch = 11,
     GadgeteerPigeon_Roll = 12,
 }
+enum_names!(RemoteSensorSource {
+    Off,
+    TalonSRX_SelectedSensor,
+    Pigeon_Yaw,
+    Pigeon_Pitch,
+    Pigeon_Roll,
+    CANifier_Quadrature,
+    CANifier_PWMInput0,
+    CANifier_PWMInput1,
+    CANifier_PWMInput2,
+    CANifier_PWMInput3,
+    GadgeteerPigeon_Yaw,
+    GadgeteerPigeon_Pitch,
+    GadgeteerPigeon_Roll,
+});
 
 #[repr(i32)]
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
@@ -164,6 +270,12 @@ pub enum SensorTerm {
     Diff0 = 2,
     Diff1 = 3,
 }
+enum_names!(SensorTerm {
+    Sum0,
+    Sum1,
+    Diff0,
+    Diff1,
+});
 
 #[repr(i32)]
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
@@ -183,6 +295,22 @@ pub enum StatusFrameEnhanced {
     Status_8_PulseWidth = 5568,
     Status_11_UartGadgeteer = 5760,
 }
+enum_names!(StatusFrameEnhanced {
+    Status_1_General,
+    Status_2_Feedback0,
+    Status_4_AinTempVbat,
+    Status_6_Misc,
+    Status_7_CommStatus,
+    Status_9_MotProfBuffer,
+    Status_10_Targets,
+    Status_12_Feedback1,
+    Status_13_Base_PIDF0,
+    Status_14_Turn_PIDF1,
+    Status_15_FirmareApiStatus,
+    Status_3_Quadrature,
+    Status_8_PulseWidth,
+    Status_11_UartGadgeteer,
+});
 #[repr(i32)]
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum StatusFrame {
@@ -198,6 +326,19 @@ pub enum StatusFrame {
     Status_14_Turn_PIDF1 = 5952,
     Status_15_FirmareApiStatus = 6016,
 }
+enum_names!(StatusFrame {
+    Status_1_General,
+    Status_2_Feedback0,
+    Status_4_AinTempVbat,
+    Status_6_Misc,
+    Status_7_CommStatus,
+    Status_9_MotProfBuffer,
+    Status_10_Targets,
+    Status_12_Feedback1,
+    Status_13_Base_PIDF0,
+    Status_14_Turn_PIDF1,
+    Status_15_FirmareApiStatus,
+});
 
 #[repr(i32)]
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
@@ -211,6 +352,16 @@ pub enum VelocityMeasPeriod {
     Period_50Ms = 50,
     Period_100Ms = 100,
 }
+enum_names!(VelocityMeasPeriod {
+    Period_1Ms,
+    Period_2Ms,
+    Period_5Ms,
+    Period_10Ms,
+    Period_20Ms,
+    Period_25Ms,
+    Period_50Ms,
+    Period_100Ms,
+});
 
 #[repr(i32)]
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
@@ -220,6 +371,12 @@ pub enum SetValueMotionProfile {
     Enable = 1,
     Hold = 2,
 }
+enum_names!(SetValueMotionProfile {
+    Invalid,
+    Disable,
+    Enable,
+    Hold,
+});
 impl From<c_int> for SetValueMotionProfile {
     fn from(value: c_int) -> SetValueMotionProfile {
         match value {
@@ -316,7 +473,7 @@ fn bindgen_test_layout_TrajectoryPoint() {
         concat!("Alignment of ", stringify!(TrajectoryPoint))
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<TrajectoryPoint>())).position as *const _ as usize },
+        ::std::mem::offset_of!(TrajectoryPoint, position),
         0usize,
         concat!(
             "Offset of field: ",
@@ -326,7 +483,7 @@ fn bindgen_test_layout_TrajectoryPoint() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<TrajectoryPoint>())).velocity as *const _ as usize },
+        ::std::mem::offset_of!(TrajectoryPoint, velocity),
         8usize,
         concat!(
             "Offset of field: ",
@@ -336,7 +493,7 @@ fn bindgen_test_layout_TrajectoryPoint() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<TrajectoryPoint>())).heading_deg as *const _ as usize },
+        ::std::mem::offset_of!(TrajectoryPoint, heading_deg),
         16usize,
         concat!(
             "Offset of field: ",
@@ -346,7 +503,7 @@ fn bindgen_test_layout_TrajectoryPoint() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<TrajectoryPoint>())).auxiliary_pos as *const _ as usize },
+        ::std::mem::offset_of!(TrajectoryPoint, auxiliary_pos),
         24usize,
         concat!(
             "Offset of field: ",
@@ -356,9 +513,7 @@ fn bindgen_test_layout_TrajectoryPoint() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<TrajectoryPoint>())).profile_slot_select_0 as *const _ as usize
-        },
+        ::std::mem::offset_of!(TrajectoryPoint, profile_slot_select_0),
         32usize,
         concat!(
             "Offset of field: ",
@@ -368,9 +523,7 @@ fn bindgen_test_layout_TrajectoryPoint() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<TrajectoryPoint>())).profile_slot_select_1 as *const _ as usize
-        },
+        ::std::mem::offset_of!(TrajectoryPoint, profile_slot_select_1),
         36usize,
         concat!(
             "Offset of field: ",
@@ -380,7 +533,7 @@ fn bindgen_test_layout_TrajectoryPoint() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<TrajectoryPoint>())).is_last_point as *const _ as usize },
+        ::std::mem::offset_of!(TrajectoryPoint, is_last_point),
         40usize,
         concat!(
             "Offset of field: ",
@@ -390,7 +543,7 @@ fn bindgen_test_layout_TrajectoryPoint() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<TrajectoryPoint>())).zero_pos as *const _ as usize },
+        ::std::mem::offset_of!(TrajectoryPoint, zero_pos),
         41usize,
         concat!(
             "Offset of field: ",
@@ -400,7 +553,7 @@ fn bindgen_test_layout_TrajectoryPoint() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<TrajectoryPoint>())).time_dur as *const _ as usize },
+        ::std::mem::offset_of!(TrajectoryPoint, time_dur),
         44usize,
         concat!(
             "Offset of field: ",
@@ -462,9 +615,7 @@ fn bindgen_test_layout_MotionProfileStatus() {
         concat!("Alignment of ", stringify!(MotionProfileStatus))
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<MotionProfileStatus>())).top_buffer_rem as *const _ as usize
-        },
+        ::std::mem::offset_of!(MotionProfileStatus, top_buffer_rem),
         0usize,
         concat!(
             "Offset of field: ",
@@ -474,9 +625,7 @@ fn bindgen_test_layout_MotionProfileStatus() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<MotionProfileStatus>())).top_buffer_cnt as *const _ as usize
-        },
+        ::std::mem::offset_of!(MotionProfileStatus, top_buffer_cnt),
         4usize,
         concat!(
             "Offset of field: ",
@@ -486,9 +635,7 @@ fn bindgen_test_layout_MotionProfileStatus() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<MotionProfileStatus>())).btm_buffer_cnt as *const _ as usize
-        },
+        ::std::mem::offset_of!(MotionProfileStatus, btm_buffer_cnt),
         8usize,
         concat!(
             "Offset of field: ",
@@ -498,9 +645,7 @@ fn bindgen_test_layout_MotionProfileStatus() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<MotionProfileStatus>())).has_underrun as *const _ as usize
-        },
+        ::std::mem::offset_of!(MotionProfileStatus, has_underrun),
         12usize,
         concat!(
             "Offset of field: ",
@@ -510,7 +655,7 @@ fn bindgen_test_layout_MotionProfileStatus() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<MotionProfileStatus>())).is_underrun as *const _ as usize },
+        ::std::mem::offset_of!(MotionProfileStatus, is_underrun),
         13usize,
         concat!(
             "Offset of field: ",
@@ -520,9 +665,7 @@ fn bindgen_test_layout_MotionProfileStatus() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<MotionProfileStatus>())).active_point_valid as *const _ as usize
-        },
+        ::std::mem::offset_of!(MotionProfileStatus, active_point_valid),
         14usize,
         concat!(
             "Offset of field: ",
@@ -532,7 +675,7 @@ fn bindgen_test_layout_MotionProfileStatus() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<MotionProfileStatus>())).is_last as *const _ as usize },
+        ::std::mem::offset_of!(MotionProfileStatus, is_last),
         15usize,
         concat!(
             "Offset of field: ",
@@ -542,10 +685,7 @@ fn bindgen_test_layout_MotionProfileStatus() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<MotionProfileStatus>())).profile_slot_select_0 as *const _
-                as usize
-        },
+        ::std::mem::offset_of!(MotionProfileStatus, profile_slot_select_0),
         16usize,
         concat!(
             "Offset of field: ",
@@ -555,10 +695,7 @@ fn bindgen_test_layout_MotionProfileStatus() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<MotionProfileStatus>())).profile_slot_select_1 as *const _
-                as usize
-        },
+        ::std::mem::offset_of!(MotionProfileStatus, profile_slot_select_1),
         20usize,
         concat!(
             "Offset of field: ",
@@ -568,9 +705,7 @@ fn bindgen_test_layout_MotionProfileStatus() {
         )
     );
     assert_eq!(
-        unsafe {
-            &(*(::std::ptr::null::<MotionProfileStatus>())).output_enable as *const _ as usize
-        },
+        ::std::mem::offset_of!(MotionProfileStatus, output_enable),
         24usize,
         concat!(
             "Offset of field: ",
@@ -580,7 +715,7 @@ fn bindgen_test_layout_MotionProfileStatus() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<MotionProfileStatus>())).time_dur_ms as *const _ as usize },
+        ::std::mem::offset_of!(MotionProfileStatus, time_dur_ms),
         28usize,
         concat!(
             "Offset of field: ",
@@ -591,596 +726,690 @@ fn bindgen_test_layout_MotionProfileStatus() {
     );
 }
 
-extern "C" {
-    pub fn c_MotController_Create1(baseArbId: c_int) -> Handle;
-
-    pub fn c_MotController_GetDeviceNumber(handle: Handle, deviceNumber: *mut c_int) -> ErrorCode;
-
-    pub fn c_MotController_GetDescription(
-        handle: Handle,
-        toFill: *mut c_char,
-        toFillByteSz: c_int,
-        numBytesFilled: *mut c_int,
-    ) -> ErrorCode;
-
-    pub fn c_MotController_SetDemand(
-        handle: Handle,
-        mode: c_int,
-        demand0: c_int,
-        demand1: c_int,
-    ) -> ErrorCode;
-
-    pub fn c_MotController_Set_4(
-        handle: Handle,
-        mode: c_int,
-        demand0: f64,
-        demand1: f64,
-        demand1Type: c_int,
-    ) -> ErrorCode;
-
-    pub fn c_MotController_SetNeutralMode(handle: Handle, neutralMode: c_int);
-
-    pub fn c_MotController_SetSensorPhase(handle: Handle, PhaseSensor: bool);
-
-    pub fn c_MotController_SetInverted(handle: Handle, invert: bool);
-
-    pub fn c_MotController_ConfigOpenLoopRamp(
-        handle: Handle,
-        secondsFromNeutralToFull: f64,
-        timeoutMs: c_int,
-    ) -> ErrorCode;
-
-    pub fn c_MotController_ConfigClosedLoopRamp(
-        handle: Handle,
-        secondsFromNeutralToFull: f64,
-        timeoutMs: c_int,
-    ) -> ErrorCode;
-
-    pub fn c_MotController_ConfigPeakOutputForward(
-        handle: Handle,
-        percentOut: f64,
-        timeoutMs: c_int,
-    ) -> ErrorCode;
-
-    pub fn c_MotController_ConfigPeakOutputReverse(
-        handle: Handle,
-        percentOut: f64,
-        timeoutMs: c_int,
-    ) -> ErrorCode;
-
-    pub fn c_MotController_ConfigNominalOutputForward(
-        handle: Handle,
-        percentOut: f64,
-        timeoutMs: c_int,
-    ) -> ErrorCode;
-
-    pub fn c_MotController_ConfigNominalOutputReverse(
-        handle: Handle,
-        percentOut: f64,
-        timeoutMs: c_int,
-    ) -> ErrorCode;
-
-    pub fn c_MotController_ConfigNeutralDeadband(
-        handle: Handle,
-        percentDeadband: f64,
-        timeoutMs: c_int,
-    ) -> ErrorCode;
-
-    pub fn c_MotController_ConfigVoltageCompSaturation(
-        handle: Handle,
-        voltage: f64,
-        timeoutMs: c_int,
-    ) -> ErrorCode;
-
-    pub fn c_MotController_ConfigVoltageMeasurementFilter(
-        handle: Handle,
-        filterWindowSamples: c_int,
-        timeoutMs: c_int,
-    ) -> ErrorCode;
-
-    pub fn c_MotController_EnableVoltageCompensation(handle: Handle, enable: bool);
-
-    pub fn c_MotController_GetBusVoltage(handle: Handle, voltage: *mut f64) -> ErrorCode;
-
-    pub fn c_MotController_GetMotorOutputPercent(
-        handle: Handle,
-        percentOutput: *mut f64,
-    ) -> ErrorCode;
-
-    pub fn c_MotController_GetOutputCurrent(handle: Handle, current: *mut f64) -> ErrorCode;
-
-    pub fn c_MotController_GetTemperature(handle: Handle, temperature: *mut f64) -> ErrorCode;
-
-    pub fn c_MotController_ConfigSelectedFeedbackSensor(
-        handle: Handle,
-        feedbackDevice: c_int,
-        pidIdx: c_int,
-        timeoutMs: c_int,
-    ) -> ErrorCode;
-
-    pub fn c_MotController_ConfigSelectedFeedbackCoefficient(
-        handle: Handle,
-        coefficient: f64,
-        pidIdx: c_int,
-        timeoutMs: c_int,
-    ) -> ErrorCode;
-
-    pub fn c_MotController_ConfigRemoteFeedbackFilter(
-        handle: Handle,
-        deviceID: c_int,
-        remoteSensorSource: c_int,
-        remoteOrdinal: c_int,
-        timeoutMs: c_int,
-    ) -> ErrorCode;
-
-    pub fn c_MotController_ConfigSensorTerm(
-        handle: Handle,
-        sensorTerm: c_int,
-        feedbackDevice: c_int,
-        timeoutMs: c_int,
-    ) -> ErrorCode;
-
-    pub fn c_MotController_GetSelectedSensorPosition(
-        handle: Handle,
-        param: *mut c_int,
-        pidIdx: c_int,
-    ) -> ErrorCode;
-
-    pub fn c_MotController_GetSelectedSensorVelocity(
-        handle: Handle,
-        param: *mut c_int,
-        pidIdx: c_int,
-    ) -> ErrorCode;
-
-    pub fn c_MotController_SetSelectedSensorPosition(
-        handle: Handle,
-        sensorPos: c_int,
-        pidIdx: c_int,
-        timeoutMs: c_int,
-    ) -> ErrorCode;
-
-    pub fn c_MotController_SetControlFramePeriod(
-        handle: Handle,
-        frame: c_int,
-        periodMs: c_int,
-    ) -> ErrorCode;
-
-    pub fn c_MotController_SetStatusFramePeriod(
-        handle: Handle,
-        frame: c_int,
-        periodMs: c_int,
-        timeoutMs: c_int,
-    ) -> ErrorCode;
-
-    pub fn c_MotController_GetStatusFramePeriod(
-        handle: Handle,
-        frame: c_int,
-        periodMs: *mut c_int,
-        timeoutMs: c_int,
-    ) -> ErrorCode;
-
-    pub fn c_MotController_ConfigVelocityMeasurementPeriod(
-        handle: Handle,
-        period: c_int,
-        timeoutMs: c_int,
-    ) -> ErrorCode;
-
-    pub fn c_MotController_ConfigVelocityMeasurementWindow(
-        handle: Handle,
-        windowSize: c_int,
-        timeoutMs: c_int,
-    ) -> ErrorCode;
-
-    pub fn c_MotController_ConfigForwardLimitSwitchSource(
-        handle: Handle,
-        type_: c_int,
-        normalOpenOrClose: c_int,
-        deviceID: c_int,
-        timeoutMs: c_int,
-    ) -> ErrorCode;
-
-    pub fn c_MotController_ConfigReverseLimitSwitchSource(
-        handle: Handle,
-        type_: c_int,
-        normalOpenOrClose: c_int,
-        deviceID: c_int,
-        timeoutMs: c_int,
-    ) -> ErrorCode;
-
-    pub fn c_MotController_OverrideLimitSwitchesEnable(handle: Handle, enable: bool);
-
-    pub fn c_MotController_ConfigForwardSoftLimitThreshold(
-        handle: Handle,
-        forwardSensorLimit: c_int,
-        timeoutMs: c_int,
-    ) -> ErrorCode;
-
-    pub fn c_MotController_ConfigReverseSoftLimitThreshold(
-        handle: Handle,
-        reverseSensorLimit: c_int,
-        timeoutMs: c_int,
-    ) -> ErrorCode;
-
-    pub fn c_MotController_ConfigForwardSoftLimitEnable(
-        handle: Handle,
-        enable: bool,
-        timeoutMs: c_int,
-    ) -> ErrorCode;
-
-    pub fn c_MotController_ConfigReverseSoftLimitEnable(
-        handle: Handle,
-        enable: bool,
-        timeoutMs: c_int,
-    ) -> ErrorCode;
-
-    pub fn c_MotController_OverrideSoftLimitsEnable(handle: Handle, enable: bool);
-
-    pub fn c_MotController_Config_kP(
-        handle: Handle,
-        slotIdx: c_int,
-        value: f64,
-        timeoutMs: c_int,
-    ) -> ErrorCode;
-
-    pub fn c_MotController_Config_kI(
-        handle: Handle,
-        slotIdx: c_int,
-        value: f64,
-        timeoutMs: c_int,
-    ) -> ErrorCode;
-
-    pub fn c_MotController_Config_kD(
-        handle: Handle,
-        slotIdx: c_int,
-        value: f64,
-        timeoutMs: c_int,
-    ) -> ErrorCode;
-
-    pub fn c_MotController_Config_kF(
-        handle: Handle,
-        slotIdx: c_int,
-        value: f64,
-        timeoutMs: c_int,
-    ) -> ErrorCode;
-
-    pub fn c_MotController_Config_IntegralZone(
-        handle: Handle,
-        slotIdx: c_int,
-        izone: f64,
-        timeoutMs: c_int,
-    ) -> ErrorCode;
-
-    pub fn c_MotController_ConfigAllowableClosedloopError(
-        handle: Handle,
-        slotIdx: c_int,
-        allowableClosedLoopError: c_int,
-        timeoutMs: c_int,
-    ) -> ErrorCode;
-
-    pub fn c_MotController_ConfigMaxIntegralAccumulator(
-        handle: Handle,
-        slotIdx: c_int,
-        iaccum: f64,
-        timeoutMs: c_int,
-    ) -> ErrorCode;
-
-    pub fn c_MotController_ConfigClosedLoopPeakOutput(
-        handle: Handle,
-        slotIdx: c_int,
-        percentOut: f64,
-        timeoutMs: c_int,
-    ) -> ErrorCode;
-
-    pub fn c_MotController_ConfigClosedLoopPeriod(
-        handle: Handle,
-        slotIdx: c_int,
-        loopTimeMs: c_int,
-        timeoutMs: c_int,
-    ) -> ErrorCode;
-
-    pub fn c_MotController_SetIntegralAccumulator(
-        handle: Handle,
-        iaccum: f64,
-        pidIdx: c_int,
-        timeoutMs: c_int,
-    ) -> ErrorCode;
-
-    pub fn c_MotController_GetClosedLoopError(
-        handle: Handle,
-        closedLoopError: *mut c_int,
-        pidIdx: c_int,
-    ) -> ErrorCode;
-
-    pub fn c_MotController_GetIntegralAccumulator(
-        handle: Handle,
-        iaccum: *mut f64,
-        pidIdx: c_int,
-    ) -> ErrorCode;
-
-    pub fn c_MotController_GetErrorDerivative(
-        handle: Handle,
-        derror: *mut f64,
-        pidIdx: c_int,
-    ) -> ErrorCode;
-
-    pub fn c_MotController_SelectProfileSlot(
-        handle: Handle,
-        slotIdx: c_int,
-        pidIdx: c_int,
-    ) -> ErrorCode;
-
-    pub fn c_MotController_GetActiveTrajectoryPosition(
-        handle: Handle,
-        param: *mut c_int,
-    ) -> ErrorCode;
-
-    pub fn c_MotController_GetActiveTrajectoryVelocity(
-        handle: Handle,
-        param: *mut c_int,
-    ) -> ErrorCode;
-
-    pub fn c_MotController_GetActiveTrajectoryHeading(handle: Handle, param: *mut f64)
-        -> ErrorCode;
-
-    pub fn c_MotController_GetActiveTrajectoryAll(
-        handle: Handle,
-        vel: *mut c_int,
-        pos: *mut c_int,
-        heading: *mut f64,
-    ) -> ErrorCode;
-
-    pub fn c_MotController_ConfigMotionCruiseVelocity(
-        handle: Handle,
-        sensorUnitsPer100ms: c_int,
-        timeoutMs: c_int,
-    ) -> ErrorCode;
-
-    pub fn c_MotController_ConfigMotionAcceleration(
-        handle: Handle,
-        sensorUnitsPer100msPerSec: c_int,
-        timeoutMs: c_int,
-    ) -> ErrorCode;
-
-    pub fn c_MotController_ClearMotionProfileTrajectories(handle: Handle) -> ErrorCode;
-
-    pub fn c_MotController_GetMotionProfileTopLevelBufferCount(
-        handle: Handle,
-        value: *mut c_int,
-    ) -> ErrorCode;
-
-    pub fn c_MotController_PushMotionProfileTrajectory(
-        handle: Handle,
-        position: f64,
-        velocity: f64,
-        headingDeg: f64,
-        profileSlotSelect: c_int,
-        isLastPoint: bool,
-        zeroPos: bool,
-    ) -> ErrorCode;
-
-    pub fn c_MotController_PushMotionProfileTrajectory_2(
-        handle: Handle,
-        position: f64,
-        velocity: f64,
-        headingDeg: f64,
-        profileSlotSelect0: c_int,
-        profileSlotSelect1: c_int,
-        isLastPoint: bool,
-        zeroPos: bool,
-        durationMs: c_int,
-    ) -> ErrorCode;
-
-    pub fn c_MotController_IsMotionProfileTopLevelBufferFull(
-        handle: Handle,
-        value: *mut bool,
-    ) -> ErrorCode;
-
-    pub fn c_MotController_ProcessMotionProfileBuffer(handle: Handle) -> ErrorCode;
-
-    pub fn c_MotController_GetMotionProfileStatus(
-        handle: Handle,
-        topBufferRem: *mut c_int,
-        topBufferCnt: *mut c_int,
-        btmBufferCnt: *mut c_int,
-        hasUnderrun: *mut bool,
-        isUnderrun: *mut bool,
-        activePointValid: *mut bool,
-        isLast: *mut bool,
-        profileSlotSelect: *mut c_int,
-        outputEnable: *mut c_int,
-    ) -> ErrorCode;
-
-    pub fn c_MotController_GetMotionProfileStatus_2(
-        handle: Handle,
-        topBufferRem: *mut c_int,
-        topBufferCnt: *mut c_int,
-        btmBufferCnt: *mut c_int,
-        hasUnderrun: *mut bool,
-        isUnderrun: *mut bool,
-        activePointValid: *mut bool,
-        isLast: *mut bool,
-        profileSlotSelect: *mut c_int,
-        outputEnable: *mut c_int,
-        timeDurMs: *mut c_int,
-        profileSlotSelect1: *mut c_int,
-    ) -> ErrorCode;
-
-    pub fn c_MotController_ClearMotionProfileHasUnderrun(
-        handle: Handle,
-        timeoutMs: c_int,
-    ) -> ErrorCode;
-
-    pub fn c_MotController_ChangeMotionControlFramePeriod(
-        handle: Handle,
-        periodMs: c_int,
-    ) -> ErrorCode;
-
-    pub fn c_MotController_ConfigMotionProfileTrajectoryPeriod(
-        handle: Handle,
-        durationMs: c_int,
-        timeoutMs: c_int,
-    ) -> ErrorCode;
-
-    pub fn c_MotController_GetLastError(handle: Handle) -> ErrorCode;
-
-    pub fn c_MotController_GetFirmwareVersion(handle: Handle, arg1: *mut c_int) -> ErrorCode;
-
-    pub fn c_MotController_HasResetOccurred(handle: Handle, arg1: *mut bool) -> ErrorCode;
-
-    pub fn c_MotController_ConfigSetCustomParam(
-        handle: Handle,
-        newValue: c_int,
-        paramIndex: c_int,
-        timeoutMs: c_int,
-    ) -> ErrorCode;
-
-    pub fn c_MotController_ConfigGetCustomParam(
-        handle: Handle,
-        readValue: *mut c_int,
-        paramIndex: c_int,
-        timoutMs: c_int,
-    ) -> ErrorCode;
-
-    pub fn c_MotController_ConfigSetParameter(
-        handle: Handle,
-        param: c_int,
-        value: f64,
-        subValue: c_int,
-        ordinal: c_int,
-        timeoutMs: c_int,
-    ) -> ErrorCode;
-
-    pub fn c_MotController_ConfigGetParameter(
-        handle: Handle,
-        param: c_int,
-        value: *mut f64,
-        ordinal: c_int,
-        timeoutMs: c_int,
-    ) -> ErrorCode;
-
-    pub fn c_MotController_ConfigPeakCurrentLimit(
-        handle: Handle,
-        amps: c_int,
-        timeoutMs: c_int,
-    ) -> ErrorCode;
-
-    pub fn c_MotController_ConfigPeakCurrentDuration(
-        handle: Handle,
-        milliseconds: c_int,
-        timeoutMs: c_int,
-    ) -> ErrorCode;
-
-    pub fn c_MotController_ConfigContinuousCurrentLimit(
-        handle: Handle,
-        amps: c_int,
-        timeoutMs: c_int,
-    ) -> ErrorCode;
-
-    pub fn c_MotController_EnableCurrentLimit(handle: Handle, enable: bool) -> ErrorCode;
-
-    pub fn c_MotController_SetLastError(handle: Handle, error: c_int) -> ErrorCode;
-
-    pub fn c_MotController_GetAnalogIn(handle: Handle, param: *mut c_int) -> ErrorCode;
-
-    pub fn c_MotController_SetAnalogPosition(
-        handle: Handle,
-        newPosition: c_int,
-        timeoutMs: c_int,
-    ) -> ErrorCode;
-
-    pub fn c_MotController_GetAnalogInRaw(handle: Handle, param: *mut c_int) -> ErrorCode;
-
-    pub fn c_MotController_GetAnalogInVel(handle: Handle, param: *mut c_int) -> ErrorCode;
-
-    pub fn c_MotController_GetQuadraturePosition(handle: Handle, param: *mut c_int) -> ErrorCode;
-
-    pub fn c_MotController_SetQuadraturePosition(
-        handle: Handle,
-        newPosition: c_int,
-        timeoutMs: c_int,
-    ) -> ErrorCode;
-
-    pub fn c_MotController_GetQuadratureVelocity(handle: Handle, param: *mut c_int) -> ErrorCode;
-
-    pub fn c_MotController_GetPulseWidthPosition(handle: Handle, param: *mut c_int) -> ErrorCode;
-
-    pub fn c_MotController_SetPulseWidthPosition(
-        handle: Handle,
-        newPosition: c_int,
-        timeoutMs: c_int,
-    ) -> ErrorCode;
-
-    pub fn c_MotController_GetPulseWidthVelocity(handle: Handle, param: *mut c_int) -> ErrorCode;
-
-    pub fn c_MotController_GetPulseWidthRiseToFallUs(
-        handle: Handle,
-        param: *mut c_int,
-    ) -> ErrorCode;
-
-    pub fn c_MotController_GetPulseWidthRiseToRiseUs(
-        handle: Handle,
-        param: *mut c_int,
-    ) -> ErrorCode;
-
-    pub fn c_MotController_GetPinStateQuadA(handle: Handle, param: *mut c_int) -> ErrorCode;
-
-    pub fn c_MotController_GetPinStateQuadB(handle: Handle, param: *mut c_int) -> ErrorCode;
-
-    pub fn c_MotController_GetPinStateQuadIdx(handle: Handle, param: *mut c_int) -> ErrorCode;
-
-    pub fn c_MotController_IsFwdLimitSwitchClosed(handle: Handle, param: *mut c_int) -> ErrorCode;
-
-    pub fn c_MotController_IsRevLimitSwitchClosed(handle: Handle, param: *mut c_int) -> ErrorCode;
-
-    pub fn c_MotController_GetFaults(handle: Handle, param: *mut c_int) -> ErrorCode;
-
-    pub fn c_MotController_GetStickyFaults(handle: Handle, param: *mut c_int) -> ErrorCode;
-
-    pub fn c_MotController_ClearStickyFaults(handle: Handle, timeoutMs: c_int) -> ErrorCode;
-
-    pub fn c_MotController_SelectDemandType(handle: Handle, enable: bool) -> ErrorCode;
+#[cfg(feature = "regen-bindings")]
+include!(concat!(env!("OUT_DIR"), "/mot.rs"));
+
+#[cfg(not(feature = "regen-bindings"))]
+cci! {
+    extern "C" {
+        pub fn c_MotController_Create1(baseArbId: c_int) -> Handle;
+
+        pub fn c_MotController_GetDeviceNumber(
+            handle: Handle,
+            deviceNumber: *mut c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_GetDescription(
+            handle: Handle,
+            toFill: *mut c_char,
+            toFillByteSz: c_int,
+            numBytesFilled: *mut c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_SetDemand(
+            handle: Handle,
+            mode: c_int,
+            demand0: c_int,
+            demand1: c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_Set_4(
+            handle: Handle,
+            mode: c_int,
+            demand0: f64,
+            demand1: f64,
+            demand1Type: c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_SetNeutralMode(handle: Handle, neutralMode: c_int);
+
+        pub fn c_MotController_SetSensorPhase(handle: Handle, PhaseSensor: bool);
+
+        pub fn c_MotController_SetInverted(handle: Handle, invert: bool);
+
+        pub fn c_MotController_ConfigOpenLoopRamp(
+            handle: Handle,
+            secondsFromNeutralToFull: f64,
+            timeoutMs: c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_ConfigClosedLoopRamp(
+            handle: Handle,
+            secondsFromNeutralToFull: f64,
+            timeoutMs: c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_ConfigPeakOutputForward(
+            handle: Handle,
+            percentOut: f64,
+            timeoutMs: c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_ConfigPeakOutputReverse(
+            handle: Handle,
+            percentOut: f64,
+            timeoutMs: c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_ConfigNominalOutputForward(
+            handle: Handle,
+            percentOut: f64,
+            timeoutMs: c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_ConfigNominalOutputReverse(
+            handle: Handle,
+            percentOut: f64,
+            timeoutMs: c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_ConfigNeutralDeadband(
+            handle: Handle,
+            percentDeadband: f64,
+            timeoutMs: c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_ConfigVoltageCompSaturation(
+            handle: Handle,
+            voltage: f64,
+            timeoutMs: c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_ConfigVoltageMeasurementFilter(
+            handle: Handle,
+            filterWindowSamples: c_int,
+            timeoutMs: c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_EnableVoltageCompensation(handle: Handle, enable: bool);
+
+        pub fn c_MotController_GetBusVoltage(handle: Handle, voltage: *mut f64) -> ErrorCode;
+
+        pub fn c_MotController_GetMotorOutputPercent(
+            handle: Handle,
+            percentOutput: *mut f64,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_GetOutputCurrent(handle: Handle, current: *mut f64) -> ErrorCode;
+
+        pub fn c_MotController_GetTemperature(handle: Handle, temperature: *mut f64) -> ErrorCode;
+
+        pub fn c_MotController_ConfigSelectedFeedbackSensor(
+            handle: Handle,
+            feedbackDevice: c_int,
+            pidIdx: c_int,
+            timeoutMs: c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_ConfigSelectedFeedbackCoefficient(
+            handle: Handle,
+            coefficient: f64,
+            pidIdx: c_int,
+            timeoutMs: c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_ConfigRemoteFeedbackFilter(
+            handle: Handle,
+            deviceID: c_int,
+            remoteSensorSource: c_int,
+            remoteOrdinal: c_int,
+            timeoutMs: c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_ConfigSensorTerm(
+            handle: Handle,
+            sensorTerm: c_int,
+            feedbackDevice: c_int,
+            timeoutMs: c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_GetSelectedSensorPosition(
+            handle: Handle,
+            param: *mut c_int,
+            pidIdx: c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_GetSelectedSensorVelocity(
+            handle: Handle,
+            param: *mut c_int,
+            pidIdx: c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_SetSelectedSensorPosition(
+            handle: Handle,
+            sensorPos: c_int,
+            pidIdx: c_int,
+            timeoutMs: c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_SetControlFramePeriod(
+            handle: Handle,
+            frame: c_int,
+            periodMs: c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_SetStatusFramePeriod(
+            handle: Handle,
+            frame: c_int,
+            periodMs: c_int,
+            timeoutMs: c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_GetStatusFramePeriod(
+            handle: Handle,
+            frame: c_int,
+            periodMs: *mut c_int,
+            timeoutMs: c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_ConfigVelocityMeasurementPeriod(
+            handle: Handle,
+            period: c_int,
+            timeoutMs: c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_ConfigVelocityMeasurementWindow(
+            handle: Handle,
+            windowSize: c_int,
+            timeoutMs: c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_ConfigForwardLimitSwitchSource(
+            handle: Handle,
+            type_: c_int,
+            normalOpenOrClose: c_int,
+            deviceID: c_int,
+            timeoutMs: c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_ConfigReverseLimitSwitchSource(
+            handle: Handle,
+            type_: c_int,
+            normalOpenOrClose: c_int,
+            deviceID: c_int,
+            timeoutMs: c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_OverrideLimitSwitchesEnable(handle: Handle, enable: bool);
+
+        pub fn c_MotController_ConfigForwardSoftLimitThreshold(
+            handle: Handle,
+            forwardSensorLimit: c_int,
+            timeoutMs: c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_ConfigReverseSoftLimitThreshold(
+            handle: Handle,
+            reverseSensorLimit: c_int,
+            timeoutMs: c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_ConfigForwardSoftLimitEnable(
+            handle: Handle,
+            enable: bool,
+            timeoutMs: c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_ConfigReverseSoftLimitEnable(
+            handle: Handle,
+            enable: bool,
+            timeoutMs: c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_OverrideSoftLimitsEnable(handle: Handle, enable: bool);
+
+        pub fn c_MotController_Config_kP(
+            handle: Handle,
+            slotIdx: c_int,
+            value: f64,
+            timeoutMs: c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_Config_kI(
+            handle: Handle,
+            slotIdx: c_int,
+            value: f64,
+            timeoutMs: c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_Config_kD(
+            handle: Handle,
+            slotIdx: c_int,
+            value: f64,
+            timeoutMs: c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_Config_kF(
+            handle: Handle,
+            slotIdx: c_int,
+            value: f64,
+            timeoutMs: c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_Config_IntegralZone(
+            handle: Handle,
+            slotIdx: c_int,
+            izone: f64,
+            timeoutMs: c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_ConfigAllowableClosedloopError(
+            handle: Handle,
+            slotIdx: c_int,
+            allowableClosedLoopError: c_int,
+            timeoutMs: c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_ConfigMaxIntegralAccumulator(
+            handle: Handle,
+            slotIdx: c_int,
+            iaccum: f64,
+            timeoutMs: c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_ConfigClosedLoopPeakOutput(
+            handle: Handle,
+            slotIdx: c_int,
+            percentOut: f64,
+            timeoutMs: c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_ConfigClosedLoopPeriod(
+            handle: Handle,
+            slotIdx: c_int,
+            loopTimeMs: c_int,
+            timeoutMs: c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_SetIntegralAccumulator(
+            handle: Handle,
+            iaccum: f64,
+            pidIdx: c_int,
+            timeoutMs: c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_GetClosedLoopError(
+            handle: Handle,
+            closedLoopError: *mut c_int,
+            pidIdx: c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_GetIntegralAccumulator(
+            handle: Handle,
+            iaccum: *mut f64,
+            pidIdx: c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_GetErrorDerivative(
+            handle: Handle,
+            derror: *mut f64,
+            pidIdx: c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_SelectProfileSlot(
+            handle: Handle,
+            slotIdx: c_int,
+            pidIdx: c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_GetActiveTrajectoryPosition(
+            handle: Handle,
+            param: *mut c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_GetActiveTrajectoryVelocity(
+            handle: Handle,
+            param: *mut c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_GetActiveTrajectoryHeading(handle: Handle, param: *mut f64)
+            -> ErrorCode;
+
+        pub fn c_MotController_GetActiveTrajectoryAll(
+            handle: Handle,
+            vel: *mut c_int,
+            pos: *mut c_int,
+            heading: *mut f64,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_ConfigMotionCruiseVelocity(
+            handle: Handle,
+            sensorUnitsPer100ms: c_int,
+            timeoutMs: c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_ConfigMotionAcceleration(
+            handle: Handle,
+            sensorUnitsPer100msPerSec: c_int,
+            timeoutMs: c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_ClearMotionProfileTrajectories(handle: Handle) -> ErrorCode;
+
+        pub fn c_MotController_GetMotionProfileTopLevelBufferCount(
+            handle: Handle,
+            value: *mut c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_PushMotionProfileTrajectory(
+            handle: Handle,
+            position: f64,
+            velocity: f64,
+            headingDeg: f64,
+            profileSlotSelect: c_int,
+            isLastPoint: bool,
+            zeroPos: bool,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_PushMotionProfileTrajectory_2(
+            handle: Handle,
+            position: f64,
+            velocity: f64,
+            headingDeg: f64,
+            profileSlotSelect0: c_int,
+            profileSlotSelect1: c_int,
+            isLastPoint: bool,
+            zeroPos: bool,
+            durationMs: c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_IsMotionProfileTopLevelBufferFull(
+            handle: Handle,
+            value: *mut bool,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_ProcessMotionProfileBuffer(handle: Handle) -> ErrorCode;
+
+        pub fn c_MotController_GetMotionProfileStatus(
+            handle: Handle,
+            topBufferRem: *mut c_int,
+            topBufferCnt: *mut c_int,
+            btmBufferCnt: *mut c_int,
+            hasUnderrun: *mut bool,
+            isUnderrun: *mut bool,
+            activePointValid: *mut bool,
+            isLast: *mut bool,
+            profileSlotSelect: *mut c_int,
+            outputEnable: *mut c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_GetMotionProfileStatus_2(
+            handle: Handle,
+            topBufferRem: *mut c_int,
+            topBufferCnt: *mut c_int,
+            btmBufferCnt: *mut c_int,
+            hasUnderrun: *mut bool,
+            isUnderrun: *mut bool,
+            activePointValid: *mut bool,
+            isLast: *mut bool,
+            profileSlotSelect: *mut c_int,
+            outputEnable: *mut c_int,
+            timeDurMs: *mut c_int,
+            profileSlotSelect1: *mut c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_ClearMotionProfileHasUnderrun(
+            handle: Handle,
+            timeoutMs: c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_ChangeMotionControlFramePeriod(
+            handle: Handle,
+            periodMs: c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_ConfigMotionProfileTrajectoryPeriod(
+            handle: Handle,
+            durationMs: c_int,
+            timeoutMs: c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_GetLastError(handle: Handle) -> ErrorCode;
+
+        pub fn c_MotController_GetFirmwareVersion(handle: Handle, arg1: *mut c_int) -> ErrorCode;
+
+        pub fn c_MotController_HasResetOccurred(handle: Handle, arg1: *mut bool) -> ErrorCode;
+
+        pub fn c_MotController_ConfigSetCustomParam(
+            handle: Handle,
+            newValue: c_int,
+            paramIndex: c_int,
+            timeoutMs: c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_ConfigGetCustomParam(
+            handle: Handle,
+            readValue: *mut c_int,
+            paramIndex: c_int,
+            timoutMs: c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_ConfigSetParameter(
+            handle: Handle,
+            param: c_int,
+            value: f64,
+            subValue: c_int,
+            ordinal: c_int,
+            timeoutMs: c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_ConfigGetParameter(
+            handle: Handle,
+            param: c_int,
+            value: *mut f64,
+            ordinal: c_int,
+            timeoutMs: c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_ConfigPeakCurrentLimit(
+            handle: Handle,
+            amps: c_int,
+            timeoutMs: c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_ConfigPeakCurrentDuration(
+            handle: Handle,
+            milliseconds: c_int,
+            timeoutMs: c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_ConfigContinuousCurrentLimit(
+            handle: Handle,
+            amps: c_int,
+            timeoutMs: c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_EnableCurrentLimit(handle: Handle, enable: bool) -> ErrorCode;
+
+        pub fn c_MotController_SetLastError(handle: Handle, error: c_int) -> ErrorCode;
+
+        pub fn c_MotController_GetAnalogIn(handle: Handle, param: *mut c_int) -> ErrorCode;
+
+        pub fn c_MotController_SetAnalogPosition(
+            handle: Handle,
+            newPosition: c_int,
+            timeoutMs: c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_GetAnalogInRaw(handle: Handle, param: *mut c_int) -> ErrorCode;
+
+        pub fn c_MotController_GetAnalogInVel(handle: Handle, param: *mut c_int) -> ErrorCode;
+
+        pub fn c_MotController_GetQuadraturePosition(
+            handle: Handle,
+            param: *mut c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_SetQuadraturePosition(
+            handle: Handle,
+            newPosition: c_int,
+            timeoutMs: c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_GetQuadratureVelocity(
+            handle: Handle,
+            param: *mut c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_GetPulseWidthPosition(
+            handle: Handle,
+            param: *mut c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_SetPulseWidthPosition(
+            handle: Handle,
+            newPosition: c_int,
+            timeoutMs: c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_GetPulseWidthVelocity(
+            handle: Handle,
+            param: *mut c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_GetPulseWidthRiseToFallUs(
+            handle: Handle,
+            param: *mut c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_GetPulseWidthRiseToRiseUs(
+            handle: Handle,
+            param: *mut c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_GetPinStateQuadA(handle: Handle, param: *mut c_int) -> ErrorCode;
+
+        pub fn c_MotController_GetPinStateQuadB(handle: Handle, param: *mut c_int) -> ErrorCode;
+
+        pub fn c_MotController_GetPinStateQuadIdx(handle: Handle, param: *mut c_int) -> ErrorCode;
+
+        pub fn c_MotController_IsFwdLimitSwitchClosed(
+            handle: Handle,
+            param: *mut c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_IsRevLimitSwitchClosed(
+            handle: Handle,
+            param: *mut c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_GetFaults(handle: Handle, param: *mut c_int) -> ErrorCode;
+
+        pub fn c_MotController_GetStickyFaults(handle: Handle, param: *mut c_int) -> ErrorCode;
+
+        pub fn c_MotController_ClearStickyFaults(handle: Handle, timeoutMs: c_int) -> ErrorCode;
+
+        pub fn c_MotController_SelectDemandType(handle: Handle, enable: bool) -> ErrorCode;
+
+        pub fn c_MotController_SetMPEOutput(handle: Handle, MpeOutput: c_int) -> ErrorCode;
+
+        pub fn c_MotController_EnableHeadingHold(handle: Handle, enable: bool) -> ErrorCode;
+
+        pub fn c_MotController_GetAnalogInAll(
+            handle: Handle,
+            withOv: *mut c_int,
+            raw: *mut c_int,
+            vel: *mut c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_GetQuadratureSensor(
+            handle: Handle,
+            pos: *mut c_int,
+            vel: *mut c_int,
+        ) -> ErrorCode;
 
-    pub fn c_MotController_SetMPEOutput(handle: Handle, MpeOutput: c_int) -> ErrorCode;
-
-    pub fn c_MotController_EnableHeadingHold(handle: Handle, enable: bool) -> ErrorCode;
-
-    pub fn c_MotController_GetAnalogInAll(
-        handle: Handle,
-        withOv: *mut c_int,
-        raw: *mut c_int,
-        vel: *mut c_int,
-    ) -> ErrorCode;
-
-    pub fn c_MotController_GetQuadratureSensor(
-        handle: Handle,
-        pos: *mut c_int,
-        vel: *mut c_int,
-    ) -> ErrorCode;
-
-    pub fn c_MotController_GetPulseWidthAll(
-        handle: Handle,
-        pos: *mut c_int,
-        vel: *mut c_int,
-        riseToRiseUs: *mut c_int,
-        riseToFallUs: *mut c_int,
-    ) -> ErrorCode;
-
-    pub fn c_MotController_GetQuadPinStates(
-        handle: Handle,
-        quadA: *mut c_int,
-        quadB: *mut c_int,
-        quadIdx: *mut c_int,
-    ) -> ErrorCode;
+        pub fn c_MotController_GetPulseWidthAll(
+            handle: Handle,
+            pos: *mut c_int,
+            vel: *mut c_int,
+            riseToRiseUs: *mut c_int,
+            riseToFallUs: *mut c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_GetQuadPinStates(
+            handle: Handle,
+            quadA: *mut c_int,
+            quadB: *mut c_int,
+            quadIdx: *mut c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_GetLimitSwitchState(
+            handle: Handle,
+            isFwdClosed: *mut c_int,
+            isRevClosed: *mut c_int,
+        ) -> ErrorCode;
+
+        pub fn c_MotController_GetClosedLoopTarget(
+            handle: Handle,
+            value: *mut c_int,
+            pidIdx: c_int,
+        ) -> ErrorCode;
+    }
+}
 
-    pub fn c_MotController_GetLimitSwitchState(
-        handle: Handle,
-        isFwdClosed: *mut c_int,
-        isRevClosed: *mut c_int,
-    ) -> ErrorCode;
+// Functions added in Phoenix 5.12 (2019 season).
+#[cfg(all(feature = "phoenix-2019", not(feature = "regen-bindings")))]
+cci! {
+    extern "C" {
+        pub fn c_MotController_ConfigFactoryDefault(handle: Handle, timeoutMs: c_int) -> ErrorCode;
+        pub fn c_MotController_ConfigFeedbackNotContinuous(
+            handle: Handle,
+            feedbackNotContinuous: bool,
+            timeoutMs: c_int,
+        ) -> ErrorCode;
+        pub fn c_MotController_ConfigRemoteSensorClosedLoopDisableNeutralOnLOS(
+            handle: Handle,
+            remoteSensorClosedLoopDisableNeutralOnLOS: bool,
+            timeoutMs: c_int,
+        ) -> ErrorCode;
+        pub fn c_MotController_ConfigClearPositionOnLimitF(
+            handle: Handle,
+            clearPositionOnLimitF: bool,
+            timeoutMs: c_int,
+        ) -> ErrorCode;
+        pub fn c_MotController_ConfigClearPositionOnLimitR(
+            handle: Handle,
+            clearPositionOnLimitR: bool,
+            timeoutMs: c_int,
+        ) -> ErrorCode;
+        pub fn c_MotController_ConfigClearPositionOnQuadIdx(
+            handle: Handle,
+            clearPositionOnQuadIdx: bool,
+            timeoutMs: c_int,
+        ) -> ErrorCode;
+        pub fn c_MotController_ConfigLimitSwitchDisableNeutralOnLOS(
+            handle: Handle,
+            limitSwitchDisableNeutralOnLOS: bool,
+            timeoutMs: c_int,
+        ) -> ErrorCode;
+        pub fn c_MotController_ConfigSoftLimitDisableNeutralOnLOS(
+            handle: Handle,
+            softLimitDisableNeutralOnLOS: bool,
+            timeoutMs: c_int,
+        ) -> ErrorCode;
+        pub fn c_MotController_ConfigPulseWidthPeriod_EdgesPerRot(
+            handle: Handle,
+            pulseWidthPeriod_EdgesPerRot: c_int,
+            timeoutMs: c_int,
+        ) -> ErrorCode;
+        pub fn c_MotController_ConfigPulseWidthPeriod_FilterWindowSz(
+            handle: Handle,
+            pulseWidthPeriod_FilterWindowSz: c_int,
+            timeoutMs: c_int,
+        ) -> ErrorCode;
+    }
+}
 
-    pub fn c_MotController_GetClosedLoopTarget(
-        handle: Handle,
-        value: *mut c_int,
-        pidIdx: c_int,
-    ) -> ErrorCode;
+// Functions added in Phoenix 5.17 (2020 season).
+#[cfg(all(feature = "phoenix-2020", not(feature = "regen-bindings")))]
+cci! {
+    extern "C" {
+        pub fn c_MotController_ConfigMotionSCurveStrength(
+            handle: Handle,
+            curveStrength: c_int,
+            timeoutMs: c_int,
+        ) -> ErrorCode;
+        pub fn c_MotController_GetSupplyCurrent(handle: Handle, current: *mut f64) -> ErrorCode;
+        pub fn c_MotController_GetStatorCurrent(handle: Handle, current: *mut f64) -> ErrorCode;
+    }
 }
@@ -0,0 +1,118 @@
+//! Battery voltage sag from the current the simulated devices draw.
+//!
+//! By default each simulated device's bus voltage is whatever it was set to.
+//! With a [`Battery`] set, every device sees the battery's voltage less the
+//! drop across its internal resistance, from the supply current of every
+//! motor controller plus the rest of the robot's draw.  Brownout handling and
+//! voltage compensation can then be tried out against a sagging bus.
+//!
+//! Motor controllers draw their `output_current` scaled by their output; with
+//! closed-loop emulation on, that current comes from their `Mechanism`.
+//!
+//! [`Battery`]: struct.Battery.html
+
+use std::sync::{Mutex, MutexGuard};
+
+/// A battery with an internal resistance.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Battery {
+    /// Open-circuit voltage.
+    pub voltage: f64,
+    /// Internal resistance, plus the wiring's, in ohms.
+    pub resistance: f64,
+    /// Amps drawn by everything other than the simulated motor controllers.
+    pub other_current: f64,
+}
+
+impl Default for Battery {
+    /// A charged FRC battery.
+    fn default() -> Self {
+        Battery {
+            voltage: 12.7,
+            resistance: 0.02,
+            other_current: 2.0,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct State {
+    battery: Option<Battery>,
+    /// Supply current of each motor controller, by base arbitration ID.
+    draws: Vec<(i32, f64)>,
+}
+
+impl State {
+    const fn new() -> Self {
+        State {
+            battery: None,
+            draws: Vec::new(),
+        }
+    }
+
+    fn total_current(&self) -> f64 {
+        let draws = self.draws.iter().map(|&(_, amps)| amps).sum::<f64>();
+        draws + self.battery.map_or(0.0, |battery| battery.other_current)
+    }
+
+    fn bus_voltage(&self) -> Option<f64> {
+        let battery = self.battery?;
+        Some((battery.voltage - battery.resistance * self.total_current()).max(0.0))
+    }
+
+    fn draw(&mut self, device: i32, amps: f64) {
+        match self.draws.iter_mut().find(|draw| draw.0 == device) {
+            Some(draw) => draw.1 = amps,
+            None => self.draws.push((device, amps)),
+        }
+    }
+}
+
+static STATE: Mutex<State> = Mutex::new(State::new());
+
+fn state() -> MutexGuard<'static, State> {
+    STATE.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Powers the simulated devices from `battery`, or stops modelling the
+/// battery with `None`, leaving each device's bus voltage as it was last set.
+pub fn set_battery(battery: Option<Battery>) {
+    state().battery = battery;
+}
+
+pub fn battery() -> Option<Battery> {
+    state().battery
+}
+
+/// Amps drawn from the battery, as of each motor controller's last update.
+pub fn total_current() -> f64 {
+    state().total_current()
+}
+
+/// The voltage the devices see, or `None` if the battery isn't modelled.
+pub fn bus_voltage() -> Option<f64> {
+    state().bus_voltage()
+}
+
+/// Records the supply current of the motor controller `device`.
+pub(crate) fn draw(device: i32, amps: f64) {
+    state().draw(device, amps);
+}
+
+#[test]
+fn voltage_sags_with_current() {
+    let mut state = State::new();
+    state.draw(1, 40.0);
+    assert_eq!(state.bus_voltage(), None);
+    state.battery = Some(Battery {
+        voltage: 12.5,
+        resistance: 0.02,
+        other_current: 10.0,
+    });
+    state.draw(2, 50.0);
+    assert_eq!(state.total_current(), 100.0);
+    assert!((state.bus_voltage().unwrap() - 10.5).abs() < 1e-9);
+    // A controller's latest draw replaces its last.
+    state.draw(1, 0.0);
+    assert!((state.bus_voltage().unwrap() - 11.3).abs() < 1e-9);
+}
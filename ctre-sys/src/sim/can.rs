@@ -0,0 +1,175 @@
+//! Simulated CAN bus conditions: status frame latency, jitter and drops.
+//!
+//! By default every read sees the device as it is now, and every config write
+//! lands.  With [`set_conditions`], the devices run a latency (plus some
+//! jitter) behind, so reads see them as they were that long ago and commands
+//! take effect that much later.  Frames can be dropped too: a read whose status
+//! frame is dropped sees the device as of the last read that wasn't, and
+//! returns `CAN_MSG_STALE`, and a config write that is dropped isn't applied
+//! and returns `SigNotUpdated`.  That exercises staleness detection, retries
+//! and config verification in tests.
+//!
+//! Drops are pseudo-random but repeatable, from `CanConditions::seed`.
+//!
+//! [`set_conditions`]: fn.set_conditions.html
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use clock;
+use ErrorCode;
+
+/// Conditions on the simulated CAN bus, shared by every device.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct CanConditions {
+    /// How far the devices run behind: status frames are this old when read,
+    /// and commands take this long to take effect.  In device time.
+    pub latency: Duration,
+    /// Up to this much more latency, at random for each read.
+    pub jitter: Duration,
+    /// The fraction of frames lost, from 0 (none) to 1 (all).
+    pub drop_rate: f64,
+    /// Seeds the choice of which frames are dropped and the jitter.
+    pub seed: u64,
+}
+
+impl CanConditions {
+    /// No latency, and every frame arrives.
+    pub const IDEAL: CanConditions = CanConditions {
+        latency: Duration::from_secs(0),
+        jitter: Duration::from_secs(0),
+        drop_rate: 0.0,
+        seed: 1,
+    };
+}
+
+impl Default for CanConditions {
+    fn default() -> Self {
+        CanConditions::IDEAL
+    }
+}
+
+/// The bus conditions and the random state they are sampled with.
+#[derive(Debug)]
+struct Bus {
+    conditions: CanConditions,
+    rng: u64,
+}
+
+impl Bus {
+    const fn new() -> Self {
+        Bus {
+            conditions: CanConditions::IDEAL,
+            rng: 1,
+        }
+    }
+
+    fn set_conditions(&mut self, conditions: CanConditions) {
+        self.conditions = conditions;
+        // xorshift gets stuck on zero.
+        self.rng = conditions.seed.max(1);
+    }
+
+    /// A uniformly distributed number in [0, 1), by xorshift64*.
+    fn random(&mut self) -> f64 {
+        self.rng ^= self.rng >> 12;
+        self.rng ^= self.rng << 25;
+        self.rng ^= self.rng >> 27;
+        let bits = self.rng.wrapping_mul(0x2545_F491_4F6C_DD1D) >> 11;
+        bits as f64 / (1u64 << 53) as f64
+    }
+
+    fn dropped(&mut self) -> bool {
+        self.conditions.drop_rate > 0.0 && self.random() < self.conditions.drop_rate
+    }
+
+    fn latency(&mut self) -> Duration {
+        let jitter = self.conditions.jitter;
+        let jitter = if jitter > Duration::from_secs(0) {
+            jitter.mul_f64(self.random())
+        } else {
+            jitter
+        };
+        self.conditions.latency + jitter
+    }
+
+    /// How old a status frame is, or `None` if it was dropped.
+    fn receive(&mut self) -> Option<Duration> {
+        if self.dropped() {
+            return None;
+        }
+        Some(self.latency())
+    }
+}
+
+static BUS: Mutex<Bus> = Mutex::new(Bus::new());
+
+fn bus() -> ::std::sync::MutexGuard<'static, Bus> {
+    BUS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Sets the conditions on the simulated bus, restarting the random sequence.
+pub fn set_conditions(conditions: CanConditions) {
+    bus().set_conditions(conditions);
+}
+
+pub fn conditions() -> CanConditions {
+    bus().conditions
+}
+
+fn before_now(age: Duration) -> Instant {
+    let now = clock::now();
+    now.checked_sub(age).unwrap_or(now)
+}
+
+/// The time on the devices, which run a latency behind the code talking to them.
+pub(crate) fn device_time() -> Instant {
+    before_now(bus().latency())
+}
+
+/// When the status frame being read was sent, or `None` if it was dropped.
+pub(crate) fn receive() -> Option<Instant> {
+    bus().receive().map(before_now)
+}
+
+/// How a read of a device without a time model went: `OK`, or `CAN_MSG_STALE`
+/// if its status frame was dropped.
+pub(crate) fn status_frame() -> ErrorCode {
+    match receive() {
+        Some(_) => ErrorCode::OK,
+        None => ErrorCode::CAN_MSG_STALE,
+    }
+}
+
+/// Whether a config frame reaches the device.
+pub(crate) fn transmit() -> bool {
+    !bus().dropped()
+}
+
+#[test]
+fn conditions_delay_and_drop_frames() {
+    let mut bus = Bus::new();
+    assert_eq!(bus.receive(), Some(Duration::from_secs(0)));
+    bus.set_conditions(CanConditions {
+        latency: Duration::from_millis(20),
+        jitter: Duration::from_millis(10),
+        drop_rate: 0.25,
+        seed: 7,
+    });
+    let frames: Vec<_> = (0..1000).map(|_| bus.receive()).collect();
+    let dropped = frames.iter().filter(|frame| frame.is_none()).count();
+    assert!(dropped > 200 && dropped < 300);
+    assert!(frames
+        .iter()
+        .flatten()
+        .all(|&age| { age >= Duration::from_millis(20) && age <= Duration::from_millis(30) }));
+    // The same seed drops the same frames.
+    bus.set_conditions(CanConditions {
+        seed: 7,
+        ..bus.conditions
+    });
+    assert_eq!(
+        frames[..10],
+        *(0..10).map(|_| bus.receive()).collect::<Vec<_>>()
+    );
+}
@@ -0,0 +1,488 @@
+//! Simulated CANifiers.
+
+use std::collections::HashMap;
+use std::os::raw::{c_char, c_int};
+
+use super::battery;
+use super::can;
+use super::{call, put, put_all, put_description, Params, Table};
+use canifier::Handle;
+use {ErrorCode, ParamEnum};
+
+/// Number of general purpose pins on a CANifier.
+pub const NUM_PINS: usize = 11;
+
+/// State of a simulated CANifier.
+///
+/// The public fields are the signals the CANifier would measure;
+/// set them to whatever the code under test should see.
+#[derive(Debug)]
+pub struct SimCANifier {
+    device_number: i32,
+    params: Params,
+    status_frame_periods: HashMap<c_int, c_int>,
+    last_error: ErrorCode,
+
+    led_outputs: [u32; 3],
+    pwm_outputs: [u32; 4],
+    pwm_output_enabled: [bool; 4],
+    output_values: [bool; NUM_PINS],
+    output_enabled: [bool; NUM_PINS],
+
+    /// Measured pin states.  Pins driven as outputs read back their output value.
+    pub inputs: [bool; NUM_PINS],
+    /// `[duty cycle, period]` per PWM input channel.
+    pub pwm_inputs: [[f64; 2]; 4],
+    pub bus_voltage: f64,
+    pub quadrature_position: i32,
+    pub quadrature_velocity: i32,
+    pub faults: i32,
+    pub sticky_faults: i32,
+    pub firmware_version: i32,
+    pub has_reset_occurred: bool,
+}
+
+impl SimCANifier {
+    fn new(device_number: i32) -> Self {
+        SimCANifier {
+            device_number,
+            params: Params::default(),
+            status_frame_periods: HashMap::new(),
+            last_error: ErrorCode::OK,
+            led_outputs: [0; 3],
+            pwm_outputs: [0; 4],
+            pwm_output_enabled: [false; 4],
+            output_values: [false; NUM_PINS],
+            output_enabled: [false; NUM_PINS],
+            inputs: [false; NUM_PINS],
+            pwm_inputs: [[0.0; 2]; 4],
+            bus_voltage: 12.0,
+            quadrature_position: 0,
+            quadrature_velocity: 0,
+            faults: 0,
+            sticky_faults: 0,
+            firmware_version: 0x0400,
+            has_reset_occurred: false,
+        }
+    }
+
+    pub fn device_number(&self) -> i32 {
+        self.device_number
+    }
+    /// Configuration parameters, as set through the config calls.
+    pub fn params(&self) -> &Params {
+        &self.params
+    }
+    /// Raw LED duty cycles, out of 1023.
+    pub fn led_outputs(&self) -> [u32; 3] {
+        self.led_outputs
+    }
+    /// Raw PWM output duty cycles, out of 1023, or `None` if the channel is disabled.
+    pub fn pwm_output(&self, channel: usize) -> Option<u32> {
+        if self.pwm_output_enabled[channel] {
+            Some(self.pwm_outputs[channel])
+        } else {
+            None
+        }
+    }
+    /// The value a pin is driven to, or `None` if it isn't an output.
+    pub fn general_output(&self, pin: usize) -> Option<bool> {
+        if self.output_enabled[pin] {
+            Some(self.output_values[pin])
+        } else {
+            None
+        }
+    }
+    fn input(&self, pin: usize) -> bool {
+        self.general_output(pin).unwrap_or(self.inputs[pin])
+    }
+}
+
+static DEVICES: Table<SimCANifier> = Table::new();
+
+/// Runs `f` on the simulated CANifier with the given device number.
+/// Returns `None` if no such CANifier has been created.
+pub fn with_device<R>(device_number: i32, f: impl FnOnce(&mut SimCANifier) -> R) -> Option<R> {
+    DEVICES.find(|d| d.device_number == device_number, f)
+}
+
+/// Runs `f` on every simulated CANifier, in the order they were created.
+pub fn for_each_device(f: impl FnMut(&mut SimCANifier)) {
+    DEVICES.for_each(f)
+}
+
+/// Runs `f` on the CANifier behind `handle`, recording the returned error code.
+fn canifier(handle: Handle, f: impl FnOnce(&mut SimCANifier) -> ErrorCode) -> ErrorCode {
+    call(&DEVICES, handle, |d| {
+        if let Some(volts) = battery::bus_voltage() {
+            d.bus_voltage = volts;
+        }
+        let err = f(d);
+        d.last_error = err;
+        err
+    })
+}
+
+/// Reads a value from the CANifier behind `handle` into `out`.
+unsafe fn get<T>(handle: Handle, out: *mut T, f: impl FnOnce(&SimCANifier) -> T) -> ErrorCode {
+    canifier(handle, |d| {
+        put(out, f(d));
+        can::status_frame()
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn c_CANifier_Create1(deviceNumber: c_int) -> Handle {
+    DEVICES.get_or_create(
+        |d| d.device_number == deviceNumber,
+        || SimCANifier::new(deviceNumber),
+    )
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_CANifier_GetDescription(
+    handle: Handle,
+    toFill: *mut c_char,
+    toFillByteSz: c_int,
+    numBytesFilled: *mut c_int,
+) -> ErrorCode {
+    canifier(handle, |d| {
+        let desc = format!("CANifier {}", d.device_number);
+        put_description(&desc, toFill, toFillByteSz, numBytesFilled);
+        ErrorCode::OK
+    })
+}
+#[no_mangle]
+pub extern "C" fn c_CANifier_SetLEDOutput(
+    handle: Handle,
+    dutyCycle: u32,
+    ledChannel: u32,
+) -> ErrorCode {
+    canifier(handle, |d| {
+        match d.led_outputs.get_mut(ledChannel as usize) {
+            Some(led) => {
+                *led = dutyCycle;
+                ErrorCode::OK
+            }
+            None => ErrorCode::InvalidParamValue,
+        }
+    })
+}
+#[no_mangle]
+pub extern "C" fn c_CANifier_SetGeneralOutputs(
+    handle: Handle,
+    outputsBits: u32,
+    isOutputBits: u32,
+) -> ErrorCode {
+    canifier(handle, |d| {
+        for pin in 0..NUM_PINS {
+            d.output_values[pin] = outputsBits & (1 << pin) != 0;
+            d.output_enabled[pin] = isOutputBits & (1 << pin) != 0;
+        }
+        ErrorCode::OK
+    })
+}
+#[no_mangle]
+pub extern "C" fn c_CANifier_SetGeneralOutput(
+    handle: Handle,
+    outputPin: u32,
+    outputValue: bool,
+    outputEnable: bool,
+) -> ErrorCode {
+    canifier(handle, |d| {
+        let pin = outputPin as usize;
+        if pin >= NUM_PINS {
+            return ErrorCode::InvalidParamValue;
+        }
+        d.output_values[pin] = outputValue;
+        d.output_enabled[pin] = outputEnable;
+        ErrorCode::OK
+    })
+}
+#[no_mangle]
+pub extern "C" fn c_CANifier_SetPWMOutput(
+    handle: Handle,
+    pwmChannel: u32,
+    dutyCycle: u32,
+) -> ErrorCode {
+    canifier(handle, |d| {
+        match d.pwm_outputs.get_mut(pwmChannel as usize) {
+            Some(pwm) => {
+                *pwm = dutyCycle;
+                ErrorCode::OK
+            }
+            None => ErrorCode::InvalidParamValue,
+        }
+    })
+}
+#[no_mangle]
+pub extern "C" fn c_CANifier_EnablePWMOutput(
+    handle: Handle,
+    pwmChannel: u32,
+    bEnable: bool,
+) -> ErrorCode {
+    canifier(handle, |d| {
+        match d.pwm_output_enabled.get_mut(pwmChannel as usize) {
+            Some(enabled) => {
+                *enabled = bEnable;
+                ErrorCode::OK
+            }
+            None => ErrorCode::InvalidParamValue,
+        }
+    })
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_CANifier_GetGeneralInputs(
+    handle: Handle,
+    allPins: *mut bool,
+    capacity: u32,
+) -> ErrorCode {
+    canifier(handle, |d| {
+        let pins: Vec<bool> = (0..NUM_PINS).map(|pin| d.input(pin)).collect();
+        let len = NUM_PINS.min(capacity as usize);
+        put_all(allPins, &pins[..len]);
+        if len < NUM_PINS {
+            ErrorCode::InvalidParamValue
+        } else {
+            ErrorCode::OK
+        }
+    })
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_CANifier_GetGeneralInput(
+    handle: Handle,
+    inputPin: u32,
+    measuredInput: *mut bool,
+) -> ErrorCode {
+    canifier(handle, |d| {
+        let pin = inputPin as usize;
+        if pin >= NUM_PINS {
+            return ErrorCode::InvalidParamValue;
+        }
+        put(measuredInput, d.input(pin));
+        ErrorCode::OK
+    })
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_CANifier_GetPWMInput(
+    handle: Handle,
+    pwmChannel: u32,
+    dutyCycleAndPeriod: *mut f64,
+) -> ErrorCode {
+    canifier(handle, |d| match d.pwm_inputs.get(pwmChannel as usize) {
+        Some(input) => {
+            put_all(dutyCycleAndPeriod, input);
+            ErrorCode::OK
+        }
+        None => ErrorCode::InvalidParamValue,
+    })
+}
+#[no_mangle]
+pub extern "C" fn c_CANifier_GetLastError(handle: Handle) -> ErrorCode {
+    DEVICES
+        .with(handle, |d| d.last_error)
+        .unwrap_or(ErrorCode::InvalidHandle)
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_CANifier_GetBusVoltage(
+    handle: Handle,
+    batteryVoltage: *mut f64,
+) -> ErrorCode {
+    get(handle, batteryVoltage, |d| d.bus_voltage)
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_CANifier_GetQuadraturePosition(
+    handle: Handle,
+    pos: *mut c_int,
+) -> ErrorCode {
+    get(handle, pos, |d| d.quadrature_position)
+}
+#[no_mangle]
+pub extern "C" fn c_CANifier_SetQuadraturePosition(
+    handle: Handle,
+    pos: c_int,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    canifier(handle, |d| {
+        d.quadrature_position = pos;
+        ErrorCode::OK
+    })
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_CANifier_GetQuadratureVelocity(
+    handle: Handle,
+    vel: *mut c_int,
+) -> ErrorCode {
+    get(handle, vel, |d| d.quadrature_velocity)
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_CANifier_GetQuadratureSensor(
+    handle: Handle,
+    pos: *mut c_int,
+    vel: *mut c_int,
+) -> ErrorCode {
+    canifier(handle, |d| {
+        put(pos, d.quadrature_position);
+        put(vel, d.quadrature_velocity);
+        ErrorCode::OK
+    })
+}
+#[no_mangle]
+pub extern "C" fn c_CANifier_ConfigVelocityMeasurementPeriod(
+    handle: Handle,
+    period: c_int,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    canifier(handle, |d| {
+        d.params
+            .set(ParamEnum::SampleVelocityPeriod, f64::from(period), 0);
+        ErrorCode::OK
+    })
+}
+#[no_mangle]
+pub extern "C" fn c_CANifier_ConfigVelocityMeasurementWindow(
+    handle: Handle,
+    window: c_int,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    canifier(handle, |d| {
+        d.params
+            .set(ParamEnum::SampleVelocityWindow, f64::from(window), 0);
+        ErrorCode::OK
+    })
+}
+#[no_mangle]
+pub extern "C" fn c_CANifier_SetLastError(handle: Handle, error: c_int) {
+    DEVICES.with(handle, |d| {
+        d.last_error = if error == 0 {
+            ErrorCode::OK
+        } else {
+            ErrorCode::GeneralError
+        }
+    });
+}
+#[no_mangle]
+pub extern "C" fn c_CANifier_ConfigSetParameter(
+    handle: Handle,
+    param: c_int,
+    value: f64,
+    _subValue: c_int,
+    ordinal: c_int,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    canifier(handle, |d| {
+        if !can::transmit() {
+            return ErrorCode::SigNotUpdated;
+        }
+        d.params.set_raw(param, value, ordinal);
+        ErrorCode::OK
+    })
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_CANifier_ConfigGetParameter(
+    handle: Handle,
+    param: c_int,
+    value: *mut f64,
+    ordinal: c_int,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    get(handle, value, |d| d.params.get_raw(param, ordinal))
+}
+#[no_mangle]
+pub extern "C" fn c_CANifier_ConfigSetCustomParam(
+    handle: Handle,
+    newValue: c_int,
+    paramIndex: c_int,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    canifier(handle, |d| {
+        d.params
+            .set(ParamEnum::CustomParam, f64::from(newValue), paramIndex);
+        ErrorCode::OK
+    })
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_CANifier_ConfigGetCustomParam(
+    handle: Handle,
+    readValue: *mut c_int,
+    paramIndex: c_int,
+    _timoutMs: c_int,
+) -> ErrorCode {
+    get(handle, readValue, |d| {
+        d.params.get(ParamEnum::CustomParam, paramIndex) as c_int
+    })
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_CANifier_GetFaults(handle: Handle, param: *mut c_int) -> ErrorCode {
+    get(handle, param, |d| d.faults)
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_CANifier_GetStickyFaults(
+    handle: Handle,
+    param: *mut c_int,
+) -> ErrorCode {
+    get(handle, param, |d| d.sticky_faults)
+}
+#[no_mangle]
+pub extern "C" fn c_CANifier_ClearStickyFaults(handle: Handle, _timeoutMs: c_int) -> ErrorCode {
+    canifier(handle, |d| {
+        d.sticky_faults = 0;
+        ErrorCode::OK
+    })
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_CANifier_GetFirmwareVersion(
+    handle: Handle,
+    firmwareVers: *mut c_int,
+) -> ErrorCode {
+    get(handle, firmwareVers, |d| d.firmware_version)
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_CANifier_HasResetOccurred(
+    handle: Handle,
+    hasReset: *mut bool,
+) -> ErrorCode {
+    canifier(handle, |d| {
+        put(hasReset, d.has_reset_occurred);
+        d.has_reset_occurred = false;
+        ErrorCode::OK
+    })
+}
+#[no_mangle]
+pub extern "C" fn c_CANifier_SetStatusFramePeriod(
+    handle: Handle,
+    frame: c_int,
+    periodMs: c_int,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    canifier(handle, |d| {
+        d.status_frame_periods.insert(frame, periodMs);
+        ErrorCode::OK
+    })
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_CANifier_GetStatusFramePeriod(
+    handle: Handle,
+    frame: c_int,
+    periodMs: *mut c_int,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    get(handle, periodMs, |d| {
+        d.status_frame_periods.get(&frame).cloned().unwrap_or(100)
+    })
+}
+#[no_mangle]
+pub extern "C" fn c_CANifier_SetControlFramePeriod(
+    handle: Handle,
+    _frame: c_int,
+    _periodMs: c_int,
+) -> ErrorCode {
+    canifier(handle, |_| ErrorCode::OK)
+}
+#[cfg(feature = "phoenix-2019")]
+#[no_mangle]
+pub extern "C" fn c_CANifier_ConfigFactoryDefault(handle: Handle, _timeoutMs: c_int) -> ErrorCode {
+    canifier(handle, |d| {
+        d.params.clear();
+        ErrorCode::OK
+    })
+}
@@ -0,0 +1,198 @@
+//! Closed-loop emulation for simulated motor controllers.
+//!
+//! By default a simulated controller applies neutral output in closed-loop
+//! modes, and its sensors read whatever they were set to.  With a [`Fidelity`]
+//! other than `Off`, it runs the firmware's PIDF loop at 1 kHz instead, driving
+//! a [`Mechanism`] which moves the selected sensor, so gains tuned against the
+//! simulation behave much the same on the robot, and tests can assert on how
+//! the closed-loop error evolves.
+//!
+//! ```no_run
+//! # use ctre_sys::sim::closed_loop::Fidelity;
+//! # use ctre_sys::sim::mot;
+//! mot::with_device(0x0204_0000 | 3, |talon| talon.set_fidelity(Fidelity::Firmware));
+//! ```
+//!
+//! [`Fidelity`]: enum.Fidelity.html
+//! [`Mechanism`]: struct.Mechanism.html
+
+use ParamEnum;
+
+use super::Params;
+
+/// How closely a simulated controller emulates the firmware's closed loop.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum Fidelity {
+    /// Closed-loop modes apply neutral output and sensors don't move, except
+    /// that Motion Magic moves the sensor along its trajectory.
+    #[default]
+    Off,
+    /// The P, I, D and F terms alone, clamped to the peak outputs.
+    Basic,
+    /// As the firmware does it: also the integral zone, the integral
+    /// accumulator limit, the allowable error, the slot's peak output, and the
+    /// nominal outputs.
+    Firmware,
+}
+
+/**
+ * What the motor drives, as seen by its selected sensor: a first-order
+ * response from the applied voltage to the velocity.
+ *
+ * The motor's current follows from how far it is from the speed the applied
+ * voltage would settle at, and replaces the controller's `output_current`.
+ */
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Mechanism {
+    /// Steady-state velocity at 12V, in native units per 100ms.
+    pub free_speed: f64,
+    /// Seconds to reach 63% of the way to a new steady-state velocity.
+    pub time_constant: f64,
+    /// Amps drawn when stalled at 12V.
+    pub stall_current: f64,
+}
+
+impl Default for Mechanism {
+    fn default() -> Self {
+        Mechanism {
+            free_speed: 1000.,
+            time_constant: 0.05,
+            stall_current: 130.,
+        }
+    }
+}
+
+impl Mechanism {
+    /// The velocity `dt` seconds on from `velocity`, with `volts` applied.
+    pub(crate) fn step(&self, velocity: f64, volts: f64, dt: f64) -> f64 {
+        let steady = self.free_speed * volts / 12.;
+        if self.time_constant <= dt {
+            return steady;
+        }
+        velocity + (steady - velocity) * dt / self.time_constant
+    }
+
+    /// The motor's current at `velocity` with `volts` applied; negative when
+    /// it is driven faster than the voltage would settle at.
+    pub(crate) fn current(&self, velocity: f64, volts: f64) -> f64 {
+        if self.free_speed == 0. {
+            return 0.;
+        }
+        let back_emf = 12. * velocity / self.free_speed;
+        self.stall_current * (volts - back_emf) / 12.
+    }
+}
+
+/// The gains and limits of one slot, as configured.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Gains {
+    kp: f64,
+    ki: f64,
+    kd: f64,
+    kf: f64,
+    izone: f64,
+    allowable_error: f64,
+    max_integral: f64,
+    peak_output: f64,
+}
+
+impl Gains {
+    pub fn read(params: &Params, slot: i32) -> Self {
+        Gains {
+            kp: params.get(ParamEnum::ProfileParamSlot_P, slot),
+            ki: params.get(ParamEnum::ProfileParamSlot_I, slot),
+            kd: params.get(ParamEnum::ProfileParamSlot_D, slot),
+            kf: params.get(ParamEnum::ProfileParamSlot_F, slot),
+            izone: params.get(ParamEnum::ProfileParamSlot_IZone, slot),
+            allowable_error: params.get(ParamEnum::ProfileParamSlot_AllowableErr, slot),
+            max_integral: params.get(ParamEnum::ProfileParamSlot_MaxIAccum, slot),
+            peak_output: params.get(ParamEnum::ProfileParamSlot_PeakOutput, slot),
+        }
+    }
+}
+
+/// State of the emulated PIDF loop.
+#[derive(Debug, Default, Copy, Clone)]
+pub(crate) struct Pidf {
+    /// The error the loop last ran on, after the allowable error.
+    pub error: f64,
+    pub derivative: f64,
+    /// The loop's output in the interval [-1, +1], before the controller's own
+    /// clamps and any arbitrary feed forward.
+    pub output: f64,
+}
+
+impl Pidf {
+    /**
+     * Runs the loop once on `error`, updating `integral`.  The F term is
+     * `kF * feed_forward`, where the firmware feeds forward the target
+     * (or the trajectory's velocity in Motion Magic).
+     */
+    pub fn run(
+        &mut self,
+        fidelity: Fidelity,
+        gains: &Gains,
+        error: f64,
+        feed_forward: f64,
+        integral: &mut f64,
+    ) {
+        let firmware = fidelity == Fidelity::Firmware;
+        let mut error = error;
+        if firmware && error.abs() <= gains.allowable_error {
+            error = 0.;
+            *integral = 0.;
+        }
+        if firmware && gains.izone != 0. && error.abs() > gains.izone {
+            *integral = 0.;
+        } else {
+            *integral += error;
+        }
+        if firmware && gains.max_integral != 0. {
+            *integral = integral.clamp(-gains.max_integral, gains.max_integral);
+        }
+        self.derivative = error - self.error;
+        self.error = error;
+        let output = (gains.kp * error
+            + gains.ki * *integral
+            + gains.kd * self.derivative
+            + gains.kf * feed_forward)
+            / 1023.;
+        let peak = if firmware { gains.peak_output } else { 1. };
+        self.output = output.clamp(-peak, peak);
+    }
+}
+
+#[test]
+fn mechanism_settles_at_free_speed() {
+    let mechanism = Mechanism::default();
+    let mut velocity = 0.;
+    for _ in 0..1000 {
+        velocity = mechanism.step(velocity, 6., 0.001);
+    }
+    assert!((velocity - 500.).abs() < 1.);
+    assert!(mechanism.current(velocity, 6.).abs() < 1.);
+    assert_eq!(mechanism.current(0., 12.), 130.);
+}
+
+#[test]
+fn firmware_fidelity_applies_izone_and_allowable_error() {
+    let gains = Gains {
+        kp: 1.,
+        ki: 0.1,
+        kd: 0.,
+        kf: 0.,
+        izone: 100.,
+        allowable_error: 5.,
+        max_integral: 0.,
+        peak_output: 0.5,
+    };
+    let (mut pidf, mut integral) = (Pidf::default(), 50.);
+    // Outside the integral zone, the integral is cleared.
+    pidf.run(Fidelity::Firmware, &gains, 1023., 0., &mut integral);
+    assert_eq!((integral, pidf.output), (0., 0.5));
+    // Basic fidelity knows nothing of either.
+    pidf.run(Fidelity::Basic, &gains, 1023., 0., &mut integral);
+    assert_eq!((integral, pidf.output), (1023., 1.));
+    pidf.run(Fidelity::Firmware, &gains, 4., 0., &mut integral);
+    assert_eq!((integral, pidf.error, pidf.output), (0., 0., 0.));
+}
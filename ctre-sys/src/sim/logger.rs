@@ -0,0 +1,28 @@
+//! Logger stand-in; non-OK codes go to stderr.
+
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_int};
+
+use ErrorCode;
+
+#[no_mangle]
+pub extern "C" fn c_Logger_Close() {}
+#[no_mangle]
+pub extern "C" fn c_Logger_Open(_language: c_int, _logDriverStation: bool) {}
+#[no_mangle]
+pub unsafe extern "C" fn c_Logger_Log(
+    code: ErrorCode,
+    origin: *const c_char,
+    _hierarchy: c_int,
+    _stacktrace: *const c_char,
+) -> ErrorCode {
+    if code != ErrorCode::OK {
+        let origin = if origin.is_null() {
+            "".into()
+        } else {
+            CStr::from_ptr(origin).to_string_lossy()
+        };
+        eprintln!("CTRE: {} ({:?})", origin, code);
+    }
+    code
+}
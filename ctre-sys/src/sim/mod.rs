@@ -0,0 +1,208 @@
+//! In-process stand-in for the Phoenix CCI.
+//!
+//! When the NI and Phoenix shared libraries aren't linked (the `no-link` feature,
+//! or any target other than the roboRIO), every CCI function is provided by this
+//! module instead, so the extern declarations elsewhere in this crate resolve here.
+//!
+//! Devices remember their configuration and the last demand applied to them,
+//! and followers mirror their master's output.
+//! A controller's remote sensors read the simulated Pigeon, CANifier or Talon
+//! they are configured to, so remote feedback and auxiliary PID work off-robot.
+//! Sensor signals read back whatever was last put into them through this module,
+//! which is enough for running unit tests and lints on a development machine.
+//! In Motion Magic mode, though, the selected sensor follows the trajectory the
+//! firmware would generate; see `motion_magic`.  `closed_loop` can emulate the
+//! firmware's PIDF loop driving a mechanism, for tuning gains off-robot.
+//! `can` adds latency and dropped frames, to exercise staleness handling, and
+//! `battery` sags the bus voltage under load.
+//! `provider` lists every device's signals, for a GUI to show and set them.
+//! [`set_time_scale`] runs the simulation faster or slower than real time.
+//!
+//! [`set_time_scale`]: fn.set_time_scale.html
+#![allow(
+    non_snake_case,
+    clippy::missing_safety_doc,
+    clippy::not_unsafe_ptr_arg_deref
+)]
+
+use std::collections::HashMap;
+use std::os::raw::{c_char, c_int};
+use std::ptr;
+use std::slice;
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+
+use {clock, ErrorCode, ParamEnum};
+
+pub mod battery;
+pub mod can;
+pub mod canifier;
+pub mod closed_loop;
+mod logger;
+pub mod mot;
+pub mod motion_magic;
+pub mod pigeon;
+pub mod provider;
+
+/// The period the firmware runs its control loops at.
+const STEP: Duration = Duration::from_millis(1);
+/// `STEP` in seconds.
+const DT: f64 = 0.001;
+
+/**
+ * Runs the simulation `scale` times as fast as real time, as seen through
+ * `clock::now` and `clock::sleep`.  Simulated time carries on from where it
+ * was, so it never goes backwards.
+ *
+ * # Panics
+ *
+ * If `scale` isn't positive and finite.
+ */
+pub fn set_time_scale(scale: f64) {
+    clock::set_scale(scale);
+}
+
+/// Configuration parameters of a simulated device, keyed by `(param, ordinal)`.
+#[derive(Debug, Default, Clone)]
+pub struct Params {
+    values: HashMap<(c_int, c_int), f64>,
+}
+impl Params {
+    /// Gets a parameter, or the factory default if it was never set.
+    pub fn get(&self, param: ParamEnum, ordinal: i32) -> f64 {
+        self.get_raw(param as _, ordinal)
+    }
+    pub fn set(&mut self, param: ParamEnum, value: f64, ordinal: i32) {
+        self.values.insert((param as _, ordinal), value);
+    }
+    fn get_raw(&self, param: c_int, ordinal: c_int) -> f64 {
+        match self.values.get(&(param, ordinal)) {
+            Some(&value) => value,
+            None => factory_default(param),
+        }
+    }
+    fn set_raw(&mut self, param: c_int, value: f64, ordinal: c_int) {
+        self.values.insert((param, ordinal), value);
+    }
+    /// Forget everything that was configured.
+    pub fn clear(&mut self) {
+        self.values.clear();
+    }
+}
+
+/// Factory defaults for the parameters which don't default to zero.
+fn factory_default(param: c_int) -> f64 {
+    const DEFAULTS: &[(ParamEnum, f64)] = &[
+        (ParamEnum::PeakPosOutput, 1.0),
+        (ParamEnum::PeakNegOutput, -1.0),
+        (ParamEnum::NeutralDeadband, 0.04),
+        (ParamEnum::NominalBatteryVoltage, 12.0),
+        (ParamEnum::BatteryVoltageFilterSize, 32.0),
+        (ParamEnum::SampleVelocityPeriod, 100.0),
+        (ParamEnum::SampleVelocityWindow, 64.0),
+        (ParamEnum::SelectedSensorCoefficient, 1.0),
+        (ParamEnum::ProfileParamSlot_PeakOutput, 1.0),
+        (ParamEnum::PIDLoopPeriod, 1.0),
+        (ParamEnum::LimitSwitchSource, 3.0),
+        (ParamEnum::PulseWidthPeriod_EdgesPerRot, 1.0),
+        (ParamEnum::PulseWidthPeriod_FilterWindowSz, 1.0),
+    ];
+    DEFAULTS
+        .iter()
+        .find(|&&(p, _)| p as c_int == param)
+        .map_or(0.0, |&(_, value)| value)
+}
+
+/// A table of simulated devices of one kind.
+/// Handles are 1-based indices into the table.
+pub(crate) struct Table<T> {
+    devices: Mutex<Vec<T>>,
+}
+impl<T> Table<T> {
+    pub const fn new() -> Self {
+        Table {
+            devices: Mutex::new(Vec::new()),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Vec<T>> {
+        // A panicking test shouldn't take the whole simulation down with it.
+        self.devices.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Finds the device matching `pred`, creating it with `create` if there is none.
+    pub fn get_or_create<H>(
+        &self,
+        pred: impl Fn(&T) -> bool,
+        create: impl FnOnce() -> T,
+    ) -> *mut H {
+        let mut devices = self.lock();
+        let idx = match devices.iter().position(pred) {
+            Some(idx) => idx,
+            None => {
+                devices.push(create());
+                devices.len() - 1
+            }
+        };
+        (idx + 1) as *mut H
+    }
+
+    /// Runs `f` on the device behind `handle`.
+    pub fn with<H, R>(&self, handle: *mut H, f: impl FnOnce(&mut T) -> R) -> Option<R> {
+        let idx = (handle as usize).wrapping_sub(1);
+        self.lock().get_mut(idx).map(f)
+    }
+
+    /// Runs `f` on every device, in the order they were created.
+    pub fn for_each(&self, f: impl FnMut(&mut T)) {
+        self.lock().iter_mut().for_each(f)
+    }
+
+    /// Runs `f` on the first device matching `pred`.
+    pub fn find<R>(&self, pred: impl Fn(&T) -> bool, f: impl FnOnce(&mut T) -> R) -> Option<R> {
+        self.lock().iter_mut().find(|d| pred(d)).map(f)
+    }
+}
+
+/// Runs `f` on the device behind `handle`, returning `InvalidHandle` if there is none.
+fn call<T, H>(table: &Table<T>, handle: *mut H, f: impl FnOnce(&mut T) -> ErrorCode) -> ErrorCode {
+    table.with(handle, f).unwrap_or(ErrorCode::InvalidHandle)
+}
+
+/// Writes `value` through `out` if it isn't null.
+unsafe fn put<T>(out: *mut T, value: T) {
+    if !out.is_null() {
+        *out = value;
+    }
+}
+
+/// Copies `values` into the array at `out`.
+unsafe fn put_all<T: Copy>(out: *mut T, values: &[T]) {
+    if !out.is_null() {
+        slice::from_raw_parts_mut(out, values.len()).copy_from_slice(values);
+    }
+}
+
+/// Writes a device description into a C string buffer, truncating if necessary.
+unsafe fn put_description(desc: &str, to_fill: *mut c_char, capacity: c_int, filled: *mut c_int) {
+    let len = desc.len().min((capacity.max(1) - 1) as usize);
+    if !to_fill.is_null() && capacity > 0 {
+        ptr::copy_nonoverlapping(desc.as_ptr() as *const c_char, to_fill, len);
+        *to_fill.add(len) = 0;
+    }
+    put(filled, len as c_int);
+}
+
+#[test]
+fn time_scale_speeds_up_the_clock() {
+    use std::time::{Duration, Instant};
+    set_time_scale(10.);
+    assert_eq!(clock::time_scale(), 10.);
+    let (real_start, start) = (Instant::now(), clock::now());
+    clock::sleep(Duration::from_millis(200));
+    assert!(clock::now() - start >= Duration::from_millis(200));
+    assert!(real_start.elapsed() < Duration::from_millis(150));
+    let before = clock::now();
+    set_time_scale(1.);
+    assert!(clock::now() >= before);
+}
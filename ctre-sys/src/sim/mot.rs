@@ -0,0 +1,2221 @@
+//! Simulated Talon SRX and Victor SPX motor controllers.
+
+use std::collections::{HashMap, VecDeque};
+use std::os::raw::{c_char, c_int};
+use std::sync::{Mutex, MutexGuard};
+use std::time::Instant;
+
+use super::battery;
+use super::can;
+use super::canifier;
+use super::closed_loop::{Fidelity, Gains, Mechanism, Pidf};
+use super::motion_magic::Trajectory;
+use super::pigeon;
+use super::{call, put, put_description, Params, Table, DT, STEP};
+use clock;
+use mot::{ControlMode, DemandType, FeedbackDevice, Handle, NeutralMode};
+use {ErrorCode, ParamEnum};
+
+/// Number of points the API-level (top) motion profile buffer holds.
+pub const TOP_BUFFER_CAPACITY: usize = 2048;
+/// Number of points the controller's (bottom) motion profile buffer holds.
+pub const BOTTOM_BUFFER_CAPACITY: usize = 128;
+/// The device type in a Talon SRX's id, as followers and remote sensors name it.
+const TALON_SRX_TYPE: i32 = 0x0204;
+/// Phoenix's Pigeon units, 8192 to the rotation.
+const PIGEON_UNITS_PER_DEGREE: f64 = 8192.0 / 360.0;
+
+/// A buffered motion profile trajectory point.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct BufferedPoint {
+    pub position: f64,
+    pub velocity: f64,
+    pub auxiliary_pos: f64,
+    pub profile_slot_select_0: i32,
+    pub profile_slot_select_1: i32,
+    pub is_last_point: bool,
+    pub zero_pos: bool,
+    pub duration_ms: i32,
+}
+
+/// State of a simulated motor controller.
+///
+/// The public fields are the signals the controller would measure;
+/// set them to whatever the code under test should see.
+#[derive(Debug)]
+pub struct SimMotController {
+    base_arb_id: i32,
+    params: Params,
+    status_frame_periods: HashMap<c_int, c_int>,
+    control_frame_periods: HashMap<c_int, c_int>,
+    last_error: ErrorCode,
+
+    control_mode: c_int,
+    demand0: f64,
+    demand1: f64,
+    demand1_type: c_int,
+    neutral_mode: c_int,
+    inverted: bool,
+    sensor_phase: bool,
+    voltage_compensation: bool,
+    current_limit: bool,
+    limit_switch_override: bool,
+    soft_limit_override: bool,
+
+    selected_sensor_offset: [i32; 2],
+    integral_accumulator: [f64; 2],
+    profile_slot: [i32; 2],
+
+    top_buffer: VecDeque<BufferedPoint>,
+    bottom_buffer: VecDeque<BufferedPoint>,
+    has_underrun: bool,
+    motion_magic: Option<Trajectory>,
+
+    fidelity: Fidelity,
+    pidf: Pidf,
+    /// Milliseconds since the PIDF loop last ran.
+    since_loop: i32,
+    /// The mechanism's position and velocity, in selected sensor units.
+    mechanism_position: f64,
+    mechanism_velocity: f64,
+    /// What the selected sensor read after the mechanism last moved it.
+    mechanism_reading: i32,
+    stepped: Instant,
+    /// The output terms mirrored from the master, in follower mode.
+    master_output: (f64, f64),
+
+    /// What the motor drives, when closed-loop emulation is on.
+    pub mechanism: Mechanism,
+    pub bus_voltage: f64,
+    pub output_current: f64,
+    pub temperature: f64,
+    pub quadrature_position: i32,
+    pub quadrature_velocity: i32,
+    pub analog_position: i32,
+    pub analog_velocity: i32,
+    pub pulse_width_position: i32,
+    pub pulse_width_velocity: i32,
+    pub pulse_width_rise_to_fall_us: i32,
+    pub pulse_width_rise_to_rise_us: i32,
+    pub quad_a: bool,
+    pub quad_b: bool,
+    pub quad_idx: bool,
+    pub fwd_limit_switch_closed: bool,
+    pub rev_limit_switch_closed: bool,
+    pub faults: i32,
+    pub sticky_faults: i32,
+    pub firmware_version: i32,
+    pub has_reset_occurred: bool,
+}
+
+impl SimMotController {
+    fn new(base_arb_id: i32) -> Self {
+        SimMotController {
+            base_arb_id,
+            params: Params::default(),
+            status_frame_periods: HashMap::new(),
+            control_frame_periods: HashMap::new(),
+            last_error: ErrorCode::OK,
+            control_mode: ControlMode::Disabled as _,
+            demand0: 0.0,
+            demand1: 0.0,
+            demand1_type: DemandType::Neutral as _,
+            neutral_mode: NeutralMode::EEPROMSetting as _,
+            inverted: false,
+            sensor_phase: false,
+            voltage_compensation: false,
+            current_limit: false,
+            limit_switch_override: true,
+            soft_limit_override: true,
+            selected_sensor_offset: [0; 2],
+            integral_accumulator: [0.0; 2],
+            profile_slot: [0; 2],
+            top_buffer: VecDeque::new(),
+            bottom_buffer: VecDeque::new(),
+            has_underrun: false,
+            motion_magic: None,
+            fidelity: Fidelity::Off,
+            pidf: Pidf::default(),
+            since_loop: 0,
+            mechanism_position: 0.0,
+            mechanism_velocity: 0.0,
+            mechanism_reading: 0,
+            stepped: clock::now(),
+            master_output: (0.0, 0.0),
+            mechanism: Mechanism::default(),
+            bus_voltage: 12.0,
+            output_current: 0.0,
+            temperature: 25.0,
+            quadrature_position: 0,
+            quadrature_velocity: 0,
+            analog_position: 0,
+            analog_velocity: 0,
+            pulse_width_position: 0,
+            pulse_width_velocity: 0,
+            pulse_width_rise_to_fall_us: 0,
+            pulse_width_rise_to_rise_us: 0,
+            quad_a: false,
+            quad_b: false,
+            quad_idx: false,
+            fwd_limit_switch_closed: false,
+            rev_limit_switch_closed: false,
+            faults: 0,
+            sticky_faults: 0,
+            firmware_version: 0x0400,
+            has_reset_occurred: false,
+        }
+    }
+
+    /// The arbitration ID this controller was created with.
+    pub fn base_arb_id(&self) -> i32 {
+        self.base_arb_id
+    }
+    /// The CAN device number of this controller.
+    pub fn device_number(&self) -> i32 {
+        self.base_arb_id & 0x3F
+    }
+    /// Configuration parameters, as set through the config calls.
+    pub fn params(&self) -> &Params {
+        &self.params
+    }
+    /// The control mode of the last demand.
+    pub fn control_mode(&self) -> c_int {
+        self.control_mode
+    }
+    /// The last demand, as `(demand0, demand1, demand1_type)`.
+    pub fn demand(&self) -> (f64, f64, c_int) {
+        (self.demand0, self.demand1, self.demand1_type)
+    }
+    pub fn neutral_mode(&self) -> c_int {
+        self.neutral_mode
+    }
+    pub fn inverted(&self) -> bool {
+        self.inverted
+    }
+    pub fn sensor_phase(&self) -> bool {
+        self.sensor_phase
+    }
+    /// The trajectory points waiting in the top and bottom buffers.
+    pub fn buffered_points(&self) -> impl Iterator<Item = &BufferedPoint> {
+        self.top_buffer.iter().chain(self.bottom_buffer.iter())
+    }
+
+    /// The Motion Magic trajectory being followed, if in Motion Magic mode.
+    pub fn motion_magic(&self) -> Option<&Trajectory> {
+        self.motion_magic.as_ref()
+    }
+
+    pub fn fidelity(&self) -> Fidelity {
+        self.fidelity
+    }
+    /// Sets how closely the closed loop is emulated, starting the mechanism
+    /// from rest wherever the selected sensor is.
+    pub fn set_fidelity(&mut self, fidelity: Fidelity) {
+        self.update();
+        self.fidelity = fidelity;
+        self.pidf = Pidf::default();
+        self.since_loop = 0;
+        self.mechanism_reading = self.selected_sensor_position(0);
+        self.mechanism_position = f64::from(self.mechanism_reading);
+        self.mechanism_velocity = 0.0;
+        self.stepped = can::device_time();
+    }
+
+    /// Catches the emulated firmware up with the current device time.
+    ///
+    /// Without closed-loop emulation, the mechanism follows a Motion Magic
+    /// trajectory exactly, so its selected sensor is moved along with it.
+    fn update(&mut self) {
+        self.update_to(can::device_time());
+    }
+
+    /// Catches the emulated firmware up with `now`, if it is behind.
+    fn update_to(&mut self, now: Instant) {
+        if let Some(volts) = battery::bus_voltage() {
+            self.bus_voltage = volts;
+        }
+        if self.control_mode == ControlMode::Follower as c_int {
+            self.follow_master();
+        }
+        self.advance(now);
+        battery::draw(self.base_arb_id, self.supply_current());
+        self.publish();
+    }
+
+    /// The id followers are told to follow this controller by.
+    fn id24(&self) -> i32 {
+        ((self.base_arb_id >> 16) & 0xFFFF) << 8 | (self.base_arb_id & 0xFF)
+    }
+
+    /// Makes this controller's output terms available to its followers, and its
+    /// selected sensor to controllers using it as a remote sensor.
+    fn publish(&self) {
+        let published = Published {
+            id24: self.id24(),
+            terms: self.output_terms(),
+            sensor: (
+                f64::from(self.selected_sensor_position(0)),
+                f64::from(self.selected_sensor_velocity(0)),
+            ),
+        };
+        let mut all = published_lock();
+        match all.iter_mut().find(|p| p.id24 == published.id24) {
+            Some(p) => *p = published,
+            None => all.push(published),
+        }
+    }
+
+    /// Mirrors the master's output terms; with `AuxOutput1`, the auxiliary
+    /// term is subtracted rather than added.
+    fn follow_master(&mut self) {
+        let master = self.demand0 as i32;
+        let (primary, aux) = published_lock()
+            .iter()
+            .find(|p| p.id24 == master)
+            .map_or((0.0, 0.0), |p| p.terms);
+        self.master_output = if self.demand1_type == DemandType::AuxPID as c_int {
+            (primary, -aux)
+        } else {
+            (primary, aux)
+        };
+    }
+
+    fn advance(&mut self, now: Instant) {
+        if self.fidelity != Fidelity::Off {
+            // Follow the sensor if it was moved from outside since the last step.
+            let reading = self.selected_sensor_position(0);
+            if reading != self.mechanism_reading {
+                self.mechanism_position = f64::from(reading);
+            }
+            while self.stepped + STEP <= now {
+                self.stepped += STEP;
+                self.step(self.stepped);
+            }
+            return;
+        }
+        let (position, velocity) = match self.motion_magic {
+            Some(ref mut trajectory) => {
+                trajectory.advance(now);
+                (trajectory.position(), trajectory.velocity())
+            }
+            None => return,
+        };
+        self.set_raw_selected_sensor(0, position, velocity);
+    }
+
+    /// Runs the firmware and the mechanism for one step, ending at `now`.
+    fn step(&mut self, now: Instant) {
+        if let Some(ref mut trajectory) = self.motion_magic {
+            trajectory.advance(now);
+        }
+        if self.runs_pidf() {
+            self.since_loop += 1;
+            let slot = self.profile_slot[0];
+            let period = self.params.get(ParamEnum::PIDLoopPeriod, slot).max(1.0) as i32;
+            if self.since_loop >= period {
+                self.since_loop = 0;
+                self.run_pidf(slot);
+            }
+        } else {
+            self.pidf = Pidf::default();
+        }
+
+        let supply = if self.voltage_compensation {
+            let saturation = self.params.get(ParamEnum::NominalBatteryVoltage, 0);
+            saturation.min(self.bus_voltage)
+        } else {
+            self.bus_voltage
+        };
+        let volts = self.motor_output_percent() * supply;
+        self.output_current = self.mechanism.current(self.mechanism_velocity, volts).abs();
+        self.mechanism_velocity = self.mechanism.step(self.mechanism_velocity, volts, DT);
+        self.mechanism_position += self.mechanism_velocity * 10.0 * DT;
+        let (position, velocity) = (self.mechanism_position, self.mechanism_velocity);
+        self.set_raw_selected_sensor(0, position, velocity);
+        self.mechanism_reading = self.selected_sensor_position(0);
+    }
+
+    /// Whether the emulated firmware is running its PIDF loop.
+    fn runs_pidf(&self) -> bool {
+        let mode = self.control_mode;
+        self.fidelity != Fidelity::Off
+            && (mode == ControlMode::Position as c_int
+                || mode == ControlMode::Velocity as c_int
+                || mode == ControlMode::MotionMagic as c_int)
+    }
+
+    fn run_pidf(&mut self, slot: c_int) {
+        let position = f64::from(self.selected_sensor_position(0));
+        let velocity = f64::from(self.selected_sensor_velocity(0));
+        // The firmware feeds forward the target, or the trajectory's velocity.
+        let (error, feed_forward) = match self.motion_magic {
+            Some(ref trajectory) => (trajectory.position() - position, trajectory.velocity()),
+            None if self.control_mode == ControlMode::Velocity as c_int => {
+                (self.demand0 - velocity, self.demand0)
+            }
+            None => (self.demand0 - position, self.demand0),
+        };
+        let gains = Gains::read(&self.params, slot);
+        let integral = &mut self.integral_accumulator[0];
+        self.pidf
+            .run(self.fidelity, &gains, error, feed_forward, integral);
+    }
+
+    /// Starts, retargets or stops the Motion Magic trajectory for a new demand.
+    fn demand_changed(&mut self) {
+        if self.control_mode != ControlMode::MotionMagic as c_int {
+            self.motion_magic = None;
+            return;
+        }
+        let cruise = self.params.get(ParamEnum::MotMag_VelCruise, 0);
+        let accel = self.params.get(ParamEnum::MotMag_Accel, 0);
+        #[cfg(feature = "phoenix-2020")]
+        let s_curve = self.params.get(ParamEnum::MotMag_SCurveLevel, 0) as i32;
+        #[cfg(not(feature = "phoenix-2020"))]
+        let s_curve = 0;
+        if self.motion_magic.is_none() {
+            let position = f64::from(self.selected_sensor_position(0));
+            let velocity = f64::from(self.selected_sensor_velocity(0));
+            let now = can::device_time();
+            self.motion_magic = Some(Trajectory::new(now, position, velocity));
+        }
+        if let Some(ref mut trajectory) = self.motion_magic {
+            trajectory.retarget(self.demand0, cruise, accel, s_curve);
+        }
+    }
+
+    /// The output the controller would apply, in the interval [-1, +1].
+    /// Closed-loop modes apply neutral output unless they are emulated, and
+    /// followers mirror their master.
+    pub fn motor_output_percent(&self) -> f64 {
+        let (primary, aux) = self.output_terms();
+        let mut output = primary + aux;
+        if self.runs_pidf() && self.fidelity == Fidelity::Firmware && output != 0.0 {
+            let nominal_fwd = self.params.get(ParamEnum::NominalPosOutput, 0);
+            let nominal_rev = self.params.get(ParamEnum::NominalNegOutput, 0);
+            output = if output > 0.0 {
+                output.max(nominal_fwd)
+            } else {
+                output.min(nominal_rev)
+            };
+        }
+        let peak_fwd = self.params.get(ParamEnum::PeakPosOutput, 0);
+        let peak_rev = self.params.get(ParamEnum::PeakNegOutput, 0);
+        let output = output.max(peak_rev).min(peak_fwd);
+        if output.abs() < self.params.get(ParamEnum::NeutralDeadband, 0) {
+            0.0
+        } else {
+            output
+        }
+    }
+
+    /**
+     * The output before the controller's own clamps, as its primary term and
+     * its auxiliary term (the arbitrary feed forward).  A follower mirrors its
+     * master's, as of the follower's last update.
+     */
+    fn output_terms(&self) -> (f64, f64) {
+        let mode = self.control_mode;
+        let primary = if self.runs_pidf() {
+            self.pidf.output
+        } else if mode == ControlMode::PercentOutput as c_int {
+            self.demand0
+        } else if mode == ControlMode::Follower as c_int {
+            return self.master_output;
+        } else {
+            return (0.0, 0.0);
+        };
+        if self.demand1_type == DemandType::ArbitraryFeedForward as c_int {
+            (primary, self.demand1)
+        } else {
+            (primary, 0.0)
+        }
+    }
+
+    /// The current drawn from the bus, in amps.
+    pub fn supply_current(&self) -> f64 {
+        self.output_current * self.motor_output_percent().abs()
+    }
+
+    /// The raw value of the sensor selected for `pid_idx`, before the offset is applied.
+    fn raw_selected_sensor(&self, pid_idx: c_int) -> (f64, f64) {
+        let device = self.params.get(ParamEnum::FeedbackSensorType, pid_idx) as c_int;
+        let (pos, vel) = if device == FeedbackDevice::QuadEncoder as c_int {
+            (self.quadrature_position, self.quadrature_velocity)
+        } else if device == FeedbackDevice::Analog as c_int {
+            (self.analog_position, self.analog_velocity)
+        } else if device == FeedbackDevice::PulseWidthEncodedPosition as c_int {
+            (self.pulse_width_position, self.pulse_width_velocity)
+        } else if device == FeedbackDevice::RemoteSensor0 as c_int {
+            self.remote_sensor(0)
+        } else if device == FeedbackDevice::RemoteSensor1 as c_int {
+            self.remote_sensor(1)
+        } else {
+            (0, 0)
+        };
+        let phase = if self.sensor_phase { -1.0 } else { 1.0 };
+        let coefficient = self
+            .params
+            .get(ParamEnum::SelectedSensorCoefficient, pid_idx);
+        (
+            f64::from(pos) * phase * coefficient,
+            f64::from(vel) * phase * coefficient,
+        )
+    }
+    /**
+     * The position and velocity of the remote sensor configured at `ordinal`,
+     * read from the simulated device it names, or zero if there is none.
+     *
+     * Pigeon angles are in Phoenix's 8192 units per rotation, and read no
+     * velocity.  A CANifier PWM input reads its pulse width as 4096ths of the
+     * period.  Another controller's selected sensor is as of its last update.
+     */
+    fn remote_sensor(&self, ordinal: c_int) -> (i32, i32) {
+        let source = self.params.get(ParamEnum::RemoteSensorSource, ordinal) as c_int;
+        let id = self.params.get(ParamEnum::RemoteSensorDeviceID, ordinal) as i32;
+        let pigeon_angle = |connection, axis: usize| {
+            pigeon::with_device(connection, |p| {
+                (
+                    (p.yaw_pitch_roll[axis] * PIGEON_UNITS_PER_DEGREE).round() as i32,
+                    0,
+                )
+            })
+        };
+        let reading = match source {
+            // TalonSRX_SelectedSensor
+            1 => {
+                let talon = TALON_SRX_TYPE << 8 | (id & 0xFF);
+                published_lock()
+                    .iter()
+                    .find(|p| p.id24 == talon)
+                    .map(|p| (p.sensor.0 as i32, p.sensor.1 as i32))
+            }
+            // Pigeon_Yaw, Pigeon_Pitch, Pigeon_Roll
+            2..=4 => pigeon_angle(pigeon::Connection::Can(id), (source - 2) as usize),
+            // CANifier_Quadrature
+            5 => canifier::with_device(id, |c| (c.quadrature_position, c.quadrature_velocity)),
+            // CANifier_PWMInput0 to 3
+            6..=9 => canifier::with_device(id, |c| {
+                let [pulse, period] = c.pwm_inputs[(source - 6) as usize];
+                let position = if period > 0.0 {
+                    pulse / period * 4096.0
+                } else {
+                    0.0
+                };
+                (position.round() as i32, 0)
+            }),
+            // GadgeteerPigeon_Yaw, GadgeteerPigeon_Pitch, GadgeteerPigeon_Roll
+            10..=12 => pigeon_angle(pigeon::Connection::Talon(id), (source - 10) as usize),
+            _ => None,
+        };
+        reading.unwrap_or((0, 0))
+    }
+    /// Moves the sensor selected for `pid_idx` so it reads `position` and `velocity`.
+    /// Sensors other than the controller's own can't be moved.
+    fn set_raw_selected_sensor(&mut self, pid_idx: c_int, position: f64, velocity: f64) {
+        let idx = pid_idx as usize & 1;
+        let phase = if self.sensor_phase { -1.0 } else { 1.0 };
+        let scale = phase
+            * self
+                .params
+                .get(ParamEnum::SelectedSensorCoefficient, pid_idx);
+        if scale == 0.0 {
+            return;
+        }
+        let raw_position =
+            ((position - f64::from(self.selected_sensor_offset[idx])) / scale).round() as i32;
+        let raw_velocity = (velocity / scale).round() as i32;
+        let device = self.params.get(ParamEnum::FeedbackSensorType, pid_idx) as c_int;
+        if device == FeedbackDevice::QuadEncoder as c_int {
+            self.quadrature_position = raw_position;
+            self.quadrature_velocity = raw_velocity;
+        } else if device == FeedbackDevice::Analog as c_int {
+            self.analog_position = raw_position;
+            self.analog_velocity = raw_velocity;
+        } else if device == FeedbackDevice::PulseWidthEncodedPosition as c_int {
+            self.pulse_width_position = raw_position;
+            self.pulse_width_velocity = raw_velocity;
+        }
+    }
+    pub fn selected_sensor_position(&self, pid_idx: c_int) -> i32 {
+        let idx = pid_idx as usize & 1;
+        self.raw_selected_sensor(pid_idx).0 as i32 + self.selected_sensor_offset[idx]
+    }
+    pub fn selected_sensor_velocity(&self, pid_idx: c_int) -> i32 {
+        self.raw_selected_sensor(pid_idx).1 as i32
+    }
+
+    /// The closed-loop target for `pid_idx`, or zero if not closed-looping.
+    pub fn closed_loop_target(&self, pid_idx: c_int) -> i32 {
+        let mode = self.control_mode;
+        let closed_loop = mode == ControlMode::Position as c_int
+            || mode == ControlMode::Velocity as c_int
+            || mode == ControlMode::MotionMagic as c_int
+            || mode == ControlMode::Current as c_int;
+        if !closed_loop {
+            0
+        } else if pid_idx == 0 {
+            self.demand0 as i32
+        } else if self.demand1_type == DemandType::AuxPID as c_int {
+            self.demand1 as i32
+        } else {
+            0
+        }
+    }
+    pub fn closed_loop_error(&self, pid_idx: c_int) -> i32 {
+        if pid_idx == 0 && self.runs_pidf() {
+            return self.pidf.error as i32;
+        }
+        let target = self.closed_loop_target(pid_idx);
+        if target == 0 && self.control_mode == ControlMode::Disabled as c_int {
+            return 0;
+        }
+        if pid_idx == 0 && self.control_mode == ControlMode::Velocity as c_int {
+            target - self.selected_sensor_velocity(pid_idx)
+        } else {
+            target - self.selected_sensor_position(pid_idx)
+        }
+    }
+}
+
+static DEVICES: Table<SimMotController> = Table::new();
+
+/// What a controller last published for the others to see.
+#[derive(Debug, Copy, Clone)]
+struct Published {
+    /// The id followers follow it by.
+    id24: i32,
+    /// Its output terms.
+    terms: (f64, f64),
+    /// Its selected sensor's position and velocity.
+    sensor: (f64, f64),
+}
+
+/// Each controller's latest `Published`, so controllers can see each other
+/// without locking `DEVICES` again.
+static PUBLISHED: Mutex<Vec<Published>> = Mutex::new(Vec::new());
+
+fn published_lock() -> MutexGuard<'static, Vec<Published>> {
+    PUBLISHED.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Runs `f` on the simulated controller with the given base arbitration ID
+/// (the device number or'd with the device type, as passed to `c_MotController_Create1`).
+/// Returns `None` if no such controller has been created.
+pub fn with_device<R>(base_arb_id: i32, f: impl FnOnce(&mut SimMotController) -> R) -> Option<R> {
+    DEVICES.find(
+        |d| d.base_arb_id == base_arb_id,
+        |d| {
+            d.update();
+            f(d)
+        },
+    )
+}
+
+/// Runs `f` on every simulated controller, in the order they were created.
+pub fn for_each_device(mut f: impl FnMut(&mut SimMotController)) {
+    DEVICES.for_each(|d| {
+        d.update();
+        f(d)
+    })
+}
+
+/// Runs `f` on the controller behind `handle`, recording the returned error code.
+fn mot(handle: Handle, f: impl FnOnce(&mut SimMotController) -> ErrorCode) -> ErrorCode {
+    call(&DEVICES, handle, |d| {
+        d.update();
+        let err = f(d);
+        d.last_error = err;
+        d.publish();
+        err
+    })
+}
+
+/// Sets a configuration parameter on the controller behind `handle`.
+/// Fails with `SigNotUpdated` if the config frame is dropped.
+fn set_param(handle: Handle, param: ParamEnum, value: f64, ordinal: c_int) -> ErrorCode {
+    mot(handle, |d| {
+        if !can::transmit() {
+            return ErrorCode::SigNotUpdated;
+        }
+        d.params.set(param, value, ordinal);
+        ErrorCode::OK
+    })
+}
+
+/// Reads a value from the controller behind `handle` into `out`, as of when
+/// the status frame was sent; if it was dropped, as of the last one that wasn't.
+unsafe fn get<T>(handle: Handle, out: *mut T, f: impl FnOnce(&SimMotController) -> T) -> ErrorCode {
+    call(&DEVICES, handle, |d| {
+        let err = match can::receive() {
+            Some(sent) => {
+                d.update_to(sent);
+                ErrorCode::OK
+            }
+            None => ErrorCode::CAN_MSG_STALE,
+        };
+        put(out, f(d));
+        d.last_error = err;
+        err
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn c_MotController_Create1(baseArbId: c_int) -> Handle {
+    DEVICES.get_or_create(
+        |d| d.base_arb_id == baseArbId,
+        || SimMotController::new(baseArbId),
+    )
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_MotController_GetDeviceNumber(
+    handle: Handle,
+    deviceNumber: *mut c_int,
+) -> ErrorCode {
+    get(handle, deviceNumber, |d| d.device_number())
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_MotController_GetDescription(
+    handle: Handle,
+    toFill: *mut c_char,
+    toFillByteSz: c_int,
+    numBytesFilled: *mut c_int,
+) -> ErrorCode {
+    mot(handle, |d| {
+        let kind = if d.base_arb_id & 0x02040000 == 0x02040000 {
+            "Talon SRX"
+        } else {
+            "Victor SPX"
+        };
+        let desc = format!("{} {}", kind, d.device_number());
+        put_description(&desc, toFill, toFillByteSz, numBytesFilled);
+        ErrorCode::OK
+    })
+}
+#[no_mangle]
+pub extern "C" fn c_MotController_SetDemand(
+    handle: Handle,
+    mode: c_int,
+    demand0: c_int,
+    demand1: c_int,
+) -> ErrorCode {
+    mot(handle, |d| {
+        d.control_mode = mode;
+        // current mode demands are in milliamps
+        d.demand0 = if mode == ControlMode::Current as c_int {
+            f64::from(demand0) / 1000.0
+        } else {
+            f64::from(demand0)
+        };
+        d.demand1 = f64::from(demand1);
+        d.demand1_type = DemandType::Neutral as _;
+        d.demand_changed();
+        ErrorCode::OK
+    })
+}
+#[no_mangle]
+pub extern "C" fn c_MotController_Set_4(
+    handle: Handle,
+    mode: c_int,
+    demand0: f64,
+    demand1: f64,
+    demand1Type: c_int,
+) -> ErrorCode {
+    mot(handle, |d| {
+        d.control_mode = mode;
+        d.demand0 = demand0;
+        d.demand1 = demand1;
+        d.demand1_type = demand1Type;
+        d.demand_changed();
+        ErrorCode::OK
+    })
+}
+#[no_mangle]
+pub extern "C" fn c_MotController_SetNeutralMode(handle: Handle, neutralMode: c_int) {
+    DEVICES.with(handle, |d| d.neutral_mode = neutralMode);
+}
+#[no_mangle]
+pub extern "C" fn c_MotController_SetSensorPhase(handle: Handle, PhaseSensor: bool) {
+    DEVICES.with(handle, |d| d.sensor_phase = PhaseSensor);
+}
+#[no_mangle]
+pub extern "C" fn c_MotController_SetInverted(handle: Handle, invert: bool) {
+    DEVICES.with(handle, |d| d.inverted = invert);
+}
+#[no_mangle]
+pub extern "C" fn c_MotController_ConfigOpenLoopRamp(
+    handle: Handle,
+    secondsFromNeutralToFull: f64,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    set_param(handle, ParamEnum::OpenloopRamp, secondsFromNeutralToFull, 0)
+}
+#[no_mangle]
+pub extern "C" fn c_MotController_ConfigClosedLoopRamp(
+    handle: Handle,
+    secondsFromNeutralToFull: f64,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    set_param(
+        handle,
+        ParamEnum::ClosedloopRamp,
+        secondsFromNeutralToFull,
+        0,
+    )
+}
+#[no_mangle]
+pub extern "C" fn c_MotController_ConfigPeakOutputForward(
+    handle: Handle,
+    percentOut: f64,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    set_param(handle, ParamEnum::PeakPosOutput, percentOut, 0)
+}
+#[no_mangle]
+pub extern "C" fn c_MotController_ConfigPeakOutputReverse(
+    handle: Handle,
+    percentOut: f64,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    set_param(handle, ParamEnum::PeakNegOutput, percentOut, 0)
+}
+#[no_mangle]
+pub extern "C" fn c_MotController_ConfigNominalOutputForward(
+    handle: Handle,
+    percentOut: f64,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    set_param(handle, ParamEnum::NominalPosOutput, percentOut, 0)
+}
+#[no_mangle]
+pub extern "C" fn c_MotController_ConfigNominalOutputReverse(
+    handle: Handle,
+    percentOut: f64,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    set_param(handle, ParamEnum::NominalNegOutput, percentOut, 0)
+}
+#[no_mangle]
+pub extern "C" fn c_MotController_ConfigNeutralDeadband(
+    handle: Handle,
+    percentDeadband: f64,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    set_param(handle, ParamEnum::NeutralDeadband, percentDeadband, 0)
+}
+#[no_mangle]
+pub extern "C" fn c_MotController_ConfigVoltageCompSaturation(
+    handle: Handle,
+    voltage: f64,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    set_param(handle, ParamEnum::NominalBatteryVoltage, voltage, 0)
+}
+#[no_mangle]
+pub extern "C" fn c_MotController_ConfigVoltageMeasurementFilter(
+    handle: Handle,
+    filterWindowSamples: c_int,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    set_param(
+        handle,
+        ParamEnum::BatteryVoltageFilterSize,
+        f64::from(filterWindowSamples),
+        0,
+    )
+}
+#[no_mangle]
+pub extern "C" fn c_MotController_EnableVoltageCompensation(handle: Handle, enable: bool) {
+    DEVICES.with(handle, |d| d.voltage_compensation = enable);
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_MotController_GetBusVoltage(
+    handle: Handle,
+    voltage: *mut f64,
+) -> ErrorCode {
+    get(handle, voltage, |d| d.bus_voltage)
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_MotController_GetMotorOutputPercent(
+    handle: Handle,
+    percentOutput: *mut f64,
+) -> ErrorCode {
+    get(handle, percentOutput, |d| d.motor_output_percent())
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_MotController_GetOutputCurrent(
+    handle: Handle,
+    current: *mut f64,
+) -> ErrorCode {
+    get(handle, current, |d| d.output_current)
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_MotController_GetTemperature(
+    handle: Handle,
+    temperature: *mut f64,
+) -> ErrorCode {
+    get(handle, temperature, |d| d.temperature)
+}
+#[no_mangle]
+pub extern "C" fn c_MotController_ConfigSelectedFeedbackSensor(
+    handle: Handle,
+    feedbackDevice: c_int,
+    pidIdx: c_int,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    set_param(
+        handle,
+        ParamEnum::FeedbackSensorType,
+        f64::from(feedbackDevice),
+        pidIdx,
+    )
+}
+#[no_mangle]
+pub extern "C" fn c_MotController_ConfigSelectedFeedbackCoefficient(
+    handle: Handle,
+    coefficient: f64,
+    pidIdx: c_int,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    set_param(
+        handle,
+        ParamEnum::SelectedSensorCoefficient,
+        coefficient,
+        pidIdx,
+    )
+}
+#[no_mangle]
+pub extern "C" fn c_MotController_ConfigRemoteFeedbackFilter(
+    handle: Handle,
+    deviceID: c_int,
+    remoteSensorSource: c_int,
+    remoteOrdinal: c_int,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    mot(handle, |d| {
+        d.params.set(
+            ParamEnum::RemoteSensorSource,
+            f64::from(remoteSensorSource),
+            remoteOrdinal,
+        );
+        d.params.set(
+            ParamEnum::RemoteSensorDeviceID,
+            f64::from(deviceID),
+            remoteOrdinal,
+        );
+        ErrorCode::OK
+    })
+}
+#[no_mangle]
+pub extern "C" fn c_MotController_ConfigSensorTerm(
+    handle: Handle,
+    sensorTerm: c_int,
+    feedbackDevice: c_int,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    set_param(
+        handle,
+        ParamEnum::SensorTerm,
+        f64::from(feedbackDevice),
+        sensorTerm,
+    )
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_MotController_GetSelectedSensorPosition(
+    handle: Handle,
+    param: *mut c_int,
+    pidIdx: c_int,
+) -> ErrorCode {
+    get(handle, param, |d| d.selected_sensor_position(pidIdx))
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_MotController_GetSelectedSensorVelocity(
+    handle: Handle,
+    param: *mut c_int,
+    pidIdx: c_int,
+) -> ErrorCode {
+    get(handle, param, |d| d.selected_sensor_velocity(pidIdx))
+}
+#[no_mangle]
+pub extern "C" fn c_MotController_SetSelectedSensorPosition(
+    handle: Handle,
+    sensorPos: c_int,
+    pidIdx: c_int,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    mot(handle, |d| {
+        let idx = pidIdx as usize & 1;
+        let raw = d.raw_selected_sensor(pidIdx).0 as i32;
+        let delta = sensorPos - raw - d.selected_sensor_offset[idx];
+        d.selected_sensor_offset[idx] = sensorPos - raw;
+        if let (0, Some(trajectory)) = (idx, &mut d.motion_magic) {
+            trajectory.shift(f64::from(delta));
+        }
+        ErrorCode::OK
+    })
+}
+#[no_mangle]
+pub extern "C" fn c_MotController_SetControlFramePeriod(
+    handle: Handle,
+    frame: c_int,
+    periodMs: c_int,
+) -> ErrorCode {
+    mot(handle, |d| {
+        d.control_frame_periods.insert(frame, periodMs);
+        ErrorCode::OK
+    })
+}
+#[no_mangle]
+pub extern "C" fn c_MotController_SetStatusFramePeriod(
+    handle: Handle,
+    frame: c_int,
+    periodMs: c_int,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    mot(handle, |d| {
+        d.status_frame_periods.insert(frame, periodMs);
+        ErrorCode::OK
+    })
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_MotController_GetStatusFramePeriod(
+    handle: Handle,
+    frame: c_int,
+    periodMs: *mut c_int,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    get(handle, periodMs, |d| {
+        match d.status_frame_periods.get(&frame) {
+            Some(&period) => period,
+            // Status_1_General and Status_2_Feedback0 are fast by default
+            None if frame == 5120 => 10,
+            None if frame == 5184 => 20,
+            None => 160,
+        }
+    })
+}
+#[no_mangle]
+pub extern "C" fn c_MotController_ConfigVelocityMeasurementPeriod(
+    handle: Handle,
+    period: c_int,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    set_param(
+        handle,
+        ParamEnum::SampleVelocityPeriod,
+        f64::from(period),
+        0,
+    )
+}
+#[no_mangle]
+pub extern "C" fn c_MotController_ConfigVelocityMeasurementWindow(
+    handle: Handle,
+    windowSize: c_int,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    set_param(
+        handle,
+        ParamEnum::SampleVelocityWindow,
+        f64::from(windowSize),
+        0,
+    )
+}
+
+fn config_limit_switch_source(
+    handle: Handle,
+    ordinal: c_int,
+    type_: c_int,
+    normal_open_or_close: c_int,
+    device_id: c_int,
+) -> ErrorCode {
+    mot(handle, |d| {
+        d.params
+            .set(ParamEnum::LimitSwitchSource, f64::from(type_), ordinal);
+        d.params.set(
+            ParamEnum::LimitSwitchNormClosedAndDis,
+            f64::from(normal_open_or_close),
+            ordinal,
+        );
+        d.params.set(
+            ParamEnum::LimitSwitchRemoteDevID,
+            f64::from(device_id),
+            ordinal,
+        );
+        ErrorCode::OK
+    })
+}
+#[no_mangle]
+pub extern "C" fn c_MotController_ConfigForwardLimitSwitchSource(
+    handle: Handle,
+    type_: c_int,
+    normalOpenOrClose: c_int,
+    deviceID: c_int,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    config_limit_switch_source(handle, 0, type_, normalOpenOrClose, deviceID)
+}
+#[no_mangle]
+pub extern "C" fn c_MotController_ConfigReverseLimitSwitchSource(
+    handle: Handle,
+    type_: c_int,
+    normalOpenOrClose: c_int,
+    deviceID: c_int,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    config_limit_switch_source(handle, 1, type_, normalOpenOrClose, deviceID)
+}
+#[no_mangle]
+pub extern "C" fn c_MotController_OverrideLimitSwitchesEnable(handle: Handle, enable: bool) {
+    DEVICES.with(handle, |d| d.limit_switch_override = enable);
+}
+#[no_mangle]
+pub extern "C" fn c_MotController_ConfigForwardSoftLimitThreshold(
+    handle: Handle,
+    forwardSensorLimit: c_int,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    set_param(
+        handle,
+        ParamEnum::ForwardSoftLimitThreshold,
+        f64::from(forwardSensorLimit),
+        0,
+    )
+}
+#[no_mangle]
+pub extern "C" fn c_MotController_ConfigReverseSoftLimitThreshold(
+    handle: Handle,
+    reverseSensorLimit: c_int,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    set_param(
+        handle,
+        ParamEnum::ReverseSoftLimitThreshold,
+        f64::from(reverseSensorLimit),
+        0,
+    )
+}
+#[no_mangle]
+pub extern "C" fn c_MotController_ConfigForwardSoftLimitEnable(
+    handle: Handle,
+    enable: bool,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    set_param(
+        handle,
+        ParamEnum::ForwardSoftLimitEnable,
+        enable as u8 as f64,
+        0,
+    )
+}
+#[no_mangle]
+pub extern "C" fn c_MotController_ConfigReverseSoftLimitEnable(
+    handle: Handle,
+    enable: bool,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    set_param(
+        handle,
+        ParamEnum::ReverseSoftLimitEnable,
+        enable as u8 as f64,
+        0,
+    )
+}
+#[no_mangle]
+pub extern "C" fn c_MotController_OverrideSoftLimitsEnable(handle: Handle, enable: bool) {
+    DEVICES.with(handle, |d| d.soft_limit_override = enable);
+}
+#[no_mangle]
+pub extern "C" fn c_MotController_Config_kP(
+    handle: Handle,
+    slotIdx: c_int,
+    value: f64,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    set_param(handle, ParamEnum::ProfileParamSlot_P, value, slotIdx)
+}
+#[no_mangle]
+pub extern "C" fn c_MotController_Config_kI(
+    handle: Handle,
+    slotIdx: c_int,
+    value: f64,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    set_param(handle, ParamEnum::ProfileParamSlot_I, value, slotIdx)
+}
+#[no_mangle]
+pub extern "C" fn c_MotController_Config_kD(
+    handle: Handle,
+    slotIdx: c_int,
+    value: f64,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    set_param(handle, ParamEnum::ProfileParamSlot_D, value, slotIdx)
+}
+#[no_mangle]
+pub extern "C" fn c_MotController_Config_kF(
+    handle: Handle,
+    slotIdx: c_int,
+    value: f64,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    set_param(handle, ParamEnum::ProfileParamSlot_F, value, slotIdx)
+}
+#[no_mangle]
+pub extern "C" fn c_MotController_Config_IntegralZone(
+    handle: Handle,
+    slotIdx: c_int,
+    izone: f64,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    set_param(handle, ParamEnum::ProfileParamSlot_IZone, izone, slotIdx)
+}
+#[no_mangle]
+pub extern "C" fn c_MotController_ConfigAllowableClosedloopError(
+    handle: Handle,
+    slotIdx: c_int,
+    allowableClosedLoopError: c_int,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    set_param(
+        handle,
+        ParamEnum::ProfileParamSlot_AllowableErr,
+        f64::from(allowableClosedLoopError),
+        slotIdx,
+    )
+}
+#[no_mangle]
+pub extern "C" fn c_MotController_ConfigMaxIntegralAccumulator(
+    handle: Handle,
+    slotIdx: c_int,
+    iaccum: f64,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    set_param(
+        handle,
+        ParamEnum::ProfileParamSlot_MaxIAccum,
+        iaccum,
+        slotIdx,
+    )
+}
+#[no_mangle]
+pub extern "C" fn c_MotController_ConfigClosedLoopPeakOutput(
+    handle: Handle,
+    slotIdx: c_int,
+    percentOut: f64,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    set_param(
+        handle,
+        ParamEnum::ProfileParamSlot_PeakOutput,
+        percentOut,
+        slotIdx,
+    )
+}
+#[no_mangle]
+pub extern "C" fn c_MotController_ConfigClosedLoopPeriod(
+    handle: Handle,
+    slotIdx: c_int,
+    loopTimeMs: c_int,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    set_param(
+        handle,
+        ParamEnum::PIDLoopPeriod,
+        f64::from(loopTimeMs),
+        slotIdx,
+    )
+}
+#[no_mangle]
+pub extern "C" fn c_MotController_SetIntegralAccumulator(
+    handle: Handle,
+    iaccum: f64,
+    pidIdx: c_int,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    mot(handle, |d| {
+        d.integral_accumulator[pidIdx as usize & 1] = iaccum;
+        ErrorCode::OK
+    })
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_MotController_GetClosedLoopError(
+    handle: Handle,
+    closedLoopError: *mut c_int,
+    pidIdx: c_int,
+) -> ErrorCode {
+    get(handle, closedLoopError, |d| d.closed_loop_error(pidIdx))
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_MotController_GetIntegralAccumulator(
+    handle: Handle,
+    iaccum: *mut f64,
+    pidIdx: c_int,
+) -> ErrorCode {
+    get(handle, iaccum, |d| {
+        d.integral_accumulator[pidIdx as usize & 1]
+    })
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_MotController_GetErrorDerivative(
+    handle: Handle,
+    derror: *mut f64,
+    pidIdx: c_int,
+) -> ErrorCode {
+    get(handle, derror, |d| {
+        if pidIdx == 0 && d.runs_pidf() {
+            d.pidf.derivative
+        } else {
+            0.0
+        }
+    })
+}
+#[no_mangle]
+pub extern "C" fn c_MotController_SelectProfileSlot(
+    handle: Handle,
+    slotIdx: c_int,
+    pidIdx: c_int,
+) -> ErrorCode {
+    mot(handle, |d| {
+        d.profile_slot[pidIdx as usize & 1] = slotIdx;
+        ErrorCode::OK
+    })
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_MotController_GetActiveTrajectoryPosition(
+    handle: Handle,
+    param: *mut c_int,
+) -> ErrorCode {
+    get(handle, param, |d| match d.motion_magic {
+        Some(ref trajectory) => trajectory.position() as i32,
+        None => d.closed_loop_target(0),
+    })
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_MotController_GetActiveTrajectoryVelocity(
+    handle: Handle,
+    param: *mut c_int,
+) -> ErrorCode {
+    get(handle, param, |d| {
+        d.motion_magic
+            .as_ref()
+            .map_or(0, |trajectory| trajectory.velocity() as i32)
+    })
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_MotController_GetActiveTrajectoryHeading(
+    handle: Handle,
+    param: *mut f64,
+) -> ErrorCode {
+    get(handle, param, |d| f64::from(d.closed_loop_target(1)))
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_MotController_GetActiveTrajectoryAll(
+    handle: Handle,
+    vel: *mut c_int,
+    pos: *mut c_int,
+    heading: *mut f64,
+) -> ErrorCode {
+    mot(handle, |d| {
+        put(vel, 0);
+        put(pos, d.closed_loop_target(0));
+        put(heading, f64::from(d.closed_loop_target(1)));
+        ErrorCode::OK
+    })
+}
+#[no_mangle]
+pub extern "C" fn c_MotController_ConfigMotionCruiseVelocity(
+    handle: Handle,
+    sensorUnitsPer100ms: c_int,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    set_param(
+        handle,
+        ParamEnum::MotMag_VelCruise,
+        f64::from(sensorUnitsPer100ms),
+        0,
+    )
+}
+#[no_mangle]
+pub extern "C" fn c_MotController_ConfigMotionAcceleration(
+    handle: Handle,
+    sensorUnitsPer100msPerSec: c_int,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    set_param(
+        handle,
+        ParamEnum::MotMag_Accel,
+        f64::from(sensorUnitsPer100msPerSec),
+        0,
+    )
+}
+#[no_mangle]
+pub extern "C" fn c_MotController_ClearMotionProfileTrajectories(handle: Handle) -> ErrorCode {
+    mot(handle, |d| {
+        d.top_buffer.clear();
+        d.bottom_buffer.clear();
+        ErrorCode::OK
+    })
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_MotController_GetMotionProfileTopLevelBufferCount(
+    handle: Handle,
+    value: *mut c_int,
+) -> ErrorCode {
+    get(handle, value, |d| d.top_buffer.len() as c_int)
+}
+
+fn push_point(handle: Handle, point: BufferedPoint) -> ErrorCode {
+    mot(handle, |d| {
+        if d.top_buffer.len() >= TOP_BUFFER_CAPACITY {
+            return ErrorCode::BufferFull;
+        }
+        d.top_buffer.push_back(point);
+        ErrorCode::OK
+    })
+}
+#[no_mangle]
+pub extern "C" fn c_MotController_PushMotionProfileTrajectory(
+    handle: Handle,
+    position: f64,
+    velocity: f64,
+    headingDeg: f64,
+    profileSlotSelect: c_int,
+    isLastPoint: bool,
+    zeroPos: bool,
+) -> ErrorCode {
+    push_point(
+        handle,
+        BufferedPoint {
+            position,
+            velocity,
+            auxiliary_pos: headingDeg,
+            profile_slot_select_0: profileSlotSelect,
+            profile_slot_select_1: 0,
+            is_last_point: isLastPoint,
+            zero_pos: zeroPos,
+            duration_ms: 0,
+        },
+    )
+}
+#[no_mangle]
+pub extern "C" fn c_MotController_PushMotionProfileTrajectory_2(
+    handle: Handle,
+    position: f64,
+    velocity: f64,
+    headingDeg: f64,
+    profileSlotSelect0: c_int,
+    profileSlotSelect1: c_int,
+    isLastPoint: bool,
+    zeroPos: bool,
+    durationMs: c_int,
+) -> ErrorCode {
+    push_point(
+        handle,
+        BufferedPoint {
+            position,
+            velocity,
+            auxiliary_pos: headingDeg,
+            profile_slot_select_0: profileSlotSelect0,
+            profile_slot_select_1: profileSlotSelect1,
+            is_last_point: isLastPoint,
+            zero_pos: zeroPos,
+            duration_ms: durationMs,
+        },
+    )
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_MotController_IsMotionProfileTopLevelBufferFull(
+    handle: Handle,
+    value: *mut bool,
+) -> ErrorCode {
+    get(handle, value, |d| d.top_buffer.len() >= TOP_BUFFER_CAPACITY)
+}
+#[no_mangle]
+pub extern "C" fn c_MotController_ProcessMotionProfileBuffer(handle: Handle) -> ErrorCode {
+    mot(handle, |d| {
+        while d.bottom_buffer.len() < BOTTOM_BUFFER_CAPACITY {
+            match d.top_buffer.pop_front() {
+                Some(point) => d.bottom_buffer.push_back(point),
+                None => break,
+            }
+        }
+        ErrorCode::OK
+    })
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_MotController_GetMotionProfileStatus(
+    handle: Handle,
+    topBufferRem: *mut c_int,
+    topBufferCnt: *mut c_int,
+    btmBufferCnt: *mut c_int,
+    hasUnderrun: *mut bool,
+    isUnderrun: *mut bool,
+    activePointValid: *mut bool,
+    isLast: *mut bool,
+    profileSlotSelect: *mut c_int,
+    outputEnable: *mut c_int,
+) -> ErrorCode {
+    c_MotController_GetMotionProfileStatus_2(
+        handle,
+        topBufferRem,
+        topBufferCnt,
+        btmBufferCnt,
+        hasUnderrun,
+        isUnderrun,
+        activePointValid,
+        isLast,
+        profileSlotSelect,
+        outputEnable,
+        std::ptr::null_mut(),
+        std::ptr::null_mut(),
+    )
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_MotController_GetMotionProfileStatus_2(
+    handle: Handle,
+    topBufferRem: *mut c_int,
+    topBufferCnt: *mut c_int,
+    btmBufferCnt: *mut c_int,
+    hasUnderrun: *mut bool,
+    isUnderrun: *mut bool,
+    activePointValid: *mut bool,
+    isLast: *mut bool,
+    profileSlotSelect: *mut c_int,
+    outputEnable: *mut c_int,
+    timeDurMs: *mut c_int,
+    profileSlotSelect1: *mut c_int,
+) -> ErrorCode {
+    mot(handle, |d| {
+        let active = d.bottom_buffer.front();
+        put(
+            topBufferRem,
+            (TOP_BUFFER_CAPACITY - d.top_buffer.len()) as c_int,
+        );
+        put(topBufferCnt, d.top_buffer.len() as c_int);
+        put(btmBufferCnt, d.bottom_buffer.len() as c_int);
+        put(hasUnderrun, d.has_underrun);
+        put(isUnderrun, false);
+        put(activePointValid, active.is_some());
+        put(isLast, active.is_some_and(|p| p.is_last_point));
+        put(profileSlotSelect, d.profile_slot[0]);
+        put(
+            outputEnable,
+            if d.control_mode == ControlMode::MotionProfile as c_int
+                || d.control_mode == ControlMode::MotionProfileArc as c_int
+            {
+                d.demand0 as c_int
+            } else {
+                0
+            },
+        );
+        put(timeDurMs, active.map_or(0, |p| p.duration_ms));
+        put(profileSlotSelect1, d.profile_slot[1]);
+        ErrorCode::OK
+    })
+}
+#[no_mangle]
+pub extern "C" fn c_MotController_ClearMotionProfileHasUnderrun(
+    handle: Handle,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    mot(handle, |d| {
+        d.has_underrun = false;
+        ErrorCode::OK
+    })
+}
+#[no_mangle]
+pub extern "C" fn c_MotController_ChangeMotionControlFramePeriod(
+    handle: Handle,
+    periodMs: c_int,
+) -> ErrorCode {
+    // Control_6_MotProfAddTrajPoint
+    c_MotController_SetControlFramePeriod(handle, 262464, periodMs)
+}
+#[no_mangle]
+pub extern "C" fn c_MotController_ConfigMotionProfileTrajectoryPeriod(
+    handle: Handle,
+    durationMs: c_int,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    set_param(
+        handle,
+        ParamEnum::MotionProfileTrajectoryPointDurationMs,
+        f64::from(durationMs),
+        0,
+    )
+}
+#[no_mangle]
+pub extern "C" fn c_MotController_GetLastError(handle: Handle) -> ErrorCode {
+    DEVICES
+        .with(handle, |d| d.last_error)
+        .unwrap_or(ErrorCode::InvalidHandle)
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_MotController_GetFirmwareVersion(
+    handle: Handle,
+    arg1: *mut c_int,
+) -> ErrorCode {
+    get(handle, arg1, |d| d.firmware_version)
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_MotController_HasResetOccurred(
+    handle: Handle,
+    arg1: *mut bool,
+) -> ErrorCode {
+    mot(handle, |d| {
+        put(arg1, d.has_reset_occurred);
+        d.has_reset_occurred = false;
+        ErrorCode::OK
+    })
+}
+#[no_mangle]
+pub extern "C" fn c_MotController_ConfigSetCustomParam(
+    handle: Handle,
+    newValue: c_int,
+    paramIndex: c_int,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    set_param(
+        handle,
+        ParamEnum::CustomParam,
+        f64::from(newValue),
+        paramIndex,
+    )
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_MotController_ConfigGetCustomParam(
+    handle: Handle,
+    readValue: *mut c_int,
+    paramIndex: c_int,
+    _timoutMs: c_int,
+) -> ErrorCode {
+    get(handle, readValue, |d| {
+        d.params.get(ParamEnum::CustomParam, paramIndex) as c_int
+    })
+}
+#[no_mangle]
+pub extern "C" fn c_MotController_ConfigSetParameter(
+    handle: Handle,
+    param: c_int,
+    value: f64,
+    _subValue: c_int,
+    ordinal: c_int,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    mot(handle, |d| {
+        if !can::transmit() {
+            return ErrorCode::SigNotUpdated;
+        }
+        d.params.set_raw(param, value, ordinal);
+        ErrorCode::OK
+    })
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_MotController_ConfigGetParameter(
+    handle: Handle,
+    param: c_int,
+    value: *mut f64,
+    ordinal: c_int,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    get(handle, value, |d| d.params.get_raw(param, ordinal))
+}
+#[no_mangle]
+pub extern "C" fn c_MotController_ConfigPeakCurrentLimit(
+    handle: Handle,
+    amps: c_int,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    set_param(handle, ParamEnum::PeakCurrentLimitAmps, f64::from(amps), 0)
+}
+#[no_mangle]
+pub extern "C" fn c_MotController_ConfigPeakCurrentDuration(
+    handle: Handle,
+    milliseconds: c_int,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    set_param(
+        handle,
+        ParamEnum::PeakCurrentLimitMs,
+        f64::from(milliseconds),
+        0,
+    )
+}
+#[no_mangle]
+pub extern "C" fn c_MotController_ConfigContinuousCurrentLimit(
+    handle: Handle,
+    amps: c_int,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    set_param(
+        handle,
+        ParamEnum::ContinuousCurrentLimitAmps,
+        f64::from(amps),
+        0,
+    )
+}
+#[no_mangle]
+pub extern "C" fn c_MotController_EnableCurrentLimit(handle: Handle, enable: bool) -> ErrorCode {
+    mot(handle, |d| {
+        d.current_limit = enable;
+        ErrorCode::OK
+    })
+}
+#[no_mangle]
+pub extern "C" fn c_MotController_SetLastError(handle: Handle, error: c_int) -> ErrorCode {
+    call(&DEVICES, handle, |d| {
+        d.last_error = if error == 0 {
+            ErrorCode::OK
+        } else {
+            ErrorCode::GeneralError
+        };
+        ErrorCode::OK
+    })
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_MotController_GetAnalogIn(
+    handle: Handle,
+    param: *mut c_int,
+) -> ErrorCode {
+    get(handle, param, |d| d.analog_position)
+}
+#[no_mangle]
+pub extern "C" fn c_MotController_SetAnalogPosition(
+    handle: Handle,
+    newPosition: c_int,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    mot(handle, |d| {
+        d.analog_position = newPosition;
+        ErrorCode::OK
+    })
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_MotController_GetAnalogInRaw(
+    handle: Handle,
+    param: *mut c_int,
+) -> ErrorCode {
+    get(handle, param, |d| d.analog_position & 0x3FF)
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_MotController_GetAnalogInVel(
+    handle: Handle,
+    param: *mut c_int,
+) -> ErrorCode {
+    get(handle, param, |d| d.analog_velocity)
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_MotController_GetQuadraturePosition(
+    handle: Handle,
+    param: *mut c_int,
+) -> ErrorCode {
+    get(handle, param, |d| d.quadrature_position)
+}
+#[no_mangle]
+pub extern "C" fn c_MotController_SetQuadraturePosition(
+    handle: Handle,
+    newPosition: c_int,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    mot(handle, |d| {
+        d.quadrature_position = newPosition;
+        ErrorCode::OK
+    })
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_MotController_GetQuadratureVelocity(
+    handle: Handle,
+    param: *mut c_int,
+) -> ErrorCode {
+    get(handle, param, |d| d.quadrature_velocity)
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_MotController_GetPulseWidthPosition(
+    handle: Handle,
+    param: *mut c_int,
+) -> ErrorCode {
+    get(handle, param, |d| d.pulse_width_position)
+}
+#[no_mangle]
+pub extern "C" fn c_MotController_SetPulseWidthPosition(
+    handle: Handle,
+    newPosition: c_int,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    mot(handle, |d| {
+        d.pulse_width_position = newPosition;
+        ErrorCode::OK
+    })
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_MotController_GetPulseWidthVelocity(
+    handle: Handle,
+    param: *mut c_int,
+) -> ErrorCode {
+    get(handle, param, |d| d.pulse_width_velocity)
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_MotController_GetPulseWidthRiseToFallUs(
+    handle: Handle,
+    param: *mut c_int,
+) -> ErrorCode {
+    get(handle, param, |d| d.pulse_width_rise_to_fall_us)
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_MotController_GetPulseWidthRiseToRiseUs(
+    handle: Handle,
+    param: *mut c_int,
+) -> ErrorCode {
+    get(handle, param, |d| d.pulse_width_rise_to_rise_us)
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_MotController_GetPinStateQuadA(
+    handle: Handle,
+    param: *mut c_int,
+) -> ErrorCode {
+    get(handle, param, |d| d.quad_a as c_int)
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_MotController_GetPinStateQuadB(
+    handle: Handle,
+    param: *mut c_int,
+) -> ErrorCode {
+    get(handle, param, |d| d.quad_b as c_int)
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_MotController_GetPinStateQuadIdx(
+    handle: Handle,
+    param: *mut c_int,
+) -> ErrorCode {
+    get(handle, param, |d| d.quad_idx as c_int)
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_MotController_IsFwdLimitSwitchClosed(
+    handle: Handle,
+    param: *mut c_int,
+) -> ErrorCode {
+    get(handle, param, |d| d.fwd_limit_switch_closed as c_int)
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_MotController_IsRevLimitSwitchClosed(
+    handle: Handle,
+    param: *mut c_int,
+) -> ErrorCode {
+    get(handle, param, |d| d.rev_limit_switch_closed as c_int)
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_MotController_GetFaults(handle: Handle, param: *mut c_int) -> ErrorCode {
+    get(handle, param, |d| d.faults)
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_MotController_GetStickyFaults(
+    handle: Handle,
+    param: *mut c_int,
+) -> ErrorCode {
+    get(handle, param, |d| d.sticky_faults)
+}
+#[no_mangle]
+pub extern "C" fn c_MotController_ClearStickyFaults(
+    handle: Handle,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    mot(handle, |d| {
+        d.sticky_faults = 0;
+        ErrorCode::OK
+    })
+}
+#[no_mangle]
+pub extern "C" fn c_MotController_SelectDemandType(handle: Handle, _enable: bool) -> ErrorCode {
+    mot(handle, |_| ErrorCode::OK)
+}
+#[no_mangle]
+pub extern "C" fn c_MotController_SetMPEOutput(handle: Handle, _MpeOutput: c_int) -> ErrorCode {
+    mot(handle, |_| ErrorCode::OK)
+}
+#[no_mangle]
+pub extern "C" fn c_MotController_EnableHeadingHold(handle: Handle, _enable: bool) -> ErrorCode {
+    mot(handle, |_| ErrorCode::OK)
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_MotController_GetAnalogInAll(
+    handle: Handle,
+    withOv: *mut c_int,
+    raw: *mut c_int,
+    vel: *mut c_int,
+) -> ErrorCode {
+    mot(handle, |d| {
+        put(withOv, d.analog_position);
+        put(raw, d.analog_position & 0x3FF);
+        put(vel, d.analog_velocity);
+        ErrorCode::OK
+    })
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_MotController_GetQuadratureSensor(
+    handle: Handle,
+    pos: *mut c_int,
+    vel: *mut c_int,
+) -> ErrorCode {
+    mot(handle, |d| {
+        put(pos, d.quadrature_position);
+        put(vel, d.quadrature_velocity);
+        ErrorCode::OK
+    })
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_MotController_GetPulseWidthAll(
+    handle: Handle,
+    pos: *mut c_int,
+    vel: *mut c_int,
+    riseToRiseUs: *mut c_int,
+    riseToFallUs: *mut c_int,
+) -> ErrorCode {
+    mot(handle, |d| {
+        put(pos, d.pulse_width_position);
+        put(vel, d.pulse_width_velocity);
+        put(riseToRiseUs, d.pulse_width_rise_to_rise_us);
+        put(riseToFallUs, d.pulse_width_rise_to_fall_us);
+        ErrorCode::OK
+    })
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_MotController_GetQuadPinStates(
+    handle: Handle,
+    quadA: *mut c_int,
+    quadB: *mut c_int,
+    quadIdx: *mut c_int,
+) -> ErrorCode {
+    mot(handle, |d| {
+        put(quadA, d.quad_a as c_int);
+        put(quadB, d.quad_b as c_int);
+        put(quadIdx, d.quad_idx as c_int);
+        ErrorCode::OK
+    })
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_MotController_GetLimitSwitchState(
+    handle: Handle,
+    isFwdClosed: *mut c_int,
+    isRevClosed: *mut c_int,
+) -> ErrorCode {
+    mot(handle, |d| {
+        put(isFwdClosed, d.fwd_limit_switch_closed as c_int);
+        put(isRevClosed, d.rev_limit_switch_closed as c_int);
+        ErrorCode::OK
+    })
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_MotController_GetClosedLoopTarget(
+    handle: Handle,
+    value: *mut c_int,
+    pidIdx: c_int,
+) -> ErrorCode {
+    get(handle, value, |d| d.closed_loop_target(pidIdx))
+}
+
+#[cfg(feature = "phoenix-2019")]
+#[no_mangle]
+pub extern "C" fn c_MotController_ConfigFactoryDefault(
+    handle: Handle,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    mot(handle, |d| {
+        d.params.clear();
+        ErrorCode::OK
+    })
+}
+#[cfg(feature = "phoenix-2019")]
+#[no_mangle]
+pub extern "C" fn c_MotController_ConfigFeedbackNotContinuous(
+    handle: Handle,
+    feedbackNotContinuous: bool,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    set_param(
+        handle,
+        ParamEnum::FeedbackNotContinuous,
+        feedbackNotContinuous as u8 as f64,
+        0,
+    )
+}
+#[cfg(feature = "phoenix-2019")]
+#[no_mangle]
+pub extern "C" fn c_MotController_ConfigRemoteSensorClosedLoopDisableNeutralOnLOS(
+    handle: Handle,
+    remoteSensorClosedLoopDisableNeutralOnLOS: bool,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    set_param(
+        handle,
+        ParamEnum::RemoteSensorClosedLoopDisableNeutralOnLOS,
+        remoteSensorClosedLoopDisableNeutralOnLOS as u8 as f64,
+        0,
+    )
+}
+#[cfg(feature = "phoenix-2019")]
+#[no_mangle]
+pub extern "C" fn c_MotController_ConfigClearPositionOnLimitF(
+    handle: Handle,
+    clearPositionOnLimitF: bool,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    set_param(
+        handle,
+        ParamEnum::ClearPositionOnLimitF,
+        clearPositionOnLimitF as u8 as f64,
+        0,
+    )
+}
+#[cfg(feature = "phoenix-2019")]
+#[no_mangle]
+pub extern "C" fn c_MotController_ConfigClearPositionOnLimitR(
+    handle: Handle,
+    clearPositionOnLimitR: bool,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    set_param(
+        handle,
+        ParamEnum::ClearPositionOnLimitR,
+        clearPositionOnLimitR as u8 as f64,
+        0,
+    )
+}
+#[cfg(feature = "phoenix-2019")]
+#[no_mangle]
+pub extern "C" fn c_MotController_ConfigClearPositionOnQuadIdx(
+    handle: Handle,
+    clearPositionOnQuadIdx: bool,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    set_param(
+        handle,
+        ParamEnum::ClearPositionOnQuadIdx,
+        clearPositionOnQuadIdx as u8 as f64,
+        0,
+    )
+}
+#[cfg(feature = "phoenix-2019")]
+#[no_mangle]
+pub extern "C" fn c_MotController_ConfigLimitSwitchDisableNeutralOnLOS(
+    handle: Handle,
+    limitSwitchDisableNeutralOnLOS: bool,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    set_param(
+        handle,
+        ParamEnum::LimitSwitchDisableNeutralOnLOS,
+        limitSwitchDisableNeutralOnLOS as u8 as f64,
+        0,
+    )
+}
+#[cfg(feature = "phoenix-2019")]
+#[no_mangle]
+pub extern "C" fn c_MotController_ConfigSoftLimitDisableNeutralOnLOS(
+    handle: Handle,
+    softLimitDisableNeutralOnLOS: bool,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    set_param(
+        handle,
+        ParamEnum::SoftLimitDisableNeutralOnLOS,
+        softLimitDisableNeutralOnLOS as u8 as f64,
+        0,
+    )
+}
+#[cfg(feature = "phoenix-2019")]
+#[no_mangle]
+pub extern "C" fn c_MotController_ConfigPulseWidthPeriod_EdgesPerRot(
+    handle: Handle,
+    pulseWidthPeriod_EdgesPerRot: c_int,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    set_param(
+        handle,
+        ParamEnum::PulseWidthPeriod_EdgesPerRot,
+        f64::from(pulseWidthPeriod_EdgesPerRot),
+        0,
+    )
+}
+#[cfg(feature = "phoenix-2019")]
+#[no_mangle]
+pub extern "C" fn c_MotController_ConfigPulseWidthPeriod_FilterWindowSz(
+    handle: Handle,
+    pulseWidthPeriod_FilterWindowSz: c_int,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    set_param(
+        handle,
+        ParamEnum::PulseWidthPeriod_FilterWindowSz,
+        f64::from(pulseWidthPeriod_FilterWindowSz),
+        0,
+    )
+}
+#[cfg(feature = "phoenix-2020")]
+#[no_mangle]
+pub extern "C" fn c_MotController_ConfigMotionSCurveStrength(
+    handle: Handle,
+    curveStrength: c_int,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    set_param(
+        handle,
+        ParamEnum::MotMag_SCurveLevel,
+        f64::from(curveStrength),
+        0,
+    )
+}
+#[cfg(feature = "phoenix-2020")]
+#[no_mangle]
+pub unsafe extern "C" fn c_MotController_GetSupplyCurrent(
+    handle: Handle,
+    current: *mut f64,
+) -> ErrorCode {
+    get(handle, current, |d| d.supply_current())
+}
+#[cfg(feature = "phoenix-2020")]
+#[no_mangle]
+pub unsafe extern "C" fn c_MotController_GetStatorCurrent(
+    handle: Handle,
+    current: *mut f64,
+) -> ErrorCode {
+    get(handle, current, |d| d.output_current)
+}
+
+#[test]
+fn config_round_trip() {
+    let handle = unsafe { ::mot::c_MotController_Create1(0x02040000 | 62) };
+    assert_eq!(handle, c_MotController_Create1(0x02040000 | 62));
+    c_MotController_ConfigPeakOutputForward(handle, 0.5, 10);
+    c_MotController_Set_4(handle, ControlMode::PercentOutput as _, 0.75, 0.0, 0);
+
+    let mut output = 0.0;
+    let err = unsafe { ::mot::c_MotController_GetMotorOutputPercent(handle, &mut output) };
+    assert_eq!(err, ErrorCode::OK);
+    assert_eq!(output, 0.5);
+    assert_eq!(
+        with_device(0x02040000 | 62, |d| d
+            .params()
+            .get(ParamEnum::PeakPosOutput, 0)),
+        Some(0.5)
+    );
+}
+
+#[test]
+fn motion_magic_moves_sensor() {
+    use std::thread;
+    use std::time::Duration;
+    let handle = c_MotController_Create1(0x02040000 | 60);
+    c_MotController_ConfigMotionCruiseVelocity(handle, 10_000, 0);
+    c_MotController_ConfigMotionAcceleration(handle, 100_000, 0);
+    c_MotController_Set_4(handle, ControlMode::MotionMagic as _, 500.0, 0.0, 0);
+    // About 45ms to get there at 1,000,000 units/s².
+    thread::sleep(Duration::from_millis(100));
+    let (mut position, mut trajectory) = (0, 0);
+    unsafe {
+        c_MotController_GetSelectedSensorPosition(handle, &mut position, 0);
+        c_MotController_GetActiveTrajectoryPosition(handle, &mut trajectory);
+    }
+    assert_eq!((position, trajectory), (500, 500));
+    assert_eq!(
+        with_device(0x02040000 | 60, |d| d.quadrature_position),
+        Some(500)
+    );
+}
+
+#[test]
+fn emulated_closed_loop_drives_mechanism() {
+    let mut talon = SimMotController::new(0x02040000 | 59);
+    talon.set_fidelity(Fidelity::Firmware);
+    talon.params.set(ParamEnum::ProfileParamSlot_F, 1.023, 0);
+    talon.params.set(ParamEnum::ProfileParamSlot_P, 0.5, 0);
+    talon.control_mode = ControlMode::Velocity as _;
+    talon.demand0 = 500.0;
+    let mut now = talon.stepped;
+    let mut errors = Vec::new();
+    for _ in 0..500 {
+        now += STEP;
+        talon.step(now);
+        errors.push(talon.closed_loop_error(0));
+    }
+    // The error shrinks steadily as the mechanism spins up.
+    assert!(errors.windows(2).all(|pair| pair[1] <= pair[0]));
+    assert!(errors[49] < 250 && errors[499].abs() <= 1);
+    assert!((talon.motor_output_percent() - 0.5).abs() < 0.01);
+    assert!(talon.quadrature_position > 0);
+
+    talon.control_mode = ControlMode::Position as _;
+    talon.params.set(ParamEnum::ProfileParamSlot_F, 0.0, 0);
+    talon.params.set(ParamEnum::ProfileParamSlot_P, 1.0, 0);
+    talon.demand0 = f64::from(talon.quadrature_position) + 1000.0;
+    for _ in 0..2000 {
+        now += STEP;
+        talon.step(now);
+    }
+    // Short of the target, the output is within the neutral deadband.
+    let error = talon.closed_loop_error(0);
+    assert!(error != 0 && error.abs() < 41);
+    assert_eq!(talon.motor_output_percent(), 0.0);
+    talon
+        .params
+        .set(ParamEnum::ProfileParamSlot_AllowableErr, 50.0, 0);
+    talon.step(now + STEP);
+    assert_eq!(talon.closed_loop_error(0), 0);
+}
+
+#[test]
+fn followers_mirror_master() {
+    let master = c_MotController_Create1(0x02040000 | 58);
+    let follower = c_MotController_Create1(0x01040000 | 58);
+    let aux_follower = c_MotController_Create1(0x01040000 | 57);
+    let id24 = f64::from(0x0204 << 8 | 58);
+    let follower_mode = ControlMode::Follower as c_int;
+    c_MotController_Set_4(follower, follower_mode, id24, 0.0, 0);
+    c_MotController_Set_4(
+        aux_follower,
+        follower_mode,
+        id24,
+        0.0,
+        DemandType::AuxPID as _,
+    );
+    let arb_ff = DemandType::ArbitraryFeedForward as c_int;
+    c_MotController_Set_4(master, ControlMode::PercentOutput as _, 0.5, 0.2, arb_ff);
+    let output = |handle| {
+        let mut output = 0.0;
+        unsafe { c_MotController_GetMotorOutputPercent(handle, &mut output) };
+        output
+    };
+    assert!((output(follower) - 0.7).abs() < 1e-9);
+    assert!((output(aux_follower) - 0.3).abs() < 1e-9);
+    c_MotController_Set_4(master, ControlMode::Disabled as _, 0.0, 0.0, 0);
+    assert_eq!(output(follower), 0.0);
+}
+
+#[test]
+fn remote_sensors_read_simulated_devices() {
+    use mot::RemoteSensorSource;
+    use sim::{canifier, pigeon};
+    let talon = c_MotController_Create1(0x02040000 | 56);
+    let remote_talon = c_MotController_Create1(0x02040000 | 55);
+    pigeon::c_PigeonIMU_Create1(56);
+    canifier::c_CANifier_Create1(56);
+    pigeon::with_device(pigeon::Connection::Can(56), |p| p.yaw_pitch_roll[0] = 90.0);
+    canifier::with_device(56, |c| c.quadrature_position = 1234);
+    with_device(0x02040000 | 55, |d| d.quadrature_position = -300);
+
+    let source = |source: RemoteSensorSource, id| {
+        c_MotController_ConfigRemoteFeedbackFilter(talon, id, source as _, 0, 0);
+        let mut position = 0;
+        unsafe { c_MotController_GetSelectedSensorPosition(talon, &mut position, 0) };
+        position
+    };
+    c_MotController_ConfigSelectedFeedbackSensor(talon, FeedbackDevice::RemoteSensor0 as _, 0, 0);
+    assert_eq!(source(RemoteSensorSource::Pigeon_Yaw, 56), 2048);
+    assert_eq!(source(RemoteSensorSource::CANifier_Quadrature, 56), 1234);
+    // The remote Talon publishes its sensor as it updates.
+    unsafe { c_MotController_GetSelectedSensorPosition(remote_talon, &mut 0, 0) };
+    assert_eq!(
+        source(RemoteSensorSource::TalonSRX_SelectedSensor, 55),
+        -300
+    );
+    assert_eq!(source(RemoteSensorSource::Pigeon_Yaw, 54), 0);
+}
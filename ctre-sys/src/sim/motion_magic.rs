@@ -0,0 +1,176 @@
+//! Motion Magic trajectories, generated the way the firmware does.
+//!
+//! The firmware plans a trapezoid: accelerate at the configured acceleration up
+//! to the cruise velocity, cruise, then decelerate to stop on the target.  With
+//! an S-curve strength above zero the corners are rounded off; here that is
+//! approximated by a moving average of the trapezoid's velocity over
+//! `strength * 25` ms.  The trajectory is stepped at the firmware's 1ms loop
+//! period, in device time (see `clock`).
+
+use std::collections::VecDeque;
+use std::time::Instant;
+
+use super::{DT, STEP};
+/// Milliseconds of smoothing per S-curve strength level.
+const S_CURVE_MS_PER_LEVEL: usize = 25;
+
+/// A Motion Magic trajectory, from wherever the sensor was when it started.
+#[derive(Debug, Clone)]
+pub struct Trajectory {
+    target: f64,
+    /// Native units per second.
+    cruise: f64,
+    /// Native units per second squared.
+    accel: f64,
+    /// The trapezoid, before S-curve smoothing.
+    raw_position: f64,
+    raw_velocity: f64,
+    /// The trapezoid's last velocities, averaged for the S-curve.
+    history: VecDeque<f64>,
+    position: f64,
+    velocity: f64,
+    updated: Instant,
+}
+
+impl Trajectory {
+    /// Starts from `position` and `velocity` (in native units per 100ms), with
+    /// nowhere to go until `retarget` is called.
+    pub(crate) fn new(now: Instant, position: f64, velocity: f64) -> Self {
+        let velocity = velocity * 10.;
+        Trajectory {
+            target: position,
+            cruise: 0.,
+            accel: 0.,
+            raw_position: position,
+            raw_velocity: velocity,
+            history: vec![velocity].into(),
+            position,
+            velocity,
+            updated: now,
+        }
+    }
+
+    /**
+     * Heads for `target` from wherever the trajectory is now.  `cruise` is in
+     * native units per 100ms and `accel` in native units per 100ms per second,
+     * as configured on the controller.
+     */
+    pub(crate) fn retarget(&mut self, target: f64, cruise: f64, accel: f64, s_curve: i32) {
+        self.target = target;
+        self.cruise = cruise.abs() * 10.;
+        self.accel = accel.abs() * 10.;
+        let window = 1 + s_curve.clamp(0, 8) as usize * S_CURVE_MS_PER_LEVEL;
+        if window != self.history.len() {
+            self.history = vec![self.velocity; window].into();
+            self.raw_velocity = self.velocity;
+            self.raw_position = self.position;
+        }
+    }
+
+    /// Moves the whole trajectory by `delta`, as when the sensor is re-zeroed.
+    pub(crate) fn shift(&mut self, delta: f64) {
+        self.raw_position += delta;
+        self.position += delta;
+    }
+
+    /// Steps the trajectory up to `now`.
+    pub(crate) fn advance(&mut self, now: Instant) {
+        while self.updated + STEP <= now {
+            if self.is_settled() {
+                self.updated = now;
+                return;
+            }
+            self.step();
+            self.updated += STEP;
+        }
+    }
+
+    /// The position the trajectory has reached, in native units.
+    pub fn position(&self) -> f64 {
+        self.position
+    }
+    /// The trajectory's velocity, in native units per 100ms.
+    pub fn velocity(&self) -> f64 {
+        self.velocity / 10.
+    }
+    /// The position the trajectory is heading for.
+    pub fn target(&self) -> f64 {
+        self.target
+    }
+    /// Whether the trajectory has stopped, on the target unless it can't move.
+    pub fn is_settled(&self) -> bool {
+        let stuck = self.cruise == 0. || self.accel == 0.;
+        self.raw_velocity == 0.
+            && self.velocity == 0.
+            && (self.raw_position == self.target || stuck)
+    }
+
+    fn step(&mut self) {
+        // Without an acceleration to ramp at, stop dead rather than coast forever.
+        let max_dv = if self.accel > 0. {
+            self.accel * DT
+        } else {
+            f64::INFINITY
+        };
+        let remaining = self.target - self.raw_position;
+        // The fastest velocity that can still stop on the target.
+        let reachable = (2. * self.accel * remaining.abs()).sqrt();
+        let wanted = remaining.signum() * self.cruise.min(reachable);
+        let dv = (wanted - self.raw_velocity).max(-max_dv).min(max_dv);
+        self.raw_velocity += dv;
+        self.raw_position += self.raw_velocity * DT;
+        let passed = (self.target - self.raw_position) * remaining <= 0.;
+        if passed && self.raw_velocity.abs() <= 2. * max_dv {
+            self.raw_position = self.target;
+            self.raw_velocity = 0.;
+        }
+
+        self.history.pop_front();
+        self.history.push_back(self.raw_velocity);
+        self.velocity = self.history.iter().sum::<f64>() / self.history.len() as f64;
+        self.position += self.velocity * DT;
+        if self.history.iter().all(|&v| v == 0.) {
+            // Land exactly where the trapezoid did, whatever the rounding.
+            self.velocity = 0.;
+            self.position = self.raw_position;
+        }
+    }
+}
+
+#[test]
+fn trapezoid_reaches_target() {
+    use std::time::Duration;
+    let start = Instant::now();
+    // 1000 units/s cruise, 2000 units/s² acceleration.
+    let mut trajectory = Trajectory::new(start, 0., 0.);
+    trajectory.retarget(2000., 100., 200., 0);
+    // Half a second to reach cruise, covering 250 units.
+    trajectory.advance(start + Duration::from_millis(500));
+    assert!((trajectory.velocity() - 100.).abs() < 1.);
+    assert!((trajectory.position() - 250.).abs() < 5.);
+    // Cruising for 1.5s covers the next 1500, then half a second to stop.
+    trajectory.advance(start + Duration::from_millis(2250));
+    assert!((trajectory.velocity() - 50.).abs() < 2.);
+    assert!((trajectory.position() - 1937.5).abs() < 5.);
+    trajectory.advance(start + Duration::from_millis(2600));
+    assert!(trajectory.is_settled());
+    assert_eq!(trajectory.position(), 2000.);
+}
+
+#[test]
+fn s_curve_rounds_acceleration() {
+    use std::time::Duration;
+    let start = Instant::now();
+    let mut trapezoid = Trajectory::new(start, 0., 0.);
+    trapezoid.retarget(-2000., 100., 200., 0);
+    let mut s_curve = trapezoid.clone();
+    s_curve.retarget(-2000., 100., 200., 4);
+    let at = start + Duration::from_millis(50);
+    trapezoid.advance(at);
+    s_curve.advance(at);
+    assert!(s_curve.velocity() > trapezoid.velocity() && s_curve.velocity() < 0.);
+    let end = start + Duration::from_secs(4);
+    s_curve.advance(end);
+    assert!(s_curve.is_settled());
+    assert_eq!(s_curve.position(), -2000.);
+}
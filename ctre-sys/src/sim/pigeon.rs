@@ -0,0 +1,565 @@
+//! Simulated Pigeon IMUs.
+
+use std::collections::HashMap;
+use std::os::raw::{c_char, c_int, c_short};
+
+use super::can;
+use super::{call, put, put_all, put_description, Params, Table};
+use pigeon::Handle;
+use {ErrorCode, ParamEnum};
+
+/// Where a simulated Pigeon is connected.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Connection {
+    /// On the CAN bus with the given device number.
+    Can(i32),
+    /// Ribbon-cabled to the Talon SRX with the given device number.
+    Talon(i32),
+}
+
+/// State of a simulated Pigeon IMU.
+///
+/// The public fields are the signals the Pigeon would measure;
+/// set them to whatever the code under test should see.
+#[derive(Debug)]
+pub struct SimPigeon {
+    connection: Connection,
+    params: Params,
+    status_frame_periods: HashMap<c_int, c_int>,
+    last_error: ErrorCode,
+    temperature_compensation: bool,
+    compass_declination: f64,
+
+    /// `[yaw, pitch, roll]` in degrees.
+    pub yaw_pitch_roll: [f64; 3],
+    pub fused_heading: f64,
+    pub compass_heading: f64,
+    pub compass_field_strength: f64,
+    /// `[w, x, y, z]`
+    pub quaternion: [f64; 4],
+    pub accum_gyro: [f64; 3],
+    pub raw_gyro: [f64; 3],
+    pub accelerometer_angles: [f64; 3],
+    pub raw_magnetometer: [c_short; 3],
+    pub biased_magnetometer: [c_short; 3],
+    pub biased_accelerometer: [c_short; 3],
+    pub temperature: f64,
+    pub up_time: i32,
+    /// Raw pigeon state; 2 is ready.
+    pub state: i32,
+    pub faults: i32,
+    pub sticky_faults: i32,
+    pub firmware_version: i32,
+    pub reset_count: i32,
+    pub has_reset_occurred: bool,
+}
+
+impl SimPigeon {
+    fn new(connection: Connection) -> Self {
+        SimPigeon {
+            connection,
+            params: Params::default(),
+            status_frame_periods: HashMap::new(),
+            last_error: ErrorCode::OK,
+            temperature_compensation: false,
+            compass_declination: 0.0,
+            yaw_pitch_roll: [0.0; 3],
+            fused_heading: 0.0,
+            compass_heading: 0.0,
+            compass_field_strength: 0.0,
+            quaternion: [1.0, 0.0, 0.0, 0.0],
+            accum_gyro: [0.0; 3],
+            raw_gyro: [0.0; 3],
+            accelerometer_angles: [0.0; 3],
+            raw_magnetometer: [0; 3],
+            biased_magnetometer: [0; 3],
+            biased_accelerometer: [0; 3],
+            temperature: 25.0,
+            up_time: 0,
+            state: 2,
+            faults: 0,
+            sticky_faults: 0,
+            firmware_version: 0x0400,
+            reset_count: 0,
+            has_reset_occurred: false,
+        }
+    }
+
+    pub fn connection(&self) -> Connection {
+        self.connection
+    }
+    /// Configuration parameters, as set through the config calls.
+    pub fn params(&self) -> &Params {
+        &self.params
+    }
+    pub fn temperature_compensation(&self) -> bool {
+        self.temperature_compensation
+    }
+    pub fn compass_declination(&self) -> f64 {
+        self.compass_declination
+    }
+}
+
+static DEVICES: Table<SimPigeon> = Table::new();
+
+/// Runs `f` on the simulated Pigeon with the given connection.
+/// Returns `None` if no such Pigeon has been created.
+pub fn with_device<R>(connection: Connection, f: impl FnOnce(&mut SimPigeon) -> R) -> Option<R> {
+    DEVICES.find(|d| d.connection == connection, f)
+}
+
+/// Runs `f` on every simulated Pigeon, in the order they were created.
+pub fn for_each_device(f: impl FnMut(&mut SimPigeon)) {
+    DEVICES.for_each(f)
+}
+
+/// Runs `f` on the Pigeon behind `handle`, recording the returned error code.
+fn pigeon(handle: Handle, f: impl FnOnce(&mut SimPigeon) -> ErrorCode) -> ErrorCode {
+    call(&DEVICES, handle, |d| {
+        let err = f(d);
+        d.last_error = err;
+        err
+    })
+}
+
+/// Reads a value from the Pigeon behind `handle` into `out`.
+unsafe fn get<T>(handle: Handle, out: *mut T, f: impl FnOnce(&SimPigeon) -> T) -> ErrorCode {
+    pigeon(handle, |d| {
+        put(out, f(d));
+        can::status_frame()
+    })
+}
+
+/// Reads an array from the Pigeon behind `handle` into `out`.
+unsafe fn get_all<T: Copy>(
+    handle: Handle,
+    out: *mut T,
+    f: impl FnOnce(&SimPigeon) -> &[T],
+) -> ErrorCode {
+    pigeon(handle, |d| {
+        put_all(out, f(d));
+        can::status_frame()
+    })
+}
+
+fn create(connection: Connection) -> Handle {
+    DEVICES.get_or_create(
+        |d| d.connection == connection,
+        || SimPigeon::new(connection),
+    )
+}
+
+#[no_mangle]
+pub extern "C" fn c_PigeonIMU_Create2(talonDeviceID: c_int) -> Handle {
+    create(Connection::Talon(talonDeviceID))
+}
+#[no_mangle]
+pub extern "C" fn c_PigeonIMU_Create1(deviceNumber: c_int) -> Handle {
+    create(Connection::Can(deviceNumber))
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_PigeonIMU_GetDescription(
+    handle: Handle,
+    toFill: *mut c_char,
+    toFillByteSz: c_int,
+    numBytesFilled: *mut c_int,
+) -> ErrorCode {
+    pigeon(handle, |d| {
+        let desc = match d.connection {
+            Connection::Can(id) => format!("Pigeon IMU {}", id),
+            Connection::Talon(id) => format!("Pigeon IMU (Talon SRX {})", id),
+        };
+        put_description(&desc, toFill, toFillByteSz, numBytesFilled);
+        ErrorCode::OK
+    })
+}
+#[no_mangle]
+pub extern "C" fn c_PigeonIMU_ConfigSetParameter(
+    handle: Handle,
+    param: c_int,
+    value: f64,
+    _subValue: c_int,
+    ordinal: c_int,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    pigeon(handle, |d| {
+        if !can::transmit() {
+            return ErrorCode::SigNotUpdated;
+        }
+        d.params.set_raw(param, value, ordinal);
+        ErrorCode::OK
+    })
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_PigeonIMU_ConfigGetParameter(
+    handle: Handle,
+    param: c_int,
+    value: *mut f64,
+    ordinal: c_int,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    get(handle, value, |d| d.params.get_raw(param, ordinal))
+}
+#[no_mangle]
+pub extern "C" fn c_PigeonIMU_ConfigSetCustomParam(
+    handle: Handle,
+    newValue: c_int,
+    paramIndex: c_int,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    pigeon(handle, |d| {
+        d.params
+            .set(ParamEnum::CustomParam, f64::from(newValue), paramIndex);
+        ErrorCode::OK
+    })
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_PigeonIMU_ConfigGetCustomParam(
+    handle: Handle,
+    readValue: *mut c_int,
+    paramIndex: c_int,
+    _timoutMs: c_int,
+) -> ErrorCode {
+    get(handle, readValue, |d| {
+        d.params.get(ParamEnum::CustomParam, paramIndex) as c_int
+    })
+}
+#[no_mangle]
+pub extern "C" fn c_PigeonIMU_SetYaw(
+    handle: Handle,
+    angleDeg: f64,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    pigeon(handle, |d| {
+        d.yaw_pitch_roll[0] = angleDeg;
+        ErrorCode::OK
+    })
+}
+#[no_mangle]
+pub extern "C" fn c_PigeonIMU_AddYaw(
+    handle: Handle,
+    angleDeg: f64,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    pigeon(handle, |d| {
+        d.yaw_pitch_roll[0] += angleDeg;
+        ErrorCode::OK
+    })
+}
+#[no_mangle]
+pub extern "C" fn c_PigeonIMU_SetYawToCompass(handle: Handle, _timeoutMs: c_int) -> ErrorCode {
+    pigeon(handle, |d| {
+        d.yaw_pitch_roll[0] = d.compass_heading;
+        ErrorCode::OK
+    })
+}
+#[no_mangle]
+pub extern "C" fn c_PigeonIMU_SetFusedHeading(
+    handle: Handle,
+    angleDeg: f64,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    pigeon(handle, |d| {
+        d.fused_heading = angleDeg;
+        ErrorCode::OK
+    })
+}
+#[no_mangle]
+pub extern "C" fn c_PigeonIMU_AddFusedHeading(
+    handle: Handle,
+    angleDeg: f64,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    pigeon(handle, |d| {
+        d.fused_heading += angleDeg;
+        ErrorCode::OK
+    })
+}
+#[no_mangle]
+pub extern "C" fn c_PigeonIMU_SetFusedHeadingToCompass(
+    handle: Handle,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    pigeon(handle, |d| {
+        d.fused_heading = d.compass_heading;
+        ErrorCode::OK
+    })
+}
+#[no_mangle]
+pub extern "C" fn c_PigeonIMU_SetAccumZAngle(
+    handle: Handle,
+    angleDeg: f64,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    pigeon(handle, |d| {
+        d.accum_gyro[2] = angleDeg;
+        ErrorCode::OK
+    })
+}
+#[no_mangle]
+pub extern "C" fn c_PigeonIMU_ConfigTemperatureCompensationEnable(
+    handle: Handle,
+    bTempCompEnable: c_int,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    pigeon(handle, |d| {
+        d.temperature_compensation = bTempCompEnable != 0;
+        ErrorCode::OK
+    })
+}
+#[no_mangle]
+pub extern "C" fn c_PigeonIMU_SetCompassDeclination(
+    handle: Handle,
+    angleDegOffset: f64,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    pigeon(handle, |d| {
+        d.compass_declination = angleDegOffset;
+        ErrorCode::OK
+    })
+}
+#[no_mangle]
+pub extern "C" fn c_PigeonIMU_SetCompassAngle(
+    handle: Handle,
+    angleDeg: f64,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    pigeon(handle, |d| {
+        d.compass_heading = angleDeg;
+        ErrorCode::OK
+    })
+}
+#[no_mangle]
+pub extern "C" fn c_PigeonIMU_EnterCalibrationMode(
+    handle: Handle,
+    _calMode: c_int,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    pigeon(handle, |_| ErrorCode::OK)
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_PigeonIMU_GetGeneralStatus(
+    handle: Handle,
+    state: *mut c_int,
+    currentMode: *mut c_int,
+    calibrationError: *mut c_int,
+    bCalIsBooting: *mut c_int,
+    tempC: *mut f64,
+    upTimeSec: *mut c_int,
+    noMotionBiasCount: *mut c_int,
+    tempCompensationCount: *mut c_int,
+    lastError: *mut c_int,
+) -> ErrorCode {
+    pigeon(handle, |d| {
+        put(state, d.state);
+        put(currentMode, 0);
+        put(calibrationError, 0);
+        put(bCalIsBooting, 0);
+        put(tempC, d.temperature);
+        put(upTimeSec, d.up_time);
+        put(noMotionBiasCount, 0);
+        put(tempCompensationCount, 0);
+        put(lastError, d.last_error as c_int);
+        ErrorCode::OK
+    })
+}
+#[no_mangle]
+pub extern "C" fn c_PigeonIMU_GetLastError(handle: Handle) -> ErrorCode {
+    DEVICES
+        .with(handle, |d| d.last_error)
+        .unwrap_or(ErrorCode::InvalidHandle)
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_PigeonIMU_Get6dQuaternion(handle: Handle, wxyz: *mut f64) -> ErrorCode {
+    get_all(handle, wxyz, |d| &d.quaternion)
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_PigeonIMU_GetYawPitchRoll(handle: Handle, ypr: *mut f64) -> ErrorCode {
+    get_all(handle, ypr, |d| &d.yaw_pitch_roll)
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_PigeonIMU_GetAccumGyro(handle: Handle, xyz_deg: *mut f64) -> ErrorCode {
+    get_all(handle, xyz_deg, |d| &d.accum_gyro)
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_PigeonIMU_GetAbsoluteCompassHeading(
+    handle: Handle,
+    value: *mut f64,
+) -> ErrorCode {
+    get(handle, value, |d| d.compass_heading)
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_PigeonIMU_GetCompassHeading(
+    handle: Handle,
+    value: *mut f64,
+) -> ErrorCode {
+    get(handle, value, |d| d.compass_heading + d.compass_declination)
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_PigeonIMU_GetCompassFieldStrength(
+    handle: Handle,
+    value: *mut f64,
+) -> ErrorCode {
+    get(handle, value, |d| d.compass_field_strength)
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_PigeonIMU_GetTemp(handle: Handle, value: *mut f64) -> ErrorCode {
+    get(handle, value, |d| d.temperature)
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_PigeonIMU_GetState(handle: Handle, state: *mut c_int) -> ErrorCode {
+    get(handle, state, |d| d.state)
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_PigeonIMU_GetUpTime(handle: Handle, value: *mut c_int) -> ErrorCode {
+    get(handle, value, |d| d.up_time)
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_PigeonIMU_GetRawMagnetometer(
+    handle: Handle,
+    rm_xyz: *mut c_short,
+) -> ErrorCode {
+    get_all(handle, rm_xyz, |d| &d.raw_magnetometer)
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_PigeonIMU_GetBiasedMagnetometer(
+    handle: Handle,
+    bm_xyz: *mut c_short,
+) -> ErrorCode {
+    get_all(handle, bm_xyz, |d| &d.biased_magnetometer)
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_PigeonIMU_GetBiasedAccelerometer(
+    handle: Handle,
+    ba_xyz: *mut c_short,
+) -> ErrorCode {
+    get_all(handle, ba_xyz, |d| &d.biased_accelerometer)
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_PigeonIMU_GetRawGyro(handle: Handle, xyz_dps: *mut f64) -> ErrorCode {
+    get_all(handle, xyz_dps, |d| &d.raw_gyro)
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_PigeonIMU_GetAccelerometerAngles(
+    handle: Handle,
+    tiltAngles: *mut f64,
+) -> ErrorCode {
+    get_all(handle, tiltAngles, |d| &d.accelerometer_angles)
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_PigeonIMU_GetFusedHeading2(
+    handle: Handle,
+    bIsFusing: *mut c_int,
+    bIsValid: *mut c_int,
+    value: *mut f64,
+    lastError: *mut c_int,
+) -> ErrorCode {
+    pigeon(handle, |d| {
+        put(bIsFusing, 0);
+        put(bIsValid, (d.state == 2) as c_int);
+        put(value, d.fused_heading);
+        put(lastError, d.last_error as c_int);
+        ErrorCode::OK
+    })
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_PigeonIMU_GetFusedHeading1(
+    handle: Handle,
+    value: *mut f64,
+) -> ErrorCode {
+    get(handle, value, |d| d.fused_heading)
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_PigeonIMU_GetResetCount(handle: Handle, value: *mut c_int) -> ErrorCode {
+    get(handle, value, |d| d.reset_count)
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_PigeonIMU_GetResetFlags(handle: Handle, value: *mut c_int) -> ErrorCode {
+    get(handle, value, |_| 0)
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_PigeonIMU_GetFirmwareVersion(
+    handle: Handle,
+    firmwareVers: *mut c_int,
+) -> ErrorCode {
+    get(handle, firmwareVers, |d| d.firmware_version)
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_PigeonIMU_HasResetOccurred(
+    handle: Handle,
+    hasReset: *mut bool,
+) -> ErrorCode {
+    pigeon(handle, |d| {
+        put(hasReset, d.has_reset_occurred);
+        d.has_reset_occurred = false;
+        ErrorCode::OK
+    })
+}
+#[no_mangle]
+pub extern "C" fn c_PigeonIMU_SetLastError(handle: Handle, value: c_int) -> ErrorCode {
+    call(&DEVICES, handle, |d| {
+        d.last_error = if value == 0 {
+            ErrorCode::OK
+        } else {
+            ErrorCode::GeneralError
+        };
+        ErrorCode::OK
+    })
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_PigeonIMU_GetFaults(handle: Handle, param: *mut c_int) -> ErrorCode {
+    get(handle, param, |d| d.faults)
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_PigeonIMU_GetStickyFaults(
+    handle: Handle,
+    param: *mut c_int,
+) -> ErrorCode {
+    get(handle, param, |d| d.sticky_faults)
+}
+#[no_mangle]
+pub extern "C" fn c_PigeonIMU_ClearStickyFaults(handle: Handle, _timeoutMs: c_int) -> ErrorCode {
+    pigeon(handle, |d| {
+        d.sticky_faults = 0;
+        ErrorCode::OK
+    })
+}
+#[no_mangle]
+pub extern "C" fn c_PigeonIMU_SetStatusFramePeriod(
+    handle: Handle,
+    frame: c_int,
+    periodMs: c_int,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    pigeon(handle, |d| {
+        d.status_frame_periods.insert(frame, periodMs);
+        ErrorCode::OK
+    })
+}
+#[no_mangle]
+pub unsafe extern "C" fn c_PigeonIMU_GetStatusFramePeriod(
+    handle: Handle,
+    frame: c_int,
+    periodMs: *mut c_int,
+    _timeoutMs: c_int,
+) -> ErrorCode {
+    get(handle, periodMs, |d| {
+        d.status_frame_periods.get(&frame).cloned().unwrap_or(100)
+    })
+}
+#[no_mangle]
+pub extern "C" fn c_PigeonIMU_SetControlFramePeriod(
+    handle: Handle,
+    _frame: c_int,
+    _periodMs: c_int,
+) -> ErrorCode {
+    pigeon(handle, |_| ErrorCode::OK)
+}
+#[cfg(feature = "phoenix-2019")]
+#[no_mangle]
+pub extern "C" fn c_PigeonIMU_ConfigFactoryDefault(handle: Handle, _timeoutMs: c_int) -> ErrorCode {
+    pigeon(handle, |d| {
+        d.params.clear();
+        ErrorCode::OK
+    })
+}
@@ -0,0 +1,290 @@
+//! A uniform view of simulated devices, for GUIs and other tools.
+//!
+//! Each kind of simulated device implements [`SimProvider`], listing its
+//! signals by name and letting the writable ones (sensor readings, limit
+//! switches, ...) be set.  A GUI running alongside robot code on the desktop
+//! can poll [`visit_devices`] each frame to show every device and poke at it,
+//! without knowing about each kind of device.
+//!
+//! [`SimProvider`]: trait.SimProvider.html
+//! [`visit_devices`]: fn.visit_devices.html
+
+use std::fmt;
+
+use super::canifier::{self, SimCANifier};
+use super::mot::{self, SimMotController};
+use super::pigeon::{self, Connection, SimPigeon};
+
+/// The value of a simulated signal.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Value {
+    Bool(bool),
+    Int(i32),
+    Double(f64),
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Value::Bool(value) => value.fmt(f),
+            Value::Int(value) => value.fmt(f),
+            Value::Double(value) => value.fmt(f),
+        }
+    }
+}
+
+/// A named signal of a simulated device.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SimValue {
+    pub name: &'static str,
+    pub value: Value,
+    /// Whether it can be set with `SimProvider::set_value`.  Other signals
+    /// are outputs of the code under test, such as the applied demand.
+    pub writable: bool,
+}
+
+/// A simulated device whose signals can be listed and set.
+pub trait SimProvider {
+    /// e.g. `Talon SRX 3`.
+    fn name(&self) -> String;
+    /// Every signal, always in the same order.
+    fn values(&self) -> Vec<SimValue>;
+    /**
+     * Sets the writable signal `name`.  An `Int` is accepted for a `Double`
+     * signal.  Returns `false`, changing nothing, if there is no such writable
+     * signal or `value` is the wrong type.
+     */
+    fn set_value(&mut self, name: &str, value: Value) -> bool;
+}
+
+/// How to read and, if writable, set one signal of a `T`.
+struct Field<T> {
+    name: &'static str,
+    get: fn(&T) -> Value,
+    set: Option<fn(&mut T, Value) -> bool>,
+}
+
+macro_rules! field {
+    (read $name:ident: $get:expr) => {
+        Field {
+            name: stringify!($name),
+            get: $get,
+            set: None,
+        }
+    };
+    ($field:ident: Bool) => {
+        Field {
+            name: stringify!($field),
+            get: |d| Value::Bool(d.$field),
+            set: Some(|d, value| match value {
+                Value::Bool(value) => {
+                    d.$field = value;
+                    true
+                }
+                _ => false,
+            }),
+        }
+    };
+    ($field:ident: Int) => {
+        Field {
+            name: stringify!($field),
+            get: |d| Value::Int(d.$field),
+            set: Some(|d, value| match value {
+                Value::Int(value) => {
+                    d.$field = value;
+                    true
+                }
+                _ => false,
+            }),
+        }
+    };
+    ($field:ident: Double) => {
+        Field {
+            name: stringify!($field),
+            get: |d| Value::Double(d.$field),
+            set: Some(|d, value| match value {
+                Value::Double(value) => {
+                    d.$field = value;
+                    true
+                }
+                Value::Int(value) => {
+                    d.$field = f64::from(value);
+                    true
+                }
+                _ => false,
+            }),
+        }
+    };
+}
+
+fn values<T>(device: &T, fields: &[Field<T>]) -> Vec<SimValue> {
+    fields
+        .iter()
+        .map(|field| SimValue {
+            name: field.name,
+            value: (field.get)(device),
+            writable: field.set.is_some(),
+        })
+        .collect()
+}
+
+fn set_value<T>(device: &mut T, fields: &[Field<T>], name: &str, value: Value) -> bool {
+    match fields.iter().find(|field| field.name == name) {
+        Some(&Field { set: Some(set), .. }) => set(device, value),
+        _ => false,
+    }
+}
+
+const MOT_FIELDS: &[Field<SimMotController>] = &[
+    field!(read control_mode: |d| Value::Int(d.control_mode())),
+    field!(read demand0: |d| Value::Double(d.demand().0)),
+    field!(read motor_output_percent: |d| Value::Double(d.motor_output_percent())),
+    field!(read selected_sensor_position: |d| Value::Int(d.selected_sensor_position(0))),
+    field!(read selected_sensor_velocity: |d| Value::Int(d.selected_sensor_velocity(0))),
+    field!(read inverted: |d| Value::Bool(d.inverted())),
+    field!(bus_voltage: Double),
+    field!(output_current: Double),
+    field!(temperature: Double),
+    field!(quadrature_position: Int),
+    field!(quadrature_velocity: Int),
+    field!(analog_position: Int),
+    field!(analog_velocity: Int),
+    field!(pulse_width_position: Int),
+    field!(pulse_width_velocity: Int),
+    field!(fwd_limit_switch_closed: Bool),
+    field!(rev_limit_switch_closed: Bool),
+    field!(faults: Int),
+    field!(sticky_faults: Int),
+];
+
+impl SimProvider for SimMotController {
+    fn name(&self) -> String {
+        let kind = if self.base_arb_id() & 0x0204_0000 == 0x0204_0000 {
+            "Talon SRX"
+        } else {
+            "Victor SPX"
+        };
+        format!("{} {}", kind, self.device_number())
+    }
+    fn values(&self) -> Vec<SimValue> {
+        values(self, MOT_FIELDS)
+    }
+    fn set_value(&mut self, name: &str, value: Value) -> bool {
+        set_value(self, MOT_FIELDS, name, value)
+    }
+}
+
+const CANIFIER_FIELDS: &[Field<SimCANifier>] = &[
+    field!(read led_a: |d| Value::Int(d.led_outputs()[0] as i32)),
+    field!(read led_b: |d| Value::Int(d.led_outputs()[1] as i32)),
+    field!(read led_c: |d| Value::Int(d.led_outputs()[2] as i32)),
+    field!(bus_voltage: Double),
+    field!(quadrature_position: Int),
+    field!(quadrature_velocity: Int),
+    field!(faults: Int),
+    field!(sticky_faults: Int),
+];
+
+impl SimProvider for SimCANifier {
+    fn name(&self) -> String {
+        format!("CANifier {}", self.device_number())
+    }
+    fn values(&self) -> Vec<SimValue> {
+        values(self, CANIFIER_FIELDS)
+    }
+    fn set_value(&mut self, name: &str, value: Value) -> bool {
+        set_value(self, CANIFIER_FIELDS, name, value)
+    }
+}
+
+/// A writable element of one of a Pigeon's `[f64; 3]` signals.
+macro_rules! pigeon_axis {
+    ($name:ident: $field:ident[$axis:expr]) => {
+        Field {
+            name: stringify!($name),
+            get: |d: &SimPigeon| Value::Double(d.$field[$axis]),
+            set: Some(|d: &mut SimPigeon, value| match value {
+                Value::Double(value) => {
+                    d.$field[$axis] = value;
+                    true
+                }
+                Value::Int(value) => {
+                    d.$field[$axis] = f64::from(value);
+                    true
+                }
+                _ => false,
+            }),
+        }
+    };
+}
+
+const PIGEON_FIELDS: &[Field<SimPigeon>] = &[
+    pigeon_axis!(yaw: yaw_pitch_roll[0]),
+    pigeon_axis!(pitch: yaw_pitch_roll[1]),
+    pigeon_axis!(roll: yaw_pitch_roll[2]),
+    field!(fused_heading: Double),
+    field!(compass_heading: Double),
+    field!(temperature: Double),
+    field!(state: Int),
+    field!(faults: Int),
+    field!(sticky_faults: Int),
+];
+
+impl SimProvider for SimPigeon {
+    fn name(&self) -> String {
+        match self.connection() {
+            Connection::Can(id) => format!("Pigeon IMU {}", id),
+            Connection::Talon(id) => format!("Pigeon IMU (Talon SRX {})", id),
+        }
+    }
+    fn values(&self) -> Vec<SimValue> {
+        values(self, PIGEON_FIELDS)
+    }
+    fn set_value(&mut self, name: &str, value: Value) -> bool {
+        set_value(self, PIGEON_FIELDS, name, value)
+    }
+}
+
+/**
+ * Runs `f` on every simulated device, motor controllers first, then CANifiers,
+ * then Pigeons, each in the order they were created.
+ *
+ * Each kind of device is locked while `f` runs on it, so `f` mustn't call
+ * into the CCI.
+ */
+pub fn visit_devices(mut f: impl FnMut(&mut dyn SimProvider)) {
+    mot::for_each_device(|d| f(d));
+    canifier::for_each_device(|d| f(d));
+    pigeon::for_each_device(|d| f(d));
+}
+
+#[test]
+fn providers_list_and_set_values() {
+    use mot::c_MotController_Create1;
+    let handle = unsafe { c_MotController_Create1(0x0204_0000 | 61) };
+    assert!(!handle.is_null());
+    let mut found = false;
+    visit_devices(|device| {
+        if device.name() != "Talon SRX 61" {
+            return;
+        }
+        found = true;
+        assert!(device.set_value("fwd_limit_switch_closed", Value::Bool(true)));
+        assert!(device.set_value("bus_voltage", Value::Int(11)));
+        assert!(!device.set_value("bus_voltage", Value::Bool(true)));
+        // Outputs of the code under test can't be set.
+        assert!(!device.set_value("motor_output_percent", Value::Double(1.)));
+        assert!(!device.set_value("no_such_signal", Value::Int(1)));
+        let values = device.values();
+        let bus_voltage = values.iter().find(|v| v.name == "bus_voltage").unwrap();
+        assert_eq!(
+            (bus_voltage.value, bus_voltage.writable),
+            (Value::Double(11.), true)
+        );
+    });
+    assert!(found);
+    assert_eq!(
+        mot::with_device(0x0204_0000 | 61, |d| d.fwd_limit_switch_closed),
+        Some(true)
+    );
+}
@@ -0,0 +1,511 @@
+//! `ctre-cli`: CTRE device diagnostics for the pits, run on the roboRIO.
+//!
+//! Built with the `cli` feature.  Run `ctre-cli help` for the commands.
+
+extern crate ctre;
+
+use std::convert::TryFrom;
+use std::env;
+use std::error::Error;
+use std::fmt::Write;
+use std::fs::File;
+use std::io::{self, Write as IoWrite};
+use std::process;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use ctre::canifier::{CANifier, LEDChannel};
+use ctre::connection::Connection;
+use ctre::datalog::LogReader;
+use ctre::faults::StickyFaultDevice;
+use ctre::motor_control::{BaseMotorController, Faults, NeutralMode, TalonSRX, VictorSPX};
+use ctre::sensors::pigeon::PigeonIMU;
+use ctre::{DeviceId, DeviceType, FirmwareVersion, ParamEnum};
+
+const USAGE: &str = "\
+usage: ctre-cli <command>
+
+commands:
+    list                            list the devices that respond
+    dump <type> <id>                print a motor controller's configuration
+    apply <file>                    create and configure the devices in a robot config file
+    clear-faults <type> <id>        print and clear a device's sticky faults
+    blink <type> <id> [seconds]     blink a device's LED to find it
+    self-test <type> <id>           check a motor controller's health
+    telemetry <type> <id> [ms]      print a motor controller's outputs and sensors as CSV
+    top [ms]                        show a live table of every device that responds
+    convert-log <file> csv|json     print a binary telemetry log as CSV or JSON lines
+
+<type> is talon, victor, pigeon or canifier.";
+
+const TIMEOUT_MS: i32 = 50;
+const DEVICE_TYPES: [DeviceType; 4] = [
+    DeviceType::TalonSRX,
+    DeviceType::VictorSPX,
+    DeviceType::PigeonIMU,
+    DeviceType::CANifier,
+];
+
+type CliResult = Result<(), Box<dyn Error>>;
+
+enum Device {
+    TalonSRX(TalonSRX),
+    VictorSPX(VictorSPX),
+    PigeonIMU(PigeonIMU),
+    CANifier(CANifier),
+}
+
+impl Device {
+    fn new(device_type: DeviceType, id: DeviceId) -> Device {
+        match device_type {
+            DeviceType::TalonSRX => Device::TalonSRX(TalonSRX::new(id)),
+            DeviceType::VictorSPX => Device::VictorSPX(VictorSPX::new(id)),
+            DeviceType::PigeonIMU => Device::PigeonIMU(PigeonIMU::new(id)),
+            DeviceType::CANifier => Device::CANifier(CANifier::new(id)),
+        }
+    }
+    fn sticky_faults(&self) -> &dyn StickyFaultDevice {
+        match *self {
+            Device::TalonSRX(ref talon) => talon,
+            Device::VictorSPX(ref victor) => victor,
+            Device::PigeonIMU(ref pigeon) => pigeon,
+            Device::CANifier(ref canifier) => canifier,
+        }
+    }
+    fn firmware_version(&self) -> ctre::Result<FirmwareVersion> {
+        match *self {
+            Device::TalonSRX(ref talon) => talon.get_firmware_version(),
+            Device::VictorSPX(ref victor) => victor.get_firmware_version(),
+            Device::PigeonIMU(ref pigeon) => pigeon.get_firmware_version(),
+            Device::CANifier(ref canifier) => canifier.get_firmware_version(),
+        }
+    }
+}
+
+/// Runs `$body` with `$motor` bound to the motor controller `$device`,
+/// or fails if it isn't one.
+macro_rules! with_motor {
+    ($device:expr, |$motor:ident| $body:expr) => {
+        match $device {
+            Device::TalonSRX(ref $motor) => $body,
+            Device::VictorSPX(ref $motor) => $body,
+            _ => Err("not a motor controller".into()),
+        }
+    };
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    if let Err(err) = run(&args) {
+        eprintln!("ctre-cli: {}", err);
+        process::exit(1);
+    }
+}
+
+fn run(args: &[&str]) -> CliResult {
+    match *args {
+        ["list"] => list(),
+        ["apply", path] => apply(path),
+        ["dump", ty, id] => with_motor!(device(ty, id)?, |motor| dump(motor)),
+        ["clear-faults", ty, id] => clear_faults(&device(ty, id)?),
+        ["blink", ty, id] => blink(&device(ty, id)?, 5),
+        ["blink", ty, id, seconds] => blink(&device(ty, id)?, seconds.parse()?),
+        ["self-test", ty, id] => with_motor!(device(ty, id)?, |motor| self_test(motor)),
+        ["telemetry", ty, id] => with_motor!(device(ty, id)?, |motor| telemetry(motor, 100)),
+        ["telemetry", ty, id, ms] => {
+            let ms = ms.parse()?;
+            with_motor!(device(ty, id)?, |motor| telemetry(motor, ms))
+        }
+        ["top"] => top(250),
+        ["top", ms] => top(ms.parse()?),
+        ["convert-log", path, format] => convert_log(path, format),
+        ["help"] | ["--help"] | ["-h"] => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        _ => {
+            eprintln!("{}", USAGE);
+            process::exit(2);
+        }
+    }
+}
+
+fn device(ty: &str, id: &str) -> Result<Device, Box<dyn Error>> {
+    let device_type = match ty.to_ascii_lowercase().as_str() {
+        "talon" | "talonsrx" => DeviceType::TalonSRX,
+        "victor" | "victorspx" => DeviceType::VictorSPX,
+        "pigeon" | "pigeonimu" => DeviceType::PigeonIMU,
+        "canifier" => DeviceType::CANifier,
+        _ => return Err(format!("unknown device type {:?}", ty).into()),
+    };
+    let id = DeviceId::try_from(id.parse::<i32>()?)?;
+    Ok(Device::new(device_type, id))
+}
+
+/// Every device that responds.
+fn connected_devices() -> Vec<(DeviceType, DeviceId, Device)> {
+    let mut devices = Vec::new();
+    for &device_type in &DEVICE_TYPES {
+        for id in 0..=DeviceId::MAX {
+            let id = DeviceId::new(id);
+            let device = Device::new(device_type, id);
+            if device.sticky_faults().is_connected() {
+                devices.push((device_type, id, device));
+            }
+        }
+    }
+    devices
+}
+
+fn list() -> CliResult {
+    for (device_type, id, device) in connected_devices() {
+        match device.firmware_version() {
+            Ok(version) => println!("{} {}\tfirmware {}", device_type, id, version),
+            Err(_) => println!("{} {}", device_type, id),
+        }
+    }
+    Ok(())
+}
+
+fn apply(path: &str) -> CliResult {
+    let devices = ctre::load_robot_config(path)?;
+    for spec in devices.specs() {
+        println!(
+            "{} {}\t{}\tconfigured",
+            spec.device_type, spec.id, spec.name
+        );
+    }
+    Ok(())
+}
+
+/// Parameters printed by `dump`, with the ordinals to read each at.
+const DUMP_PARAMS: &[(ParamEnum, &[i32])] = &[
+    (ParamEnum::OpenloopRamp, &[0]),
+    (ParamEnum::ClosedloopRamp, &[0]),
+    (ParamEnum::NeutralDeadband, &[0]),
+    (ParamEnum::PeakPosOutput, &[0]),
+    (ParamEnum::PeakNegOutput, &[0]),
+    (ParamEnum::NominalPosOutput, &[0]),
+    (ParamEnum::NominalNegOutput, &[0]),
+    (ParamEnum::NominalBatteryVoltage, &[0]),
+    (ParamEnum::FeedbackSensorType, &[0, 1]),
+    (ParamEnum::SelectedSensorCoefficient, &[0, 1]),
+    (ParamEnum::PIDLoopPolarity, &[0, 1]),
+    (ParamEnum::ProfileParamSlot_P, &[0, 1, 2, 3]),
+    (ParamEnum::ProfileParamSlot_I, &[0, 1, 2, 3]),
+    (ParamEnum::ProfileParamSlot_D, &[0, 1, 2, 3]),
+    (ParamEnum::ProfileParamSlot_F, &[0, 1, 2, 3]),
+    (ParamEnum::ProfileParamSlot_IZone, &[0, 1, 2, 3]),
+    (ParamEnum::ProfileParamSlot_AllowableErr, &[0, 1, 2, 3]),
+    (ParamEnum::ProfileParamSlot_MaxIAccum, &[0, 1, 2, 3]),
+    (ParamEnum::ProfileParamSlot_PeakOutput, &[0, 1, 2, 3]),
+    (ParamEnum::ForwardSoftLimitThreshold, &[0]),
+    (ParamEnum::ReverseSoftLimitThreshold, &[0]),
+    (ParamEnum::ForwardSoftLimitEnable, &[0]),
+    (ParamEnum::ReverseSoftLimitEnable, &[0]),
+    (ParamEnum::ContinuousCurrentLimitAmps, &[0]),
+    (ParamEnum::PeakCurrentLimitAmps, &[0]),
+    (ParamEnum::PeakCurrentLimitMs, &[0]),
+    (ParamEnum::MotMag_VelCruise, &[0]),
+    (ParamEnum::MotMag_Accel, &[0]),
+    (ParamEnum::LimitSwitchSource, &[0, 1]),
+    (ParamEnum::LimitSwitchNormClosedAndDis, &[0, 1]),
+];
+
+fn dump<M: BaseMotorController + Sync>(motor: &M) -> CliResult {
+    for ordinal in 0..4 {
+        let params: Vec<_> = DUMP_PARAMS
+            .iter()
+            .filter(|(_, ordinals)| ordinals.contains(&ordinal))
+            .map(|&(param, _)| param)
+            .collect();
+        let values = motor.bulk_get_parameters(&params, ordinal, TIMEOUT_MS);
+        for param in params {
+            match values[&param] {
+                Ok(value) => println!("{}[{}] = {}", param, ordinal, value),
+                Err(err) => println!("{}[{}] unavailable: {}", param, ordinal, err.code),
+            }
+        }
+    }
+    Ok(())
+}
+
+fn clear_faults(device: &Device) -> CliResult {
+    let device = device.sticky_faults();
+    println!("{}", device.read_sticky_faults()?);
+    device.clear_all_sticky_faults(TIMEOUT_MS)?;
+    Ok(())
+}
+
+/// Blinks the brake/coast LED of a motor controller, or the LEDs of a CANifier.
+fn blink(device: &Device, seconds: u64) -> CliResult {
+    let period = Duration::from_millis(250);
+    let start = Instant::now();
+    let mut on = false;
+    while start.elapsed() < Duration::from_secs(seconds) {
+        on = !on;
+        let mode = if on {
+            NeutralMode::Brake
+        } else {
+            NeutralMode::Coast
+        };
+        match *device {
+            Device::TalonSRX(ref talon) => talon.set_neutral_mode(mode),
+            Device::VictorSPX(ref victor) => victor.set_neutral_mode(mode),
+            Device::CANifier(ref canifier) => {
+                let output = if on { 1. } else { 0. };
+                for &channel in &[LEDChannel::A, LEDChannel::B, LEDChannel::C] {
+                    canifier.set_led_output(output, channel)?;
+                }
+            }
+            Device::PigeonIMU(_) => return Err("a Pigeon IMU has no LED to blink".into()),
+        }
+        thread::sleep(period);
+    }
+    // Leave the neutral mode to the jumper again.
+    match *device {
+        Device::TalonSRX(ref talon) => talon.set_neutral_mode(NeutralMode::EEPROMSetting),
+        Device::VictorSPX(ref victor) => victor.set_neutral_mode(NeutralMode::EEPROMSetting),
+        _ => {}
+    }
+    Ok(())
+}
+
+fn self_test<M: BaseMotorController>(motor: &M) -> CliResult {
+    let mut failures = 0;
+    let mut check = |name: &str, result: Result<String, String>| match result {
+        Ok(detail) => println!("PASS  {:<16}{}", name, detail),
+        Err(detail) => {
+            println!("FAIL  {:<16}{}", name, detail);
+            failures += 1;
+        }
+    };
+    let connected = motor.is_connected();
+    check(
+        "connected",
+        if connected {
+            Ok(String::new())
+        } else {
+            Err("not responding".to_owned())
+        },
+    );
+    if !connected {
+        return Err("self-test failed".into());
+    }
+    check(
+        "firmware",
+        motor
+            .get_firmware_version()
+            .map(|version| version.to_string())
+            .map_err(|err| err.to_string()),
+    );
+    check(
+        "bus voltage",
+        match motor.get_bus_voltage() {
+            Ok(volts) if volts >= 10. => Ok(format!("{:.1} V", volts)),
+            Ok(volts) => Err(format!("{:.1} V is low", volts)),
+            Err(err) => Err(err.to_string()),
+        },
+    );
+    check(
+        "temperature",
+        match motor.get_temperature() {
+            Ok(celsius) if celsius < 80. => Ok(format!("{:.0} °C", celsius)),
+            Ok(celsius) => Err(format!("{:.0} °C is hot", celsius)),
+            Err(err) => Err(err.to_string()),
+        },
+    );
+    check(
+        "faults",
+        match motor.get_faults() {
+            Ok(faults) if !faults.has_any_fault() => Ok(String::new()),
+            Ok(faults) => Err(format!("{:?}", faults)),
+            Err(err) => Err(err.to_string()),
+        },
+    );
+    check(
+        "sticky faults",
+        match motor.read_sticky_faults() {
+            Ok(record) if !record.has_any_fault() => Ok(String::new()),
+            Ok(record) => Err(record.to_string()),
+            Err(err) => Err(err.to_string()),
+        },
+    );
+    check(
+        "sensor",
+        motor
+            .get_selected_sensor_position(0)
+            .map(|position| format!("position {}", position))
+            .map_err(|err| err.to_string()),
+    );
+    if failures > 0 {
+        return Err(format!("{} checks failed", failures).into());
+    }
+    Ok(())
+}
+
+/// Prints a CSV line every `period_ms` until interrupted.
+fn telemetry<M: BaseMotorController>(motor: &M, period_ms: u64) -> CliResult {
+    println!("time_ms,bus_voltage,output_percent,current_a,temperature_c,position,velocity");
+    let start = Instant::now();
+    loop {
+        println!(
+            "{},{:.2},{:.3},{:.2},{:.1},{},{}",
+            start.elapsed().as_millis(),
+            motor.get_bus_voltage()?,
+            motor.get_motor_output_percent()?,
+            motor.get_output_current()?,
+            motor.get_temperature()?,
+            motor.get_selected_sensor_position(0)?,
+            motor.get_selected_sensor_velocity(0)?,
+        );
+        thread::sleep(Duration::from_millis(period_ms));
+    }
+}
+
+type FaultFlag = fn(Faults) -> bool;
+
+/// Short names of the motor controller faults, in bit order.
+const MOTOR_FAULTS: [(&str, FaultFlag); 11] = [
+    ("UV", Faults::under_voltage),
+    ("FwdLim", Faults::forward_limit_switch),
+    ("RevLim", Faults::reverse_limit_switch),
+    ("FwdSoft", Faults::forward_soft_limit),
+    ("RevSoft", Faults::reverse_soft_limit),
+    ("HW", Faults::hardware_failure),
+    ("Reset", Faults::reset_during_en),
+    ("Overflow", Faults::sensor_overflow),
+    ("Phase", Faults::sensor_out_of_phase),
+    ("ESD", Faults::hardware_esd_reset),
+    ("RemoteLoss", Faults::remote_loss_of_signal),
+];
+
+/// Formats a reading, or `-` if it couldn't be read.
+fn cell<T: std::fmt::Display>(value: ctre::Result<T>, precision: usize) -> String {
+    match value {
+        Ok(value) => format!("{:.*}", precision, value),
+        Err(_) => "-".to_owned(),
+    }
+}
+
+fn motor_row<M: BaseMotorController>(motor: &M, row: &mut [String; 8]) {
+    row[1] = cell(motor.get_motor_output_percent().map(|out| out * 100.), 1);
+    row[2] = cell(motor.get_output_current(), 1);
+    row[3] = cell(motor.get_temperature(), 0);
+    row[4] = cell(motor.get_bus_voltage(), 1);
+    row[5] = cell(motor.get_selected_sensor_position(0), 0);
+    row[6] = cell(motor.get_selected_sensor_velocity(0), 0);
+    row[7] = match motor.get_faults() {
+        Ok(faults) => {
+            let names: Vec<_> = MOTOR_FAULTS
+                .iter()
+                .filter(|&&(_, is_set)| is_set(faults))
+                .map(|&(name, _)| name)
+                .collect();
+            names.join(",")
+        }
+        Err(_) => "-".to_owned(),
+    };
+}
+
+/// The cells of a device's row in `top`: output (%), current, temperature, bus
+/// voltage, position, velocity and faults.  Cells a device has no reading for
+/// are left empty.
+fn top_row(device_type: DeviceType, id: DeviceId, device: &Device) -> [String; 8] {
+    let mut row: [String; 8] = Default::default();
+    row[0] = format!("{} {}", device_type, id);
+    if !device.sticky_faults().is_connected() {
+        row[7] = "not responding".to_owned();
+        return row;
+    }
+    match *device {
+        Device::TalonSRX(ref talon) => motor_row(talon, &mut row),
+        Device::VictorSPX(ref victor) => motor_row(victor, &mut row),
+        Device::PigeonIMU(ref pigeon) => {
+            // The Pigeon's heading goes in the position column.
+            row[5] = cell(pigeon.get_yaw_pitch_roll().map(|ypr| ypr[0]), 1);
+            row[7] = match pigeon.get_faults() {
+                Ok(faults) if faults.has_any_fault() => "fault".to_owned(),
+                Ok(_) => String::new(),
+                Err(_) => "-".to_owned(),
+            };
+        }
+        Device::CANifier(ref canifier) => {
+            row[4] = cell(canifier.get_bus_voltage(), 1);
+            row[5] = cell(canifier.get_quadrature_position(), 0);
+            row[6] = cell(canifier.get_quadrature_velocity(), 0);
+            row[7] = match canifier.get_faults() {
+                Ok(faults) if faults.has_any_fault() => "fault".to_owned(),
+                Ok(_) => String::new(),
+                Err(_) => "-".to_owned(),
+            };
+        }
+    }
+    if device
+        .sticky_faults()
+        .read_sticky_faults()
+        .is_ok_and(|r| r.has_any_fault())
+    {
+        row[7].push_str(" (sticky)");
+    }
+    row
+}
+
+/**
+ * Redraws a table of every device's readings every `period_ms` until
+ * interrupted.
+ *
+ * Only plain ANSI escapes are used, so this works over SSH in any terminal.
+ * Devices are found once at startup; a device that stops responding stays in
+ * the table, marked as such.
+ */
+fn top(period_ms: u64) -> CliResult {
+    const HEADER: [&str; 8] = [
+        "device", "out %", "amps", "°C", "volts", "position", "velocity", "faults",
+    ];
+    const WIDTHS: [usize; 7] = [16, 8, 8, 6, 7, 12, 10];
+    let devices = connected_devices();
+    let stdout = io::stdout();
+    let start = Instant::now();
+    let mut frame = String::new();
+    loop {
+        frame.clear();
+        // Move the cursor home and clear the screen.
+        frame.push_str("\x1b[H\x1b[2J");
+        writeln!(
+            frame,
+            "ctre-cli top: {} devices, {:.1}s, every {}ms (Ctrl-C to quit)\n",
+            devices.len(),
+            start.elapsed().as_secs_f64(),
+            period_ms
+        )?;
+        let rows = devices
+            .iter()
+            .map(|(ty, id, device)| top_row(*ty, *id, device));
+        for row in Some(HEADER.map(str::to_owned)).into_iter().chain(rows) {
+            write!(frame, "{:<1$}", row[0], WIDTHS[0])?;
+            for (cell, &width) in row[1..7].iter().zip(&WIDTHS[1..]) {
+                write!(frame, "{:>1$}", cell, width)?;
+            }
+            writeln!(frame, "  {}", row[7])?;
+        }
+        let mut stdout = stdout.lock();
+        stdout.write_all(frame.as_bytes())?;
+        stdout.flush()?;
+        drop(stdout);
+        thread::sleep(Duration::from_millis(period_ms));
+    }
+}
+
+fn convert_log(path: &str, format: &str) -> CliResult {
+    let mut reader = LogReader::new(io::BufReader::new(File::open(path)?))?;
+    let stdout = io::stdout();
+    let out = io::BufWriter::new(stdout.lock());
+    match format {
+        "csv" => reader.to_csv(out)?,
+        "json" => reader.to_json(out)?,
+        _ => return Err(format!("unknown format {:?}, expected csv or json", format).into()),
+    }
+    Ok(())
+}
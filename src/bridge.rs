@@ -0,0 +1,627 @@
+//! Serving device state to co-processors and dashboards over the network.
+//!
+//! A vision co-processor or a dashboard often wants to know how the robot's
+//! devices are doing, but linking Phoenix on a Raspberry Pi is a chore.  A
+//! [`Bridge`] serves a restricted, read-mostly API on a TCP socket instead:
+//! each line sent is a JSON request, answered by one line of JSON.
+//!
+//! ```text
+//! {"request": "list"}
+//! {"request": "telemetry", "name": "intake"}
+//! {"request": "config", "type": "TalonSRX", "id": 3, "param": "ProfileParamSlot_P"}
+//! {"request": "blink", "name": "intake", "seconds": 2}
+//! {"request": "clear_sticky_faults", "name": "intake"}
+//! ```
+//!
+//! Devices are given by `name`, or by `type` and `id`.  Only devices the robot
+//! program has created (see [`registry`]) can be reached.  A `config`
+//! request's `ordinal`, e.g. the gain slot, defaults to 0, and a `blink` lasts
+//! 2 seconds unless given `seconds`.  Responses are `{"ok": ...}` or
+//! `{"error": "..."}`.
+//!
+//! ```no_run
+//! # use ctre::bridge::{Bridge, DEFAULT_PORT};
+//! # fn main() -> std::io::Result<()> {
+//! Bridge::bind(("0.0.0.0", DEFAULT_PORT))?.spawn()?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Built with the `bridge` feature.
+//!
+//! [`Bridge`]: struct.Bridge.html
+//! [`registry`]: ../registry/fn.registry.html
+
+use std::io::{self, ErrorKind, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde_json::{self, json, Value};
+
+use canifier::{CANifier, LEDChannel};
+use error::{DeviceType, ErrorStats};
+use faults::StickyFaultDevice;
+use motor_control::{BaseMotorController, NeutralMode, TalonSRX, VictorSPX};
+use registry::registry;
+use sensors::pigeon::PigeonIMU;
+use ParamEnum;
+
+/// FRC reserves ports 5800 to 5810 for teams; the bridge uses the last.
+pub const DEFAULT_PORT: u16 = 5810;
+
+const TIMEOUT_MS: i32 = 10;
+/// How often the bridge checks for requests.
+const POLL_PERIOD: Duration = Duration::from_millis(20);
+/// How long a blinking LED stays on or off.
+const BLINK_PERIOD: Duration = Duration::from_millis(250);
+/// The longest request line; a client sending a longer one is dropped.
+const MAX_LINE: usize = 4096;
+/// The most response bytes a client may leave unread; past this it is dropped.
+const MAX_PENDING: usize = 64 * 1024;
+
+/// A request, one per line.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "request", rename_all = "snake_case")]
+enum Request {
+    List,
+    Telemetry {
+        #[serde(flatten)]
+        device: DeviceRef,
+    },
+    Config {
+        #[serde(flatten)]
+        device: DeviceRef,
+        param: String,
+        #[serde(default)]
+        ordinal: i32,
+    },
+    Blink {
+        #[serde(flatten)]
+        device: DeviceRef,
+        #[serde(default = "default_blink_seconds")]
+        seconds: f64,
+    },
+    ClearStickyFaults {
+        #[serde(flatten)]
+        device: DeviceRef,
+    },
+}
+
+fn default_blink_seconds() -> f64 {
+    2.
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+enum DeviceRef {
+    Name {
+        name: String,
+    },
+    Id {
+        #[serde(rename = "type")]
+        device_type: DeviceType,
+        id: i32,
+    },
+}
+
+/// A device's outputs and sensors, as returned by a `telemetry` request.
+/// Readings the device doesn't have, or that couldn't be read, are left out.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Telemetry {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bus_voltage: Option<f64>,
+    /// In °C.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_percent: Option<f64>,
+    /// In amps.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_current: Option<f64>,
+    /// A motor controller's selected sensor, or a CANifier's quadrature input, in native units.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub position: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub velocity: Option<i32>,
+    /// A Pigeon's yaw, pitch and roll in degrees.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub yaw_pitch_roll: Option<[f64; 3]>,
+    /// Whether the device was created optional and isn't on the bus.
+    pub absent: bool,
+    pub error_stats: ErrorStats,
+}
+
+enum Device {
+    TalonSRX(TalonSRX),
+    VictorSPX(VictorSPX),
+    PigeonIMU(PigeonIMU),
+    CANifier(CANifier),
+}
+
+impl Device {
+    /// The robot program's device, if it hasn't been dropped, sharing its handle and state.
+    fn find(device_type: DeviceType, id: i32) -> Option<Device> {
+        let state = registry().state(device_type, id)?;
+        Some(match device_type {
+            DeviceType::TalonSRX => Device::TalonSRX(TalonSRX::from_state(id, state)),
+            DeviceType::VictorSPX => Device::VictorSPX(VictorSPX::from_state(id, state)),
+            DeviceType::PigeonIMU => Device::PigeonIMU(PigeonIMU::from_state(id, state)),
+            DeviceType::CANifier => Device::CANifier(CANifier::from_state(id, state)),
+        })
+    }
+    fn sticky_faults(&self) -> &dyn StickyFaultDevice {
+        match *self {
+            Device::TalonSRX(ref talon) => talon,
+            Device::VictorSPX(ref victor) => victor,
+            Device::PigeonIMU(ref pigeon) => pigeon,
+            Device::CANifier(ref canifier) => canifier,
+        }
+    }
+    fn telemetry(&self) -> Telemetry {
+        fn motor<M: BaseMotorController>(motor: &M) -> Telemetry {
+            Telemetry {
+                bus_voltage: motor.get_bus_voltage().ok(),
+                temperature: motor.get_temperature().ok(),
+                output_percent: motor.get_motor_output_percent().ok(),
+                output_current: motor.get_output_current().ok(),
+                position: motor.get_selected_sensor_position(0).ok(),
+                velocity: motor.get_selected_sensor_velocity(0).ok(),
+                error_stats: motor.error_stats(),
+                ..Default::default()
+            }
+        }
+        match *self {
+            Device::TalonSRX(ref talon) => motor(talon),
+            Device::VictorSPX(ref victor) => motor(victor),
+            Device::PigeonIMU(ref pigeon) => Telemetry {
+                temperature: pigeon.get_temp().ok(),
+                yaw_pitch_roll: pigeon.get_yaw_pitch_roll().ok(),
+                error_stats: pigeon.error_stats(),
+                ..Default::default()
+            },
+            Device::CANifier(ref canifier) => Telemetry {
+                bus_voltage: canifier.get_bus_voltage().ok(),
+                position: canifier.get_quadrature_position().ok(),
+                velocity: canifier.get_quadrature_velocity().ok(),
+                error_stats: canifier.error_stats(),
+                ..Default::default()
+            },
+        }
+    }
+    fn config_get_parameter(&self, param: ParamEnum, ordinal: i32) -> ::Result<f64> {
+        match *self {
+            Device::TalonSRX(ref talon) => talon.config_get_parameter(param, ordinal, TIMEOUT_MS),
+            Device::VictorSPX(ref victor) => {
+                victor.config_get_parameter(param, ordinal, TIMEOUT_MS)
+            }
+            Device::PigeonIMU(ref pigeon) => {
+                pigeon.config_get_parameter(param, ordinal, TIMEOUT_MS)
+            }
+            Device::CANifier(ref canifier) => {
+                canifier.config_get_parameter(param, ordinal, TIMEOUT_MS)
+            }
+        }
+    }
+    /// Turns the device's LED on or off: a motor controller's brake/coast LED,
+    /// or all of a CANifier's.
+    fn set_led(&self, on: bool) -> ::Result<()> {
+        let mode = if on {
+            NeutralMode::Brake
+        } else {
+            NeutralMode::Coast
+        };
+        match *self {
+            Device::TalonSRX(ref talon) => talon.set_neutral_mode(mode),
+            Device::VictorSPX(ref victor) => victor.set_neutral_mode(mode),
+            Device::CANifier(ref canifier) => {
+                let output = if on { 1. } else { 0. };
+                for &channel in &[LEDChannel::A, LEDChannel::B, LEDChannel::C] {
+                    canifier.set_led_output(output, channel)?;
+                }
+            }
+            Device::PigeonIMU(_) => {}
+        }
+        Ok(())
+    }
+    /// Hands the LED back to the device once blinking is done.
+    fn restore_led(&self) -> ::Result<()> {
+        match *self {
+            Device::TalonSRX(ref talon) => talon.set_neutral_mode(NeutralMode::EEPROMSetting),
+            Device::VictorSPX(ref victor) => victor.set_neutral_mode(NeutralMode::EEPROMSetting),
+            Device::CANifier(_) => self.set_led(false)?,
+            Device::PigeonIMU(_) => {}
+        }
+        Ok(())
+    }
+}
+
+struct Blink {
+    device: (DeviceType, i32),
+    until: Instant,
+    next_toggle: Instant,
+    on: bool,
+}
+
+struct Client {
+    stream: TcpStream,
+    /// Bytes received but not yet making up a whole line.
+    received: Vec<u8>,
+    /// Responses not yet taken by the client.
+    pending: Vec<u8>,
+}
+
+/**
+ * A server answering requests about the robot's devices.  See the
+ * [module documentation](index.html) for the protocol.
+ *
+ * Every client is served from one thread, which reaches devices through the
+ * registry, so a device the robot program drops can no longer be reached.
+ * Blinking a motor controller's LED toggles its neutral mode, and leaves it at
+ * the EEPROM setting (the B/C jumper) afterwards.
+ *
+ * Responses are sent as each client takes them, so a slow client doesn't hold
+ * up the others.  A client sending a line longer than 4 KiB, or leaving more
+ * than 64 KiB of responses unread, is disconnected.  LEDs still blinking when
+ * the bridge is dropped are restored.
+ */
+pub struct Bridge {
+    listener: TcpListener,
+    clients: Vec<Client>,
+    blinks: Vec<Blink>,
+}
+
+impl Bridge {
+    /// Listens on `addr`, e.g. `("0.0.0.0", DEFAULT_PORT)`.
+    pub fn bind<A: ToSocketAddrs>(addr: A) -> io::Result<Bridge> {
+        let listener = TcpListener::bind(addr)?;
+        listener.set_nonblocking(true)?;
+        Ok(Bridge::with_listener(listener))
+    }
+    fn with_listener(listener: TcpListener) -> Bridge {
+        Bridge {
+            listener,
+            clients: Vec::new(),
+            blinks: Vec::new(),
+        }
+    }
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Serves requests on a new thread, `ctre-bridge`, for the rest of the program.
+    pub fn spawn(self) -> io::Result<thread::JoinHandle<io::Result<()>>> {
+        self.spawn_until(Arc::new(AtomicBool::new(false)))
+    }
+    /// Serves requests on a new thread until `stop` is set, for `ctre::shutdown`.
+    pub(crate) fn spawn_until(
+        self,
+        stop: Arc<AtomicBool>,
+    ) -> io::Result<thread::JoinHandle<io::Result<()>>> {
+        thread::Builder::new()
+            .name("ctre-bridge".to_owned())
+            .spawn(move || self.serve_until(&stop))
+    }
+    /// Serves requests on this thread.  Only returns if the listening socket fails.
+    pub fn serve(self) -> io::Result<()> {
+        self.serve_until(&AtomicBool::new(false))
+    }
+    fn serve_until(mut self, stop: &AtomicBool) -> io::Result<()> {
+        while !stop.load(Ordering::Relaxed) {
+            self.poll()?;
+            thread::sleep(POLL_PERIOD);
+        }
+        Ok(())
+    }
+
+    /**
+     * Accepts new clients, answers every complete request and advances blinking
+     * LEDs.  Only returns an error if the listening socket has failed; a
+     * connection that fails is dropped.
+     */
+    fn poll(&mut self) -> io::Result<()> {
+        loop {
+            match self.listener.accept() {
+                Ok((stream, _)) => {
+                    if stream.set_nonblocking(true).is_ok() {
+                        self.clients.push(Client {
+                            stream,
+                            received: Vec::new(),
+                            pending: Vec::new(),
+                        });
+                    }
+                }
+                Err(ref err) if err.kind() == ErrorKind::Interrupted => {}
+                Err(err) if listener_failed(&err) => return Err(err),
+                // e.g. the connection was reset before it was accepted, or
+                // the process is out of file descriptors for now.
+                Err(_) => break,
+            }
+        }
+        let mut clients = std::mem::take(&mut self.clients);
+        clients.retain_mut(|client| self.serve_client(client).is_ok());
+        self.clients = clients;
+        self.blink_leds();
+        Ok(())
+    }
+
+    /**
+     * Answers a client's complete requests, and sends it as much of its
+     * responses as it will take without blocking.  An error means the client
+     * has gone, or is to be dropped.
+     *
+     * At most `MAX_LINE` bytes are read at a time, leaving the rest for the
+     * next poll.
+     */
+    fn serve_client(&mut self, client: &mut Client) -> io::Result<()> {
+        let mut buf = [0; 512];
+        while client.received.len() < MAX_LINE {
+            let room = (MAX_LINE - client.received.len()).min(buf.len());
+            match client.stream.read(&mut buf[..room]) {
+                Ok(0) => return Err(ErrorKind::UnexpectedEof.into()),
+                Ok(len) => client.received.extend_from_slice(&buf[..len]),
+                Err(ref err) if err.kind() == ErrorKind::WouldBlock => break,
+                Err(ref err) if err.kind() == ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+        while let Some(end) = client.received.iter().position(|&byte| byte == b'\n') {
+            let line: Vec<u8> = client.received.drain(..=end).collect();
+            let line = String::from_utf8_lossy(&line);
+            if line.trim().is_empty() {
+                continue;
+            }
+            let response = self.respond(&line).to_string();
+            client.pending.extend_from_slice(response.as_bytes());
+            client.pending.push(b'\n');
+        }
+        if client.received.len() >= MAX_LINE {
+            return Err(io::Error::new(
+                ErrorKind::InvalidData,
+                "request line too long",
+            ));
+        }
+        let mut sent = 0;
+        while sent < client.pending.len() {
+            match client.stream.write(&client.pending[sent..]) {
+                Ok(0) => return Err(ErrorKind::WriteZero.into()),
+                Ok(len) => sent += len,
+                Err(ref err) if err.kind() == ErrorKind::WouldBlock => break,
+                Err(ref err) if err.kind() == ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+        client.pending.drain(..sent);
+        if client.pending.len() > MAX_PENDING {
+            return Err(io::Error::new(
+                ErrorKind::OutOfMemory,
+                "too many responses left unread",
+            ));
+        }
+        Ok(())
+    }
+
+    fn respond(&mut self, line: &str) -> Value {
+        let request = match serde_json::from_str(line) {
+            Ok(request) => request,
+            Err(err) => return json!({ "error": format!("invalid request: {}", err) }),
+        };
+        match self.handle(request) {
+            Ok(value) => json!({ "ok": value }),
+            Err(message) => json!({ "error": message }),
+        }
+    }
+
+    fn handle(&mut self, request: Request) -> Result<Value, String> {
+        match request {
+            Request::List => {
+                let devices: Vec<Value> = registry()
+                    .devices()
+                    .iter()
+                    .map(|device| {
+                        json!({
+                            "type": device.device_type,
+                            "id": device.id,
+                            "name": device.name,
+                        })
+                    })
+                    .collect();
+                Ok(Value::from(devices))
+            }
+            Request::Telemetry { device } => {
+                let (device_type, id) = self.key(&device)?;
+                let mut telemetry = self.device(&device)?.telemetry();
+                telemetry.absent = registry().is_absent(device_type, id);
+                serde_json::to_value(telemetry).map_err(|err| err.to_string())
+            }
+            Request::Config {
+                device,
+                param,
+                ordinal,
+            } => {
+                let param: ParamEnum = param.parse().map_err(|err| format!("{}", err))?;
+                let value = self.device(&device)?.config_get_parameter(param, ordinal);
+                value.map(Value::from).map_err(|err| err.to_string())
+            }
+            Request::Blink { device, seconds } => {
+                if !(seconds > 0. && seconds <= 60.) {
+                    return Err("seconds must be more than 0 and at most 60".to_owned());
+                }
+                let key = self.key(&device)?;
+                if key.0 == DeviceType::PigeonIMU {
+                    return Err("a Pigeon IMU has no LED to blink".to_owned());
+                }
+                self.device(&device)?;
+                let now = Instant::now();
+                self.blinks.retain(|blink| blink.device != key);
+                self.blinks.push(Blink {
+                    device: key,
+                    until: now + Duration::from_secs_f64(seconds),
+                    next_toggle: now,
+                    on: false,
+                });
+                Ok(Value::Null)
+            }
+            Request::ClearStickyFaults { device } => {
+                let device = self.device(&device)?;
+                device
+                    .sticky_faults()
+                    .clear_all_sticky_faults(TIMEOUT_MS)
+                    .map_err(|err| err.to_string())?;
+                Ok(Value::Null)
+            }
+        }
+    }
+
+    /// The type and id of a device the robot program has created.
+    fn key(&self, device: &DeviceRef) -> Result<(DeviceType, i32), String> {
+        let info = match *device {
+            DeviceRef::Name { ref name } => registry().find(name),
+            DeviceRef::Id { device_type, id } => registry().get(device_type, id),
+        };
+        match info {
+            Some(info) => Ok((info.device_type, info.id)),
+            None => Err("no such device".to_owned()),
+        }
+    }
+    fn device(&self, device: &DeviceRef) -> Result<Device, String> {
+        let (device_type, id) = self.key(device)?;
+        Device::find(device_type, id).ok_or_else(|| "no such device".to_owned())
+    }
+
+    fn blink_leds(&mut self) {
+        let now = Instant::now();
+        self.blinks.retain_mut(|blink| {
+            let device = match Device::find(blink.device.0, blink.device.1) {
+                Some(device) => device,
+                None => return false,
+            };
+            if now >= blink.until {
+                let _ = device.restore_led();
+                return false;
+            }
+            if now >= blink.next_toggle {
+                blink.on = !blink.on;
+                blink.next_toggle = now + BLINK_PERIOD;
+                let _ = device.set_led(blink.on);
+            }
+            true
+        });
+    }
+}
+
+impl Drop for Bridge {
+    fn drop(&mut self) {
+        for blink in self.blinks.drain(..) {
+            if let Some(device) = Device::find(blink.device.0, blink.device.1) {
+                let _ = device.restore_led();
+            }
+        }
+    }
+}
+
+/// Whether an `accept` error means the listening socket itself is broken,
+/// rather than that one connection failed or resources are short for now.
+fn listener_failed(err: &io::Error) -> bool {
+    matches!(err.kind(), ErrorKind::InvalidInput | ErrorKind::Unsupported)
+}
+
+#[test]
+fn bridge_answers_requests() {
+    use motor_control::shared_state;
+    use std::io::{BufRead, BufReader};
+    use DeviceId;
+    let talon = TalonSRX::new(DeviceId::new(51));
+    talon.set_name("bridge_arm");
+    let mut bridge = Bridge::bind("127.0.0.1:0").unwrap();
+    let stream = TcpStream::connect(bridge.local_addr().unwrap()).unwrap();
+    let mut responses = BufReader::new(stream.try_clone().unwrap());
+    let mut request = |bridge: &mut Bridge, line: &str| -> Value {
+        (&stream)
+            .write_all(format!("{}\n", line).as_bytes())
+            .unwrap();
+        bridge.poll().unwrap();
+        let mut response = String::new();
+        responses.read_line(&mut response).unwrap();
+        serde_json::from_str(&response).unwrap()
+    };
+
+    let list = request(&mut bridge, r#"{"request": "list"}"#);
+    let devices = list["ok"].as_array().unwrap();
+    assert!(devices.contains(&json!({"type": "TalonSRX", "id": 51, "name": "bridge_arm"})));
+
+    let telemetry = request(
+        &mut bridge,
+        r#"{"request": "telemetry", "name": "bridge_arm"}"#,
+    );
+    assert_eq!(telemetry["ok"]["temperature"], json!(25.));
+    assert_eq!(telemetry["ok"]["yaw_pitch_roll"], Value::Null);
+
+    let kp = request(
+        &mut bridge,
+        r#"{"request": "config", "type": "TalonSRX", "id": 51, "param": "ProfileParamSlot_P"}"#,
+    );
+    assert_eq!(kp, json!({"ok": 0.}));
+    let blink = request(&mut bridge, r#"{"request": "blink", "name": "bridge_arm"}"#);
+    assert_eq!(blink, json!({"ok": null}));
+    assert_eq!(bridge.blinks.len(), 1);
+
+    // Devices the robot program hasn't created can't be reached.
+    let missing = request(
+        &mut bridge,
+        r#"{"request": "telemetry", "type": "TalonSRX", "id": 61}"#,
+    );
+    assert_eq!(missing, json!({"error": "no such device"}));
+    let invalid = request(&mut bridge, r#"{"request": "set", "name": "bridge_arm"}"#);
+    assert!(invalid["error"]
+        .as_str()
+        .unwrap()
+        .starts_with("invalid request"));
+
+    // A blink still running when the bridge goes is ended.
+    let outputs = &shared_state(&talon).outputs;
+    assert_eq!(outputs.neutral_mode(), Some(NeutralMode::Brake));
+    drop(bridge);
+    assert_eq!(outputs.neutral_mode(), Some(NeutralMode::EEPROMSetting));
+}
+
+#[test]
+fn bridge_thread_stops_when_asked() {
+    let stop = Arc::new(AtomicBool::new(false));
+    let bridge = Bridge::bind("127.0.0.1:0").unwrap();
+    let thread = bridge.spawn_until(Arc::clone(&stop)).unwrap();
+    thread::sleep(POLL_PERIOD);
+    assert!(!thread.is_finished());
+    stop.store(true, Ordering::Relaxed);
+    thread.join().unwrap().unwrap();
+}
+
+#[test]
+fn bridge_reaches_only_live_devices_and_drops_long_lines() {
+    use std::io::{BufRead, BufReader};
+    use DeviceId;
+    let talon = TalonSRX::new(DeviceId::new(58));
+    talon.set_name("bridge_wrist");
+    let mut bridge = Bridge::bind("127.0.0.1:0").unwrap();
+    let stream = TcpStream::connect(bridge.local_addr().unwrap()).unwrap();
+    let mut responses = BufReader::new(stream.try_clone().unwrap());
+    let request = r#"{"request": "telemetry", "name": "bridge_wrist"}"#;
+    (&stream)
+        .write_all(format!("{}\n", request).as_bytes())
+        .unwrap();
+    bridge.poll().unwrap();
+    let mut response = String::new();
+    responses.read_line(&mut response).unwrap();
+    assert!(response.starts_with(r#"{"ok":"#));
+
+    // The bridge doesn't keep the device alive.
+    drop(talon);
+    assert_eq!(registry().find("bridge_wrist"), None);
+
+    (&stream).write_all(&[b' '; MAX_LINE]).unwrap();
+    thread::sleep(POLL_PERIOD);
+    bridge.poll().unwrap();
+    assert!(bridge.clients.is_empty());
+}
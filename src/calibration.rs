@@ -0,0 +1,208 @@
+//! Per-robot calibration, for teams with more than one robot.
+//!
+//! Practice and competition robots share code but not encoder offsets or
+//! gains.  A [`CalibrationStore`] holds each robot's constants and motor
+//! settings; at startup the robot identifies itself, by a custom parameter
+//! set on one of its devices or by a file on the roboRIO, and its entry is
+//! applied:
+//!
+//! ```toml
+//! [[robots]]
+//! name = "competition"
+//! id = 1
+//! [robots.constants]
+//! front_left_offset = 1234.0
+//! [robots.motors.shooter]
+//! closed_loop_ramp = 0.1
+//! [[robots.motors.shooter.slots]]
+//! slot = 0
+//! kf = 0.051
+//!
+//! [[robots]]
+//! name = "practice"
+//! id = 2
+//! [robots.constants]
+//! front_left_offset = 2870.0
+//! ```
+//!
+//! Motor settings take the same form as in a robot config (see
+//! `robot_config::MotorConfig`), keyed by device name.
+//!
+//! [`CalibrationStore`]: struct.CalibrationStore.html
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use config::{ConfigContext, ConfigErrors};
+use manifest::RobotDevices;
+use motor_control::BaseMotorController;
+use robot_config::{configure_motor, read_file, MotorConfig, RobotConfigError};
+
+/// One robot's calibration in a [`CalibrationStore`].
+///
+/// [`CalibrationStore`]: struct.CalibrationStore.html
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RobotCalibration {
+    pub name: String,
+    /// The value of the identifying custom parameter on this robot, if it has one.
+    #[serde(default)]
+    pub id: Option<i32>,
+    /// Named constants, such as encoder offsets.
+    #[serde(default)]
+    pub constants: BTreeMap<String, f64>,
+    /// Settings for motor controllers, by device name.
+    #[serde(default)]
+    pub motors: BTreeMap<String, MotorConfig>,
+}
+
+impl RobotCalibration {
+    /// The constant called `name`, if this robot has one.
+    pub fn constant(&self, name: &str) -> Option<f64> {
+        self.constants.get(name).copied()
+    }
+    /**
+     * Applies the motor settings to the matching devices in `devices`, using
+     * the current thread's `ConfigContext` timeout.
+     *
+     * Stops at the first motor controller with failed settings.
+     */
+    pub fn apply(&self, devices: &RobotDevices) -> Result<(), RobotConfigError> {
+        let timeout_ms = ConfigContext::current().default_timeout_ms;
+        for (name, motor) in &self.motors {
+            configure_motor(devices, name, motor, timeout_ms, None)?;
+        }
+        Ok(())
+    }
+}
+
+/**
+ * The calibration of each of a team's robots, loaded from a TOML or JSON file.
+ * See the [module documentation](index.html) for the format.
+ *
+ * ```no_run
+ * # use ctre::calibration::CalibrationStore;
+ * # use ctre::manifest::DeviceManifest;
+ * # use ctre::motor_control::BaseMotorController;
+ * # use ctre::error::DeviceType;
+ * # use ctre::DeviceId;
+ * # fn main() -> Result<(), Box<dyn std::error::Error>> {
+ * let mut manifest = DeviceManifest::new();
+ * manifest.add(DeviceType::TalonSRX, DeviceId::new(1), "shooter");
+ * let devices = manifest.build()?;
+ * let store = CalibrationStore::load("/home/lvuser/deploy/calibration.toml")?;
+ * let shooter = devices.talon_srx("shooter").unwrap();
+ * let robot = store.select_by_custom_param(shooter, 0, 30)?;
+ * robot.apply(&devices)?;
+ * let offset = robot.constant("front_left_offset").unwrap_or(0.);
+ * # Ok(())
+ * # }
+ * ```
+ */
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CalibrationStore {
+    #[serde(default)]
+    pub robots: Vec<RobotCalibration>,
+}
+
+impl CalibrationStore {
+    pub fn from_toml(text: &str) -> Result<Self, RobotConfigError> {
+        toml::from_str(text).map_err(|err| RobotConfigError::Parse(err.to_string()))
+    }
+    /// Reads a calibration file: JSON if its extension is `.json`, TOML otherwise.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, RobotConfigError> {
+        read_file(path.as_ref())
+    }
+
+    /// The robot whose `id` is `id`.
+    pub fn select(&self, id: i32) -> Result<&RobotCalibration, RobotConfigError> {
+        self.robots
+            .iter()
+            .find(|robot| robot.id == Some(id))
+            .ok_or_else(|| RobotConfigError::Invalid(format!("no robot with id {}", id)))
+    }
+    /// The robot called `name`.
+    pub fn select_by_name(&self, name: &str) -> Result<&RobotCalibration, RobotConfigError> {
+        self.robots
+            .iter()
+            .find(|robot| robot.name == name)
+            .ok_or_else(|| RobotConfigError::Invalid(format!("no robot named {}", name)))
+    }
+    /**
+     * The robot whose `id` is the value of custom parameter `param_index` on
+     * `motor`.  Set it once per robot, e.g. with Phoenix Tuner; custom
+     * parameters survive power cycles.
+     */
+    pub fn select_by_custom_param<M: BaseMotorController>(
+        &self,
+        motor: &M,
+        param_index: i32,
+        timeout_ms: i32,
+    ) -> Result<&RobotCalibration, RobotConfigError> {
+        let id = motor
+            .config_get_custom_param(param_index, timeout_ms)
+            .map_err(|err| {
+                let name = match motor.name() {
+                    Some(name) => name.to_owned(),
+                    None => format!("device {}", motor.device_number()),
+                };
+                RobotConfigError::Config(name, ConfigErrors::from(err))
+            })?;
+        self.select(id)
+    }
+    /**
+     * The robot named in the file at `path`, e.g. `/home/lvuser/robot_name`,
+     * which holds just the robot's name.  Surrounding whitespace is ignored.
+     */
+    pub fn select_by_file<P: AsRef<Path>>(
+        &self,
+        path: P,
+    ) -> Result<&RobotCalibration, RobotConfigError> {
+        let name = fs::read_to_string(path)?;
+        self.select_by_name(name.trim())
+    }
+}
+
+#[test]
+fn store_selects_by_custom_param() {
+    use error::DeviceType;
+    use manifest::DeviceManifest;
+    use {DeviceId, ParamEnum};
+    let store = CalibrationStore::from_toml(
+        r#"
+        [[robots]]
+        name = "competition"
+        id = 1
+        [robots.constants]
+        front_left_offset = 1234.0
+
+        [[robots]]
+        name = "practice"
+        id = 2
+        [robots.constants]
+        front_left_offset = 2870.0
+        [robots.motors.flywheel]
+        closed_loop_ramp = 0.1
+        "#,
+    )
+    .unwrap();
+    let mut manifest = DeviceManifest::new();
+    manifest.add(DeviceType::TalonSRX, DeviceId::new(35), "flywheel");
+    let devices = manifest.build().unwrap();
+    let flywheel = devices.talon_srx("flywheel").unwrap();
+    flywheel.config_set_custom_param(2, 0, 0).unwrap();
+
+    let robot = store.select_by_custom_param(flywheel, 0, 0).unwrap();
+    assert_eq!(robot.name, "practice");
+    assert_eq!(robot.constant("front_left_offset"), Some(2870.));
+    robot.apply(&devices).unwrap();
+    assert_eq!(
+        flywheel.config_get_parameter(ParamEnum::ClosedloopRamp, 0, 0),
+        Ok(0.1)
+    );
+
+    assert_eq!(store.select_by_name("competition").unwrap().id, Some(1));
+    assert!(matches!(store.select(3), Err(RobotConfigError::Invalid(_))));
+}
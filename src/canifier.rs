@@ -1,10 +1,18 @@
 //! CANifier
 
+use std::sync::Arc;
+
 use ctre_sys::canifier::*;
 pub use ctre_sys::canifier::{
     CANifierControlFrame as ControlFrame, CANifierStatusFrame as StatusFrame,
     CANifierVelocityMeasPeriod as VelocityMeasPeriod, GeneralPin,
 };
+use device::{DeviceContext, DeviceState};
+use error::{DeviceType, ErrorRecord, ErrorStats};
+use events::{events, DeviceEvent};
+use firmware::FirmwareVersion;
+use id::DeviceId;
+use registry::registry;
 use {ErrorCode, ParamEnum, Result};
 
 #[repr(u32)]
@@ -57,6 +65,10 @@ impl StickyFaults {
     pub fn has_any_fault(self) -> bool {
         self.0 != 0
     }
+    /// The raw fault bits.
+    pub fn bits(self) -> i32 {
+        self.0
+    }
 }
 
 /**
@@ -66,21 +78,56 @@ impl StickyFaults {
  */
 pub struct CANifier {
     handle: Handle,
+    device_number: i32,
+    state: Arc<DeviceState>,
 }
 impl CANifier {
     /// Constructor.
     /// * `device_number` - The CAN Device ID of the CANifier.
-    pub fn new(device_number: i32) -> CANifier {
-        let handle = unsafe { c_CANifier_Create1(device_number) };
-        CANifier { handle }
+    pub fn new(device_number: DeviceId) -> CANifier {
+        let handle = unsafe { c_CANifier_Create1(i32::from(device_number)) };
+        let state = registry().register(DeviceType::CANifier, device_number, handle);
+        CANifier::from_state(i32::from(device_number), state)
+    }
+    /// Another object for the registered CANifier owning `state`.
+    pub(crate) fn from_state(device_number: i32, state: Arc<DeviceState>) -> CANifier {
+        CANifier {
+            handle: state.handle(),
+            device_number,
+            state,
+        }
+    }
+    /**
+     * Creates a CANifier that may not be on the bus.  While it isn't, calls
+     * fail at once with `DeviceMissing`, a warning, instead of waiting on CAN.
+     */
+    pub fn new_optional(device_number: DeviceId) -> CANifier {
+        let canifier = CANifier::new(device_number);
+        canifier.make_optional();
+        canifier
+    }
+    /// The device number, e.g. to pass to a motor controller's remote sensor APIs.
+    pub fn id(&self) -> DeviceId {
+        self.state.device_id()
+    }
+    /// The label set with `set_name`, if any.
+    pub fn name(&self) -> Option<&'static str> {
+        self.state.name()
+    }
+    /// Labels the CANifier for logs and error messages, e.g. `"led_controller"`.
+    pub fn set_name(&self, name: &'static str) {
+        self.state.set_name(name)
     }
 
-    pub fn _set_led_output(&self, duty_cycle: u32, led_channel: LEDChannel) -> ErrorCode {
-        unsafe { c_CANifier_SetLEDOutput(self.handle, duty_cycle, led_channel as u32) }
+    pub fn _set_led_output(&self, duty_cycle: u32, led_channel: LEDChannel) -> Result<()> {
+        cci_call!(
+            self,
+            c_CANifier_SetLEDOutput(self.handle, duty_cycle, led_channel as u32)
+        )
     }
-    pub fn set_led_output(&self, percent_output: f64, led_channel: LEDChannel) -> ErrorCode {
+    pub fn set_led_output(&self, percent_output: f64, led_channel: LEDChannel) -> Result<()> {
         // convert float to integral fixed pt
-        let duty_cycle = 1023. * percent_output.min(1.).max(0.);
+        let duty_cycle = 1023. * percent_output.clamp(0., 1.);
         self._set_led_output(duty_cycle as u32, led_channel)
     }
     /**
@@ -90,22 +137,34 @@ impl CANifier {
      * * `is_output_bits` - A boolean bit mask that sets the pins to be outputs or inputs.
      *   A bit of 1 enables output.
      */
-    pub fn set_general_outputs(&self, outputs_bits: u32, is_output_bits: u32) -> ErrorCode {
-        unsafe { c_CANifier_SetGeneralOutputs(self.handle, outputs_bits, is_output_bits) }
+    pub fn set_general_outputs(&self, outputs_bits: u32, is_output_bits: u32) -> Result<()> {
+        cci_call!(
+            self,
+            c_CANifier_SetGeneralOutputs(self.handle, outputs_bits, is_output_bits)
+        )
     }
     pub fn set_general_output(
         &self,
         output_pin: GeneralPin,
         output_value: bool,
         output_enable: bool,
-    ) -> ErrorCode {
-        unsafe {
-            c_CANifier_SetGeneralOutput(self.handle, output_pin as u32, output_value, output_enable)
-        }
+    ) -> Result<()> {
+        cci_call!(
+            self,
+            c_CANifier_SetGeneralOutput(
+                self.handle,
+                output_pin as u32,
+                output_value,
+                output_enable,
+            )
+        )
     }
 
-    pub fn _set_pwm_output(&self, pwm_channel: u32, duty_cycle: u32) -> ErrorCode {
-        unsafe { c_CANifier_SetPWMOutput(self.handle, pwm_channel, duty_cycle) }
+    pub fn _set_pwm_output(&self, pwm_channel: u32, duty_cycle: u32) -> Result<()> {
+        cci_call!(
+            self,
+            c_CANifier_SetPWMOutput(self.handle, pwm_channel, duty_cycle)
+        )
     }
     /**
      * Sets the PWM Output
@@ -114,73 +173,94 @@ impl CANifier {
      * * `duty_cycle` - Duty Cycle (0 to 1) to output.
      *   Default period of the signal is 4.2 ms.
      */
-    pub fn set_pwm_output(&self, pwm_channel: PWMChannel, duty_cycle: f64) -> ErrorCode {
-        let duty_cyc_10bit = 1023. * duty_cycle.max(0.).min(1.);
+    pub fn set_pwm_output(&self, pwm_channel: PWMChannel, duty_cycle: f64) -> Result<()> {
+        let duty_cyc_10bit = 1023. * duty_cycle.clamp(0., 1.);
         self._set_pwm_output(pwm_channel as u32, duty_cyc_10bit as u32)
     }
-    pub fn _enable_pwm_output(&self, pwm_channel: u32, b_enable: bool) -> ErrorCode {
-        unsafe { c_CANifier_EnablePWMOutput(self.handle, pwm_channel, b_enable) }
+    pub fn _enable_pwm_output(&self, pwm_channel: u32, b_enable: bool) -> Result<()> {
+        cci_call!(
+            self,
+            c_CANifier_EnablePWMOutput(self.handle, pwm_channel, b_enable)
+        )
     }
     /**
      * Enables PWM Outputs
      * Currently supports PWM 0, PWM 1, and PWM 2
      */
-    pub fn enable_pwm_output(&self, pwm_channel: PWMChannel, enable: bool) -> ErrorCode {
+    pub fn enable_pwm_output(&self, pwm_channel: PWMChannel, enable: bool) -> Result<()> {
         self._enable_pwm_output(pwm_channel as u32, enable)
     }
 
-    pub fn _get_general_inputs(&self, all_pins: &mut [bool]) -> ErrorCode {
-        unsafe {
+    pub fn _get_general_inputs(&self, all_pins: &mut [bool]) -> Result<()> {
+        cci_call!(
+            self,
             c_CANifier_GetGeneralInputs(self.handle, all_pins.as_mut_ptr(), all_pins.len() as _)
-        }
+        )
     }
     /// Gets the state of all General Pins
     pub fn get_general_inputs(&self) -> Result<PinValues> {
         let mut temp_pins = [false; 11];
-        let err = self._get_general_inputs(&mut temp_pins);
-        match err {
-            ErrorCode::OK => Ok(PinValues {
-                LIMF: temp_pins[GeneralPin::LIMF as usize],
-                LIMR: temp_pins[GeneralPin::LIMR as usize],
-                QUAD_A: temp_pins[GeneralPin::QUAD_A as usize],
-                QUAD_B: temp_pins[GeneralPin::QUAD_B as usize],
-                QUAD_IDX: temp_pins[GeneralPin::QUAD_IDX as usize],
-                SCL: temp_pins[GeneralPin::SCL as usize],
-                SDA: temp_pins[GeneralPin::SDA as usize],
-                SPI_CLK_PWM0: temp_pins[GeneralPin::SPI_CLK_PWM0P as usize],
-                SPI_MOSI_PWM1: temp_pins[GeneralPin::SPI_MOSI_PWM1P as usize],
-                SPI_MISO_PWM2: temp_pins[GeneralPin::SPI_MISO_PWM2P as usize],
-                SPI_CS_PWM3: temp_pins[GeneralPin::SPI_CS as usize],
-            }),
-            _ => Err(err),
-        }
+        self._get_general_inputs(&mut temp_pins)?;
+        Ok(PinValues {
+            LIMF: temp_pins[GeneralPin::LIMF as usize],
+            LIMR: temp_pins[GeneralPin::LIMR as usize],
+            QUAD_A: temp_pins[GeneralPin::QUAD_A as usize],
+            QUAD_B: temp_pins[GeneralPin::QUAD_B as usize],
+            QUAD_IDX: temp_pins[GeneralPin::QUAD_IDX as usize],
+            SCL: temp_pins[GeneralPin::SCL as usize],
+            SDA: temp_pins[GeneralPin::SDA as usize],
+            SPI_CLK_PWM0: temp_pins[GeneralPin::SPI_CLK_PWM0P as usize],
+            SPI_MOSI_PWM1: temp_pins[GeneralPin::SPI_MOSI_PWM1P as usize],
+            SPI_MISO_PWM2: temp_pins[GeneralPin::SPI_MISO_PWM2P as usize],
+            SPI_CS_PWM3: temp_pins[GeneralPin::SPI_CS as usize],
+        })
     }
     /// Gets the state of the specified pin
     pub fn get_general_input(&self, input_pin: GeneralPin) -> Result<bool> {
-        cci_get_call!(c_CANifier_GetGeneralInput(self.handle, input_pin as u32, _: bool))
+        cci_get_call!(self, c_CANifier_GetGeneralInput(self.handle, input_pin as u32, _: bool))
     }
 
     /// Gets the PWM Input.
     /// Returns a 2-array holding the Pulse Width (microseconds) [0] and Period (microseconds) [1].
     pub fn get_pwm_input(&self, pwm_channel: PWMChannel) -> Result<[f64; 2]> {
-        cci_get_call_array!(c_CANifier_GetPWMInput(self.handle, pwm_channel as u32, _: [f64; 2]))
+        cci_get_call_array!(
+            self,
+            c_CANifier_GetPWMInput(self.handle, pwm_channel as u32, _: [f64; 2])
+        )
     }
 
     pub fn get_last_error(&self) -> ErrorCode {
         unsafe { c_CANifier_GetLastError(self.handle) }
     }
+    /// The last few errors returned by calls on this object, oldest first.
+    /// Unlike `get_last_error`, each entry is tied to the call that failed.
+    pub fn recent_errors(&self) -> Vec<ErrorRecord> {
+        self.state.errors.to_vec()
+    }
+    /// Like `recent_errors`, but reuses `records`' allocation.
+    /// `records` is cleared first; it never needs more than 16 entries.
+    pub fn recent_errors_into(&self, records: &mut Vec<ErrorRecord>) {
+        self.state.errors.copy_into(records)
+    }
+    /// How many calls on this object have failed, by kind of error.
+    pub fn error_stats(&self) -> ErrorStats {
+        self.state.error_counts.stats()
+    }
     pub fn get_bus_voltage(&self) -> Result<f64> {
-        cci_get_call!(c_CANifier_GetBusVoltage(self.handle, _: f64))
+        cci_get_call!(self, c_CANifier_GetBusVoltage(self.handle, _: f64))
     }
 
     pub fn get_quadrature_position(&self) -> Result<i32> {
-        cci_get_call!(c_CANifier_GetQuadraturePosition(self.handle, _: i32))
+        cci_get_call!(self, c_CANifier_GetQuadraturePosition(self.handle, _: i32))
     }
-    pub fn set_quadrature_position(&self, pos: i32, timeout_ms: i32) -> ErrorCode {
-        unsafe { c_CANifier_SetQuadraturePosition(self.handle, pos, timeout_ms) }
+    pub fn set_quadrature_position(&self, pos: i32, timeout_ms: i32) -> Result<()> {
+        cci_call!(
+            self,
+            c_CANifier_SetQuadraturePosition(self.handle, pos, timeout_ms)
+        )
     }
     pub fn get_quadrature_velocity(&self) -> Result<i32> {
-        cci_get_call!(c_CANifier_GetQuadratureVelocity(self.handle, _: i32))
+        cci_get_call!(self, c_CANifier_GetQuadratureVelocity(self.handle, _: i32))
     }
 
     /**
@@ -193,11 +273,17 @@ impl CANifier {
         &self,
         period: VelocityMeasPeriod,
         timeout_ms: i32,
-    ) -> ErrorCode {
-        unsafe { c_CANifier_ConfigVelocityMeasurementPeriod(self.handle, period as _, timeout_ms) }
-    }
-    pub fn config_velocity_measurement_window(&self, window: i32, timeout_ms: i32) -> ErrorCode {
-        unsafe { c_CANifier_ConfigVelocityMeasurementWindow(self.handle, window, timeout_ms) }
+    ) -> Result<()> {
+        cci_call!(
+            self,
+            c_CANifier_ConfigVelocityMeasurementPeriod(self.handle, period as _, timeout_ms)
+        )
+    }
+    pub fn config_velocity_measurement_window(&self, window: i32, timeout_ms: i32) -> Result<()> {
+        cci_call!(
+            self,
+            c_CANifier_ConfigVelocityMeasurementWindow(self.handle, window, timeout_ms)
+        )
     }
 
     /**
@@ -214,8 +300,9 @@ impl CANifier {
         sub_value: i32,
         ordinal: i32,
         timeout_ms: i32,
-    ) -> ErrorCode {
-        unsafe {
+    ) -> Result<()> {
+        cci_call!(
+            self,
             c_CANifier_ConfigSetParameter(
                 self.handle,
                 param as _,
@@ -224,7 +311,7 @@ impl CANifier {
                 ordinal,
                 timeout_ms,
             )
-        }
+        )
     }
     pub fn config_get_parameter(
         &self,
@@ -232,7 +319,7 @@ impl CANifier {
         ordinal: i32,
         timeout_ms: i32,
     ) -> Result<f64> {
-        cci_get_call!(c_CANifier_ConfigGetParameter(
+        cci_get_call!(self, c_CANifier_ConfigGetParameter(
             self.handle,
             param as _,
             _: f64,
@@ -240,6 +327,18 @@ impl CANifier {
             timeout_ms,
         ))
     }
+    /**
+     * Revert all configurations to factory default values.
+     *
+     * Requires Phoenix 2019 or later.
+     */
+    #[cfg(feature = "phoenix-2019")]
+    pub fn config_factory_default(&self, timeout_ms: i32) -> Result<()> {
+        cci_call!(
+            self,
+            c_CANifier_ConfigFactoryDefault(self.handle, timeout_ms)
+        )
+    }
     /**
      * Sets the value of a custom parameter. This is for arbitrary use.
      *
@@ -258,8 +357,11 @@ impl CANifier {
         new_value: i32,
         param_index: i32,
         timeout_ms: i32,
-    ) -> ErrorCode {
-        unsafe { c_CANifier_ConfigSetCustomParam(self.handle, new_value, param_index, timeout_ms) }
+    ) -> Result<()> {
+        cci_call!(
+            self,
+            c_CANifier_ConfigSetCustomParam(self.handle, new_value, param_index, timeout_ms)
+        )
     }
     /**
      * Gets the value of a custom parameter. This is for arbitrary use.
@@ -270,28 +372,37 @@ impl CANifier {
      *   If zero, no blocking or checking is performed.
      */
     pub fn config_get_custom_param(&self, param_index: i32, timout_ms: i32) -> Result<i32> {
-        cci_get_call!(c_CANifier_ConfigGetCustomParam(self.handle, _: i32, param_index, timout_ms))
+        cci_get_call!(
+            self,
+            c_CANifier_ConfigGetCustomParam(self.handle, _: i32, param_index, timout_ms)
+        )
     }
 
     pub fn get_faults(&self) -> Result<Faults> {
         Ok(Faults(
-            cci_get_call!(c_CANifier_GetFaults(self.handle, _: i32))?,
+            cci_get_call!(self, c_CANifier_GetFaults(self.handle, _: i32))?,
         ))
     }
     pub fn get_sticky_faults(&self) -> Result<StickyFaults> {
         Ok(StickyFaults(
-            cci_get_call!(c_CANifier_GetStickyFaults(self.handle, _: i32))?,
+            cci_get_call!(self, c_CANifier_GetStickyFaults(self.handle, _: i32))?,
         ))
     }
-    pub fn clear_sticky_faults(&self, timeout_ms: i32) -> ErrorCode {
-        unsafe { c_CANifier_ClearStickyFaults(self.handle, timeout_ms) }
+    pub fn clear_sticky_faults(&self, timeout_ms: i32) -> Result<()> {
+        cci_call!(self, c_CANifier_ClearStickyFaults(self.handle, timeout_ms))
     }
 
-    pub fn get_firmware_version(&self) -> Result<i32> {
-        cci_get_call!(c_CANifier_GetFirmwareVersion(self.handle, _: i32))
+    pub fn get_firmware_version(&self) -> Result<FirmwareVersion> {
+        cci_get_call!(self, c_CANifier_GetFirmwareVersion(self.handle, _: i32))
+            .map(FirmwareVersion::from_raw)
     }
+    /// Returns true if the device has reset since last call.
     pub fn has_reset_occurred(&self) -> Result<bool> {
-        cci_get_call!(c_CANifier_HasResetOccurred(self.handle, _: bool))
+        let reset = cci_get_call!(self, c_CANifier_HasResetOccurred(self.handle, _: bool))?;
+        if reset {
+            events().publish(DeviceEvent::Reset(self.device_info()));
+        }
+        Ok(reset)
     }
 
     pub fn set_status_frame_period(
@@ -299,13 +410,38 @@ impl CANifier {
         frame: StatusFrame,
         period_ms: i32,
         timeout_ms: i32,
-    ) -> ErrorCode {
-        unsafe { c_CANifier_SetStatusFramePeriod(self.handle, frame as _, period_ms, timeout_ms) }
+    ) -> Result<()> {
+        cci_call!(
+            self,
+            c_CANifier_SetStatusFramePeriod(self.handle, frame as _, period_ms, timeout_ms)
+        )
     }
     pub fn get_status_frame_period(&self, frame: StatusFrame, timeout_ms: i32) -> Result<i32> {
-        cci_get_call!(c_CANifier_GetStatusFramePeriod(self.handle, frame as _, _: i32, timeout_ms))
+        cci_get_call!(
+            self,
+            c_CANifier_GetStatusFramePeriod(self.handle, frame as _, _: i32, timeout_ms)
+        )
+    }
+    pub fn set_control_frame_period(&self, frame: ControlFrame, period_ms: i32) -> Result<()> {
+        cci_call!(
+            self,
+            c_CANifier_SetControlFramePeriod(self.handle, frame as _, period_ms)
+        )
+    }
+}
+
+impl DeviceContext for CANifier {
+    fn device_type(&self) -> DeviceType {
+        DeviceType::CANifier
+    }
+    fn device_id(&self) -> i32 {
+        self.device_number
+    }
+    fn state(&self) -> &DeviceState {
+        &self.state
     }
-    pub fn set_control_frame_period(&self, frame: ControlFrame, period_ms: i32) -> ErrorCode {
-        unsafe { c_CANifier_SetControlFramePeriod(self.handle, frame as _, period_ms) }
+    fn probe(&self) -> bool {
+        let mut volts = 0.;
+        unsafe { c_CANifier_GetBusVoltage(self.handle, &mut volts) }.is_ok()
     }
 }
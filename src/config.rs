@@ -0,0 +1,1029 @@
+//! Defaults for configuration calls.
+//!
+//! Every `config_*` method takes a timeout, and most code passes the same one
+//! everywhere.  A [`ConfigContext`] supplies it instead, along with retries and
+//! read-back verification, through the [`Configurator`] returned by
+//! `BaseMotorController::cfg`:
+//!
+//! ```no_run
+//! # use ctre::DeviceId;
+//! # use ctre::motor_control::{BaseMotorController, TalonSRX};
+//! # fn main() -> ctre::Result<()> {
+//! let talon = TalonSRX::new(DeviceId::new(1));
+//! talon.cfg().kp(0, 0.2)?.kf(0, 0.05)?.motion_cruise_velocity(1500)?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Alternatively, `BaseMotorController::configure` collects settings into a
+//! [`ConfigBuilder`] and applies them together, reporting every failure at once:
+//!
+//! ```no_run
+//! # use ctre::DeviceId;
+//! # use ctre::motor_control::{BaseMotorController, TalonSRX};
+//! # fn main() -> Result<(), ctre::config::ConfigErrors> {
+//! let talon = TalonSRX::new(DeviceId::new(1));
+//! talon
+//!     .configure()
+//!     .open_loop_ramp(0.2)
+//!     .peak_output(1.0, -1.0)
+//!     .neutral_deadband(0.02)
+//!     .apply(30)?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`ConfigContext`]: struct.ConfigContext.html
+//! [`Configurator`]: struct.Configurator.html
+//! [`ConfigBuilder`]: struct.ConfigBuilder.html
+
+use std::cell::{Cell, RefCell};
+use std::cmp::Reverse;
+use std::convert::TryInto;
+use std::error;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use device::DeviceContext;
+use events::{events, DeviceEvent};
+use motor_control::{raw_pid_idx, raw_slot_idx, BaseMotorController, Gains, PidLoop, Slot};
+use registry::DeviceInfo;
+use {DeviceError, ErrorCode, ParamEnum, Result};
+
+/// Defaults applied to configuration calls made through a [`Configurator`].
+///
+/// [`Configurator`]: struct.Configurator.html
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ConfigContext {
+    /// Timeout passed to every call, in ms.
+    pub default_timeout_ms: i32,
+    /// How many times to retry a call which timed out or failed verification.
+    pub retries: u32,
+    /// Whether to read each value back and check it was applied.
+    pub verify: bool,
+}
+
+impl ConfigContext {
+    /// A 30ms timeout, as in CTRE's examples, with no retries or verification.
+    pub const DEFAULT: ConfigContext = ConfigContext {
+        default_timeout_ms: 30,
+        retries: 0,
+        verify: false,
+    };
+
+    /// The context for the current thread.
+    pub fn current() -> Self {
+        CONTEXT.with(Cell::get)
+    }
+    /// Sets the context for the current thread.
+    pub fn set_current(self) {
+        CONTEXT.with(|context| context.set(self));
+    }
+    /// Runs `f` with this as the current thread's context, then restores the previous one.
+    pub fn scope<R, F: FnOnce() -> R>(self, f: F) -> R {
+        struct Restore(ConfigContext);
+        impl Drop for Restore {
+            fn drop(&mut self) {
+                self.0.set_current();
+            }
+        }
+        let _restore = Restore(Self::current());
+        self.set_current();
+        f()
+    }
+}
+
+impl Default for ConfigContext {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+thread_local!(static CONTEXT: Cell<ConfigContext> = const { Cell::new(ConfigContext::DEFAULT) });
+
+/**
+ * Lets a watchdog or shutdown sequence stop a long config sequence early,
+ * rather than waiting for every call to time out.
+ *
+ * Pass a clone to `ConfigBuilder::cancel_on`.  Cancellation is cooperative:
+ * the call in progress finishes (each call's timeout is shortened so it
+ * doesn't run past the deadline), and the settings after it are skipped.
+ *
+ * ```no_run
+ * # use ctre::DeviceId;
+ * # use std::time::Duration;
+ * # use ctre::config::CancelToken;
+ * # use ctre::motor_control::{BaseMotorController, TalonSRX};
+ * let talon = TalonSRX::new(DeviceId::new(1));
+ * let token = CancelToken::with_timeout(Duration::from_millis(500));
+ * let result = talon
+ *     .configure()
+ *     .open_loop_ramp(0.2)
+ *     .neutral_deadband(0.02)
+ *     .cancel_on(&token)
+ *     .apply(50);
+ * ```
+ */
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken {
+    cancelled: Arc<AtomicBool>,
+    deadline: Option<Instant>,
+}
+
+impl CancelToken {
+    /// A token that is only cancelled by `cancel`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// A token that is also cancelled once `deadline` passes.
+    pub fn with_deadline(deadline: Instant) -> Self {
+        CancelToken {
+            cancelled: Arc::default(),
+            deadline: Some(deadline),
+        }
+    }
+    /// A token that is also cancelled once `timeout` has passed from now.
+    pub fn with_timeout(timeout: Duration) -> Self {
+        Self::with_deadline(Instant::now() + timeout)
+    }
+    /// Cancels this token and its clones.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+    pub fn is_cancelled(&self) -> bool {
+        self.timeout_ms(i32::MAX).is_none()
+    }
+    /// `timeout_ms`, shortened to end by the deadline, or `None` if cancelled.
+    fn timeout_ms(&self, timeout_ms: i32) -> Option<i32> {
+        if self.cancelled.load(Ordering::Relaxed) {
+            return None;
+        }
+        let deadline = match self.deadline {
+            Some(deadline) => deadline,
+            None => return Some(timeout_ms),
+        };
+        // A timeout of 0 means not waiting at all, so a deadline under 1 ms away has passed.
+        match deadline
+            .saturating_duration_since(Instant::now())
+            .as_millis()
+        {
+            0 => None,
+            remaining => Some(timeout_ms.min(remaining.min(i32::MAX as u128) as i32)),
+        }
+    }
+}
+
+/**
+ * Configures a motor controller using a [`ConfigContext`].
+ *
+ * Each method takes the same arguments as the corresponding `config_*` method,
+ * minus the timeout, and returns the configurator so calls can be chained.
+ *
+ * [`ConfigContext`]: struct.ConfigContext.html
+ */
+#[derive(Debug)]
+pub struct Configurator<'a, M: 'a + ?Sized> {
+    motor: &'a M,
+    context: ConfigContext,
+}
+
+impl<'a, M: BaseMotorController + ?Sized> Configurator<'a, M> {
+    /// Uses the current thread's context.
+    pub fn new(motor: &'a M) -> Self {
+        Self::with_context(motor, ConfigContext::current())
+    }
+    pub fn with_context(motor: &'a M, context: ConfigContext) -> Self {
+        Configurator { motor, context }
+    }
+    pub fn context(&self) -> ConfigContext {
+        self.context
+    }
+
+    /// Makes a call with the context's timeout and retries, then verifies it
+    /// by reading `param` back if enabled.
+    fn apply<F>(&self, param: ParamEnum, ordinal: i32, value: f64, write: F) -> Result<&Self>
+    where
+        F: Fn(&M, i32) -> Result<()>,
+    {
+        let timeout_ms = self.context.default_timeout_ms;
+        let started = Instant::now();
+        let mut attempts = 1;
+        let result = loop {
+            let result = write(self.motor, timeout_ms).and_then(|()| {
+                if self.context.verify {
+                    self.verify(param, ordinal, value)
+                } else {
+                    Ok(())
+                }
+            });
+            match result {
+                Err(ref err) if attempts <= self.context.retries && is_retryable(err.code) => {
+                    attempts += 1;
+                }
+                result => break result,
+            }
+        };
+        ConfigSession::record(|| ConfigCall {
+            device: self.motor.device_info(),
+            param,
+            ordinal,
+            duration: started.elapsed(),
+            attempts,
+            ok: result.is_ok(),
+        });
+        if result.is_ok() {
+            // Kept for `rebind`, to configure a replacement device the same way.
+            let record = &self.motor.state().config_record;
+            record.record(param, ordinal, value);
+        }
+        result.map(|()| self)
+    }
+
+    fn verify(&self, param: ParamEnum, ordinal: i32, value: f64) -> Result<()> {
+        let timeout_ms = self.context.default_timeout_ms;
+        let actual = self
+            .motor
+            .config_get_parameter(param, ordinal, timeout_ms)?;
+        // Values are stored in fixed point, so allow for rounding.
+        if (actual - value).abs() <= (value.abs() * 0.01).max(1. / 1024.) {
+            Ok(())
+        } else {
+            Err(self.motor.error(ErrorCode::GeneralError, "verify_config"))
+        }
+    }
+
+    /// Sets any parameter.  See `config_set_parameter`.
+    pub fn param(&self, param: ParamEnum, value: f64, ordinal: i32) -> Result<&Self> {
+        self.apply(param, ordinal, value, |m, t| {
+            m.config_set_parameter(param, value, 0, ordinal, t)
+        })
+    }
+
+    pub fn kp(&self, slot_idx: impl TryInto<Slot>, value: f64) -> Result<&Self> {
+        let slot_idx = cci_index!(self.motor, kp, slot_idx: Slot)? as i32;
+        self.apply(ParamEnum::ProfileParamSlot_P, slot_idx, value, |m, t| {
+            m.config_kp(slot_idx, value, t)
+        })
+    }
+    pub fn ki(&self, slot_idx: impl TryInto<Slot>, value: f64) -> Result<&Self> {
+        let slot_idx = cci_index!(self.motor, ki, slot_idx: Slot)? as i32;
+        self.apply(ParamEnum::ProfileParamSlot_I, slot_idx, value, |m, t| {
+            m.config_ki(slot_idx, value, t)
+        })
+    }
+    pub fn kd(&self, slot_idx: impl TryInto<Slot>, value: f64) -> Result<&Self> {
+        let slot_idx = cci_index!(self.motor, kd, slot_idx: Slot)? as i32;
+        self.apply(ParamEnum::ProfileParamSlot_D, slot_idx, value, |m, t| {
+            m.config_kd(slot_idx, value, t)
+        })
+    }
+    pub fn kf(&self, slot_idx: impl TryInto<Slot>, value: f64) -> Result<&Self> {
+        let slot_idx = cci_index!(self.motor, kf, slot_idx: Slot)? as i32;
+        self.apply(ParamEnum::ProfileParamSlot_F, slot_idx, value, |m, t| {
+            m.config_kf(slot_idx, value, t)
+        })
+    }
+    pub fn integral_zone(&self, slot_idx: impl TryInto<Slot>, izone: i32) -> Result<&Self> {
+        let slot_idx = cci_index!(self.motor, integral_zone, slot_idx: Slot)? as i32;
+        self.apply(
+            ParamEnum::ProfileParamSlot_IZone,
+            slot_idx,
+            f64::from(izone),
+            |m, t| m.config_integral_zone(slot_idx, izone, t),
+        )
+    }
+    pub fn allowable_closedloop_error(
+        &self,
+        slot_idx: impl TryInto<Slot>,
+        error: i32,
+    ) -> Result<&Self> {
+        let slot_idx = cci_index!(self.motor, allowable_closedloop_error, slot_idx: Slot)? as i32;
+        let param = ParamEnum::ProfileParamSlot_AllowableErr;
+        self.apply(param, slot_idx, f64::from(error), |m, t| {
+            m.config_allowable_closedloop_error(slot_idx, error, t)
+        })
+    }
+    pub fn max_integral_accumulator(
+        &self,
+        slot_idx: impl TryInto<Slot>,
+        iaccum: f64,
+    ) -> Result<&Self> {
+        let slot_idx = cci_index!(self.motor, max_integral_accumulator, slot_idx: Slot)? as i32;
+        self.apply(
+            ParamEnum::ProfileParamSlot_MaxIAccum,
+            slot_idx,
+            iaccum,
+            |m, t| m.config_max_integral_accumulator(slot_idx, iaccum, t),
+        )
+    }
+    pub fn closed_loop_peak_output(
+        &self,
+        slot_idx: impl TryInto<Slot>,
+        percent_out: f64,
+    ) -> Result<&Self> {
+        let slot_idx = cci_index!(self.motor, closed_loop_peak_output, slot_idx: Slot)? as i32;
+        self.apply(
+            ParamEnum::ProfileParamSlot_PeakOutput,
+            slot_idx,
+            percent_out,
+            |m, t| m.config_closed_loop_peak_output(slot_idx, percent_out, t),
+        )
+    }
+    pub fn closed_loop_period(
+        &self,
+        slot_idx: impl TryInto<Slot>,
+        loop_time_ms: i32,
+    ) -> Result<&Self> {
+        let slot_idx = cci_index!(self.motor, closed_loop_period, slot_idx: Slot)? as i32;
+        self.apply(
+            ParamEnum::PIDLoopPeriod,
+            slot_idx,
+            f64::from(loop_time_ms),
+            |m, t| m.config_closed_loop_period(slot_idx, loop_time_ms, t),
+        )
+    }
+    /// Configures every setting of a gain slot.
+    pub fn gains(&self, slot_idx: impl TryInto<Slot>, gains: &Gains) -> Result<&Self> {
+        let slot_idx = cci_index!(self.motor, gains, slot_idx: Slot)?;
+        self.kp(slot_idx, gains.kp)?
+            .ki(slot_idx, gains.ki)?
+            .kd(slot_idx, gains.kd)?
+            .kf(slot_idx, gains.kf)?
+            .integral_zone(slot_idx, gains.integral_zone)?
+            .allowable_closedloop_error(slot_idx, gains.allowable_closedloop_error)?
+            .max_integral_accumulator(slot_idx, gains.max_integral_accumulator)?
+            .closed_loop_peak_output(slot_idx, gains.closed_loop_peak_output)?
+            .closed_loop_period(slot_idx, gains.closed_loop_period_ms)
+    }
+
+    pub fn openloop_ramp(&self, seconds_from_neutral_to_full: f64) -> Result<&Self> {
+        let seconds = seconds_from_neutral_to_full;
+        self.apply(ParamEnum::OpenloopRamp, 0, seconds, |m, t| {
+            m.config_openloop_ramp(seconds, t)
+        })
+    }
+    pub fn closedloop_ramp(&self, seconds_from_neutral_to_full: f64) -> Result<&Self> {
+        let seconds = seconds_from_neutral_to_full;
+        self.apply(ParamEnum::ClosedloopRamp, 0, seconds, |m, t| {
+            m.config_closedloop_ramp(seconds, t)
+        })
+    }
+    pub fn peak_output_forward(&self, percent_out: f64) -> Result<&Self> {
+        self.apply(ParamEnum::PeakPosOutput, 0, percent_out, |m, t| {
+            m.config_peak_output_forward(percent_out, t)
+        })
+    }
+    pub fn peak_output_reverse(&self, percent_out: f64) -> Result<&Self> {
+        self.apply(ParamEnum::PeakNegOutput, 0, percent_out, |m, t| {
+            m.config_peak_output_reverse(percent_out, t)
+        })
+    }
+    pub fn nominal_output_forward(&self, percent_out: f64) -> Result<&Self> {
+        self.apply(ParamEnum::NominalPosOutput, 0, percent_out, |m, t| {
+            m.config_nominal_output_forward(percent_out, t)
+        })
+    }
+    pub fn nominal_output_reverse(&self, percent_out: f64) -> Result<&Self> {
+        self.apply(ParamEnum::NominalNegOutput, 0, percent_out, |m, t| {
+            m.config_nominal_output_reverse(percent_out, t)
+        })
+    }
+    pub fn neutral_deadband(&self, percent_deadband: f64) -> Result<&Self> {
+        self.apply(ParamEnum::NeutralDeadband, 0, percent_deadband, |m, t| {
+            m.config_neutral_deadband(percent_deadband, t)
+        })
+    }
+    pub fn voltage_comp_saturation(&self, voltage: f64) -> Result<&Self> {
+        self.apply(ParamEnum::NominalBatteryVoltage, 0, voltage, |m, t| {
+            m.config_voltage_comp_saturation(voltage, t)
+        })
+    }
+    pub fn selected_feedback_coefficient(
+        &self,
+        coefficient: f64,
+        pid_idx: impl TryInto<PidLoop>,
+    ) -> Result<&Self> {
+        let pid_idx =
+            cci_index!(self.motor, selected_feedback_coefficient, pid_idx: PidLoop)? as i32;
+        let param = ParamEnum::SelectedSensorCoefficient;
+        self.apply(param, pid_idx, coefficient, |m, t| {
+            m.config_selected_feedback_coefficient(coefficient, pid_idx, t)
+        })
+    }
+
+    pub fn forward_soft_limit_threshold(&self, forward_sensor_limit: i32) -> Result<&Self> {
+        let limit = forward_sensor_limit;
+        self.apply(
+            ParamEnum::ForwardSoftLimitThreshold,
+            0,
+            f64::from(limit),
+            |m, t| m.config_forward_soft_limit_threshold(limit, t),
+        )
+    }
+    pub fn reverse_soft_limit_threshold(&self, reverse_sensor_limit: i32) -> Result<&Self> {
+        let limit = reverse_sensor_limit;
+        self.apply(
+            ParamEnum::ReverseSoftLimitThreshold,
+            0,
+            f64::from(limit),
+            |m, t| m.config_reverse_soft_limit_threshold(limit, t),
+        )
+    }
+    pub fn forward_soft_limit_enable(&self, enable: bool) -> Result<&Self> {
+        let value = if enable { 1. } else { 0. };
+        self.apply(ParamEnum::ForwardSoftLimitEnable, 0, value, |m, t| {
+            m.config_forward_soft_limit_enable(enable, t)
+        })
+    }
+    pub fn reverse_soft_limit_enable(&self, enable: bool) -> Result<&Self> {
+        let value = if enable { 1. } else { 0. };
+        self.apply(ParamEnum::ReverseSoftLimitEnable, 0, value, |m, t| {
+            m.config_reverse_soft_limit_enable(enable, t)
+        })
+    }
+
+    pub fn motion_cruise_velocity(&self, sensor_units_per_100ms: i32) -> Result<&Self> {
+        let velocity = sensor_units_per_100ms;
+        self.apply(
+            ParamEnum::MotMag_VelCruise,
+            0,
+            f64::from(velocity),
+            |m, t| m.config_motion_cruise_velocity(velocity, t),
+        )
+    }
+    pub fn motion_acceleration(&self, sensor_units_per_100ms_per_sec: i32) -> Result<&Self> {
+        let accel = sensor_units_per_100ms_per_sec;
+        self.apply(ParamEnum::MotMag_Accel, 0, f64::from(accel), |m, t| {
+            m.config_motion_acceleration(accel, t)
+        })
+    }
+}
+
+/// The failures from applying a [`ConfigBuilder`], in the order the settings were added.
+///
+/// [`ConfigBuilder`]: struct.ConfigBuilder.html
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigErrors {
+    pub errors: Vec<DeviceError>,
+    /// How many settings were skipped because a `CancelToken` was cancelled.
+    pub cancelled: usize,
+}
+
+impl From<DeviceError> for ConfigErrors {
+    fn from(err: DeviceError) -> Self {
+        ConfigErrors {
+            errors: vec![err],
+            cancelled: 0,
+        }
+    }
+}
+
+impl fmt::Display for ConfigErrors {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} config calls failed", self.errors.len())?;
+        for err in &self.errors {
+            write!(f, "; {}", err)?;
+        }
+        if self.cancelled > 0 {
+            write!(f, "; {} cancelled", self.cancelled)?;
+        }
+        Ok(())
+    }
+}
+
+impl error::Error for ConfigErrors {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        self.errors.first().map(|err| err as &dyn error::Error)
+    }
+}
+
+/// One call made through a `Configurator` during a [`ConfigSession`].
+///
+/// [`ConfigSession`]: struct.ConfigSession.html
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ConfigCall {
+    pub device: DeviceInfo,
+    pub param: ParamEnum,
+    pub ordinal: i32,
+    /// Time taken by every attempt, including reading the value back to verify it.
+    pub duration: Duration,
+    /// How many times the call was made; more than 1 if it was retried.
+    pub attempts: u32,
+    /// Whether the call eventually succeeded.
+    pub ok: bool,
+}
+
+/// e.g. `shooter (TalonSRX 3) ProfileParamSlot_P[0]: 64 ms, 3 attempts, failed`
+impl fmt::Display for ConfigCall {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} {}[{}]: {} ms",
+            self.device,
+            self.param,
+            self.ordinal,
+            self.duration.as_millis()
+        )?;
+        if self.attempts > 1 {
+            write!(f, ", {} attempts", self.attempts)?;
+        }
+        if !self.ok {
+            f.write_str(", failed")?;
+        }
+        Ok(())
+    }
+}
+
+thread_local!(static SESSION: RefCell<Option<Vec<ConfigCall>>> = const { RefCell::new(None) });
+
+/**
+ * Times the configuration calls made on this thread, to find out where robot
+ * startup spends its time.
+ *
+ * Only calls made through a `Configurator`, i.e. `cfg()`, `configure()` or a
+ * robot config, are timed; direct `config_*` calls aren't.
+ *
+ * ```no_run
+ * # use ctre::DeviceId;
+ * # use ctre::config::ConfigSession;
+ * # use ctre::motor_control::{BaseMotorController, TalonSRX};
+ * let session = ConfigSession::start();
+ * let talon = TalonSRX::new(DeviceId::new(1));
+ * let _ = talon.configure().open_loop_ramp(0.2).neutral_deadband(0.02).apply(30);
+ * print!("{}", session.finish());
+ * ```
+ */
+#[derive(Debug)]
+pub struct ConfigSession {
+    started: Instant,
+    /// The calls of the session this one is nested in, if any.
+    outer: Option<Vec<ConfigCall>>,
+    finished: bool,
+}
+
+impl ConfigSession {
+    /// Starts timing calls on this thread.  A session started inside another
+    /// hides its calls from the outer one.
+    pub fn start() -> Self {
+        let outer = SESSION.with(|session| session.replace(Some(Vec::new())));
+        ConfigSession {
+            started: Instant::now(),
+            outer,
+            finished: false,
+        }
+    }
+    /// Stops timing, returning the calls made since `start`.
+    pub fn finish(mut self) -> ConfigReport {
+        let calls = self.take_calls();
+        ConfigReport {
+            total: self.started.elapsed(),
+            calls,
+        }
+    }
+    fn take_calls(&mut self) -> Vec<ConfigCall> {
+        self.finished = true;
+        let outer = self.outer.take();
+        SESSION
+            .with(|session| session.replace(outer))
+            .unwrap_or_default()
+    }
+    /// Adds a call to the current thread's session, if there is one.
+    fn record<F: FnOnce() -> ConfigCall>(call: F) {
+        SESSION.with(|session| {
+            if let Some(ref mut calls) = *session.borrow_mut() {
+                calls.push(call());
+            }
+        });
+    }
+}
+
+impl Drop for ConfigSession {
+    fn drop(&mut self) {
+        if !self.finished {
+            self.take_calls();
+        }
+    }
+}
+
+/// The calls timed by a [`ConfigSession`].
+///
+/// [`ConfigSession`]: struct.ConfigSession.html
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigReport {
+    /// Time from the session starting to it finishing, including time between calls.
+    pub total: Duration,
+    /// The calls, in the order they were made.
+    pub calls: Vec<ConfigCall>,
+}
+
+impl ConfigReport {
+    /// Time spent in calls.
+    pub fn call_time(&self) -> Duration {
+        self.calls.iter().map(|call| call.duration).sum()
+    }
+    /// The `n` slowest calls, slowest first.
+    pub fn slowest(&self, n: usize) -> Vec<&ConfigCall> {
+        let mut calls: Vec<_> = self.calls.iter().collect();
+        calls.sort_by_key(|call| Reverse(call.duration));
+        calls.truncate(n);
+        calls
+    }
+    /// The calls that were retried.
+    pub fn retried(&self) -> impl Iterator<Item = &ConfigCall> {
+        self.calls.iter().filter(|call| call.attempts > 1)
+    }
+}
+
+/// A summary line, then the ten slowest calls.
+impl fmt::Display for ConfigReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "{} config calls in {:.2} s ({:.2} s in calls), {} retried, {} failed",
+            self.calls.len(),
+            self.total.as_secs_f64(),
+            self.call_time().as_secs_f64(),
+            self.retried().count(),
+            self.calls.iter().filter(|call| !call.ok).count()
+        )?;
+        for call in self.slowest(10) {
+            writeln!(f, "  {}", call)?;
+        }
+        Ok(())
+    }
+}
+
+/// Related parameters, to keep across a factory reset.  See
+/// `BaseMotorController::reset_and_restore`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ParamGroup {
+    /// The two custom parameters, often used to hold calibration offsets.
+    CustomParams,
+    /// Every slot's gains, integral zone, allowable error and peak output.
+    Gains,
+    /// The selected sensors, their coefficients and the loop polarities.
+    Feedback,
+    /// Soft limit thresholds and whether they are enabled.
+    SoftLimits,
+    /// Current limit thresholds.
+    CurrentLimits,
+    /// Motion Magic cruise velocity and acceleration.
+    MotionMagic,
+    /// Ramps, peak and nominal outputs and the neutral deadband.
+    Output,
+}
+
+impl ParamGroup {
+    /// The group's parameters, with the ordinals each is set at.
+    pub fn params(self) -> &'static [(ParamEnum, &'static [i32])] {
+        const SLOTS: &[i32] = &[0, 1, 2, 3];
+        match self {
+            ParamGroup::CustomParams => &[(ParamEnum::CustomParam, &[0, 1])],
+            ParamGroup::Gains => &[
+                (ParamEnum::ProfileParamSlot_P, SLOTS),
+                (ParamEnum::ProfileParamSlot_I, SLOTS),
+                (ParamEnum::ProfileParamSlot_D, SLOTS),
+                (ParamEnum::ProfileParamSlot_F, SLOTS),
+                (ParamEnum::ProfileParamSlot_IZone, SLOTS),
+                (ParamEnum::ProfileParamSlot_AllowableErr, SLOTS),
+                (ParamEnum::ProfileParamSlot_MaxIAccum, SLOTS),
+                (ParamEnum::ProfileParamSlot_PeakOutput, SLOTS),
+            ],
+            ParamGroup::Feedback => &[
+                (ParamEnum::FeedbackSensorType, &[0, 1]),
+                (ParamEnum::SelectedSensorCoefficient, &[0, 1]),
+                (ParamEnum::PIDLoopPolarity, &[0, 1]),
+            ],
+            ParamGroup::SoftLimits => &[
+                (ParamEnum::ForwardSoftLimitThreshold, &[0]),
+                (ParamEnum::ReverseSoftLimitThreshold, &[0]),
+                (ParamEnum::ForwardSoftLimitEnable, &[0]),
+                (ParamEnum::ReverseSoftLimitEnable, &[0]),
+            ],
+            ParamGroup::CurrentLimits => &[
+                (ParamEnum::ContinuousCurrentLimitAmps, &[0]),
+                (ParamEnum::PeakCurrentLimitAmps, &[0]),
+                (ParamEnum::PeakCurrentLimitMs, &[0]),
+            ],
+            ParamGroup::MotionMagic => &[
+                (ParamEnum::MotMag_VelCruise, &[0]),
+                (ParamEnum::MotMag_Accel, &[0]),
+            ],
+            ParamGroup::Output => &[
+                (ParamEnum::OpenloopRamp, &[0]),
+                (ParamEnum::ClosedloopRamp, &[0]),
+                (ParamEnum::PeakPosOutput, &[0]),
+                (ParamEnum::PeakNegOutput, &[0]),
+                (ParamEnum::NominalPosOutput, &[0]),
+                (ParamEnum::NominalNegOutput, &[0]),
+                (ParamEnum::NeutralDeadband, &[0]),
+            ],
+        }
+    }
+}
+
+type Setting<'a, M> = Box<dyn Fn(&Configurator<'a, M>) -> Result<()> + 'a>;
+
+/**
+ * Collects settings for a motor controller, to be applied together.
+ *
+ * Settings are applied in the order they were added.  A failing setting doesn't
+ * stop the rest from being applied; all failures are returned together.  The
+ * current thread's [`ConfigContext`] supplies the retries and whether to verify,
+ * unless overridden.
+ *
+ * [`ConfigContext`]: struct.ConfigContext.html
+ */
+pub struct ConfigBuilder<'a, M: 'a + ?Sized> {
+    motor: &'a M,
+    verify: Option<bool>,
+    cancel: Option<CancelToken>,
+    settings: Vec<Setting<'a, M>>,
+}
+
+impl<'a, M: BaseMotorController + ?Sized> ConfigBuilder<'a, M> {
+    pub fn new(motor: &'a M) -> Self {
+        ConfigBuilder {
+            motor,
+            verify: None,
+            cancel: None,
+            settings: Vec::new(),
+        }
+    }
+    fn push<F: Fn(&Configurator<'a, M>) -> Result<()> + 'a>(mut self, setting: F) -> Self {
+        self.settings.push(Box::new(setting));
+        self
+    }
+
+    /// Whether to read each value back and check it was applied.
+    pub fn verify(mut self, verify: bool) -> Self {
+        self.verify = Some(verify);
+        self
+    }
+    /**
+     * Skips the remaining settings once `token` is cancelled, and shortens
+     * each call's timeout to end by its deadline.
+     */
+    pub fn cancel_on(mut self, token: &CancelToken) -> Self {
+        self.cancel = Some(token.clone());
+        self
+    }
+    /// Applies the settings, using `timeout_ms` for each call.
+    pub fn apply(self, timeout_ms: i32) -> ::std::result::Result<(), ConfigErrors> {
+        let current = ConfigContext::current();
+        let context = ConfigContext {
+            default_timeout_ms: timeout_ms,
+            verify: self.verify.unwrap_or(current.verify),
+            ..current
+        };
+        let mut errors = Vec::new();
+        let mut cancelled = 0;
+        for setting in &self.settings {
+            let timeout_ms = match self.cancel {
+                Some(ref token) => token.timeout_ms(timeout_ms),
+                None => Some(timeout_ms),
+            };
+            let default_timeout_ms = match timeout_ms {
+                Some(timeout_ms) => timeout_ms,
+                None => {
+                    cancelled += 1;
+                    continue;
+                }
+            };
+            let context = ConfigContext {
+                default_timeout_ms,
+                ..context
+            };
+            if let Err(err) = setting(&Configurator::with_context(self.motor, context)) {
+                errors.push(err);
+            }
+        }
+        events().publish(DeviceEvent::ConfigApplied {
+            device: self.motor.device_info(),
+            failures: errors.len(),
+        });
+        if errors.is_empty() && cancelled == 0 {
+            Ok(())
+        } else {
+            Err(ConfigErrors { errors, cancelled })
+        }
+    }
+
+    pub fn param(self, param: ParamEnum, value: f64, ordinal: i32) -> Self {
+        self.push(move |c| c.param(param, value, ordinal).map(drop))
+    }
+    pub fn kp(self, slot_idx: impl TryInto<Slot>, value: f64) -> Self {
+        let slot_idx = raw_slot_idx(slot_idx);
+        self.push(move |c| c.kp(slot_idx, value).map(drop))
+    }
+    pub fn ki(self, slot_idx: impl TryInto<Slot>, value: f64) -> Self {
+        let slot_idx = raw_slot_idx(slot_idx);
+        self.push(move |c| c.ki(slot_idx, value).map(drop))
+    }
+    pub fn kd(self, slot_idx: impl TryInto<Slot>, value: f64) -> Self {
+        let slot_idx = raw_slot_idx(slot_idx);
+        self.push(move |c| c.kd(slot_idx, value).map(drop))
+    }
+    pub fn kf(self, slot_idx: impl TryInto<Slot>, value: f64) -> Self {
+        let slot_idx = raw_slot_idx(slot_idx);
+        self.push(move |c| c.kf(slot_idx, value).map(drop))
+    }
+    pub fn integral_zone(self, slot_idx: impl TryInto<Slot>, izone: i32) -> Self {
+        let slot_idx = raw_slot_idx(slot_idx);
+        self.push(move |c| c.integral_zone(slot_idx, izone).map(drop))
+    }
+    pub fn allowable_closedloop_error(self, slot_idx: impl TryInto<Slot>, error: i32) -> Self {
+        let slot_idx = raw_slot_idx(slot_idx);
+        self.push(move |c| c.allowable_closedloop_error(slot_idx, error).map(drop))
+    }
+    pub fn max_integral_accumulator(self, slot_idx: impl TryInto<Slot>, iaccum: f64) -> Self {
+        let slot_idx = raw_slot_idx(slot_idx);
+        self.push(move |c| c.max_integral_accumulator(slot_idx, iaccum).map(drop))
+    }
+    pub fn closed_loop_peak_output(self, slot_idx: impl TryInto<Slot>, percent_out: f64) -> Self {
+        let slot_idx = raw_slot_idx(slot_idx);
+        self.push(move |c| c.closed_loop_peak_output(slot_idx, percent_out).map(drop))
+    }
+    pub fn closed_loop_period(self, slot_idx: impl TryInto<Slot>, loop_time_ms: i32) -> Self {
+        let slot_idx = raw_slot_idx(slot_idx);
+        self.push(move |c| c.closed_loop_period(slot_idx, loop_time_ms).map(drop))
+    }
+    /// Sets every setting of a gain slot, as separate settings.
+    pub fn gains(self, slot_idx: impl TryInto<Slot>, gains: &Gains) -> Self {
+        let slot_idx = raw_slot_idx(slot_idx);
+        self.kp(slot_idx, gains.kp)
+            .ki(slot_idx, gains.ki)
+            .kd(slot_idx, gains.kd)
+            .kf(slot_idx, gains.kf)
+            .integral_zone(slot_idx, gains.integral_zone)
+            .allowable_closedloop_error(slot_idx, gains.allowable_closedloop_error)
+            .max_integral_accumulator(slot_idx, gains.max_integral_accumulator)
+            .closed_loop_peak_output(slot_idx, gains.closed_loop_peak_output)
+            .closed_loop_period(slot_idx, gains.closed_loop_period_ms)
+    }
+    pub fn open_loop_ramp(self, seconds_from_neutral_to_full: f64) -> Self {
+        self.push(move |c| c.openloop_ramp(seconds_from_neutral_to_full).map(drop))
+    }
+    pub fn closed_loop_ramp(self, seconds_from_neutral_to_full: f64) -> Self {
+        self.push(move |c| c.closedloop_ramp(seconds_from_neutral_to_full).map(drop))
+    }
+    /// Sets the peak forward and reverse outputs, e.g. `(1.0, -1.0)`.
+    pub fn peak_output(self, forward: f64, reverse: f64) -> Self {
+        self.push(move |c| c.peak_output_forward(forward).map(drop))
+            .push(move |c| c.peak_output_reverse(reverse).map(drop))
+    }
+    /// Sets the nominal forward and reverse outputs, e.g. `(0.0, -0.0)`.
+    pub fn nominal_output(self, forward: f64, reverse: f64) -> Self {
+        self.push(move |c| c.nominal_output_forward(forward).map(drop))
+            .push(move |c| c.nominal_output_reverse(reverse).map(drop))
+    }
+    pub fn neutral_deadband(self, percent_deadband: f64) -> Self {
+        self.push(move |c| c.neutral_deadband(percent_deadband).map(drop))
+    }
+    pub fn voltage_comp_saturation(self, voltage: f64) -> Self {
+        self.push(move |c| c.voltage_comp_saturation(voltage).map(drop))
+    }
+    pub fn selected_feedback_coefficient(
+        self,
+        coefficient: f64,
+        pid_idx: impl TryInto<PidLoop>,
+    ) -> Self {
+        let pid_idx = raw_pid_idx(pid_idx);
+        self.push(move |c| {
+            c.selected_feedback_coefficient(coefficient, pid_idx)
+                .map(drop)
+        })
+    }
+    /// Sets the soft limit thresholds and enables both soft limits.
+    pub fn soft_limits(self, forward: i32, reverse: i32) -> Self {
+        self.push(move |c| c.forward_soft_limit_threshold(forward).map(drop))
+            .push(move |c| c.reverse_soft_limit_threshold(reverse).map(drop))
+            .push(|c| c.forward_soft_limit_enable(true).map(drop))
+            .push(|c| c.reverse_soft_limit_enable(true).map(drop))
+    }
+    pub fn motion_cruise_velocity(self, sensor_units_per_100ms: i32) -> Self {
+        self.push(move |c| c.motion_cruise_velocity(sensor_units_per_100ms).map(drop))
+    }
+    pub fn motion_acceleration(self, sensor_units_per_100ms_per_sec: i32) -> Self {
+        self.push(move |c| {
+            c.motion_acceleration(sensor_units_per_100ms_per_sec)
+                .map(drop)
+        })
+    }
+}
+
+impl<'a, M: ?Sized> fmt::Debug for ConfigBuilder<'a, M> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ConfigBuilder")
+            .field("verify", &self.verify)
+            .field("cancel", &self.cancel)
+            .field("settings", &self.settings.len())
+            .finish()
+    }
+}
+
+/// Whether a failed call is worth retrying.
+fn is_retryable(code: ErrorCode) -> bool {
+    code.is_timeout() || code.is_can_error() || code == ErrorCode::GeneralError
+}
+
+#[test]
+fn configurator_uses_thread_context() {
+    use motor_control::TalonSRX;
+    use DeviceId;
+    let talon = TalonSRX::new(DeviceId::new(8));
+    let verified = ConfigContext {
+        default_timeout_ms: 10,
+        retries: 1,
+        verify: true,
+    };
+    verified.scope(|| {
+        let cfg = talon.cfg();
+        assert_eq!(cfg.context(), verified);
+        cfg.kp(1, 0.2)
+            .unwrap()
+            .motion_cruise_velocity(1500)
+            .unwrap();
+    });
+    assert_eq!(ConfigContext::current(), ConfigContext::DEFAULT);
+    assert_eq!(
+        talon.config_get_parameter(ParamEnum::ProfileParamSlot_P, 1, 0),
+        Ok(0.2)
+    );
+    assert_eq!(
+        talon.config_get_parameter(ParamEnum::MotMag_VelCruise, 0, 0),
+        Ok(1500.)
+    );
+}
+
+#[test]
+fn builder_applies_in_order() {
+    use motor_control::TalonSRX;
+    use DeviceId;
+    let talon = TalonSRX::new(DeviceId::new(9));
+    talon
+        .configure()
+        .open_loop_ramp(0.2)
+        .peak_output(0.8, -0.6)
+        .neutral_deadband(0.02)
+        .verify(true)
+        .apply(10)
+        .unwrap();
+    assert_eq!(
+        talon.config_get_parameter(ParamEnum::PeakNegOutput, 0, 0),
+        Ok(-0.6)
+    );
+    assert_eq!(
+        talon.config_get_parameter(ParamEnum::NeutralDeadband, 0, 0),
+        Ok(0.02)
+    );
+}
+
+#[test]
+fn cancelled_builder_skips_settings() {
+    use motor_control::TalonSRX;
+    use DeviceId;
+    let talon = TalonSRX::new(DeviceId::new(39));
+    let token = CancelToken::new();
+    let builder = talon.configure().open_loop_ramp(0.3).cancel_on(&token);
+    token.cancel();
+    let errors = builder.apply(10).unwrap_err();
+    assert_eq!((errors.errors.len(), errors.cancelled), (0, 1));
+    assert_eq!(errors.to_string(), "0 config calls failed; 1 cancelled");
+    assert_eq!(
+        talon.config_get_parameter(ParamEnum::OpenloopRamp, 0, 0),
+        Ok(0.)
+    );
+
+    let expired = CancelToken::with_deadline(Instant::now());
+    assert!(expired.is_cancelled());
+    let later = CancelToken::with_timeout(Duration::from_secs(60));
+    assert_eq!(later.timeout_ms(50), Some(50));
+}
+
+#[test]
+fn session_times_calls() {
+    use motor_control::TalonSRX;
+    use DeviceId;
+    let talon = TalonSRX::new(DeviceId::new(42));
+    let session = ConfigSession::start();
+    talon.cfg().kp(0, 0.1).unwrap();
+    let inner = ConfigSession::start();
+    talon.cfg().openloop_ramp(0.2).unwrap();
+    assert_eq!(inner.finish().calls.len(), 1);
+    talon.configure().neutral_deadband(0.04).apply(10).unwrap();
+    let report = session.finish();
+    let params: Vec<_> = report.calls.iter().map(|call| call.param).collect();
+    assert_eq!(
+        params,
+        [ParamEnum::ProfileParamSlot_P, ParamEnum::NeutralDeadband]
+    );
+    assert!(report.call_time() <= report.total);
+    assert_eq!(report.slowest(1).len(), 1);
+    assert!(report.to_string().starts_with("2 config calls in "));
+    assert!(report.calls[0]
+        .to_string()
+        .starts_with("TalonSRX 42 ProfileParamSlot_P[0]: "));
+}
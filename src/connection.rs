@@ -0,0 +1,197 @@
+//! Detecting devices that have dropped off the CAN bus.
+//!
+//! A device's getters fail with a stale or timeout code once its status frames
+//! stop arriving, so a device is considered connected while its getters succeed.
+
+use std::cell::Cell;
+use std::time::Duration;
+
+use canifier::CANifier;
+use device::DeviceContext;
+use error::DeviceType;
+use events::{events, DeviceEvent};
+use motor_control::BaseMotorController;
+use sensors::pigeon::PigeonIMU;
+
+/// Connection status of a CAN device.
+pub trait Connection {
+    /// The kind of device and its CAN device number.
+    fn device(&self) -> (DeviceType, i32);
+    /// The label set with the device's `set_name`, if any.
+    fn device_name(&self) -> Option<&'static str> {
+        None
+    }
+    /// Polls a status signal, returning `false` if it is stale or missing.
+    fn is_connected(&self) -> bool;
+    /// Whether the device was created optional and its last status read got
+    /// no response.  Doesn't touch CAN.
+    fn is_absent(&self) -> bool;
+    /// Time since a getter on this object last returned fresh data,
+    /// or `None` if none has yet.
+    fn last_status_frame_age(&self) -> Option<Duration>;
+}
+
+impl<T: BaseMotorController> Connection for T {
+    fn device(&self) -> (DeviceType, i32) {
+        (self.device_type(), self.device_id())
+    }
+    fn device_name(&self) -> Option<&'static str> {
+        self.state().name()
+    }
+    fn is_connected(&self) -> bool {
+        self.get_bus_voltage().is_ok()
+    }
+    fn is_absent(&self) -> bool {
+        self.state().presence.is_absent()
+    }
+    fn last_status_frame_age(&self) -> Option<Duration> {
+        self.state().last_frame.age()
+    }
+}
+
+impl Connection for CANifier {
+    fn device(&self) -> (DeviceType, i32) {
+        (self.device_type(), self.device_id())
+    }
+    fn device_name(&self) -> Option<&'static str> {
+        self.state().name()
+    }
+    fn is_connected(&self) -> bool {
+        self.get_bus_voltage().is_ok()
+    }
+    fn is_absent(&self) -> bool {
+        self.state().presence.is_absent()
+    }
+    fn last_status_frame_age(&self) -> Option<Duration> {
+        self.state().last_frame.age()
+    }
+}
+
+impl Connection for PigeonIMU {
+    fn device(&self) -> (DeviceType, i32) {
+        (self.device_type(), self.device_id())
+    }
+    fn device_name(&self) -> Option<&'static str> {
+        self.state().name()
+    }
+    fn is_connected(&self) -> bool {
+        self.get_temp().is_ok()
+    }
+    fn is_absent(&self) -> bool {
+        self.state().presence.is_absent()
+    }
+    fn last_status_frame_age(&self) -> Option<Duration> {
+        self.state().last_frame.age()
+    }
+}
+
+/// A device reported by [`ConnectionMonitor::silent_devices`].
+///
+/// [`ConnectionMonitor::silent_devices`]: struct.ConnectionMonitor.html#method.silent_devices
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SilentDevice {
+    pub device_type: DeviceType,
+    pub device_id: i32,
+    pub name: Option<&'static str>,
+    /// Time since the device last responded, or `None` if it never has.
+    pub last_seen: Option<Duration>,
+}
+
+/**
+ * Watches a set of devices for ones that have gone silent on the bus.
+ *
+ * Call [`silent_devices`] periodically, e.g. once per robot loop, and report
+ * what it returns.  A device is only reported once it has been unresponsive
+ * for longer than the timeout, so a single dropped frame isn't flagged.
+ *
+ * [`silent_devices`]: #method.silent_devices
+ */
+pub struct ConnectionMonitor<'a> {
+    devices: Vec<&'a dyn Connection>,
+    /// Whether each device was silent at the last poll, to publish `Disconnected` once.
+    silent: Vec<Cell<bool>>,
+    timeout: Duration,
+}
+
+impl<'a> ConnectionMonitor<'a> {
+    /// * `timeout` - How long a device may go without responding before it is reported.
+    pub fn new(timeout: Duration) -> Self {
+        ConnectionMonitor {
+            devices: Vec::new(),
+            silent: Vec::new(),
+            timeout,
+        }
+    }
+    pub fn add(&mut self, device: &'a dyn Connection) -> &mut Self {
+        self.devices.push(device);
+        self.silent.push(Cell::new(false));
+        self
+    }
+    /// Polls every device, returning the ones that have been silent for too long.
+    pub fn silent_devices(&self) -> Vec<SilentDevice> {
+        let mut silent = Vec::new();
+        self.silent_devices_into(&mut silent);
+        silent
+    }
+    /// Like `silent_devices`, but reuses `silent`'s allocation.  `silent` is cleared first.
+    pub fn silent_devices_into(&self, silent: &mut Vec<SilentDevice>) {
+        silent.clear();
+        for (device, was_silent) in self.devices.iter().zip(&self.silent) {
+            if device.is_connected() {
+                was_silent.set(false);
+                continue;
+            }
+            let last_seen = device.last_status_frame_age();
+            if last_seen.is_some_and(|age| age <= self.timeout) {
+                continue;
+            }
+            let (device_type, device_id) = device.device();
+            let device = SilentDevice {
+                device_type,
+                device_id,
+                name: device.device_name(),
+                last_seen,
+            };
+            if !was_silent.replace(true) {
+                events().publish(DeviceEvent::Disconnected(device));
+            }
+            silent.push(device);
+        }
+    }
+}
+
+#[test]
+fn simulated_talon_is_connected() {
+    use motor_control::TalonSRX;
+    use DeviceId;
+    let talon = TalonSRX::new(DeviceId::new(40));
+    assert_eq!(talon.last_status_frame_age(), None);
+    assert!(talon.is_connected());
+    assert!(talon.last_status_frame_age().is_some());
+    let mut monitor = ConnectionMonitor::new(Duration::from_millis(100));
+    monitor.add(&talon);
+    assert!(monitor.silent_devices().is_empty());
+}
+
+#[test]
+fn absent_optional_device_fails_softly() {
+    use motor_control::TalonSRX;
+    use registry::registry;
+    use DeviceId;
+    use {ErrorCode, ParamEnum};
+    let talon = TalonSRX::new_optional(DeviceId::new(55));
+    assert!(!talon.is_absent());
+    // As if its last status read had got no frame.
+    talon.state().presence.set_absent(true);
+    talon.state().presence.last_probe.mark();
+    let err = talon.config_get_parameter(ParamEnum::PeakPosOutput, 0, 10);
+    assert_eq!(err.unwrap_err().code, ErrorCode::DeviceMissing);
+    let err = talon.config_peak_output_forward(0.5, 10).unwrap_err();
+    assert!(err.code.is_warning());
+    assert!(registry().is_absent(DeviceType::TalonSRX, 55));
+    assert_eq!(talon.error_stats().total(), 0);
+    // Once it is time to check again, the simulated Talon answers.
+    talon.state().presence.last_probe.clear();
+    assert_eq!(talon.config_peak_output_forward(0.5, 10), Ok(()));
+    assert!(!talon.is_absent());
+}
@@ -0,0 +1,116 @@
+//! Control requests: a control mode's demand packaged with its gain slot and feedforward.
+//!
+//! ```no_run
+//! # use ctre::DeviceId;
+//! # use ctre::control::VelocityRequest;
+//! # use ctre::motor_control::{BaseMotorController, Slot, TalonSRX};
+//! # fn main() -> ctre::Result<()> {
+//! let shooter = TalonSRX::new(DeviceId::new(1));
+//! shooter.apply(&VelocityRequest::new(2000.).with_slot(Slot::S1).with_ff(0.1))?;
+//! # Ok(())
+//! # }
+//! ```
+
+use motor_control::{ControlMode, Slot};
+
+/// A demand for a motor controller's primary loop.  See [`BaseMotorController::apply`].
+///
+/// [`BaseMotorController::apply`]: ../motor_control/trait.BaseMotorController.html#method.apply
+pub trait ControlRequest {
+    fn control_mode(&self) -> ControlMode;
+    /// The demand, in the units of the control mode.
+    fn demand(&self) -> f64;
+    /// Percent output added to the loop's output, from -1 to 1.
+    fn arbitrary_feed_forward(&self) -> f64 {
+        0.
+    }
+    /// The gain slot the primary loop should use, or `None` to leave it as is.
+    fn slot(&self) -> Option<Slot> {
+        None
+    }
+}
+
+macro_rules! control_request {
+    (
+        $(#[$attr:meta])*
+        $name:ident { $demand:ident }, $mode:expr
+    ) => {
+        $(#[$attr])*
+        #[derive(Debug, Copy, Clone, PartialEq)]
+        pub struct $name {
+            pub $demand: f64,
+            /// Arbitrary feedforward, in percent output.
+            pub ff: f64,
+            pub slot: Option<Slot>,
+        }
+
+        impl $name {
+            /// A request with no feedforward, leaving the selected slot as is.
+            pub fn new($demand: f64) -> Self {
+                $name {
+                    $demand,
+                    ff: 0.,
+                    slot: None,
+                }
+            }
+            pub fn with_ff(self, ff: f64) -> Self {
+                $name { ff, ..self }
+            }
+            pub fn with_slot(self, slot: Slot) -> Self {
+                $name {
+                    slot: Some(slot),
+                    ..self
+                }
+            }
+        }
+
+        impl ControlRequest for $name {
+            fn control_mode(&self) -> ControlMode {
+                $mode
+            }
+            fn demand(&self) -> f64 {
+                self.$demand
+            }
+            fn arbitrary_feed_forward(&self) -> f64 {
+                self.ff
+            }
+            fn slot(&self) -> Option<Slot> {
+                self.slot
+            }
+        }
+    };
+}
+
+control_request! {
+    /// Closed-loop position, in sensor units.
+    PositionRequest { position }, ControlMode::Position
+}
+control_request! {
+    /// Closed-loop velocity, in sensor units per 100ms.
+    VelocityRequest { velocity }, ControlMode::Velocity
+}
+control_request! {
+    /// Motion Magic to a position, in sensor units.
+    MotionMagicRequest { position }, ControlMode::MotionMagic
+}
+
+/// Open-loop output from -1 to 1.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct PercentOutputRequest {
+    pub output: f64,
+}
+
+impl PercentOutputRequest {
+    pub fn new(output: f64) -> Self {
+        PercentOutputRequest { output }
+    }
+}
+
+impl ControlRequest for PercentOutputRequest {
+    fn control_mode(&self) -> ControlMode {
+        ControlMode::PercentOutput
+    }
+    fn demand(&self) -> f64 {
+        self.output
+    }
+}
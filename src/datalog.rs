@@ -0,0 +1,403 @@
+//! A compact binary log of telemetry signals, for replaying and analysing runs.
+//!
+//! A log starts with a header naming its format version, followed by records.
+//! Signals are defined by records of their own, so a log can gain signals at
+//! any point and tooling that looks signals up by name keeps working as new
+//! ones are added.  Every record is prefixed with its kind and length, so a
+//! reader skips kinds of record added after it was written.
+//!
+//! ```text
+//! header:  "CTRELOG\0", version: u16
+//! record:  kind: u8, length: u16, payload
+//!   signal (kind 1):  id: u16, name length: u8, name, unit length: u8, unit
+//!   sample (kind 2):  id: u16, microseconds since the log started: u64, value: f64
+//! ```
+//!
+//! Integers and floats are little-endian; names and units are UTF-8.
+//!
+//! ```
+//! # use ctre::datalog::{Entry, LogReader, LogWriter};
+//! # fn main() -> std::io::Result<()> {
+//! let mut writer = LogWriter::new(Vec::new())?;
+//! let current = writer.add_signal("arm/output_current", "A")?;
+//! writer.write(current, 12.5)?;
+//!
+//! let bytes = writer.into_inner();
+//! let mut reader = LogReader::new(&bytes[..])?;
+//! let mut csv = Vec::new();
+//! reader.to_csv(&mut csv)?;
+//! assert!(String::from_utf8(csv).unwrap().ends_with("arm/output_current,A,12.5\n"));
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::BTreeMap;
+use std::io::{self, ErrorKind, Read, Write};
+use std::time::{Duration, Instant};
+
+use ctre_sys::clock;
+
+/// The format version written by `LogWriter`, and the newest `LogReader` reads.
+pub const VERSION: u16 = 1;
+
+const MAGIC: &[u8; 8] = b"CTRELOG\0";
+const SIGNAL: u8 = 1;
+const SAMPLE: u8 = 2;
+
+/// Identifies a signal within one log.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SignalId(pub u16);
+
+/// A signal's definition.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Signal {
+    pub id: SignalId,
+    /// e.g. `arm/output_current`.
+    pub name: String,
+    /// e.g. `A`, or empty if unitless.
+    pub unit: String,
+}
+
+/// One value of a signal.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Sample {
+    pub signal: SignalId,
+    /// Time since the log started.
+    pub timestamp: Duration,
+    pub value: f64,
+}
+
+/// A record read from a log.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Entry {
+    Signal(Signal),
+    Sample(Sample),
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, message.to_owned())
+}
+
+/// Writes a log.  Timestamps are measured from when the writer was created.
+#[derive(Debug)]
+pub struct LogWriter<W: Write> {
+    writer: W,
+    started: Instant,
+    next_id: u16,
+}
+
+impl<W: Write> LogWriter<W> {
+    /// Starts a log, writing its header.
+    pub fn new(mut writer: W) -> io::Result<Self> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&VERSION.to_le_bytes())?;
+        Ok(LogWriter {
+            writer,
+            started: clock::now(),
+            next_id: 0,
+        })
+    }
+
+    /// Defines a signal.  Names and units are at most 255 bytes.
+    pub fn add_signal(&mut self, name: &str, unit: &str) -> io::Result<SignalId> {
+        if name.len() > 255 || unit.len() > 255 {
+            return Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                "signal names and units are at most 255 bytes",
+            ));
+        }
+        let id = SignalId(self.next_id);
+        self.next_id = self
+            .next_id
+            .checked_add(1)
+            .ok_or_else(|| io::Error::new(ErrorKind::InvalidInput, "too many signals"))?;
+        let mut payload = Vec::with_capacity(4 + name.len() + unit.len());
+        payload.extend_from_slice(&id.0.to_le_bytes());
+        payload.push(name.len() as u8);
+        payload.extend_from_slice(name.as_bytes());
+        payload.push(unit.len() as u8);
+        payload.extend_from_slice(unit.as_bytes());
+        self.write_record(SIGNAL, &payload)?;
+        Ok(id)
+    }
+
+    /// Writes a value of `signal`, timestamped now.
+    pub fn write(&mut self, signal: SignalId, value: f64) -> io::Result<()> {
+        let timestamp = clock::now() - self.started;
+        self.write_at(signal, timestamp, value)
+    }
+    /// Writes a value of `signal` with the given time since the log started.
+    pub fn write_at(
+        &mut self,
+        signal: SignalId,
+        timestamp: Duration,
+        value: f64,
+    ) -> io::Result<()> {
+        let mut payload = [0; 18];
+        payload[..2].copy_from_slice(&signal.0.to_le_bytes());
+        payload[2..10].copy_from_slice(&(timestamp.as_micros() as u64).to_le_bytes());
+        payload[10..].copy_from_slice(&value.to_le_bytes());
+        self.write_record(SAMPLE, &payload)
+    }
+
+    fn write_record(&mut self, kind: u8, payload: &[u8]) -> io::Result<()> {
+        self.writer.write_all(&[kind])?;
+        self.writer
+            .write_all(&(payload.len() as u16).to_le_bytes())?;
+        self.writer.write_all(payload)
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/**
+ * Reads a log, as an iterator of its records.
+ *
+ * Records of kinds added in later versions of the format are skipped.  A log
+ * cut short, e.g. by losing power, ends the iteration with an
+ * `UnexpectedEof` error after its last whole record.
+ */
+#[derive(Debug)]
+pub struct LogReader<R: Read> {
+    reader: R,
+    version: u16,
+    signals: BTreeMap<SignalId, Signal>,
+}
+
+impl<R: Read> LogReader<R> {
+    /// Reads the log's header.  Fails if it isn't a log, or is a newer version.
+    pub fn new(mut reader: R) -> io::Result<Self> {
+        let mut header = [0; 10];
+        reader.read_exact(&mut header)?;
+        if &header[..8] != MAGIC {
+            return Err(invalid_data("not a CTRE log"));
+        }
+        let version = u16::from_le_bytes([header[8], header[9]]);
+        if version > VERSION {
+            return Err(invalid_data("log is from a newer version of the format"));
+        }
+        Ok(LogReader {
+            reader,
+            version,
+            signals: BTreeMap::new(),
+        })
+    }
+
+    /// The log's format version.
+    pub fn version(&self) -> u16 {
+        self.version
+    }
+    /// The definition of `id`, if it has been read yet.
+    pub fn signal(&self, id: SignalId) -> Option<&Signal> {
+        self.signals.get(&id)
+    }
+
+    /// Reads the next record, or `None` at the end of the log.
+    fn read_entry(&mut self) -> io::Result<Option<Entry>> {
+        loop {
+            let mut kind = [0; 1];
+            if self.reader.read(&mut kind)? == 0 {
+                return Ok(None);
+            }
+            let mut len = [0; 2];
+            self.reader.read_exact(&mut len)?;
+            let mut payload = vec![0; u16::from_le_bytes(len) as usize];
+            self.reader.read_exact(&mut payload)?;
+            match kind[0] {
+                SIGNAL => {
+                    let signal = parse_signal(&payload)?;
+                    self.signals.insert(signal.id, signal.clone());
+                    return Ok(Some(Entry::Signal(signal)));
+                }
+                SAMPLE => return parse_sample(&payload).map(|sample| Some(Entry::Sample(sample))),
+                _ => continue,
+            }
+        }
+    }
+
+    /// Converts the rest of the log to CSV, one row per sample:
+    /// `time_s,signal,unit,value`.
+    pub fn to_csv<W: Write>(&mut self, mut out: W) -> io::Result<()> {
+        writeln!(out, "time_s,signal,unit,value")?;
+        while let Some(entry) = self.read_entry()? {
+            if let Entry::Sample(sample) = entry {
+                let (name, unit) = self.name_and_unit(sample.signal);
+                writeln!(
+                    out,
+                    "{:.6},{},{},{}",
+                    sample.timestamp.as_secs_f64(),
+                    csv_field(&name),
+                    csv_field(unit),
+                    sample.value
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Converts the rest of the log to JSON lines, one object per sample:
+    /// `{"time_s": 1.5, "signal": "arm/output_current", "unit": "A", "value": 12.5}`.
+    /// Values that aren't finite are `null`.
+    pub fn to_json<W: Write>(&mut self, mut out: W) -> io::Result<()> {
+        while let Some(entry) = self.read_entry()? {
+            if let Entry::Sample(sample) = entry {
+                let (name, unit) = self.name_and_unit(sample.signal);
+                let value = if sample.value.is_finite() {
+                    sample.value.to_string()
+                } else {
+                    "null".to_owned()
+                };
+                writeln!(
+                    out,
+                    "{{\"time_s\": {:.6}, \"signal\": {}, \"unit\": {}, \"value\": {}}}",
+                    sample.timestamp.as_secs_f64(),
+                    json_string(&name),
+                    json_string(unit),
+                    value
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    /// A sample's signal name and unit, or its id if it wasn't defined.
+    fn name_and_unit(&self, id: SignalId) -> (String, &str) {
+        match self.signals.get(&id) {
+            Some(signal) => (signal.name.clone(), &signal.unit),
+            None => (format!("#{}", id.0), ""),
+        }
+    }
+}
+
+impl<R: Read> Iterator for LogReader<R> {
+    type Item = io::Result<Entry>;
+    fn next(&mut self) -> Option<io::Result<Entry>> {
+        self.read_entry().transpose()
+    }
+}
+
+fn parse_signal(payload: &[u8]) -> io::Result<Signal> {
+    let truncated = || invalid_data("truncated signal record");
+    let id = SignalId(u16::from_le_bytes([
+        *payload.first().ok_or_else(truncated)?,
+        *payload.get(1).ok_or_else(truncated)?,
+    ]));
+    let mut rest = &payload[2..];
+    let mut string = || -> io::Result<String> {
+        let (&len, tail) = rest.split_first().ok_or_else(truncated)?;
+        let bytes = tail.get(..len as usize).ok_or_else(truncated)?;
+        rest = &tail[len as usize..];
+        String::from_utf8(bytes.to_vec()).map_err(|_| invalid_data("signal name is not UTF-8"))
+    };
+    let name = string()?;
+    let unit = string()?;
+    Ok(Signal { id, name, unit })
+}
+
+fn parse_sample(payload: &[u8]) -> io::Result<Sample> {
+    if payload.len() < 18 {
+        return Err(invalid_data("truncated sample record"));
+    }
+    let mut micros = [0; 8];
+    micros.copy_from_slice(&payload[2..10]);
+    let mut value = [0; 8];
+    value.copy_from_slice(&payload[10..18]);
+    Ok(Sample {
+        signal: SignalId(u16::from_le_bytes([payload[0], payload[1]])),
+        timestamp: Duration::from_micros(u64::from_le_bytes(micros)),
+        value: f64::from_le_bytes(value),
+    })
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+fn json_string(string: &str) -> String {
+    let mut json = String::with_capacity(string.len() + 2);
+    json.push('"');
+    for c in string.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            c if (c as u32) < 0x20 => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+#[test]
+fn log_round_trips_and_skips_unknown_records() {
+    let mut writer = LogWriter::new(Vec::new()).unwrap();
+    let current = writer.add_signal("arm/output_current", "A").unwrap();
+    writer
+        .write_at(current, Duration::from_millis(20), 12.5)
+        .unwrap();
+    // A record kind from a later version of the format.
+    writer.write_record(9, &[1, 2, 3]).unwrap();
+    let label = writer.add_signal("say \"hi\", arm", "").unwrap();
+    writer
+        .write_at(label, Duration::from_millis(40), f64::NAN)
+        .unwrap();
+    let bytes = writer.into_inner();
+
+    let entries: Vec<Entry> = LogReader::new(&bytes[..])
+        .unwrap()
+        .collect::<io::Result<_>>()
+        .unwrap();
+    assert_eq!(entries.len(), 4);
+    assert_eq!(
+        entries[1],
+        Entry::Sample(Sample {
+            signal: current,
+            timestamp: Duration::from_millis(20),
+            value: 12.5,
+        })
+    );
+
+    let mut csv = Vec::new();
+    LogReader::new(&bytes[..])
+        .unwrap()
+        .to_csv(&mut csv)
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(csv).unwrap(),
+        "time_s,signal,unit,value\n\
+         0.020000,arm/output_current,A,12.5\n\
+         0.040000,\"say \"\"hi\"\", arm\",,NaN\n"
+    );
+    let mut json = Vec::new();
+    LogReader::new(&bytes[..])
+        .unwrap()
+        .to_json(&mut json)
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(json).unwrap().lines().nth(1),
+        Some(r#"{"time_s": 0.040000, "signal": "say \"hi\", arm", "unit": "", "value": null}"#)
+    );
+
+    // A cut-off log ends with an error after its last whole record.
+    let mut reader = LogReader::new(&bytes[..bytes.len() - 1]).unwrap();
+    assert_eq!(reader.by_ref().filter(Result::is_ok).count(), 3);
+}
+
+#[test]
+fn newer_logs_are_rejected() {
+    let mut bytes = MAGIC.to_vec();
+    bytes.extend_from_slice(&(VERSION + 1).to_le_bytes());
+    let err = LogReader::new(&bytes[..]).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+    assert!(LogReader::new(&b"not a log!"[..]).is_err());
+}
@@ -0,0 +1,215 @@
+//! Running with some of the robot's devices missing.
+//!
+//! On a practice robot or a bench, devices are often unplugged or not fitted
+//! yet.  Created with `new_optional`, a missing device fails its calls softly
+//! rather than stalling the program; a [`DegradationPolicy`] then turns off
+//! the features that can't work without it, such as holding a heading with an
+//! auxiliary loop when the Pigeon is missing, and back on if it returns.
+//!
+//! ```no_run
+//! # use ctre::DeviceId;
+//! # use ctre::degradation::DegradationPolicy;
+//! # use ctre::sensors::pigeon::PigeonIMU;
+//! let imu = PigeonIMU::new_optional(DeviceId::new(0));
+//! imu.set_name("imu");
+//! let mut policy = DegradationPolicy::new();
+//! policy.feature("heading_lock", &["imu"]);
+//! // Every loop:
+//! policy.update();
+//! if policy.is_enabled("heading_lock") {
+//!     // drive straight with the aux loop on the Pigeon's heading
+//! }
+//! ```
+//!
+//! [`DegradationPolicy`]: struct.DegradationPolicy.html
+
+use std::fmt;
+
+use events::{events, DeviceEvent};
+use registry::{registry, DeviceInfo};
+
+/// Why a feature was turned off or back on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Decision {
+    pub feature: &'static str,
+    pub enabled: bool,
+    /// When disabled, the devices it needs that are missing.
+    pub missing: Vec<Missing>,
+}
+
+/// A device a feature needs that isn't available.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Missing {
+    /// Created optional, and not on the bus.
+    Absent(DeviceInfo),
+    /// No device with this name has been created.
+    NotCreated(&'static str),
+}
+
+impl fmt::Display for Missing {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Missing::Absent(device) => write!(f, "{} is absent", device),
+            Missing::NotCreated(name) => write!(f, "{} was never created", name),
+        }
+    }
+}
+
+/// e.g. `heading_lock disabled: imu (PigeonIMU 0) is absent`.
+impl fmt::Display for Decision {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.enabled {
+            return write!(f, "{} enabled", self.feature);
+        }
+        write!(f, "{} disabled", self.feature)?;
+        for (i, missing) in self.missing.iter().enumerate() {
+            f.write_str(if i == 0 { ": " } else { ", " })?;
+            missing.fmt(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Feature {
+    name: &'static str,
+    /// Names of the devices it needs, as set with `set_name`.
+    requires: Vec<&'static str>,
+    enabled: bool,
+}
+
+/**
+ * Turns features on and off as the devices they need come and go.
+ *
+ * Each feature names the devices it needs.  `update` checks the [`registry`]
+ * for each of them, and disables any feature with a device that is absent or
+ * was never created, publishing each decision as a `FeatureChanged` [event]
+ * as it changes.  Features
+ * start out enabled, and are re-enabled once all their devices are back.
+ *
+ * [`registry`]: ../fn.registry.html
+ * [event]: ../events/enum.DeviceEvent.html
+ */
+#[derive(Debug, Clone, Default)]
+pub struct DegradationPolicy {
+    features: Vec<Feature>,
+}
+
+impl DegradationPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Declares `name` as a feature needing the devices named `requires`.
+    pub fn feature(&mut self, name: &'static str, requires: &[&'static str]) -> &mut Self {
+        self.features.push(Feature {
+            name,
+            requires: requires.to_vec(),
+            enabled: true,
+        });
+        self
+    }
+
+    /// Whether the feature `name` can run, as of the last `update`.  Features
+    /// that weren't declared are always enabled.
+    pub fn is_enabled(&self, name: &str) -> bool {
+        self.features
+            .iter()
+            .filter(|feature| feature.name == name)
+            .all(|feature| feature.enabled)
+    }
+
+    /// The features disabled as of the last `update`.
+    pub fn disabled(&self) -> Vec<&'static str> {
+        self.features
+            .iter()
+            .filter(|feature| !feature.enabled)
+            .map(|feature| feature.name)
+            .collect()
+    }
+
+    /**
+     * Checks the devices each feature needs, and disables or re-enables
+     * features accordingly.  Returns the features that changed, each of
+     * which is also published as an event.  Doesn't touch CAN.
+     */
+    pub fn update(&mut self) -> Vec<Decision> {
+        let decisions = self.decide();
+        for decision in &decisions {
+            events().publish(DeviceEvent::FeatureChanged {
+                feature: decision.feature,
+                enabled: decision.enabled,
+            });
+        }
+        decisions
+    }
+
+    fn decide(&mut self) -> Vec<Decision> {
+        let mut decisions = Vec::new();
+        for feature in &mut self.features {
+            let missing: Vec<_> = feature
+                .requires
+                .iter()
+                .filter_map(|&name| missing(name))
+                .collect();
+            let enabled = missing.is_empty();
+            if enabled != feature.enabled {
+                feature.enabled = enabled;
+                decisions.push(Decision {
+                    feature: feature.name,
+                    enabled,
+                    missing,
+                });
+            }
+        }
+        decisions
+    }
+}
+
+fn missing(name: &'static str) -> Option<Missing> {
+    match registry().find(name) {
+        None => Some(Missing::NotCreated(name)),
+        Some(device) if registry().is_absent(device.device_type, device.id) => {
+            Some(Missing::Absent(device))
+        }
+        Some(_) => None,
+    }
+}
+
+#[test]
+fn features_follow_their_devices() {
+    use device::DeviceContext;
+    use sensors::pigeon::PigeonIMU;
+    use DeviceId;
+    let imu = PigeonIMU::new_optional(DeviceId::new(21));
+    imu.set_name("degradation_imu");
+    let mut policy = DegradationPolicy::new();
+    policy
+        .feature("heading_lock", &["degradation_imu"])
+        .feature("vision", &["degradation_camera_lights"]);
+    assert_eq!(
+        policy.update()[0].to_string(),
+        "vision disabled: degradation_camera_lights was never created"
+    );
+    assert!(policy.is_enabled("heading_lock"));
+
+    // As if the Pigeon had stopped answering.
+    imu.state().presence.set_absent(true);
+    let receiver = events().channel();
+    let decisions = policy.update();
+    let disabled = DeviceEvent::FeatureChanged {
+        feature: "heading_lock",
+        enabled: false,
+    };
+    assert!(receiver.try_iter().any(|event| event == disabled));
+    assert_eq!(decisions.len(), 1);
+    assert_eq!(
+        decisions[0].to_string(),
+        "heading_lock disabled: degradation_imu (PigeonIMU 21) is absent"
+    );
+    assert_eq!(policy.disabled(), ["heading_lock", "vision"]);
+    assert!(policy.update().is_empty());
+
+    imu.state().presence.set_absent(false);
+    assert_eq!(policy.update()[0].to_string(), "heading_lock enabled");
+    assert!(policy.is_enabled("heading_lock") && !policy.is_enabled("vision"));
+}
@@ -0,0 +1,53 @@
+//! The arithmetic Phoenix applies to demands and settings before they reach the CCI.
+//!
+//! These are pure functions so they can be checked against Phoenix's C++
+//! implementation without hardware; see `tests/phoenix_golden.rs`.  When
+//! porting a new Phoenix behavior, add its arithmetic here with golden values.
+
+/**
+ * The id a follower is told to follow: the master's device class bits from
+ * its arbitration id, then its device number.  As Phoenix's
+ * `BaseMotorController::Follow`.
+ */
+pub fn follower_id24(master_base_id: i32) -> i32 {
+    let class = i32::from((master_base_id >> 16) as u16);
+    (class << 8) | (master_base_id & 0xFF)
+}
+
+/**
+ * The demand sent for `ControlMode::Follower` by a controller with
+ * `own_base_id`.  A device number in [0,62] is taken to be a controller of
+ * the same class; anything else is passed on as an id from `follower_id24`.
+ * As Phoenix's `BaseMotorController::Set`.
+ */
+pub fn follower_demand(own_base_id: i32, demand0: f64) -> f64 {
+    let work = if (0.0..=62.0).contains(&demand0) {
+        (((own_base_id as u32) >> 16) << 8) | ((demand0 as u32) & 0xFF)
+    } else {
+        demand0 as u32
+    };
+    // Single precision has 24 bits of integral precision, so this is exact.
+    f64::from(work)
+}
+
+/// The demand sent for `ControlMode::Current`: milliamps, truncated toward zero.
+pub fn current_demand(amps: f64) -> i32 {
+    (1000. * amps) as i32
+}
+
+/**
+ * The feedback coefficient scaling a sensor with `native_units_per_rotation`
+ * to `units_per_rotation`, or `None` if it would be outside (0, 1], which the
+ * controller doesn't support.
+ */
+pub fn feedback_coefficient(
+    native_units_per_rotation: f64,
+    units_per_rotation: f64,
+) -> Option<f64> {
+    let coefficient = units_per_rotation / native_units_per_rotation;
+    if coefficient > 0. && coefficient <= 1. {
+        Some(coefficient)
+    } else {
+        None
+    }
+}
@@ -0,0 +1,289 @@
+//! Bookkeeping shared by every device type.
+
+use std::os::raw::c_void;
+use std::ptr;
+use std::sync::atomic::{AtomicBool, AtomicI32, AtomicPtr, AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use error::{DeviceError, DeviceType, ErrorCounters, ErrorLog, ErrorRecord};
+use motor_control::{Inversion, Outputs};
+use registry::DeviceInfo;
+use {DeviceId, ErrorCode, ParamEnum};
+
+/// State kept alongside each device's handle.
+#[derive(Debug, Default)]
+pub(crate) struct DeviceState {
+    /// The CCI handle and the device number it is for.
+    binding: AtomicPtr<Binding>,
+    pub errors: ErrorLog,
+    pub error_counts: ErrorCounters,
+    pub last_frame: FrameClock,
+    /// Label set with `set_name`, for logs and error messages.
+    name: Mutex<Option<&'static str>>,
+    pub selected_slots: SelectedSlots,
+    /// When each closed loop came within tolerance, for `at_setpoint`.
+    pub on_target: [FrameClock; 2],
+    pub config_record: ConfigRecord,
+    /// Motor controllers only.
+    pub inversion: Mutex<Inversion>,
+    /// Motor controllers only: whether the last `set` was in Follower mode.
+    pub following: AtomicBool,
+    /// Motor controllers only.
+    pub outputs: Outputs,
+    pub presence: Presence,
+}
+
+/// A CCI handle, untyped as each device type has its own handle type, and the
+/// device number it was created for.  `rebind` replaces both at once.
+#[derive(Debug)]
+struct Binding {
+    handle: *mut c_void,
+    device_id: DeviceId,
+}
+
+impl DeviceState {
+    fn binding(&self) -> Option<&Binding> {
+        unsafe { self.binding.load(Ordering::Acquire).as_ref() }
+    }
+    /// The device's CCI handle, which `rebind` replaces on a motor controller.
+    pub fn handle<T>(&self) -> *mut T {
+        self.binding()
+            .map_or(ptr::null_mut(), |binding| binding.handle as *mut T)
+    }
+    /// The device number `handle` is for.
+    pub fn device_id(&self) -> DeviceId {
+        self.binding()
+            .map_or(DeviceId::new(0), |binding| binding.device_id)
+    }
+    pub fn device_number(&self) -> i32 {
+        i32::from(self.device_id())
+    }
+    pub fn bind<T>(&self, handle: *mut T, device_id: DeviceId) {
+        let binding = Box::new(Binding {
+            handle: handle as *mut c_void,
+            device_id,
+        });
+        // A call on another thread may still be reading the old binding, so
+        // it is left as is, like its handle.
+        self.binding
+            .store(Box::into_raw(binding), Ordering::Release);
+    }
+    pub fn name(&self) -> Option<&'static str> {
+        *self.name.lock().unwrap()
+    }
+    pub fn set_name(&self, name: &'static str) {
+        *self.name.lock().unwrap() = Some(name);
+    }
+}
+
+impl Drop for DeviceState {
+    fn drop(&mut self) {
+        let binding = *self.binding.get_mut();
+        if !binding.is_null() {
+            drop(unsafe { Box::from_raw(binding) });
+        }
+    }
+}
+
+/// The gain slot last selected for each closed loop, so it needn't be reselected.
+#[derive(Debug)]
+pub(crate) struct SelectedSlots {
+    /// Slot index per loop, or -1 if unknown.
+    slots: [AtomicI32; 2],
+}
+
+impl Default for SelectedSlots {
+    fn default() -> Self {
+        SelectedSlots {
+            slots: [AtomicI32::new(-1), AtomicI32::new(-1)],
+        }
+    }
+}
+
+impl SelectedSlots {
+    pub fn get(&self, pid_idx: i32) -> Option<i32> {
+        match self.slots[pid_idx as usize].load(Ordering::Relaxed) {
+            -1 => None,
+            slot_idx => Some(slot_idx),
+        }
+    }
+    pub fn set(&self, pid_idx: i32, slot_idx: Option<i32>) {
+        self.slots[pid_idx as usize].store(slot_idx.unwrap_or(-1), Ordering::Relaxed);
+    }
+}
+
+/// Parameters set through a `Configurator`, so they can be re-applied to a
+/// replacement device.
+#[derive(Debug, Default)]
+pub(crate) struct ConfigRecord {
+    /// `(param, ordinal, value)`, in the order each was first set.
+    settings: Mutex<Vec<(ParamEnum, i32, f64)>>,
+}
+
+impl ConfigRecord {
+    pub fn record(&self, param: ParamEnum, ordinal: i32, value: f64) {
+        let mut settings = self.settings.lock().unwrap();
+        match settings.iter_mut().find(|s| (s.0, s.1) == (param, ordinal)) {
+            Some(setting) => setting.2 = value,
+            None => settings.push((param, ordinal, value)),
+        }
+    }
+    pub fn settings(&self) -> Vec<(ParamEnum, i32, f64)> {
+        self.settings.lock().unwrap().clone()
+    }
+    #[cfg(feature = "phoenix-2019")]
+    pub fn clear(&self) {
+        self.settings.lock().unwrap().clear();
+    }
+}
+
+/**
+ * Whether a device created optional answered its last status read.
+ *
+ * A status read that gets no frame from an optional device marks it absent.
+ * While it is absent, calls return `DeviceMissing` at once rather than going
+ * out on CAN and waiting out their timeouts, so a robot can run with it
+ * unplugged.  Once every `PROBE_PERIOD`, a call first checks whether it has
+ * come back.
+ */
+#[derive(Debug, Default)]
+pub(crate) struct Presence {
+    optional: AtomicBool,
+    absent: AtomicBool,
+    /// When it was last checked for while absent.
+    pub last_probe: FrameClock,
+}
+
+/// How often an absent device is checked for.
+const PROBE_PERIOD: Duration = Duration::from_secs(1);
+
+impl Presence {
+    pub fn set_optional(&self) {
+        self.optional.store(true, Ordering::Relaxed);
+    }
+    pub fn is_optional(&self) -> bool {
+        self.optional.load(Ordering::Relaxed)
+    }
+    pub fn is_absent(&self) -> bool {
+        self.absent.load(Ordering::Relaxed)
+    }
+    pub fn set_absent(&self, absent: bool) {
+        self.absent.store(absent, Ordering::Relaxed);
+    }
+    /// Whether it is time to check for an absent device again.
+    fn probe_due(&self) -> bool {
+        let due = self.last_probe.age().is_none_or(|age| age >= PROBE_PERIOD);
+        if due {
+            self.last_probe.mark();
+        }
+        due
+    }
+}
+
+/// When something last happened on a device, such as a call getting a fresh
+/// response over CAN.
+#[derive(Debug, Default)]
+pub(crate) struct FrameClock {
+    /// Nanoseconds after `epoch()`, plus one; zero if never.
+    nanos: AtomicU64,
+}
+
+fn epoch() -> Instant {
+    static EPOCH: OnceLock<Instant> = OnceLock::new();
+    *EPOCH.get_or_init(Instant::now)
+}
+
+impl FrameClock {
+    pub fn mark(&self) {
+        let nanos = epoch().elapsed().as_nanos() as u64 + 1;
+        self.nanos.store(nanos, Ordering::Relaxed);
+    }
+
+    pub fn clear(&self) {
+        self.nanos.store(0, Ordering::Relaxed);
+    }
+
+    /// Time since the last `mark`, or `None` if there hasn't been one.
+    pub fn age(&self) -> Option<Duration> {
+        match self.nanos.load(Ordering::Relaxed) {
+            0 => None,
+            nanos => Some(epoch().elapsed() - Duration::from_nanos(nanos - 1)),
+        }
+    }
+}
+
+/// Devices whose calls can be attributed and tracked.
+pub(crate) trait DeviceContext {
+    fn device_type(&self) -> DeviceType;
+    fn device_id(&self) -> i32;
+    fn state(&self) -> &DeviceState;
+    /// Reads a status signal, without blocking, to see if the device responds.
+    fn probe(&self) -> bool;
+
+    /// Records a failed call to `operation` and builds its `DeviceError`.
+    fn error(&self, code: ErrorCode, operation: &'static str) -> DeviceError {
+        self.state().errors.push(ErrorRecord {
+            timestamp: Instant::now(),
+            operation,
+            code,
+        });
+        self.state().error_counts.count(code);
+        DeviceError::new(code, self.device_type(), self.device_id(), operation)
+            .with_name(self.state().name())
+    }
+
+    /// Records that a getter returned fresh data from the device.
+    fn frame_received(&self) {
+        self.state().last_frame.mark();
+        self.state().presence.set_absent(false);
+    }
+
+    /// Makes the device optional, and checks whether it is there.
+    fn make_optional(&self) {
+        let presence = &self.state().presence;
+        presence.set_optional();
+        presence.set_absent(!self.probe());
+    }
+
+    /// The soft error a call to `operation` returns instead of going out on
+    /// CAN, if the device is optional and absent.  It isn't recorded.
+    fn missing(&self, operation: &'static str) -> Option<DeviceError> {
+        let presence = &self.state().presence;
+        if !presence.is_absent() {
+            return None;
+        }
+        if presence.probe_due() && self.probe() {
+            presence.set_absent(false);
+            return None;
+        }
+        let err = DeviceError::new(
+            ErrorCode::DeviceMissing,
+            self.device_type(),
+            self.device_id(),
+            operation,
+        );
+        Some(err.with_name(self.state().name()))
+    }
+
+    /// Records a status read of `operation` failing with `code`.  An optional
+    /// device that sent no frame is marked absent, and gets `DeviceMissing`.
+    fn read_failed(&self, code: ErrorCode, operation: &'static str) -> DeviceError {
+        let presence = &self.state().presence;
+        if presence.is_optional() && (code.is_can_error() || code.is_timeout()) {
+            presence.set_absent(true);
+        }
+        match self.missing(operation) {
+            Some(err) => err,
+            None => self.error(code, operation),
+        }
+    }
+
+    fn device_info(&self) -> DeviceInfo {
+        DeviceInfo {
+            device_type: self.device_type(),
+            id: self.device_id(),
+            name: self.state().name(),
+        }
+    }
+}
@@ -0,0 +1,473 @@
+//! Drivetrain moves built on the motor controllers' closed loops, drivetrain neutral modes,
+//! and heading hold.
+
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use ctre_sys::clock;
+
+use input::JoystickAxis;
+use motion::{HeadingUnits, MotionMagicCompletion};
+use motor_control::{BaseMotorController, ControlMode, DemandType, FollowerType, NeutralMode};
+use robot_state::RobotStateProvider;
+use sensors::pigeon::PigeonIMU;
+use Result;
+
+/// Settings for [`drive_straight_distance`].
+///
+/// [`drive_straight_distance`]: fn.drive_straight_distance.html
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct DriveStraightConfig {
+    /// Units of the master's aux (heading) loop.
+    pub heading_units: HeadingUnits,
+    /// Allowed distance from the target and closed-loop error at the end of the move.
+    pub tolerance_ticks: i32,
+    /// Allowed trajectory velocity at the end of the move, in sensor units per 100ms.
+    pub velocity_tolerance: i32,
+    /// How long the move must stay on target before it is finished.
+    pub settle: Duration,
+}
+
+impl Default for DriveStraightConfig {
+    fn default() -> Self {
+        DriveStraightConfig {
+            heading_units: HeadingUnits::TENTHS_OF_DEGREE,
+            tolerance_ticks: 50,
+            velocity_tolerance: 20,
+            settle: Duration::from_millis(100),
+        }
+    }
+}
+
+/// A drive started by [`drive_straight_distance`].
+///
+/// [`drive_straight_distance`]: fn.drive_straight_distance.html
+#[derive(Debug)]
+pub struct DriveStraight<'a, M: 'a> {
+    master: &'a M,
+    start: i32,
+    distance: f64,
+    completion: MotionMagicCompletion<'a, M>,
+}
+
+/**
+ * Drives `distance` sensor units from the current position with Motion Magic,
+ * while the aux loop holds `heading_deg`.
+ *
+ * `master` must have its primary loop on the drive distance (e.g. the sum of
+ * both sides' encoders) and its aux loop on a heading sensor such as a Pigeon,
+ * with Motion Magic cruise velocity and acceleration configured.  `follower`
+ * is set to follow the master's aux output, so the aux loop steers.
+ *
+ * Call [`is_finished`] periodically until the move is done.
+ *
+ * [`is_finished`]: struct.DriveStraight.html#method.is_finished
+ */
+pub fn drive_straight_distance<'a, M, F>(
+    master: &'a M,
+    follower: &F,
+    distance: f64,
+    heading_deg: f64,
+    config: &DriveStraightConfig,
+) -> Result<DriveStraight<'a, M>>
+where
+    M: BaseMotorController,
+    F: BaseMotorController,
+{
+    let start = master.get_selected_sensor_position(0)?;
+    let target = f64::from(start) + distance;
+    let mut completion = MotionMagicCompletion::new(
+        master,
+        config.tolerance_ticks,
+        config.velocity_tolerance,
+        config.settle,
+    );
+    completion.set_target(target);
+    follower.follow(master, FollowerType::AuxOutput1)?;
+    let heading = config.heading_units.degrees_to_units(heading_deg);
+    master.set(
+        ControlMode::MotionMagic,
+        target,
+        DemandType::AuxPID,
+        heading,
+    );
+    Ok(DriveStraight {
+        master,
+        start,
+        distance,
+        completion,
+    })
+}
+
+impl<'a, M: BaseMotorController> DriveStraight<'a, M> {
+    /// The fraction of the distance covered so far, from 0 to 1.
+    pub fn progress(&self) -> Result<f64> {
+        if self.distance == 0. {
+            return Ok(1.);
+        }
+        let position = self.master.get_selected_sensor_position(0)?;
+        let covered = f64::from(position - self.start) / self.distance;
+        Ok(covered.clamp(0., 1.))
+    }
+    /// Whether the drive has reached the distance and settled.
+    pub fn is_finished(&mut self) -> Result<bool> {
+        self.completion.is_motion_magic_finished()
+    }
+    /// Stops the drive by disabling the master's output.  The follower follows.
+    pub fn stop(&self) {
+        self.master.neutral_output();
+    }
+}
+
+/// Setting the neutral mode, for any motor controller.
+trait NeutralModeDevice {
+    fn apply_neutral_mode(&self, mode: NeutralMode);
+}
+
+impl<T: BaseMotorController> NeutralModeDevice for T {
+    fn apply_neutral_mode(&self, mode: NeutralMode) {
+        self.set_neutral_mode(mode);
+    }
+}
+
+/**
+ * Brakes drivetrain motors while the robot is enabled, and lets them coast a
+ * while after it is disabled so the robot can be pushed off the field.
+ *
+ * Call [`update`] or [`update_from`] periodically, in both enabled and
+ * disabled modes.  The neutral mode is only sent to the controllers when it
+ * changes.
+ *
+ * ```no_run
+ * # use ctre::DeviceId;
+ * # use std::time::Duration;
+ * # use ctre::drive::NeutralModeScheduler;
+ * # use ctre::motor_control::{BaseMotorController, TalonSRX};
+ * # use ctre::robot_state::{RobotMode, SharedRobotState};
+ * let state = SharedRobotState::new(RobotMode::Disabled);
+ * let (left, right) = (TalonSRX::new(DeviceId::new(1)), TalonSRX::new(DeviceId::new(2)));
+ * let mut scheduler = NeutralModeScheduler::new(Duration::from_secs(3));
+ * scheduler.add(&left).add(&right);
+ * loop {
+ *     scheduler.update_from(&state);
+ * #   break;
+ * }
+ * ```
+ *
+ * [`update`]: #method.update
+ * [`update_from`]: #method.update_from
+ */
+pub struct NeutralModeScheduler<'a> {
+    motors: Vec<&'a dyn NeutralModeDevice>,
+    coast_delay: Duration,
+    disabled_since: Option<Instant>,
+    mode: Option<NeutralMode>,
+}
+
+impl<'a> NeutralModeScheduler<'a> {
+    /// Switches to Coast `coast_delay` after the robot is disabled.
+    pub fn new(coast_delay: Duration) -> Self {
+        NeutralModeScheduler {
+            motors: Vec::new(),
+            coast_delay,
+            disabled_since: None,
+            mode: None,
+        }
+    }
+    /// Registers a controller.  It is set to the current mode on the next update.
+    pub fn add<M: BaseMotorController>(&mut self, motor: &'a M) -> &mut Self {
+        self.motors.push(motor);
+        self.mode = None;
+        self
+    }
+    /// The neutral mode last sent to the controllers, if any.
+    pub fn mode(&self) -> Option<NeutralMode> {
+        self.mode
+    }
+    /// Brakes while `enabled`, and coasts once the robot has been disabled for the delay.
+    pub fn update(&mut self, enabled: bool) {
+        self.update_at(enabled, clock::now());
+    }
+    /// Updates from the robot's state.
+    pub fn update_from<S: RobotStateProvider + ?Sized>(&mut self, state: &S) {
+        self.update(state.is_enabled());
+    }
+
+    fn update_at(&mut self, enabled: bool, now: Instant) {
+        let mode = if enabled {
+            self.disabled_since = None;
+            NeutralMode::Brake
+        } else {
+            let since = *self.disabled_since.get_or_insert(now);
+            if now.duration_since(since) >= self.coast_delay {
+                NeutralMode::Coast
+            } else {
+                NeutralMode::Brake
+            }
+        };
+        if self.mode != Some(mode) {
+            for motor in &self.motors {
+                motor.apply_neutral_mode(mode);
+            }
+            self.mode = Some(mode);
+        }
+    }
+}
+
+impl<'a> fmt::Debug for NeutralModeScheduler<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("NeutralModeScheduler")
+            .field("motors", &self.motors.len())
+            .field("coast_delay", &self.coast_delay)
+            .field("mode", &self.mode)
+            .finish()
+    }
+}
+
+/// Gains and limits for a [`HeadingController`], with errors in degrees.
+///
+/// [`HeadingController`]: struct.HeadingController.html
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct HeadingHoldConfig {
+    /// Output per degree of heading error.
+    pub kp: f64,
+    /// Output per degree-second of accumulated heading error.
+    pub ki: f64,
+    /// Output per degree per second of yaw rate, damping the correction.
+    pub kd: f64,
+    /// Limit of the integral term's contribution to the output.  Defaults to 0.1.
+    pub max_integral_output: f64,
+    /// Limit of the correction's magnitude.  Defaults to 0.3.
+    pub max_output: f64,
+    /**
+     * Turn commands at or below this magnitude count as holding.  Defaults to
+     * 0.05.  Commands shaped with a `JoystickAxis` are 0 within its deadband,
+     * so this can be left at the default.
+     */
+    pub turn_deadband: f64,
+    /**
+     * Yaw rate, in degrees per second, below which a heading is captured once
+     * the driver stops turning, so the robot isn't pulled back against its
+     * momentum.  Defaults to 10.
+     */
+    pub settle_rate: f64,
+}
+
+impl Default for HeadingHoldConfig {
+    fn default() -> Self {
+        HeadingHoldConfig {
+            kp: 0.02,
+            ki: 0.,
+            kd: 0.002,
+            max_integral_output: 0.1,
+            max_output: 0.3,
+            turn_deadband: 0.05,
+            settle_rate: 10.,
+        }
+    }
+}
+
+/**
+ * Software heading hold for teleop: keeps the robot pointing the same way
+ * while the driver isn't turning, using a Pigeon's yaw.
+ *
+ * Each [`update`] takes the driver's turn command and returns the turn output
+ * to use instead.  While the driver turns, the command passes through and the
+ * held heading follows the robot; once they stop and the robot has settled,
+ * its heading is held.  The output is in the turn command's units, positive
+ * towards increasing yaw (counter-clockwise), and suits an arbitrary
+ * feedforward on each side:
+ *
+ * ```no_run
+ * # use ctre::DeviceId;
+ * # use ctre::drive::{HeadingController, HeadingHoldConfig};
+ * # use ctre::motor_control::{BaseMotorController, ControlMode, DemandType, TalonSRX};
+ * # use ctre::sensors::pigeon::PigeonIMU;
+ * # fn main() -> ctre::Result<()> {
+ * let left = TalonSRX::new(DeviceId::new(1));
+ * let right = TalonSRX::new(DeviceId::new(2));
+ * let pigeon = PigeonIMU::new(DeviceId::new(0));
+ * let mut heading = HeadingController::new(&pigeon, HeadingHoldConfig::default());
+ * heading.set_enabled(true);
+ * # let (throttle, turn) = (0.5, 0.);
+ * let turn = heading.update(turn)?;
+ * left.set(ControlMode::PercentOutput, throttle, DemandType::ArbitraryFeedForward, -turn);
+ * right.set(ControlMode::PercentOutput, throttle, DemandType::ArbitraryFeedForward, turn);
+ * # Ok(())
+ * # }
+ * ```
+ *
+ * The integral only accumulates while the correction isn't saturated, and is
+ * reset whenever the held heading changes or the controller is enabled.
+ *
+ * [`update`]: #method.update
+ */
+pub struct HeadingController<'a> {
+    pigeon: &'a PigeonIMU,
+    config: HeadingHoldConfig,
+    enabled: bool,
+    /// The heading being held, in degrees, or `None` while turning or settling.
+    target: Option<f64>,
+    /// Accumulated heading error, in degree-seconds.
+    integral: f64,
+    last_update: Option<Instant>,
+}
+
+impl<'a> HeadingController<'a> {
+    pub fn new(pigeon: &'a PigeonIMU, config: HeadingHoldConfig) -> Self {
+        HeadingController {
+            pigeon,
+            config,
+            enabled: false,
+            target: None,
+            integral: 0.,
+            last_update: None,
+        }
+    }
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+    /**
+     * Turns heading hold on or off.  While off, turn commands pass through.
+     *
+     * Enabling starts from a clean state: the heading is captured afresh once
+     * the robot is settled, rather than snapping back to one held before.
+     */
+    pub fn set_enabled(&mut self, enabled: bool) {
+        if enabled && !self.enabled {
+            self.reset();
+        }
+        self.enabled = enabled;
+    }
+    /// The heading being held, in degrees, if any.
+    pub fn target(&self) -> Option<f64> {
+        self.target
+    }
+    /// Forgets the held heading and the integral, e.g. after resetting the Pigeon's yaw.
+    pub fn reset(&mut self) {
+        self.target = None;
+        self.integral = 0.;
+        self.last_update = None;
+    }
+    /// Reads the Pigeon and returns the turn output for the driver's `turn` command.
+    pub fn update(&mut self, turn: f64) -> Result<f64> {
+        if !self.enabled {
+            return Ok(turn);
+        }
+        let yaw = self.pigeon.get_yaw_pitch_roll()?[0];
+        let yaw_rate = self.pigeon.get_raw_gyro()?[2];
+        Ok(self.update_at(turn, yaw, yaw_rate, clock::now()))
+    }
+
+    /**
+     * Shapes a raw joystick reading with `axis` and returns the turn output for
+     * it.  Readings within the axis's deadband shape to 0, so count as holding.
+     */
+    pub fn update_axis(&mut self, axis: &JoystickAxis, raw_turn: f64) -> Result<f64> {
+        self.update(axis.apply(raw_turn))
+    }
+
+    fn update_at(&mut self, turn: f64, yaw: f64, yaw_rate: f64, now: Instant) -> f64 {
+        let dt = match self.last_update.replace(now) {
+            Some(last) => now.duration_since(last).as_secs_f64(),
+            None => 0.,
+        };
+        let config = &self.config;
+        if !self.enabled || turn.abs() > config.turn_deadband {
+            self.target = None;
+            self.integral = 0.;
+            return turn;
+        }
+        let target = match self.target {
+            Some(target) => target,
+            None if yaw_rate.abs() <= config.settle_rate => *self.target.get_or_insert(yaw),
+            // Still coasting from the last turn.
+            None => return turn,
+        };
+        let error = target - yaw;
+        let unclamped = config.kp * error + config.ki * self.integral - config.kd * yaw_rate;
+        if unclamped.abs() < config.max_output && config.ki != 0. {
+            let max_integral = config.max_integral_output / config.ki.abs();
+            self.integral = (self.integral + error * dt).clamp(-max_integral, max_integral);
+        }
+        let correction = config.kp * error + config.ki * self.integral - config.kd * yaw_rate;
+        correction.clamp(-config.max_output, config.max_output)
+    }
+}
+
+impl<'a> fmt::Debug for HeadingController<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("HeadingController")
+            .field("config", &self.config)
+            .field("enabled", &self.enabled)
+            .field("target", &self.target)
+            .field("integral", &self.integral)
+            .finish()
+    }
+}
+
+#[test]
+fn drive_straight_routes_heading_to_aux_pid() {
+    use motor_control::TalonSRX;
+    use DeviceId;
+    let master = TalonSRX::new(DeviceId::new(4));
+    let follower = TalonSRX::new(DeviceId::new(5));
+    let config = DriveStraightConfig::default();
+    let mut drive = drive_straight_distance(&master, &follower, 2000., 90., &config).unwrap();
+    assert_eq!(master.get_closed_loop_target(0).unwrap(), 2000);
+    assert_eq!(master.get_closed_loop_target(1).unwrap(), 900);
+    assert_eq!(drive.progress().unwrap(), 0.);
+    assert!(!drive.is_finished().unwrap());
+}
+
+#[test]
+fn neutral_mode_coasts_after_disable() {
+    use motor_control::TalonSRX;
+    use DeviceId;
+    let talon = TalonSRX::new(DeviceId::new(24));
+    let start = Instant::now();
+    let at = |ms| start + Duration::from_millis(ms);
+    let mut scheduler = NeutralModeScheduler::new(Duration::from_secs(3));
+    scheduler.add(&talon);
+    scheduler.update_at(true, at(0));
+    assert_eq!(scheduler.mode(), Some(NeutralMode::Brake));
+    scheduler.update_at(false, at(1000));
+    scheduler.update_at(false, at(3999));
+    assert_eq!(scheduler.mode(), Some(NeutralMode::Brake));
+    scheduler.update_at(false, at(4000));
+    assert_eq!(scheduler.mode(), Some(NeutralMode::Coast));
+    scheduler.update_at(true, at(5000));
+    assert_eq!(scheduler.mode(), Some(NeutralMode::Brake));
+}
+
+#[test]
+fn heading_hold_captures_after_turning() {
+    use DeviceId;
+    let pigeon = PigeonIMU::new(DeviceId::new(20));
+    let config = HeadingHoldConfig {
+        ki: 0.01,
+        ..HeadingHoldConfig::default()
+    };
+    let mut heading = HeadingController::new(&pigeon, config);
+    let start = Instant::now();
+    let at = |ms| start + Duration::from_millis(ms);
+    // Disabled, commands pass through.
+    assert_eq!(heading.update_at(0., 10., 0., at(0)), 0.);
+    heading.set_enabled(true);
+    assert_eq!(heading.update_at(0.5, 10., 90., at(20)), 0.5);
+    // Released while still spinning: coast, then hold where it settles.
+    assert_eq!(heading.update_at(0., 40., 50., at(40)), 0.);
+    assert_eq!(heading.target(), None);
+    assert_eq!(heading.update_at(0., 45., 5., at(60)), -0.01);
+    assert_eq!(heading.target(), Some(45.));
+    // Knocked clockwise, it turns back counter-clockwise.
+    let correction = heading.update_at(0., 40., 0., at(80));
+    assert!(correction > 0.1 && correction <= 0.3);
+    // Far off, the correction saturates and the integral stops growing.
+    heading.update_at(0., -100., 0., at(1080));
+    assert_eq!(heading.update_at(0., -100., 0., at(2080)), 0.3);
+    assert!(heading.integral <= 0.1 / 0.01);
+    // The Pigeon isn't read while disabled.
+    heading.set_enabled(false);
+    assert_eq!(heading.update(0.2).unwrap(), 0.2);
+}
@@ -0,0 +1,251 @@
+//! Accounting for the battery energy each mechanism uses.
+
+use std::fmt;
+use std::ops::{Add, AddAssign};
+use std::time::Instant;
+
+use ctre_sys::clock;
+
+use motor_control::BaseMotorController;
+
+/// Energy drawn from the battery.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Energy {
+    pub joules: f64,
+    /// The charge drawn, in amp-hours.
+    pub amp_hours: f64,
+}
+
+impl Energy {
+    pub fn watt_hours(&self) -> f64 {
+        self.joules / 3600.
+    }
+}
+
+impl Add for Energy {
+    type Output = Energy;
+    fn add(self, other: Energy) -> Energy {
+        Energy {
+            joules: self.joules + other.joules,
+            amp_hours: self.amp_hours + other.amp_hours,
+        }
+    }
+}
+
+impl AddAssign for Energy {
+    fn add_assign(&mut self, other: Energy) {
+        *self = *self + other;
+    }
+}
+
+/// e.g. `1520 J (0.42 Wh), 0.035 Ah`
+impl fmt::Display for Energy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:.0} J ({:.2} Wh), {:.3} Ah",
+            self.joules,
+            self.watt_hours(),
+            self.amp_hours
+        )
+    }
+}
+
+/// Reading the power drawn from the battery, for any motor controller.
+trait PowerDevice {
+    /// The supply current and power, in amps and watts, or `None` if they couldn't be read.
+    fn supply_power(&self) -> Option<(f64, f64)>;
+}
+
+impl<T: BaseMotorController> PowerDevice for T {
+    fn supply_power(&self) -> Option<(f64, f64)> {
+        let volts = self.get_bus_voltage().ok()?;
+        let output_amps = self.get_output_current().ok()?;
+        let duty = self.get_motor_output_percent().ok()?.abs();
+        // The controller switches the battery across the motor `duty` of the
+        // time, so the battery supplies the motor current for that fraction.
+        let supply_amps = output_amps * duty;
+        Some((supply_amps, supply_amps * volts))
+    }
+}
+
+struct Mechanism<'a> {
+    name: &'static str,
+    motors: Vec<&'a dyn PowerDevice>,
+    energy: Energy,
+}
+
+/**
+ * Integrates the battery power used by each mechanism's motor controllers,
+ * to guide battery management and gearing decisions.
+ *
+ * Call [`update`] periodically, e.g. once per robot loop.  Each update reads
+ * the bus voltage, output current and output of every controller, and counts
+ * that power as drawn since the previous update.
+ *
+ * The Victor SPX doesn't measure current, so Victors read as drawing none.
+ *
+ * ```no_run
+ * # use ctre::DeviceId;
+ * # use ctre::energy::EnergyMeter;
+ * # use ctre::motor_control::{BaseMotorController, TalonSRX};
+ * let left = TalonSRX::new(DeviceId::new(1));
+ * let right = TalonSRX::new(DeviceId::new(2));
+ * let intake = TalonSRX::new(DeviceId::new(3));
+ * let mut meter = EnergyMeter::new();
+ * meter.add("drive", &left).add("drive", &right).add("intake", &intake);
+ * loop {
+ *     meter.update();
+ * #   break;
+ * }
+ * print!("{}", meter);
+ * ```
+ *
+ * [`update`]: #method.update
+ */
+pub struct EnergyMeter<'a> {
+    mechanisms: Vec<Mechanism<'a>>,
+    last_update: Option<Instant>,
+}
+
+impl<'a> EnergyMeter<'a> {
+    pub fn new() -> Self {
+        EnergyMeter {
+            mechanisms: Vec::new(),
+            last_update: None,
+        }
+    }
+    /// Counts `motor`'s power towards `mechanism`.
+    pub fn add<M: BaseMotorController>(
+        &mut self,
+        mechanism: &'static str,
+        motor: &'a M,
+    ) -> &mut Self {
+        self.add_device(mechanism, motor)
+    }
+    fn add_device(&mut self, mechanism: &'static str, device: &'a dyn PowerDevice) -> &mut Self {
+        match self.mechanisms.iter_mut().find(|m| m.name == mechanism) {
+            Some(existing) => existing.motors.push(device),
+            None => self.mechanisms.push(Mechanism {
+                name: mechanism,
+                motors: vec![device],
+                energy: Energy::default(),
+            }),
+        }
+        self
+    }
+    /// Reads every controller, adding the energy used since the last update.
+    pub fn update(&mut self) {
+        self.update_at(clock::now());
+    }
+    /// The energy `mechanism` has used, or `None` if it has no controllers.
+    pub fn energy(&self, mechanism: &str) -> Option<Energy> {
+        self.mechanisms
+            .iter()
+            .find(|m| m.name == mechanism)
+            .map(|m| m.energy)
+    }
+    /// The energy each mechanism has used, in the order they were added.
+    pub fn mechanisms(&self) -> Vec<(&'static str, Energy)> {
+        self.mechanisms.iter().map(|m| (m.name, m.energy)).collect()
+    }
+    /// The energy used by every mechanism together.
+    pub fn total(&self) -> Energy {
+        self.mechanisms
+            .iter()
+            .fold(Energy::default(), |total, m| total + m.energy)
+    }
+    /// Starts counting from zero again, e.g. when the next match starts.
+    pub fn reset(&mut self) {
+        for mechanism in &mut self.mechanisms {
+            mechanism.energy = Energy::default();
+        }
+        self.last_update = None;
+    }
+
+    fn update_at(&mut self, now: Instant) {
+        let hours = match self.last_update.replace(now) {
+            Some(last) => now.duration_since(last).as_secs_f64() / 3600.,
+            // Nothing to count the first reading over.
+            None => return,
+        };
+        for mechanism in &mut self.mechanisms {
+            for motor in &mechanism.motors {
+                if let Some((amps, watts)) = motor.supply_power() {
+                    mechanism.energy += Energy {
+                        joules: watts * hours * 3600.,
+                        amp_hours: amps * hours,
+                    };
+                }
+            }
+        }
+    }
+}
+
+impl<'a> Default for EnergyMeter<'a> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> fmt::Debug for EnergyMeter<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut map = f.debug_map();
+        for mechanism in &self.mechanisms {
+            map.entry(&mechanism.name, &mechanism.energy);
+        }
+        map.finish()
+    }
+}
+
+/// One line per mechanism, then the total.
+impl<'a> fmt::Display for EnergyMeter<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for mechanism in &self.mechanisms {
+            writeln!(f, "{}: {}", mechanism.name, mechanism.energy)?;
+        }
+        writeln!(f, "total: {}", self.total())
+    }
+}
+
+#[test]
+fn meter_integrates_power() {
+    use std::time::Duration;
+    /// Draws 10 A at 12 V.
+    struct Load;
+    impl PowerDevice for Load {
+        fn supply_power(&self) -> Option<(f64, f64)> {
+            Some((10., 120.))
+        }
+    }
+    let (a, b, c) = (Load, Load, Load);
+    let mut meter = EnergyMeter::new();
+    meter
+        .add_device("drive", &a)
+        .add_device("drive", &b)
+        .add_device("intake", &c);
+    let start = Instant::now();
+    meter.update_at(start);
+    meter.update_at(start + Duration::from_secs(30));
+    meter.update_at(start + Duration::from_secs(36));
+    let drive = meter.energy("drive").unwrap();
+    assert!((drive.joules - 2. * 120. * 36.).abs() < 1e-6);
+    assert!((drive.amp_hours - 2. * 10. * 0.01).abs() < 1e-9);
+    assert_eq!(meter.total(), drive + meter.energy("intake").unwrap());
+    assert_eq!(meter.energy("shooter"), None);
+    meter.reset();
+    assert_eq!(meter.total(), Energy::default());
+}
+
+#[test]
+fn idle_talon_uses_no_energy() {
+    use motor_control::TalonSRX;
+    use DeviceId;
+    let talon = TalonSRX::new(DeviceId::new(29));
+    let mut meter = EnergyMeter::new();
+    meter.add("arm", &talon);
+    meter.update();
+    meter.update();
+    assert_eq!(meter.mechanisms(), vec![("arm", Energy::default())]);
+}
@@ -0,0 +1,300 @@
+//! Errors returned by device calls, with the device and call they came from.
+
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fmt;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Mutex;
+use std::time::Instant;
+
+use ErrorCode;
+
+/// The kinds of CTRE device an error can come from.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum DeviceType {
+    TalonSRX,
+    VictorSPX,
+    PigeonIMU,
+    CANifier,
+}
+
+impl fmt::Display for DeviceType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+/// An `ErrorCode` along with the device and call it came from.
+///
+/// The categorization helpers on `ErrorCode` (`severity`, `is_timeout`, ...)
+/// are available through the `code` field.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct DeviceError {
+    pub code: ErrorCode,
+    /// The kind of device the call was made on, if known.
+    pub device_type: Option<DeviceType>,
+    /// CAN device number of the device the call was made on, if known.
+    /// For a Pigeon connected through a Talon, this is the Talon's device number.
+    pub device_id: Option<i32>,
+    /// Name of the CCI function that failed, if known.
+    pub operation: Option<&'static str>,
+    /// Label of the device the call was made on, if it has one.
+    pub name: Option<&'static str>,
+}
+
+impl DeviceError {
+    pub fn new(
+        code: ErrorCode,
+        device_type: DeviceType,
+        device_id: i32,
+        operation: &'static str,
+    ) -> Self {
+        DeviceError {
+            code,
+            device_type: Some(device_type),
+            device_id: Some(device_id),
+            operation: Some(operation),
+            name: None,
+        }
+    }
+    pub fn with_name(self, name: Option<&'static str>) -> Self {
+        DeviceError { name, ..self }
+    }
+}
+
+/// For codes with no context, e.g. from `ErrorCode::into_res`.
+impl From<ErrorCode> for DeviceError {
+    fn from(code: ErrorCode) -> Self {
+        DeviceError {
+            code,
+            device_type: None,
+            device_id: None,
+            operation: None,
+            name: None,
+        }
+    }
+}
+
+impl From<DeviceError> for ErrorCode {
+    fn from(err: DeviceError) -> Self {
+        err.code
+    }
+}
+
+/// e.g. `TalonSRX 3: c_MotController_ConfigKP failed: RxTimeout`, or
+/// `intake_roller (TalonSRX 3): c_MotController_ConfigKP failed: RxTimeout` for a named device.
+impl fmt::Display for DeviceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (self.name, self.device_type, self.device_id) {
+            (Some(name), Some(ty), Some(id)) => write!(f, "{} ({} {}): ", name, ty, id)?,
+            (_, Some(ty), Some(id)) => write!(f, "{} {}: ", ty, id)?,
+            (_, Some(ty), None) => write!(f, "{}: ", ty)?,
+            (_, None, Some(id)) => write!(f, "device {}: ", id)?,
+            (_, None, None) => {}
+        }
+        match self.operation {
+            Some(op) => write!(f, "{} failed: {}", op, self.code),
+            None => write!(f, "{}", self.code),
+        }
+    }
+}
+
+impl Error for DeviceError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.code)
+    }
+}
+
+/// A failed call, as kept by a device's recent error history.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ErrorRecord {
+    /// When the error was returned.
+    pub timestamp: Instant,
+    /// Name of the CCI function that failed.
+    pub operation: &'static str,
+    pub code: ErrorCode,
+}
+
+/// The last few errors returned by calls on one device, oldest first.
+///
+/// Unlike the device's last error, this is kept per call and is safe to
+/// read while other threads are using the device.
+#[derive(Debug)]
+pub(crate) struct ErrorLog {
+    entries: Mutex<VecDeque<ErrorRecord>>,
+}
+
+impl Default for ErrorLog {
+    /// Allocates the full capacity up front, so recording an error doesn't allocate.
+    fn default() -> Self {
+        ErrorLog {
+            entries: Mutex::new(VecDeque::with_capacity(Self::CAPACITY)),
+        }
+    }
+}
+
+impl ErrorLog {
+    /// How many errors are kept per device.
+    pub const CAPACITY: usize = 16;
+
+    pub fn push(&self, record: ErrorRecord) {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        if entries.len() == Self::CAPACITY {
+            entries.pop_front();
+        }
+        entries.push_back(record);
+    }
+
+    pub fn to_vec(&self) -> Vec<ErrorRecord> {
+        let mut records = Vec::with_capacity(Self::CAPACITY);
+        self.copy_into(&mut records);
+        records
+    }
+
+    /// Replaces the contents of `records` with the log's.
+    pub fn copy_into(&self, records: &mut Vec<ErrorRecord>) {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        records.clear();
+        records.extend(entries.iter().cloned());
+    }
+}
+
+/**
+ * How many calls to a device have failed, by the kind of error, as a measure
+ * of bus health.  Counts start when the device object is created.
+ */
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ErrorStats {
+    /// Frames or signals not received in time (`ErrorCode::is_timeout`).
+    pub timeouts: u32,
+    /// Frames that couldn't be transmitted because the CAN transmit buffer was full.
+    pub tx_full: u32,
+    /// Reads of frames that haven't been received recently (`CAN_MSG_STALE`).
+    pub stale_frames: u32,
+    /// Every other error.
+    pub other: u32,
+}
+
+impl ErrorStats {
+    pub fn total(&self) -> u32 {
+        self.timeouts + self.tx_full + self.stale_frames + self.other
+    }
+    /// The errors counted since `earlier`, an earlier snapshot of the same device's.
+    pub fn since(&self, earlier: &ErrorStats) -> ErrorStats {
+        ErrorStats {
+            timeouts: self.timeouts.saturating_sub(earlier.timeouts),
+            tx_full: self.tx_full.saturating_sub(earlier.tx_full),
+            stale_frames: self.stale_frames.saturating_sub(earlier.stale_frames),
+            other: self.other.saturating_sub(earlier.other),
+        }
+    }
+}
+
+/// e.g. `3 timeouts, 0 TX full, 1 stale frames, 0 other`
+impl fmt::Display for ErrorStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} timeouts, {} TX full, {} stale frames, {} other",
+            self.timeouts, self.tx_full, self.stale_frames, self.other
+        )
+    }
+}
+
+/// The counters behind a device's `ErrorStats`.
+#[derive(Debug, Default)]
+pub(crate) struct ErrorCounters {
+    timeouts: AtomicU32,
+    tx_full: AtomicU32,
+    stale_frames: AtomicU32,
+    other: AtomicU32,
+}
+
+impl ErrorCounters {
+    pub fn count(&self, code: ErrorCode) {
+        let counter = match code {
+            _ if code.is_timeout() => &self.timeouts,
+            ErrorCode::TxFailed | ErrorCode::BufferFull | ErrorCode::CAN_OVERFLOW => &self.tx_full,
+            ErrorCode::CAN_MSG_STALE => &self.stale_frames,
+            _ => &self.other,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+    pub fn stats(&self) -> ErrorStats {
+        ErrorStats {
+            timeouts: self.timeouts.load(Ordering::Relaxed),
+            tx_full: self.tx_full.load(Ordering::Relaxed),
+            stale_frames: self.stale_frames.load(Ordering::Relaxed),
+            other: self.other.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[test]
+fn device_error_display() {
+    let err = DeviceError::new(
+        ErrorCode::RxTimeout,
+        DeviceType::TalonSRX,
+        3,
+        "c_MotController_Config_kP",
+    );
+    assert_eq!(
+        err.to_string(),
+        "TalonSRX 3: c_MotController_Config_kP failed: RxTimeout"
+    );
+    assert_eq!(ErrorCode::from(err), ErrorCode::RxTimeout);
+    assert_eq!(
+        DeviceError::from(ErrorCode::TxFailed).to_string(),
+        "TxFailed"
+    );
+}
+
+#[test]
+fn error_log_keeps_most_recent() {
+    let log = ErrorLog::default();
+    for _ in 0..ErrorLog::CAPACITY {
+        log.push(ErrorRecord {
+            timestamp: Instant::now(),
+            operation: "first",
+            code: ErrorCode::TxFailed,
+        });
+    }
+    log.push(ErrorRecord {
+        timestamp: Instant::now(),
+        operation: "last",
+        code: ErrorCode::RxTimeout,
+    });
+    let recent = log.to_vec();
+    assert_eq!(recent.len(), ErrorLog::CAPACITY);
+    assert_eq!(recent.last().map(|r| r.operation), Some("last"));
+}
+
+#[test]
+fn error_counters_classify_codes() {
+    let counters = ErrorCounters::default();
+    for &code in &[
+        ErrorCode::RxTimeout,
+        ErrorCode::SigNotUpdated,
+        ErrorCode::TxFailed,
+        ErrorCode::CAN_MSG_STALE,
+        ErrorCode::InvalidParamValue,
+    ] {
+        counters.count(code);
+    }
+    let stats = counters.stats();
+    assert_eq!(
+        stats,
+        ErrorStats {
+            timeouts: 2,
+            tx_full: 1,
+            stale_frames: 1,
+            other: 1,
+        }
+    );
+    assert_eq!(stats.total(), 5);
+    counters.count(ErrorCode::RxTimeout);
+    assert_eq!(counters.stats().since(&stats).timeouts, 1);
+    assert_eq!(counters.stats().since(&stats).total(), 1);
+}
@@ -0,0 +1,200 @@
+//! Notifications of what happens to devices, from anywhere in the crate.
+//!
+//! Subsystems publish a [`DeviceEvent`] to the process-wide [`EventBus`] as
+//! things happen: devices being created, resetting, latching faults, being
+//! configured or going silent, and features being turned off without them.
+//! Code that wants to react to these subscribes once, with a callback or a
+//! channel, rather than polling each monitor.
+//!
+//! ```no_run
+//! # use ctre::events::{events, DeviceEvent};
+//! let receiver = events().channel();
+//! // e.g. on a logging thread:
+//! for event in receiver {
+//!     if let DeviceEvent::Reset(device) = event {
+//!         eprintln!("{} reset", device);
+//!     }
+//! }
+//! ```
+//!
+//! [`DeviceEvent`]: enum.DeviceEvent.html
+//! [`EventBus`]: struct.EventBus.html
+
+use std::fmt;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock};
+
+use connection::SilentDevice;
+use faults::StickyFaultRecord;
+use registry::DeviceInfo;
+
+/// Something that happened to a device.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DeviceEvent {
+    /// A device object was created.  It hasn't been named yet.
+    Created(DeviceInfo),
+    /// `has_reset_occurred` found that the device had reset.
+    Reset(DeviceInfo),
+    /// A `StickyFaultPolicy` read sticky faults latched on the device.
+    FaultLatched(StickyFaultRecord),
+    /// A `ConfigBuilder` was applied to the device, with this many settings failing.
+    ConfigApplied { device: DeviceInfo, failures: usize },
+    /// A `ConnectionMonitor` found the device had gone silent.  Only sent when
+    /// the device first goes silent, not on every poll while it stays so.
+    Disconnected(SilentDevice),
+    /// A `DegradationPolicy` turned a feature off, as a device it needs is
+    /// missing, or back on.  `update` returns which devices were missing.
+    FeatureChanged {
+        feature: &'static str,
+        enabled: bool,
+    },
+}
+
+type Callback = Arc<dyn Fn(&DeviceEvent) + Send + Sync>;
+
+enum Subscriber {
+    Callback(u64, Callback),
+    Channel(Sender<DeviceEvent>),
+}
+
+struct Subscribers {
+    next_id: u64,
+    subscribers: Vec<Subscriber>,
+}
+
+/**
+ * Delivers each published [`DeviceEvent`] to every subscriber.  See [`events`].
+ *
+ * Events are delivered on the thread that published them, e.g. the robot loop
+ * calling `ConnectionMonitor::silent_devices`, so callbacks should be quick.
+ * Publishing with no subscribers costs a lock and nothing more.
+ *
+ * [`DeviceEvent`]: enum.DeviceEvent.html
+ * [`events`]: fn.events.html
+ */
+pub struct EventBus {
+    subscribers: Mutex<Subscribers>,
+}
+
+impl EventBus {
+    fn lock(&self) -> MutexGuard<'_, Subscribers> {
+        // A panicking callback runs outside the lock, so the list is never left half-updated.
+        self.subscribers
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+    }
+    /// Calls `callback` with every event published until the returned handle is dropped.
+    pub fn subscribe<F>(&self, callback: F) -> Subscription
+    where
+        F: Fn(&DeviceEvent) + Send + Sync + 'static,
+    {
+        let mut state = self.lock();
+        let id = state.next_id;
+        state.next_id += 1;
+        state
+            .subscribers
+            .push(Subscriber::Callback(id, Arc::new(callback)));
+        Subscription { id }
+    }
+    /// Sends every event published until the receiver is dropped.
+    pub fn channel(&self) -> Receiver<DeviceEvent> {
+        let (sender, receiver) = mpsc::channel();
+        self.lock().subscribers.push(Subscriber::Channel(sender));
+        receiver
+    }
+    /// Delivers `event` to every subscriber.
+    pub(crate) fn publish(&self, event: DeviceEvent) {
+        let callbacks: Vec<Callback> = {
+            let mut state = self.lock();
+            if state.subscribers.is_empty() {
+                return;
+            }
+            // Channels whose receiver was dropped fail to send, and are removed.
+            state.subscribers.retain(|subscriber| match *subscriber {
+                Subscriber::Channel(ref sender) => sender.send(event).is_ok(),
+                Subscriber::Callback(..) => true,
+            });
+            state
+                .subscribers
+                .iter()
+                .filter_map(|subscriber| match *subscriber {
+                    Subscriber::Callback(_, ref callback) => Some(callback.clone()),
+                    Subscriber::Channel(_) => None,
+                })
+                .collect()
+        };
+        // Called without the lock held, so callbacks may subscribe or unsubscribe.
+        for callback in callbacks {
+            callback(&event);
+        }
+    }
+}
+
+impl fmt::Debug for EventBus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("EventBus")
+            .field("subscribers", &self.lock().subscribers.len())
+            .finish()
+    }
+}
+
+/// The event bus every device and monitor publishes to.
+pub fn events() -> &'static EventBus {
+    static EVENTS: OnceLock<EventBus> = OnceLock::new();
+    EVENTS.get_or_init(|| EventBus {
+        subscribers: Mutex::new(Subscribers {
+            next_id: 0,
+            subscribers: Vec::new(),
+        }),
+    })
+}
+
+/**
+ * A callback subscribed with [`EventBus::subscribe`].  Dropping it unsubscribes.
+ *
+ * [`EventBus::subscribe`]: struct.EventBus.html#method.subscribe
+ */
+#[derive(Debug)]
+pub struct Subscription {
+    id: u64,
+}
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        events()
+            .lock()
+            .subscribers
+            .retain(|subscriber| match *subscriber {
+                Subscriber::Callback(id, _) => id != self.id,
+                Subscriber::Channel(_) => true,
+            });
+    }
+}
+
+#[test]
+fn bus_delivers_to_subscribers() {
+    use error::DeviceType;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    let device = DeviceInfo {
+        device_type: DeviceType::TalonSRX,
+        id: 37,
+        name: Some("events_test"),
+    };
+    let resets = Arc::new(AtomicUsize::new(0));
+    let counted = resets.clone();
+    let subscription = events().subscribe(move |event| {
+        if *event == DeviceEvent::Reset(device) {
+            counted.fetch_add(1, Ordering::SeqCst);
+        }
+    });
+    let receiver = events().channel();
+    events().publish(DeviceEvent::Reset(device));
+    drop(subscription);
+    events().publish(DeviceEvent::Reset(device));
+    assert_eq!(resets.load(Ordering::SeqCst), 1);
+    // Other tests publish too, so look for this test's events among them.
+    let received = receiver
+        .try_iter()
+        .filter(|event| *event == DeviceEvent::Reset(device));
+    assert_eq!(received.count(), 2);
+}
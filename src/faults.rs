@@ -0,0 +1,442 @@
+//! Capturing and clearing sticky faults.
+//!
+//! Sticky faults latch until cleared, so on boot they describe what happened
+//! during the previous power cycle.  Recording and then clearing them at startup
+//! keeps that history without it being mistaken for a fault in the current match.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use canifier::CANifier;
+use connection::Connection;
+use error::DeviceType;
+use events::{events, DeviceEvent};
+use motor_control::BaseMotorController;
+use sensors::pigeon::PigeonIMU;
+use Result;
+
+/// Names of the motor controller sticky fault bits, from bit 0 up.
+const MOT_STICKY_FAULTS: [&str; 10] = [
+    "under_voltage",
+    "forward_limit_switch",
+    "reverse_limit_switch",
+    "forward_soft_limit",
+    "reverse_soft_limit",
+    "reset_during_en",
+    "sensor_overflow",
+    "sensor_out_of_phase",
+    "hardware_esd_reset",
+    "remote_loss_of_signal",
+];
+
+/// The sticky faults a device had latched when they were read.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct StickyFaultRecord {
+    pub timestamp: Instant,
+    pub device_type: DeviceType,
+    pub device_id: i32,
+    /// Label of the device, if it has one.
+    pub name: Option<&'static str>,
+    /// The raw sticky fault bits, as in the device's `StickyFaults`.
+    pub bits: i32,
+}
+
+impl StickyFaultRecord {
+    /// True iff any sticky fault was latched.
+    pub fn has_any_fault(&self) -> bool {
+        self.bits != 0
+    }
+    /// Names of the latched faults, for devices whose fault bits are known.
+    pub fn fault_names(&self) -> Option<Vec<&'static str>> {
+        match self.device_type {
+            DeviceType::TalonSRX | DeviceType::VictorSPX => Some(
+                MOT_STICKY_FAULTS
+                    .iter()
+                    .enumerate()
+                    .filter(|&(bit, _)| self.bits & (1 << bit) != 0)
+                    .map(|(_, &name)| name)
+                    .collect(),
+            ),
+            DeviceType::PigeonIMU | DeviceType::CANifier => None,
+        }
+    }
+}
+
+/// e.g. `TalonSRX 3 sticky faults: under_voltage, reset_during_en`, with the
+/// device's name first if it has one: `intake_roller (TalonSRX 3) sticky faults: ...`
+impl fmt::Display for StickyFaultRecord {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.name {
+            Some(name) => write!(f, "{} ({} {})", name, self.device_type, self.device_id)?,
+            None => write!(f, "{} {}", self.device_type, self.device_id)?,
+        }
+        f.write_str(" sticky faults: ")?;
+        match self.fault_names() {
+            _ if !self.has_any_fault() => f.write_str("none"),
+            Some(names) => f.write_str(&names.join(", ")),
+            None => write!(f, "{:#x}", self.bits),
+        }
+    }
+}
+
+/// Devices with sticky faults.
+pub trait StickyFaultDevice: Connection {
+    /// Reads the device's sticky faults without clearing them.
+    fn read_sticky_faults(&self) -> Result<StickyFaultRecord>;
+    /// Clears all of the device's sticky faults.
+    fn clear_all_sticky_faults(&self, timeout_ms: i32) -> Result<()>;
+
+    /**
+     * Reads the device's sticky faults, then clears them.  Returns what was
+     * read; any faults are also published as a `FaultLatched` [event].
+     * See [`StickyFaultPolicy`] for more control over what is recorded and where.
+     *
+     * [event]: ../events/enum.DeviceEvent.html
+     * [`StickyFaultPolicy`]: struct.StickyFaultPolicy.html
+     */
+    fn record_and_clear_sticky_faults(&self, timeout_ms: i32) -> Result<StickyFaultRecord>
+    where
+        Self: Sized,
+    {
+        let policy = StickyFaultPolicy {
+            timeout_ms,
+            ..Default::default()
+        };
+        policy.apply_one(self, &mut |_| {})
+    }
+}
+
+fn record(device: &dyn Connection, bits: i32) -> StickyFaultRecord {
+    let (device_type, device_id) = device.device();
+    StickyFaultRecord {
+        timestamp: Instant::now(),
+        device_type,
+        device_id,
+        name: device.device_name(),
+        bits,
+    }
+}
+
+impl<T: BaseMotorController> StickyFaultDevice for T {
+    fn read_sticky_faults(&self) -> Result<StickyFaultRecord> {
+        Ok(record(self, self.get_sticky_faults()?.bits()))
+    }
+    fn clear_all_sticky_faults(&self, timeout_ms: i32) -> Result<()> {
+        self.clear_sticky_faults(timeout_ms)
+    }
+}
+
+impl StickyFaultDevice for CANifier {
+    fn read_sticky_faults(&self) -> Result<StickyFaultRecord> {
+        Ok(record(self, self.get_sticky_faults()?.bits()))
+    }
+    fn clear_all_sticky_faults(&self, timeout_ms: i32) -> Result<()> {
+        self.clear_sticky_faults(timeout_ms)
+    }
+}
+
+impl StickyFaultDevice for PigeonIMU {
+    fn read_sticky_faults(&self) -> Result<StickyFaultRecord> {
+        Ok(record(self, self.get_sticky_faults()?.bits()))
+    }
+    fn clear_all_sticky_faults(&self, timeout_ms: i32) -> Result<()> {
+        self.clear_sticky_faults(timeout_ms)
+    }
+}
+
+/**
+ * What to do with sticky faults left over from the previous power cycle.
+ *
+ * Typically applied once, when the robot program starts:
+ *
+ * ```no_run
+ * # use ctre::DeviceId;
+ * # use ctre::faults::StickyFaultPolicy;
+ * # use ctre::motor_control::{BaseMotorController, TalonSRX};
+ * # use ctre::sensors::pigeon::PigeonIMU;
+ * # fn main() -> ctre::Result<()> {
+ * let left = TalonSRX::new(DeviceId::new(1));
+ * let right = TalonSRX::new(DeviceId::new(2));
+ * let pigeon = PigeonIMU::new(DeviceId::new(0));
+ * let mut log = Vec::new();
+ * StickyFaultPolicy::default().apply(&[&left, &right, &pigeon], |record| log.push(*record))?;
+ * # Ok(())
+ * # }
+ * ```
+ */
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct StickyFaultPolicy {
+    /// Clear the faults once recorded.  Defaults to `true`.
+    pub clear: bool,
+    /// Also pass devices without sticky faults to the sink.  Defaults to `false`.
+    pub record_empty: bool,
+    /// Timeout for clearing, in ms.  Defaults to 10.
+    pub timeout_ms: i32,
+}
+
+impl Default for StickyFaultPolicy {
+    fn default() -> Self {
+        StickyFaultPolicy {
+            clear: true,
+            record_empty: false,
+            timeout_ms: 10,
+        }
+    }
+}
+
+impl StickyFaultPolicy {
+    /**
+     * Records then (per the policy) clears the sticky faults of each device.
+     *
+     * A device's faults are only cleared once they have been read and passed
+     * to `sink`.  Every device is attempted; the first error is returned.
+     */
+    pub fn apply<F>(&self, devices: &[&dyn StickyFaultDevice], mut sink: F) -> Result<()>
+    where
+        F: FnMut(&StickyFaultRecord),
+    {
+        let mut result = Ok(());
+        for &device in devices {
+            if let Err(err) = self.apply_one(device, &mut sink) {
+                result = result.and(Err(err));
+            }
+        }
+        result
+    }
+
+    fn apply_one(
+        &self,
+        device: &dyn StickyFaultDevice,
+        sink: &mut dyn FnMut(&StickyFaultRecord),
+    ) -> Result<StickyFaultRecord> {
+        let record = device.read_sticky_faults()?;
+        if record.has_any_fault() {
+            events().publish(DeviceEvent::FaultLatched(record));
+        }
+        if record.has_any_fault() || self.record_empty {
+            sink(&record);
+        }
+        if self.clear && record.has_any_fault() {
+            device.clear_all_sticky_faults(self.timeout_ms)?;
+        }
+        Ok(record)
+    }
+}
+
+/// Counts of the faults worth tracking across power cycles, for one device.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct FaultCounts {
+    pub reset_during_en: u32,
+    pub under_voltage: u32,
+    pub hardware_esd_reset: u32,
+}
+
+impl FaultCounts {
+    pub fn total(&self) -> u32 {
+        self.reset_during_en + self.under_voltage + self.hardware_esd_reset
+    }
+}
+
+/**
+ * Counts of notable faults per device, kept in a file on the roboRIO so they
+ * accumulate across reboots.
+ *
+ * Faults like a reset during enable are usually intermittent wiring problems,
+ * which only show up as a pattern over several matches.  Feed it the sticky
+ * faults read at startup, e.g. as the sink of a [`StickyFaultPolicy`]:
+ *
+ * ```no_run
+ * # use ctre::DeviceId;
+ * # use ctre::faults::{FaultHistory, StickyFaultPolicy};
+ * # use ctre::motor_control::{BaseMotorController, TalonSRX};
+ * # fn main() -> Result<(), Box<dyn std::error::Error>> {
+ * # let talon = TalonSRX::new(DeviceId::new(1));
+ * let mut history = FaultHistory::load(FaultHistory::DEFAULT_PATH)?;
+ * StickyFaultPolicy::default().apply(&[&talon], |record| history.record(record))?;
+ * history.save()?;
+ * print!("{}", history);
+ * # Ok(())
+ * # }
+ * ```
+ *
+ * Only motor controllers report these faults; records for other devices are ignored.
+ *
+ * [`StickyFaultPolicy`]: struct.StickyFaultPolicy.html
+ */
+#[derive(Debug, Clone)]
+pub struct FaultHistory {
+    path: PathBuf,
+    counts: BTreeMap<(DeviceType, i32), FaultCounts>,
+}
+
+impl FaultHistory {
+    /// Where robot programs can write on the roboRIO.
+    pub const DEFAULT_PATH: &'static str = "/home/lvuser/ctre_fault_history.txt";
+
+    /// Loads the history from `path`, starting empty if the file doesn't exist.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<FaultHistory> {
+        let path = path.as_ref().to_path_buf();
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e),
+        };
+        let mut counts = BTreeMap::new();
+        for line in contents.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, value) = parse_line(line).ok_or_else(|| {
+                let msg = format!("bad fault history line: {}", line);
+                io::Error::new(io::ErrorKind::InvalidData, msg)
+            })?;
+            counts.insert(key, value);
+        }
+        Ok(FaultHistory { path, counts })
+    }
+
+    /// Writes the history back to the file it was loaded from.
+    pub fn save(&self) -> io::Result<()> {
+        let mut contents =
+            String::from("# device id reset_during_en under_voltage hardware_esd_reset\n");
+        for (&(device_type, device_id), counts) in &self.counts {
+            contents += &format!(
+                "{} {} {} {} {}\n",
+                device_type,
+                device_id,
+                counts.reset_during_en,
+                counts.under_voltage,
+                counts.hardware_esd_reset
+            );
+        }
+        // Write then rename, so losing power mid-write doesn't lose the history.
+        let tmp = self.path.with_extension("tmp");
+        fs::write(&tmp, contents)?;
+        fs::rename(&tmp, &self.path)
+    }
+
+    /// Counts the notable faults in `record`.
+    pub fn record(&mut self, record: &StickyFaultRecord) {
+        let names = match record.fault_names() {
+            Some(names) => names,
+            None => return,
+        };
+        let counts = self
+            .counts
+            .entry((record.device_type, record.device_id))
+            .or_default();
+        for name in names {
+            match name {
+                "reset_during_en" => counts.reset_during_en += 1,
+                "under_voltage" => counts.under_voltage += 1,
+                "hardware_esd_reset" => counts.hardware_esd_reset += 1,
+                _ => {}
+            }
+        }
+    }
+
+    pub fn counts(&self, device_type: DeviceType, device_id: i32) -> FaultCounts {
+        self.counts
+            .get(&(device_type, device_id))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Forgets all recorded faults.  The file is only changed on the next `save`.
+    pub fn clear(&mut self) {
+        self.counts.clear();
+    }
+}
+
+fn parse_line(line: &str) -> Option<((DeviceType, i32), FaultCounts)> {
+    let mut fields = line.split_whitespace();
+    let device_type = match fields.next()? {
+        "TalonSRX" => DeviceType::TalonSRX,
+        "VictorSPX" => DeviceType::VictorSPX,
+        "PigeonIMU" => DeviceType::PigeonIMU,
+        "CANifier" => DeviceType::CANifier,
+        _ => return None,
+    };
+    let mut next = || fields.next()?.parse().ok();
+    let device_id = next()? as i32;
+    let counts = FaultCounts {
+        reset_during_en: next()?,
+        under_voltage: next()?,
+        hardware_esd_reset: next()?,
+    };
+    Some(((device_type, device_id), counts))
+}
+
+/// A report of the devices that have had notable faults, worst first.
+impl fmt::Display for FaultHistory {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut devices: Vec<_> = self.counts.iter().filter(|(_, c)| c.total() > 0).collect();
+        devices.sort_by_key(|&(_, counts)| ::std::cmp::Reverse(counts.total()));
+        if devices.is_empty() {
+            return writeln!(f, "No faults recorded.");
+        }
+        for (&(device_type, device_id), counts) in devices {
+            writeln!(
+                f,
+                "{} {}: {} resets during enable, {} under-voltage, {} ESD resets",
+                device_type,
+                device_id,
+                counts.reset_during_en,
+                counts.under_voltage,
+                counts.hardware_esd_reset
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn sticky_fault_record_display() {
+    let mut record = StickyFaultRecord {
+        timestamp: Instant::now(),
+        device_type: DeviceType::TalonSRX,
+        device_id: 3,
+        name: None,
+        bits: (1 << 0) | (1 << 5),
+    };
+    assert_eq!(
+        record.to_string(),
+        "TalonSRX 3 sticky faults: under_voltage, reset_during_en"
+    );
+    record.device_type = DeviceType::CANifier;
+    assert_eq!(record.to_string(), "CANifier 3 sticky faults: 0x21");
+    record.bits = 0;
+    assert_eq!(record.to_string(), "CANifier 3 sticky faults: none");
+    record.name = Some("leds");
+    assert_eq!(record.to_string(), "leds (CANifier 3) sticky faults: none");
+}
+
+#[test]
+fn fault_history_round_trip() {
+    let name = format!("ctre_fault_history_{}.txt", ::std::process::id());
+    let path = ::std::env::temp_dir().join(name);
+    let mut history = FaultHistory::load(&path).unwrap();
+    let record = StickyFaultRecord {
+        timestamp: Instant::now(),
+        device_type: DeviceType::VictorSPX,
+        device_id: 7,
+        name: None,
+        bits: (1 << 5) | (1 << 8),
+    };
+    history.record(&record);
+    history.record(&record);
+    history.save().unwrap();
+
+    let loaded = FaultHistory::load(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    let counts = loaded.counts(DeviceType::VictorSPX, 7);
+    assert_eq!((counts.reset_during_en, counts.hardware_esd_reset), (2, 2));
+    assert_eq!(
+        loaded.to_string(),
+        "VictorSPX 7: 2 resets during enable, 0 under-voltage, 2 ESD resets\n"
+    );
+}
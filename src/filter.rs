@@ -0,0 +1,96 @@
+//! Software filters for sensor readings.
+
+use std::collections::VecDeque;
+use std::convert::TryInto;
+
+use motor_control::{raw_pid_idx, BaseMotorController, PidLoop};
+use Result;
+
+/**
+ * Running median over the last few samples.
+ *
+ * A median of `window` samples rejects spikes up to `window / 2` samples long,
+ * while following a real step change after `window / 2 + 1` samples.
+ */
+#[derive(Debug, Clone)]
+pub struct MedianFilter {
+    samples: VecDeque<i32>,
+    window: usize,
+}
+
+impl MedianFilter {
+    /// * `window` - Number of samples to take the median of.  Zero is treated as one.
+    pub fn new(window: usize) -> Self {
+        let window = window.max(1);
+        MedianFilter {
+            samples: VecDeque::with_capacity(window),
+            window,
+        }
+    }
+    /// Adds a sample and returns the median of the window.
+    pub fn update(&mut self, sample: i32) -> i32 {
+        if self.samples.len() == self.window {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+        let mut sorted: Vec<i32> = self.samples.iter().cloned().collect();
+        sorted.sort_unstable();
+        sorted[sorted.len() / 2]
+    }
+    /// Forgets all samples.
+    pub fn reset(&mut self) {
+        self.samples.clear();
+    }
+}
+
+/**
+ * Median filtered `get_selected_sensor_velocity`.
+ *
+ * Noisy quadrature wiring can produce single-frame velocity spikes, which will
+ * trip at-speed checks or kick a software control loop.  Call [`get_velocity`]
+ * once per new reading (e.g. once per robot loop) in place of the raw getter.
+ *
+ * [`get_velocity`]: #method.get_velocity
+ */
+#[derive(Debug)]
+pub struct VelocityFilter<'a, M: 'a> {
+    motor: &'a M,
+    pid_idx: i32,
+    median: MedianFilter,
+}
+
+impl<'a, M: BaseMotorController> VelocityFilter<'a, M> {
+    /// Filters the velocity of `motor`'s primary closed loop sensor over `window` samples.
+    pub fn new(motor: &'a M, window: usize) -> Self {
+        Self::with_pid_idx(motor, 0, window)
+    }
+    /// * `pid_idx` - 0 for Primary closed-loop. 1 for auxiliary closed-loop.
+    pub fn with_pid_idx(motor: &'a M, pid_idx: impl TryInto<PidLoop>, window: usize) -> Self {
+        let pid_idx = raw_pid_idx(pid_idx);
+        VelocityFilter {
+            motor,
+            pid_idx,
+            median: MedianFilter::new(window),
+        }
+    }
+    /// Reads the velocity and returns the filtered value.
+    /// Errors don't affect the filter.
+    pub fn get_velocity(&mut self) -> Result<i32> {
+        let velocity = self.motor.get_selected_sensor_velocity(self.pid_idx)?;
+        Ok(self.median.update(velocity))
+    }
+    /// Forgets past readings, e.g. after the sensor is reset.
+    pub fn reset(&mut self) {
+        self.median.reset();
+    }
+}
+
+#[test]
+fn median_filter_rejects_spike() {
+    let mut filter = MedianFilter::new(5);
+    let out: Vec<i32> = [100, 102, 30000, 101, 99, -30000, 100]
+        .iter()
+        .map(|&v| filter.update(v))
+        .collect();
+    assert_eq!(out, [100, 102, 102, 102, 101, 101, 100]);
+}
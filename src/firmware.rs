@@ -0,0 +1,86 @@
+//! Device firmware versions.
+
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/**
+ * A firmware version, as reported by `get_firmware_version`.
+ *
+ * Versions order by major then minor version, so a minimum version can be
+ * checked with `>=`.  They display and parse as `"major.minor"`, e.g. `"4.22"`.
+ */
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FirmwareVersion {
+    pub major: u8,
+    pub minor: u8,
+}
+
+impl FirmwareVersion {
+    pub const fn new(major: u8, minor: u8) -> Self {
+        FirmwareVersion { major, minor }
+    }
+    /// From the CCI's encoding, with the major version in the high byte:
+    /// version 1.2 is 0x0102.
+    pub const fn from_raw(raw: i32) -> Self {
+        FirmwareVersion {
+            major: (raw >> 8) as u8,
+            minor: raw as u8,
+        }
+    }
+    /// The CCI's encoding of the version.
+    pub const fn raw(self) -> i32 {
+        (self.major as i32) << 8 | self.minor as i32
+    }
+}
+
+impl fmt::Display for FirmwareVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+impl FromStr for FirmwareVersion {
+    type Err = ParseFirmwareVersionError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || ParseFirmwareVersionError(s.to_owned());
+        let mut parts = s.trim().splitn(2, '.');
+        let major = parts.next().ok_or_else(err)?;
+        let minor = parts.next().ok_or_else(err)?;
+        Ok(FirmwareVersion {
+            major: major.parse().map_err(|_| err())?,
+            minor: minor.parse().map_err(|_| err())?,
+        })
+    }
+}
+
+/// A string that isn't a `"major.minor"` firmware version.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseFirmwareVersionError(pub String);
+
+impl fmt::Display for ParseFirmwareVersionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "invalid firmware version {:?}, expected e.g. \"4.22\"",
+            self.0
+        )
+    }
+}
+
+impl Error for ParseFirmwareVersionError {}
+
+#[test]
+fn firmware_versions_parse_and_order() {
+    let version: FirmwareVersion = "4.22".parse().unwrap();
+    assert_eq!(version, FirmwareVersion::new(4, 22));
+    assert_eq!(version.raw(), 0x0416);
+    assert_eq!(FirmwareVersion::from_raw(0x0416), version);
+    assert_eq!(version.to_string(), "4.22");
+    assert!(FirmwareVersion::new(4, 3) < version);
+    assert!(FirmwareVersion::new(20, 0) > version);
+    for bad in &["4", "4.", "4.x", "4.2.1", "256.0"] {
+        assert!(bad.parse::<FirmwareVersion>().is_err(), "{}", bad);
+    }
+}
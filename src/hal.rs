@@ -0,0 +1,101 @@
+//! [`embedded-hal`] adapters, for driving CTRE motor controllers from code
+//! written against the generic embedded traits.
+//!
+//! [`embedded-hal`]: https://docs.rs/embedded-hal
+
+use std::error;
+use std::fmt;
+
+use embedded_hal::pwm::{self, ErrorKind, ErrorType, SetDutyCycle};
+
+use device::DeviceContext;
+use motor_control::{BaseMotorController, ControlMode, DemandType};
+use {DeviceError, ErrorCode};
+
+/// An error reported by the motor controller while setting the duty cycle.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Error(pub DeviceError);
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(&self.0)
+    }
+}
+
+impl pwm::Error for Error {
+    fn kind(&self) -> ErrorKind {
+        ErrorKind::Other
+    }
+}
+
+/**
+ * Drives a motor controller in `PercentOutput` mode as a PWM channel.
+ *
+ * Duty cycles are unsigned, so the direction is fixed when the adapter is made;
+ * use [`reversed`] to drive the motor backwards.
+ *
+ * [`reversed`]: #method.reversed
+ */
+#[derive(Debug)]
+pub struct DutyCycle<'a, M: 'a> {
+    motor: &'a M,
+    reverse: bool,
+}
+
+impl<'a, M: BaseMotorController> DutyCycle<'a, M> {
+    /// The duty cycle that gives full output.
+    /// This matches the resolution of the motor controller's output.
+    pub const MAX_DUTY_CYCLE: u16 = 1023;
+
+    /// Drives `motor` forwards.
+    pub fn new(motor: &'a M) -> Self {
+        DutyCycle {
+            motor,
+            reverse: false,
+        }
+    }
+    /// Drives `motor` in reverse.
+    pub fn reversed(motor: &'a M) -> Self {
+        DutyCycle {
+            motor,
+            reverse: true,
+        }
+    }
+    /// Changes the direction subsequent duty cycles drive the motor in.
+    pub fn set_reverse(&mut self, reverse: bool) {
+        self.reverse = reverse;
+    }
+}
+
+impl<'a, M: BaseMotorController> ErrorType for DutyCycle<'a, M> {
+    type Error = Error;
+}
+
+impl<'a, M: BaseMotorController> SetDutyCycle for DutyCycle<'a, M> {
+    fn max_duty_cycle(&self) -> u16 {
+        Self::MAX_DUTY_CYCLE
+    }
+    fn set_duty_cycle(&mut self, duty: u16) -> Result<(), Error> {
+        let mut percent =
+            f64::from(duty.min(Self::MAX_DUTY_CYCLE)) / f64::from(Self::MAX_DUTY_CYCLE);
+        if self.reverse {
+            percent = -percent;
+        }
+        self.motor.set(
+            ControlMode::PercentOutput,
+            percent,
+            DemandType::Neutral,
+            0.0,
+        );
+        match self.motor.get_last_error() {
+            ErrorCode::OK => Ok(()),
+            err => Err(Error(self.motor.error(err, "c_MotController_Set_4"))),
+        }
+    }
+}
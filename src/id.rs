@@ -0,0 +1,185 @@
+//! CAN device numbers, and declaring a robot's as checked constants.
+//!
+//! Two mechanisms accidentally sharing an id make both misbehave in confusing
+//! ways.  [`device_ids!`] declares every id in one place and fails to compile
+//! if two devices of the same type share one:
+//!
+//! ```
+//! # #[macro_use] extern crate ctre;
+//! # use ctre::motor_control::{BaseMotorController, TalonSRX};
+//! mod ids {
+//!     device_ids! {
+//!         TalonSRX {
+//!             LEFT_MASTER = 1,
+//!             RIGHT_MASTER = 2,
+//!             /// The roller, not the arm.
+//!             INTAKE = 3,
+//!         }
+//!         PigeonIMU {
+//!             IMU = 1,
+//!         }
+//!     }
+//! }
+//! # fn main() {
+//! let left = TalonSRX::new(ids::LEFT_MASTER);
+//! # }
+//! ```
+//!
+//! ```compile_fail
+//! # #[macro_use] extern crate ctre;
+//! device_ids! {
+//!     TalonSRX {
+//!         SHOOTER = 4,
+//!         CLIMBER = 4,
+//!     }
+//! }
+//! # fn main() {}
+//! ```
+//!
+//! [`device_ids!`]: ../macro.device_ids.html
+
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+
+/**
+ * A CAN device number, [0,62].
+ *
+ * Constructors and other APIs taking a device number take a `DeviceId`.  A
+ * literal or constant is checked by `new`, at compile time in a constant:
+ * `TalonSRX::new(DeviceId::new(3))`.  A number from elsewhere, e.g. a config
+ * file, converts with `try_from`, which rejects numbers the CAN layer would
+ * otherwise wrap into another device's id.
+ */
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "i32", into = "i32"))]
+pub struct DeviceId(u8);
+
+impl DeviceId {
+    /// The largest device number.
+    pub const MAX: u8 = 62;
+
+    /**
+     * # Panics
+     * If `id` is over 62.  In a constant, e.g. from `device_ids!`, this is a
+     * compile error instead.
+     */
+    pub const fn new(id: u8) -> DeviceId {
+        if id > DeviceId::MAX {
+            panic!("device ids are 0 to 62");
+        }
+        DeviceId(id)
+    }
+    pub const fn get(self) -> u8 {
+        self.0
+    }
+}
+
+impl TryFrom<i32> for DeviceId {
+    type Error = InvalidDeviceId;
+    fn try_from(id: i32) -> Result<DeviceId, InvalidDeviceId> {
+        match u8::try_from(id) {
+            Ok(valid) if valid <= DeviceId::MAX => Ok(DeviceId(valid)),
+            _ => Err(InvalidDeviceId(id)),
+        }
+    }
+}
+
+impl From<DeviceId> for i32 {
+    fn from(id: DeviceId) -> i32 {
+        i32::from(id.0)
+    }
+}
+
+impl fmt::Display for DeviceId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// A device number outside [0,62].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct InvalidDeviceId(pub i32);
+
+impl fmt::Display for InvalidDeviceId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid device id {}, must be 0 to 62", self.0)
+    }
+}
+
+impl Error for InvalidDeviceId {}
+
+/// Used by `device_ids!`: panics, failing compilation, if `ids` has a duplicate.
+#[doc(hidden)]
+pub const fn assert_unique(ids: &[u8]) {
+    let mut i = 0;
+    while i < ids.len() {
+        let mut j = i + 1;
+        while j < ids.len() {
+            if ids[i] == ids[j] {
+                panic!("two devices of the same type in device_ids! share an id");
+            }
+            j += 1;
+        }
+        i += 1;
+    }
+}
+
+/**
+ * Declares `pub const` [`DeviceId`]s, grouped by device type, and fails to
+ * compile if two in a group share an id or any is over 62.
+ *
+ * Each group is named after a `DeviceType` variant.  Devices of different
+ * types may share an id.  See the [`id` module](id/index.html) for an example.
+ *
+ * [`DeviceId`]: id/struct.DeviceId.html
+ */
+#[macro_export]
+macro_rules! device_ids {
+    ($($device_type:ident {
+        $($(#[$attr:meta])* $name:ident = $id:expr),* $(,)*
+    })*) => {
+        $(
+            $(
+                $(#[$attr])*
+                pub const $name: $crate::DeviceId = $crate::DeviceId::new($id);
+            )*
+            const _: () = {
+                let _ = $crate::DeviceType::$device_type;
+                $crate::id::assert_unique(&[$($id),*]);
+            };
+        )*
+    };
+}
+
+#[cfg(test)]
+mod test_ids {
+    device_ids! {
+        TalonSRX {
+            ARM = 5,
+            /// Shares an id with a device of another type.
+            WRIST = 6,
+        }
+        VictorSPX {
+            ROLLER = 5,
+        }
+    }
+}
+
+#[test]
+fn device_ids_declares_constants() {
+    assert_eq!(test_ids::ARM.get(), 5);
+    assert_eq!(i32::from(test_ids::WRIST), 6);
+    assert_eq!(test_ids::ROLLER, DeviceId::new(5));
+    assert_eq!(test_ids::WRIST.to_string(), "6");
+}
+
+#[test]
+fn out_of_range_ids_are_rejected() {
+    assert_eq!(DeviceId::try_from(62), Ok(DeviceId::new(62)));
+    assert_eq!(DeviceId::try_from(63), Err(InvalidDeviceId(63)));
+    assert_eq!(DeviceId::try_from(-1), Err(InvalidDeviceId(-1)));
+    assert_eq!(DeviceId::try_from(256 + 3), Err(InvalidDeviceId(259)));
+    assert!(::std::panic::catch_unwind(|| DeviceId::new(63)).is_err());
+}
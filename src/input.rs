@@ -0,0 +1,126 @@
+//! Shaping driver joystick input before it becomes a motor demand.
+//!
+//! Two deadbands meet between a joystick and a motor.  Sticks rarely rest
+//! exactly at zero, so small readings are ignored with a *stick* deadband.
+//! Motor controllers also treat small demands as neutral, below their
+//! *neutral* deadband (`config_neutral_deadband`, 0.04 by default).  Shaping
+//! in one place keeps the two consistent:
+//!
+//! * readings within the stick deadband give exactly 0,
+//! * the rest of the stick's travel is rescaled to start from 0 and raised to
+//!   an exponent, for finer control at low speeds,
+//! * and, with a neutral deadband set, non-zero outputs start just past the
+//!   controller's, so the first movement out of the stick deadband moves the
+//!   motor rather than disappearing into its neutral band.
+//!
+//! ```no_run
+//! # use ctre::DeviceId;
+//! # use ctre::input::JoystickAxis;
+//! # use ctre::motor_control::{BaseMotorController, ControlMode, DemandType, TalonSRX};
+//! # fn main() -> ctre::Result<()> {
+//! let left = TalonSRX::new(DeviceId::new(1));
+//! let throttle = JoystickAxis::new(0.08, 2.).inverted().matching(&left, 30)?;
+//! # let raw = 0.5;
+//! left.set(ControlMode::PercentOutput, throttle.apply(raw), DemandType::Neutral, 0.);
+//! # Ok(())
+//! # }
+//! ```
+
+use motor_control::BaseMotorController;
+use Result;
+
+/**
+ * Applies a deadband and exponent to a joystick reading in [-1, 1].
+ *
+ * Readings within `deadband` of zero give 0.  The rest of the range is
+ * rescaled to [0, 1] and raised to `exponent`, keeping the sign; 1 is linear,
+ * 2 or 3 give finer control near the center.  Readings outside [-1, 1] are
+ * clamped.
+ */
+pub fn shape(value: f64, deadband: f64, exponent: f64) -> f64 {
+    let value = value.clamp(-1., 1.);
+    let deadband = deadband.clamp(0., 1.);
+    if value.abs() <= deadband || deadband >= 1. {
+        return 0.;
+    }
+    let scaled = (value.abs() - deadband) / (1. - deadband);
+    scaled.powf(exponent).copysign(value)
+}
+
+/**
+ * How to turn one joystick axis's readings into a demand.  See the
+ * [module documentation](index.html).
+ */
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct JoystickAxis {
+    /// Readings at or below this magnitude give 0.
+    pub deadband: f64,
+    /// Exponent applied to the rescaled reading.  1 is linear.
+    pub exponent: f64,
+    /// Whether to negate the reading, e.g. for a Y axis that reads negative when pushed forward.
+    pub inverted: bool,
+    /// The motor controller's neutral deadband, that non-zero outputs should exceed.
+    pub neutral_deadband: f64,
+}
+
+impl JoystickAxis {
+    pub fn new(deadband: f64, exponent: f64) -> Self {
+        JoystickAxis {
+            deadband,
+            exponent,
+            inverted: false,
+            neutral_deadband: 0.,
+        }
+    }
+    pub fn inverted(self) -> Self {
+        JoystickAxis {
+            inverted: !self.inverted,
+            ..self
+        }
+    }
+    /// Sets the neutral deadband non-zero outputs should exceed.
+    pub fn with_neutral_deadband(self, neutral_deadband: f64) -> Self {
+        JoystickAxis {
+            neutral_deadband,
+            ..self
+        }
+    }
+    /// Reads `motor`'s neutral deadband and uses it as the axis's.
+    pub fn matching<M: BaseMotorController>(self, motor: &M, timeout_ms: i32) -> Result<Self> {
+        Ok(self.with_neutral_deadband(motor.get_neutral_deadband(timeout_ms)?))
+    }
+
+    /// Shapes a raw reading into a demand in [-1, 1].
+    pub fn apply(&self, raw: f64) -> f64 {
+        let raw = if self.inverted { -raw } else { raw };
+        let shaped = shape(raw, self.deadband, self.exponent);
+        if shaped == 0. {
+            return 0.;
+        }
+        let neutral = self.neutral_deadband.clamp(0., 1.);
+        (neutral + (1. - neutral) * shaped.abs()).copysign(shaped)
+    }
+}
+
+impl Default for JoystickAxis {
+    /// A 0.05 deadband, linear, not inverted.
+    fn default() -> Self {
+        JoystickAxis::new(0.05, 1.)
+    }
+}
+
+#[test]
+fn shape_rescales_past_deadbands() {
+    assert_eq!(shape(0.05, 0.1, 1.), 0.);
+    assert_eq!(shape(-1.5, 0.1, 2.), -1.);
+    assert!((shape(0.55, 0.1, 1.) - 0.5).abs() < 1e-9);
+    assert!((shape(-0.55, 0.1, 2.) + 0.25).abs() < 1e-9);
+
+    let axis = JoystickAxis::new(0.1, 1.)
+        .inverted()
+        .with_neutral_deadband(0.04);
+    assert_eq!(axis.apply(0.08), 0.);
+    assert!(axis.apply(-0.1001) > 0.04);
+    assert!((axis.apply(-0.55) - 0.52).abs() < 1e-9);
+    assert_eq!(axis.apply(1.), -1.);
+}
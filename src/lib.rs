@@ -1,15 +1,81 @@
 //! CTRE Phoenix bindings for Rust
 
 extern crate ctre_sys;
-pub use ctre_sys::{ErrorCode, ParamEnum};
-pub type Result<T> = std::result::Result<T, ErrorCode>;
+#[cfg(feature = "embedded-hal")]
+extern crate embedded_hal;
+#[cfg(target_os = "linux")]
+extern crate libc;
+#[cfg(feature = "serde")]
+#[macro_use]
+extern crate serde;
+#[cfg(any(feature = "robot-config", feature = "bridge"))]
+extern crate serde_json;
+#[cfg(feature = "robot-config")]
+extern crate toml;
+#[cfg(feature = "uom")]
+extern crate uom;
+pub use ctre_sys::{ErrorCode, ParamEnum, ParseEnumError};
+pub use error::{DeviceError, DeviceType};
+pub use firmware::FirmwareVersion;
+pub use id::DeviceId;
+pub use lifecycle::{init, shutdown, Options};
+pub use manifest::wait_for_devices;
+pub use registry::registry;
+#[cfg(feature = "robot-config")]
+pub use robot_config::load_robot_config;
+
+pub type Result<T> = std::result::Result<T, DeviceError>;
 
 #[macro_use]
 mod macros;
 
+#[cfg(feature = "bridge")]
+pub mod bridge;
+#[cfg(feature = "robot-config")]
+pub mod calibration;
 pub mod canifier;
+pub mod config;
+pub mod connection;
+pub mod control;
+pub mod datalog;
+pub mod degradation;
+pub mod demand;
+mod device;
+pub mod drive;
+pub mod energy;
+pub mod error;
+pub mod events;
+pub mod faults;
+pub mod filter;
+pub mod firmware;
+#[cfg(feature = "embedded-hal")]
+pub mod hal;
+pub mod id;
+pub mod input;
+pub mod lifecycle;
+pub mod manifest;
+pub mod mechanisms;
 pub mod motion;
 pub mod motor_control;
+pub mod neutral;
+pub mod output;
+pub mod ramp;
+pub mod registry;
+pub mod report;
+#[cfg(feature = "robot-config")]
+pub mod robot_config;
+pub mod robot_state;
+#[cfg(feature = "ros2")]
+pub mod ros2;
+pub mod scheduler;
 pub mod sensors;
+pub mod supervisor;
+pub mod swerve;
+pub mod thermal;
+pub mod tip;
+#[cfg(feature = "uom")]
+pub mod units;
+#[cfg(feature = "wpilib")]
+pub mod wpilib;
 
 pub use canifier::CANifier;
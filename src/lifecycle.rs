@@ -0,0 +1,181 @@
+//! Starting and stopping the crate's background threads.
+//!
+//! By default the scheduler thread starts the first time something schedules a
+//! task, such as a motion profile being pumped, and runs for the rest of the
+//! program.  An embedded program that needs to know when background work
+//! exists calls [`init`] at startup instead, and [`shutdown`] before it exits
+//! or hands the CPU to something else.
+//!
+//! ```no_run
+//! # fn main() -> std::io::Result<()> {
+//! ctre::init(ctre::Options::default())?;
+//! // ...
+//! ctre::shutdown()?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The registry and event bus hold no threads, so `init` only creates them up
+//! front, and `shutdown` leaves them be.  Neither does the simulation, which
+//! runs on the threads calling into it.
+//!
+//! [`init`]: fn.init.html
+//! [`shutdown`]: fn.shutdown.html
+
+use std::io;
+#[cfg(feature = "bridge")]
+use std::net::SocketAddr;
+#[cfg(feature = "bridge")]
+use std::sync::atomic::{AtomicBool, Ordering};
+#[cfg(feature = "bridge")]
+use std::sync::Arc;
+use std::sync::{Mutex, MutexGuard};
+#[cfg(feature = "bridge")]
+use std::thread::JoinHandle;
+
+#[cfg(feature = "bridge")]
+use bridge::Bridge;
+use events::events;
+use registry::registry;
+use scheduler::{self, ThreadOptions};
+
+/// What [`init`](fn.init.html) starts.
+#[derive(Debug, Clone)]
+pub struct Options {
+    /// Start the scheduler thread with these options.  With `None`, it stays
+    /// stopped until a later `init`, and scheduled tasks wait until then.
+    pub scheduler: Option<ThreadOptions>,
+    /// Serve the [`Bridge`](../bridge/struct.Bridge.html) on this address.
+    #[cfg(feature = "bridge")]
+    pub bridge: Option<SocketAddr>,
+}
+
+impl Default for Options {
+    /// Starts the scheduler with the default thread options, and nothing else.
+    fn default() -> Self {
+        Options {
+            scheduler: Some(ThreadOptions::default()),
+            #[cfg(feature = "bridge")]
+            bridge: None,
+        }
+    }
+}
+
+struct State {
+    initialized: bool,
+    #[cfg(feature = "bridge")]
+    bridge: Option<(Arc<AtomicBool>, JoinHandle<io::Result<()>>)>,
+}
+
+fn state() -> MutexGuard<'static, State> {
+    static STATE: Mutex<State> = Mutex::new(State {
+        initialized: false,
+        #[cfg(feature = "bridge")]
+        bridge: None,
+    });
+    STATE.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+/**
+ * Starts the crate's background work as `options` says, rather than as it is
+ * first needed.
+ *
+ * Returns an `AlreadyExists` error if called again before `shutdown`, and any
+ * error setting the scheduler's thread options or binding the bridge, in
+ * which case the scheduler is left as it was before.
+ */
+pub fn init(options: Options) -> io::Result<()> {
+    let mut state = state();
+    if state.initialized {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            "ctre::init called again without ctre::shutdown",
+        ));
+    }
+    registry();
+    events();
+    let was_stopped = scheduler::is_stopped();
+    let undo = || {
+        if was_stopped {
+            scheduler::stop();
+        } else {
+            scheduler::reset();
+        }
+    };
+    match options.scheduler {
+        Some(thread) => {
+            scheduler::start();
+            if let Err(err) = scheduler::set_thread_options(thread) {
+                undo();
+                return Err(err);
+            }
+        }
+        None => scheduler::stop(),
+    }
+    #[cfg(feature = "bridge")]
+    {
+        if let Some(addr) = options.bridge {
+            let stop = Arc::new(AtomicBool::new(false));
+            let thread = Bridge::bind(addr).and_then(|bridge| bridge.spawn_until(stop.clone()));
+            match thread {
+                Ok(thread) => state.bridge = Some((stop, thread)),
+                Err(err) => {
+                    undo();
+                    return Err(err);
+                }
+            }
+        }
+    }
+    state.initialized = true;
+    Ok(())
+}
+
+/**
+ * Stops the threads `init` started, and keeps the scheduler from starting
+ * again until the next `init`.  Blocks until any running task has finished.
+ * Scheduled tasks aren't dropped: they carry on after the next `init`.
+ * Must not be called from a scheduled task.
+ *
+ * Returns the error the bridge stopped with, if it failed while serving;
+ * everything is stopped either way.
+ */
+pub fn shutdown() -> io::Result<()> {
+    let mut state = state();
+    #[cfg(feature = "bridge")]
+    let result = match state.bridge.take() {
+        Some((stop, thread)) => {
+            stop.store(true, Ordering::Relaxed);
+            thread
+                .join()
+                .unwrap_or_else(|_| Err(io::Error::other("the bridge thread panicked")))
+        }
+        None => Ok(()),
+    };
+    #[cfg(not(feature = "bridge"))]
+    let result = Ok(());
+    scheduler::stop();
+    state.initialized = false;
+    result
+}
+
+#[test]
+fn failed_init_leaves_the_scheduler_lazy() {
+    use std::sync::mpsc;
+    use std::time::Duration;
+    let options = Options {
+        // Out of range for SCHED_FIFO, and unsupported off Linux.
+        scheduler: Some(ThreadOptions {
+            priority: Some(100),
+            ..ThreadOptions::default()
+        }),
+        #[cfg(feature = "bridge")]
+        bridge: None,
+    };
+    assert!(init(options).is_err());
+    assert!(!scheduler::is_stopped());
+    let (sender, receiver) = mpsc::channel();
+    let _task = scheduler::schedule(Duration::from_millis(1), move || {
+        let _ = sender.send(());
+    });
+    assert!(receiver.recv_timeout(Duration::from_secs(1)).is_ok());
+}
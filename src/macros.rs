@@ -1,26 +1,72 @@
-/// Convenience wrapper for making simple get calls.
-macro_rules! cci_get_call {
-    ($function:ident($($arg0:expr,)+ _: $type:ty $(, $arg1:expr)*$(,)*)) => ({
-        let mut value: $type = Default::default();
-        let error = unsafe { $function($($arg0,)* &mut value, $($arg1,)*) };
-        if error == ErrorCode::OK { Ok(value) } else { Err(error) }
+/// Records a failed call to `$function` on `$device` and builds its `DeviceError`.
+macro_rules! cci_error {
+    ($device:expr, $function:ident, $error:expr) => {
+        $crate::device::DeviceContext::error($device, $error, stringify!($function))
+    };
+}
+
+/// Converts an index argument of `$function` on `$device`, such as a `pid_idx`
+/// or `slot_idx`, failing with `InvalidParamValue` if it is out of range.
+macro_rules! cci_index {
+    ($device:expr, $function:ident, $index:ident: $type:ty) => {
+        <_ as ::std::convert::TryInto<$type>>::try_into($index)
+            .map_err(|_| cci_error!($device, $function, $crate::ErrorCode::InvalidParamValue))
+    };
+}
+
+/// Convenience wrapper for calls on `$device` that only return an `ErrorCode`.
+macro_rules! cci_call {
+    ($device:expr, $function:ident($($arg:expr),* $(,)*)) => ({
+        match $crate::device::DeviceContext::missing($device, stringify!($function)) {
+            Some(err) => Err(err),
+            None => {
+                let error = unsafe { $function($($arg),*) };
+                if error == ErrorCode::OK {
+                    Ok(())
+                } else {
+                    Err(cci_error!($device, $function, error))
+                }
+            }
+        }
     })
 }
 
-/// Convenience wrapper for making simple get calls, ignoring the ErrorCode.
-macro_rules! cci_get_only {
-    ($function:ident($($arg0:expr,)+ _: $type:ty $(, $arg1:expr)*$(,)*)) => ({
+/// Convenience wrapper for making simple get calls on `$device`.
+macro_rules! cci_get_call {
+    ($device:expr, $function:ident($($arg0:expr,)+ _: $type:ty $(, $arg1:expr)*$(,)*)) => ({
         let mut value: $type = Default::default();
-        unsafe { $function($($arg0,)* &mut value, $($arg1,)*) };
-        value
+        match $crate::device::DeviceContext::missing($device, stringify!($function)) {
+            Some(err) => Err(err),
+            None => {
+                let error = unsafe { $function($($arg0,)* &mut value, $($arg1,)*) };
+                if error == ErrorCode::OK {
+                    $crate::device::DeviceContext::frame_received($device);
+                    Ok(value)
+                } else {
+                    let operation = stringify!($function);
+                    Err($crate::device::DeviceContext::read_failed($device, error, operation))
+                }
+            }
+        }
     })
 }
 
 /// Convenience wrapper for making simple get calls which expect fixed size arrays.
 macro_rules! cci_get_call_array {
-    ($function:ident($($arg0:expr,)+ _: $type:ty $(, $arg1:expr)*$(,)*)) => ({
+    ($device:expr, $function:ident($($arg0:expr,)+ _: $type:ty $(, $arg1:expr)*$(,)*)) => ({
         let mut value: $type = Default::default();
-        let error = unsafe { $function($($arg0,)* value.as_mut_ptr(), $($arg1,)*) };
-        if error == ErrorCode::OK { Ok(value) } else { Err(error) }
+        match $crate::device::DeviceContext::missing($device, stringify!($function)) {
+            Some(err) => Err(err),
+            None => {
+                let error = unsafe { $function($($arg0,)* value.as_mut_ptr(), $($arg1,)*) };
+                if error == ErrorCode::OK {
+                    $crate::device::DeviceContext::frame_received($device);
+                    Ok(value)
+                } else {
+                    let operation = stringify!($function);
+                    Err($crate::device::DeviceContext::read_failed($device, error, operation))
+                }
+            }
+        }
     })
 }
@@ -0,0 +1,366 @@
+//! Declaring all of a robot's devices in one place.
+
+use std::collections::BTreeMap;
+use std::error;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use canifier::CANifier;
+use connection::{Connection, ConnectionMonitor};
+use ctre_sys::clock;
+use error::DeviceType;
+use faults::StickyFaultDevice;
+use id::DeviceId;
+use motor_control::{BaseMotorController, TalonSRX, VictorSPX};
+use sensors::pigeon::PigeonIMU;
+
+/// The CAN bus devices are on unless another is given.
+pub const DEFAULT_BUS: &str = "rio";
+/// How often `wait_for_devices` polls the devices that haven't responded yet.
+const WAIT_POLL_PERIOD: Duration = Duration::from_millis(20);
+
+/// One device in a [`DeviceManifest`].
+///
+/// [`DeviceManifest`]: struct.DeviceManifest.html
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct DeviceSpec {
+    pub device_type: DeviceType,
+    pub id: DeviceId,
+    /// Name given to the device with `set_name`.  Must be unique in the manifest.
+    pub name: &'static str,
+    /**
+     * Name of the CAN bus the device is on.  Ids only need to be unique per bus.
+     * Devices are always created on the default bus; this is for bookkeeping.
+     */
+    pub bus: &'static str,
+}
+
+impl DeviceSpec {
+    pub fn new(device_type: DeviceType, id: DeviceId, name: &'static str) -> Self {
+        DeviceSpec {
+            device_type,
+            id,
+            name,
+            bus: DEFAULT_BUS,
+        }
+    }
+    pub fn on_bus(self, bus: &'static str) -> Self {
+        DeviceSpec { bus, ..self }
+    }
+}
+
+/// Why a [`DeviceManifest`] couldn't be built.
+///
+/// [`DeviceManifest`]: struct.DeviceManifest.html
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ManifestError {
+    /// Two devices of the same type share an id on the same bus.
+    IdCollision(DeviceSpec, DeviceSpec),
+    /// Two devices share a name.
+    DuplicateName(&'static str),
+    /// The devices that didn't respond once created.
+    Missing(Vec<DeviceSpec>),
+}
+
+impl fmt::Display for ManifestError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ManifestError::IdCollision(ref a, ref b) => write!(
+                f,
+                "{} and {} are both {} {} on bus {}",
+                a.name, b.name, a.device_type, a.id, a.bus
+            ),
+            ManifestError::DuplicateName(name) => write!(f, "device name {} is used twice", name),
+            ManifestError::Missing(ref specs) => {
+                f.write_str("devices not responding:")?;
+                for spec in specs {
+                    write!(f, " {} ({} {})", spec.name, spec.device_type, spec.id)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl error::Error for ManifestError {}
+
+/**
+ * The CTRE devices on a robot, declared once.
+ *
+ * [`build`] checks the declarations for collisions, creates and names every
+ * device, and checks that each one responds.  The resulting [`RobotDevices`]
+ * looks devices up by name and hands them to the systems checks.
+ *
+ * ```no_run
+ * # use ctre::error::DeviceType;
+ * # use ctre::DeviceId;
+ * # use ctre::manifest::DeviceManifest;
+ * # fn main() -> Result<(), ctre::manifest::ManifestError> {
+ * let devices = DeviceManifest::new()
+ *     .add(DeviceType::TalonSRX, DeviceId::new(1), "left_master")
+ *     .add(DeviceType::TalonSRX, DeviceId::new(2), "right_master")
+ *     .add(DeviceType::PigeonIMU, DeviceId::new(0), "imu")
+ *     .build()?;
+ * let left = devices.talon_srx("left_master").unwrap();
+ * # Ok(())
+ * # }
+ * ```
+ *
+ * [`build`]: #method.build
+ * [`RobotDevices`]: struct.RobotDevices.html
+ */
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DeviceManifest {
+    specs: Vec<DeviceSpec>,
+}
+
+impl DeviceManifest {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Declares a device on the default bus.
+    pub fn add(&mut self, device_type: DeviceType, id: DeviceId, name: &'static str) -> &mut Self {
+        self.add_spec(DeviceSpec::new(device_type, id, name))
+    }
+    pub fn add_spec(&mut self, spec: DeviceSpec) -> &mut Self {
+        self.specs.push(spec);
+        self
+    }
+    pub fn specs(&self) -> &[DeviceSpec] {
+        &self.specs
+    }
+
+    /// Checks for devices sharing an id or a name, without creating any devices.
+    pub fn validate(&self) -> Result<(), ManifestError> {
+        let mut ids = BTreeMap::new();
+        let mut names = BTreeMap::new();
+        for spec in &self.specs {
+            let key = (spec.bus, spec.device_type, spec.id);
+            if let Some(other) = ids.insert(key, spec) {
+                return Err(ManifestError::IdCollision(*other, *spec));
+            }
+            if names.insert(spec.name, spec).is_some() {
+                return Err(ManifestError::DuplicateName(spec.name));
+            }
+        }
+        Ok(())
+    }
+
+    /// Validates the manifest, then creates and names every device and checks
+    /// that each one responds.
+    pub fn build(&self) -> Result<RobotDevices, ManifestError> {
+        self.validate()?;
+        let devices: Vec<_> = self
+            .specs
+            .iter()
+            .map(|spec| (*spec, Device::create(spec)))
+            .collect();
+        let missing: Vec<_> = devices
+            .iter()
+            .filter(|&(_, device)| !device.connection().is_connected())
+            .map(|&(spec, _)| spec)
+            .collect();
+        if !missing.is_empty() {
+            return Err(ManifestError::Missing(missing));
+        }
+        Ok(RobotDevices { devices })
+    }
+}
+
+/**
+ * Blocks until every device in `manifest` responds, or `deadline` (in `clock`
+ * time) passes.  Call it in robot init, so devices still booting after the
+ * roboRIO has don't fail the first enable.  Every device is polled at least
+ * once, even if the deadline has already passed.
+ *
+ * Returns `ManifestError::Missing` with the devices that never responded, in
+ * the order they were declared.  The manifest isn't validated; `build` it
+ * afterwards to get the devices.
+ */
+pub fn wait_for_devices(manifest: &DeviceManifest, deadline: Instant) -> Result<(), ManifestError> {
+    let mut waiting: Vec<_> = manifest
+        .specs
+        .iter()
+        .map(|spec| (*spec, Device::create(spec)))
+        .collect();
+    loop {
+        waiting.retain(|(_, device)| !device.connection().is_connected());
+        if waiting.is_empty() {
+            return Ok(());
+        }
+        let now = clock::now();
+        if now >= deadline {
+            let missing = waiting.into_iter().map(|(spec, _)| spec).collect();
+            return Err(ManifestError::Missing(missing));
+        }
+        clock::sleep(WAIT_POLL_PERIOD.min(deadline - now));
+    }
+}
+
+enum Device {
+    TalonSRX(TalonSRX),
+    VictorSPX(VictorSPX),
+    PigeonIMU(PigeonIMU),
+    CANifier(CANifier),
+}
+
+impl Device {
+    fn create(spec: &DeviceSpec) -> Device {
+        let device = match spec.device_type {
+            DeviceType::TalonSRX => Device::TalonSRX(TalonSRX::new(spec.id)),
+            DeviceType::VictorSPX => Device::VictorSPX(VictorSPX::new(spec.id)),
+            DeviceType::PigeonIMU => Device::PigeonIMU(PigeonIMU::new(spec.id)),
+            DeviceType::CANifier => Device::CANifier(CANifier::new(spec.id)),
+        };
+        match device {
+            Device::TalonSRX(ref talon) => talon.set_name(spec.name),
+            Device::VictorSPX(ref victor) => victor.set_name(spec.name),
+            Device::PigeonIMU(ref pigeon) => pigeon.set_name(spec.name),
+            Device::CANifier(ref canifier) => canifier.set_name(spec.name),
+        }
+        device
+    }
+    fn connection(&self) -> &dyn Connection {
+        self.sticky_faults()
+    }
+    fn sticky_faults(&self) -> &dyn StickyFaultDevice {
+        match *self {
+            Device::TalonSRX(ref talon) => talon,
+            Device::VictorSPX(ref victor) => victor,
+            Device::PigeonIMU(ref pigeon) => pigeon,
+            Device::CANifier(ref canifier) => canifier,
+        }
+    }
+}
+
+/// The devices created from a [`DeviceManifest`], looked up by name.
+///
+/// [`DeviceManifest`]: struct.DeviceManifest.html
+pub struct RobotDevices {
+    devices: Vec<(DeviceSpec, Device)>,
+}
+
+impl fmt::Debug for RobotDevices {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.specs()).finish()
+    }
+}
+
+impl RobotDevices {
+    fn get(&self, name: &str) -> Option<&Device> {
+        self.devices
+            .iter()
+            .find(|&(spec, _)| spec.name == name)
+            .map(|(_, device)| device)
+    }
+    /// The Talon SRX named `name`, if there is one.
+    pub fn talon_srx(&self, name: &str) -> Option<&TalonSRX> {
+        match self.get(name) {
+            Some(Device::TalonSRX(talon)) => Some(talon),
+            _ => None,
+        }
+    }
+    /// The Victor SPX named `name`, if there is one.
+    pub fn victor_spx(&self, name: &str) -> Option<&VictorSPX> {
+        match self.get(name) {
+            Some(Device::VictorSPX(victor)) => Some(victor),
+            _ => None,
+        }
+    }
+    /// The Pigeon IMU named `name`, if there is one.
+    pub fn pigeon_imu(&self, name: &str) -> Option<&PigeonIMU> {
+        match self.get(name) {
+            Some(Device::PigeonIMU(pigeon)) => Some(pigeon),
+            _ => None,
+        }
+    }
+    /// The CANifier named `name`, if there is one.
+    pub fn canifier(&self, name: &str) -> Option<&CANifier> {
+        match self.get(name) {
+            Some(Device::CANifier(canifier)) => Some(canifier),
+            _ => None,
+        }
+    }
+
+    pub fn specs(&self) -> impl Iterator<Item = &DeviceSpec> {
+        self.devices.iter().map(|(spec, _)| spec)
+    }
+    /// Every device, for checks that work on any kind of device.
+    pub fn connections(&self) -> Vec<&dyn Connection> {
+        self.devices
+            .iter()
+            .map(|(_, device)| device.connection())
+            .collect()
+    }
+    /// Every device, e.g. for `StickyFaultPolicy::apply`.
+    pub fn sticky_fault_devices(&self) -> Vec<&dyn StickyFaultDevice> {
+        self.devices
+            .iter()
+            .map(|(_, device)| device.sticky_faults())
+            .collect()
+    }
+    /// A monitor watching every device.
+    pub fn connection_monitor(&self, timeout: Duration) -> ConnectionMonitor<'_> {
+        let mut monitor = ConnectionMonitor::new(timeout);
+        for device in self.connections() {
+            monitor.add(device);
+        }
+        monitor
+    }
+}
+
+#[test]
+fn manifest_rejects_collisions() {
+    let mut manifest = DeviceManifest::new();
+    manifest
+        .add(DeviceType::TalonSRX, DeviceId::new(15), "left")
+        .add(DeviceType::VictorSPX, DeviceId::new(15), "left_follower")
+        .add_spec(
+            DeviceSpec::new(DeviceType::TalonSRX, DeviceId::new(15), "other").on_bus("canivore"),
+        );
+    assert_eq!(manifest.validate(), Ok(()));
+    manifest.add(DeviceType::TalonSRX, DeviceId::new(15), "right");
+    match manifest.validate() {
+        Err(ManifestError::IdCollision(a, b)) => assert_eq!((a.name, b.name), ("left", "right")),
+        other => panic!("expected a collision, got {:?}", other),
+    }
+    let mut manifest = DeviceManifest::new();
+    manifest
+        .add(DeviceType::TalonSRX, DeviceId::new(15), "left")
+        .add(DeviceType::TalonSRX, DeviceId::new(16), "left");
+    assert_eq!(
+        manifest.validate(),
+        Err(ManifestError::DuplicateName("left"))
+    );
+}
+
+#[test]
+fn manifest_builds_named_devices() {
+    let devices = DeviceManifest::new()
+        .add(DeviceType::TalonSRX, DeviceId::new(15), "arm")
+        .add(DeviceType::VictorSPX, DeviceId::new(16), "arm_follower")
+        .build()
+        .unwrap();
+    let arm = devices.talon_srx("arm").unwrap();
+    assert_eq!((arm.device_number(), arm.name()), (15, Some("arm")));
+    assert!(devices.victor_spx("arm").is_none());
+    assert!(devices
+        .connection_monitor(Duration::from_millis(100))
+        .silent_devices()
+        .is_empty());
+}
+
+#[test]
+fn wait_for_devices_returns_once_they_respond() {
+    let mut manifest = DeviceManifest::new();
+    manifest
+        .add(DeviceType::TalonSRX, DeviceId::new(55), "shooter")
+        .add(DeviceType::CANifier, DeviceId::new(22), "lights");
+    // The simulated devices respond at once, so a passed deadline is no matter.
+    assert_eq!(wait_for_devices(&manifest, clock::now()), Ok(()));
+    let devices = manifest.build().unwrap();
+    assert_eq!(
+        devices.talon_srx("shooter").unwrap().name(),
+        Some("shooter")
+    );
+}
@@ -0,0 +1,119 @@
+//! Multi-motor mechanisms built from motor controller features.
+
+use motor_control::{
+    BaseMotorController, ControlMode, DemandType, FeedbackDevice, FollowerType, RemoteSource,
+    SensorTerm, TalonSRX,
+};
+use Result;
+
+/// Settings for a [`DifferentialMechanism`].
+///
+/// [`DifferentialMechanism`]: struct.DifferentialMechanism.html
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct DifferentialConfig {
+    /// Scales the sum of the two encoders, e.g. 0.5 to use their average.
+    pub sum_coefficient: f64,
+    /// Scales the difference of the two encoders (follower minus master).
+    pub difference_coefficient: f64,
+    /**
+     * Inverts the aux PID output.  With `false`, the aux output is added to the
+     * master and subtracted from the follower.  Flip this if the difference
+     * loop drives away from its target.
+     */
+    pub aux_pid_polarity: bool,
+}
+
+impl Default for DifferentialConfig {
+    fn default() -> Self {
+        DifferentialConfig {
+            sum_coefficient: 0.5,
+            difference_coefficient: 1.0,
+            aux_pid_polarity: false,
+        }
+    }
+}
+
+/**
+ * Two motors controlling two degrees of freedom through their sum and difference,
+ * such as a differential wrist or a climber that tilts and extends.
+ *
+ * Each motor has a quadrature encoder on its Talon SRX.  The master closes the
+ * primary loop (PID0) on the sum of the encoders and the aux loop (PID1) on
+ * their difference.  It applies primary + aux output, and the follower applies
+ * primary - aux.
+ *
+ * Gains are configured on the master as usual: slot 0 for the sum, slot 1 for
+ * the difference (see `select_profile_slot`).
+ */
+#[derive(Debug)]
+pub struct DifferentialMechanism<'a> {
+    master: &'a TalonSRX,
+    follower: &'a TalonSRX,
+}
+
+impl<'a> DifferentialMechanism<'a> {
+    pub fn new(master: &'a TalonSRX, follower: &'a TalonSRX) -> Self {
+        DifferentialMechanism { master, follower }
+    }
+
+    /// Configures the sensor terms, feedback sensors and aux polarity, and starts
+    /// the follower following the master's aux output.
+    pub fn configure(&self, config: &DifferentialConfig, timeout_ms: i32) -> Result<()> {
+        let master = self.master;
+        // The master reads the follower's selected sensor as remote sensor 0.
+        self.follower.config_selected_feedback_sensor(
+            FeedbackDevice::QuadEncoder,
+            0,
+            timeout_ms,
+        )?;
+        let follower = RemoteSource::TalonSelectedSensor(self.follower);
+        master.config_remote_source(0, follower, timeout_ms)?;
+        master.config_sensor_term(SensorTerm::Sum0, FeedbackDevice::QuadEncoder, timeout_ms)?;
+        master.config_sensor_term(SensorTerm::Sum1, FeedbackDevice::RemoteSensor0, timeout_ms)?;
+        // Difference is Diff0 - Diff1.
+        master.config_sensor_term(SensorTerm::Diff0, FeedbackDevice::RemoteSensor0, timeout_ms)?;
+        master.config_sensor_term(SensorTerm::Diff1, FeedbackDevice::QuadEncoder, timeout_ms)?;
+        master.config_selected_feedback_sensor(FeedbackDevice::SensorSum, 0, timeout_ms)?;
+        master.config_selected_feedback_coefficient(config.sum_coefficient, 0, timeout_ms)?;
+        master.config_selected_feedback_sensor(FeedbackDevice::SensorDifference, 1, timeout_ms)?;
+        master.config_selected_feedback_coefficient(
+            config.difference_coefficient,
+            1,
+            timeout_ms,
+        )?;
+        master.config_aux_pid_polarity(config.aux_pid_polarity, timeout_ms)?;
+        self.follower.follow(master, FollowerType::AuxOutput1)
+    }
+
+    /**
+     * Drives both axes.
+     *
+     * * `mode` - Control mode of the sum loop, e.g. `Position` or `MotionMagic`.
+     * * `sum_target` - Target of the sum loop, in the scaled sensor units.
+     * * `difference_target` - Target of the difference loop, in the scaled sensor units.
+     */
+    pub fn set(&self, mode: ControlMode, sum_target: f64, difference_target: f64) {
+        self.master
+            .set(mode, sum_target, DemandType::AuxPID, difference_target);
+    }
+
+    /// Position of the sum axis, in the scaled sensor units.
+    pub fn get_sum_position(&self) -> Result<i32> {
+        self.master.get_selected_sensor_position(0)
+    }
+    /// Position of the difference axis, in the scaled sensor units.
+    pub fn get_difference_position(&self) -> Result<i32> {
+        self.master.get_selected_sensor_position(1)
+    }
+
+    pub fn neutral_output(&self) {
+        self.master.neutral_output();
+        self.follower.neutral_output();
+    }
+    pub fn master(&self) -> &'a TalonSRX {
+        self.master
+    }
+    pub fn follower(&self) -> &'a TalonSRX {
+        self.follower
+    }
+}
@@ -2,3 +2,702 @@
 
 pub use ctre_sys::mot::{MotionProfileStatus, SetValueMotionProfile};
 pub use ctre_sys::mot::{TrajectoryDuration, TrajectoryPoint};
+
+use std::iter::Peekable;
+use std::marker::PhantomData;
+use std::time::{Duration, Instant};
+
+use ctre_sys::clock;
+use ctre_sys::mot::c_MotController_ProcessMotionProfileBuffer;
+
+use device::DeviceContext;
+use motor_control::{shared_state, FollowerType, RemoteSource, TalonSRX};
+use motor_control::{BaseMotorController, ControlMode, DemandType, FeedbackDevice};
+use scheduler::{self, TaskHandle};
+use sensors::pigeon::PigeonIMU;
+use {ErrorCode, ParamEnum, Result};
+
+/// Pigeon IMU yaw units per rotation, as read by a motor controller's remote sensor.
+pub const PIGEON_UNITS_PER_ROTATION: f64 = 8192.;
+
+/**
+ * Units of a Pigeon heading as seen by a motor controller's aux loop.
+ *
+ * The controller reads Pigeon yaw as 8192 units per rotation, then scales it by
+ * the selected feedback coefficient of the loop.  Gains, targets and trajectory
+ * aux positions are all in the scaled units, so degrees have to be converted
+ * with the same coefficient the loop is configured with.
+ */
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct HeadingUnits {
+    coefficient: f64,
+}
+
+impl HeadingUnits {
+    /// Unscaled Pigeon units, 8192 per rotation.
+    pub const NATIVE: HeadingUnits = HeadingUnits { coefficient: 1. };
+    /// Tenths of a degree, 3600 per rotation, as used in CTRE's examples.
+    pub const TENTHS_OF_DEGREE: HeadingUnits = HeadingUnits {
+        coefficient: 3600. / PIGEON_UNITS_PER_ROTATION,
+    };
+
+    /// Units produced by a loop with the given selected feedback coefficient.
+    pub fn from_coefficient(coefficient: f64) -> Self {
+        HeadingUnits { coefficient }
+    }
+    /// Units with `units_per_rotation` per full rotation.
+    pub fn per_rotation(units_per_rotation: f64) -> Self {
+        Self::from_coefficient(units_per_rotation / PIGEON_UNITS_PER_ROTATION)
+    }
+    /// The selected feedback coefficient that produces these units.
+    pub fn coefficient(&self) -> f64 {
+        self.coefficient
+    }
+    pub fn units_per_rotation(&self) -> f64 {
+        PIGEON_UNITS_PER_ROTATION * self.coefficient
+    }
+    /// Converts a heading in degrees to these units.
+    pub fn degrees_to_units(&self, degrees: f64) -> f64 {
+        degrees / 360. * self.units_per_rotation()
+    }
+    /// Converts a heading (or heading error) in these units to degrees.
+    pub fn units_to_degrees(&self, units: f64) -> f64 {
+        units * 360. / self.units_per_rotation()
+    }
+}
+
+/// How far ahead of execution, in ms, points should be buffered before enabling a profile.
+const MIN_BUFFERED_TIME_MS: i32 = 50;
+/// Point duration assumed when neither the base period nor an override gives one.
+const DEFAULT_POINT_DURATION_MS: i32 = 10;
+
+/**
+ * Settings for running a `MotionProfileArc`, where the primary loop (PID0)
+ * follows the trajectory position and the aux loop (PID1) holds a Pigeon heading.
+ */
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ArcProfileConfig {
+    /// Gain slot for the primary (distance) loop.
+    pub primary_slot: u32,
+    /// Gain slot for the aux (heading) loop.
+    pub aux_slot: u32,
+    /// Remote sensor ordinal (0 or 1) used to read the Pigeon.
+    pub remote_ordinal: i32,
+    /// Units of the aux loop.  Defaults to tenths of a degree.
+    pub heading_units: HeadingUnits,
+}
+
+impl Default for ArcProfileConfig {
+    fn default() -> Self {
+        ArcProfileConfig {
+            primary_slot: 0,
+            aux_slot: 1,
+            remote_ordinal: 1,
+            heading_units: HeadingUnits::TENTHS_OF_DEGREE,
+        }
+    }
+}
+
+impl ArcProfileConfig {
+    /// Copies `point`, setting the aux position to `heading_deg` and selecting both slots.
+    pub fn point(&self, point: &TrajectoryPoint, heading_deg: f64) -> TrajectoryPoint {
+        let mut arc = TrajectoryPoint::default();
+        arc.position = point.position;
+        arc.velocity = point.velocity;
+        arc.auxiliary_pos = self.heading_units.degrees_to_units(heading_deg);
+        arc.profile_slot_select_0 = self.primary_slot;
+        arc.profile_slot_select_1 = self.aux_slot;
+        arc.is_last_point = point.is_last_point;
+        arc.zero_pos = point.zero_pos;
+        arc.time_dur = point.time_dur;
+        arc
+    }
+}
+
+/**
+ * Configures `master`'s aux loop (PID1) to use `pigeon`'s yaw, and selects the
+ * gain slots for both loops.  The primary loop's sensor is left as configured.
+ */
+pub fn config_arc_heading(
+    master: &TalonSRX,
+    pigeon: &PigeonIMU,
+    config: &ArcProfileConfig,
+    timeout_ms: i32,
+) -> Result<()> {
+    let source = RemoteSource::PigeonYaw(pigeon);
+    master.config_remote_source(config.remote_ordinal, source, timeout_ms)?;
+    let remote = match config.remote_ordinal {
+        0 => FeedbackDevice::RemoteSensor0,
+        _ => FeedbackDevice::RemoteSensor1,
+    };
+    master.config_selected_feedback_sensor(remote, 1, timeout_ms)?;
+    let coefficient = config.heading_units.coefficient();
+    master.config_selected_feedback_coefficient(coefficient, 1, timeout_ms)?;
+    master.select_profile_slot(config.primary_slot as i32, 0)?;
+    master.select_profile_slot(config.aux_slot as i32, 1)
+}
+
+/**
+ * Drives `master` with its aux loop holding a heading, e.g. to drive straight
+ * with a follower on `FollowerType::AuxOutput1`.
+ *
+ * * `mode` - Control mode of the primary loop.
+ * * `demand0` - Output or target of the primary loop.
+ * * `heading_deg` - Heading for the aux loop to hold, in degrees.
+ * * `units` - The units the aux loop is configured with.
+ */
+pub fn set_with_heading(
+    master: &TalonSRX,
+    mode: ControlMode,
+    demand0: f64,
+    heading_deg: f64,
+    units: HeadingUnits,
+) {
+    master.set(
+        mode,
+        demand0,
+        DemandType::AuxPID,
+        units.degrees_to_units(heading_deg),
+    );
+}
+
+/**
+ * Runs an arc profile on a drivetrain and blocks until its last point is reached,
+ * leaving the profile holding the final point.
+ *
+ * `right` runs the profile in `MotionProfileArc` with its aux loop configured by
+ * [`config_arc_heading`], and `left` follows its aux output.
+ *
+ * * `points` - Trajectory points for the primary (distance) loop.
+ * * `heading_points` - Target heading in degrees for each point.
+ * * `config` - The settings passed to [`config_arc_heading`].
+ *
+ * [`config_arc_heading`]: fn.config_arc_heading.html
+ */
+pub fn run_arc_profile(
+    left: &TalonSRX,
+    right: &TalonSRX,
+    points: &[TrajectoryPoint],
+    heading_points: &[f64],
+    config: &ArcProfileConfig,
+) -> Result<()> {
+    if points.len() != heading_points.len() || points.is_empty() {
+        return Err(right.error(ErrorCode::InvalidParamValue, "run_arc_profile"));
+    }
+    let mut executor = MotionProfileExecutor::new(right, ControlMode::MotionProfileArc);
+    executor.abort()?;
+    left.follow(right, FollowerType::AuxOutput1)?;
+    right.clear_motion_profile_has_underrun(0)?;
+
+    let arc_points = points
+        .iter()
+        .zip(heading_points)
+        .map(|(point, &heading)| config.point(point, heading));
+    let mut stream = stream_profile(arc_points);
+    executor.start()?;
+    loop {
+        stream.fill(right)?;
+        right.process_motion_profile_buffer();
+        executor.update()?;
+        if executor.is_complete() {
+            return Ok(());
+        }
+        clock::sleep(Duration::from_millis(5));
+    }
+}
+
+/**
+ * Buffer handshaking settings for a profile, derived from its point duration.
+ *
+ * Points have to be sent to the controller faster than it executes them, and
+ * enough must be buffered before enabling that the first few can't underrun.
+ */
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ProfileTiming {
+    /// Period of the control frame carrying points to the controller: half the point duration.
+    pub control_frame_period_ms: i32,
+    /// Points to have in the controller before enabling: 50ms worth, and at least 2.
+    pub min_buffered_points: i32,
+}
+
+impl ProfileTiming {
+    pub fn for_point_duration(point_duration_ms: i32) -> Self {
+        let duration = if point_duration_ms > 0 {
+            point_duration_ms
+        } else {
+            DEFAULT_POINT_DURATION_MS
+        };
+        ProfileTiming {
+            control_frame_period_ms: (duration / 2).max(1),
+            min_buffered_points: ((MIN_BUFFERED_TIME_MS + duration - 1) / duration).max(2),
+        }
+    }
+}
+
+/// The stage of a profile run by a [`MotionProfileExecutor`].
+///
+/// [`MotionProfileExecutor`]: struct.MotionProfileExecutor.html
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ExecutorState {
+    /// Not running a profile; the output is disabled.
+    Idle,
+    /// Started, waiting for enough points to reach the controller.
+    Buffering,
+    /// Executing the profile.
+    Running,
+    /// The last point was reached and is being held.
+    Complete,
+}
+
+/**
+ * Runs a motion profile through the Disable, Enable, Hold sequence recommended by CTRE.
+ *
+ * Push or stream the points, call [`start`], then call [`update`] periodically
+ * (e.g. once per robot loop).  The executor disables the output until enough
+ * points are buffered in the controller, enables it, and holds the final point
+ * once it is reached.
+ *
+ * [`start`]: #method.start
+ * [`update`]: #method.update
+ */
+#[derive(Debug)]
+pub struct MotionProfileExecutor<'a, M: 'a> {
+    motor: &'a M,
+    mode: ControlMode,
+    state: ExecutorState,
+    /// Overrides of the settings otherwise derived in `start`.
+    point_duration_ms: Option<i32>,
+    control_frame_period_ms: Option<i32>,
+    min_buffered_points: Option<i32>,
+    timing: ProfileTiming,
+    has_underrun: bool,
+}
+
+impl<'a, M: BaseMotorController> MotionProfileExecutor<'a, M> {
+    /// * `mode` - `MotionProfile`, or `MotionProfileArc` to also run the aux loop.
+    pub fn new(motor: &'a M, mode: ControlMode) -> Self {
+        MotionProfileExecutor {
+            motor,
+            mode,
+            state: ExecutorState::Idle,
+            point_duration_ms: None,
+            control_frame_period_ms: None,
+            min_buffered_points: None,
+            timing: ProfileTiming::for_point_duration(DEFAULT_POINT_DURATION_MS),
+            has_underrun: false,
+        }
+    }
+    /// Sets the point duration used to derive the [`ProfileTiming`], instead of
+    /// reading the configured base trajectory period.
+    ///
+    /// [`ProfileTiming`]: struct.ProfileTiming.html
+    pub fn set_point_duration(&mut self, point_duration_ms: i32) {
+        self.point_duration_ms = Some(point_duration_ms);
+    }
+    /// Overrides the derived control frame period.
+    pub fn set_control_frame_period(&mut self, period_ms: i32) {
+        self.control_frame_period_ms = Some(period_ms);
+    }
+    /// Overrides the derived number of points to buffer before enabling.
+    pub fn set_min_buffered_points(&mut self, points: i32) {
+        self.min_buffered_points = Some(points);
+    }
+    /**
+     * Starts the profile on the next [`update`].
+     *
+     * This speeds up the control frame and works out how many points to buffer
+     * before enabling, from the point duration.  Unless set with
+     * [`set_point_duration`], the duration is read from the controller's base
+     * trajectory period, which blocks for up to 10ms.
+     *
+     * [`update`]: #method.update
+     * [`set_point_duration`]: #method.set_point_duration
+     */
+    pub fn start(&mut self) -> Result<()> {
+        let duration = match self.point_duration_ms {
+            Some(duration) => duration,
+            None => self.motor.config_get_parameter(
+                ParamEnum::MotionProfileTrajectoryPointDurationMs,
+                0,
+                10,
+            )? as i32,
+        };
+        let derived = ProfileTiming::for_point_duration(duration);
+        self.timing = ProfileTiming {
+            control_frame_period_ms: self
+                .control_frame_period_ms
+                .unwrap_or(derived.control_frame_period_ms),
+            min_buffered_points: self
+                .min_buffered_points
+                .unwrap_or(derived.min_buffered_points),
+        };
+        self.motor
+            .change_motion_control_frame_period(self.timing.control_frame_period_ms)?;
+        self.state = ExecutorState::Buffering;
+        self.has_underrun = false;
+        Ok(())
+    }
+    /// The settings applied by the last `start`.
+    pub fn timing(&self) -> ProfileTiming {
+        self.timing
+    }
+    /// Reads the profile status and applies the output for the current state.
+    pub fn update(&mut self) -> Result<ExecutorState> {
+        let status = self.motor.get_new_motion_profile_status()?;
+        self.step(&status);
+        self.apply();
+        Ok(self.state)
+    }
+    /// Disables the output and clears the buffered points.
+    pub fn abort(&mut self) -> Result<()> {
+        self.state = ExecutorState::Idle;
+        self.apply();
+        self.motor.clear_motion_profile_trajectories()
+    }
+    pub fn state(&self) -> ExecutorState {
+        self.state
+    }
+    /// Whether the final point has been reached.
+    pub fn is_complete(&self) -> bool {
+        self.state == ExecutorState::Complete
+    }
+    /// Whether the controller ran out of points since the profile started.
+    pub fn has_underrun(&self) -> bool {
+        self.has_underrun
+    }
+
+    /// The set value for the current state.
+    pub fn set_value(&self) -> SetValueMotionProfile {
+        match self.state {
+            ExecutorState::Idle | ExecutorState::Buffering => SetValueMotionProfile::Disable,
+            ExecutorState::Running => SetValueMotionProfile::Enable,
+            ExecutorState::Complete => SetValueMotionProfile::Hold,
+        }
+    }
+
+    fn step(&mut self, status: &MotionProfileStatus) {
+        self.has_underrun |= status.has_underrun;
+        self.state = match self.state {
+            ExecutorState::Buffering => {
+                let all_buffered = status.top_buffer_cnt == 0 && status.btm_buffer_cnt > 0;
+                if status.btm_buffer_cnt >= self.timing.min_buffered_points || all_buffered {
+                    ExecutorState::Running
+                } else {
+                    ExecutorState::Buffering
+                }
+            }
+            ExecutorState::Running if status.active_point_valid && status.is_last => {
+                ExecutorState::Complete
+            }
+            state => state,
+        };
+    }
+
+    fn apply(&self) {
+        let value = self.set_value() as i32;
+        self.motor
+            .set(self.mode, f64::from(value), DemandType::Neutral, 0.);
+    }
+}
+
+/**
+ * Streams trajectory points into a motor controller's top buffer, as room allows.
+ *
+ * Create one with [`stream_profile`] and call [`fill`] periodically, e.g. once
+ * per robot loop.  Each call pushes points until the top buffer is full or the
+ * points run out, so a long profile can be started before it is all buffered.
+ * The final point is always marked with `is_last_point`, and any other point
+ * has it cleared.
+ *
+ * The top buffer still has to be moved into the controller with
+ * `process_motion_profile_buffer`, either by the caller or by a [`BufferPump`].
+ *
+ * [`stream_profile`]: fn.stream_profile.html
+ * [`fill`]: #method.fill
+ * [`BufferPump`]: struct.BufferPump.html
+ */
+#[derive(Debug)]
+pub struct ProfileStream<I: Iterator<Item = TrajectoryPoint>> {
+    points: Peekable<I>,
+    pushed: usize,
+}
+
+/// Starts streaming `points`.  See [`ProfileStream`].
+///
+/// [`ProfileStream`]: struct.ProfileStream.html
+pub fn stream_profile<I>(points: I) -> ProfileStream<I::IntoIter>
+where
+    I: IntoIterator<Item = TrajectoryPoint>,
+{
+    ProfileStream {
+        points: points.into_iter().peekable(),
+        pushed: 0,
+    }
+}
+
+impl<I: Iterator<Item = TrajectoryPoint>> ProfileStream<I> {
+    /// Pushes points into `motor`'s top buffer until it is full, returning the number pushed.
+    pub fn fill<M: BaseMotorController + ?Sized>(&mut self, motor: &M) -> Result<usize> {
+        let mut count = 0;
+        while !self.is_done() && !motor.is_motion_profile_top_level_buffer_full()? {
+            let mut point = match self.points.next() {
+                Some(point) => point,
+                None => break,
+            };
+            point.is_last_point = self.points.peek().is_none();
+            motor.push_motion_profile_trajectory(&point)?;
+            count += 1;
+        }
+        self.pushed += count;
+        Ok(count)
+    }
+    /// Whether every point has been pushed.
+    pub fn is_done(&mut self) -> bool {
+        self.points.peek().is_none()
+    }
+    /// Number of points pushed so far.
+    pub fn pushed(&self) -> usize {
+        self.pushed
+    }
+}
+
+/**
+ * A scheduler task which periodically moves trajectory points from a motor
+ * controller's top buffer into the controller, i.e. calls
+ * `process_motion_profile_buffer`.
+ *
+ * CTRE recommends running this at half the trajectory point duration.  The
+ * task stops when the pump is dropped.  It follows the motor controller to a
+ * new device if it is rebound with `rebind`.
+ */
+#[derive(Debug)]
+pub struct BufferPump<'a> {
+    _task: TaskHandle,
+    /// Ties the pump to the motor it drives.
+    _motor: PhantomData<&'a ()>,
+}
+
+impl<'a> BufferPump<'a> {
+    pub fn start<M: BaseMotorController + ?Sized>(motor: &'a M, period: Duration) -> Self {
+        let state = shared_state(motor);
+        let task = scheduler::schedule_named("ctre-mp-pump", period, move || {
+            // The CCI motion profile functions lock internally, so they are safe
+            // to call from any thread.
+            unsafe { c_MotController_ProcessMotionProfileBuffer(state.handle()) };
+        });
+        BufferPump {
+            _task: task,
+            _motor: PhantomData,
+        }
+    }
+}
+
+/// A single Motion Magic progress sample.
+#[derive(Debug, Copy, Clone)]
+struct MotionMagicSample {
+    trajectory_position: i32,
+    trajectory_velocity: i32,
+    error: i32,
+}
+
+/**
+ * Detects when a Motion Magic move has finished, for sequencing autonomous
+ * steps without timers.
+ *
+ * A move is finished once the trajectory has reached the target and stopped,
+ * and the closed-loop error is within tolerance, continuously for the settle time.
+ *
+ * ```no_run
+ * # use ctre::DeviceId;
+ * # use ctre::motion::MotionMagicCompletion;
+ * # use ctre::motor_control::{BaseMotorController, ControlMode, DemandType, TalonSRX};
+ * # use std::time::Duration;
+ * # fn main() -> ctre::Result<()> {
+ * let talon = TalonSRX::new(DeviceId::new(1));
+ * let mut completion = MotionMagicCompletion::new(&talon, 50, 20, Duration::from_millis(100));
+ * completion.set_target(4096.);
+ * talon.set(ControlMode::MotionMagic, 4096., DemandType::Neutral, 0.);
+ * while !completion.is_motion_magic_finished()? {
+ *     // ...
+ * }
+ * # Ok(())
+ * # }
+ * ```
+ */
+#[derive(Debug)]
+pub struct MotionMagicCompletion<'a, M: 'a> {
+    motor: &'a M,
+    target: f64,
+    tolerance_ticks: i32,
+    velocity_tolerance: i32,
+    settle: Duration,
+    /// When the move was first seen on target, if it still is.
+    on_target_since: Option<Instant>,
+}
+
+impl<'a, M: BaseMotorController> MotionMagicCompletion<'a, M> {
+    /**
+     * * `tolerance_ticks` - Allowed distance from the target and closed-loop error,
+     *   in sensor units.
+     * * `velocity_tolerance` - Allowed trajectory velocity, in sensor units per 100ms.
+     * * `settle` - How long the move must stay on target before it is finished.
+     */
+    pub fn new(
+        motor: &'a M,
+        tolerance_ticks: i32,
+        velocity_tolerance: i32,
+        settle: Duration,
+    ) -> Self {
+        MotionMagicCompletion {
+            motor,
+            target: 0.,
+            tolerance_ticks,
+            velocity_tolerance,
+            settle,
+            on_target_since: None,
+        }
+    }
+    /// Sets the target of the next move.  Call this along with setting the Motion Magic target.
+    pub fn set_target(&mut self, target: f64) {
+        self.target = target;
+        self.on_target_since = None;
+    }
+    /// Whether the move to the target has finished and settled.
+    pub fn is_motion_magic_finished(&mut self) -> Result<bool> {
+        let sample = MotionMagicSample {
+            trajectory_position: self.motor.get_active_trajectory_position()?,
+            trajectory_velocity: self.motor.get_active_trajectory_velocity()?,
+            error: self.motor.get_closed_loop_error(0)?,
+        };
+        Ok(self.update(sample, clock::now()))
+    }
+
+    fn update(&mut self, sample: MotionMagicSample, now: Instant) -> bool {
+        let tolerance = f64::from(self.tolerance_ticks);
+        let on_target = (f64::from(sample.trajectory_position) - self.target).abs() <= tolerance
+            && sample.trajectory_velocity.abs() <= self.velocity_tolerance
+            && sample.error.abs() <= self.tolerance_ticks;
+        if !on_target {
+            self.on_target_since = None;
+            return false;
+        }
+        let since = *self.on_target_since.get_or_insert(now);
+        now.duration_since(since) >= self.settle
+    }
+}
+
+#[test]
+fn arc_point_scales_heading() {
+    let config = ArcProfileConfig::default();
+    let mut point = TrajectoryPoint::default();
+    point.position = 1000.;
+    point.time_dur = TrajectoryDuration::T10ms;
+    let arc = config.point(&point, 90.);
+    assert_eq!(arc.position, 1000.);
+    assert_eq!(arc.auxiliary_pos, 900.);
+    assert_eq!(arc.profile_slot_select_1, 1);
+    assert_eq!(arc.time_dur, TrajectoryDuration::T10ms);
+}
+
+#[test]
+fn heading_unit_conversions() {
+    assert_eq!(HeadingUnits::NATIVE.degrees_to_units(-180.), -4096.);
+    assert_eq!(HeadingUnits::TENTHS_OF_DEGREE.degrees_to_units(12.5), 125.);
+    assert_eq!(HeadingUnits::TENTHS_OF_DEGREE.units_to_degrees(900.), 90.);
+    assert_eq!(
+        HeadingUnits::per_rotation(3600.),
+        HeadingUnits::TENTHS_OF_DEGREE
+    );
+}
+
+#[test]
+fn motion_magic_finishes_after_settling() {
+    use motor_control::TalonSRX;
+    use DeviceId;
+    let talon = TalonSRX::new(DeviceId::new(1));
+    let mut completion = MotionMagicCompletion::new(&talon, 10, 5, Duration::from_millis(50));
+    completion.set_target(1000.);
+    let start = Instant::now();
+    let at = |ms| start + Duration::from_millis(ms);
+    let sample = |trajectory_position, trajectory_velocity, error| MotionMagicSample {
+        trajectory_position,
+        trajectory_velocity,
+        error,
+    };
+    // Still cruising towards the target.
+    assert!(!completion.update(sample(900, 100, 0), at(0)));
+    // Trajectory done, but the mechanism is lagging.
+    assert!(!completion.update(sample(1000, 0, 40), at(20)));
+    assert!(!completion.update(sample(1000, 0, 5), at(40)));
+    assert!(!completion.update(sample(1000, 0, -3), at(80)));
+    assert!(completion.update(sample(1000, 0, 2), at(90)));
+}
+
+#[test]
+fn stream_marks_last_point() {
+    use motor_control::TalonSRX;
+    use DeviceId;
+    let talon = TalonSRX::new(DeviceId::new(2));
+    let points = (0..3).map(|i| {
+        let mut point = TrajectoryPoint::default();
+        point.position = f64::from(i);
+        point.is_last_point = i == 0;
+        point
+    });
+    let mut stream = stream_profile(points);
+    assert_eq!(stream.fill(&talon).unwrap(), 3);
+    assert!(stream.is_done());
+    assert_eq!(stream.fill(&talon).unwrap(), 0);
+    assert_eq!(stream.pushed(), 3);
+    assert_eq!(
+        talon.get_motion_profile_top_level_buffer_count().unwrap(),
+        3
+    );
+}
+
+#[test]
+fn executor_sequence() {
+    use motor_control::TalonSRX;
+    use DeviceId;
+    let talon = TalonSRX::new(DeviceId::new(3));
+    let mut executor = MotionProfileExecutor::new(&talon, ControlMode::MotionProfile);
+    let mut status = MotionProfileStatus::default();
+    executor.step(&status);
+    assert_eq!(executor.state(), ExecutorState::Idle);
+
+    executor.set_point_duration(10);
+    executor.start().unwrap();
+    assert_eq!(executor.timing().control_frame_period_ms, 5);
+    status.top_buffer_cnt = 20;
+    status.btm_buffer_cnt = 2;
+    executor.step(&status);
+    assert_eq!(executor.set_value(), SetValueMotionProfile::Disable);
+    status.btm_buffer_cnt = 5;
+    executor.step(&status);
+    assert_eq!(executor.set_value(), SetValueMotionProfile::Enable);
+
+    status.active_point_valid = true;
+    status.is_last = true;
+    executor.step(&status);
+    assert!(executor.is_complete());
+    assert_eq!(executor.set_value(), SetValueMotionProfile::Hold);
+}
+
+#[test]
+fn profile_timing_from_duration() {
+    let timing = ProfileTiming::for_point_duration(20);
+    assert_eq!(timing.control_frame_period_ms, 10);
+    assert_eq!(timing.min_buffered_points, 3);
+    assert_eq!(
+        ProfileTiming::for_point_duration(100).min_buffered_points,
+        2
+    );
+    assert_eq!(
+        ProfileTiming::for_point_duration(1).control_frame_period_ms,
+        1
+    );
+    assert_eq!(
+        ProfileTiming::for_point_duration(0),
+        ProfileTiming::for_point_duration(10)
+    );
+}
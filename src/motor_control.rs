@@ -1,7 +1,16 @@
 //! Support for motor controllers (Talon SRX and Victor SPX).
-use std::fmt::*;
 use std::cmp::PartialEq;
+use std::collections::HashMap;
+use std::convert::{TryFrom, TryInto};
+use std::fmt::*;
+use std::hint;
+use std::ptr;
+use std::sync::atomic::{fence, AtomicI32, AtomicU32, AtomicU64, AtomicU8, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::thread;
+use std::time::Duration;
 
+use ctre_sys::clock;
 use ctre_sys::mot::*;
 pub use ctre_sys::mot::{
     ControlFrame, ControlFrameEnhanced, ControlMode, DemandType, FeedbackDevice, FollowerType,
@@ -10,8 +19,21 @@ pub use ctre_sys::mot::{
     VelocityMeasPeriod,
 };
 
+#[cfg(feature = "phoenix-2019")]
+use super::config::ParamGroup;
 use super::{
+    canifier::{CANifier, PWMChannel},
+    config::{ConfigBuilder, ConfigContext, ConfigErrors, Configurator},
+    control::ControlRequest,
+    demand,
+    device::{DeviceContext, DeviceState},
+    error::{DeviceError, DeviceType, ErrorRecord, ErrorStats},
+    events::{events, DeviceEvent},
+    firmware::FirmwareVersion,
+    id::DeviceId,
     motion::{MotionProfileStatus, TrajectoryPoint},
+    registry::registry,
+    sensors::{pigeon::PigeonIMU, SensorUnits},
     ErrorCode, ParamEnum, Result,
 };
 
@@ -94,20 +116,280 @@ impl StickyFaults {
     pub fn has_any_fault(self) -> bool {
         self.0 != 0
     }
+    /// The raw fault bits.
+    pub fn bits(self) -> i32 {
+        self.0
+    }
+}
+
+/// A closed loop of a motor controller.
+#[repr(i32)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum PidLoop {
+    Primary = 0,
+    /// The auxiliary loop, e.g. for heading in `MotionProfileArc`.
+    Aux = 1,
+}
+impl TryFrom<i32> for PidLoop {
+    type Error = ErrorCode;
+    /// Converts a raw `pid_idx`, which must be 0 or 1.
+    fn try_from(pid_idx: i32) -> ::std::result::Result<Self, ErrorCode> {
+        match pid_idx {
+            0 => Ok(PidLoop::Primary),
+            1 => Ok(PidLoop::Aux),
+            _ => Err(ErrorCode::InvalidParamValue),
+        }
+    }
+}
+
+/// A gain slot of a motor controller.
+#[repr(i32)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Slot {
+    S0 = 0,
+    S1 = 1,
+    S2 = 2,
+    S3 = 3,
+}
+impl TryFrom<i32> for Slot {
+    type Error = ErrorCode;
+    /// Converts a raw `slot_idx`, which must be in [0,3].
+    fn try_from(slot_idx: i32) -> ::std::result::Result<Self, ErrorCode> {
+        match slot_idx {
+            0 => Ok(Slot::S0),
+            1 => Ok(Slot::S1),
+            2 => Ok(Slot::S2),
+            3 => Ok(Slot::S3),
+            _ => Err(ErrorCode::InvalidParamValue),
+        }
+    }
+}
+
+/// A raw `pid_idx`, for settings checked when they are applied: -1, which
+/// they reject, if it is out of range.
+pub(crate) fn raw_pid_idx(pid_idx: impl TryInto<PidLoop>) -> i32 {
+    pid_idx.try_into().map_or(-1, |pid_idx| pid_idx as i32)
+}
+
+/// A raw `slot_idx`, as `raw_pid_idx`.
+pub(crate) fn raw_slot_idx(slot_idx: impl TryInto<Slot>) -> i32 {
+    slot_idx.try_into().map_or(-1, |slot_idx| slot_idx as i32)
+}
+
+/// The per-slot P, I, D and F gains.
+const GAIN_PARAMS: &[ParamEnum] = &[
+    ParamEnum::ProfileParamSlot_P,
+    ParamEnum::ProfileParamSlot_I,
+    ParamEnum::ProfileParamSlot_D,
+    ParamEnum::ProfileParamSlot_F,
+];
+
+/**
+ * Everything a gain slot configures, so a slot's closed-loop behavior can be
+ * set, read back or copied as one value.  See `config_gains` and `get_gains`.
+ */
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Gains {
+    pub kp: f64,
+    pub ki: f64,
+    pub kd: f64,
+    pub kf: f64,
+    pub integral_zone: i32,
+    pub allowable_closedloop_error: i32,
+    pub max_integral_accumulator: f64,
+    /// Largest output of the loop, from 0 to 1.
+    pub closed_loop_peak_output: f64,
+    /// Loop period in ms, from 1 to 64.
+    pub closed_loop_period_ms: i32,
+}
+
+/// The factory defaults.
+impl Default for Gains {
+    fn default() -> Self {
+        Gains {
+            kp: 0.,
+            ki: 0.,
+            kd: 0.,
+            kf: 0.,
+            integral_zone: 0,
+            allowable_closedloop_error: 0,
+            max_integral_accumulator: 0.,
+            closed_loop_peak_output: 1.,
+            closed_loop_period_ms: 1,
+        }
+    }
 }
 
+/// Every feedback device, all of which a Talon SRX can use.
+const ALL_FEEDBACK_DEVICES: [FeedbackDevice; 10] = [
+    FeedbackDevice::None,
+    FeedbackDevice::QuadEncoder,
+    FeedbackDevice::Analog,
+    FeedbackDevice::Tachometer,
+    FeedbackDevice::PulseWidthEncodedPosition,
+    FeedbackDevice::SensorSum,
+    FeedbackDevice::SensorDifference,
+    FeedbackDevice::RemoteSensor0,
+    FeedbackDevice::RemoteSensor1,
+    FeedbackDevice::SoftwareEmulatedSensor,
+];
+/// The feedback devices that don't need sensors wired to the controller itself.
+const REMOTE_FEEDBACK_DEVICES: [FeedbackDevice; 6] = [
+    FeedbackDevice::None,
+    FeedbackDevice::SensorSum,
+    FeedbackDevice::SensorDifference,
+    FeedbackDevice::RemoteSensor0,
+    FeedbackDevice::RemoteSensor1,
+    FeedbackDevice::SoftwareEmulatedSensor,
+];
+
 /// Base motor controller features for all CTRE CAN motor controllers.
 ///
 /// This trait is sealed and cannot be implemented for types outside this crate.
 pub trait BaseMotorController: private::Sealed {
     /// Constructor.
     /// * `device_number` - [0,62]
-    fn new(device_number: i32) -> Self;
+    fn new(device_number: DeviceId) -> Self;
+    /**
+     * Creates a controller that may not be on the bus, e.g. on a mechanism
+     * the robot can do without.  While it isn't, calls fail at once with
+     * `DeviceMissing`, a warning, instead of waiting on CAN, and
+     * `Connection::is_absent` says so.  It is checked for again every second.
+     */
+    fn new_optional(device_number: DeviceId) -> Self
+    where
+        Self: Sized,
+    {
+        let controller = Self::new(device_number);
+        controller.make_optional();
+        controller
+    }
 
     fn handle(&self) -> Handle;
     fn get_base_id(&self) -> i32;
+    /// Configures the controller using the current thread's [`ConfigContext`].
+    ///
+    /// [`ConfigContext`]: ../config/struct.ConfigContext.html
+    fn cfg(&self) -> Configurator<'_, Self> {
+        Configurator::new(self)
+    }
+    /// Starts collecting settings to apply together.  See [`ConfigBuilder`].
+    ///
+    /// [`ConfigBuilder`]: ../config/struct.ConfigBuilder.html
+    fn configure(&self) -> ConfigBuilder<'_, Self> {
+        ConfigBuilder::new(self)
+    }
+    /// The label set with `set_name`, if any.
+    fn name(&self) -> Option<&'static str> {
+        private::Sealed::state(self).name()
+    }
+    /**
+     * Labels the controller, e.g. `"intake_roller"`.  The name is included in
+     * `Display`, error messages and fault records, to tell devices apart in logs.
+     */
+    fn set_name(&self, name: &'static str) {
+        private::Sealed::state(self).set_name(name)
+    }
+    /// The device number the controller was created with.  This doesn't touch CAN.
+    fn device_number(&self) -> i32 {
+        self.get_base_id() & 0x3F
+    }
+    /// The device number, e.g. to pass to another controller's remote sensor APIs.
+    fn id(&self) -> DeviceId {
+        private::Sealed::state(self).device_id()
+    }
+    /// Same as `device_number`.  Use `verify_device_id` to ask the CAN layer.
     fn get_device_id(&self) -> i32 {
-        cci_get_only!(c_MotController_GetDeviceNumber(self.handle(), _: i32))
+        self.device_number()
+    }
+    /**
+     * Checks that the CAN layer reports the same device number as `device_number`.
+     * Returns `UnexpectedArbId` if they differ.
+     */
+    fn verify_device_id(&self) -> Result<()> {
+        let reported = cci_get_call!(self, c_MotController_GetDeviceNumber(self.handle(), _: i32))?;
+        if reported == self.device_number() {
+            Ok(())
+        } else {
+            Err(cci_error!(
+                self,
+                verify_device_id,
+                ErrorCode::UnexpectedArbId
+            ))
+        }
+    }
+    /**
+     * Switches the controller over to the device numbered `device_number`, e.g.
+     * after swapping in a spare for one that failed mid-event, and re-applies
+     * the configuration set on the old device.
+     *
+     * Code holding this controller keeps working, and its calls go to the new
+     * device from then on, as do running helpers such as a `BufferPump` or
+     * `ThermalGuard`.  The controller still compares equal to, and hashes the
+     * same as, it did before.
+     *
+     * Only settings made through `cfg()`, `configure()` or a robot config are
+     * re-applied; anything set with `config_*` calls directly must be set
+     * again.  The inversion, neutral mode and sensor phase last set are sent
+     * to the new device, then the last `set` output.
+     *
+     * If the new device doesn't report its firmware version, the controller
+     * stays on the old one and the failed read is returned.  Otherwise every
+     * setting is re-applied even if some fail, using the current thread's
+     * `ConfigContext` timeout; the failures are returned together.
+     */
+    fn rebind(&self, device_number: DeviceId) -> ::std::result::Result<(), ConfigErrors> {
+        let device_id = device_number;
+        let device_number = i32::from(device_id);
+        let arb_id = (self.get_base_id() & !0x3F) | device_number;
+        // The CCI has no way to destroy a handle, and a call on another thread
+        // may still be using the old one, so it is left as is.
+        let handle = unsafe { c_MotController_Create1(arb_id) };
+        let state = private::Sealed::state(self);
+        let mut version = 0;
+        let code = unsafe { c_MotController_GetFirmwareVersion(handle, &mut version) };
+        if code.is_err() {
+            let err = DeviceError::new(code, Self::DEVICE_TYPE, device_number, "rebind");
+            return Err(ConfigErrors {
+                errors: vec![err.with_name(state.name())],
+                cancelled: 0,
+            });
+        }
+        state.bind(handle, device_id);
+        registry().set_id(state, device_number);
+        update_inversion(state);
+        if let Some(neutral_mode) = state.outputs.neutral_mode() {
+            self.set_neutral_mode(neutral_mode);
+        }
+        if let Some(phase_sensor) = state.outputs.sensor_phase() {
+            self.set_sensor_phase(phase_sensor);
+        }
+        for pid_idx in 0..2 {
+            state.selected_slots.set(pid_idx, None);
+            state.on_target[pid_idx as usize].clear();
+        }
+        state.last_frame.clear();
+        let timeout_ms = ConfigContext::current().default_timeout_ms;
+        let errors: Vec<_> = state
+            .config_record
+            .settings()
+            .into_iter()
+            .filter_map(|(param, ordinal, value)| {
+                self.config_set_parameter(param, value, 0, ordinal, timeout_ms)
+                    .err()
+            })
+            .collect();
+        if let Some((mode, demand0, demand1_type, demand1)) = state.outputs.demand() {
+            self.set(mode, demand0, demand1_type, demand1);
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigErrors {
+                errors,
+                cancelled: 0,
+            })
+        }
     }
 
     /**
@@ -126,49 +408,45 @@ pub trait BaseMotorController: private::Sealed {
      * # Examples
      *
      * Arcade Drive Example:
-     * ```
+     * ```ignore
      * talonLeft.set(ControlMode::PercentOutput, joyForward, DemandType::ArbitraryFeedForward, +joyTurn);
      * talonRght.set(ControlMode::PercentOutput, joyForward, DemandType::ArbitraryFeedForward, -joyTurn);
      * ```
      *
      * Drive Straight Example:
      * Note: Selected Sensor Configuration is necessary for both PID0 and PID1.
-     * ```
+     * ```ignore
      * talonLeft.follow(talonRght, FollowerType::AuxOutput1);
      * talonRght.set(ControlMode::PercentOutput, joyForward, DemandType::AuxPID, desiredRobotHeading);
      * ```
      *
      * Drive Straight to a Distance Example:
      * Note: Other configurations (sensor selection, PID gains, etc.) need to be set.
-     * ```
+     * ```ignore
      * talonLeft.follow(talonRght, FollowerType::AuxOutput1);
      * talonRght.set(ControlMode::MotionMagic, targetDistance, DemandType::AuxPID, desiredRobotHeading);
      * ```
      */
     fn set(&self, mode: ControlMode, demand0: f64, demand1_type: DemandType, demand1: f64) {
+        let following = mode == ControlMode::Follower;
+        let state = private::Sealed::state(self);
+        state.following.store(following, Ordering::Relaxed);
+        state
+            .outputs
+            .set_demand((mode, demand0, demand1_type, demand1));
         match mode {
-            ControlMode::Follower => {
-                // did caller specify device ID
-                let work = if 0.0 <= demand0 && demand0 <= 62.0 {
-                    ((self.get_base_id() as u32 >> 16) << 8) | (demand0 as u32)
-                } else {
-                    demand0 as u32
-                };
-                unsafe {
-                    /* single precision guarantees 16bits of integral precision,
-                     *  so float/double cast on work is safe */
-                    c_MotController_Set_4(
-                        self.handle(),
-                        mode as _,
-                        work as f64,
-                        demand1,
-                        demand1_type as _,
-                    )
-                }
-            }
+            ControlMode::Follower => unsafe {
+                c_MotController_Set_4(
+                    self.handle(),
+                    mode as _,
+                    demand::follower_demand(self.get_base_id(), demand0),
+                    demand1,
+                    demand1_type as _,
+                )
+            },
             ControlMode::Current => unsafe {
-                // milliamps
-                c_MotController_SetDemand(self.handle(), mode as _, (1000.0 * demand0) as _, 0)
+                let milliamps = demand::current_demand(demand0);
+                c_MotController_SetDemand(self.handle(), mode as _, milliamps, 0)
             },
             ControlMode::PercentOutput
             //| ControlMode::TimedPercentOutput
@@ -192,12 +470,41 @@ pub trait BaseMotorController: private::Sealed {
         };
     }
 
+    /**
+     * Selects the request's gain slot for the primary loop, if it names one,
+     * then sets the output with the request's arbitrary feedforward.
+     *
+     * The slot is only selected if it differs from `selected_profile_slot`,
+     * so applying a request every loop doesn't resend it.  Like `set` and the
+     * getters, this doesn't allocate, so it is safe to call from a fast loop.
+     *
+     * See the [`control`](../control/index.html) module for the requests.
+     */
+    fn apply<R: ControlRequest + ?Sized>(&self, request: &R) -> Result<()> {
+        match request.slot() {
+            Some(slot) if self.selected_profile_slot(PidLoop::Primary) != Some(slot) => {
+                self.select_profile_slot(slot, PidLoop::Primary)?
+            }
+            _ => {}
+        }
+        self.set(
+            request.control_mode(),
+            request.demand(),
+            DemandType::ArbitraryFeedForward,
+            request.arbitrary_feed_forward(),
+        );
+        Ok(())
+    }
+
     /// Neutral the motor output by setting control mode to disabled.
     fn neutral_output(&self) {
         self.set(ControlMode::Disabled, 0.0, DemandType::Neutral, 0.0)
     }
     /// Sets the mode of operation during neutral throttle output.
     fn set_neutral_mode(&self, neutral_mode: NeutralMode) {
+        private::Sealed::state(self)
+            .outputs
+            .set_neutral_mode(neutral_mode);
         unsafe { c_MotController_SetNeutralMode(self.handle(), neutral_mode as _) }
     }
 
@@ -212,6 +519,9 @@ pub trait BaseMotorController: private::Sealed {
      * [`set_inverted`]: #method.set_inverted
      */
     fn set_sensor_phase(&self, phase_sensor: bool) {
+        private::Sealed::state(self)
+            .outputs
+            .set_sensor_phase(phase_sensor);
         unsafe { c_MotController_SetSensorPhase(self.handle(), phase_sensor) }
     }
     /**
@@ -224,60 +534,123 @@ pub trait BaseMotorController: private::Sealed {
      *  - Green LEDs always represents positive request from robot-controller/closed-looping mode.
      *  - Green LEDs correlates to forward limit switch.
      *  - Green LEDs correlates to forward soft limit.
+     *
+     * Takes a `bool` or an [`InvertType`].  A follower set to
+     * `InvertType::FollowMaster` or `OpposeMaster` is re-inverted whenever the
+     * master it was told to `follow` is, so a mechanism's motors only need
+     * the master's inversion changed.
+     *
+     * [`InvertType`]: enum.InvertType.html
      */
-    fn set_inverted(&self, invert: bool) {
-        unsafe { c_MotController_SetInverted(self.handle(), invert) }
+    fn set_inverted(&self, invert: impl Into<InvertType>) {
+        let state = private::Sealed::state(self);
+        {
+            let mut inversion = state.inversion.lock().unwrap();
+            inversion.invert_type = invert.into();
+            inversion.apply = true;
+        }
+        update_inversion(state);
+    }
+    /// Whether the output is inverted, with `FollowMaster` and `OpposeMaster` resolved.
+    fn get_inverted(&self) -> bool {
+        private::Sealed::state(self)
+            .inversion
+            .lock()
+            .unwrap()
+            .inverted
+    }
+    /// The inversion last set with `set_inverted`.
+    fn get_invert_type(&self) -> InvertType {
+        private::Sealed::state(self)
+            .inversion
+            .lock()
+            .unwrap()
+            .invert_type
     }
 
     fn config_openloop_ramp(
         &self,
         seconds_from_neutral_to_full: f64,
         timeout_ms: i32,
-    ) -> ErrorCode {
-        unsafe {
+    ) -> Result<()> {
+        cci_call!(
+            self,
             c_MotController_ConfigOpenLoopRamp(
                 self.handle(),
                 seconds_from_neutral_to_full,
                 timeout_ms,
             )
-        }
+        )
     }
     fn config_closedloop_ramp(
         &self,
         seconds_from_neutral_to_full: f64,
         timeout_ms: i32,
-    ) -> ErrorCode {
-        unsafe {
+    ) -> Result<()> {
+        cci_call!(
+            self,
             c_MotController_ConfigClosedLoopRamp(
                 self.handle(),
                 seconds_from_neutral_to_full,
                 timeout_ms,
             )
-        }
+        )
+    }
+    /// Reads the open loop ramp, in seconds from neutral to full output.
+    fn get_openloop_ramp(&self, timeout_ms: i32) -> Result<f64> {
+        self.config_get_parameter(ParamEnum::OpenloopRamp, 0, timeout_ms)
+    }
+    /// Reads the closed loop ramp, in seconds from neutral to full output.
+    fn get_closedloop_ramp(&self, timeout_ms: i32) -> Result<f64> {
+        self.config_get_parameter(ParamEnum::ClosedloopRamp, 0, timeout_ms)
     }
 
-    fn config_peak_output_forward(&self, percent_out: f64, timeout_ms: i32) -> ErrorCode {
-        unsafe { c_MotController_ConfigPeakOutputForward(self.handle(), percent_out, timeout_ms) }
+    fn config_peak_output_forward(&self, percent_out: f64, timeout_ms: i32) -> Result<()> {
+        cci_call!(
+            self,
+            c_MotController_ConfigPeakOutputForward(self.handle(), percent_out, timeout_ms)
+        )
     }
-    fn config_peak_output_reverse(&self, percent_out: f64, timeout_ms: i32) -> ErrorCode {
-        unsafe { c_MotController_ConfigPeakOutputReverse(self.handle(), percent_out, timeout_ms) }
+    fn config_peak_output_reverse(&self, percent_out: f64, timeout_ms: i32) -> Result<()> {
+        cci_call!(
+            self,
+            c_MotController_ConfigPeakOutputReverse(self.handle(), percent_out, timeout_ms)
+        )
     }
 
-    fn config_nominal_output_forward(&self, percent_out: f64, timeout_ms: i32) -> ErrorCode {
-        unsafe {
+    fn config_nominal_output_forward(&self, percent_out: f64, timeout_ms: i32) -> Result<()> {
+        cci_call!(
+            self,
             c_MotController_ConfigNominalOutputForward(self.handle(), percent_out, timeout_ms)
-        }
+        )
     }
-    fn config_nominal_output_reverse(&self, percent_out: f64, timeout_ms: i32) -> ErrorCode {
-        unsafe {
+    fn config_nominal_output_reverse(&self, percent_out: f64, timeout_ms: i32) -> Result<()> {
+        cci_call!(
+            self,
             c_MotController_ConfigNominalOutputReverse(self.handle(), percent_out, timeout_ms)
-        }
+        )
+    }
+    fn get_peak_output_forward(&self, timeout_ms: i32) -> Result<f64> {
+        self.config_get_parameter(ParamEnum::PeakPosOutput, 0, timeout_ms)
+    }
+    fn get_peak_output_reverse(&self, timeout_ms: i32) -> Result<f64> {
+        self.config_get_parameter(ParamEnum::PeakNegOutput, 0, timeout_ms)
+    }
+    fn get_nominal_output_forward(&self, timeout_ms: i32) -> Result<f64> {
+        self.config_get_parameter(ParamEnum::NominalPosOutput, 0, timeout_ms)
+    }
+    fn get_nominal_output_reverse(&self, timeout_ms: i32) -> Result<f64> {
+        self.config_get_parameter(ParamEnum::NominalNegOutput, 0, timeout_ms)
     }
 
-    fn config_neutral_deadband(&self, percent_deadband: f64, timeout_ms: i32) -> ErrorCode {
-        unsafe {
+    fn config_neutral_deadband(&self, percent_deadband: f64, timeout_ms: i32) -> Result<()> {
+        cci_call!(
+            self,
             c_MotController_ConfigNeutralDeadband(self.handle(), percent_deadband, timeout_ms)
-        }
+        )
+    }
+    fn get_neutral_deadband(&self, timeout_ms: i32) -> Result<f64> {
+        self.config_get_parameter(ParamEnum::NeutralDeadband, 0, timeout_ms)
     }
 
     /**
@@ -291,8 +664,15 @@ pub trait BaseMotorController: private::Sealed {
      *   If nonzero, function will wait for config success and report an error if it times out.
      *   If zero, no blocking or checking is performed.
      */
-    fn config_voltage_comp_saturation(&self, voltage: f64, timeout_ms: i32) -> ErrorCode {
-        unsafe { c_MotController_ConfigVoltageCompSaturation(self.handle(), voltage, timeout_ms) }
+    fn config_voltage_comp_saturation(&self, voltage: f64, timeout_ms: i32) -> Result<()> {
+        cci_call!(
+            self,
+            c_MotController_ConfigVoltageCompSaturation(self.handle(), voltage, timeout_ms)
+        )
+    }
+    /// Reads the voltage compensation saturation voltage.
+    fn get_voltage_comp_saturation(&self, timeout_ms: i32) -> Result<f64> {
+        self.config_get_parameter(ParamEnum::NominalBatteryVoltage, 0, timeout_ms)
     }
     /// Configures the voltage measurement filter.
     /// * `filter_window_samples` - Number of samples in the rolling average of voltage measurement.
@@ -300,14 +680,15 @@ pub trait BaseMotorController: private::Sealed {
         &self,
         filter_window_samples: i32,
         timeout_ms: i32,
-    ) -> ErrorCode {
-        unsafe {
+    ) -> Result<()> {
+        cci_call!(
+            self,
             c_MotController_ConfigVoltageMeasurementFilter(
                 self.handle(),
                 filter_window_samples,
                 timeout_ms,
             )
-        }
+        )
     }
     /// Enable voltage compensation.
     /// If enabled, voltage compensation works in all control modes.
@@ -316,21 +697,21 @@ pub trait BaseMotorController: private::Sealed {
     }
 
     fn get_bus_voltage(&self) -> Result<f64> {
-        cci_get_call!(c_MotController_GetBusVoltage(self.handle(), _: f64))
+        cci_get_call!(self, c_MotController_GetBusVoltage(self.handle(), _: f64))
     }
     /// Gets the output percentage of the motor controller, in the interval [-1,+1].
     fn get_motor_output_percent(&self) -> Result<f64> {
-        cci_get_call!(c_MotController_GetMotorOutputPercent(self.handle(), _: f64))
+        cci_get_call!(self, c_MotController_GetMotorOutputPercent(self.handle(), _: f64))
     }
     fn get_motor_output_voltage(&self) -> Result<f64> {
         Ok(self.get_bus_voltage()? * self.get_motor_output_percent()?)
     }
     fn get_output_current(&self) -> Result<f64> {
-        cci_get_call!(c_MotController_GetOutputCurrent(self.handle(), _: f64))
+        cci_get_call!(self, c_MotController_GetOutputCurrent(self.handle(), _: f64))
     }
     /// Gets the temperature of the motor controller in degrees Celsius.
     fn get_temperature(&self) -> Result<f64> {
-        cci_get_call!(c_MotController_GetTemperature(self.handle(), _: f64))
+        cci_get_call!(self, c_MotController_GetTemperature(self.handle(), _: f64))
     }
 
     /**
@@ -346,17 +727,20 @@ pub trait BaseMotorController: private::Sealed {
     fn config_selected_feedback_sensor(
         &self,
         feedback_device: RemoteFeedbackDevice,
-        pid_idx: i32,
+        pid_idx: impl TryInto<PidLoop>,
         timeout_ms: i32,
-    ) -> ErrorCode {
-        unsafe {
+    ) -> Result<()> {
+        let pid_idx = cci_index!(self, c_MotController_ConfigSelectedFeedbackSensor, pid_idx: PidLoop)?
+            as i32;
+        cci_call!(
+            self,
             c_MotController_ConfigSelectedFeedbackSensor(
                 self.handle(),
                 feedback_device as _,
                 pid_idx,
                 timeout_ms,
             )
-        }
+        )
     }
     /**
      * The Feedback Coefficient is a scalar applied to the value of the
@@ -376,42 +760,170 @@ pub trait BaseMotorController: private::Sealed {
     fn config_selected_feedback_coefficient(
         &self,
         coefficient: f64,
-        pid_idx: i32,
+        pid_idx: impl TryInto<PidLoop>,
         timeout_ms: i32,
-    ) -> ErrorCode {
-        unsafe {
+    ) -> Result<()> {
+        let pid_idx = cci_index!(self, c_MotController_ConfigSelectedFeedbackCoefficient, pid_idx: PidLoop)?
+            as i32;
+        cci_call!(
+            self,
             c_MotController_ConfigSelectedFeedbackCoefficient(
                 self.handle(),
                 coefficient,
                 pid_idx,
                 timeout_ms,
             )
-        }
+        )
     }
 
+    /**
+     * Applies the feedback coefficient which makes the selected sensor read
+     * `units_per_rotation` units per rotation, so that gains carry over between
+     * mechanisms with different sensors.  Returns the coefficient.
+     *
+     * The selected sensor must have a fixed resolution (see
+     * `FeedbackDevice::units_per_rotation`).  For other sensors, such as remote
+     * sensors, use [`normalize_feedback_from`] with the sensor's resolution.
+     *
+     * * `pid_idx` - 0 for Primary closed-loop. 1 for auxiliary closed-loop.
+     *
+     * [`normalize_feedback_from`]: #method.normalize_feedback_from
+     */
+    fn normalize_feedback_to(
+        &self,
+        units_per_rotation: f64,
+        pid_idx: impl TryInto<PidLoop>,
+        timeout_ms: i32,
+    ) -> Result<f64> {
+        let pid_idx = cci_index!(self, normalize_feedback_to, pid_idx: PidLoop)? as i32;
+        let selected =
+            self.config_get_parameter(ParamEnum::FeedbackSensorType, pid_idx, timeout_ms)?;
+        let native = [
+            FeedbackDevice::QuadEncoder,
+            FeedbackDevice::Analog,
+            FeedbackDevice::PulseWidthEncodedPosition,
+        ]
+        .iter()
+        .find(|&&device| f64::from(device as i32) == selected)
+        .and_then(|device| device.units_per_rotation());
+        match native {
+            Some(native) => {
+                self.normalize_feedback_from(native, units_per_rotation, pid_idx, timeout_ms)
+            }
+            None => Err(cci_error!(
+                self,
+                normalize_feedback_to,
+                ErrorCode::InvalidParamValue
+            )),
+        }
+    }
+    /**
+     * Applies the feedback coefficient which scales a sensor with
+     * `native_units_per_rotation` to `units_per_rotation`.  Returns the coefficient.
+     *
+     * The coefficient can't be more than 1, so a sensor can only be scaled down
+     * to a lower resolution.
+     */
+    fn normalize_feedback_from(
+        &self,
+        native_units_per_rotation: f64,
+        units_per_rotation: f64,
+        pid_idx: impl TryInto<PidLoop>,
+        timeout_ms: i32,
+    ) -> Result<f64> {
+        let pid_idx = cci_index!(self, normalize_feedback_from, pid_idx: PidLoop)? as i32;
+        let coefficient =
+            match demand::feedback_coefficient(native_units_per_rotation, units_per_rotation) {
+                Some(coefficient) => coefficient,
+                None => {
+                    return Err(cci_error!(
+                        self,
+                        normalize_feedback_from,
+                        ErrorCode::InvalidParamValue
+                    ))
+                }
+            };
+        self.config_selected_feedback_coefficient(coefficient, pid_idx, timeout_ms)?;
+        Ok(coefficient)
+    }
     /**
      * Select what remote device and signal to assign to Remote Sensor 0 or Remote Sensor 1.
      * After binding a remote device and signal to Remote Sensor X, you may select Remote Sensor X
      * as a PID source for closed-loop features.
+     *
+     * Prefer [`config_remote_source`], which can't pair a source with the wrong kind of device.
+     *
+     * [`config_remote_source`]: #method.config_remote_source
      */
     fn config_remote_feedback_filter(
         &self,
-        device_id: i32,
+        device_id: DeviceId,
         remote_sensor_source: RemoteSensorSource,
         remote_ordinal: i32,
         timeout_ms: i32,
-    ) -> ErrorCode {
-        unsafe {
+    ) -> Result<()> {
+        cci_call!(
+            self,
             c_MotController_ConfigRemoteFeedbackFilter(
                 self.handle(),
-                device_id,
+                i32::from(device_id),
                 remote_sensor_source as _,
                 remote_ordinal,
                 timeout_ms,
             )
+        )
+    }
+
+    /**
+     * Assigns `source` to Remote Sensor `remote_ordinal`, 0 or 1, after which
+     * `FeedbackDevice::RemoteSensor0` or `RemoteSensor1` can be selected as a
+     * PID source.
+     *
+     * ```no_run
+     * # use ctre::DeviceId;
+     * # use ctre::motor_control::{BaseMotorController, RemoteSource, TalonSRX};
+     * # use ctre::sensors::pigeon::PigeonIMU;
+     * let (talon, pigeon) = (TalonSRX::new(DeviceId::new(1)), PigeonIMU::new(DeviceId::new(0)));
+     * talon.config_remote_source(0, RemoteSource::PigeonYaw(&pigeon), 30).unwrap();
+     * ```
+     *
+     * Returns `InvalidParamValue` if `remote_ordinal` isn't 0 or 1.
+     */
+    fn config_remote_source(
+        &self,
+        remote_ordinal: i32,
+        source: RemoteSource,
+        timeout_ms: i32,
+    ) -> Result<()> {
+        if !(0..=1).contains(&remote_ordinal) {
+            return Err(cci_error!(
+                self,
+                config_remote_source,
+                ErrorCode::InvalidParamValue
+            ));
         }
+        let (device_id, remote_sensor_source) = source.raw();
+        self.config_remote_feedback_filter(
+            device_id,
+            remote_sensor_source,
+            remote_ordinal,
+            timeout_ms,
+        )
     }
 
+    /**
+     * The feedback devices this controller can use, as a selected sensor or
+     * sensor term.  A Victor SPX has no sensor inputs of its own, so it can
+     * only use remote sensors and the sum or difference of them.
+     *
+     * Selecting any other device fails with `FeatureNotSupported`.
+     */
+    fn supported_feedback_devices(&self) -> &'static [FeedbackDevice] {
+        match <Self as private::Sealed>::DEVICE_TYPE {
+            DeviceType::VictorSPX => &REMOTE_FEEDBACK_DEVICES,
+            _ => &ALL_FEEDBACK_DEVICES,
+        }
+    }
     /**
      * Select what sensor term should be bound to switch feedback device.
      * Sensor Sum = Sensor Sum Term 0 - Sensor Sum Term 1
@@ -424,27 +936,82 @@ pub trait BaseMotorController: private::Sealed {
         sensor_term: SensorTerm,
         feedback_device: FeedbackDevice,
         timeout_ms: i32,
-    ) -> ErrorCode {
-        unsafe {
+    ) -> Result<()> {
+        if !self.supported_feedback_devices().contains(&feedback_device) {
+            return Err(cci_error!(
+                self,
+                config_sensor_term,
+                ErrorCode::FeatureNotSupported
+            ));
+        }
+        cci_call!(
+            self,
             c_MotController_ConfigSensorTerm(
                 self.handle(),
                 sensor_term as _,
                 feedback_device as _,
                 timeout_ms,
             )
-        }
+        )
+    }
+    /**
+     * Disables continuous tracking of the position for analog and pulse-width.
+     * If the signal goes from 4095 to 0 (pulse-width) a motor controller will
+     * continue to read 4096 by default. If overflow tracking is disabled, it
+     * will wrap to 0 (not continuous).
+     *
+     * Requires Phoenix 2019 or later.
+     */
+    #[cfg(feature = "phoenix-2019")]
+    fn config_feedback_not_continuous(
+        &self,
+        feedback_not_continuous: bool,
+        timeout_ms: i32,
+    ) -> Result<()> {
+        cci_call!(
+            self,
+            c_MotController_ConfigFeedbackNotContinuous(
+                self.handle(),
+                feedback_not_continuous,
+                timeout_ms,
+            )
+        )
+    }
+    /**
+     * Disables going to neutral (brake/coast) when a remote sensor is no longer detected.
+     *
+     * Requires Phoenix 2019 or later.
+     */
+    #[cfg(feature = "phoenix-2019")]
+    fn config_remote_sensor_closed_loop_disable_neutral_on_los(
+        &self,
+        disable: bool,
+        timeout_ms: i32,
+    ) -> Result<()> {
+        cci_call!(
+            self,
+            c_MotController_ConfigRemoteSensorClosedLoopDisableNeutralOnLOS(
+                self.handle(),
+                disable,
+                timeout_ms,
+            )
+        )
     }
 
     /// Get the selected sensor position (in raw sensor units).
-    fn get_selected_sensor_position(&self, pid_idx: i32) -> Result<i32> {
-        cci_get_call!(c_MotController_GetSelectedSensorPosition(
+    fn get_selected_sensor_position(&self, pid_idx: impl TryInto<PidLoop>) -> Result<i32> {
+        let pid_idx =
+            cci_index!(self, c_MotController_GetSelectedSensorPosition, pid_idx: PidLoop)? as i32;
+        cci_get_call!(self, c_MotController_GetSelectedSensorPosition(
             self.handle(),
             _: i32,
             pid_idx,
         ))
     }
-    fn get_selected_sensor_velocity(&self, pid_idx: i32) -> Result<i32> {
-        cci_get_call!(c_MotController_GetSelectedSensorVelocity(
+    fn get_selected_sensor_velocity(&self, pid_idx: impl TryInto<PidLoop>) -> Result<i32> {
+        let pid_idx =
+            cci_index!(self, c_MotController_GetSelectedSensorVelocity, pid_idx: PidLoop)? as i32;
+        cci_get_call!(self, c_MotController_GetSelectedSensorVelocity(
             self.handle(),
             _: i32,
             pid_idx,
@@ -453,34 +1020,103 @@ pub trait BaseMotorController: private::Sealed {
     fn set_selected_sensor_position(
         &self,
         sensor_pos: i32,
-        pid_idx: i32,
+        pid_idx: impl TryInto<PidLoop>,
         timeout_ms: i32,
-    ) -> ErrorCode {
-        unsafe {
+    ) -> Result<()> {
+        let pid_idx =
+            cci_index!(self, c_MotController_SetSelectedSensorPosition, pid_idx: PidLoop)? as i32;
+        cci_call!(
+            self,
             c_MotController_SetSelectedSensorPosition(
                 self.handle(),
                 sensor_pos,
                 pid_idx,
                 timeout_ms,
             )
-        }
+        )
+    }
+    /// Enables clearing the position of the feedback sensor when the forward limit switch is triggered.
+    /// Requires Phoenix 2019 or later.
+    #[cfg(feature = "phoenix-2019")]
+    fn config_clear_position_on_limit_f(&self, clear: bool, timeout_ms: i32) -> Result<()> {
+        cci_call!(
+            self,
+            c_MotController_ConfigClearPositionOnLimitF(self.handle(), clear, timeout_ms)
+        )
+    }
+    /// Enables clearing the position of the feedback sensor when the reverse limit switch is triggered.
+    /// Requires Phoenix 2019 or later.
+    #[cfg(feature = "phoenix-2019")]
+    fn config_clear_position_on_limit_r(&self, clear: bool, timeout_ms: i32) -> Result<()> {
+        cci_call!(
+            self,
+            c_MotController_ConfigClearPositionOnLimitR(self.handle(), clear, timeout_ms)
+        )
+    }
+    /// Enables clearing the position of the feedback sensor when the quadrature index signal is detected.
+    /// Requires Phoenix 2019 or later.
+    #[cfg(feature = "phoenix-2019")]
+    fn config_clear_position_on_quad_idx(&self, clear: bool, timeout_ms: i32) -> Result<()> {
+        cci_call!(
+            self,
+            c_MotController_ConfigClearPositionOnQuadIdx(self.handle(), clear, timeout_ms)
+        )
+    }
+    /// Sets the edges per rotation of a pulse width sensor.
+    /// Requires Phoenix 2019 or later.
+    #[cfg(feature = "phoenix-2019")]
+    fn config_pulse_width_period_edges_per_rot(
+        &self,
+        edges_per_rot: i32,
+        timeout_ms: i32,
+    ) -> Result<()> {
+        cci_call!(
+            self,
+            c_MotController_ConfigPulseWidthPeriod_EdgesPerRot(
+                self.handle(),
+                edges_per_rot,
+                timeout_ms,
+            )
+        )
+    }
+    /// Sets the number of samples to use in smoothing a pulse width sensor with a rolling average.
+    /// Requires Phoenix 2019 or later.
+    #[cfg(feature = "phoenix-2019")]
+    fn config_pulse_width_period_filter_window_sz(
+        &self,
+        filter_window_sz: i32,
+        timeout_ms: i32,
+    ) -> Result<()> {
+        cci_call!(
+            self,
+            c_MotController_ConfigPulseWidthPeriod_FilterWindowSz(
+                self.handle(),
+                filter_window_sz,
+                timeout_ms,
+            )
+        )
     }
 
-    fn set_control_frame_period(&self, frame: ControlFrame, period_ms: i32) -> ErrorCode {
-        unsafe { c_MotController_SetControlFramePeriod(self.handle(), frame as _, period_ms) }
+    fn set_control_frame_period(&self, frame: ControlFrame, period_ms: i32) -> Result<()> {
+        cci_call!(
+            self,
+            c_MotController_SetControlFramePeriod(self.handle(), frame as _, period_ms)
+        )
     }
     fn set_status_frame_period(
         &self,
         frame: StatusFrame,
         period_ms: i32,
         timeout_ms: i32,
-    ) -> ErrorCode {
-        unsafe {
+    ) -> Result<()> {
+        cci_call!(
+            self,
             c_MotController_SetStatusFramePeriod(self.handle(), frame as _, period_ms, timeout_ms)
-        }
+        )
     }
     fn get_status_frame_period(&self, frame: StatusFrame, timeout_ms: i32) -> Result<i32> {
         cci_get_call!(
+            self,
             c_MotController_GetStatusFramePeriod(self.handle(), frame as _, _: i32, timeout_ms)
         )
     }
@@ -503,18 +1139,19 @@ pub trait BaseMotorController: private::Sealed {
         &self,
         type_: RemoteLimitSwitchSource,
         normal_open_or_close: LimitSwitchNormal,
-        device_id: i32,
+        device_id: DeviceId,
         timeout_ms: i32,
-    ) -> ErrorCode {
-        unsafe {
+    ) -> Result<()> {
+        cci_call!(
+            self,
             c_MotController_ConfigForwardLimitSwitchSource(
                 self.handle(),
                 type_ as _,
                 normal_open_or_close as _,
-                device_id,
+                i32::from(device_id),
                 timeout_ms,
             )
-        }
+        )
     }
     /**
      * Configures the reverse limit switch for a remote source.
@@ -534,186 +1171,550 @@ pub trait BaseMotorController: private::Sealed {
         &self,
         type_: RemoteLimitSwitchSource,
         normal_open_or_close: LimitSwitchNormal,
-        device_id: i32,
+        device_id: DeviceId,
         timeout_ms: i32,
-    ) -> ErrorCode {
-        unsafe {
+    ) -> Result<()> {
+        cci_call!(
+            self,
             c_MotController_ConfigReverseLimitSwitchSource(
                 self.handle(),
                 type_ as _,
                 normal_open_or_close as _,
-                device_id,
+                i32::from(device_id),
                 timeout_ms,
             )
-        }
+        )
+    }
+    /**
+     * Configures the forward limit switch to be the Limit-F pin of `remote`,
+     * a Talon SRX or CANifier.
+     *
+     * * `remote` - Device whose limit switch input to use.
+     * * `normal_open_or_close` - Setting for normally open, normally closed, or disabled.
+     * * `timeout_ms` - Timeout value in ms.
+     *   If nonzero, function will wait for config success and report an error if it times out.
+     *   If zero, no blocking or checking is performed.
+     */
+    fn use_remote_forward_limit<R: RemoteLimitSwitch>(
+        &self,
+        remote: &R,
+        normal_open_or_close: LimitSwitchNormal,
+        timeout_ms: i32,
+    ) -> Result<()> {
+        let (type_, device_id) = remote.remote_limit_switch_source();
+        BaseMotorController::config_forward_limit_switch_source(
+            self,
+            type_,
+            normal_open_or_close,
+            device_id,
+            timeout_ms,
+        )
+    }
+    /**
+     * Configures the reverse limit switch to be the Limit-R pin of `remote`,
+     * a Talon SRX or CANifier.
+     * See [`use_remote_forward_limit`].
+     *
+     * [`use_remote_forward_limit`]: #method.use_remote_forward_limit
+     */
+    fn use_remote_reverse_limit<R: RemoteLimitSwitch>(
+        &self,
+        remote: &R,
+        normal_open_or_close: LimitSwitchNormal,
+        timeout_ms: i32,
+    ) -> Result<()> {
+        let (type_, device_id) = remote.remote_limit_switch_source();
+        BaseMotorController::config_reverse_limit_switch_source(
+            self,
+            type_,
+            normal_open_or_close,
+            device_id,
+            timeout_ms,
+        )
     }
     fn override_limit_switches_enable(&self, enable: bool) {
         unsafe { c_MotController_OverrideLimitSwitchesEnable(self.handle(), enable) }
     }
+    /// Disables limit switches triggering (if enabled) when the sensor is no longer detected.
+    /// Requires Phoenix 2019 or later.
+    #[cfg(feature = "phoenix-2019")]
+    fn config_limit_switch_disable_neutral_on_los(
+        &self,
+        disable: bool,
+        timeout_ms: i32,
+    ) -> Result<()> {
+        cci_call!(
+            self,
+            c_MotController_ConfigLimitSwitchDisableNeutralOnLOS(
+                self.handle(),
+                disable,
+                timeout_ms,
+            )
+        )
+    }
 
     fn config_forward_soft_limit_threshold(
         &self,
         forward_sensor_limit: i32,
         timeout_ms: i32,
-    ) -> ErrorCode {
-        unsafe {
+    ) -> Result<()> {
+        cci_call!(
+            self,
             c_MotController_ConfigForwardSoftLimitThreshold(
                 self.handle(),
                 forward_sensor_limit,
                 timeout_ms,
             )
-        }
+        )
     }
     fn config_reverse_soft_limit_threshold(
         &self,
         reverse_sensor_limit: i32,
         timeout_ms: i32,
-    ) -> ErrorCode {
-        unsafe {
+    ) -> Result<()> {
+        cci_call!(
+            self,
             c_MotController_ConfigReverseSoftLimitThreshold(
                 self.handle(),
                 reverse_sensor_limit,
                 timeout_ms,
             )
-        }
+        )
+    }
+    fn config_forward_soft_limit_enable(&self, enable: bool, timeout_ms: i32) -> Result<()> {
+        cci_call!(
+            self,
+            c_MotController_ConfigForwardSoftLimitEnable(self.handle(), enable, timeout_ms)
+        )
+    }
+    fn config_reverse_soft_limit_enable(&self, enable: bool, timeout_ms: i32) -> Result<()> {
+        cci_call!(
+            self,
+            c_MotController_ConfigReverseSoftLimitEnable(self.handle(), enable, timeout_ms)
+        )
+    }
+    fn get_forward_soft_limit_threshold(&self, timeout_ms: i32) -> Result<i32> {
+        let param = ParamEnum::ForwardSoftLimitThreshold;
+        Ok(self.config_get_parameter(param, 0, timeout_ms)? as i32)
+    }
+    fn get_reverse_soft_limit_threshold(&self, timeout_ms: i32) -> Result<i32> {
+        let param = ParamEnum::ReverseSoftLimitThreshold;
+        Ok(self.config_get_parameter(param, 0, timeout_ms)? as i32)
     }
-    fn config_forward_soft_limit_enable(&self, enable: bool, timeout_ms: i32) -> ErrorCode {
-        unsafe { c_MotController_ConfigForwardSoftLimitEnable(self.handle(), enable, timeout_ms) }
+    fn get_forward_soft_limit_enable(&self, timeout_ms: i32) -> Result<bool> {
+        let param = ParamEnum::ForwardSoftLimitEnable;
+        Ok(self.config_get_parameter(param, 0, timeout_ms)? != 0.)
     }
-    fn config_reverse_soft_limit_enable(&self, enable: bool, timeout_ms: i32) -> ErrorCode {
-        unsafe { c_MotController_ConfigReverseSoftLimitEnable(self.handle(), enable, timeout_ms) }
+    fn get_reverse_soft_limit_enable(&self, timeout_ms: i32) -> Result<bool> {
+        let param = ParamEnum::ReverseSoftLimitEnable;
+        Ok(self.config_get_parameter(param, 0, timeout_ms)? != 0.)
     }
     fn override_soft_limits_enable(&self, enable: bool) {
         unsafe { c_MotController_OverrideSoftLimitsEnable(self.handle(), enable) }
     }
+    /// Disables soft limits triggering (if enabled) when the sensor is no longer detected.
+    /// Requires Phoenix 2019 or later.
+    #[cfg(feature = "phoenix-2019")]
+    fn config_soft_limit_disable_neutral_on_los(
+        &self,
+        disable: bool,
+        timeout_ms: i32,
+    ) -> Result<()> {
+        cci_call!(
+            self,
+            c_MotController_ConfigSoftLimitDisableNeutralOnLOS(self.handle(), disable, timeout_ms)
+        )
+    }
 
     // current limiting is Talon-specific
 
-    fn config_kp(&self, slot_idx: i32, value: f64, timeout_ms: i32) -> ErrorCode {
-        unsafe { c_MotController_Config_kP(self.handle(), slot_idx, value, timeout_ms) }
+    fn config_kp(&self, slot_idx: impl TryInto<Slot>, value: f64, timeout_ms: i32) -> Result<()> {
+        let slot_idx = cci_index!(self, c_MotController_Config_kP, slot_idx: Slot)? as i32;
+        cci_call!(
+            self,
+            c_MotController_Config_kP(self.handle(), slot_idx, value, timeout_ms)
+        )
     }
-    fn config_ki(&self, slot_idx: i32, value: f64, timeout_ms: i32) -> ErrorCode {
-        unsafe { c_MotController_Config_kI(self.handle(), slot_idx, value, timeout_ms) }
+    fn config_ki(&self, slot_idx: impl TryInto<Slot>, value: f64, timeout_ms: i32) -> Result<()> {
+        let slot_idx = cci_index!(self, c_MotController_Config_kI, slot_idx: Slot)? as i32;
+        cci_call!(
+            self,
+            c_MotController_Config_kI(self.handle(), slot_idx, value, timeout_ms)
+        )
     }
-    fn config_kd(&self, slot_idx: i32, value: f64, timeout_ms: i32) -> ErrorCode {
-        unsafe { c_MotController_Config_kD(self.handle(), slot_idx, value, timeout_ms) }
+    fn config_kd(&self, slot_idx: impl TryInto<Slot>, value: f64, timeout_ms: i32) -> Result<()> {
+        let slot_idx = cci_index!(self, c_MotController_Config_kD, slot_idx: Slot)? as i32;
+        cci_call!(
+            self,
+            c_MotController_Config_kD(self.handle(), slot_idx, value, timeout_ms)
+        )
     }
-    fn config_kf(&self, slot_idx: i32, value: f64, timeout_ms: i32) -> ErrorCode {
-        unsafe { c_MotController_Config_kF(self.handle(), slot_idx, value, timeout_ms) }
+    fn config_kf(&self, slot_idx: impl TryInto<Slot>, value: f64, timeout_ms: i32) -> Result<()> {
+        let slot_idx = cci_index!(self, c_MotController_Config_kF, slot_idx: Slot)? as i32;
+        cci_call!(
+            self,
+            c_MotController_Config_kF(self.handle(), slot_idx, value, timeout_ms)
+        )
     }
-    fn config_integral_zone(&self, slot_idx: i32, izone: i32, timeout_ms: i32) -> ErrorCode {
-        unsafe {
+    fn config_integral_zone(
+        &self,
+        slot_idx: impl TryInto<Slot>,
+        izone: i32,
+        timeout_ms: i32,
+    ) -> Result<()> {
+        let slot_idx =
+            cci_index!(self, c_MotController_Config_IntegralZone, slot_idx: Slot)? as i32;
+        cci_call!(
+            self,
             c_MotController_Config_IntegralZone(
                 self.handle(),
                 slot_idx,
                 izone as f64, // idek both C++ and Java do this too
                 timeout_ms,
             )
-        }
+        )
     }
     fn config_allowable_closedloop_error(
         &self,
-        slot_idx: i32,
+        slot_idx: impl TryInto<Slot>,
         allowable_closed_loop_error: i32,
         timeout_ms: i32,
-    ) -> ErrorCode {
-        unsafe {
+    ) -> Result<()> {
+        let slot_idx = cci_index!(self, c_MotController_ConfigAllowableClosedloopError, slot_idx: Slot)?
+            as i32;
+        cci_call!(
+            self,
             c_MotController_ConfigAllowableClosedloopError(
                 self.handle(),
                 slot_idx,
                 allowable_closed_loop_error,
                 timeout_ms,
             )
-        }
+        )
     }
     fn config_max_integral_accumulator(
         &self,
-        slot_idx: i32,
+        slot_idx: impl TryInto<Slot>,
         iaccum: f64,
         timeout_ms: i32,
-    ) -> ErrorCode {
-        unsafe {
+    ) -> Result<()> {
+        let slot_idx =
+            cci_index!(self, c_MotController_ConfigMaxIntegralAccumulator, slot_idx: Slot)? as i32;
+        cci_call!(
+            self,
             c_MotController_ConfigMaxIntegralAccumulator(
                 self.handle(),
                 slot_idx,
                 iaccum,
                 timeout_ms,
             )
-        }
+        )
     }
     fn config_closed_loop_peak_output(
         &self,
-        slot_idx: i32,
+        slot_idx: impl TryInto<Slot>,
         percent_out: f64,
         timeout_ms: i32,
-    ) -> ErrorCode {
-        unsafe {
+    ) -> Result<()> {
+        let slot_idx =
+            cci_index!(self, c_MotController_ConfigClosedLoopPeakOutput, slot_idx: Slot)? as i32;
+        cci_call!(
+            self,
             c_MotController_ConfigClosedLoopPeakOutput(
                 self.handle(),
                 slot_idx,
                 percent_out,
                 timeout_ms,
             )
-        }
+        )
     }
     fn config_closed_loop_period(
         &self,
-        slot_idx: i32,
+        slot_idx: impl TryInto<Slot>,
         loop_time_ms: i32,
         timeout_ms: i32,
-    ) -> ErrorCode {
-        unsafe {
+    ) -> Result<()> {
+        let slot_idx =
+            cci_index!(self, c_MotController_ConfigClosedLoopPeriod, slot_idx: Slot)? as i32;
+        cci_call!(
+            self,
             c_MotController_ConfigClosedLoopPeriod(
                 self.handle(),
                 slot_idx,
                 loop_time_ms,
                 timeout_ms,
             )
-        }
+        )
     }
-    fn config_aux_pid_polarity(&self, invert: bool, timeout_ms: i32) -> ErrorCode {
-        self.config_set_parameter(
-            ParamEnum::PIDLoopPolarity,
-            invert as i8 as f64,
-            0,
+    /**
+     * Copies all of a slot's closed-loop settings to another slot: everything
+     * in `Gains`.
+     * Each value is read back from the controller, so this blocks for up to
+     * `timeout_ms` per setting.
+     */
+    fn copy_gains(&self, from_slot: Slot, to_slot: Slot, timeout_ms: i32) -> Result<()> {
+        let gains = self.get_gains(from_slot, timeout_ms)?;
+        self.config_gains(to_slot, &gains, timeout_ms)
+    }
+    /// Configures every setting of a gain slot.
+    fn config_gains(
+        &self,
+        slot_idx: impl TryInto<Slot>,
+        gains: &Gains,
+        timeout_ms: i32,
+    ) -> Result<()> {
+        let slot_idx = cci_index!(self, config_gains, slot_idx: Slot)?;
+        self.config_kp(slot_idx, gains.kp, timeout_ms)?;
+        self.config_ki(slot_idx, gains.ki, timeout_ms)?;
+        self.config_kd(slot_idx, gains.kd, timeout_ms)?;
+        self.config_kf(slot_idx, gains.kf, timeout_ms)?;
+        self.config_integral_zone(slot_idx, gains.integral_zone, timeout_ms)?;
+        let error = gains.allowable_closedloop_error;
+        self.config_allowable_closedloop_error(slot_idx, error, timeout_ms)?;
+        let iaccum = gains.max_integral_accumulator;
+        self.config_max_integral_accumulator(slot_idx, iaccum, timeout_ms)?;
+        let peak_output = gains.closed_loop_peak_output;
+        self.config_closed_loop_peak_output(slot_idx, peak_output, timeout_ms)?;
+        self.config_closed_loop_period(slot_idx, gains.closed_loop_period_ms, timeout_ms)
+    }
+    /// Reads every setting of a gain slot.
+    fn get_gains(&self, slot_idx: impl TryInto<Slot>, timeout_ms: i32) -> Result<Gains> {
+        let slot_idx = cci_index!(self, get_gains, slot_idx: Slot)? as i32;
+        let get = |param| self.config_get_parameter(param, slot_idx, timeout_ms);
+        Ok(Gains {
+            kp: get(ParamEnum::ProfileParamSlot_P)?,
+            ki: get(ParamEnum::ProfileParamSlot_I)?,
+            kd: get(ParamEnum::ProfileParamSlot_D)?,
+            kf: get(ParamEnum::ProfileParamSlot_F)?,
+            integral_zone: get(ParamEnum::ProfileParamSlot_IZone)? as i32,
+            allowable_closedloop_error: get(ParamEnum::ProfileParamSlot_AllowableErr)? as i32,
+            max_integral_accumulator: get(ParamEnum::ProfileParamSlot_MaxIAccum)?,
+            closed_loop_peak_output: get(ParamEnum::ProfileParamSlot_PeakOutput)?,
+            closed_loop_period_ms: get(ParamEnum::PIDLoopPeriod)? as i32,
+        })
+    }
+    /**
+     * Multiplies a slot's kP, kI, kD and kF by `factor`.
+     *
+     * Settings in sensor units (integral zone, allowable error) are left alone.
+     * For example, after changing the feedback coefficient by `c`, scale the
+     * gains by `1 / c` to get the same output for the same physical error.
+     */
+    fn scale_gains(
+        &self,
+        slot_idx: impl TryInto<Slot>,
+        factor: f64,
+        timeout_ms: i32,
+    ) -> Result<()> {
+        let slot_idx = cci_index!(self, scale_gains, slot_idx: Slot)? as i32;
+        for &param in GAIN_PARAMS {
+            let value = self.config_get_parameter(param, slot_idx, timeout_ms)?;
+            self.config_set_parameter(param, value * factor, 0, slot_idx, timeout_ms)?;
+        }
+        Ok(())
+    }
+    /**
+     * Estimates kF with a bump test: runs the motor at `percent_output` for
+     * `settle_time`, averages the selected sensor's velocity over the next
+     * 100ms, and returns `percent_output * 1023 / velocity`.  With `slot_idx`,
+     * also writes the estimate to that slot's kF.
+     *
+     * The motor is free to move while this runs, and is neutral-output after,
+     * even if a read fails.  The velocity is read from the primary loop's
+     * sensor, so select and phase it first.  Fails with `SensorNotPresent` if
+     * the velocity is zero or its sign disagrees with `percent_output`, as
+     * with a sensor out of phase.
+     */
+    fn estimate_kf(
+        &self,
+        slot_idx: Option<Slot>,
+        percent_output: f64,
+        settle_time: Duration,
+        timeout_ms: i32,
+    ) -> Result<f64> {
+        const SAMPLES: i32 = 10;
+        self.set(
+            ControlMode::PercentOutput,
+            percent_output,
+            DemandType::Neutral,
+            0.,
+        );
+        clock::sleep(settle_time);
+        let mut total = 0.;
+        let mut result = Ok(());
+        for _ in 0..SAMPLES {
+            match self.get_selected_sensor_velocity(0) {
+                Ok(velocity) => total += f64::from(velocity),
+                Err(err) => {
+                    result = Err(err);
+                    break;
+                }
+            }
+            clock::sleep(Duration::from_millis(10));
+        }
+        self.neutral_output();
+        result?;
+        let velocity = total / f64::from(SAMPLES);
+        let kf = percent_output * 1023. / velocity;
+        if !(kf.is_finite() && kf > 0.) {
+            return Err(cci_error!(self, estimate_kf, ErrorCode::SensorNotPresent));
+        }
+        if let Some(slot_idx) = slot_idx {
+            self.config_kf(slot_idx, kf, timeout_ms)?;
+        }
+        Ok(kf)
+    }
+    fn config_aux_pid_polarity(&self, invert: bool, timeout_ms: i32) -> Result<()> {
+        self.config_set_parameter(
+            ParamEnum::PIDLoopPolarity,
+            invert as i8 as f64,
+            0,
             1,
             timeout_ms,
         )
     }
-    fn set_integral_accumulator(&self, iaccum: f64, pid_idx: i32, timeout_ms: i32) -> ErrorCode {
-        unsafe {
+    fn set_integral_accumulator(
+        &self,
+        iaccum: f64,
+        pid_idx: impl TryInto<PidLoop>,
+        timeout_ms: i32,
+    ) -> Result<()> {
+        let pid_idx =
+            cci_index!(self, c_MotController_SetIntegralAccumulator, pid_idx: PidLoop)? as i32;
+        cci_call!(
+            self,
             c_MotController_SetIntegralAccumulator(self.handle(), iaccum, pid_idx, timeout_ms)
+        )
+    }
+    fn get_closed_loop_error(&self, pid_idx: impl TryInto<PidLoop>) -> Result<i32> {
+        let pid_idx =
+            cci_index!(self, c_MotController_GetClosedLoopError, pid_idx: PidLoop)? as i32;
+        cci_get_call!(self, c_MotController_GetClosedLoopError(self.handle(), _: i32, pid_idx))
+    }
+    /**
+     * Whether a closed loop has stayed within `tolerance` of its target for at
+     * least `debounce`, e.g. to move on to the next step of a sequence.
+     *
+     * Both the closed-loop error and the sensor velocity must be within
+     * tolerance.  Call this periodically; the debounce timer only runs between
+     * calls that find the loop within tolerance.
+     */
+    fn at_setpoint(
+        &self,
+        pid_idx: impl TryInto<PidLoop>,
+        tolerance: SetpointTolerance,
+        debounce: Duration,
+    ) -> Result<bool> {
+        let pid_idx = cci_index!(self, at_setpoint, pid_idx: PidLoop)? as i32;
+        let error = self.get_closed_loop_error(pid_idx)?;
+        let velocity = self.get_selected_sensor_velocity(pid_idx)?;
+        let on_target = &private::Sealed::state(self).on_target[pid_idx as usize];
+        if error.abs() > tolerance.error || velocity.abs() > tolerance.velocity {
+            on_target.clear();
+            return Ok(false);
+        }
+        match on_target.age() {
+            Some(age) => Ok(age >= debounce),
+            None => {
+                on_target.mark();
+                Ok(debounce == Duration::from_secs(0))
+            }
         }
     }
-    fn get_closed_loop_error(&self, pid_idx: i32) -> Result<i32> {
-        cci_get_call!(c_MotController_GetClosedLoopError(self.handle(), _: i32, pid_idx))
+    /**
+     * `at_setpoint` with tolerances in rotations and rotations per second.
+     *
+     * * `units` - Resolution of the loop's sensor, after any feedback coefficient.
+     */
+    fn at_setpoint_rotations(
+        &self,
+        pid_idx: impl TryInto<PidLoop>,
+        rotations: f64,
+        rps: f64,
+        debounce: Duration,
+        units: SensorUnits,
+    ) -> Result<bool> {
+        let tolerance = SetpointTolerance {
+            error: units.rotations_to_units(rotations) as i32,
+            velocity: units.rps_to_velocity(rps) as i32,
+        };
+        self.at_setpoint(pid_idx, tolerance, debounce)
     }
-    fn get_integral_accumulator(&self, pid_idx: i32) -> Result<f64> {
-        cci_get_call!(c_MotController_GetIntegralAccumulator(self.handle(), _: f64, pid_idx))
+    fn get_integral_accumulator(&self, pid_idx: impl TryInto<PidLoop>) -> Result<f64> {
+        let pid_idx =
+            cci_index!(self, c_MotController_GetIntegralAccumulator, pid_idx: PidLoop)? as i32;
+        cci_get_call!(self, c_MotController_GetIntegralAccumulator(self.handle(), _: f64, pid_idx))
     }
     /// Gets the derivative of the closed-loop error.
-    fn get_error_derivative(&self, pid_idx: i32) -> Result<f64> {
-        cci_get_call!(c_MotController_GetErrorDerivative(self.handle(), _: f64, pid_idx))
+    fn get_error_derivative(&self, pid_idx: impl TryInto<PidLoop>) -> Result<f64> {
+        let pid_idx =
+            cci_index!(self, c_MotController_GetErrorDerivative, pid_idx: PidLoop)? as i32;
+        cci_get_call!(self, c_MotController_GetErrorDerivative(self.handle(), _: f64, pid_idx))
     }
     /// Selects which profile slot to use for closed-loop control.
-    fn select_profile_slot(&self, slot_idx: i32, pid_idx: i32) -> ErrorCode {
-        unsafe { c_MotController_SelectProfileSlot(self.handle(), slot_idx, pid_idx) }
+    fn select_profile_slot(
+        &self,
+        slot_idx: impl TryInto<Slot>,
+        pid_idx: impl TryInto<PidLoop>,
+    ) -> Result<()> {
+        let slot_idx = cci_index!(self, c_MotController_SelectProfileSlot, slot_idx: Slot)? as i32;
+        let pid_idx = cci_index!(self, c_MotController_SelectProfileSlot, pid_idx: PidLoop)? as i32;
+        let result = cci_call!(
+            self,
+            c_MotController_SelectProfileSlot(self.handle(), slot_idx, pid_idx)
+        );
+        let selected = if result.is_ok() { Some(slot_idx) } else { None };
+        private::Sealed::state(self)
+            .selected_slots
+            .set(pid_idx, selected);
+        result
     }
-    fn get_closed_loop_target(&self, pid_idx: i32) -> Result<i32> {
-        cci_get_call!(c_MotController_GetClosedLoopTarget(self.handle(), _: i32, pid_idx))
+    /**
+     * The gain slot last selected for a loop with `select_profile_slot`, or
+     * `None` if there hasn't been a successful selection since the controller
+     * was created or last reported a reset.  This is tracked locally; the
+     * controller isn't asked.
+     */
+    fn selected_profile_slot(&self, pid_idx: impl TryInto<PidLoop>) -> Option<Slot> {
+        let pid_idx = pid_idx.try_into().ok()? as i32;
+        private::Sealed::state(self)
+            .selected_slots
+            .get(pid_idx)
+            .and_then(|slot_idx| Slot::try_from(slot_idx).ok())
+    }
+    fn get_closed_loop_target(&self, pid_idx: impl TryInto<PidLoop>) -> Result<i32> {
+        let pid_idx =
+            cci_index!(self, c_MotController_GetClosedLoopTarget, pid_idx: PidLoop)? as i32;
+        cci_get_call!(self, c_MotController_GetClosedLoopTarget(self.handle(), _: i32, pid_idx))
+    }
+    /**
+     * Gets the target, error, integral accumulator and error derivative of a
+     * closed loop together, along with the motor output, for logging.
+     *
+     * * `pid_idx` - 0 for Primary closed-loop. 1 for auxiliary closed-loop.
+     */
+    fn get_closed_loop_state(&self, pid_idx: impl TryInto<PidLoop>) -> Result<ClosedLoopState> {
+        let pid_idx = cci_index!(self, get_closed_loop_state, pid_idx: PidLoop)? as i32;
+        Ok(ClosedLoopState {
+            target: self.get_closed_loop_target(pid_idx)?,
+            error: self.get_closed_loop_error(pid_idx)?,
+            iaccum: self.get_integral_accumulator(pid_idx)?,
+            derr: self.get_error_derivative(pid_idx)?,
+            output: self.get_motor_output_percent()?,
+        })
     }
 
     /// Gets the active trajectory target position using MotionMagic/MotionProfile control modes.
     fn get_active_trajectory_position(&self) -> Result<i32> {
-        cci_get_call!(c_MotController_GetActiveTrajectoryPosition(self.handle(), _: i32))
+        cci_get_call!(self, c_MotController_GetActiveTrajectoryPosition(self.handle(), _: i32))
     }
     /// Gets the active trajectory target velocity using MotionMagic/MotionProfile control modes.
     fn get_active_trajectory_velocity(&self) -> Result<i32> {
-        cci_get_call!(c_MotController_GetActiveTrajectoryVelocity(self.handle(), _: i32))
+        cci_get_call!(self, c_MotController_GetActiveTrajectoryVelocity(self.handle(), _: i32))
     }
     /// Gets the active trajectory target heading using MotionMagic/MotionProfile control modes.
     fn get_active_trajectory_heading(&self) -> Result<f64> {
-        cci_get_call!(c_MotController_GetActiveTrajectoryHeading(self.handle(), _: f64))
+        cci_get_call!(self, c_MotController_GetActiveTrajectoryHeading(self.handle(), _: f64))
     }
 
     /// Sets the Motion Magic Cruise Velocity.
@@ -722,14 +1723,15 @@ pub trait BaseMotorController: private::Sealed {
         &self,
         sensor_units_per_100ms: i32,
         timeout_ms: i32,
-    ) -> ErrorCode {
-        unsafe {
+    ) -> Result<()> {
+        cci_call!(
+            self,
             c_MotController_ConfigMotionCruiseVelocity(
                 self.handle(),
                 sensor_units_per_100ms,
                 timeout_ms,
             )
-        }
+        )
     }
     /// Sets the Motion Magic Acceleration.
     /// This is the target acceleration that the motion magic curve generator can use.
@@ -737,20 +1739,54 @@ pub trait BaseMotorController: private::Sealed {
         &self,
         sensor_units_per_100ms_per_sec: i32,
         timeout_ms: i32,
-    ) -> ErrorCode {
-        unsafe {
+    ) -> Result<()> {
+        cci_call!(
+            self,
             c_MotController_ConfigMotionAcceleration(
                 self.handle(),
                 sensor_units_per_100ms_per_sec,
                 timeout_ms,
             )
-        }
+        )
+    }
+    /// Reads the Motion Magic Cruise Velocity, in sensor units per 100ms.
+    fn get_motion_cruise_velocity(&self, timeout_ms: i32) -> Result<i32> {
+        Ok(self.config_get_parameter(ParamEnum::MotMag_VelCruise, 0, timeout_ms)? as i32)
+    }
+    /// Reads the Motion Magic Acceleration, in sensor units per 100ms per second.
+    fn get_motion_acceleration(&self, timeout_ms: i32) -> Result<i32> {
+        Ok(self.config_get_parameter(ParamEnum::MotMag_Accel, 0, timeout_ms)? as i32)
+    }
+    /**
+     * Sets the Motion Magic S-Curve Strength.
+     * Call this before using Motion Magic.
+     * Modifying this during a Motion Magic action should be avoided.
+     *
+     * * `curve_strength` - 0 to use Trapezoidal Motion Profile.
+     *   [1,8] for S-Curve (greater value yields greater smoothing).
+     *
+     * Requires Phoenix 2020 or later.
+     */
+    #[cfg(feature = "phoenix-2020")]
+    fn config_motion_s_curve_strength(&self, curve_strength: i32, timeout_ms: i32) -> Result<()> {
+        cci_call!(
+            self,
+            c_MotController_ConfigMotionSCurveStrength(self.handle(), curve_strength, timeout_ms)
+        )
+    }
+    /// Reads the Motion Magic S-Curve Strength.  Requires Phoenix 2020 or later.
+    #[cfg(feature = "phoenix-2020")]
+    fn get_motion_s_curve_strength(&self, timeout_ms: i32) -> Result<i32> {
+        Ok(self.config_get_parameter(ParamEnum::MotMag_SCurveLevel, 0, timeout_ms)? as i32)
     }
 
     /// Clear the buffered motion profile in both motor controller's RAM (bottom),
     /// and in the API (top).
-    fn clear_motion_profile_trajectories(&self) -> ErrorCode {
-        unsafe { c_MotController_ClearMotionProfileTrajectories(self.handle()) }
+    fn clear_motion_profile_trajectories(&self) -> Result<()> {
+        cci_call!(
+            self,
+            c_MotController_ClearMotionProfileTrajectories(self.handle())
+        )
     }
     /**
      * Retrieve just the buffer count for the api-level (top) buffer.
@@ -761,12 +1797,16 @@ pub trait BaseMotorController: private::Sealed {
      * [`get_motion_profile_status`]: #method.get_motion_profile_status
      */
     fn get_motion_profile_top_level_buffer_count(&self) -> Result<i32> {
-        cci_get_call!(c_MotController_GetMotionProfileTopLevelBufferCount(self.handle(), _: i32))
+        cci_get_call!(
+            self,
+            c_MotController_GetMotionProfileTopLevelBufferCount(self.handle(), _: i32)
+        )
     }
     /// Push another trajectory point into the top level buffer (which is emptied
     /// into the motor controller's bottom buffer as room allows).
-    fn push_motion_profile_trajectory(&self, traj_pt: &TrajectoryPoint) -> ErrorCode {
-        unsafe {
+    fn push_motion_profile_trajectory(&self, traj_pt: &TrajectoryPoint) -> Result<()> {
+        cci_call!(
+            self,
             c_MotController_PushMotionProfileTrajectory_2(
                 self.handle(),
                 traj_pt.position,
@@ -778,7 +1818,7 @@ pub trait BaseMotorController: private::Sealed {
                 traj_pt.zero_pos,
                 traj_pt.time_dur as _,
             )
-        }
+        )
     }
     /**
      * Retrieve just the buffer full for the api-level (top) buffer.
@@ -788,7 +1828,10 @@ pub trait BaseMotorController: private::Sealed {
      * [`get_motion_profile_status`]: #method.get_motion_profile_status
      */
     fn is_motion_profile_top_level_buffer_full(&self) -> Result<bool> {
-        cci_get_call!(c_MotController_IsMotionProfileTopLevelBufferFull(self.handle(), _: bool))
+        cci_get_call!(
+            self,
+            c_MotController_IsMotionProfileTopLevelBufferFull(self.handle(), _: bool)
+        )
     }
     /**
      * This must be called periodically to funnel the trajectory points from the
@@ -806,7 +1849,7 @@ pub trait BaseMotorController: private::Sealed {
      * For best performance, Caller can snapshot all status information regarding the
      * motion profile executer.
      */
-    fn get_motion_profile_status(&self, status_to_fill: &mut MotionProfileStatus) -> ErrorCode {
+    fn get_motion_profile_status(&self, status_to_fill: &mut MotionProfileStatus) -> Result<()> {
         let mut output_enable: ::std::os::raw::c_int = 0;
         let code = unsafe {
             c_MotController_GetMotionProfileStatus_2(
@@ -825,22 +1868,32 @@ pub trait BaseMotorController: private::Sealed {
             )
         };
         status_to_fill.output_enable = output_enable.into();
-        code
+        match code {
+            ErrorCode::OK => {
+                self.frame_received();
+                Ok(())
+            }
+            _ => Err(cci_error!(
+                self,
+                c_MotController_GetMotionProfileStatus_2,
+                code
+            )),
+        }
     }
     /// Get all motion profile status information.  This returns a new MotionProfileStatus.
     /// See `get_motion_profile_status`.
     fn get_new_motion_profile_status(&self) -> Result<MotionProfileStatus> {
         let mut status_to_fill: MotionProfileStatus = Default::default();
-        let code = self.get_motion_profile_status(&mut status_to_fill);
-        match code {
-            ErrorCode::OK => Ok(status_to_fill),
-            _ => Err(code),
-        }
+        self.get_motion_profile_status(&mut status_to_fill)?;
+        Ok(status_to_fill)
     }
     /// Clear the "Has Underrun" flag.
     /// Typically this is called after application has confirmed an underrun had occured.
-    fn clear_motion_profile_has_underrun(&self, timeout_ms: i32) -> ErrorCode {
-        unsafe { c_MotController_ClearMotionProfileHasUnderrun(self.handle(), timeout_ms) }
+    fn clear_motion_profile_has_underrun(&self, timeout_ms: i32) -> Result<()> {
+        cci_call!(
+            self,
+            c_MotController_ClearMotionProfileHasUnderrun(self.handle(), timeout_ms)
+        )
     }
     /**
      * Calling application can opt to speed up the handshaking between the robot API
@@ -848,8 +1901,11 @@ pub trait BaseMotorController: private::Sealed {
      * Ideally the period should be no more than half the period of a trajectory
      * point.
      */
-    fn change_motion_control_frame_period(&self, period_ms: i32) -> ErrorCode {
-        unsafe { c_MotController_ChangeMotionControlFramePeriod(self.handle(), period_ms) }
+    fn change_motion_control_frame_period(&self, period_ms: i32) -> Result<()> {
+        cci_call!(
+            self,
+            c_MotController_ChangeMotionControlFramePeriod(self.handle(), period_ms)
+        )
     }
     /**
      * When trajectory points are processed in the motion profile executer, the MPE determines
@@ -871,14 +1927,15 @@ pub trait BaseMotorController: private::Sealed {
         &self,
         base_traj_duration_ms: i32,
         timeout_ms: i32,
-    ) -> ErrorCode {
-        unsafe {
+    ) -> Result<()> {
+        cci_call!(
+            self,
             c_MotController_ConfigMotionProfileTrajectoryPeriod(
                 self.handle(),
                 base_traj_duration_ms,
                 timeout_ms,
             )
-        }
+        )
     }
 
     /**
@@ -889,32 +1946,58 @@ pub trait BaseMotorController: private::Sealed {
     fn get_last_error(&self) -> ErrorCode {
         unsafe { c_MotController_GetLastError(self.handle()) }
     }
+    /// The last few errors returned by calls on this object, oldest first.
+    /// Unlike `get_last_error`, each entry is tied to the call that failed.
+    fn recent_errors(&self) -> Vec<ErrorRecord> {
+        self.state().errors.to_vec()
+    }
+    /// Like `recent_errors`, but reuses `records`' allocation.
+    /// `records` is cleared first; it never needs more than 16 entries.
+    fn recent_errors_into(&self, records: &mut Vec<ErrorRecord>) {
+        self.state().errors.copy_into(records)
+    }
+    /// How many calls on this object have failed, by kind of error.
+    fn error_stats(&self) -> ErrorStats {
+        self.state().error_counts.stats()
+    }
 
     fn get_faults(&self) -> Result<Faults> {
         Ok(Faults(
-            cci_get_call!(c_MotController_GetFaults(self.handle(), _: i32))?,
+            cci_get_call!(self, c_MotController_GetFaults(self.handle(), _: i32))?,
         ))
     }
     fn get_sticky_faults(&self) -> Result<StickyFaults> {
         Ok(StickyFaults(
-            cci_get_call!(c_MotController_GetStickyFaults(self.handle(), _: i32))?,
+            cci_get_call!(self, c_MotController_GetStickyFaults(self.handle(), _: i32))?,
         ))
     }
-    fn clear_sticky_faults(&self, timeout_ms: i32) -> ErrorCode {
-        unsafe { c_MotController_ClearStickyFaults(self.handle(), timeout_ms) }
+    fn clear_sticky_faults(&self, timeout_ms: i32) -> Result<()> {
+        cci_call!(
+            self,
+            c_MotController_ClearStickyFaults(self.handle(), timeout_ms)
+        )
     }
 
     /**
      * Gets the firmware version of the device.
      *
-     * @return Firmware version of device.  For example: version 1-dot-2 is 0x0102.
+     * @return Firmware version of device.
      */
-    fn get_firmware_version(&self) -> Result<i32> {
-        cci_get_call!(c_MotController_GetFirmwareVersion(self.handle(), _: i32))
+    fn get_firmware_version(&self) -> Result<FirmwareVersion> {
+        cci_get_call!(self, c_MotController_GetFirmwareVersion(self.handle(), _: i32))
+            .map(FirmwareVersion::from_raw)
     }
     /// Returns true if the device has reset since last call.
+    /// A reset also forgets the slots tracked by `selected_profile_slot`.
     fn has_reset_occurred(&self) -> Result<bool> {
-        cci_get_call!(c_MotController_HasResetOccurred(self.handle(), _: bool))
+        let reset = cci_get_call!(self, c_MotController_HasResetOccurred(self.handle(), _: bool))?;
+        if reset {
+            let slots = &private::Sealed::state(self).selected_slots;
+            slots.set(0, None);
+            slots.set(1, None);
+            events().publish(DeviceEvent::Reset(self.device_info()));
+        }
+        Ok(reset)
     }
 
     /**
@@ -935,10 +2018,11 @@ pub trait BaseMotorController: private::Sealed {
         new_value: i32,
         param_index: i32,
         timeout_ms: i32,
-    ) -> ErrorCode {
-        unsafe {
+    ) -> Result<()> {
+        cci_call!(
+            self,
             c_MotController_ConfigSetCustomParam(self.handle(), new_value, param_index, timeout_ms)
-        }
+        )
     }
     /**
      * Gets the value of a custom parameter.
@@ -950,6 +2034,7 @@ pub trait BaseMotorController: private::Sealed {
      */
     fn config_get_custom_param(&self, param_index: i32, timout_ms: i32) -> Result<i32> {
         cci_get_call!(
+            self,
             c_MotController_ConfigGetCustomParam(self.handle(), _: i32, param_index, timout_ms)
         )
     }
@@ -968,8 +2053,22 @@ pub trait BaseMotorController: private::Sealed {
         sub_value: u8,
         ordinal: i32,
         timeout_ms: i32,
-    ) -> ErrorCode {
-        unsafe {
+    ) -> Result<()> {
+        if let ParamEnum::FeedbackSensorType | ParamEnum::SensorTerm = param {
+            let supported = self.supported_feedback_devices();
+            if !supported
+                .iter()
+                .any(|&device| device as i32 as f64 == value)
+            {
+                return Err(cci_error!(
+                    self,
+                    c_MotController_ConfigSetParameter,
+                    ErrorCode::FeatureNotSupported
+                ));
+            }
+        }
+        cci_call!(
+            self,
             c_MotController_ConfigSetParameter(
                 self.handle(),
                 param as _,
@@ -978,10 +2077,10 @@ pub trait BaseMotorController: private::Sealed {
                 ordinal,
                 timeout_ms,
             )
-        }
+        )
     }
     fn config_get_parameter(&self, param: ParamEnum, ordinal: i32, timeout_ms: i32) -> Result<f64> {
-        cci_get_call!(c_MotController_ConfigGetParameter(
+        cci_get_call!(self, c_MotController_ConfigGetParameter(
             self.handle(),
             param as _,
             _: f64,
@@ -989,20 +2088,179 @@ pub trait BaseMotorController: private::Sealed {
             timeout_ms,
         ))
     }
+    /**
+     * Reads several parameters at once, for diagnostics such as full config dumps.
+     *
+     * Each read blocks for up to `timeout_ms`, so rather than reading them in
+     * turn, the reads are spread across a few threads.
+     *
+     * * `params` - Parameters to read.
+     * * `ordinal` - Ordinal passed to every read.
+     * * `timeout_ms` - Timeout for each read.
+     */
+    fn bulk_get_parameters(
+        &self,
+        params: &[ParamEnum],
+        ordinal: i32,
+        timeout_ms: i32,
+    ) -> HashMap<ParamEnum, Result<f64>>
+    where
+        Self: Sync,
+    {
+        const THREADS: usize = 4;
+        let chunk_len = params.len().div_ceil(THREADS).max(1);
+        thread::scope(|scope| {
+            let threads: Vec<_> = params
+                .chunks(chunk_len)
+                .map(|chunk| {
+                    scope.spawn(move || {
+                        chunk
+                            .iter()
+                            .map(|&param| {
+                                (param, self.config_get_parameter(param, ordinal, timeout_ms))
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+            threads
+                .into_iter()
+                .flat_map(|thread| thread.join().unwrap())
+                .collect()
+        })
+    }
+    /**
+     * Revert all configurations to factory default values.
+     * Use this before your individual config* calls to avoid having to config every single param.
+     *
+     * Requires Phoenix 2019 or later.
+     */
+    #[cfg(feature = "phoenix-2019")]
+    fn config_factory_default(&self, timeout_ms: i32) -> Result<()> {
+        cci_call!(
+            self,
+            c_MotController_ConfigFactoryDefault(self.handle(), timeout_ms)
+        )
+    }
+    /**
+     * Reverts to factory defaults, except for the parameters in `keep`, e.g. to
+     * clear unknown state while keeping a robot's calibration.  The device ID
+     * isn't a parameter and is never reset.
+     *
+     * * `keep` - Groups of parameters to read before the reset and restore after.
+     * * `timeout_ms` - Timeout value in ms, for each call.
+     *
+     * If any kept parameter can't be read, nothing is reset.  Otherwise every
+     * kept parameter is restored even if some fail, and the failures are
+     * returned together.  Settings recorded for `rebind` are replaced by the
+     * restored parameters.
+     *
+     * Requires Phoenix 2019 or later.
+     */
+    #[cfg(feature = "phoenix-2019")]
+    fn reset_and_restore(
+        &self,
+        keep: &[ParamGroup],
+        timeout_ms: i32,
+    ) -> ::std::result::Result<(), ConfigErrors> {
+        let mut kept = Vec::new();
+        for group in keep {
+            for &(param, ordinals) in group.params() {
+                for &ordinal in ordinals {
+                    let value = self.config_get_parameter(param, ordinal, timeout_ms)?;
+                    kept.push((param, ordinal, value));
+                }
+            }
+        }
+        self.config_factory_default(timeout_ms)?;
+        let record = &private::Sealed::state(self).config_record;
+        record.clear();
+        let mut errors = Vec::new();
+        for (param, ordinal, value) in kept {
+            match self.config_set_parameter(param, value, 0, ordinal, timeout_ms) {
+                Ok(()) => record.record(param, ordinal, value),
+                Err(err) => errors.push(err),
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigErrors {
+                errors,
+                cancelled: 0,
+            })
+        }
+    }
 
     /**
      * Set the control mode and output value so that this motor controller will
      * follow another motor controller.
-     * Currently supports following Victor SPX and Talon SRX.
      *
      * * `master_to_follow` - Motor Controller object to follow.
      * * `follower_type` - Type of following control.
      *   Use AuxOutput1 to follow the master device's auxiliary output 1.
      *   Use PercentOutput for standard follower mode.
+     *
+     * Any Talon SRX or Victor SPX can follow any other, with either follower
+     * type: both run the auxiliary loop, so a Victor can follow a Talon's
+     * `AuxOutput1` and vice versa.  A follower can itself be followed.
+     *
+     * Fails with `InvalidParamValue`, leaving this controller as it was, if
+     * `master_to_follow` is this controller, or with `IncompatibleMode` if it
+     * is following this controller, directly or through other followers, as
+     * the pair would only ever mirror each other's neutral output.
+     *
+     * A follower set to `InvertType::FollowMaster` or `OpposeMaster` takes its
+     * inversion from `master_to_follow` from then on.
      */
-    fn follow(&self, master_to_follow: &impl BaseMotorController, follower_type: FollowerType) {
-        let base_id = master_to_follow.get_base_id();
-        let id24: i32 = ((base_id >> 0x10) << 8) | (base_id & 0xFF);
+    fn follow(
+        &self,
+        master_to_follow: &impl BaseMotorController,
+        follower_type: FollowerType,
+    ) -> Result<()> {
+        if master_to_follow.get_base_id() == self.get_base_id() {
+            return Err(cci_error!(self, follow, ErrorCode::InvalidParamValue));
+        }
+        let master = private::Sealed::state(master_to_follow);
+        let state = private::Sealed::state(self);
+        // Held until this device is following, so two threads can't each
+        // pass the cycle check and then make a cycle between them.
+        let _topology = FOLLOW_TOPOLOGY
+            .lock()
+            .unwrap_or_else(|err| err.into_inner());
+        if follows(master, state) {
+            return Err(cci_error!(self, follow, ErrorCode::IncompatibleMode));
+        }
+        let previous = {
+            let mut inversion = state.inversion.lock().unwrap();
+            inversion.apply = true;
+            inversion.master.replace(Arc::downgrade(master))
+        };
+        if let Some(previous) = previous.and_then(|previous| previous.upgrade()) {
+            if !Arc::ptr_eq(&previous, master) {
+                let mut inversion = previous.inversion.lock().unwrap();
+                let me = Arc::downgrade(state);
+                inversion.followers.retain(|follower| !follower.ptr_eq(&me));
+            }
+        }
+        {
+            // Robot code often calls `follow` every loop, so don't add it twice.
+            let mut inversion = master.inversion.lock().unwrap();
+            inversion
+                .followers
+                .retain(|follower| follower.strong_count() > 0);
+            let me = Arc::downgrade(state);
+            if !inversion
+                .followers
+                .iter()
+                .any(|follower| follower.ptr_eq(&me))
+            {
+                inversion.followers.push(me);
+            }
+        }
+        update_inversion(state);
+
+        let id24 = demand::follower_id24(master_to_follow.get_base_id());
 
         match follower_type {
             FollowerType::PercentOutput => {
@@ -1012,103 +2270,516 @@ pub trait BaseMotorController: private::Sealed {
                 self.set(ControlMode::Follower, id24 as f64, DemandType::AuxPID, 0.0)
             }
         };
+        Ok(())
+    }
+}
+
+/// How a motor controller's output is inverted.  See `BaseMotorController::set_inverted`.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum InvertType {
+    /// Not inverted.
+    #[default]
+    None,
+    /// Inverted.
+    InvertMotorOutput,
+    /// Inverted if the master being followed is.
+    FollowMaster,
+    /// Inverted if the master being followed isn't.
+    OpposeMaster,
+}
+
+impl From<bool> for InvertType {
+    fn from(invert: bool) -> Self {
+        if invert {
+            InvertType::InvertMotorOutput
+        } else {
+            InvertType::None
+        }
     }
 }
 
+/// A motor controller's inversion, kept so followers can track their master's.
+#[derive(Debug, Default)]
+pub(crate) struct Inversion {
+    pub invert_type: InvertType,
+    /// The inversion last sent to the device.
+    pub inverted: bool,
+    /// Whether to send the inversion to the device, once it has been set or it follows a master.
+    pub apply: bool,
+    /// The master last passed to `follow`.
+    pub master: Option<Weak<DeviceState>>,
+    /// Devices following this one, each once.
+    pub followers: Vec<Weak<DeviceState>>,
+}
+
+/// What was last sent with `set`, `set_neutral_mode` and `set_sensor_phase`,
+/// for `rebind` to send to the new device.  Recording them takes no lock, as
+/// `set` is called every loop.
+#[derive(Debug, Default)]
+pub(crate) struct Outputs {
+    /// Sequence number, odd while `demand` is being written.  A reader retries
+    /// if it changed while reading, so it never sees half of one demand.
+    seq: AtomicU32,
+    /// `ControlMode` and `DemandType`, as `mode << 8 | demand1_type`, plus one;
+    /// zero if never set.
+    modes: AtomicI32,
+    /// `demand0` and `demand1`, as bits.
+    demands: [AtomicU64; 2],
+    /// `NeutralMode` plus one; zero if never set.
+    neutral_mode: AtomicI32,
+    /// The sensor phase plus one; zero if never set.
+    sensor_phase: AtomicU8,
+}
+
+impl Outputs {
+    pub fn set_demand(&self, demand: (ControlMode, f64, DemandType, f64)) {
+        // Claim the slot, in case another thread is also setting the output.
+        let mut seq = self.seq.load(Ordering::Relaxed);
+        loop {
+            if seq % 2 == 1 {
+                hint::spin_loop();
+                seq = self.seq.load(Ordering::Relaxed);
+                continue;
+            }
+            let claimed = seq.wrapping_add(1);
+            match self
+                .seq
+                .compare_exchange_weak(seq, claimed, Ordering::Acquire, Ordering::Relaxed)
+            {
+                Ok(_) => break,
+                Err(current) => seq = current,
+            }
+        }
+        fence(Ordering::Release);
+        let (mode, demand0, demand1_type, demand1) = demand;
+        let modes = ((mode as i32) << 8 | demand1_type as i32) + 1;
+        self.modes.store(modes, Ordering::Relaxed);
+        self.demands[0].store(demand0.to_bits(), Ordering::Relaxed);
+        self.demands[1].store(demand1.to_bits(), Ordering::Relaxed);
+        self.seq.store(seq.wrapping_add(2), Ordering::Release);
+    }
+    pub fn demand(&self) -> Option<(ControlMode, f64, DemandType, f64)> {
+        let (modes, demand0, demand1) = loop {
+            let seq = self.seq.load(Ordering::Acquire);
+            if seq % 2 == 1 {
+                hint::spin_loop();
+                continue;
+            }
+            let modes = self.modes.load(Ordering::Relaxed);
+            let demand0 = self.demands[0].load(Ordering::Relaxed);
+            let demand1 = self.demands[1].load(Ordering::Relaxed);
+            fence(Ordering::Acquire);
+            if self.seq.load(Ordering::Relaxed) == seq {
+                break (modes - 1, demand0, demand1);
+            }
+        };
+        let mode = CONTROL_MODES
+            .iter()
+            .copied()
+            .find(|&m| m as i32 == modes >> 8)?;
+        let demand1_type = DEMAND_TYPES
+            .iter()
+            .copied()
+            .find(|&t| t as i32 == modes & 0xFF)?;
+        Some((
+            mode,
+            f64::from_bits(demand0),
+            demand1_type,
+            f64::from_bits(demand1),
+        ))
+    }
+    pub fn set_neutral_mode(&self, neutral_mode: NeutralMode) {
+        self.neutral_mode
+            .store(neutral_mode as i32 + 1, Ordering::Relaxed);
+    }
+    pub fn neutral_mode(&self) -> Option<NeutralMode> {
+        let raw = self.neutral_mode.load(Ordering::Relaxed) - 1;
+        NEUTRAL_MODES
+            .iter()
+            .copied()
+            .find(|&mode| mode as i32 == raw)
+    }
+    pub fn set_sensor_phase(&self, phase_sensor: bool) {
+        self.sensor_phase
+            .store(phase_sensor as u8 + 1, Ordering::Relaxed);
+    }
+    pub fn sensor_phase(&self) -> Option<bool> {
+        match self.sensor_phase.load(Ordering::Relaxed) {
+            0 => None,
+            raw => Some(raw == 2),
+        }
+    }
+}
+
+/// Every variant, to read back the ones `Outputs` stores as numbers.
+const CONTROL_MODES: [ControlMode; 9] = [
+    ControlMode::PercentOutput,
+    ControlMode::Position,
+    ControlMode::Velocity,
+    ControlMode::Current,
+    ControlMode::Follower,
+    ControlMode::MotionProfile,
+    ControlMode::MotionMagic,
+    ControlMode::MotionProfileArc,
+    ControlMode::Disabled,
+];
+const DEMAND_TYPES: [DemandType; 3] = [
+    DemandType::Neutral,
+    DemandType::AuxPID,
+    DemandType::ArbitraryFeedForward,
+];
+const NEUTRAL_MODES: [NeutralMode; 3] = [
+    NeutralMode::EEPROMSetting,
+    NeutralMode::Coast,
+    NeutralMode::Brake,
+];
+
+/// `motor`'s state, for helpers running on other threads to reach its current handle through.
+pub(crate) fn shared_state<M: BaseMotorController + ?Sized>(motor: &M) -> Arc<DeviceState> {
+    Arc::clone(private::Sealed::state(motor))
+}
+
+/// Serializes changes to who follows whom, for `follow`'s cycle check.
+static FOLLOW_TOPOLOGY: Mutex<()> = Mutex::new(());
+
+/// Whether `follower` is following `master`, directly or through other followers.
+fn follows(follower: &Arc<DeviceState>, master: &Arc<DeviceState>) -> bool {
+    let mut current = follower.clone();
+    let mut visited: Vec<*const DeviceState> = Vec::new();
+    while current.following.load(Ordering::Relaxed) && !visited.contains(&Arc::as_ptr(&current)) {
+        visited.push(Arc::as_ptr(&current));
+        let next = current.inversion.lock().unwrap().master.clone();
+        match next.and_then(|next| next.upgrade()) {
+            Some(next) if Arc::ptr_eq(&next, master) => return true,
+            Some(next) => current = next,
+            None => return false,
+        }
+    }
+    false
+}
+
+/**
+ * Sends the inversion `state`'s invert type resolves to, then does the same for
+ * its followers, and theirs.
+ *
+ * Only one device's lock is held at a time, so devices updating at once on
+ * different threads can't deadlock.
+ */
+fn update_inversion(state: &Arc<DeviceState>) {
+    let mut pending = vec![state.clone()];
+    let mut visited: Vec<*const DeviceState> = Vec::new();
+    while let Some(state) = pending.pop() {
+        // A follow cycle mustn't loop forever.
+        if visited.contains(&Arc::as_ptr(&state)) {
+            continue;
+        }
+        visited.push(Arc::as_ptr(&state));
+        let master = state.inversion.lock().unwrap().master.clone();
+        let master_inverted = master
+            .and_then(|master| master.upgrade())
+            .is_some_and(|master| master.inversion.lock().unwrap().inverted);
+        let mut inversion = state.inversion.lock().unwrap();
+        let inverted = match inversion.invert_type {
+            InvertType::None => false,
+            InvertType::InvertMotorOutput => true,
+            InvertType::FollowMaster => master_inverted,
+            InvertType::OpposeMaster => !master_inverted,
+        };
+        inversion.inverted = inverted;
+        if inversion.apply {
+            // The CCI functions lock internally, so they are safe to call from any thread.
+            unsafe { c_MotController_SetInverted(state.handle(), inverted) };
+        }
+        inversion
+            .followers
+            .retain(|follower| follower.strong_count() > 0);
+        let followers: Vec<_> = inversion
+            .followers
+            .iter()
+            .filter_map(Weak::upgrade)
+            .collect();
+        drop(inversion);
+        for follower in followers {
+            let following = follower.inversion.lock().unwrap().master.clone();
+            if following.is_some_and(|master| ptr::eq(master.as_ptr(), &*state)) {
+                pending.push(follower);
+            }
+        }
+    }
+}
+
+/// A snapshot of one closed loop.  See `BaseMotorController::get_closed_loop_state`.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct ClosedLoopState {
+    pub target: i32,
+    pub error: i32,
+    /// Integral accumulator.
+    pub iaccum: f64,
+    /// Derivative of the closed-loop error.
+    pub derr: f64,
+    /// Motor output percent, from -1 to 1.  This is the combined output of both loops.
+    pub output: f64,
+}
+
+/// How close a closed loop must be to count as at its setpoint.
+/// See `BaseMotorController::at_setpoint`.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct SetpointTolerance {
+    /// Largest allowed closed-loop error, in sensor units.
+    pub error: i32,
+    /// Largest allowed sensor velocity, in sensor units per 100ms.
+    pub velocity: i32,
+}
+
 /// An interface for getting and setting raw sensor values.
 pub trait SensorCollection: BaseMotorController {
     fn get_analog_in(&self) -> Result<i32> {
-        cci_get_call!(c_MotController_GetAnalogIn(self.handle(), _: i32))
+        cci_get_call!(self, c_MotController_GetAnalogIn(self.handle(), _: i32))
     }
-    fn set_analog_position(&self, new_position: i32, timeout_ms: i32) -> ErrorCode {
-        unsafe { c_MotController_SetAnalogPosition(self.handle(), new_position, timeout_ms) }
+    fn set_analog_position(&self, new_position: i32, timeout_ms: i32) -> Result<()> {
+        cci_call!(
+            self,
+            c_MotController_SetAnalogPosition(self.handle(), new_position, timeout_ms)
+        )
     }
     fn get_analog_in_raw(&self) -> Result<i32> {
-        cci_get_call!(c_MotController_GetAnalogInRaw(self.handle(), _: i32))
+        cci_get_call!(self, c_MotController_GetAnalogInRaw(self.handle(), _: i32))
     }
     fn get_analog_in_vel(&self) -> Result<i32> {
-        cci_get_call!(c_MotController_GetAnalogInVel(self.handle(), _: i32))
+        cci_get_call!(self, c_MotController_GetAnalogInVel(self.handle(), _: i32))
     }
     fn get_quadrature_position(&self) -> Result<i32> {
-        cci_get_call!(c_MotController_GetQuadraturePosition(self.handle(), _: i32))
+        cci_get_call!(self, c_MotController_GetQuadraturePosition(self.handle(), _: i32))
     }
-    fn set_quadrature_position(&self, new_position: i32, timeout_ms: i32) -> ErrorCode {
-        unsafe { c_MotController_SetQuadraturePosition(self.handle(), new_position, timeout_ms) }
+    fn set_quadrature_position(&self, new_position: i32, timeout_ms: i32) -> Result<()> {
+        cci_call!(
+            self,
+            c_MotController_SetQuadraturePosition(self.handle(), new_position, timeout_ms)
+        )
     }
     fn get_quadrature_velocity(&self) -> Result<i32> {
-        cci_get_call!(c_MotController_GetQuadratureVelocity(self.handle(), _: i32))
+        cci_get_call!(self, c_MotController_GetQuadratureVelocity(self.handle(), _: i32))
     }
     fn get_pulse_width_position(&self) -> Result<i32> {
-        cci_get_call!(c_MotController_GetPulseWidthPosition(self.handle(), _: i32))
+        cci_get_call!(self, c_MotController_GetPulseWidthPosition(self.handle(), _: i32))
     }
-    fn set_pulse_width_position(&self, new_position: i32, timeout_ms: i32) -> ErrorCode {
-        unsafe { c_MotController_SetPulseWidthPosition(self.handle(), new_position, timeout_ms) }
+    fn set_pulse_width_position(&self, new_position: i32, timeout_ms: i32) -> Result<()> {
+        cci_call!(
+            self,
+            c_MotController_SetPulseWidthPosition(self.handle(), new_position, timeout_ms)
+        )
     }
     fn get_pulse_width_velocity(&self) -> Result<i32> {
-        cci_get_call!(c_MotController_GetPulseWidthVelocity(self.handle(), _: i32))
+        cci_get_call!(self, c_MotController_GetPulseWidthVelocity(self.handle(), _: i32))
     }
     fn get_pulse_width_rise_to_fall_us(&self) -> Result<i32> {
-        cci_get_call!(c_MotController_GetPulseWidthRiseToFallUs(self.handle(), _: i32))
+        cci_get_call!(self, c_MotController_GetPulseWidthRiseToFallUs(self.handle(), _: i32))
     }
     fn get_pulse_width_rise_to_rise_us(&self) -> Result<i32> {
-        cci_get_call!(c_MotController_GetPulseWidthRiseToRiseUs(self.handle(), _: i32))
+        cci_get_call!(self, c_MotController_GetPulseWidthRiseToRiseUs(self.handle(), _: i32))
     }
     fn get_pin_state_quad_a(&self) -> Result<i32> {
-        cci_get_call!(c_MotController_GetPinStateQuadA(self.handle(), _: i32))
+        cci_get_call!(self, c_MotController_GetPinStateQuadA(self.handle(), _: i32))
     }
     fn get_pin_state_quad_b(&self) -> Result<i32> {
-        cci_get_call!(c_MotController_GetPinStateQuadB(self.handle(), _: i32))
+        cci_get_call!(self, c_MotController_GetPinStateQuadB(self.handle(), _: i32))
     }
     fn get_pin_state_quad_idx(&self) -> Result<i32> {
-        cci_get_call!(c_MotController_GetPinStateQuadIdx(self.handle(), _: i32))
+        cci_get_call!(self, c_MotController_GetPinStateQuadIdx(self.handle(), _: i32))
     }
     fn is_fwd_limit_switch_closed(&self) -> Result<i32> {
-        cci_get_call!(c_MotController_IsFwdLimitSwitchClosed(self.handle(), _: i32))
+        cci_get_call!(self, c_MotController_IsFwdLimitSwitchClosed(self.handle(), _: i32))
     }
     fn is_rev_limit_switch_closed(&self) -> Result<i32> {
-        cci_get_call!(c_MotController_IsRevLimitSwitchClosed(self.handle(), _: i32))
+        cci_get_call!(self, c_MotController_IsRevLimitSwitchClosed(self.handle(), _: i32))
+    }
+}
+
+/**
+ * A signal on another device that a motor controller can use as a remote
+ * sensor.  See `BaseMotorController::config_remote_source`.
+ *
+ * Each source names the device it comes from, so it can't be paired with the
+ * id of a device of another kind.  A Pigeon connected through a Talon's
+ * gadgeteer port is addressed through the Talon.
+ */
+#[derive(Copy, Clone)]
+pub enum RemoteSource<'a> {
+    /// No remote sensor.
+    Off,
+    TalonSelectedSensor(&'a TalonSRX),
+    PigeonYaw(&'a PigeonIMU),
+    PigeonPitch(&'a PigeonIMU),
+    PigeonRoll(&'a PigeonIMU),
+    CANifierQuadrature(&'a CANifier),
+    CANifierPWMInput(&'a CANifier, PWMChannel),
+}
+
+impl<'a> RemoteSource<'a> {
+    /// The device id and source to pass to the CCI.
+    fn raw(&self) -> (DeviceId, RemoteSensorSource) {
+        use self::RemoteSource::*;
+        let pigeon = |pigeon: &PigeonIMU, direct, gadgeteer| {
+            (
+                pigeon.id(),
+                if pigeon.is_gadgeteer() {
+                    gadgeteer
+                } else {
+                    direct
+                },
+            )
+        };
+        match *self {
+            Off => (DeviceId::new(0), RemoteSensorSource::Off),
+            TalonSelectedSensor(talon) => (talon.id(), RemoteSensorSource::TalonSRX_SelectedSensor),
+            PigeonYaw(imu) => pigeon(
+                imu,
+                RemoteSensorSource::Pigeon_Yaw,
+                RemoteSensorSource::GadgeteerPigeon_Yaw,
+            ),
+            PigeonPitch(imu) => pigeon(
+                imu,
+                RemoteSensorSource::Pigeon_Pitch,
+                RemoteSensorSource::GadgeteerPigeon_Pitch,
+            ),
+            PigeonRoll(imu) => pigeon(
+                imu,
+                RemoteSensorSource::Pigeon_Roll,
+                RemoteSensorSource::GadgeteerPigeon_Roll,
+            ),
+            CANifierQuadrature(canifier) => {
+                (canifier.id(), RemoteSensorSource::CANifier_Quadrature)
+            }
+            CANifierPWMInput(canifier, channel) => {
+                let source = match channel {
+                    PWMChannel::P0 => RemoteSensorSource::CANifier_PWMInput0,
+                    PWMChannel::P1 => RemoteSensorSource::CANifier_PWMInput1,
+                    PWMChannel::P2 => RemoteSensorSource::CANifier_PWMInput2,
+                    PWMChannel::P3 => RemoteSensorSource::CANifier_PWMInput3,
+                };
+                (canifier.id(), source)
+            }
+        }
+    }
+}
+
+/// Devices whose limit switch inputs another motor controller can use.
+pub trait RemoteLimitSwitch {
+    /// The remote source type and device ID that select this device's limit switches.
+    fn remote_limit_switch_source(&self) -> (RemoteLimitSwitchSource, DeviceId);
+}
+
+impl RemoteLimitSwitch for TalonSRX {
+    fn remote_limit_switch_source(&self) -> (RemoteLimitSwitchSource, DeviceId) {
+        (RemoteLimitSwitchSource::RemoteTalonSRX, self.id())
+    }
+}
+
+impl RemoteLimitSwitch for CANifier {
+    fn remote_limit_switch_source(&self) -> (RemoteLimitSwitchSource, DeviceId) {
+        (RemoteLimitSwitchSource::RemoteCANifier, self.id())
     }
 }
 
 /// CTRE Talon SRX Motor Controller when used on CAN Bus.
 pub struct TalonSRX {
-    handle: Handle,
-    arb_id: i32,
+    /// The arbitration id the controller was created with, for comparisons.
+    created_arb_id: i32,
+    state: Arc<DeviceState>,
+}
+
+impl TalonSRX {
+    /// Another object for the registered Talon owning `state`.
+    pub(crate) fn from_state(device_number: i32, state: Arc<DeviceState>) -> TalonSRX {
+        TalonSRX {
+            created_arb_id: device_number | 0x02040000,
+            state,
+        }
+    }
 }
 
 impl BaseMotorController for TalonSRX {
-    fn new(device_number: i32) -> TalonSRX {
-        let arb_id = device_number | 0x02040000;
-        let handle = unsafe { c_MotController_Create1(arb_id) };
-        TalonSRX { handle, arb_id }
+    fn new(device_number: DeviceId) -> TalonSRX {
+        let handle = unsafe { c_MotController_Create1(i32::from(device_number) | 0x02040000) };
+        let state = registry().register(DeviceType::TalonSRX, device_number, handle);
+        TalonSRX::from_state(i32::from(device_number), state)
     }
 
     fn handle(&self) -> Handle {
-        self.handle
+        self.state.handle()
     }
     fn get_base_id(&self) -> i32 {
-        self.arb_id
+        self.state.device_number() | 0x02040000
     }
 }
 
-impl Debug for TalonSRX {
-    fn fmt(&self, f: &mut Formatter) ->  std::result::Result<(), std::fmt::Error>  {
-        write!(f, "TalonSRX(id={})", self.get_device_id())
-    }
-}
+/**
+ * Implements the sealed trait, formatting and comparisons for a motor controller
+ * type.  Formatting uses the local arbitration id, without FFI calls.
+ * Comparisons use the arbitration id the controller was created with, so
+ * `rebind` doesn't move a controller already in a set, map or sorted list.
+ *
+ * The type needs `created_arb_id: i32` and `state: Arc<DeviceState>` fields.
+ */
+macro_rules! impl_motor_controller {
+    ($device:ident, $device_type:expr) => {
+        #[allow(private_interfaces)]
+        impl private::Sealed for $device {
+            const DEVICE_TYPE: DeviceType = $device_type;
+            fn state(&self) -> &Arc<DeviceState> {
+                &self.state
+            }
+        }
 
-impl Display for TalonSRX {
-    fn fmt(&self, f: &mut Formatter) ->  std::result::Result<(), std::fmt::Error> {
-        write!(f, "TalonSRX(id={})", self.get_device_id())
-    }
-}
+        impl Debug for $device {
+            fn fmt(&self, f: &mut Formatter) -> std::result::Result<(), std::fmt::Error> {
+                Display::fmt(self, f)
+            }
+        }
 
-impl PartialEq for TalonSRX {
-    fn eq(&self, other: &TalonSRX) -> bool {
-        self.get_device_id() == other.get_device_id()
-    }
+        /// e.g. `TalonSRX(id=3)`, or `TalonSRX(id=3, name=intake_roller)` for a named device.
+        impl Display for $device {
+            fn fmt(&self, f: &mut Formatter) -> std::result::Result<(), std::fmt::Error> {
+                write!(
+                    f,
+                    concat!(stringify!($device), "(id={}"),
+                    self.device_number()
+                )?;
+                if let Some(name) = self.name() {
+                    write!(f, ", name={}", name)?;
+                }
+                f.write_str(")")
+            }
+        }
+
+        impl PartialEq for $device {
+            fn eq(&self, other: &$device) -> bool {
+                self.created_arb_id == other.created_arb_id
+            }
+        }
+        impl Eq for $device {}
+
+        impl std::hash::Hash for $device {
+            fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+                self.created_arb_id.hash(state)
+            }
+        }
+
+        impl PartialOrd for $device {
+            fn partial_cmp(&self, other: &$device) -> Option<std::cmp::Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+        impl Ord for $device {
+            fn cmp(&self, other: &$device) -> std::cmp::Ordering {
+                self.created_arb_id.cmp(&other.created_arb_id)
+            }
+        }
+    };
 }
 
+impl_motor_controller!(TalonSRX, DeviceType::TalonSRX);
 
 impl TalonSRX {
     /**
@@ -1123,17 +2794,20 @@ impl TalonSRX {
     pub fn config_selected_feedback_sensor(
         &self,
         feedback_device: FeedbackDevice,
-        pid_idx: i32,
+        pid_idx: impl TryInto<PidLoop>,
         timeout_ms: i32,
-    ) -> ErrorCode {
-        unsafe {
+    ) -> Result<()> {
+        let pid_idx = cci_index!(self, c_MotController_ConfigSelectedFeedbackSensor, pid_idx: PidLoop)?
+            as i32;
+        cci_call!(
+            self,
             c_MotController_ConfigSelectedFeedbackSensor(
-                self.handle,
+                self.handle(),
                 feedback_device as _,
                 pid_idx,
                 timeout_ms,
             )
-        }
+        )
     }
 
     // XXX: not provided by CTRE's APIs
@@ -1142,8 +2816,8 @@ impl TalonSRX {
         &self,
         frame: ControlFrameEnhanced,
         period_ms: i32,
-    ) -> ErrorCode {
-        unsafe { c_MotController_SetControlFramePeriod(self.handle, frame as _, period_ms) }
+    ) -> Result<()> {
+        cci_call!(self, c_MotController_SetControlFramePeriod(self.handle(), frame as _, period_ms))
     }
     */
     pub fn set_status_frame_period(
@@ -1151,10 +2825,11 @@ impl TalonSRX {
         frame: StatusFrameEnhanced,
         period_ms: i32,
         timeout_ms: i32,
-    ) -> ErrorCode {
-        unsafe {
-            c_MotController_SetStatusFramePeriod(self.handle, frame as _, period_ms, timeout_ms)
-        }
+    ) -> Result<()> {
+        cci_call!(
+            self,
+            c_MotController_SetStatusFramePeriod(self.handle(), frame as _, period_ms, timeout_ms)
+        )
     }
     pub fn get_status_frame_period(
         &self,
@@ -1162,7 +2837,8 @@ impl TalonSRX {
         timeout_ms: i32,
     ) -> Result<i32> {
         cci_get_call!(
-            c_MotController_GetStatusFramePeriod(self.handle, frame as _, _: i32, timeout_ms)
+            self,
+            c_MotController_GetStatusFramePeriod(self.handle(), frame as _, _: i32, timeout_ms)
         )
     }
 
@@ -1176,20 +2852,22 @@ impl TalonSRX {
         &self,
         period: VelocityMeasPeriod,
         timeout_ms: i32,
-    ) -> ErrorCode {
-        unsafe {
-            c_MotController_ConfigVelocityMeasurementPeriod(self.handle, period as _, timeout_ms)
-        }
+    ) -> Result<()> {
+        cci_call!(
+            self,
+            c_MotController_ConfigVelocityMeasurementPeriod(self.handle(), period as _, timeout_ms)
+        )
     }
     /// Sets the number of velocity samples used in the rolling average velocity measurement.
     pub fn config_velocity_measurement_window(
         &self,
         window_size: i32,
         timeout_ms: i32,
-    ) -> ErrorCode {
-        unsafe {
-            c_MotController_ConfigVelocityMeasurementWindow(self.handle, window_size, timeout_ms)
-        }
+    ) -> Result<()> {
+        cci_call!(
+            self,
+            c_MotController_ConfigVelocityMeasurementWindow(self.handle(), window_size, timeout_ms)
+        )
     }
 
     /**
@@ -1214,16 +2892,17 @@ impl TalonSRX {
         type_: LimitSwitchSource,
         normal_open_or_close: LimitSwitchNormal,
         timeout_ms: i32,
-    ) -> ErrorCode {
-        unsafe {
+    ) -> Result<()> {
+        cci_call!(
+            self,
             c_MotController_ConfigForwardLimitSwitchSource(
-                self.handle,
+                self.handle(),
                 type_ as _,
                 normal_open_or_close as _,
                 0,
                 timeout_ms,
             )
-        }
+        )
     }
     /**
      * Configures the reverse limit switch for a local/remote source.
@@ -1247,16 +2926,17 @@ impl TalonSRX {
         type_: LimitSwitchSource,
         normal_open_or_close: LimitSwitchNormal,
         timeout_ms: i32,
-    ) -> ErrorCode {
-        unsafe {
+    ) -> Result<()> {
+        cci_call!(
+            self,
             c_MotController_ConfigReverseLimitSwitchSource(
-                self.handle,
+                self.handle(),
                 type_ as _,
                 normal_open_or_close as _,
                 0,
                 timeout_ms,
             )
-        }
+        )
     }
 
     /**
@@ -1278,8 +2958,11 @@ impl TalonSRX {
      *
      * [`config_continuous_current_limit`]: #method.config_continuous_current_limit
      */
-    pub fn config_peak_current_limit(&self, amps: i32, timeout_ms: i32) -> ErrorCode {
-        unsafe { c_MotController_ConfigPeakCurrentLimit(self.handle, amps, timeout_ms) }
+    pub fn config_peak_current_limit(&self, amps: i32, timeout_ms: i32) -> Result<()> {
+        cci_call!(
+            self,
+            c_MotController_ConfigPeakCurrentLimit(self.handle(), amps, timeout_ms)
+        )
     }
     /**
      * Configure the peak allowable duration (when current limit is enabled).
@@ -1300,8 +2983,11 @@ impl TalonSRX {
      *
      * [`config_continuous_current_limit`]: #method.config_continuous_current_limit
      */
-    pub fn config_peak_current_duration(&self, milliseconds: i32, timeout_ms: i32) -> ErrorCode {
-        unsafe { c_MotController_ConfigPeakCurrentLimit(self.handle, milliseconds, timeout_ms) }
+    pub fn config_peak_current_duration(&self, milliseconds: i32, timeout_ms: i32) -> Result<()> {
+        cci_call!(
+            self,
+            c_MotController_ConfigPeakCurrentDuration(self.handle(), milliseconds, timeout_ms)
+        )
     }
     /**
      * Configure the continuous allowable current-draw (when current limit is enabled).
@@ -1320,11 +3006,40 @@ impl TalonSRX {
      *   If nonzero, function will wait for config success and report an error if it times out.
      *   If zero, no blocking or checking is performed.
      */
-    pub fn config_continuous_current_limit(&self, amps: i32, timeout_ms: i32) -> ErrorCode {
-        unsafe { c_MotController_ConfigContinuousCurrentLimit(self.handle, amps, timeout_ms) }
+    pub fn config_continuous_current_limit(&self, amps: i32, timeout_ms: i32) -> Result<()> {
+        cci_call!(
+            self,
+            c_MotController_ConfigContinuousCurrentLimit(self.handle(), amps, timeout_ms)
+        )
+    }
+    /// Reads the peak supply current limit, in amps.
+    pub fn get_peak_current_limit(&self, timeout_ms: i32) -> Result<i32> {
+        let param = ParamEnum::PeakCurrentLimitAmps;
+        Ok(self.config_get_parameter(param, 0, timeout_ms)? as i32)
+    }
+    /// Reads how long current may exceed the peak limit, in milliseconds.
+    pub fn get_peak_current_duration(&self, timeout_ms: i32) -> Result<i32> {
+        let param = ParamEnum::PeakCurrentLimitMs;
+        Ok(self.config_get_parameter(param, 0, timeout_ms)? as i32)
+    }
+    /// Reads the continuous supply current limit, in amps.
+    pub fn get_continuous_current_limit(&self, timeout_ms: i32) -> Result<i32> {
+        let param = ParamEnum::ContinuousCurrentLimitAmps;
+        Ok(self.config_get_parameter(param, 0, timeout_ms)? as i32)
     }
     pub fn enable_current_limit(&self, enable: bool) {
-        unsafe { c_MotController_EnableCurrentLimit(self.handle, enable) };
+        unsafe { c_MotController_EnableCurrentLimit(self.handle(), enable) };
+    }
+
+    /// Gets the supply (input) current in amps.  Requires Phoenix 2020 or later.
+    #[cfg(feature = "phoenix-2020")]
+    pub fn get_supply_current(&self) -> Result<f64> {
+        cci_get_call!(self, c_MotController_GetSupplyCurrent(self.handle(), _: f64))
+    }
+    /// Gets the stator (output) current in amps.  Requires Phoenix 2020 or later.
+    #[cfg(feature = "phoenix-2020")]
+    pub fn get_stator_current(&self) -> Result<f64> {
+        cci_get_call!(self, c_MotController_GetStatorCurrent(self.handle(), _: f64))
     }
 }
 
@@ -1332,28 +3047,497 @@ impl SensorCollection for TalonSRX {}
 
 /// VEX Victor SPX Motor Controller when used on CAN Bus.
 pub struct VictorSPX {
-    handle: Handle,
-    arb_id: i32,
+    /// The arbitration id the controller was created with, for comparisons.
+    created_arb_id: i32,
+    state: Arc<DeviceState>,
+}
+
+impl VictorSPX {
+    /// Another object for the registered Victor owning `state`.
+    pub(crate) fn from_state(device_number: i32, state: Arc<DeviceState>) -> VictorSPX {
+        VictorSPX {
+            created_arb_id: device_number | 0x01040000,
+            state,
+        }
+    }
 }
 
 impl BaseMotorController for VictorSPX {
-    fn new(device_number: i32) -> VictorSPX {
-        let arb_id = device_number | 0x01040000;
-        let handle = unsafe { c_MotController_Create1(arb_id) };
-        VictorSPX { handle, arb_id }
+    fn new(device_number: DeviceId) -> VictorSPX {
+        let handle = unsafe { c_MotController_Create1(i32::from(device_number) | 0x01040000) };
+        let state = registry().register(DeviceType::VictorSPX, device_number, handle);
+        VictorSPX::from_state(i32::from(device_number), state)
     }
 
     fn handle(&self) -> Handle {
-        self.handle
+        self.state.handle()
     }
     fn get_base_id(&self) -> i32 {
-        self.arb_id
+        self.state.device_number() | 0x01040000
+    }
+}
+
+impl_motor_controller!(VictorSPX, DeviceType::VictorSPX);
+
+impl<T: BaseMotorController + ?Sized> DeviceContext for T {
+    fn device_type(&self) -> DeviceType {
+        T::DEVICE_TYPE
+    }
+    fn device_id(&self) -> i32 {
+        self.device_number()
+    }
+    fn state(&self) -> &DeviceState {
+        private::Sealed::state(self)
+    }
+    fn probe(&self) -> bool {
+        let mut volts = 0.;
+        unsafe { c_MotController_GetBusVoltage(self.handle(), &mut volts) }.is_ok()
     }
 }
 
 // Prevent users from implementing the BaseMotorController trait.
+// `Sealed` can't be named outside the crate, so its crate-private types don't leak.
+#[allow(private_interfaces)]
 mod private {
-    pub trait Sealed {}
-    impl Sealed for super::TalonSRX {}
-    impl Sealed for super::VictorSPX {}
+    use std::sync::Arc;
+
+    use device::DeviceState;
+    use error::DeviceType;
+
+    pub trait Sealed {
+        const DEVICE_TYPE: DeviceType;
+        fn state(&self) -> &Arc<DeviceState>;
+    }
+}
+
+#[test]
+fn remote_limit_switch_from_device() {
+    let victor = VictorSPX::new(DeviceId::new(41));
+    let canifier = CANifier::new(DeviceId::new(9));
+    victor
+        .use_remote_reverse_limit(&canifier, LimitSwitchNormal::NormallyClosed, 0)
+        .unwrap();
+    let source = victor.config_get_parameter(ParamEnum::LimitSwitchSource, 1, 0);
+    let device_id = victor.config_get_parameter(ParamEnum::LimitSwitchRemoteDevID, 1, 0);
+    assert_eq!(
+        source,
+        Ok(RemoteLimitSwitchSource::RemoteCANifier as i32 as f64)
+    );
+    assert_eq!(device_id, Ok(9.0));
+}
+
+#[test]
+fn normalize_mag_encoder_feedback() {
+    let talon = TalonSRX::new(DeviceId::new(6));
+    talon
+        .config_selected_feedback_sensor(FeedbackDevice::CTRE_MagEncoder_Relative, 0, 0)
+        .unwrap();
+    assert_eq!(talon.normalize_feedback_to(1024., 0, 0), Ok(0.25));
+    let coefficient = talon.config_get_parameter(ParamEnum::SelectedSensorCoefficient, 0, 0);
+    assert_eq!(coefficient, Ok(0.25));
+    assert!(talon.normalize_feedback_to(8192., 0, 0).is_err());
+}
+
+#[test]
+fn copy_and_scale_gains() {
+    let talon = TalonSRX::new(DeviceId::new(7));
+    talon.config_kp(0, 0.5, 0).unwrap();
+    talon.config_kf(0, 0.25, 0).unwrap();
+    talon.config_integral_zone(0, 200, 0).unwrap();
+    talon.copy_gains(Slot::S0, Slot::S2, 0).unwrap();
+    talon.scale_gains(2, 2., 0).unwrap();
+    let get = |param| talon.config_get_parameter(param, 2, 0).unwrap();
+    assert_eq!(get(ParamEnum::ProfileParamSlot_P), 1.);
+    assert_eq!(get(ParamEnum::ProfileParamSlot_F), 0.5);
+    assert_eq!(get(ParamEnum::ProfileParamSlot_IZone), 200.);
+    assert_eq!(get(ParamEnum::ProfileParamSlot_PeakOutput), 1.);
+}
+
+#[test]
+fn pid_and_slot_indices() {
+    assert_eq!(PidLoop::try_from(1), Ok(PidLoop::Aux));
+    assert_eq!(Slot::try_from(2), Ok(Slot::S2));
+    assert_eq!(Slot::try_from(4), Err(ErrorCode::InvalidParamValue));
+    let talon = TalonSRX::new(DeviceId::new(10));
+    talon.config_kp(Slot::S2, 0.25, 0).unwrap();
+    assert_eq!(
+        talon
+            .config_get_parameter(ParamEnum::ProfileParamSlot_P, 2, 0)
+            .unwrap(),
+        0.25
+    );
+    talon.select_profile_slot(Slot::S2, PidLoop::Aux).unwrap();
+    // Raw indexes are checked rather than panicking.
+    let err = talon.config_kp(7, 1., 0).unwrap_err();
+    assert_eq!(err.code, ErrorCode::InvalidParamValue);
+    assert!(talon.select_profile_slot(0, 2).is_err());
+    assert_eq!(talon.selected_profile_slot(2), None);
+}
+
+#[test]
+fn device_number_is_local() {
+    let talon = TalonSRX::new(DeviceId::new(11));
+    assert_eq!(talon.device_number(), 11);
+    assert_eq!(talon.to_string(), "TalonSRX(id=11)");
+    talon.verify_device_id().unwrap();
+}
+
+#[test]
+// Only the immutable `created_arb_id` is hashed, not the interior-mutable `DeviceState`.
+#[allow(clippy::mutable_key_type)]
+fn controllers_compare_by_arb_id() {
+    use std::collections::HashSet;
+    let mut talons = [
+        TalonSRX::new(DeviceId::new(13)),
+        TalonSRX::new(DeviceId::new(12)),
+    ];
+    talons.sort();
+    assert_eq!(talons[0].device_number(), 12);
+    let victor = VictorSPX::new(DeviceId::new(12));
+    assert_eq!(format!("{:?}", victor), "VictorSPX(id=12)");
+    let ids: HashSet<_> = talons.iter().collect();
+    assert!(ids.contains(&TalonSRX::new(DeviceId::new(12))));
+}
+
+#[test]
+fn named_controller_labels_errors() {
+    let talon = TalonSRX::new(DeviceId::new(14));
+    talon.set_name("intake_roller");
+    assert_eq!(talon.to_string(), "TalonSRX(id=14, name=intake_roller)");
+    let err = talon.normalize_feedback_to(8192., 0, 0).unwrap_err();
+    assert_eq!(err.name, Some("intake_roller"));
+    assert!(err.to_string().starts_with("intake_roller (TalonSRX 14): "));
+}
+
+#[test]
+fn control_request_selects_slot_and_feed_forward() {
+    use control::VelocityRequest;
+    let talon = TalonSRX::new(DeviceId::new(19));
+    assert_eq!(talon.selected_profile_slot(PidLoop::Primary), None);
+    talon
+        .apply(&VelocityRequest::new(300.).with_slot(Slot::S1).with_ff(0.1))
+        .unwrap();
+    assert_eq!(talon.get_closed_loop_target(PidLoop::Primary).unwrap(), 300);
+    assert_eq!(
+        talon.selected_profile_slot(PidLoop::Primary),
+        Some(Slot::S1)
+    );
+    assert_eq!(talon.selected_profile_slot(PidLoop::Aux), None);
+}
+
+#[test]
+fn at_setpoint_debounces() {
+    let talon = TalonSRX::new(DeviceId::new(20));
+    let tolerance = SetpointTolerance {
+        error: 10,
+        velocity: 5,
+    };
+    let zero = Duration::from_secs(0);
+    talon.set(ControlMode::Position, 1000., DemandType::Neutral, 0.);
+    assert!(!talon
+        .at_setpoint(PidLoop::Primary, tolerance, zero)
+        .unwrap());
+    talon.set_selected_sensor_position(995, 0, 0).unwrap();
+    assert!(talon
+        .at_setpoint(PidLoop::Primary, tolerance, zero)
+        .unwrap());
+    let debounce = Duration::from_millis(20);
+    talon.set_selected_sensor_position(0, 0, 0).unwrap();
+    assert!(!talon
+        .at_setpoint(PidLoop::Primary, tolerance, debounce)
+        .unwrap());
+    talon.set_selected_sensor_position(1000, 0, 0).unwrap();
+    assert!(!talon
+        .at_setpoint(PidLoop::Primary, tolerance, debounce)
+        .unwrap());
+    ::std::thread::sleep(debounce);
+    assert!(talon
+        .at_setpoint(PidLoop::Primary, tolerance, debounce)
+        .unwrap());
+}
+
+#[test]
+fn gains_round_trip() {
+    let talon = TalonSRX::new(DeviceId::new(21));
+    let gains = Gains {
+        kp: 0.5,
+        kf: 0.125,
+        allowable_closedloop_error: 20,
+        closed_loop_peak_output: 0.75,
+        closed_loop_period_ms: 2,
+        ..Gains::default()
+    };
+    talon.config_gains(Slot::S1, &gains, 0).unwrap();
+    assert_eq!(talon.get_gains(Slot::S1, 0).unwrap(), gains);
+    assert_eq!(talon.get_gains(Slot::S0, 0).unwrap(), Gains::default());
+}
+
+#[test]
+fn bulk_get_matches_single_reads() {
+    let talon = TalonSRX::new(DeviceId::new(27));
+    talon.config_openloop_ramp(0.5, 0).unwrap();
+    let params = [
+        ParamEnum::OpenloopRamp,
+        ParamEnum::ClosedloopRamp,
+        ParamEnum::PeakPosOutput,
+        ParamEnum::PeakNegOutput,
+        ParamEnum::NominalPosOutput,
+        ParamEnum::NominalNegOutput,
+    ];
+    let values = talon.bulk_get_parameters(&params, 0, 0);
+    assert_eq!(values.len(), params.len());
+    for &param in &params {
+        assert_eq!(values[&param], talon.config_get_parameter(param, 0, 0));
+    }
+    assert_eq!(values[&ParamEnum::OpenloopRamp], Ok(0.5));
+    assert!(talon.bulk_get_parameters(&[], 0, 0).is_empty());
+}
+
+#[test]
+fn outputs_read_back_what_was_set() {
+    let outputs = Outputs::default();
+    assert_eq!(outputs.demand(), None);
+    assert_eq!(outputs.neutral_mode(), None);
+    assert_eq!(outputs.sensor_phase(), None);
+    let demand = (ControlMode::MotionMagic, -1024.5, DemandType::AuxPID, 90.);
+    outputs.set_demand(demand);
+    outputs.set_neutral_mode(NeutralMode::EEPROMSetting);
+    outputs.set_sensor_phase(false);
+    assert_eq!(outputs.demand(), Some(demand));
+    assert_eq!(outputs.neutral_mode(), Some(NeutralMode::EEPROMSetting));
+    assert_eq!(outputs.sensor_phase(), Some(false));
+}
+
+#[test]
+fn rebind_reapplies_config() {
+    use thermal::{ThermalGuard, ThermalPolicy};
+    let talon = TalonSRX::new(DeviceId::new(32));
+    let twin = TalonSRX::new(DeviceId::new(32));
+    talon.cfg().kp(0, 0.3).unwrap().openloop_ramp(0.5).unwrap();
+    talon.set(ControlMode::PercentOutput, 0.4, DemandType::Neutral, 0.);
+    let policy = ThermalPolicy {
+        base_limit_amps: 35,
+        period: Duration::from_millis(1),
+        ..ThermalPolicy::default()
+    };
+    let guard = ThermalGuard::start(&talon, policy);
+    thread::sleep(Duration::from_millis(20));
+    talon.rebind(DeviceId::new(33)).unwrap();
+    assert_eq!(talon.device_number(), 33);
+    // Still equal to the controller it was created alongside.
+    assert_eq!(talon, twin);
+    assert_eq!(talon.get_motor_output_percent(), Ok(0.4));
+    thread::sleep(Duration::from_millis(20));
+    assert_eq!(
+        talon.config_get_parameter(ParamEnum::ContinuousCurrentLimitAmps, 0, 0),
+        Ok(35.)
+    );
+    drop(guard);
+    assert_eq!(
+        talon.config_get_parameter(ParamEnum::ProfileParamSlot_P, 0, 0),
+        Ok(0.3)
+    );
+    assert_eq!(
+        talon.config_get_parameter(ParamEnum::OpenloopRamp, 0, 0),
+        Ok(0.5)
+    );
+}
+
+#[cfg(feature = "phoenix-2019")]
+#[test]
+fn reset_keeps_selected_groups() {
+    let talon = TalonSRX::new(DeviceId::new(34));
+    talon.config_set_custom_param(1234, 1, 0).unwrap();
+    talon.config_kp(2, 0.4, 0).unwrap();
+    talon.config_openloop_ramp(0.5, 0).unwrap();
+    talon
+        .reset_and_restore(&[ParamGroup::CustomParams, ParamGroup::Gains], 0)
+        .unwrap();
+    assert_eq!(talon.config_get_custom_param(1, 0), Ok(1234));
+    assert_eq!(
+        talon.config_get_parameter(ParamEnum::ProfileParamSlot_P, 2, 0),
+        Ok(0.4)
+    );
+    assert_eq!(
+        talon.config_get_parameter(ParamEnum::OpenloopRamp, 0, 0),
+        Ok(0.)
+    );
+}
+
+#[test]
+fn remote_source_addresses_device() {
+    use sensors::pigeon::PigeonIMU;
+    let talon = TalonSRX::new(DeviceId::new(43));
+    let gadgeteer = PigeonIMU::from(&TalonSRX::new(DeviceId::new(44)));
+    let canifier = CANifier::new(DeviceId::new(20));
+    let remote = |ordinal| {
+        let source = talon.config_get_parameter(ParamEnum::RemoteSensorSource, ordinal, 0);
+        let id = talon.config_get_parameter(ParamEnum::RemoteSensorDeviceID, ordinal, 0);
+        (source.unwrap() as i32, id.unwrap() as i32)
+    };
+    talon
+        .config_remote_source(0, RemoteSource::PigeonPitch(&gadgeteer), 0)
+        .unwrap();
+    assert_eq!(
+        remote(0),
+        (RemoteSensorSource::GadgeteerPigeon_Pitch as i32, 44)
+    );
+    let pwm = RemoteSource::CANifierPWMInput(&canifier, PWMChannel::P2);
+    talon.config_remote_source(1, pwm, 0).unwrap();
+    assert_eq!(
+        remote(1),
+        (RemoteSensorSource::CANifier_PWMInput2 as i32, 20)
+    );
+    let err = talon.config_remote_source(2, pwm, 0).unwrap_err();
+    assert_eq!(err.code, ErrorCode::InvalidParamValue);
+}
+
+#[test]
+fn victor_rejects_local_feedback_devices() {
+    let victor = VictorSPX::new(DeviceId::new(45));
+    assert!(!victor
+        .supported_feedback_devices()
+        .contains(&FeedbackDevice::QuadEncoder));
+    let err = victor
+        .config_sensor_term(SensorTerm::Sum0, FeedbackDevice::QuadEncoder, 0)
+        .unwrap_err();
+    assert_eq!(err.code, ErrorCode::FeatureNotSupported);
+    let quad = FeedbackDevice::QuadEncoder as i32 as f64;
+    let err = victor
+        .config_set_parameter(ParamEnum::FeedbackSensorType, quad, 0, 0, 0)
+        .unwrap_err();
+    assert_eq!(err.code, ErrorCode::FeatureNotSupported);
+    victor
+        .config_sensor_term(SensorTerm::Sum0, FeedbackDevice::RemoteSensor0, 0)
+        .unwrap();
+    let talon = TalonSRX::new(DeviceId::new(45));
+    assert_eq!(talon.supported_feedback_devices().len(), 10);
+}
+
+#[test]
+fn getters_read_back_config() {
+    let talon = TalonSRX::new(DeviceId::new(46));
+    talon.config_closedloop_ramp(0.25, 0).unwrap();
+    talon.config_peak_output_reverse(-0.75, 0).unwrap();
+    talon.config_voltage_comp_saturation(11., 0).unwrap();
+    talon.config_forward_soft_limit_threshold(4000, 0).unwrap();
+    talon.config_forward_soft_limit_enable(true, 0).unwrap();
+    talon.config_motion_cruise_velocity(1500, 0).unwrap();
+    talon.config_continuous_current_limit(30, 0).unwrap();
+    assert_eq!(talon.get_closedloop_ramp(0), Ok(0.25));
+    assert_eq!(talon.get_peak_output_reverse(0), Ok(-0.75));
+    assert_eq!(talon.get_voltage_comp_saturation(0), Ok(11.));
+    assert_eq!(talon.get_forward_soft_limit_threshold(0), Ok(4000));
+    assert_eq!(talon.get_forward_soft_limit_enable(0), Ok(true));
+    assert_eq!(talon.get_reverse_soft_limit_enable(0), Ok(false));
+    assert_eq!(talon.get_motion_cruise_velocity(0), Ok(1500));
+    assert_eq!(talon.get_continuous_current_limit(0), Ok(30));
+}
+
+#[test]
+fn followers_track_master_inversion() {
+    let master = TalonSRX::new(DeviceId::new(47));
+    let opposed = TalonSRX::new(DeviceId::new(48));
+    let same = VictorSPX::new(DeviceId::new(47));
+    opposed.set_inverted(InvertType::OpposeMaster);
+    same.set_inverted(InvertType::FollowMaster);
+    opposed
+        .follow(&master, FollowerType::PercentOutput)
+        .unwrap();
+    same.follow(&opposed, FollowerType::PercentOutput).unwrap();
+    assert_eq!((opposed.get_inverted(), same.get_inverted()), (true, true));
+    master.set_inverted(true);
+    assert_eq!(
+        (opposed.get_inverted(), same.get_inverted()),
+        (false, false)
+    );
+    assert_eq!(master.get_invert_type(), InvertType::InvertMotorOutput);
+    // Following another master stops tracking this one.
+    master.set_inverted(false);
+    let other = TalonSRX::new(DeviceId::new(49));
+    opposed.follow(&other, FollowerType::PercentOutput).unwrap();
+    master.set_inverted(true);
+    assert!(opposed.get_inverted());
+}
+
+#[test]
+fn follow_rejects_itself_and_cycles() {
+    let talon = TalonSRX::new(DeviceId::new(53));
+    let victor = VictorSPX::new(DeviceId::new(53));
+    let chained = TalonSRX::new(DeviceId::new(54));
+    // A Victor can follow a Talon's auxiliary output.
+    victor.follow(&talon, FollowerType::AuxOutput1).unwrap();
+    talon.set(
+        ControlMode::PercentOutput,
+        0.5,
+        DemandType::ArbitraryFeedForward,
+        0.2,
+    );
+    assert!((victor.get_motor_output_percent().unwrap() - 0.3).abs() < 1e-9);
+    chained
+        .follow(&victor, FollowerType::PercentOutput)
+        .unwrap();
+
+    let err = talon
+        .follow(&talon, FollowerType::PercentOutput)
+        .unwrap_err();
+    assert_eq!(err.code, ErrorCode::InvalidParamValue);
+    let err = talon
+        .follow(&chained, FollowerType::PercentOutput)
+        .unwrap_err();
+    assert_eq!(err.code, ErrorCode::IncompatibleMode);
+    assert_eq!(talon.get_motor_output_percent(), Ok(0.7));
+    // Once the Victor is driven itself, the chain is broken.
+    victor.set(ControlMode::PercentOutput, 0.0, DemandType::Neutral, 0.0);
+    talon.follow(&victor, FollowerType::PercentOutput).unwrap();
+}
+
+#[test]
+fn follow_cycle_is_rejected_across_threads() {
+    let left = TalonSRX::new(DeviceId::new(59));
+    let right = TalonSRX::new(DeviceId::new(60));
+    for _ in 0..20 {
+        let (a, b) = thread::scope(|scope| {
+            let a = scope.spawn(|| left.follow(&right, FollowerType::PercentOutput));
+            let b = scope.spawn(|| right.follow(&left, FollowerType::PercentOutput));
+            (a.join().unwrap(), b.join().unwrap())
+        });
+        assert!(a.is_ok() != b.is_ok());
+        left.neutral_output();
+        right.neutral_output();
+    }
+}
+
+#[test]
+fn follow_repeatedly_keeps_one_follower() {
+    let first = TalonSRX::new(DeviceId::new(56));
+    let second = TalonSRX::new(DeviceId::new(57));
+    let follower = VictorSPX::new(DeviceId::new(56));
+    let followers = |master: &TalonSRX| master.state().inversion.lock().unwrap().followers.len();
+    for _ in 0..3 {
+        follower
+            .follow(&first, FollowerType::PercentOutput)
+            .unwrap();
+    }
+    assert_eq!(followers(&first), 1);
+    follower
+        .follow(&second, FollowerType::PercentOutput)
+        .unwrap();
+    assert_eq!(followers(&first), 0);
+    assert_eq!(followers(&second), 1);
+}
+
+#[test]
+fn estimate_kf_needs_motion() {
+    let talon = TalonSRX::new(DeviceId::new(50));
+    let settle = Duration::from_millis(1);
+    let err = talon
+        .estimate_kf(Some(Slot::S0), 0.5, settle, 0)
+        .unwrap_err();
+    assert_eq!(err.code, ErrorCode::SensorNotPresent);
+    assert_eq!(talon.get_motor_output_percent(), Ok(0.));
+    assert_eq!(
+        talon.config_get_parameter(ParamEnum::ProfileParamSlot_F, 0, 0),
+        Ok(0.)
+    );
 }
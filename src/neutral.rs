@@ -0,0 +1,179 @@
+//! Making sure motors stop when the code driving them panics.
+//!
+//! A panic in an autonomous routine skips the code that would have stopped
+//! its motors, leaving them running at their last output.  A
+//! [`NeutralOnDrop`] guard neutral-outputs its motors when it is dropped,
+//! including while the scope that owns it unwinds.  [`neutral_on_panic`]
+//! also covers panics on other threads and builds that abort on panic.
+//!
+//! [`NeutralOnDrop`]: struct.NeutralOnDrop.html
+//! [`neutral_on_panic`]: fn.neutral_on_panic.html
+
+use std::fmt;
+use std::panic;
+use std::sync::{Arc, Mutex, Once, TryLockError};
+
+use ctre_sys::mot::c_MotController_SetDemand;
+use device::DeviceState;
+use motor_control::{shared_state, BaseMotorController, ControlMode};
+
+/// Neutralizes the motor controller owning `state` from the panic hook.
+fn neutral_output(state: &DeviceState) {
+    // The CCI functions lock internally, so they are safe to call from any thread.
+    unsafe { c_MotController_SetDemand(state.handle(), ControlMode::Disabled as _, 0, 0) };
+}
+
+/// The state of every live guard's motors, by guard id.
+struct Guarded {
+    next_id: u64,
+    motors: Vec<(u64, Arc<DeviceState>)>,
+}
+
+static GUARDED: Mutex<Guarded> = Mutex::new(Guarded {
+    next_id: 0,
+    motors: Vec::new(),
+});
+
+/// Neutralizing a motor controller, for any motor controller.
+trait NeutralDevice {
+    fn neutral_output(&self);
+}
+
+impl<T: BaseMotorController> NeutralDevice for T {
+    fn neutral_output(&self) {
+        BaseMotorController::neutral_output(self)
+    }
+}
+
+/**
+ * Neutral-outputs a set of motor controllers when dropped, so they stop if
+ * the scope owning the guard returns early or panics.
+ *
+ * ```no_run
+ * # use ctre::DeviceId;
+ * # use ctre::motor_control::{BaseMotorController, ControlMode, DemandType, TalonSRX};
+ * # use ctre::neutral::NeutralOnDrop;
+ * let (left, right) = (TalonSRX::new(DeviceId::new(1)), TalonSRX::new(DeviceId::new(2)));
+ * fn autonomous(left: &TalonSRX, right: &TalonSRX) {
+ *     let mut guard = NeutralOnDrop::new();
+ *     guard.add(left).add(right);
+ *     left.set(ControlMode::PercentOutput, 0.5, DemandType::Neutral, 0.);
+ *     // A panic here stops both motors.
+ * }
+ * autonomous(&left, &right);
+ * ```
+ *
+ * With [`neutral_on_panic`], the guard's motors are also neutralized when any
+ * thread panics.
+ *
+ * [`neutral_on_panic`]: fn.neutral_on_panic.html
+ */
+pub struct NeutralOnDrop<'a> {
+    id: u64,
+    motors: Vec<&'a dyn NeutralDevice>,
+    armed: bool,
+}
+
+impl<'a> NeutralOnDrop<'a> {
+    pub fn new() -> Self {
+        let mut guarded = GUARDED.lock().unwrap_or_else(|err| err.into_inner());
+        let id = guarded.next_id;
+        guarded.next_id += 1;
+        NeutralOnDrop {
+            id,
+            motors: Vec::new(),
+            armed: true,
+        }
+    }
+    pub fn add<M: BaseMotorController>(&mut self, motor: &'a M) -> &mut Self {
+        self.motors.push(motor);
+        let state = shared_state(motor);
+        let mut guarded = GUARDED.lock().unwrap_or_else(|err| err.into_inner());
+        guarded.motors.push((self.id, state));
+        self
+    }
+    /// Drops the guard without neutralizing its motors, e.g. when the routine finishes normally.
+    pub fn release(mut self) {
+        self.armed = false;
+    }
+}
+
+impl<'a> Default for NeutralOnDrop<'a> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> Drop for NeutralOnDrop<'a> {
+    fn drop(&mut self) {
+        if self.armed {
+            for motor in &self.motors {
+                motor.neutral_output();
+            }
+        }
+        let mut guarded = GUARDED.lock().unwrap_or_else(|err| err.into_inner());
+        guarded.motors.retain(|&(id, _)| id != self.id);
+    }
+}
+
+impl<'a> fmt::Debug for NeutralOnDrop<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("NeutralOnDrop")
+            .field("motors", &self.motors.len())
+            .field("armed", &self.armed)
+            .finish()
+    }
+}
+
+/**
+ * Installs a panic hook which neutral-outputs the motors of every live
+ * [`NeutralOnDrop`] guard, then runs the previous hook.
+ *
+ * The hook runs when a thread panics, before it unwinds, so it also covers
+ * guards owned by other threads and builds with `panic = "abort"`.  Installing
+ * it more than once has no further effect.
+ *
+ * [`NeutralOnDrop`]: struct.NeutralOnDrop.html
+ */
+pub fn neutral_on_panic() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            // A panic while the list is locked mustn't deadlock the hook.
+            let guarded = match GUARDED.try_lock() {
+                Ok(guarded) => Some(guarded),
+                Err(TryLockError::Poisoned(err)) => Some(err.into_inner()),
+                Err(TryLockError::WouldBlock) => None,
+            };
+            for (_, state) in guarded.iter().flat_map(|guarded| &guarded.motors) {
+                neutral_output(state);
+            }
+            drop(guarded);
+            previous(info);
+        }));
+    });
+}
+
+#[test]
+fn guard_neutralizes_on_unwind() {
+    use motor_control::{DemandType, TalonSRX};
+    use DeviceId;
+    let talon = TalonSRX::new(DeviceId::new(38));
+    let output = || talon.get_motor_output_percent().unwrap();
+    let result = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+        let mut guard = NeutralOnDrop::new();
+        guard.add(&talon);
+        talon.set(ControlMode::PercentOutput, 0.5, DemandType::Neutral, 0.);
+        assert_eq!(output(), 0.5);
+        panic!("autonomous failed");
+    }));
+    assert!(result.is_err());
+    assert_eq!(output(), 0.);
+
+    let mut guard = NeutralOnDrop::new();
+    guard.add(&talon);
+    talon.set(ControlMode::PercentOutput, 0.25, DemandType::Neutral, 0.);
+    guard.release();
+    assert_eq!(output(), 0.25);
+}
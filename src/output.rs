@@ -0,0 +1,186 @@
+//! Scaling down every motor's output, e.g. for demos and outreach events.
+
+use std::fmt;
+
+use motor_control::BaseMotorController;
+use {ParamEnum, Result};
+
+/// A controller's peak output settings, as configured for competition.
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct PeakOutputs {
+    forward: f64,
+    reverse: f64,
+    /// Closed-loop peak output of each slot.
+    slots: [f64; 4],
+}
+
+impl PeakOutputs {
+    fn scaled(&self, factor: f64) -> PeakOutputs {
+        PeakOutputs {
+            forward: self.forward * factor,
+            reverse: self.reverse * factor,
+            slots: [
+                self.slots[0] * factor,
+                self.slots[1] * factor,
+                self.slots[2] * factor,
+                self.slots[3] * factor,
+            ],
+        }
+    }
+}
+
+/// Reading and writing peak outputs, for any motor controller.
+trait PeakOutputDevice {
+    fn read_peak_outputs(&self, timeout_ms: i32) -> Result<PeakOutputs>;
+    fn write_peak_outputs(&self, peaks: &PeakOutputs, timeout_ms: i32) -> Result<()>;
+}
+
+impl<T: BaseMotorController> PeakOutputDevice for T {
+    fn read_peak_outputs(&self, timeout_ms: i32) -> Result<PeakOutputs> {
+        let get = |param, ordinal| self.config_get_parameter(param, ordinal, timeout_ms);
+        let slot = |slot_idx| get(ParamEnum::ProfileParamSlot_PeakOutput, slot_idx);
+        Ok(PeakOutputs {
+            forward: get(ParamEnum::PeakPosOutput, 0)?,
+            reverse: get(ParamEnum::PeakNegOutput, 0)?,
+            slots: [slot(0)?, slot(1)?, slot(2)?, slot(3)?],
+        })
+    }
+    fn write_peak_outputs(&self, peaks: &PeakOutputs, timeout_ms: i32) -> Result<()> {
+        self.config_peak_output_forward(peaks.forward, timeout_ms)?;
+        self.config_peak_output_reverse(peaks.reverse, timeout_ms)?;
+        for (slot_idx, &peak) in peaks.slots.iter().enumerate() {
+            self.config_closed_loop_peak_output(slot_idx as i32, peak, timeout_ms)?;
+        }
+        Ok(())
+    }
+}
+
+/**
+ * Limits every registered motor controller's output to a fraction of its
+ * competition settings, for demos and outreach events.
+ *
+ * Scaling adjusts the peak output configs: the forward and reverse peak outputs,
+ * which limit open-loop output, and each slot's closed-loop peak output.  The
+ * competition settings are read from each controller before the first scaling,
+ * and written back by [`restore`].
+ *
+ * ```no_run
+ * # use ctre::DeviceId;
+ * # use ctre::motor_control::{BaseMotorController, TalonSRX};
+ * # use ctre::output::OutputScaler;
+ * # fn main() -> ctre::Result<()> {
+ * let (left, right) = (TalonSRX::new(DeviceId::new(1)), TalonSRX::new(DeviceId::new(2)));
+ * let mut scaler = OutputScaler::new();
+ * scaler.add(&left).add(&right);
+ * scaler.set_factor(0.3, 30)?;
+ * // ... demo ...
+ * scaler.restore(30)?;
+ * # Ok(())
+ * # }
+ * ```
+ *
+ * [`restore`]: #method.restore
+ */
+#[derive(Default)]
+pub struct OutputScaler<'a> {
+    devices: Vec<&'a dyn PeakOutputDevice>,
+    /// Competition settings of each device, while scaled.
+    saved: Option<Vec<PeakOutputs>>,
+    factor: Option<f64>,
+}
+
+impl<'a> OutputScaler<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Registers a controller.  Register every controller before scaling.
+    pub fn add<M: BaseMotorController>(&mut self, motor: &'a M) -> &mut Self {
+        self.devices.push(motor);
+        self
+    }
+    /// The factor outputs are currently scaled by, or `None` if they aren't.
+    pub fn factor(&self) -> Option<f64> {
+        self.factor
+    }
+
+    /**
+     * Scales every controller's peak outputs to `factor` (from 0 to 1) of its
+     * competition settings.  Can be called again to change the factor.
+     *
+     * If a controller fails to update, the remaining controllers are still
+     * updated and the first error is returned.
+     */
+    pub fn set_factor(&mut self, factor: f64, timeout_ms: i32) -> Result<()> {
+        let factor = factor.clamp(0., 1.);
+        if self.saved.is_none() {
+            let saved = self
+                .devices
+                .iter()
+                .map(|device| device.read_peak_outputs(timeout_ms))
+                .collect::<Result<Vec<_>>>()?;
+            self.saved = Some(saved);
+        }
+        self.factor = Some(factor);
+        let saved = self.saved.as_ref().unwrap();
+        let mut result = Ok(());
+        for (device, peaks) in self.devices.iter().zip(saved) {
+            let written = device.write_peak_outputs(&peaks.scaled(factor), timeout_ms);
+            result = result.and(written);
+        }
+        result
+    }
+
+    /**
+     * Writes back the competition settings and stops scaling.
+     *
+     * If a controller fails to update, the remaining controllers are still
+     * updated, the first error is returned, and the scaler stays scaled so
+     * `restore` can be retried.
+     */
+    pub fn restore(&mut self, timeout_ms: i32) -> Result<()> {
+        let saved = match self.saved {
+            Some(ref saved) => saved,
+            None => return Ok(()),
+        };
+        let mut result = Ok(());
+        for (device, peaks) in self.devices.iter().zip(saved) {
+            result = result.and(device.write_peak_outputs(peaks, timeout_ms));
+        }
+        if result.is_ok() {
+            self.saved = None;
+            self.factor = None;
+        }
+        result
+    }
+}
+
+impl<'a> fmt::Debug for OutputScaler<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("OutputScaler")
+            .field("devices", &self.devices.len())
+            .field("factor", &self.factor)
+            .finish()
+    }
+}
+
+#[test]
+fn scaler_scales_and_restores() {
+    use motor_control::{TalonSRX, VictorSPX};
+    use DeviceId;
+    let talon = TalonSRX::new(DeviceId::new(23));
+    let victor = VictorSPX::new(DeviceId::new(23));
+    talon.config_peak_output_reverse(-0.8, 0).unwrap();
+    let mut scaler = OutputScaler::new();
+    scaler.add(&talon).add(&victor);
+    scaler.set_factor(0.5, 0).unwrap();
+    scaler.set_factor(0.25, 0).unwrap();
+    let get = |param, ordinal| talon.config_get_parameter(param, ordinal, 0).unwrap();
+    assert_eq!(get(ParamEnum::PeakPosOutput, 0), 0.25);
+    assert_eq!(get(ParamEnum::PeakNegOutput, 0), -0.2);
+    assert_eq!(get(ParamEnum::ProfileParamSlot_PeakOutput, 3), 0.25);
+    scaler.restore(0).unwrap();
+    assert_eq!(scaler.factor(), None);
+    assert_eq!(get(ParamEnum::PeakPosOutput, 0), 1.);
+    assert_eq!(get(ParamEnum::PeakNegOutput, 0), -0.8);
+    assert_eq!(get(ParamEnum::ProfileParamSlot_PeakOutput, 3), 1.);
+}
@@ -0,0 +1,150 @@
+//! Named output ramp profiles, switched for a group of controllers at once.
+//!
+//! A drivetrain might ramp gently while lining up a shot and not at all while
+//! crossing the field.  [`RampProfiles`] holds the open-loop and closed-loop
+//! ramp rates for each such profile, and applies one to every controller in the
+//! group with a single call:
+//!
+//! ```no_run
+//! # use ctre::DeviceId;
+//! # use ctre::motor_control::{BaseMotorController, TalonSRX};
+//! # use ctre::ramp::{RampProfile, RampProfiles};
+//! # fn main() -> ctre::Result<()> {
+//! let (left, right) = (TalonSRX::new(DeviceId::new(1)), TalonSRX::new(DeviceId::new(2)));
+//! let mut drive = RampProfiles::new();
+//! drive.add(&left).add(&right);
+//! drive
+//!     .add_profile("precision", RampProfile::new(0.5, 0.25))
+//!     .add_profile("sprint", RampProfile::new(0., 0.));
+//! drive.select("sprint", 30)?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`RampProfiles`]: struct.RampProfiles.html
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use motor_control::BaseMotorController;
+use {ErrorCode, Result};
+
+/// Ramp rates, in seconds from neutral to full output.
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub struct RampProfile {
+    /// Ramp in open-loop modes, such as PercentOutput.
+    pub open_loop: f64,
+    /// Ramp in closed-loop modes, applied to the loop's output.
+    pub closed_loop: f64,
+}
+
+impl RampProfile {
+    pub fn new(open_loop: f64, closed_loop: f64) -> Self {
+        RampProfile {
+            open_loop,
+            closed_loop,
+        }
+    }
+}
+
+/// Applying ramp rates, for any motor controller.
+pub(crate) trait RampDevice {
+    fn apply_ramp(&self, profile: &RampProfile, timeout_ms: i32) -> Result<()>;
+}
+
+impl<T: BaseMotorController> RampDevice for T {
+    fn apply_ramp(&self, profile: &RampProfile, timeout_ms: i32) -> Result<()> {
+        self.config_openloop_ramp(profile.open_loop, timeout_ms)?;
+        self.config_closedloop_ramp(profile.closed_loop, timeout_ms)
+    }
+}
+
+/// Named [`RampProfile`]s for a group of controllers.
+///
+/// [`RampProfile`]: struct.RampProfile.html
+#[derive(Default)]
+pub struct RampProfiles<'a> {
+    motors: Vec<&'a dyn RampDevice>,
+    profiles: BTreeMap<&'static str, RampProfile>,
+    active: Option<&'static str>,
+}
+
+impl<'a> RampProfiles<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Adds a controller to the group.  It gets the next selected profile.
+    pub fn add<M: BaseMotorController>(&mut self, motor: &'a M) -> &mut Self {
+        self.motors.push(motor);
+        self
+    }
+    /// Adds a profile, replacing any with the same name.
+    pub fn add_profile(&mut self, name: &'static str, profile: RampProfile) -> &mut Self {
+        self.profiles.insert(name, profile);
+        self
+    }
+    pub fn profile(&self, name: &str) -> Option<RampProfile> {
+        self.profiles.get(name).cloned()
+    }
+    /// The name of the profile last applied to every controller, if any.
+    pub fn active(&self) -> Option<&'static str> {
+        self.active
+    }
+
+    /**
+     * Applies the profile called `name` to every controller in the group.
+     *
+     * Returns `InvalidParamValue` if there is no such profile.  If a controller
+     * fails to update, the remaining controllers are still updated, the first
+     * error is returned, and no profile is considered active.
+     */
+    pub fn select(&mut self, name: &str, timeout_ms: i32) -> Result<()> {
+        let (&name, profile) = match self.profiles.get_key_value(name) {
+            Some(entry) => entry,
+            None => return Err(ErrorCode::InvalidParamValue.into()),
+        };
+        let mut result = Ok(());
+        for motor in &self.motors {
+            result = result.and(motor.apply_ramp(profile, timeout_ms));
+        }
+        self.active = result.as_ref().ok().map(|_| name);
+        result
+    }
+}
+
+impl<'a> fmt::Debug for RampProfiles<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("RampProfiles")
+            .field("motors", &self.motors.len())
+            .field("profiles", &self.profiles)
+            .field("active", &self.active)
+            .finish()
+    }
+}
+
+#[test]
+fn profiles_apply_to_group() {
+    use motor_control::TalonSRX;
+    use DeviceId;
+    use ParamEnum;
+    let (left, right) = (
+        TalonSRX::new(DeviceId::new(25)),
+        TalonSRX::new(DeviceId::new(26)),
+    );
+    let mut drive = RampProfiles::new();
+    drive
+        .add(&left)
+        .add(&right)
+        .add_profile("precision", RampProfile::new(0.5, 0.25));
+    assert_eq!(
+        drive.select("sprint", 0).unwrap_err().code,
+        ErrorCode::InvalidParamValue
+    );
+    drive.select("precision", 0).unwrap();
+    assert_eq!(drive.active(), Some("precision"));
+    for talon in &[&left, &right] {
+        let ramp = |param| talon.config_get_parameter(param, 0, 0).unwrap();
+        assert_eq!(ramp(ParamEnum::OpenloopRamp), 0.5);
+        assert_eq!(ramp(ParamEnum::ClosedloopRamp), 0.25);
+    }
+}
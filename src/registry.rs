@@ -0,0 +1,195 @@
+//! A process-wide list of the devices that have been created.
+//!
+//! Every device registers itself when created, so telemetry, the CLI or a
+//! dashboard can find devices by name or id without being handed them.  The
+//! registry only holds weak references: a dropped device drops out of it.
+//!
+//! ```no_run
+//! # use ctre::DeviceId;
+//! # use ctre::motor_control::{BaseMotorController, TalonSRX};
+//! let intake = TalonSRX::new(DeviceId::new(3));
+//! intake.set_name("intake");
+//! // Anywhere else:
+//! for device in ctre::registry().devices() {
+//!     println!("{}", device);
+//! }
+//! assert_eq!(ctre::registry().find("intake").map(|device| device.id), Some(3));
+//! ```
+
+use std::fmt;
+use std::ptr;
+use std::sync::{Arc, Mutex, OnceLock, Weak};
+
+use device::DeviceState;
+use error::DeviceType;
+use events::{events, DeviceEvent};
+use DeviceId;
+
+/// A device in the [`Registry`].
+///
+/// [`Registry`]: struct.Registry.html
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct DeviceInfo {
+    pub device_type: DeviceType,
+    /// CAN device number, [0,62].  A Pigeon on a Talon's gadgeteer port has the Talon's.
+    pub id: i32,
+    /// Label set with `set_name`, if any.
+    pub name: Option<&'static str>,
+}
+
+/// e.g. `intake (TalonSRX 3)`, or `TalonSRX 3` if unnamed.
+impl fmt::Display for DeviceInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.name {
+            Some(name) => write!(f, "{} ({} {})", name, self.device_type, self.id),
+            None => write!(f, "{} {}", self.device_type, self.id),
+        }
+    }
+}
+
+struct Entry {
+    device_type: DeviceType,
+    id: i32,
+    state: Weak<DeviceState>,
+}
+
+/**
+ * The devices created so far that haven't been dropped.  See [`registry`].
+ *
+ * Creating a second object for the same device, e.g. `TalonSRX::new(id)` twice,
+ * registers both; lookups and `devices` report each device once, named if
+ * either object is.
+ *
+ * [`registry`]: fn.registry.html
+ */
+pub struct Registry {
+    entries: Mutex<Vec<Entry>>,
+}
+
+impl Registry {
+    /// Registers a device and its CCI handle, returning the state for it to keep.
+    pub(crate) fn register<T>(
+        &self,
+        device_type: DeviceType,
+        device_id: DeviceId,
+        handle: *mut T,
+    ) -> Arc<DeviceState> {
+        let state = Arc::new(DeviceState::default());
+        state.bind(handle, device_id);
+        let id = i32::from(device_id);
+        {
+            let mut entries = self.entries.lock().unwrap();
+            entries.retain(|entry| entry.state.strong_count() > 0);
+            entries.push(Entry {
+                device_type,
+                id,
+                state: Arc::downgrade(&state),
+            });
+        }
+        let device = DeviceInfo {
+            device_type,
+            id,
+            name: None,
+        };
+        events().publish(DeviceEvent::Created(device));
+        state
+    }
+    /// Records that the device owning `state` now has device number `id`.
+    pub(crate) fn set_id(&self, state: &DeviceState, id: i32) {
+        let mut entries = self.entries.lock().unwrap();
+        for entry in entries.iter_mut() {
+            if ptr::eq(entry.state.as_ptr(), state) {
+                entry.id = id;
+            }
+        }
+    }
+
+    /// Every live device, in the order they were first created.
+    pub fn devices(&self) -> Vec<DeviceInfo> {
+        let entries = self.entries.lock().unwrap();
+        let mut devices: Vec<DeviceInfo> = Vec::new();
+        for entry in entries.iter() {
+            let name = match entry.state.upgrade() {
+                Some(state) => state.name(),
+                None => continue,
+            };
+            let same = |device: &&mut DeviceInfo| {
+                (device.device_type, device.id) == (entry.device_type, entry.id)
+            };
+            match devices.iter_mut().find(same) {
+                Some(device) => device.name = device.name.or(name),
+                None => devices.push(DeviceInfo {
+                    device_type: entry.device_type,
+                    id: entry.id,
+                    name,
+                }),
+            }
+        }
+        devices
+    }
+    /// The live device named `name`, if there is one.
+    pub fn find(&self, name: &str) -> Option<DeviceInfo> {
+        self.devices()
+            .into_iter()
+            .find(|device| device.name == Some(name))
+    }
+    /// Whether the `device_type` device numbered `id` was created optional and
+    /// isn't on the bus, as of its last status read.
+    pub fn is_absent(&self, device_type: DeviceType, id: i32) -> bool {
+        let entries = self.entries.lock().unwrap();
+        entries
+            .iter()
+            .filter(|entry| (entry.device_type, entry.id) == (device_type, id))
+            .filter_map(|entry| entry.state.upgrade())
+            .any(|state| state.presence.is_absent())
+    }
+    /// The state of a live `device_type` device numbered `id`, to reach the
+    /// device without creating another object for it.
+    #[cfg(feature = "bridge")]
+    pub(crate) fn state(&self, device_type: DeviceType, id: i32) -> Option<Arc<DeviceState>> {
+        let entries = self.entries.lock().unwrap();
+        entries
+            .iter()
+            .filter(|entry| (entry.device_type, entry.id) == (device_type, id))
+            .find_map(|entry| entry.state.upgrade())
+    }
+    /// The live `device_type` device numbered `id`, if there is one.
+    pub fn get(&self, device_type: DeviceType, id: i32) -> Option<DeviceInfo> {
+        self.devices()
+            .into_iter()
+            .find(|device| (device.device_type, device.id) == (device_type, id))
+    }
+}
+
+impl fmt::Debug for Registry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.devices()).finish()
+    }
+}
+
+/// The registry of every device created in this process.
+pub fn registry() -> &'static Registry {
+    static REGISTRY: OnceLock<Registry> = OnceLock::new();
+    REGISTRY.get_or_init(|| Registry {
+        entries: Mutex::new(Vec::new()),
+    })
+}
+
+#[test]
+fn registry_tracks_live_devices() {
+    use canifier::CANifier;
+    use motor_control::{BaseMotorController, TalonSRX};
+    let talon = TalonSRX::new(DeviceId::new(36));
+    talon.set_name("registry_arm");
+    let alias = TalonSRX::new(DeviceId::new(36));
+    let canifier = CANifier::new(DeviceId::new(19));
+    let info = registry().find("registry_arm").unwrap();
+    assert_eq!((info.device_type, info.id), (DeviceType::TalonSRX, 36));
+    assert_eq!(info.to_string(), "registry_arm (TalonSRX 36)");
+    let talons = registry().devices();
+    assert_eq!(talons.iter().filter(|device| **device == info).count(), 1);
+    assert_eq!(registry().get(DeviceType::CANifier, 19).unwrap().name, None);
+    drop((talon, alias, canifier));
+    assert_eq!(registry().find("registry_arm"), None);
+    assert_eq!(registry().get(DeviceType::CANifier, 19), None);
+}
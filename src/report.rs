@@ -0,0 +1,386 @@
+//! End-of-match reports of what each device went through.
+//!
+//! A [`MatchRecorder`] samples every device through the match, keeping the
+//! peaks and counts; [`MatchReport::capture`] then combines those with the
+//! sticky faults latched since they were last cleared.
+//!
+//! ```no_run
+//! # use ctre::DeviceId;
+//! # use ctre::motor_control::{BaseMotorController, TalonSRX};
+//! # use ctre::report::{MatchRecorder, MatchReport};
+//! # fn main() -> std::io::Result<()> {
+//! let (left, right) = (TalonSRX::new(DeviceId::new(1)), TalonSRX::new(DeviceId::new(2)));
+//! let mut recorder = MatchRecorder::new();
+//! recorder.add(&left).add(&right);
+//! # let match_over = true;
+//! loop {
+//!     recorder.sample();
+//!     // ...
+//! #   if match_over { break; }
+//! }
+//! MatchReport::capture(&recorder).save("/home/lvuser/match_report.txt")?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`MatchRecorder`]: struct.MatchRecorder.html
+//! [`MatchReport::capture`]: struct.MatchReport.html#method.capture
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use canifier::CANifier;
+use error::{DeviceType, ErrorRecord, ErrorStats};
+use faults::StickyFaultDevice;
+use motor_control::BaseMotorController;
+use sensors::pigeon::PigeonIMU;
+
+/// Devices a [`MatchRecorder`] can track.
+///
+/// [`MatchRecorder`]: struct.MatchRecorder.html
+pub trait MatchDevice: StickyFaultDevice {
+    /// Output current in amps, or `None` if the device has none or it couldn't be read.
+    fn sample_current(&self) -> Option<f64>;
+    /// Temperature in °C, or `None` if the device has none or it couldn't be read.
+    fn sample_temperature(&self) -> Option<f64>;
+    /// Whether the device has reset since this was last called.
+    fn take_reset(&self) -> bool;
+    /// The device's recent errors, oldest first.  `records` is cleared first.
+    fn errors_into(&self, records: &mut Vec<ErrorRecord>);
+    /// How many calls to the device have failed, by kind of error.
+    fn error_stats(&self) -> ErrorStats;
+}
+
+impl<T: BaseMotorController> MatchDevice for T {
+    fn sample_current(&self) -> Option<f64> {
+        self.get_output_current().ok()
+    }
+    fn sample_temperature(&self) -> Option<f64> {
+        self.get_temperature().ok()
+    }
+    fn take_reset(&self) -> bool {
+        self.has_reset_occurred().unwrap_or(false)
+    }
+    fn errors_into(&self, records: &mut Vec<ErrorRecord>) {
+        self.recent_errors_into(records)
+    }
+    fn error_stats(&self) -> ErrorStats {
+        self.error_stats()
+    }
+}
+
+impl MatchDevice for CANifier {
+    fn sample_current(&self) -> Option<f64> {
+        None
+    }
+    fn sample_temperature(&self) -> Option<f64> {
+        None
+    }
+    fn take_reset(&self) -> bool {
+        self.has_reset_occurred().unwrap_or(false)
+    }
+    fn errors_into(&self, records: &mut Vec<ErrorRecord>) {
+        self.recent_errors_into(records)
+    }
+    fn error_stats(&self) -> ErrorStats {
+        self.error_stats()
+    }
+}
+
+impl MatchDevice for PigeonIMU {
+    fn sample_current(&self) -> Option<f64> {
+        None
+    }
+    fn sample_temperature(&self) -> Option<f64> {
+        self.get_temp().ok()
+    }
+    fn take_reset(&self) -> bool {
+        self.has_reset_occurred().unwrap_or(false)
+    }
+    fn errors_into(&self, records: &mut Vec<ErrorRecord>) {
+        self.recent_errors_into(records)
+    }
+    fn error_stats(&self) -> ErrorStats {
+        self.error_stats()
+    }
+}
+
+/// Peaks and counts for one device, as kept by a `MatchRecorder`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct DeviceStats {
+    max_current: Option<f64>,
+    max_temperature: Option<f64>,
+    resets: u32,
+    can_errors: u32,
+    /// The device's error counts when it was added or the recorder was reset.
+    error_stats_at_start: ErrorStats,
+    /// Errors up to this time have been counted.
+    errors_seen_until: Instant,
+}
+
+fn max(peak: Option<f64>, value: Option<f64>) -> Option<f64> {
+    match (peak, value) {
+        (Some(peak), Some(value)) => Some(peak.max(value)),
+        (peak, value) => peak.or(value),
+    }
+}
+
+/**
+ * Samples a set of devices through a match, keeping each one's peak current
+ * and temperature and counting its resets and failed calls.
+ *
+ * Call [`sample`] periodically, e.g. once per robot loop.  Failed calls are
+ * counted from each device's recent error history, which holds the last 16,
+ * so sample often enough that more than that can't fail in between.
+ *
+ * [`sample`]: #method.sample
+ */
+pub struct MatchRecorder<'a> {
+    devices: Vec<(&'a dyn MatchDevice, DeviceStats)>,
+    started: Instant,
+    errors: Vec<ErrorRecord>,
+}
+
+impl<'a> MatchRecorder<'a> {
+    pub fn new() -> Self {
+        MatchRecorder {
+            devices: Vec::new(),
+            started: Instant::now(),
+            errors: Vec::new(),
+        }
+    }
+    /// Adds a device.  Only errors from after it is added are counted.
+    pub fn add(&mut self, device: &'a dyn MatchDevice) -> &mut Self {
+        let stats = DeviceStats {
+            max_current: None,
+            max_temperature: None,
+            resets: 0,
+            can_errors: 0,
+            error_stats_at_start: device.error_stats(),
+            errors_seen_until: Instant::now(),
+        };
+        self.devices.push((device, stats));
+        self
+    }
+    /// Reads every device, updating its peaks and counts.
+    pub fn sample(&mut self) {
+        for &mut (device, ref mut stats) in &mut self.devices {
+            stats.max_current = max(stats.max_current, device.sample_current());
+            stats.max_temperature = max(stats.max_temperature, device.sample_temperature());
+            if device.take_reset() {
+                stats.resets += 1;
+            }
+            device.errors_into(&mut self.errors);
+            for record in &self.errors {
+                if record.timestamp > stats.errors_seen_until {
+                    stats.can_errors += 1;
+                    stats.errors_seen_until = record.timestamp;
+                }
+            }
+        }
+    }
+    /// Forgets the peaks and counts, e.g. when the next match starts.
+    pub fn reset(&mut self) {
+        let now = Instant::now();
+        self.started = now;
+        for &mut (device, ref mut stats) in &mut self.devices {
+            *stats = DeviceStats {
+                max_current: None,
+                max_temperature: None,
+                resets: 0,
+                can_errors: 0,
+                error_stats_at_start: device.error_stats(),
+                errors_seen_until: now,
+            };
+        }
+    }
+}
+
+impl<'a> Default for MatchRecorder<'a> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> fmt::Debug for MatchRecorder<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("MatchRecorder")
+            .field("devices", &self.devices.len())
+            .field("started", &self.started)
+            .finish()
+    }
+}
+
+/// What one device went through in a match.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct DeviceReport {
+    pub device_type: DeviceType,
+    pub device_id: i32,
+    /// Label of the device, if it has one.
+    pub name: Option<&'static str>,
+    /// The raw sticky fault bits, or `None` if they couldn't be read.
+    pub sticky_faults: Option<i32>,
+    /// Names of the latched sticky faults, for devices whose fault bits are known.
+    pub sticky_fault_names: Vec<&'static str>,
+    /// Peak output current in amps, if the device has one and it was read.
+    pub max_current: Option<f64>,
+    /// Peak temperature in °C, if the device has one and it was read.
+    pub max_temperature: Option<f64>,
+    /// How many times the device reset.
+    pub resets: u32,
+    /// How many calls to the device failed.
+    pub can_errors: u32,
+    /// The failed calls by kind of error.  Unlike `can_errors`, this is counted
+    /// however rarely the recorder samples.
+    pub error_stats: ErrorStats,
+}
+
+impl DeviceReport {
+    /// True iff anything went wrong with the device.
+    pub fn has_problems(&self) -> bool {
+        self.sticky_faults != Some(0) || self.resets > 0 || self.can_errors > 0
+    }
+}
+
+/// e.g. `intake (TalonSRX 3): max 41.5 A, max 62 °C, 0 resets, 2 CAN errors
+/// (2 timeouts, 0 TX full, 0 stale frames, 0 other), sticky faults: under_voltage`
+impl fmt::Display for DeviceReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.name {
+            Some(name) => write!(f, "{} ({} {}):", name, self.device_type, self.device_id)?,
+            None => write!(f, "{} {}:", self.device_type, self.device_id)?,
+        }
+        if let Some(amps) = self.max_current {
+            write!(f, " max {:.1} A,", amps)?;
+        }
+        if let Some(celsius) = self.max_temperature {
+            write!(f, " max {:.0} °C,", celsius)?;
+        }
+        write!(f, " {} resets, {} CAN errors", self.resets, self.can_errors)?;
+        if self.error_stats.total() > 0 {
+            write!(f, " ({})", self.error_stats)?;
+        }
+        f.write_str(", sticky faults: ")?;
+        match self.sticky_faults {
+            None => f.write_str("unknown"),
+            Some(0) => f.write_str("none"),
+            Some(_) if !self.sticky_fault_names.is_empty() => {
+                f.write_str(&self.sticky_fault_names.join(", "))
+            }
+            Some(bits) => write!(f, "{:#x}", bits),
+        }
+    }
+}
+
+/// What every device went through in a match.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct MatchReport {
+    /// Time from the recorder starting (or being reset) to the capture.
+    pub duration: Duration,
+    pub devices: Vec<DeviceReport>,
+}
+
+impl MatchReport {
+    /**
+     * Reports on the devices of `recorder`, reading their sticky faults now.
+     *
+     * Sticky faults latch until cleared, so clear them when the robot program
+     * starts (see `StickyFaultPolicy`) for the report to only cover this run.
+     */
+    pub fn capture(recorder: &MatchRecorder) -> MatchReport {
+        let devices = recorder
+            .devices
+            .iter()
+            .map(|&(device, stats)| {
+                let (device_type, device_id) = device.device();
+                let sticky = device.read_sticky_faults().ok();
+                DeviceReport {
+                    device_type,
+                    device_id,
+                    name: device.device_name(),
+                    sticky_faults: sticky.map(|record| record.bits),
+                    sticky_fault_names: sticky
+                        .and_then(|record| record.fault_names())
+                        .unwrap_or_default(),
+                    max_current: stats.max_current,
+                    max_temperature: stats.max_temperature,
+                    resets: stats.resets,
+                    can_errors: stats.can_errors,
+                    error_stats: device.error_stats().since(&stats.error_stats_at_start),
+                }
+            })
+            .collect();
+        MatchReport {
+            duration: recorder.started.elapsed(),
+            devices,
+        }
+    }
+    /// Writes the report to `path` as text, replacing the file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let path = path.as_ref();
+        // Write then rename, so losing power mid-write doesn't leave half a report.
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, self.to_string())?;
+        fs::rename(&tmp, path)
+    }
+}
+
+/// One line per device, devices with problems first.
+impl fmt::Display for MatchReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Match report ({:.1} s)", self.duration.as_secs_f64())?;
+        let problems = self.devices.iter().filter(|device| device.has_problems());
+        let fine = self.devices.iter().filter(|device| !device.has_problems());
+        for device in problems.chain(fine) {
+            writeln!(f, "{}", device)?;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn recorder_counts_errors_and_peaks() {
+    use motor_control::TalonSRX;
+    use DeviceId;
+    let talon = TalonSRX::new(DeviceId::new(28));
+    let canifier = CANifier::new(DeviceId::new(18));
+    // Errors from before the recorder are not this match's.
+    talon.normalize_feedback_to(8192., 0, 0).unwrap_err();
+    let mut recorder = MatchRecorder::new();
+    recorder.add(&talon).add(&canifier);
+    recorder.sample();
+    talon.normalize_feedback_to(8192., 0, 0).unwrap_err();
+    talon.normalize_feedback_to(8192., 0, 0).unwrap_err();
+    recorder.sample();
+    recorder.sample();
+
+    let report = MatchReport::capture(&recorder);
+    let (talon_report, canifier_report) = (&report.devices[0], &report.devices[1]);
+    assert_eq!(talon_report.can_errors, 2);
+    assert_eq!(talon_report.error_stats.other, 2);
+    assert_eq!(talon_report.error_stats.total(), 2);
+    assert_eq!(talon_report.max_temperature, Some(25.));
+    assert_eq!(canifier_report.max_current, None);
+    assert_eq!(
+        canifier_report.to_string(),
+        "CANifier 18: 0 resets, 0 CAN errors, sticky faults: none"
+    );
+    // The talon had errors, so it is listed first.
+    let text = report.to_string();
+    assert!(text
+        .lines()
+        .nth(1)
+        .unwrap()
+        .starts_with("TalonSRX 28: max 0.0 A, max 25 °C,"));
+
+    recorder.reset();
+    recorder.sample();
+    let after_reset = MatchReport::capture(&recorder);
+    assert_eq!(after_reset.devices[0].can_errors, 0);
+    assert_eq!(after_reset.devices[0].error_stats, ErrorStats::default());
+}
@@ -0,0 +1,687 @@
+//! Loading a robot's devices and their configuration from a file.
+//!
+//! A TOML file lists each device, and optionally a motor controller's settings:
+//!
+//! ```toml
+//! [[devices]]
+//! type = "TalonSRX"
+//! id = 1
+//! name = "left_master"
+//!
+//! [devices.motor]
+//! open_loop_ramp = 0.2
+//! peak_output = [1.0, -1.0]
+//!
+//! [[devices.motor.slots]]
+//! slot = 0
+//! kp = 0.4
+//! kf = 0.05
+//!
+//! [[devices]]
+//! type = "PigeonIMU"
+//! id = 0
+//! name = "imu"
+//! ```
+//!
+//! JSON files (ending in `.json`) have the same structure.
+
+use std::collections::BTreeSet;
+use std::convert::TryFrom;
+use std::error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use serde::de::DeserializeOwned;
+
+use config::{ConfigBuilder, ConfigContext, ConfigErrors};
+use device::DeviceContext;
+use error::DeviceType;
+use id::DeviceId;
+use manifest::{DeviceManifest, DeviceSpec, ManifestError, RobotDevices};
+use motor_control::{BaseMotorController, Slot};
+
+/// The contents of a robot config file.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RobotConfig {
+    #[serde(default)]
+    pub devices: Vec<DeviceConfig>,
+    /// Timeout for each config call, in ms.  Defaults to the thread's `ConfigContext`.
+    #[serde(default)]
+    pub timeout_ms: Option<i32>,
+}
+
+/// One device in a [`RobotConfig`].
+///
+/// [`RobotConfig`]: struct.RobotConfig.html
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DeviceConfig {
+    #[serde(rename = "type")]
+    pub device_type: DeviceType,
+    pub id: DeviceId,
+    pub name: String,
+    #[serde(default)]
+    pub bus: Option<String>,
+    /// Settings for a motor controller.
+    #[serde(default)]
+    pub motor: Option<MotorConfig>,
+}
+
+/// Motor controller settings.  Settings left out aren't changed.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MotorConfig {
+    pub open_loop_ramp: Option<f64>,
+    pub closed_loop_ramp: Option<f64>,
+    /// Forward and reverse peak outputs.
+    pub peak_output: Option<(f64, f64)>,
+    /// Forward and reverse nominal outputs.
+    pub nominal_output: Option<(f64, f64)>,
+    pub neutral_deadband: Option<f64>,
+    pub voltage_comp_saturation: Option<f64>,
+    /// Forward and reverse soft limit thresholds.  Setting these enables both limits.
+    pub soft_limits: Option<(i32, i32)>,
+    pub motion_cruise_velocity: Option<i32>,
+    pub motion_acceleration: Option<i32>,
+    #[serde(default)]
+    pub slots: Vec<SlotConfig>,
+}
+
+/// Gains for one slot of a [`MotorConfig`].
+///
+/// [`MotorConfig`]: struct.MotorConfig.html
+#[derive(Debug, Copy, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SlotConfig {
+    /// [0,3]
+    pub slot: i32,
+    pub kp: Option<f64>,
+    pub ki: Option<f64>,
+    pub kd: Option<f64>,
+    pub kf: Option<f64>,
+    pub integral_zone: Option<i32>,
+    pub allowable_closedloop_error: Option<i32>,
+    pub max_integral_accumulator: Option<f64>,
+    pub closed_loop_peak_output: Option<f64>,
+    pub closed_loop_period_ms: Option<i32>,
+}
+
+/// Why a robot config couldn't be loaded or applied.
+#[derive(Debug)]
+pub enum RobotConfigError {
+    Io(io::Error),
+    /// The file isn't valid TOML or JSON, or doesn't match `RobotConfig`.
+    Parse(String),
+    /// The file parsed, but describes something that can't be set up.
+    Invalid(String),
+    Manifest(ManifestError),
+    /// Config calls failed on the named device.
+    Config(String, ConfigErrors),
+}
+
+impl fmt::Display for RobotConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            RobotConfigError::Io(ref err) => write!(f, "couldn't read robot config: {}", err),
+            RobotConfigError::Parse(ref msg) => write!(f, "couldn't parse robot config: {}", msg),
+            RobotConfigError::Invalid(ref msg) => write!(f, "invalid robot config: {}", msg),
+            RobotConfigError::Manifest(ref err) => err.fmt(f),
+            RobotConfigError::Config(ref name, ref errors) => write!(f, "{}: {}", name, errors),
+        }
+    }
+}
+
+impl error::Error for RobotConfigError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            RobotConfigError::Io(ref err) => Some(err),
+            RobotConfigError::Manifest(ref err) => Some(err),
+            RobotConfigError::Config(_, ref errors) => Some(errors),
+            RobotConfigError::Parse(_) | RobotConfigError::Invalid(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for RobotConfigError {
+    fn from(err: io::Error) -> Self {
+        RobotConfigError::Io(err)
+    }
+}
+
+impl From<ManifestError> for RobotConfigError {
+    fn from(err: ManifestError) -> Self {
+        RobotConfigError::Manifest(err)
+    }
+}
+
+/**
+ * Device names and buses for a manifest, which needs them to live as long as
+ * the program.  Each distinct name is leaked once, so reloading a config
+ * doesn't leak it again.
+ */
+fn intern(name: &str) -> &'static str {
+    static NAMES: Mutex<BTreeSet<&'static str>> = Mutex::new(BTreeSet::new());
+    let mut names = NAMES.lock().unwrap_or_else(|err| err.into_inner());
+    if let Some(&interned) = names.get(name) {
+        return interned;
+    }
+    let interned: &'static str = Box::leak(name.to_owned().into_boxed_str());
+    names.insert(interned);
+    interned
+}
+
+impl RobotConfig {
+    pub fn from_toml(text: &str) -> Result<Self, RobotConfigError> {
+        toml::from_str(text).map_err(|err| RobotConfigError::Parse(err.to_string()))
+    }
+    pub fn from_json(text: &str) -> Result<Self, RobotConfigError> {
+        serde_json::from_str(text).map_err(|err| RobotConfigError::Parse(err.to_string()))
+    }
+    /// Reads a config file: JSON if its extension is `.json`, TOML otherwise.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, RobotConfigError> {
+        read_file(path.as_ref())
+    }
+
+    /// Checks that settings are only given for motor controllers, with slots in range.
+    pub fn validate(&self) -> Result<(), RobotConfigError> {
+        for device in &self.devices {
+            let motor = match device.motor {
+                Some(ref motor) => motor,
+                None => continue,
+            };
+            match device.device_type {
+                DeviceType::TalonSRX | DeviceType::VictorSPX => {}
+                DeviceType::PigeonIMU | DeviceType::CANifier => {
+                    return Err(RobotConfigError::Invalid(format!(
+                        "{} is a {}, not a motor controller",
+                        device.name, device.device_type
+                    )))
+                }
+            }
+            motor.validate_slots(&device.name)?;
+        }
+        Ok(())
+    }
+
+    /// A manifest declaring the config's devices.
+    pub fn manifest(&self) -> DeviceManifest {
+        let mut manifest = DeviceManifest::new();
+        for device in &self.devices {
+            let mut spec = DeviceSpec::new(device.device_type, device.id, intern(&device.name));
+            if let Some(ref bus) = device.bus {
+                spec = spec.on_bus(intern(bus));
+            }
+            manifest.add_spec(spec);
+        }
+        manifest
+    }
+
+    /// Applies each motor controller's settings to the matching device in `devices`.
+    pub fn apply(&self, devices: &RobotDevices) -> Result<(), RobotConfigError> {
+        self.validate()?;
+        for device in &self.devices {
+            if let Some(ref motor) = device.motor {
+                self.apply_motor(devices, &device.name, motor, None)?;
+            }
+        }
+        Ok(())
+    }
+
+    /**
+     * Applies only the settings that differ from `old`, verifying each one.
+     *
+     * The devices must be the same as in `old`; changing them needs a restart.
+     * Settings removed from the config are left as they are.
+     */
+    pub fn apply_changes(
+        &self,
+        old: &RobotConfig,
+        devices: &RobotDevices,
+    ) -> Result<(), RobotConfigError> {
+        self.validate()?;
+        if self.manifest_specs() != old.manifest_specs() {
+            return Err(RobotConfigError::Invalid(
+                "devices can't be added, removed or changed while running".to_owned(),
+            ));
+        }
+        for (device, old_device) in self.devices.iter().zip(&old.devices) {
+            let motor = match device.motor {
+                Some(ref motor) => motor,
+                None => continue,
+            };
+            let changes = match old_device.motor {
+                Some(ref old_motor) => motor.changed_from(old_motor),
+                None => motor.clone(),
+            };
+            if changes != MotorConfig::default() {
+                self.apply_motor(devices, &device.name, &changes, Some(true))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn manifest_specs(&self) -> Vec<(DeviceType, DeviceId, &str, Option<&str>)> {
+        self.devices
+            .iter()
+            .map(|device| {
+                (
+                    device.device_type,
+                    device.id,
+                    &*device.name,
+                    device.bus.as_deref(),
+                )
+            })
+            .collect()
+    }
+
+    fn apply_motor(
+        &self,
+        devices: &RobotDevices,
+        name: &str,
+        motor: &MotorConfig,
+        verify: Option<bool>,
+    ) -> Result<(), RobotConfigError> {
+        let timeout_ms = self
+            .timeout_ms
+            .unwrap_or_else(|| ConfigContext::current().default_timeout_ms);
+        configure_motor(devices, name, motor, timeout_ms, verify)
+    }
+}
+
+/// Reads a TOML file, or a JSON one if its extension is `.json`.
+pub(crate) fn read_file<T: DeserializeOwned>(path: &Path) -> Result<T, RobotConfigError> {
+    let text = fs::read_to_string(path)?;
+    let parsed = if path.extension().is_some_and(|ext| ext == "json") {
+        serde_json::from_str(&text).map_err(|err| err.to_string())
+    } else {
+        toml::from_str(&text).map_err(|err| err.to_string())
+    };
+    parsed.map_err(RobotConfigError::Parse)
+}
+
+/// Applies `motor` to the motor controller named `name` in `devices`.
+pub(crate) fn configure_motor(
+    devices: &RobotDevices,
+    name: &str,
+    motor: &MotorConfig,
+    timeout_ms: i32,
+    verify: Option<bool>,
+) -> Result<(), RobotConfigError> {
+    motor.validate_slots(name)?;
+    let result = if let Some(talon) = devices.talon_srx(name) {
+        motor.with_verify(talon, verify).apply(timeout_ms)
+    } else if let Some(victor) = devices.victor_spx(name) {
+        motor.with_verify(victor, verify).apply(timeout_ms)
+    } else {
+        return Err(RobotConfigError::Invalid(format!(
+            "no motor controller named {}",
+            name
+        )));
+    };
+    result.map_err(|errors| RobotConfigError::Config(name.to_owned(), errors))
+}
+
+impl MotorConfig {
+    /// The settings as a `ConfigBuilder` for `motor`, in the order they're declared.
+    pub fn builder<'a, M: BaseMotorController>(&self, motor: &'a M) -> ConfigBuilder<'a, M> {
+        let mut builder = motor.configure();
+        if let Some(seconds) = self.open_loop_ramp {
+            builder = builder.open_loop_ramp(seconds);
+        }
+        if let Some(seconds) = self.closed_loop_ramp {
+            builder = builder.closed_loop_ramp(seconds);
+        }
+        if let Some((forward, reverse)) = self.peak_output {
+            builder = builder.peak_output(forward, reverse);
+        }
+        if let Some((forward, reverse)) = self.nominal_output {
+            builder = builder.nominal_output(forward, reverse);
+        }
+        if let Some(deadband) = self.neutral_deadband {
+            builder = builder.neutral_deadband(deadband);
+        }
+        if let Some(voltage) = self.voltage_comp_saturation {
+            builder = builder.voltage_comp_saturation(voltage);
+        }
+        if let Some((forward, reverse)) = self.soft_limits {
+            builder = builder.soft_limits(forward, reverse);
+        }
+        if let Some(velocity) = self.motion_cruise_velocity {
+            builder = builder.motion_cruise_velocity(velocity);
+        }
+        if let Some(acceleration) = self.motion_acceleration {
+            builder = builder.motion_acceleration(acceleration);
+        }
+        for slot in &self.slots {
+            builder = slot.add_to(builder);
+        }
+        builder
+    }
+    fn with_verify<'a, M>(&self, motor: &'a M, verify: Option<bool>) -> ConfigBuilder<'a, M>
+    where
+        M: BaseMotorController,
+    {
+        match verify {
+            Some(verify) => self.builder(motor).verify(verify),
+            None => self.builder(motor),
+        }
+    }
+    /// Applies the settings to `motor`, unless a slot is out of range.
+    pub fn apply_to<M: BaseMotorController>(
+        &self,
+        motor: &M,
+        timeout_ms: i32,
+    ) -> Result<(), RobotConfigError> {
+        let name = motor.device_info().to_string();
+        self.validate_slots(&name)?;
+        self.builder(motor)
+            .apply(timeout_ms)
+            .map_err(|errors| RobotConfigError::Config(name, errors))
+    }
+
+    /// Checks that each slot is in [0,3], naming the first that isn't as an
+    /// entry of the settings for `name`.
+    fn validate_slots(&self, name: &str) -> Result<(), RobotConfigError> {
+        for (i, slot) in self.slots.iter().enumerate() {
+            if Slot::try_from(slot.slot).is_err() {
+                return Err(RobotConfigError::Invalid(format!(
+                    "{}: motor.slots[{}] has slot {}; slots are 0 to 3",
+                    name, i, slot.slot
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// The settings that differ from `old`.  Settings only in `old` are left out.
+    pub fn changed_from(&self, old: &MotorConfig) -> MotorConfig {
+        let slots = self
+            .slots
+            .iter()
+            .map(|slot| {
+                let old_slot = old.slots.iter().find(|old| old.slot == slot.slot);
+                slot.changed_from(&old_slot.copied().unwrap_or_default())
+            })
+            .filter(|slot| {
+                *slot
+                    != SlotConfig {
+                        slot: slot.slot,
+                        ..SlotConfig::default()
+                    }
+            })
+            .collect();
+        MotorConfig {
+            open_loop_ramp: changed(self.open_loop_ramp, old.open_loop_ramp),
+            closed_loop_ramp: changed(self.closed_loop_ramp, old.closed_loop_ramp),
+            peak_output: changed(self.peak_output, old.peak_output),
+            nominal_output: changed(self.nominal_output, old.nominal_output),
+            neutral_deadband: changed(self.neutral_deadband, old.neutral_deadband),
+            voltage_comp_saturation: changed(
+                self.voltage_comp_saturation,
+                old.voltage_comp_saturation,
+            ),
+            soft_limits: changed(self.soft_limits, old.soft_limits),
+            motion_cruise_velocity: changed(
+                self.motion_cruise_velocity,
+                old.motion_cruise_velocity,
+            ),
+            motion_acceleration: changed(self.motion_acceleration, old.motion_acceleration),
+            slots,
+        }
+    }
+}
+
+fn changed<T: PartialEq>(new: Option<T>, old: Option<T>) -> Option<T> {
+    if new == old {
+        None
+    } else {
+        new
+    }
+}
+
+impl SlotConfig {
+    /// The gains that differ from `old`.
+    fn changed_from(&self, old: &SlotConfig) -> SlotConfig {
+        SlotConfig {
+            slot: self.slot,
+            kp: changed(self.kp, old.kp),
+            ki: changed(self.ki, old.ki),
+            kd: changed(self.kd, old.kd),
+            kf: changed(self.kf, old.kf),
+            integral_zone: changed(self.integral_zone, old.integral_zone),
+            allowable_closedloop_error: changed(
+                self.allowable_closedloop_error,
+                old.allowable_closedloop_error,
+            ),
+            max_integral_accumulator: changed(
+                self.max_integral_accumulator,
+                old.max_integral_accumulator,
+            ),
+            closed_loop_peak_output: changed(
+                self.closed_loop_peak_output,
+                old.closed_loop_peak_output,
+            ),
+            closed_loop_period_ms: changed(self.closed_loop_period_ms, old.closed_loop_period_ms),
+        }
+    }
+    /// A `slot` out of [0,3] fails each setting when applied.
+    /// `RobotConfig::validate` checks this.
+    fn add_to<'a, M>(&self, mut builder: ConfigBuilder<'a, M>) -> ConfigBuilder<'a, M>
+    where
+        M: BaseMotorController,
+    {
+        let slot = self.slot;
+        if let Some(kp) = self.kp {
+            builder = builder.kp(slot, kp);
+        }
+        if let Some(ki) = self.ki {
+            builder = builder.ki(slot, ki);
+        }
+        if let Some(kd) = self.kd {
+            builder = builder.kd(slot, kd);
+        }
+        if let Some(kf) = self.kf {
+            builder = builder.kf(slot, kf);
+        }
+        if let Some(izone) = self.integral_zone {
+            builder = builder.integral_zone(slot, izone);
+        }
+        if let Some(error) = self.allowable_closedloop_error {
+            builder = builder.allowable_closedloop_error(slot, error);
+        }
+        if let Some(iaccum) = self.max_integral_accumulator {
+            builder = builder.max_integral_accumulator(slot, iaccum);
+        }
+        if let Some(percent_out) = self.closed_loop_peak_output {
+            builder = builder.closed_loop_peak_output(slot, percent_out);
+        }
+        if let Some(loop_time_ms) = self.closed_loop_period_ms {
+            builder = builder.closed_loop_period(slot, loop_time_ms);
+        }
+        builder
+    }
+}
+
+/**
+ * Creates, names and configures the devices listed in the config file at `path`.
+ * See the [module documentation](index.html) for the file format.
+ *
+ * The devices are checked as by `DeviceManifest::build`, then each motor
+ * controller's settings are applied.
+ */
+pub fn load_robot_config<P: AsRef<Path>>(path: P) -> Result<RobotDevices, RobotConfigError> {
+    let config = RobotConfig::load(path)?;
+    config.validate()?;
+    let devices = config.manifest().build()?;
+    config.apply(&devices)?;
+    Ok(devices)
+}
+
+/**
+ * Re-applies a config file's settings when the file changes, for tuning ramps,
+ * limits and gains without redeploying.  Only meant for practice; a file
+ * changing mid-match would reconfigure the robot.
+ *
+ * Call [`poll`] periodically, e.g. once a second.  Only settings that changed
+ * are applied, and each is read back to check it was applied.
+ *
+ * [`poll`]: #method.poll
+ */
+#[derive(Debug)]
+pub struct ConfigWatcher<'a> {
+    path: PathBuf,
+    devices: &'a RobotDevices,
+    config: RobotConfig,
+    /// Modification time and length of the file when it was last read.
+    stamp: Option<(SystemTime, u64)>,
+}
+
+impl<'a> ConfigWatcher<'a> {
+    /// The file's modification time and length, to tell when it changes.
+    fn stamp(&self) -> Result<(SystemTime, u64), RobotConfigError> {
+        let metadata = fs::metadata(&self.path)?;
+        Ok((metadata.modified()?, metadata.len()))
+    }
+    /// The config as last applied.
+    pub fn config(&self) -> &RobotConfig {
+        &self.config
+    }
+    /**
+     * Reloads the file if it has changed and applies the changed settings.
+     * Returns whether the file had changed.
+     *
+     * If the new file can't be loaded or applied, the error is returned and the
+     * file isn't read again until it next changes.
+     */
+    pub fn poll(&mut self) -> Result<bool, RobotConfigError> {
+        let stamp = self.stamp()?;
+        if self.stamp == Some(stamp) {
+            return Ok(false);
+        }
+        self.stamp = Some(stamp);
+        let config = RobotConfig::load(&self.path)?;
+        let result = config.apply_changes(&self.config, self.devices);
+        if result.is_ok() {
+            self.config = config;
+        }
+        result.map(|()| true)
+    }
+}
+
+/**
+ * Watches the config file at `path` for changes to apply to `devices`,
+ * e.g. those from [`load_robot_config`].  See [`ConfigWatcher`].
+ *
+ * The file's current settings are taken as already applied.
+ *
+ * [`load_robot_config`]: fn.load_robot_config.html
+ * [`ConfigWatcher`]: struct.ConfigWatcher.html
+ */
+pub fn watch_config<P: AsRef<Path>>(
+    path: P,
+    devices: &RobotDevices,
+) -> Result<ConfigWatcher<'_>, RobotConfigError> {
+    let mut watcher = ConfigWatcher {
+        path: path.as_ref().to_owned(),
+        devices,
+        config: RobotConfig::default(),
+        stamp: None,
+    };
+    watcher.stamp = Some(watcher.stamp()?);
+    watcher.config = RobotConfig::load(&watcher.path)?;
+    Ok(watcher)
+}
+
+#[test]
+fn robot_config_configures_devices() {
+    use ParamEnum;
+    let config = RobotConfig::from_toml(
+        r#"
+        [[devices]]
+        type = "TalonSRX"
+        id = 17
+        name = "shooter"
+        [devices.motor]
+        open_loop_ramp = 0.5
+        [[devices.motor.slots]]
+        slot = 1
+        kp = 0.25
+
+        [[devices]]
+        type = "CANifier"
+        id = 17
+        name = "leds"
+        "#,
+    )
+    .unwrap();
+    let devices = config.manifest().build().unwrap();
+    config.apply(&devices).unwrap();
+    let shooter = devices.talon_srx("shooter").unwrap();
+    let get = |param, ordinal| shooter.config_get_parameter(param, ordinal, 0).unwrap();
+    assert_eq!(get(ParamEnum::OpenloopRamp, 0), 0.5);
+    assert_eq!(get(ParamEnum::ProfileParamSlot_P, 1), 0.25);
+    assert_eq!(shooter.name(), Some("shooter"));
+    // Names are only leaked once, however often the manifest is rebuilt.
+    let name = |manifest: DeviceManifest| manifest.specs()[0].name.as_ptr();
+    assert_eq!(name(config.manifest()), name(config.manifest()));
+
+    let json = r#"{"devices": [{"type": "PigeonIMU", "id": 1, "name": "imu", "motor": {}}]}"#;
+    let invalid = RobotConfig::from_json(json).unwrap().validate();
+    assert!(matches!(invalid, Err(RobotConfigError::Invalid(_))));
+    assert!(RobotConfig::from_toml("[[devices]]\ntype = \"Falcon\"").is_err());
+
+    // A slot out of range is an error naming the entry, not a panic.
+    let motor = MotorConfig {
+        slots: vec![SlotConfig {
+            slot: 7,
+            kp: Some(1.),
+            ..SlotConfig::default()
+        }],
+        ..MotorConfig::default()
+    };
+    let invalid = motor.apply_to(shooter, 0).unwrap_err().to_string();
+    assert_eq!(
+        invalid,
+        "invalid robot config: shooter (TalonSRX 17): motor.slots[0] has slot 7; slots are 0 to 3"
+    );
+}
+
+#[test]
+fn watcher_applies_changed_settings() {
+    use std::time::Duration;
+    use ParamEnum;
+    let name = format!("ctre_robot_config_{}.toml", ::std::process::id());
+    let path = ::std::env::temp_dir().join(name);
+    let write = |ramp: f64, kp: f64, age: u64| {
+        let text = format!(
+            "[[devices]]\ntype = \"TalonSRX\"\nid = 18\nname = \"elevator\"\n\
+             [devices.motor]\nclosed_loop_ramp = {}\n\
+             [[devices.motor.slots]]\nslot = 0\nkp = {}\n",
+            ramp, kp
+        );
+        fs::write(&path, text).unwrap();
+        let file = fs::File::options().write(true).open(&path).unwrap();
+        file.set_modified(SystemTime::now() - Duration::from_secs(age))
+            .unwrap();
+    };
+    write(0.25, 1.0, 10);
+    let devices = load_robot_config(&path).unwrap();
+    let mut watcher = watch_config(&path, &devices).unwrap();
+    assert!(!watcher.poll().unwrap());
+
+    write(0.25, 2.0, 0);
+    let elevator = devices.talon_srx("elevator").unwrap();
+    elevator.config_closedloop_ramp(0.5, 0).unwrap();
+    assert!(watcher.poll().unwrap());
+    let result = fs::remove_file(&path);
+    let get = |param| elevator.config_get_parameter(param, 0, 0).unwrap();
+    assert_eq!(get(ParamEnum::ProfileParamSlot_P), 2.0);
+    // The ramp didn't change in the file, so it wasn't re-applied.
+    assert_eq!(get(ParamEnum::ClosedloopRamp), 0.5);
+    result.unwrap();
+}
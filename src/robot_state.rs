@@ -0,0 +1,85 @@
+//! Whether the robot is enabled, and in which mode.
+//!
+//! Helpers that behave differently while the robot is disabled, such as
+//! [`NeutralModeScheduler`], read the robot's state through
+//! [`RobotStateProvider`].  Implement it for your framework's driver station
+//! interface, or use [`SharedRobotState`] and set it from the robot loop.
+//!
+//! [`NeutralModeScheduler`]: ../drive/struct.NeutralModeScheduler.html
+//! [`RobotStateProvider`]: trait.RobotStateProvider.html
+//! [`SharedRobotState`]: struct.SharedRobotState.html
+
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// The mode the robot is in, as reported by the driver station.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+#[repr(u8)]
+pub enum RobotMode {
+    #[default]
+    Disabled,
+    Autonomous,
+    Teleop,
+    Test,
+}
+
+/// A source of the robot's current mode.
+pub trait RobotStateProvider {
+    fn robot_mode(&self) -> RobotMode;
+    fn is_enabled(&self) -> bool {
+        self.robot_mode() != RobotMode::Disabled
+    }
+    fn is_autonomous(&self) -> bool {
+        self.robot_mode() == RobotMode::Autonomous
+    }
+    fn is_test(&self) -> bool {
+        self.robot_mode() == RobotMode::Test
+    }
+}
+
+/// A fixed mode, e.g. for tests.
+impl RobotStateProvider for RobotMode {
+    fn robot_mode(&self) -> RobotMode {
+        *self
+    }
+}
+
+impl<F: Fn() -> RobotMode> RobotStateProvider for F {
+    fn robot_mode(&self) -> RobotMode {
+        self()
+    }
+}
+
+/// A mode set by the robot loop and read from any thread.
+#[derive(Debug, Default)]
+pub struct SharedRobotState(AtomicU8);
+
+impl SharedRobotState {
+    pub fn new(mode: RobotMode) -> Self {
+        SharedRobotState(AtomicU8::new(mode as u8))
+    }
+    pub fn set(&self, mode: RobotMode) {
+        self.0.store(mode as u8, Ordering::Relaxed);
+    }
+}
+
+impl RobotStateProvider for SharedRobotState {
+    fn robot_mode(&self) -> RobotMode {
+        match self.0.load(Ordering::Relaxed) {
+            1 => RobotMode::Autonomous,
+            2 => RobotMode::Teleop,
+            3 => RobotMode::Test,
+            _ => RobotMode::Disabled,
+        }
+    }
+}
+
+#[test]
+fn shared_state_round_trips() {
+    let state = SharedRobotState::default();
+    assert!(!state.is_enabled());
+    for &mode in &[RobotMode::Autonomous, RobotMode::Teleop, RobotMode::Test] {
+        state.set(mode);
+        assert_eq!((state.robot_mode(), state.is_enabled()), (mode, true));
+    }
+    assert!((|| RobotMode::Test).is_test());
+}
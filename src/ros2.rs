@@ -0,0 +1,349 @@
+//! Device telemetry as ROS 2 messages, for teams running ROS 2 on a co-processor.
+//!
+//! [`Ros2Telemetry`] samples a set of devices and builds the standard messages
+//! for them: a `sensor_msgs/msg/JointState` with each motor controller's
+//! selected sensor, and a `diagnostic_msgs/msg/DiagnosticArray` with each
+//! device's health.  The message types here mirror the ROS ones field for
+//! field, so publishing them through whichever ROS 2 client library the team
+//! uses is a matter of implementing [`Ros2Publisher`]; this crate doesn't
+//! depend on one.
+//!
+//! ```no_run
+//! # use ctre::DeviceId;
+//! # use ctre::motor_control::{BaseMotorController, TalonSRX};
+//! # use ctre::ros2::{DiagnosticArray, JointState, Ros2Publisher, Ros2Telemetry};
+//! struct Publisher;
+//! impl Ros2Publisher for Publisher {
+//!     fn publish_joint_state(&mut self, msg: &JointState) {
+//!         // e.g. convert to r2r::sensor_msgs::msg::JointState and publish
+//!     }
+//!     fn publish_diagnostics(&mut self, msg: &DiagnosticArray) {}
+//! }
+//! let arm = TalonSRX::new(DeviceId::new(3));
+//! arm.set_name("arm");
+//! let mut telemetry = Ros2Telemetry::new();
+//! telemetry.add_joint(&arm);
+//! # let match_over = true;
+//! loop {
+//!     telemetry.publish(&mut Publisher);
+//!     // ...
+//! #   if match_over { break; }
+//! }
+//! ```
+//!
+//! Built with the `ros2` feature.
+//!
+//! [`Ros2Telemetry`]: struct.Ros2Telemetry.html
+//! [`Ros2Publisher`]: trait.Ros2Publisher.html
+
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use motor_control::BaseMotorController;
+use report::MatchDevice;
+
+/// `builtin_interfaces/msg/Time`.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Time {
+    pub sec: i32,
+    pub nanosec: u32,
+}
+
+impl Time {
+    /// The current system time.
+    pub fn now() -> Time {
+        let since_epoch = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        Time {
+            sec: since_epoch.as_secs() as i32,
+            nanosec: since_epoch.subsec_nanos(),
+        }
+    }
+}
+
+/// `std_msgs/msg/Header`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Header {
+    pub stamp: Time,
+    pub frame_id: String,
+}
+
+/**
+ * `sensor_msgs/msg/JointState`, with one joint per motor controller.
+ *
+ * Positions and velocities are of the selected sensor, in native units and
+ * native units per 100ms; effort is the output current in amps.  Readings that
+ * fail are NaN.
+ */
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct JointState {
+    pub header: Header,
+    pub name: Vec<String>,
+    pub position: Vec<f64>,
+    pub velocity: Vec<f64>,
+    pub effort: Vec<f64>,
+}
+
+/// The `level` of a `diagnostic_msgs/msg/DiagnosticStatus`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(u8)]
+pub enum DiagnosticLevel {
+    Ok = 0,
+    Warn = 1,
+    Error = 2,
+    Stale = 3,
+}
+
+/// `diagnostic_msgs/msg/KeyValue`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KeyValue {
+    pub key: String,
+    pub value: String,
+}
+
+impl KeyValue {
+    fn new(key: &str, value: impl fmt::Display) -> KeyValue {
+        KeyValue {
+            key: key.to_owned(),
+            value: value.to_string(),
+        }
+    }
+}
+
+/// `diagnostic_msgs/msg/DiagnosticStatus`, for one device.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiagnosticStatus {
+    pub level: DiagnosticLevel,
+    /// The device's label, or its type and id if it has none.
+    pub name: String,
+    pub message: String,
+    /// The device's type and id, e.g. `TalonSRX 3`.
+    pub hardware_id: String,
+    pub values: Vec<KeyValue>,
+}
+
+/// `diagnostic_msgs/msg/DiagnosticArray`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DiagnosticArray {
+    pub header: Header,
+    pub status: Vec<DiagnosticStatus>,
+}
+
+/// Publishes the messages built by [`Ros2Telemetry`] with a ROS 2 client library.
+///
+/// [`Ros2Telemetry`]: struct.Ros2Telemetry.html
+pub trait Ros2Publisher {
+    fn publish_joint_state(&mut self, msg: &JointState);
+    fn publish_diagnostics(&mut self, msg: &DiagnosticArray);
+}
+
+/// Motor controllers, which [`Ros2Telemetry`] reports as joints.
+///
+/// [`Ros2Telemetry`]: struct.Ros2Telemetry.html
+pub trait Joint: MatchDevice {
+    /// Selected sensor position, in native units.
+    fn joint_position(&self) -> Option<f64>;
+    /// Selected sensor velocity, in native units per 100ms.
+    fn joint_velocity(&self) -> Option<f64>;
+}
+
+impl<T: BaseMotorController> Joint for T {
+    fn joint_position(&self) -> Option<f64> {
+        self.get_selected_sensor_position(0).ok().map(f64::from)
+    }
+    fn joint_velocity(&self) -> Option<f64> {
+        self.get_selected_sensor_velocity(0).ok().map(f64::from)
+    }
+}
+
+/**
+ * Builds ROS 2 messages for a set of devices.  See the
+ * [module documentation](index.html).
+ *
+ * Call [`publish`] periodically, e.g. once per robot loop; every call reads
+ * each device, so a few times a second is plenty for diagnostics.
+ *
+ * [`publish`]: #method.publish
+ */
+pub struct Ros2Telemetry<'a> {
+    joints: Vec<&'a dyn Joint>,
+    devices: Vec<&'a dyn MatchDevice>,
+    /// The `frame_id` of the messages' headers.
+    pub frame_id: String,
+}
+
+impl<'a> Ros2Telemetry<'a> {
+    pub fn new() -> Self {
+        Ros2Telemetry {
+            joints: Vec::new(),
+            devices: Vec::new(),
+            frame_id: String::new(),
+        }
+    }
+    /// Adds a motor controller, reported as a joint and in the diagnostics.
+    pub fn add_joint(&mut self, motor: &'a dyn Joint) -> &mut Self {
+        self.joints.push(motor);
+        self.devices.push(motor);
+        self
+    }
+    /// Adds a device to the diagnostics only.
+    pub fn add(&mut self, device: &'a dyn MatchDevice) -> &mut Self {
+        self.devices.push(device);
+        self
+    }
+
+    /// Reads the joints' sensors and outputs.
+    pub fn joint_state(&self) -> JointState {
+        let mut msg = JointState {
+            header: self.header(),
+            ..Default::default()
+        };
+        for &joint in &self.joints {
+            msg.name.push(status_name(joint));
+            msg.position
+                .push(joint.joint_position().unwrap_or(f64::NAN));
+            msg.velocity
+                .push(joint.joint_velocity().unwrap_or(f64::NAN));
+            msg.effort.push(joint.sample_current().unwrap_or(f64::NAN));
+        }
+        msg
+    }
+
+    /// Reads every device's health.
+    pub fn diagnostics(&self) -> DiagnosticArray {
+        DiagnosticArray {
+            header: self.header(),
+            status: self
+                .devices
+                .iter()
+                .map(|&device| diagnose(device))
+                .collect(),
+        }
+    }
+
+    /// Builds and publishes both messages.
+    pub fn publish(&self, publisher: &mut dyn Ros2Publisher) {
+        publisher.publish_joint_state(&self.joint_state());
+        publisher.publish_diagnostics(&self.diagnostics());
+    }
+
+    fn header(&self) -> Header {
+        Header {
+            stamp: Time::now(),
+            frame_id: self.frame_id.clone(),
+        }
+    }
+}
+
+impl<'a> Default for Ros2Telemetry<'a> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> fmt::Debug for Ros2Telemetry<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Ros2Telemetry")
+            .field("joints", &self.joints.len())
+            .field("devices", &self.devices.len())
+            .field("frame_id", &self.frame_id)
+            .finish()
+    }
+}
+
+fn hardware_id(device: &dyn MatchDevice) -> String {
+    let (device_type, id) = device.device();
+    format!("{} {}", device_type, id)
+}
+
+fn status_name(device: &dyn MatchDevice) -> String {
+    match device.device_name() {
+        Some(name) => name.to_owned(),
+        None => hardware_id(device),
+    }
+}
+
+/// Not responding is stale, latched sticky faults or failed calls are a warning.
+fn diagnose(device: &dyn MatchDevice) -> DiagnosticStatus {
+    let mut status = DiagnosticStatus {
+        level: DiagnosticLevel::Ok,
+        name: status_name(device),
+        message: "OK".to_owned(),
+        hardware_id: hardware_id(device),
+        values: Vec::new(),
+    };
+    if !device.is_connected() {
+        status.level = DiagnosticLevel::Stale;
+        status.message = "not responding".to_owned();
+        return status;
+    }
+    if let Some(amps) = device.sample_current() {
+        status.values.push(KeyValue::new("output_current", amps));
+    }
+    if let Some(celsius) = device.sample_temperature() {
+        status.values.push(KeyValue::new("temperature", celsius));
+    }
+    let errors = device.error_stats();
+    status
+        .values
+        .push(KeyValue::new("timeouts", errors.timeouts));
+    status.values.push(KeyValue::new("tx_full", errors.tx_full));
+    status
+        .values
+        .push(KeyValue::new("stale_frames", errors.stale_frames));
+    status
+        .values
+        .push(KeyValue::new("other_errors", errors.other));
+    match device.read_sticky_faults() {
+        Ok(record) if record.has_any_fault() => {
+            status.level = DiagnosticLevel::Warn;
+            status.message = match record.fault_names() {
+                Some(names) => format!("sticky faults: {}", names.join(", ")),
+                None => format!("sticky faults: {:#x}", record.bits),
+            };
+        }
+        Ok(_) => {}
+        Err(err) => {
+            status.level = DiagnosticLevel::Warn;
+            status.message = format!("couldn't read sticky faults: {}", err);
+        }
+    }
+    if status.level == DiagnosticLevel::Ok && errors.total() > 0 {
+        status.level = DiagnosticLevel::Warn;
+        status.message = format!("{} failed calls", errors.total());
+    }
+    status
+}
+
+#[test]
+fn telemetry_builds_messages() {
+    use canifier::CANifier;
+    use motor_control::TalonSRX;
+    use DeviceId;
+    let arm = TalonSRX::new(DeviceId::new(52));
+    arm.set_name("ros2_arm");
+    let canifier = CANifier::new(DeviceId::new(21));
+    let mut telemetry = Ros2Telemetry::new();
+    telemetry.add_joint(&arm).add(&canifier);
+
+    let joints = telemetry.joint_state();
+    assert_eq!(joints.name, ["ros2_arm"]);
+    assert_eq!(joints.position, [0.]);
+    assert_eq!(joints.effort.len(), 1);
+
+    let diagnostics = telemetry.diagnostics();
+    let (arm_status, canifier_status) = (&diagnostics.status[0], &diagnostics.status[1]);
+    assert_eq!(arm_status.level, DiagnosticLevel::Ok);
+    assert_eq!(arm_status.hardware_id, "TalonSRX 52");
+    assert!(arm_status
+        .values
+        .contains(&KeyValue::new("temperature", 25.)));
+    assert_eq!(canifier_status.name, "CANifier 21");
+
+    // Failed calls are a warning.
+    arm.normalize_feedback_to(8192., 0, 0).unwrap_err();
+    let arm_status = telemetry.diagnostics().status.remove(0);
+    assert_eq!(arm_status.level, DiagnosticLevel::Warn);
+    assert_eq!(arm_status.message, "1 failed calls");
+}
@@ -0,0 +1,573 @@
+//! A single thread running the crate's periodic background tasks.
+//!
+//! Helpers such as [`BufferPump`] and [`ThermalGuard`] need to call into the
+//! CCI periodically.  Rather than each spawning its own thread, they schedule
+//! a task here, so the roboRIO's two cores aren't shared between a thread per
+//! helper.  Tasks run one at a time, so each should return quickly.
+//!
+//! Motion profile pumping is timing-sensitive, so on the roboRIO the thread can
+//! be given real-time priority and a core of its own with [`set_thread_options`].
+//!
+//! The thread starts the first time a task is scheduled, unless `ctre::init`
+//! or `ctre::shutdown` have taken control of when it runs.  It is named
+//! `ctre-sched`, and the time each task takes is kept by name (see
+//! [`task_stats`]), so CPU load on the thread can be put down to the helper
+//! causing it.
+//!
+//! ```no_run
+//! # use std::time::Duration;
+//! let task = ctre::scheduler::schedule(Duration::from_millis(10), || {
+//!     // ...
+//! });
+//! // The task runs every 10ms until the handle is dropped.
+//! drop(task);
+//! ```
+//!
+//! [`BufferPump`]: ../motion/struct.BufferPump.html
+//! [`ThermalGuard`]: ../thermal/struct.ThermalGuard.html
+//! [`set_thread_options`]: fn.set_thread_options.html
+//! [`task_stats`]: fn.task_stats.html
+
+use std::io;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc;
+use std::sync::{Condvar, Mutex, MutexGuard, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use ctre_sys::clock;
+
+struct Task {
+    id: u64,
+    period: Duration,
+    next: Instant,
+    run: Box<dyn FnMut() + Send>,
+}
+
+#[derive(Default)]
+struct State {
+    tasks: Vec<Task>,
+    /// Statistics for each task, by ID, including the running task.
+    stats: Vec<(u64, TaskStats)>,
+    next_id: u64,
+    /// The task taken out of `tasks` to run, if any.
+    running: Option<u64>,
+    /// Whether the running task was cancelled while it ran.
+    cancel_running: bool,
+    /// Set by `stop` until the next `start`: tasks wait rather than the
+    /// thread starting for them.
+    stopped: bool,
+    /// Tells the thread to return.
+    stopping: bool,
+}
+
+impl State {
+    fn stats_mut(&mut self, id: u64) -> Option<&mut TaskStats> {
+        let stats = self.stats.iter_mut().find(|&&mut (task, _)| task == id);
+        stats.map(|(_, stats)| stats)
+    }
+}
+
+#[derive(Default)]
+struct Scheduler {
+    state: Mutex<State>,
+    /// Signalled when a task is added.
+    added: Condvar,
+    /// Signalled when a task finishes running.
+    finished: Condvar,
+    /// The thread running the tasks, if it has been started.
+    thread: Mutex<Option<thread::JoinHandle<()>>>,
+}
+
+impl Scheduler {
+    fn global() -> &'static Scheduler {
+        static SCHEDULER: OnceLock<&'static Scheduler> = OnceLock::new();
+        SCHEDULER.get_or_init(|| Box::leak(Box::default()))
+    }
+    fn lock(&self) -> MutexGuard<'_, State> {
+        // Tasks run without the lock held, so nothing can panic while it is locked.
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// Adds a task, starting the thread unless the scheduler was stopped.
+    fn add(
+        &'static self,
+        name: &'static str,
+        period: Duration,
+        run: Box<dyn FnMut() + Send>,
+    ) -> u64 {
+        let state = self.lock();
+        self.insert(state, name, period, run)
+    }
+
+    /// Adds a task unless the scheduler is stopped, checked under the lock
+    /// `stop` takes.
+    fn add_if_started(
+        &'static self,
+        name: &'static str,
+        period: Duration,
+        run: Box<dyn FnMut() + Send>,
+    ) -> Option<u64> {
+        let state = self.lock();
+        if state.stopped {
+            return None;
+        }
+        Some(self.insert(state, name, period, run))
+    }
+
+    fn insert(
+        &'static self,
+        mut state: MutexGuard<'_, State>,
+        name: &'static str,
+        period: Duration,
+        run: Box<dyn FnMut() + Send>,
+    ) -> u64 {
+        let id = state.next_id;
+        state.next_id += 1;
+        state.tasks.push(Task {
+            id,
+            period,
+            next: clock::now(),
+            run,
+        });
+        state.stats.push((id, TaskStats::new(name, period)));
+        self.added.notify_one();
+        let stopped = state.stopped;
+        drop(state);
+        if !stopped {
+            self.spawn();
+        }
+        id
+    }
+
+    /// Starts the thread if it isn't running.
+    fn spawn(&'static self) {
+        let mut thread = self.thread.lock().unwrap_or_else(|err| err.into_inner());
+        if thread.is_none() {
+            let handle = thread::Builder::new()
+                .name("ctre-sched".to_owned())
+                .spawn(move || self.run())
+                .expect("failed to spawn the CTRE scheduler thread");
+            *thread = Some(handle);
+        }
+    }
+
+    fn start(&'static self) {
+        self.lock().stopped = false;
+        self.spawn();
+    }
+
+    /// Stops the thread once any running task finishes, keeping the tasks for
+    /// the next `start`.
+    fn stop(&self) {
+        {
+            let mut state = self.lock();
+            state.stopped = true;
+            state.stopping = true;
+            self.added.notify_all();
+        }
+        let thread = self
+            .thread
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .take();
+        if let Some(thread) = thread {
+            let _ = thread.join();
+        }
+        self.lock().stopping = false;
+    }
+
+    /// Stops the thread and goes back to starting it when a task is scheduled,
+    /// restarting it now if any are.
+    fn reset(&'static self) {
+        self.stop();
+        let has_tasks = {
+            let mut state = self.lock();
+            state.stopped = false;
+            !state.tasks.is_empty()
+        };
+        if has_tasks {
+            self.spawn();
+        }
+    }
+
+    fn run(&self) {
+        let mut state = self.lock();
+        loop {
+            if state.stopping {
+                return;
+            }
+            let now = clock::now();
+            let due = state
+                .tasks
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, task)| task.next)
+                .map(|(i, task)| (i, task.next));
+            state = match due {
+                None => self
+                    .added
+                    .wait(state)
+                    .unwrap_or_else(|err| err.into_inner()),
+                Some((_, next)) if next > now => {
+                    let wait = self
+                        .added
+                        .wait_timeout(state, clock::real_duration(next - now));
+                    wait.unwrap_or_else(|err| err.into_inner()).0
+                }
+                Some((i, _)) => {
+                    let mut task = state.tasks.swap_remove(i);
+                    state.running = Some(task.id);
+                    drop(state);
+                    let started = Instant::now();
+                    // A panicking task is dropped rather than taking the other tasks with it.
+                    let ok = panic::catch_unwind(AssertUnwindSafe(|| (task.run)())).is_ok();
+                    let took = started.elapsed();
+                    let mut state = self.lock();
+                    state.running = None;
+                    if ok && !state.cancel_running {
+                        task.next += task.period;
+                        // Skip periods missed while other tasks ran, rather than catching up.
+                        let now = clock::now();
+                        let mut missed = 0;
+                        if task.next < now {
+                            missed = ((now - task.next).as_nanos() / task.period.as_nanos().max(1))
+                                as u64
+                                + 1;
+                            task.next = now + task.period;
+                        }
+                        if let Some(stats) = state.stats_mut(task.id) {
+                            stats.record(took, missed);
+                        }
+                        state.tasks.push(task);
+                    } else {
+                        state.stats.retain(|&(id, _)| id != task.id);
+                    }
+                    state.cancel_running = false;
+                    self.finished.notify_all();
+                    state
+                }
+            };
+        }
+    }
+
+    fn cancel(&self, id: u64) {
+        let mut state = self.lock();
+        state.stats.retain(|&(task, _)| task != id);
+        if let Some(i) = state.tasks.iter().position(|task| task.id == id) {
+            state.tasks.swap_remove(i);
+            return;
+        }
+        if state.running == Some(id) {
+            state.cancel_running = true;
+            while state.running == Some(id) {
+                state = self
+                    .finished
+                    .wait(state)
+                    .unwrap_or_else(|err| err.into_inner());
+            }
+        }
+    }
+}
+
+/**
+ * Runs `task` on the scheduler thread every `period`, starting now.
+ *
+ * The task runs until the returned handle is dropped, or until it panics.
+ * Periods missed while other tasks ran are skipped.
+ */
+pub fn schedule<F: FnMut() + Send + 'static>(period: Duration, task: F) -> TaskHandle {
+    schedule_named("task", period, task)
+}
+
+/// As [`schedule`](fn.schedule.html), naming the task in its [`TaskStats`].
+///
+/// [`TaskStats`]: struct.TaskStats.html
+pub fn schedule_named<F: FnMut() + Send + 'static>(
+    name: &'static str,
+    period: Duration,
+    task: F,
+) -> TaskHandle {
+    let id = Scheduler::global().add(name, period, Box::new(task));
+    TaskHandle { id }
+}
+
+/// Starts the scheduler thread, if it isn't running, for `ctre::init`.
+pub(crate) fn start() {
+    Scheduler::global().start();
+}
+
+/**
+ * Stops the scheduler thread, for `ctre::shutdown`.  A running task is
+ * finished first.  Tasks stay scheduled, and run again after `start`; until
+ * then, newly scheduled tasks wait too.  Must not be called from a task.
+ */
+pub(crate) fn stop() {
+    Scheduler::global().stop();
+}
+
+/// Whether `stop` has the scheduler stopped.
+pub(crate) fn is_stopped() -> bool {
+    Scheduler::global().lock().stopped
+}
+
+/// Undoes `start` or `stop` after a failed `ctre::init`, so tasks start the
+/// thread as they did before.
+pub(crate) fn reset() {
+    Scheduler::global().reset();
+}
+
+/// The number of tasks currently scheduled.
+pub fn task_count() -> usize {
+    let state = Scheduler::global().lock();
+    state.tasks.len() + state.running.iter().count()
+}
+
+/**
+ * How long a scheduled task has taken to run, for attributing the scheduler
+ * thread's CPU load.  Times are real time, even when the simulation's clock
+ * is scaled.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskStats {
+    pub name: &'static str,
+    pub period: Duration,
+    pub runs: u64,
+    /// Time spent running it, over all runs.
+    pub total_time: Duration,
+    pub last_time: Duration,
+    pub max_time: Duration,
+    /// Runs that took longer than the period.
+    pub overruns: u64,
+    /// Periods skipped because it ran late, whether behind other tasks or its
+    /// own previous run.
+    pub missed_periods: u64,
+}
+
+impl TaskStats {
+    fn new(name: &'static str, period: Duration) -> Self {
+        TaskStats {
+            name,
+            period,
+            runs: 0,
+            total_time: Duration::from_secs(0),
+            last_time: Duration::from_secs(0),
+            max_time: Duration::from_secs(0),
+            overruns: 0,
+            missed_periods: 0,
+        }
+    }
+
+    fn record(&mut self, took: Duration, missed_periods: u64) {
+        self.runs += 1;
+        self.total_time += took;
+        self.last_time = took;
+        self.max_time = self.max_time.max(took);
+        if took > clock::real_duration(self.period) {
+            self.overruns += 1;
+        }
+        self.missed_periods += missed_periods;
+    }
+
+    /// The average time per run.
+    pub fn mean_time(&self) -> Duration {
+        match self.runs {
+            0 => Duration::from_secs(0),
+            runs => self.total_time / runs as u32,
+        }
+    }
+
+    /// The fraction of the scheduler thread's time, from 0 to 1, this task
+    /// takes when it runs every period.
+    pub fn load(&self) -> f64 {
+        self.mean_time().as_secs_f64() / clock::real_duration(self.period).as_secs_f64()
+    }
+}
+
+/// Statistics for every task currently scheduled, in the order they were
+/// scheduled.
+pub fn task_stats() -> Vec<TaskStats> {
+    let state = Scheduler::global().lock();
+    state.stats.iter().map(|(_, stats)| stats.clone()).collect()
+}
+
+/**
+ * A task scheduled with [`schedule`].  Dropping it cancels the task.
+ *
+ * Once the handle is dropped the task won't run again, waiting for it to
+ * finish if it is running.  A task must not drop its own handle.
+ *
+ * [`schedule`]: fn.schedule.html
+ */
+#[derive(Debug)]
+pub struct TaskHandle {
+    id: u64,
+}
+
+impl Drop for TaskHandle {
+    fn drop(&mut self) {
+        Scheduler::global().cancel(self.id);
+    }
+}
+
+/// Scheduling options for the scheduler thread.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct ThreadOptions {
+    /// `SCHED_FIFO` priority, [1,99], or `None` to leave the scheduling policy as is.
+    pub priority: Option<i32>,
+    /// The CPU to pin the thread to, or `None` to leave its affinity as is.
+    pub cpu: Option<usize>,
+}
+
+/**
+ * Sets the scheduler thread's real-time priority and CPU affinity.
+ *
+ * Real-time priority needs the `CAP_SYS_NICE` capability, which robot
+ * programs have on the roboRIO.  Only supported on Linux; elsewhere any
+ * option set returns an `Unsupported` error, as does any call while
+ * `ctre::shutdown` has the thread stopped.  Must not be called from a
+ * scheduled task.
+ */
+pub fn set_thread_options(options: ThreadOptions) -> io::Result<()> {
+    let stopped = || io::Error::new(io::ErrorKind::Unsupported, "the scheduler is stopped");
+    let scheduler = Scheduler::global();
+    let (sender, receiver) = mpsc::channel();
+    let mut sender = Some(sender);
+    let task = move || {
+        if let Some(sender) = sender.take() {
+            let _ = sender.send(apply_thread_options(options));
+        }
+    };
+    let id = scheduler
+        .add_if_started("set_thread_options", Duration::from_secs(1), Box::new(task))
+        .ok_or_else(stopped)?;
+    let _task = TaskHandle { id };
+    loop {
+        // The scheduler may be stopped before the task gets to run.
+        match receiver.recv_timeout(Duration::from_millis(100)) {
+            Ok(result) => return result,
+            Err(mpsc::RecvTimeoutError::Timeout) if !scheduler.lock().stopped => {}
+            Err(mpsc::RecvTimeoutError::Timeout) => return Err(stopped()),
+            Err(mpsc::RecvTimeoutError::Disconnected) => {
+                return Err(io::Error::other("scheduler task panicked"))
+            }
+        }
+    }
+}
+
+/// Applies `options` to the calling thread.
+#[cfg(target_os = "linux")]
+fn apply_thread_options(options: ThreadOptions) -> io::Result<()> {
+    use std::mem;
+    if let Some(cpu) = options.cpu {
+        unsafe {
+            let mut set: libc::cpu_set_t = mem::zeroed();
+            libc::CPU_SET(cpu, &mut set);
+            if libc::sched_setaffinity(0, mem::size_of::<libc::cpu_set_t>(), &set) != 0 {
+                return Err(io::Error::last_os_error());
+            }
+        }
+    }
+    if let Some(priority) = options.priority {
+        let param = libc::sched_param {
+            sched_priority: priority,
+        };
+        let code =
+            unsafe { libc::pthread_setschedparam(libc::pthread_self(), libc::SCHED_FIFO, &param) };
+        if code != 0 {
+            return Err(io::Error::from_raw_os_error(code));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn apply_thread_options(options: ThreadOptions) -> io::Result<()> {
+    if options == ThreadOptions::default() {
+        Ok(())
+    } else {
+        Err(io::ErrorKind::Unsupported.into())
+    }
+}
+
+#[test]
+fn tasks_run_until_dropped() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    let runs = Arc::new(AtomicUsize::new(0));
+    let task = {
+        let runs = Arc::clone(&runs);
+        schedule(Duration::from_millis(1), move || {
+            runs.fetch_add(1, Ordering::Relaxed);
+        })
+    };
+    thread::sleep(Duration::from_millis(20));
+    drop(task);
+    let stopped_at = runs.load(Ordering::Relaxed);
+    assert!(stopped_at > 1);
+    thread::sleep(Duration::from_millis(5));
+    assert_eq!(runs.load(Ordering::Relaxed), stopped_at);
+}
+
+#[test]
+fn stopped_scheduler_keeps_its_tasks() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    let scheduler: &'static Scheduler = Box::leak(Box::default());
+    let runs = Arc::new(AtomicUsize::new(0));
+    let id = {
+        let runs = Arc::clone(&runs);
+        let task = move || {
+            runs.fetch_add(1, Ordering::Relaxed);
+        };
+        scheduler.add("test", Duration::from_millis(1), Box::new(task))
+    };
+    thread::sleep(Duration::from_millis(20));
+    scheduler.stop();
+    let stopped_at = runs.load(Ordering::Relaxed);
+    assert!(stopped_at > 1);
+    assert!(scheduler.thread.lock().unwrap().is_none());
+    thread::sleep(Duration::from_millis(5));
+    assert_eq!(runs.load(Ordering::Relaxed), stopped_at);
+    let late = scheduler.add_if_started("late", Duration::from_millis(1), Box::new(|| {}));
+    assert_eq!(late, None);
+    scheduler.start();
+    thread::sleep(Duration::from_millis(20));
+    assert!(runs.load(Ordering::Relaxed) > stopped_at);
+    scheduler.cancel(id);
+    scheduler.stop();
+}
+
+#[test]
+fn task_stats_record_overruns() {
+    let scheduler: &'static Scheduler = Box::leak(Box::default());
+    let id = scheduler.add(
+        "slow_task",
+        Duration::from_millis(1),
+        Box::new(|| thread::sleep(Duration::from_millis(3))),
+    );
+    thread::sleep(Duration::from_millis(30));
+    scheduler.stop();
+    let stats = scheduler.lock().stats_mut(id).cloned().unwrap();
+    assert_eq!(stats.name, "slow_task");
+    assert!(stats.runs > 1 && stats.overruns == stats.runs);
+    assert!(stats.missed_periods >= stats.runs - 1);
+    assert!(stats.max_time >= Duration::from_millis(3) && stats.load() > 1.);
+    scheduler.cancel(id);
+    assert!(scheduler.lock().stats.is_empty());
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn thread_options_are_checked() {
+    let pinned = ThreadOptions {
+        cpu: Some(0),
+        ..ThreadOptions::default()
+    };
+    assert!(set_thread_options(pinned).is_ok());
+    let invalid = ThreadOptions {
+        priority: Some(100),
+        ..ThreadOptions::default()
+    };
+    assert!(set_thread_options(invalid).is_err());
+}
@@ -0,0 +1,136 @@
+//! Scaling for analog sensors on a Talon SRX's feedback connector.
+//!
+//! The analog input is a 10-bit ADC over 0-3.3V, so raw readings are 0-1023.
+//! With continuous tracking (the default), the controller counts a 1023 to 0
+//! transition as an overflow and keeps counting up, as for a multi-turn
+//! potentiometer or absolute encoder.  In absolute mode it wraps back to 0.
+
+use motor_control::SensorCollection;
+use Result;
+
+/// Number of counts in one sweep of the analog input.
+pub const ANALOG_COUNTS: i32 = 1024;
+/// Voltage at the top of the analog input's range.
+pub const ANALOG_FULL_SCALE_VOLTS: f64 = 3.3;
+
+/// How an analog feedback position is tracked.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum AnalogMode {
+    /// Count past 1023, so the position keeps increasing across a wrap.
+    Continuous,
+    /// Wrap from 1023 back to 0.
+    Absolute,
+}
+
+/// Converts a raw 10-bit reading to volts.
+pub fn raw_to_volts(raw: i32) -> f64 {
+    f64::from(raw & (ANALOG_COUNTS - 1)) * ANALOG_FULL_SCALE_VOLTS / f64::from(ANALOG_COUNTS - 1)
+}
+
+/// Linear scaling from analog counts to user units.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct AnalogScale {
+    /// User units per full sweep (1024 counts) of the input.
+    pub units_per_sweep: f64,
+    /// User units at a reading of zero.
+    pub offset: f64,
+}
+
+impl AnalogScale {
+    /// Degrees, for a single-turn sensor that sweeps the input once per rotation.
+    pub fn degrees() -> Self {
+        AnalogScale {
+            units_per_sweep: 360.,
+            offset: 0.,
+        }
+    }
+    /// Scales a position in counts.  Continuous positions past 1023 scale past one sweep.
+    pub fn to_units(&self, counts: i64) -> f64 {
+        counts as f64 * self.units_per_sweep / f64::from(ANALOG_COUNTS) + self.offset
+    }
+    /// The reading, in counts, that corresponds to `units`.
+    pub fn to_counts(&self, units: f64) -> i64 {
+        ((units - self.offset) * f64::from(ANALOG_COUNTS) / self.units_per_sweep).round() as i64
+    }
+}
+
+/**
+ * Accumulates a continuous position from raw 0-1023 readings, for when the
+ * controller is in absolute mode or the raw value is read directly.
+ *
+ * A jump of more than half a sweep between samples is taken as a wrap, so the
+ * sensor must move less than half a sweep between updates.
+ */
+#[derive(Debug, Default, Copy, Clone)]
+pub struct AnalogAccumulator {
+    last: Option<i32>,
+    position: i64,
+}
+
+impl AnalogAccumulator {
+    /// Starts accumulating from `position` counts at the next sample.
+    pub fn starting_at(position: i64) -> Self {
+        AnalogAccumulator {
+            last: None,
+            position,
+        }
+    }
+    /// Feeds a raw reading, returning the accumulated position in counts.
+    pub fn update(&mut self, raw: i32) -> i64 {
+        let raw = raw & (ANALOG_COUNTS - 1);
+        if let Some(last) = self.last {
+            let mut delta = raw - last;
+            if delta > ANALOG_COUNTS / 2 {
+                delta -= ANALOG_COUNTS;
+            } else if delta < -ANALOG_COUNTS / 2 {
+                delta += ANALOG_COUNTS;
+            }
+            self.position += i64::from(delta);
+        } else {
+            self.position += i64::from(raw);
+        }
+        self.last = Some(raw);
+        self.position
+    }
+    pub fn position(&self) -> i64 {
+        self.position
+    }
+}
+
+/// Analog sensor helpers for controllers with an analog input.
+pub trait AnalogSensor: SensorCollection {
+    /**
+     * Selects whether the analog position is continuous or wraps at 1023.
+     * This also applies to a pulse-width sensor.
+     *
+     * Requires Phoenix 2019 or later.
+     */
+    #[cfg(feature = "phoenix-2019")]
+    fn config_analog_mode(&self, mode: AnalogMode, timeout_ms: i32) -> Result<()> {
+        self.config_feedback_not_continuous(mode == AnalogMode::Absolute, timeout_ms)
+    }
+    /// The voltage on the analog input.
+    fn get_analog_volts(&self) -> Result<f64> {
+        self.get_analog_in_raw().map(raw_to_volts)
+    }
+    /// The analog position (continuous, unless configured otherwise) in user units.
+    fn get_analog_scaled(&self, scale: &AnalogScale) -> Result<f64> {
+        self.get_analog_in()
+            .map(|counts| scale.to_units(i64::from(counts)))
+    }
+}
+
+impl<T: SensorCollection> AnalogSensor for T {}
+
+#[test]
+fn analog_accumulator_wraps() {
+    let mut acc = AnalogAccumulator::default();
+    assert_eq!(acc.update(1000), 1000);
+    assert_eq!(acc.update(1020), 1020);
+    // 1020 -> 10 is forwards across the wrap.
+    assert_eq!(acc.update(10), 1034);
+    // and back again.
+    assert_eq!(acc.update(1015), 1015);
+    assert_eq!(AnalogScale::degrees().to_units(1024 + 256), 450.);
+    assert_eq!(raw_to_volts(1023), ANALOG_FULL_SCALE_VOLTS);
+}
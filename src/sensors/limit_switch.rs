@@ -0,0 +1,187 @@
+//! Debounced limit switch edges.
+
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::time::{Duration, Instant};
+
+use ctre_sys::clock;
+
+use motor_control::SensorCollection;
+use Result;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum LimitSwitch {
+    Forward,
+    Reverse,
+}
+
+/// A debounced change in a limit switch's state.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct LimitSwitchEvent {
+    pub switch: LimitSwitch,
+    /// `true` if the switch closed, `false` if it opened.
+    pub closed: bool,
+    /// When the new state was first seen, before debouncing.
+    pub timestamp: Instant,
+}
+
+/// Debounces one boolean input.
+#[derive(Debug, Default, Copy, Clone)]
+struct Debouncer {
+    /// The debounced state, once known.
+    stable: Option<bool>,
+    /// A state differing from `stable`, and when it was first seen.
+    pending: Option<(bool, Instant)>,
+}
+
+impl Debouncer {
+    /// Feeds a raw sample, returning the new state and when it started if the
+    /// state has now held for `debounce`.
+    fn update(&mut self, raw: bool, now: Instant, debounce: Duration) -> Option<(bool, Instant)> {
+        let stable = match self.stable {
+            Some(stable) => stable,
+            None => {
+                // The first sample is the initial state, not an edge.
+                self.stable = Some(raw);
+                return None;
+            }
+        };
+        if raw == stable {
+            self.pending = None;
+            return None;
+        }
+        let since = match self.pending {
+            Some((state, since)) if state == raw => since,
+            _ => {
+                self.pending = Some((raw, now));
+                now
+            }
+        };
+        if now.duration_since(since) >= debounce {
+            self.stable = Some(raw);
+            self.pending = None;
+            Some((raw, since))
+        } else {
+            None
+        }
+    }
+}
+
+/**
+ * Watches a motor controller's limit switches and reports debounced edges.
+ *
+ * Call [`poll`] periodically, e.g. once per robot loop.  The switch states are
+ * read from the controller's cached status frames, so polling doesn't add CAN
+ * traffic.  Events are returned from [`poll`], and also passed to any callbacks
+ * added with [`on_event`] and sent to any channels made by [`subscribe`].
+ *
+ * [`poll`]: #method.poll
+ * [`on_event`]: #method.on_event
+ * [`subscribe`]: #method.subscribe
+ */
+pub struct LimitSwitchWatcher<'a, M: 'a> {
+    motor: &'a M,
+    debounce: Duration,
+    forward: Debouncer,
+    reverse: Debouncer,
+    callbacks: Vec<Box<dyn FnMut(LimitSwitchEvent) + 'a>>,
+    senders: Vec<Sender<LimitSwitchEvent>>,
+}
+
+impl<'a, M: SensorCollection> LimitSwitchWatcher<'a, M> {
+    /// * `debounce` - How long a switch must hold a new state before it is reported.
+    pub fn new(motor: &'a M, debounce: Duration) -> Self {
+        LimitSwitchWatcher {
+            motor,
+            debounce,
+            forward: Debouncer::default(),
+            reverse: Debouncer::default(),
+            callbacks: Vec::new(),
+            senders: Vec::new(),
+        }
+    }
+
+    /// Calls `callback` with each event.
+    pub fn on_event<F: FnMut(LimitSwitchEvent) + 'a>(&mut self, callback: F) -> &mut Self {
+        self.callbacks.push(Box::new(callback));
+        self
+    }
+
+    /// Returns a channel that receives each event.
+    pub fn subscribe(&mut self) -> Receiver<LimitSwitchEvent> {
+        let (tx, rx) = mpsc::channel();
+        self.senders.push(tx);
+        rx
+    }
+
+    /// The debounced state of `switch`, or `None` if it hasn't been polled yet.
+    pub fn is_closed(&self, switch: LimitSwitch) -> Option<bool> {
+        match switch {
+            LimitSwitch::Forward => self.forward.stable,
+            LimitSwitch::Reverse => self.reverse.stable,
+        }
+    }
+
+    /// Reads both switches and returns any debounced edges.
+    pub fn poll(&mut self) -> Result<Vec<LimitSwitchEvent>> {
+        let mut events = Vec::new();
+        self.poll_into(&mut events)?;
+        Ok(events)
+    }
+    /// Like `poll`, but reuses `events`' allocation.  `events` is cleared first.
+    pub fn poll_into(&mut self, events: &mut Vec<LimitSwitchEvent>) -> Result<()> {
+        events.clear();
+        let forward = self.motor.is_fwd_limit_switch_closed()? != 0;
+        let reverse = self.motor.is_rev_limit_switch_closed()? != 0;
+        self.update(forward, reverse, clock::now(), events);
+        Ok(())
+    }
+
+    fn update(
+        &mut self,
+        forward: bool,
+        reverse: bool,
+        now: Instant,
+        events: &mut Vec<LimitSwitchEvent>,
+    ) {
+        for &(switch, raw) in &[
+            (LimitSwitch::Forward, forward),
+            (LimitSwitch::Reverse, reverse),
+        ] {
+            let debouncer = match switch {
+                LimitSwitch::Forward => &mut self.forward,
+                LimitSwitch::Reverse => &mut self.reverse,
+            };
+            if let Some((closed, timestamp)) = debouncer.update(raw, now, self.debounce) {
+                events.push(LimitSwitchEvent {
+                    switch,
+                    closed,
+                    timestamp,
+                });
+            }
+        }
+        for &event in events.iter() {
+            for callback in &mut self.callbacks {
+                callback(event);
+            }
+            // Drop the channels whose receiver has gone away.
+            self.senders.retain(|tx| tx.send(event).is_ok());
+        }
+    }
+}
+
+#[test]
+fn debounce_ignores_bounces() {
+    let debounce = Duration::from_millis(20);
+    let start = Instant::now();
+    let at = |ms| start + Duration::from_millis(ms);
+    let mut switch = Debouncer::default();
+    assert_eq!(switch.update(false, at(0), debounce), None);
+    // A bounce shorter than the debounce time is ignored.
+    assert_eq!(switch.update(true, at(5), debounce), None);
+    assert_eq!(switch.update(false, at(10), debounce), None);
+    assert_eq!(switch.update(true, at(15), debounce), None);
+    assert_eq!(switch.update(true, at(30), debounce), None);
+    assert_eq!(switch.update(true, at(35), debounce), Some((true, at(15))));
+    assert_eq!(switch.update(true, at(40), debounce), None);
+    assert_eq!(switch.stable, Some(true));
+}
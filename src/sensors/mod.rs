@@ -1,2 +1,7 @@
+pub mod analog;
+pub mod limit_switch;
 pub mod pigeon;
+pub mod units;
+pub use self::limit_switch::LimitSwitchWatcher;
 pub use self::pigeon::PigeonIMU;
+pub use self::units::SensorUnits;
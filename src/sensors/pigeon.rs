@@ -4,8 +4,15 @@ use ctre_sys::pigeon::*;
 pub use ctre_sys::pigeon::{
     PigeonIMU_ControlFrame as ControlFrame, PigeonIMU_StatusFrame as StatusFrame,
 };
+use device::{DeviceContext, DeviceState};
+use error::{DeviceType, ErrorRecord, ErrorStats};
+use events::{events, DeviceEvent};
+use firmware::FirmwareVersion;
+use id::DeviceId;
 use motor_control::{BaseMotorController, TalonSRX};
+use registry::registry;
 use std::fmt;
+use std::sync::Arc;
 use {ErrorCode, ParamEnum, Result};
 
 /// Data object for holding fusion information.
@@ -111,19 +118,19 @@ impl From<i32> for PigeonState {
  * When a calibration mode is entered, caller can expect...
  *
  * - PigeonState to reset to Initializing and bCalIsBooting is set to true.
- * Pigeon LEDs will blink the boot pattern. This is similar to the normal
- * boot cal, however it can an additional ~30 seconds since calibration
- * generally requires more information. currentMode will reflect the user's
- * selected calibration mode.
+ *   Pigeon LEDs will blink the boot pattern. This is similar to the normal
+ *   boot cal, however it can an additional ~30 seconds since calibration
+ *   generally requires more information. currentMode will reflect the user's
+ *   selected calibration mode.
  *
  * - PigeonState will eventually settle to UserCalibration and Pigeon LEDs
- * will show cal specific blink patterns. bCalIsBooting is now false.
+ *   will show cal specific blink patterns. bCalIsBooting is now false.
  *
  * - Follow the instructions in the Pigeon User Manual to meet the
- * calibration specific requirements. When finished calibrationError will
- * update with the result. Pigeon will solid-fill LEDs with red (for
- * failure) or green (for success) for ~5 seconds. Pigeon then perform
- * boot-cal to cleanly apply the newly saved calibration data.
+ *   calibration specific requirements. When finished calibrationError will
+ *   update with the result. Pigeon will solid-fill LEDs with red (for
+ *   failure) or green (for success) for ~5 seconds. Pigeon then perform
+ *   boot-cal to cleanly apply the newly saved calibration data.
  */
 #[derive(Default, Debug)]
 pub struct GeneralStatus {
@@ -225,6 +232,10 @@ impl StickyFaults {
     pub fn has_any_fault(self) -> bool {
         self.0 != 0
     }
+    /// The raw fault bits.
+    pub fn bits(self) -> i32 {
+        self.0
+    }
 }
 
 /**
@@ -233,13 +244,58 @@ impl StickyFaults {
  */
 pub struct PigeonIMU {
     handle: Handle,
+    /// Device number of the Pigeon, or of the Talon it is connected through.
+    device_number: i32,
+    /// Whether the Pigeon is connected through a Talon's gadgeteer port.
+    gadgeteer: bool,
+    state: Arc<DeviceState>,
 }
 impl PigeonIMU {
     /// Create a Pigeon object that communicates with Pigeon on CAN Bus.
     /// * `device_number` - CAN Device Id of Pigeon [0,62]
-    pub fn new(device_number: i32) -> PigeonIMU {
-        let handle = unsafe { c_PigeonIMU_Create1(device_number) };
-        PigeonIMU { handle }
+    pub fn new(device_number: DeviceId) -> PigeonIMU {
+        let handle = unsafe { c_PigeonIMU_Create1(i32::from(device_number)) };
+        let state = registry().register(DeviceType::PigeonIMU, device_number, handle);
+        PigeonIMU::from_state(i32::from(device_number), state)
+    }
+    /**
+     * Another object for the registered Pigeon on CAN owning `state`.  The
+     * registry doesn't record whether a Pigeon is on a gadgeteer port, so one
+     * made for a Pigeon that is must not be used as a remote sensor.
+     */
+    pub(crate) fn from_state(device_number: i32, state: Arc<DeviceState>) -> PigeonIMU {
+        PigeonIMU {
+            handle: state.handle(),
+            device_number,
+            gadgeteer: false,
+            state,
+        }
+    }
+    /**
+     * Creates a Pigeon on CAN that may not be on the bus.  While it isn't,
+     * calls fail at once with `DeviceMissing`, a warning, instead of waiting on CAN.
+     */
+    pub fn new_optional(device_number: DeviceId) -> PigeonIMU {
+        let pigeon = PigeonIMU::new(device_number);
+        pigeon.make_optional();
+        pigeon
+    }
+    /// The device number, or that of the Talon the Pigeon is connected through.
+    pub fn id(&self) -> DeviceId {
+        self.state.device_id()
+    }
+    /// The label set with `set_name`, if any.
+    pub fn name(&self) -> Option<&'static str> {
+        self.state.name()
+    }
+    /// Labels the PigeonIMU for logs and error messages, e.g. `"chassis_imu"`.
+    pub fn set_name(&self, name: &'static str) {
+        self.state.set_name(name)
+    }
+
+    /// Whether the Pigeon is connected through a Talon's gadgeteer port.
+    pub(crate) fn is_gadgeteer(&self) -> bool {
+        self.gadgeteer
     }
 
     /**
@@ -250,16 +306,16 @@ impl PigeonIMU {
      *   If nonzero, function will wait for config success and report an error if it times out.
      *   If zero, no blocking or checking is performed.
      */
-    pub fn set_yaw(&self, angle_deg: f64, timeout_ms: i32) -> ErrorCode {
-        unsafe { c_PigeonIMU_SetYaw(self.handle, angle_deg, timeout_ms) }
+    pub fn set_yaw(&self, angle_deg: f64, timeout_ms: i32) -> Result<()> {
+        cci_call!(self, c_PigeonIMU_SetYaw(self.handle, angle_deg, timeout_ms))
     }
     /// Atomically add to the Yaw register.
-    pub fn add_yaw(&self, angle_deg: f64, timeout_ms: i32) -> ErrorCode {
-        unsafe { c_PigeonIMU_AddYaw(self.handle, angle_deg, timeout_ms) }
+    pub fn add_yaw(&self, angle_deg: f64, timeout_ms: i32) -> Result<()> {
+        cci_call!(self, c_PigeonIMU_AddYaw(self.handle, angle_deg, timeout_ms))
     }
     /// Sets the Yaw register to match the current compass value.
-    pub fn set_yaw_to_compass(&self, timeout_ms: i32) -> ErrorCode {
-        unsafe { c_PigeonIMU_SetYawToCompass(self.handle, timeout_ms) }
+    pub fn set_yaw_to_compass(&self, timeout_ms: i32) -> Result<()> {
+        cci_call!(self, c_PigeonIMU_SetYawToCompass(self.handle, timeout_ms))
     }
 
     /**
@@ -270,19 +326,31 @@ impl PigeonIMU {
      *   If nonzero, function will wait for config success and report an error if it times out.
      *   If zero, no blocking or checking is performed.
      */
-    pub fn set_fused_heading(&self, angle_deg: f64, timeout_ms: i32) -> ErrorCode {
-        unsafe { c_PigeonIMU_SetFusedHeading(self.handle, angle_deg, timeout_ms) }
+    pub fn set_fused_heading(&self, angle_deg: f64, timeout_ms: i32) -> Result<()> {
+        cci_call!(
+            self,
+            c_PigeonIMU_SetFusedHeading(self.handle, angle_deg, timeout_ms)
+        )
     }
     /// Atomically add to the Fused Heading register.
-    pub fn add_fused_heading(&self, angle_deg: f64, timeout_ms: i32) -> ErrorCode {
-        unsafe { c_PigeonIMU_AddFusedHeading(self.handle, angle_deg, timeout_ms) }
+    pub fn add_fused_heading(&self, angle_deg: f64, timeout_ms: i32) -> Result<()> {
+        cci_call!(
+            self,
+            c_PigeonIMU_AddFusedHeading(self.handle, angle_deg, timeout_ms)
+        )
     }
     /// Sets the Fused Heading register to match the current compass value.
-    pub fn set_fused_heading_to_compass(&self, timeout_ms: i32) -> ErrorCode {
-        unsafe { c_PigeonIMU_SetFusedHeadingToCompass(self.handle, timeout_ms) }
+    pub fn set_fused_heading_to_compass(&self, timeout_ms: i32) -> Result<()> {
+        cci_call!(
+            self,
+            c_PigeonIMU_SetFusedHeadingToCompass(self.handle, timeout_ms)
+        )
     }
-    pub fn set_accum_z_angle(&self, angle_deg: f64, timeout_ms: i32) -> ErrorCode {
-        unsafe { c_PigeonIMU_SetAccumZAngle(self.handle, angle_deg, timeout_ms) }
+    pub fn set_accum_z_angle(&self, angle_deg: f64, timeout_ms: i32) -> Result<()> {
+        cci_call!(
+            self,
+            c_PigeonIMU_SetAccumZAngle(self.handle, angle_deg, timeout_ms)
+        )
     }
 
     /// Enable/Disable Temp compensation. Pigeon defaults with this on at boot.
@@ -290,25 +358,35 @@ impl PigeonIMU {
         &self,
         enable: bool,
         timeout_ms: i32,
-    ) -> ErrorCode {
-        unsafe {
+    ) -> Result<()> {
+        cci_call!(
+            self,
             c_PigeonIMU_ConfigTemperatureCompensationEnable(self.handle, enable as _, timeout_ms)
-        }
+        )
     }
 
     /// Set the declination for compass. Declination is the difference between
     /// Earth Magnetic north, and the geographic "True North".
-    pub fn set_compass_declination(&self, angle_deg_offset: f64, timeout_ms: i32) -> ErrorCode {
-        unsafe { c_PigeonIMU_SetCompassDeclination(self.handle, angle_deg_offset, timeout_ms) }
+    pub fn set_compass_declination(&self, angle_deg_offset: f64, timeout_ms: i32) -> Result<()> {
+        cci_call!(
+            self,
+            c_PigeonIMU_SetCompassDeclination(self.handle, angle_deg_offset, timeout_ms)
+        )
     }
     /// Sets the compass angle. Although compass is absolute [0,360) degrees, the
     /// continuous compass register holds the wrap-arounds.
-    pub fn set_compass_angle(&self, angle_deg: f64, timeout_ms: i32) -> ErrorCode {
-        unsafe { c_PigeonIMU_SetCompassAngle(self.handle, angle_deg, timeout_ms) }
+    pub fn set_compass_angle(&self, angle_deg: f64, timeout_ms: i32) -> Result<()> {
+        cci_call!(
+            self,
+            c_PigeonIMU_SetCompassAngle(self.handle, angle_deg, timeout_ms)
+        )
     }
 
-    pub fn enter_calibration_mode(&self, cal_mode: CalibrationMode, timeout_ms: i32) -> ErrorCode {
-        unsafe { c_PigeonIMU_EnterCalibrationMode(self.handle, cal_mode as _, timeout_ms) }
+    pub fn enter_calibration_mode(&self, cal_mode: CalibrationMode, timeout_ms: i32) -> Result<()> {
+        cci_call!(
+            self,
+            c_PigeonIMU_EnterCalibrationMode(self.handle, cal_mode as _, timeout_ms)
+        )
     }
 
     /// Get the status of the current (or previousley complete) calibration.
@@ -335,25 +413,40 @@ impl PigeonIMU {
             status.state = state.into();
             status.current_mode = current_mode.into();
             status.cal_is_booting = b_cal_is_booting != 0;
+            self.frame_received();
             Ok(status)
         } else {
-            Err(err)
+            Err(cci_error!(self, c_PigeonIMU_GetGeneralStatus, err))
         }
     }
 
     pub fn get_last_error(&self) -> ErrorCode {
         unsafe { c_PigeonIMU_GetLastError(self.handle) }
     }
+    /// The last few errors returned by calls on this object, oldest first.
+    /// Unlike `get_last_error`, each entry is tied to the call that failed.
+    pub fn recent_errors(&self) -> Vec<ErrorRecord> {
+        self.state.errors.to_vec()
+    }
+    /// Like `recent_errors`, but reuses `records`' allocation.
+    /// `records` is cleared first; it never needs more than 16 entries.
+    pub fn recent_errors_into(&self, records: &mut Vec<ErrorRecord>) {
+        self.state.errors.copy_into(records)
+    }
+    /// How many calls on this object have failed, by kind of error.
+    pub fn error_stats(&self) -> ErrorStats {
+        self.state.error_counts.stats()
+    }
 
     /// Get 6d Quaternion data.
     /// Returns an array of the wxyz quaternion data.
     pub fn get6d_quaternion(&self) -> Result<[f64; 4]> {
-        cci_get_call_array!(c_PigeonIMU_Get6dQuaternion(self.handle, _: [f64; 4]))
+        cci_get_call_array!(self, c_PigeonIMU_Get6dQuaternion(self.handle, _: [f64; 4]))
     }
     /// Get Yaw, Pitch, and Roll data.
     /// Returns an array with yaw, pitch, and roll, in that order.
     pub fn get_yaw_pitch_roll(&self) -> Result<[f64; 3]> {
-        cci_get_call_array!(c_PigeonIMU_GetYawPitchRoll(self.handle, _: [f64; 3]))
+        cci_get_call_array!(self, c_PigeonIMU_GetYawPitchRoll(self.handle, _: [f64; 3]))
     }
     /**
      * Get AccumGyro data.
@@ -362,55 +455,55 @@ impl PigeonIMU {
      * Returns an array `xyz_deg`.
      */
     pub fn get_accum_gyro(&self) -> Result<[f64; 3]> {
-        cci_get_call_array!(c_PigeonIMU_GetAccumGyro(self.handle, _: [f64; 3]))
+        cci_get_call_array!(self, c_PigeonIMU_GetAccumGyro(self.handle, _: [f64; 3]))
     }
     /// Get the absolute compass heading, in the interval [0, 360) degrees.
     pub fn get_absolute_compass_heading(&self) -> Result<f64> {
-        cci_get_call!(c_PigeonIMU_GetAbsoluteCompassHeading(self.handle, _: f64))
+        cci_get_call!(self, c_PigeonIMU_GetAbsoluteCompassHeading(self.handle, _: f64))
     }
     /// Get the continuous compass heading, in the interval [-23040, 23040) degrees.
     pub fn get_compass_heading(&self) -> Result<f64> {
-        cci_get_call!(c_PigeonIMU_GetCompassHeading(self.handle, _: f64))
+        cci_get_call!(self, c_PigeonIMU_GetCompassHeading(self.handle, _: f64))
     }
     /// Get the compass' measured magnetic field strength in microteslas (uT).
     pub fn get_compass_field_strength(&self) -> Result<f64> {
-        cci_get_call!(c_PigeonIMU_GetCompassFieldStrength(self.handle, _: f64))
+        cci_get_call!(self, c_PigeonIMU_GetCompassFieldStrength(self.handle, _: f64))
     }
     /// Get the temperature of the pigeon, in degrees Celsius.
     pub fn get_temp(&self) -> Result<f64> {
-        cci_get_call!(c_PigeonIMU_GetTemp(self.handle, _: f64))
+        cci_get_call!(self, c_PigeonIMU_GetTemp(self.handle, _: f64))
     }
     pub fn get_state(&self) -> Result<PigeonState> {
-        Ok(cci_get_call!(c_PigeonIMU_GetState(self.handle, _: i32))?.into())
+        Ok(cci_get_call!(self, c_PigeonIMU_GetState(self.handle, _: i32))?.into())
     }
     pub fn get_up_time(&self) -> Result<i32> {
-        cci_get_call!(c_PigeonIMU_GetUpTime(self.handle, _: i32))
+        cci_get_call!(self, c_PigeonIMU_GetUpTime(self.handle, _: i32))
     }
 
     /// Get Raw Magnetometer data.
     /// Returns an array `rm_xyz`.  Number is equal to 0.6 microteslas per unit.
     pub fn get_raw_magnetometer(&self) -> Result<[i16; 3]> {
-        cci_get_call_array!(c_PigeonIMU_GetRawMagnetometer(self.handle, _: [i16; 3]))
+        cci_get_call_array!(self, c_PigeonIMU_GetRawMagnetometer(self.handle, _: [i16; 3]))
     }
     /// Get Biased Magnetometer data.
     /// Returns an array `bm_xyz`.  Number is equal to 0.6 microteslas per unit.
     pub fn get_biased_magnetometer(&self) -> Result<[i16; 3]> {
-        cci_get_call_array!(c_PigeonIMU_GetBiasedMagnetometer(self.handle, _: [i16; 3]))
+        cci_get_call_array!(self, c_PigeonIMU_GetBiasedMagnetometer(self.handle, _: [i16; 3]))
     }
     /// Get Biased Accelerometer data.
     /// Returns an array `ba_xyz`.  These are in fixed point notation Q2.14.  eg. 16384 = 1G
     pub fn get_biased_accelerometer(&self) -> Result<[i16; 3]> {
-        cci_get_call_array!(c_PigeonIMU_GetBiasedAccelerometer(self.handle, _: [i16; 3]))
+        cci_get_call_array!(self, c_PigeonIMU_GetBiasedAccelerometer(self.handle, _: [i16; 3]))
     }
     /// Get Raw Gyro data.
     /// Returns an array `xyz_dps`, with data in degrees per second.
     pub fn get_raw_gyro(&self) -> Result<[f64; 3]> {
-        cci_get_call_array!(c_PigeonIMU_GetRawGyro(self.handle, _: [f64; 3]))
+        cci_get_call_array!(self, c_PigeonIMU_GetRawGyro(self.handle, _: [f64; 3]))
     }
     /// Get Accelerometer tilt angles.
     /// Returns a 3-array of x, y, z angles in degrees.
     pub fn get_accelerometer_angles(&self) -> Result<[f64; 3]> {
-        cci_get_call_array!(c_PigeonIMU_GetAccelerometerAngles(self.handle, _: [f64; 3]))
+        cci_get_call_array!(self, c_PigeonIMU_GetAccelerometerAngles(self.handle, _: [f64; 3]))
     }
 
     /// Get the current Fusion Status (including fused heading)
@@ -430,31 +523,38 @@ impl PigeonIMU {
         if err == ErrorCode::OK {
             status.is_fusing = b_is_fusing != 0;
             status.is_valid = b_is_valid != 0;
+            self.frame_received();
             Ok(status)
         } else {
-            Err(err)
+            Err(cci_error!(self, c_PigeonIMU_GetFusedHeading2, err))
         }
     }
     /// Gets the Fused Heading in degrees.
     pub fn get_fused_heading(&self) -> Result<f64> {
-        cci_get_call!(c_PigeonIMU_GetFusedHeading1(self.handle, _: f64))
+        cci_get_call!(self, c_PigeonIMU_GetFusedHeading1(self.handle, _: f64))
     }
 
     // Use `has_reset_occurred` instead.
     /*
     pub fn get_reset_count(&self) -> Result<i32> {
-        cci_get_call!(c_PigeonIMU_GetResetCount(self.handle, _: i32))
+        cci_get_call!(self, c_PigeonIMU_GetResetCount(self.handle, _: i32))
     }
     // XXX: C++ exposes GetResetCount here again, Java exposes neither of these.
     pub fn get_reset_flags(&self) -> Result<i32> {
-        cci_get_call!(c_PigeonIMU_GetResetFlags(self.handle, _: i32))
+        cci_get_call!(self, c_PigeonIMU_GetResetFlags(self.handle, _: i32))
     }
     */
-    pub fn get_firmware_version(&self) -> Result<i32> {
-        cci_get_call!(c_PigeonIMU_GetFirmwareVersion(self.handle, _: i32))
+    pub fn get_firmware_version(&self) -> Result<FirmwareVersion> {
+        cci_get_call!(self, c_PigeonIMU_GetFirmwareVersion(self.handle, _: i32))
+            .map(FirmwareVersion::from_raw)
     }
+    /// Returns true if the device has reset since last call.
     pub fn has_reset_occurred(&self) -> Result<bool> {
-        cci_get_call!(c_PigeonIMU_HasResetOccurred(self.handle, _: bool))
+        let reset = cci_get_call!(self, c_PigeonIMU_HasResetOccurred(self.handle, _: bool))?;
+        if reset {
+            events().publish(DeviceEvent::Reset(self.device_info()));
+        }
+        Ok(reset)
     }
 
     /**
@@ -475,8 +575,11 @@ impl PigeonIMU {
         new_value: i32,
         param_index: i32,
         timeout_ms: i32,
-    ) -> ErrorCode {
-        unsafe { c_PigeonIMU_ConfigSetCustomParam(self.handle, new_value, param_index, timeout_ms) }
+    ) -> Result<()> {
+        cci_call!(
+            self,
+            c_PigeonIMU_ConfigSetCustomParam(self.handle, new_value, param_index, timeout_ms)
+        )
     }
     /**
      * Gets the value of a custom parameter. This is for arbitrary use.
@@ -487,7 +590,7 @@ impl PigeonIMU {
      *   If zero, no blocking or checking is performed.
      */
     pub fn config_get_custom_param(&self, param_index: i32, timout_ms: i32) -> Result<i32> {
-        cci_get_call!(c_PigeonIMU_ConfigGetCustomParam(
+        cci_get_call!(self, c_PigeonIMU_ConfigGetCustomParam(
             self.handle,
             _: i32,
             param_index,
@@ -508,8 +611,9 @@ impl PigeonIMU {
         sub_value: i32,
         ordinal: i32,
         timeout_ms: i32,
-    ) -> ErrorCode {
-        unsafe {
+    ) -> Result<()> {
+        cci_call!(
+            self,
             c_PigeonIMU_ConfigSetParameter(
                 self.handle,
                 param as _,
@@ -518,7 +622,7 @@ impl PigeonIMU {
                 ordinal,
                 timeout_ms,
             )
-        }
+        )
     }
     pub fn config_get_parameter(
         &self,
@@ -526,7 +630,7 @@ impl PigeonIMU {
         ordinal: i32,
         timeout_ms: i32,
     ) -> Result<f64> {
-        cci_get_call!(c_PigeonIMU_ConfigGetParameter(
+        cci_get_call!(self, c_PigeonIMU_ConfigGetParameter(
             self.handle,
             param as _,
             _: f64,
@@ -534,19 +638,31 @@ impl PigeonIMU {
             timeout_ms,
         ))
     }
+    /**
+     * Revert all configurations to factory default values.
+     *
+     * Requires Phoenix 2019 or later.
+     */
+    #[cfg(feature = "phoenix-2019")]
+    pub fn config_factory_default(&self, timeout_ms: i32) -> Result<()> {
+        cci_call!(
+            self,
+            c_PigeonIMU_ConfigFactoryDefault(self.handle, timeout_ms)
+        )
+    }
 
     pub fn get_faults(&self) -> Result<Faults> {
         Ok(Faults(
-            cci_get_call!(c_PigeonIMU_GetFaults(self.handle, _: i32))?,
+            cci_get_call!(self, c_PigeonIMU_GetFaults(self.handle, _: i32))?,
         ))
     }
     pub fn get_sticky_faults(&self) -> Result<StickyFaults> {
         Ok(StickyFaults(
-            cci_get_call!(c_PigeonIMU_GetStickyFaults(self.handle, _: i32))?,
+            cci_get_call!(self, c_PigeonIMU_GetStickyFaults(self.handle, _: i32))?,
         ))
     }
-    pub fn clear_sticky_faults(&self, timeout_ms: i32) -> ErrorCode {
-        unsafe { c_PigeonIMU_ClearStickyFaults(self.handle, timeout_ms) }
+    pub fn clear_sticky_faults(&self, timeout_ms: i32) -> Result<()> {
+        cci_call!(self, c_PigeonIMU_ClearStickyFaults(self.handle, timeout_ms))
     }
 
     pub fn set_status_frame_period(
@@ -554,14 +670,23 @@ impl PigeonIMU {
         frame: StatusFrame,
         period_ms: i32,
         timeout_ms: i32,
-    ) -> ErrorCode {
-        unsafe { c_PigeonIMU_SetStatusFramePeriod(self.handle, frame as _, period_ms, timeout_ms) }
+    ) -> Result<()> {
+        cci_call!(
+            self,
+            c_PigeonIMU_SetStatusFramePeriod(self.handle, frame as _, period_ms, timeout_ms)
+        )
     }
     pub fn get_status_frame_period(&self, frame: StatusFrame, timeout_ms: i32) -> Result<i32> {
-        cci_get_call!(c_PigeonIMU_GetStatusFramePeriod(self.handle, frame as _, _: i32, timeout_ms))
+        cci_get_call!(
+            self,
+            c_PigeonIMU_GetStatusFramePeriod(self.handle, frame as _, _: i32, timeout_ms)
+        )
     }
-    pub fn set_control_frame_period(&self, frame: ControlFrame, period_ms: i32) -> ErrorCode {
-        unsafe { c_PigeonIMU_SetControlFramePeriod(self.handle, frame as _, period_ms) }
+    pub fn set_control_frame_period(&self, frame: ControlFrame, period_ms: i32) -> Result<()> {
+        cci_call!(
+            self,
+            c_PigeonIMU_SetControlFramePeriod(self.handle, frame as _, period_ms)
+        )
     }
 }
 impl<'a> From<&'a TalonSRX> for PigeonIMU {
@@ -570,6 +695,27 @@ impl<'a> From<&'a TalonSRX> for PigeonIMU {
     fn from(talon_srx: &'a TalonSRX) -> PigeonIMU {
         let talon_device_id = talon_srx.get_device_id();
         let handle = unsafe { c_PigeonIMU_Create2(talon_device_id) };
-        PigeonIMU { handle }
+        PigeonIMU {
+            handle,
+            device_number: talon_device_id,
+            gadgeteer: true,
+            state: registry().register(DeviceType::PigeonIMU, talon_srx.id(), handle),
+        }
+    }
+}
+
+impl DeviceContext for PigeonIMU {
+    fn device_type(&self) -> DeviceType {
+        DeviceType::PigeonIMU
+    }
+    fn device_id(&self) -> i32 {
+        self.device_number
+    }
+    fn state(&self) -> &DeviceState {
+        &self.state
+    }
+    fn probe(&self) -> bool {
+        let mut temp = 0.;
+        unsafe { c_PigeonIMU_GetTemp(self.handle, &mut temp) }.is_ok()
     }
 }
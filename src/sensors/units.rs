@@ -0,0 +1,56 @@
+//! Conversions between sensor units and rotations.
+//!
+//! Positions are read in sensor units, and velocities in sensor units per 100ms.
+
+use motor_control::FeedbackDevice;
+
+/// The resolution of a sensor, for converting its readings to rotations.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SensorUnits {
+    units_per_rotation: f64,
+}
+
+impl SensorUnits {
+    pub fn new(units_per_rotation: f64) -> Self {
+        SensorUnits { units_per_rotation }
+    }
+    /// The native resolution of `device`, if it has a fixed one.
+    /// See `FeedbackDevice::units_per_rotation`.
+    pub fn for_device(device: FeedbackDevice) -> Option<Self> {
+        device.units_per_rotation().map(Self::new)
+    }
+    /// The units read through a selected feedback coefficient.
+    pub fn with_coefficient(self, coefficient: f64) -> Self {
+        Self::new(self.units_per_rotation * coefficient)
+    }
+    pub fn units_per_rotation(self) -> f64 {
+        self.units_per_rotation
+    }
+
+    /// Converts a position to rotations.
+    pub fn units_to_rotations(self, position: i32) -> f64 {
+        f64::from(position) / self.units_per_rotation
+    }
+    /// Converts rotations to a position, e.g. for a closed-loop target.
+    pub fn rotations_to_units(self, rotations: f64) -> f64 {
+        rotations * self.units_per_rotation
+    }
+    /// Converts a velocity to rotations per second.
+    pub fn velocity_to_rps(self, velocity: i32) -> f64 {
+        f64::from(velocity) * 10. / self.units_per_rotation
+    }
+    /// Converts rotations per second to a velocity, e.g. for a closed-loop target.
+    pub fn rps_to_velocity(self, rps: f64) -> f64 {
+        rps * self.units_per_rotation / 10.
+    }
+}
+
+#[test]
+fn mag_encoder_conversions() {
+    let mag = SensorUnits::for_device(FeedbackDevice::CTRE_MagEncoder_Relative).unwrap();
+    assert_eq!(mag.units_to_rotations(6144), 1.5);
+    assert_eq!(mag.velocity_to_rps(4096), 10.);
+    assert_eq!(mag.rps_to_velocity(1.), 409.6);
+    assert_eq!(mag.with_coefficient(0.5).rotations_to_units(1.), 2048.);
+    assert_eq!(SensorUnits::for_device(FeedbackDevice::SensorSum), None);
+}
@@ -0,0 +1,252 @@
+//! Closed-loop runaway protection.
+//!
+//! A sensor out of phase with the motor, or a misconfigured remote sensor, makes
+//! a closed loop push harder the further it gets from its target.  The result is
+//! a mechanism driven at full output into its hard stops.  [`RunawaySupervisor`]
+//! watches for that and stops the motor.
+//!
+//! [`RunawaySupervisor`]: struct.RunawaySupervisor.html
+
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use ctre_sys::clock;
+
+use motor_control::BaseMotorController;
+use Result;
+
+/// Thresholds for [`RunawaySupervisor`].
+///
+/// [`RunawaySupervisor`]: struct.RunawaySupervisor.html
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct RunawayConfig {
+    /// Motor output (as a fraction of full) at or above which the loop is considered saturated.
+    pub saturation: f64,
+    /// Closed-loop errors smaller than this (in sensor units) are ignored.
+    pub min_error: i32,
+    /// Velocities smaller than this (in sensor units per 100ms) are ignored.
+    pub min_velocity: i32,
+    /// How long a runaway condition must last before the supervisor trips.
+    pub trip_time: Duration,
+}
+
+impl Default for RunawayConfig {
+    fn default() -> Self {
+        RunawayConfig {
+            saturation: 0.9,
+            min_error: 100,
+            min_velocity: 50,
+            trip_time: Duration::from_millis(250),
+        }
+    }
+}
+
+/// The kind of runaway detected.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum RunawayKind {
+    /// The closed-loop error kept growing while the output was saturated.
+    ErrorDiverging,
+    /// The sensor moved opposite to the saturated output.
+    VelocityReversed,
+}
+
+/// A latched runaway trip.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct RunawayFault {
+    pub kind: RunawayKind,
+    /// When the supervisor tripped.
+    pub timestamp: Instant,
+    /// Closed-loop error when tripped.
+    pub error: i32,
+    /// Motor output (-1 to 1) when tripped.
+    pub output: f64,
+    /// Sensor velocity when tripped.
+    pub velocity: i32,
+}
+
+impl fmt::Display for RunawayFault {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let what = match self.kind {
+            RunawayKind::ErrorDiverging => "closed-loop error diverging",
+            RunawayKind::VelocityReversed => "sensor moving against output",
+        };
+        write!(
+            f,
+            "runaway: {} (error {}, output {:.2}, velocity {})",
+            what, self.error, self.output, self.velocity
+        )
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+struct Sample {
+    error: i32,
+    output: f64,
+    velocity: i32,
+}
+
+/// The detection logic, separate from the device so it can be tested.
+#[derive(Debug, Copy, Clone)]
+struct Detector {
+    config: RunawayConfig,
+    last_error: Option<i32>,
+    /// The condition currently being seen, and when it started.
+    suspect: Option<(RunawayKind, Instant)>,
+}
+
+impl Detector {
+    fn check(&mut self, sample: Sample, now: Instant) -> Option<RunawayFault> {
+        let config = &self.config;
+        let saturated = sample.output.abs() >= config.saturation;
+        let diverging = self.last_error.is_some_and(|last| {
+            sample.error.abs() >= config.min_error && sample.error.abs() > last.abs()
+        });
+        let reversed = sample.velocity.abs() >= config.min_velocity
+            && (sample.velocity > 0) != (sample.output > 0.);
+        self.last_error = Some(sample.error);
+
+        let kind = match (saturated, reversed, diverging) {
+            (false, _, _) => None,
+            (true, true, _) => Some(RunawayKind::VelocityReversed),
+            (true, false, true) => Some(RunawayKind::ErrorDiverging),
+            (true, false, false) => None,
+        };
+        let kind = match kind {
+            Some(kind) => kind,
+            None => {
+                self.suspect = None;
+                return None;
+            }
+        };
+        let since = match self.suspect {
+            Some((suspect, since)) if suspect == kind => since,
+            _ => {
+                self.suspect = Some((kind, now));
+                now
+            }
+        };
+        if now.duration_since(since) < self.config.trip_time {
+            return None;
+        }
+        Some(RunawayFault {
+            kind,
+            timestamp: now,
+            error: sample.error,
+            output: sample.output,
+            velocity: sample.velocity,
+        })
+    }
+
+    fn reset(&mut self) {
+        self.last_error = None;
+        self.suspect = None;
+    }
+}
+
+/**
+ * Trips a motor controller to neutral when its closed loop runs away.
+ *
+ * Arm the supervisor while a closed-loop mode is in use, and call [`update`]
+ * once per robot loop.  Once tripped, the fault is latched: every [`update`]
+ * neutralizes the output again until [`reset`] is called.  Code commanding the
+ * motor should check [`fault`] and stop calling `set` while tripped.
+ *
+ * [`update`]: #method.update
+ * [`reset`]: #method.reset
+ * [`fault`]: #method.fault
+ */
+#[derive(Debug)]
+pub struct RunawaySupervisor<'a, M: 'a> {
+    motor: &'a M,
+    pid_idx: i32,
+    armed: bool,
+    detector: Detector,
+    fault: Option<RunawayFault>,
+}
+
+impl<'a, M: BaseMotorController> RunawaySupervisor<'a, M> {
+    /// Supervises `motor`'s primary closed loop.  The supervisor starts disarmed.
+    pub fn new(motor: &'a M, config: RunawayConfig) -> Self {
+        RunawaySupervisor {
+            motor,
+            pid_idx: 0,
+            armed: false,
+            detector: Detector {
+                config,
+                last_error: None,
+                suspect: None,
+            },
+            fault: None,
+        }
+    }
+    /// Arms or disarms detection, e.g. when switching between closed-loop and open-loop modes.
+    /// Disarming doesn't clear a latched fault.
+    pub fn set_armed(&mut self, armed: bool) {
+        if armed != self.armed {
+            self.detector.reset();
+        }
+        self.armed = armed;
+    }
+    /// Samples the controller, tripping it to neutral if it has run away.
+    /// Returns the latched fault, if any, for the caller to report.
+    pub fn update(&mut self) -> Result<Option<RunawayFault>> {
+        if self.fault.is_some() {
+            self.motor.neutral_output();
+            return Ok(self.fault);
+        }
+        if !self.armed {
+            return Ok(None);
+        }
+        let sample = Sample {
+            error: self.motor.get_closed_loop_error(self.pid_idx)?,
+            output: self.motor.get_motor_output_percent()?,
+            velocity: self.motor.get_selected_sensor_velocity(self.pid_idx)?,
+        };
+        if let Some(fault) = self.detector.check(sample, clock::now()) {
+            self.motor.neutral_output();
+            self.fault = Some(fault);
+        }
+        Ok(self.fault)
+    }
+    /// The latched fault, if the supervisor has tripped.
+    pub fn fault(&self) -> Option<RunawayFault> {
+        self.fault
+    }
+    /// Clears a latched fault, allowing the motor to be driven again.
+    pub fn reset(&mut self) {
+        self.fault = None;
+        self.detector.reset();
+    }
+}
+
+#[test]
+fn detects_reversed_sensor() {
+    let start = Instant::now();
+    let at = |ms| start + Duration::from_millis(ms);
+    let mut detector = Detector {
+        config: RunawayConfig::default(),
+        last_error: None,
+        suspect: None,
+    };
+    // Saturated forwards while the sensor moves backwards, so the error keeps growing.
+    let sample = |t: i32| Sample {
+        error: 1000 + t * 10,
+        output: 1.0,
+        velocity: -200,
+    };
+    assert_eq!(detector.check(sample(0), at(0)), None);
+    assert_eq!(detector.check(sample(1), at(100)), None);
+    let fault = detector.check(sample(3), at(300)).unwrap();
+    assert_eq!(fault.kind, RunawayKind::VelocityReversed);
+
+    // A loop that is saturated but converging is fine.
+    detector.reset();
+    for t in 0..10 {
+        let converging = Sample {
+            error: 1000 - t * 50,
+            output: 1.0,
+            velocity: 200,
+        };
+        assert_eq!(detector.check(converging, at(t as u64 * 100)), None);
+    }
+}
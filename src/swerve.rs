@@ -0,0 +1,400 @@
+//! Checking swerve steering alignment against absolute encoders.
+//!
+//! Swerve steering is usually closed on a relative sensor, seeded at startup
+//! from an absolute encoder and a per-module offset.  A slipped magnet, wrong
+//! offset or missed seeding shows up as a wheel pointing the wrong way, which
+//! [`verify_absolute_alignment`] catches before the robot drives.
+//! [`bind_absolute_encoder`] checks an absolute encoder is healthy before a
+//! Talon closes a loop on it, as a weak or missing magnet otherwise goes
+//! unnoticed until the wheels point the wrong way.
+//!
+//! [`verify_absolute_alignment`]: fn.verify_absolute_alignment.html
+//! [`bind_absolute_encoder`]: fn.bind_absolute_encoder.html
+
+use std::error;
+use std::fmt;
+use std::time::Duration;
+
+use ctre_sys::clock;
+use motor_control::{BaseMotorController, FeedbackDevice, PidLoop, SensorCollection, TalonSRX};
+use {DeviceError, Result};
+
+/// The absolute encoder wired to a steering Talon SRX's data port.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AbsoluteSource {
+    /// A pulse width encoder, such as the CTRE Mag Encoder, 4096 units per rotation.
+    PulseWidth,
+    /// An analog encoder, such as the MA3, 1024 units per rotation.
+    Analog,
+}
+
+impl AbsoluteSource {
+    pub fn units_per_rotation(self) -> f64 {
+        match self {
+            AbsoluteSource::PulseWidth => 4096.,
+            AbsoluteSource::Analog => 1024.,
+        }
+    }
+    fn read(self, talon: &TalonSRX) -> Result<i32> {
+        match self {
+            AbsoluteSource::PulseWidth => talon.get_pulse_width_position(),
+            AbsoluteSource::Analog => talon.get_analog_in_raw(),
+        }
+    }
+    fn feedback_device(self) -> FeedbackDevice {
+        match self {
+            AbsoluteSource::PulseWidth => FeedbackDevice::PulseWidthEncodedPosition,
+            AbsoluteSource::Analog => FeedbackDevice::Analog,
+        }
+    }
+}
+
+/// A swerve module's steering Talon SRX and how its sensors relate.
+#[derive(Debug, Copy, Clone)]
+pub struct SteeringModule<'a> {
+    pub name: &'static str,
+    /// Closes the steering loop on its selected sensor (PID0).
+    pub talon: &'a TalonSRX,
+    pub absolute: AbsoluteSource,
+    /// Absolute encoder reading with the wheel pointing straight ahead.
+    pub offset: f64,
+    /// Selected sensor units per rotation of the wheel's steering.
+    pub units_per_rotation: f64,
+}
+
+/// Wraps an angle in degrees to [-180, 180].
+fn wrap_degrees(degrees: f64) -> f64 {
+    degrees - 360. * (degrees / 360.).round()
+}
+
+/// Settings for [`verify_absolute_alignment`].
+///
+/// [`verify_absolute_alignment`]: fn.verify_absolute_alignment.html
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct AlignmentConfig {
+    /// Largest allowed difference between the sensors, in degrees.  Defaults to 3.
+    pub tolerance_deg: f64,
+    /// Re-seed the selected sensor of misaligned modules from the absolute encoder.
+    pub reseed: bool,
+    /// Timeout for re-seeding, in ms.  Defaults to 50.
+    pub timeout_ms: i32,
+}
+
+impl Default for AlignmentConfig {
+    fn default() -> Self {
+        AlignmentConfig {
+            tolerance_deg: 3.,
+            reseed: false,
+            timeout_ms: 50,
+        }
+    }
+}
+
+/// How one module's sensors compared.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ModuleAlignment {
+    pub name: &'static str,
+    /// Steering angle from the absolute encoder and offset, in degrees.
+    pub absolute_deg: f64,
+    /// Steering angle from the selected sensor, in degrees.
+    pub relative_deg: f64,
+    /// `absolute_deg - relative_deg`, wrapped to [-180, 180].
+    pub error_deg: f64,
+    /// Whether the error is within tolerance.
+    pub aligned: bool,
+    /// Whether the selected sensor was re-seeded.
+    pub reseeded: bool,
+}
+
+/// e.g. `front_left: absolute 90.0°, relative 0.0°, error 90.0° MISALIGNED (re-seeded)`
+impl fmt::Display for ModuleAlignment {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}: absolute {:.1}°, relative {:.1}°, error {:.1}°",
+            self.name, self.absolute_deg, self.relative_deg, self.error_deg
+        )?;
+        if !self.aligned {
+            f.write_str(" MISALIGNED")?;
+        }
+        if self.reseeded {
+            f.write_str(" (re-seeded)")?;
+        }
+        Ok(())
+    }
+}
+
+fn verify_module(module: &SteeringModule, config: &AlignmentConfig) -> Result<ModuleAlignment> {
+    let raw = f64::from(module.absolute.read(module.talon)?);
+    let absolute_deg =
+        wrap_degrees((raw - module.offset) / module.absolute.units_per_rotation() * 360.);
+    let relative = f64::from(module.talon.get_selected_sensor_position(0)?);
+    let relative_deg = wrap_degrees(relative / module.units_per_rotation * 360.);
+    let error_deg = wrap_degrees(absolute_deg - relative_deg);
+    let aligned = error_deg.abs() <= config.tolerance_deg;
+    let reseeded = !aligned && config.reseed;
+    if reseeded {
+        // Keep the wheel's rotation count, so the steering loop doesn't unwind.
+        let seeded = relative + error_deg / 360. * module.units_per_rotation;
+        module
+            .talon
+            .set_selected_sensor_position(seeded.round() as i32, 0, config.timeout_ms)?;
+    }
+    Ok(ModuleAlignment {
+        name: module.name,
+        absolute_deg,
+        relative_deg,
+        error_deg,
+        aligned,
+        reseeded,
+    })
+}
+
+/**
+ * Compares each module's absolute steering angle with its selected sensor,
+ * flagging (and, per `config`, re-seeding) modules that disagree.
+ *
+ * Run it after seeding, e.g. at the end of robot init, and log the results:
+ *
+ * ```no_run
+ * # use ctre::DeviceId;
+ * # use ctre::motor_control::{BaseMotorController, TalonSRX};
+ * # use ctre::swerve::{verify_absolute_alignment, AbsoluteSource, AlignmentConfig, SteeringModule};
+ * let steer = TalonSRX::new(DeviceId::new(5));
+ * let modules = [SteeringModule {
+ *     name: "front_left",
+ *     talon: &steer,
+ *     absolute: AbsoluteSource::PulseWidth,
+ *     offset: 1234.,
+ *     units_per_rotation: 4096.,
+ * }];
+ * for result in verify_absolute_alignment(&modules, &AlignmentConfig::default()) {
+ *     match result {
+ *         Ok(alignment) => println!("{}", alignment),
+ *         Err(err) => eprintln!("{}", err),
+ *     }
+ * }
+ * ```
+ *
+ * Returns a result per module, in order; a module whose sensors can't be read
+ * doesn't stop the others being checked.
+ */
+pub fn verify_absolute_alignment(
+    modules: &[SteeringModule],
+    config: &AlignmentConfig,
+) -> Vec<Result<ModuleAlignment>> {
+    modules
+        .iter()
+        .map(|module| verify_module(module, config))
+        .collect()
+}
+
+/// Settings for [`bind_absolute_encoder`].
+///
+/// [`bind_absolute_encoder`]: fn.bind_absolute_encoder.html
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct EncoderCheckConfig {
+    /// The loop to close on the absolute encoder.  Defaults to the primary loop.
+    pub pid_idx: PidLoop,
+    /// Readings taken to check the position is steady.  Defaults to 5.
+    pub samples: u32,
+    /// Time between readings.  Defaults to 10 ms.
+    pub sample_period: Duration,
+    /// Largest allowed spread of the readings, in encoder units.  Defaults to 8.
+    pub max_spread: i32,
+    /// Timeout for selecting the sensor, in ms.  Defaults to 50.
+    pub timeout_ms: i32,
+}
+
+impl Default for EncoderCheckConfig {
+    fn default() -> Self {
+        EncoderCheckConfig {
+            pid_idx: PidLoop::Primary,
+            samples: 5,
+            sample_period: Duration::from_millis(10),
+            max_spread: 8,
+            timeout_ms: 50,
+        }
+    }
+}
+
+/// Why [`bind_absolute_encoder`] didn't bind a module's absolute encoder.
+///
+/// [`bind_absolute_encoder`]: fn.bind_absolute_encoder.html
+#[derive(Debug, Clone, PartialEq)]
+pub enum AbsoluteEncoderError {
+    /// A call to the module's Talon failed.
+    Device(&'static str, DeviceError),
+    /// A pulse width encoder sends no pulses, or a stuck signal: it is
+    /// unplugged, or its magnet is missing or too weak.
+    NoSignal {
+        name: &'static str,
+        rise_to_rise_us: i32,
+        rise_to_fall_us: i32,
+    },
+    /// The readings moved more than allowed with the wheel at rest, as with a
+    /// loose or marginal magnet.
+    Unsteady { name: &'static str, spread: i32 },
+}
+
+impl fmt::Display for AbsoluteEncoderError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            AbsoluteEncoderError::Device(name, ref err) => write!(f, "{}: {}", name, err),
+            AbsoluteEncoderError::NoSignal {
+                name,
+                rise_to_rise_us,
+                rise_to_fall_us,
+            } => write!(
+                f,
+                "{}: no absolute encoder signal (period {} us, pulse {} us); check the magnet",
+                name, rise_to_rise_us, rise_to_fall_us
+            ),
+            AbsoluteEncoderError::Unsteady { name, spread } => write!(
+                f,
+                "{}: absolute encoder readings spread over {} units at rest; check the magnet",
+                name, spread
+            ),
+        }
+    }
+}
+
+impl error::Error for AbsoluteEncoderError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            AbsoluteEncoderError::Device(_, ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/**
+ * Checks a module's absolute encoder, then selects it as the feedback sensor
+ * for `config.pid_idx`.  Returns the last absolute reading.
+ *
+ * A pulse width encoder must be sending pulses, which stop when its magnet is
+ * missing or too weak; an analog encoder can't report its magnet.  Either way
+ * the readings, taken with the wheel at rest, must stay within
+ * `config.max_spread`.  Nothing is selected if a check fails.
+ *
+ * ```no_run
+ * # use ctre::DeviceId;
+ * # use ctre::motor_control::{BaseMotorController, TalonSRX};
+ * # use ctre::swerve::{bind_absolute_encoder, AbsoluteSource, EncoderCheckConfig, SteeringModule};
+ * let steer = TalonSRX::new(DeviceId::new(5));
+ * let module = SteeringModule {
+ *     name: "front_left",
+ *     talon: &steer,
+ *     absolute: AbsoluteSource::PulseWidth,
+ *     offset: 1234.,
+ *     units_per_rotation: 4096.,
+ * };
+ * if let Err(err) = bind_absolute_encoder(&module, &EncoderCheckConfig::default()) {
+ *     eprintln!("{}", err);
+ * }
+ * ```
+ */
+pub fn bind_absolute_encoder(
+    module: &SteeringModule,
+    config: &EncoderCheckConfig,
+) -> ::std::result::Result<i32, AbsoluteEncoderError> {
+    let device = |err| AbsoluteEncoderError::Device(module.name, err);
+    let talon = module.talon;
+    if module.absolute == AbsoluteSource::PulseWidth {
+        let rise_to_rise_us = talon.get_pulse_width_rise_to_rise_us().map_err(device)?;
+        let rise_to_fall_us = talon.get_pulse_width_rise_to_fall_us().map_err(device)?;
+        if rise_to_rise_us <= 0 || rise_to_fall_us <= 0 || rise_to_fall_us >= rise_to_rise_us {
+            return Err(AbsoluteEncoderError::NoSignal {
+                name: module.name,
+                rise_to_rise_us,
+                rise_to_fall_us,
+            });
+        }
+    }
+
+    // Spread around the first reading, so readings either side of the
+    // encoder's wrap-around point count as close.
+    let units = module.absolute.units_per_rotation() as i32;
+    let first = module.absolute.read(talon).map_err(device)?;
+    let (mut low, mut high, mut last) = (0, 0, first);
+    for _ in 1..config.samples {
+        clock::sleep(config.sample_period);
+        last = module.absolute.read(talon).map_err(device)?;
+        let offset = (last - first + units / 2).rem_euclid(units) - units / 2;
+        low = low.min(offset);
+        high = high.max(offset);
+    }
+    if high - low > config.max_spread {
+        return Err(AbsoluteEncoderError::Unsteady {
+            name: module.name,
+            spread: high - low,
+        });
+    }
+
+    talon
+        .config_selected_feedback_sensor(
+            module.absolute.feedback_device(),
+            config.pid_idx,
+            config.timeout_ms,
+        )
+        .map_err(device)?;
+    Ok(last)
+}
+
+#[test]
+fn absolute_encoder_is_checked_before_binding() {
+    use DeviceId;
+    let talon = TalonSRX::new(DeviceId::new(17));
+    let mut module = SteeringModule {
+        name: "back_right",
+        talon: &talon,
+        absolute: AbsoluteSource::PulseWidth,
+        offset: 0.,
+        units_per_rotation: 4096.,
+    };
+    let config = EncoderCheckConfig {
+        sample_period: Duration::from_millis(1),
+        ..EncoderCheckConfig::default()
+    };
+    // No pulses from the encoder.
+    let err = bind_absolute_encoder(&module, &config).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "back_right: no absolute encoder signal (period 0 us, pulse 0 us); check the magnet"
+    );
+
+    module.absolute = AbsoluteSource::Analog;
+    talon.set_analog_position(600, 0).unwrap();
+    assert_eq!(bind_absolute_encoder(&module, &config), Ok(600));
+    assert_eq!(talon.get_selected_sensor_position(0), Ok(600));
+}
+
+#[test]
+fn misaligned_module_is_reseeded() {
+    use DeviceId;
+    let talon = TalonSRX::new(DeviceId::new(31));
+    let module = SteeringModule {
+        name: "front_left",
+        talon: &talon,
+        absolute: AbsoluteSource::PulseWidth,
+        offset: 100.,
+        units_per_rotation: 4096.,
+    };
+    // A quarter turn from straight ahead, with the selected sensor a whole turn round.
+    talon.set_pulse_width_position(100 + 1024, 0).unwrap();
+    talon.set_selected_sensor_position(4096, 0, 0).unwrap();
+    let config = AlignmentConfig {
+        reseed: true,
+        ..AlignmentConfig::default()
+    };
+    let alignment = verify_absolute_alignment(&[module], &config)[0].unwrap();
+    assert_eq!(alignment.error_deg, 90.);
+    assert!(!alignment.aligned && alignment.reseeded);
+    assert_eq!(
+        alignment.to_string(),
+        "front_left: absolute 90.0°, relative 0.0°, error 90.0° MISALIGNED (re-seeded)"
+    );
+    assert_eq!(talon.get_selected_sensor_position(0), Ok(4096 + 1024));
+    let alignment = verify_absolute_alignment(&[module], &config)[0].unwrap();
+    assert!(alignment.aligned && !alignment.reseeded);
+}
@@ -0,0 +1,190 @@
+//! Lowering a Talon SRX's current limit as it heats up.
+//!
+//! A Talon SRX driving a heavily loaded mechanism for a long match or demo can
+//! overheat.  [`ThermalGuard`] watches its temperature and steps the continuous
+//! current limit down as it rises, and back up once it has cooled.
+//!
+//! [`ThermalGuard`]: struct.ThermalGuard.html
+
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicI32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use ctre_sys::mot::{c_MotController_ConfigContinuousCurrentLimit, c_MotController_GetTemperature};
+
+use motor_control::{shared_state, TalonSRX};
+use scheduler::{self, TaskHandle};
+use ErrorCode;
+
+/// A temperature at or above which a lower current limit applies.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ThermalStage {
+    /// Controller temperature, in °C.
+    pub temperature: f64,
+    /// Continuous current limit, in amps.
+    pub limit_amps: i32,
+}
+
+/// How the continuous current limit follows the controller's temperature.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ThermalPolicy {
+    /// Continuous current limit below the first stage, in amps.
+    pub base_limit_amps: i32,
+    /// Lower limits at higher temperatures, in order of increasing temperature.
+    pub stages: Vec<ThermalStage>,
+    /// How far below a stage's temperature the controller must cool to leave it, in °C.
+    pub hysteresis: f64,
+    /// How often the guard checks the temperature.
+    pub period: Duration,
+}
+
+impl Default for ThermalPolicy {
+    fn default() -> Self {
+        let stage = |temperature, limit_amps| ThermalStage {
+            temperature,
+            limit_amps,
+        };
+        ThermalPolicy {
+            base_limit_amps: 40,
+            stages: vec![stage(60., 30), stage(75., 20), stage(85., 10)],
+            hysteresis: 5.,
+            period: Duration::from_millis(500),
+        }
+    }
+}
+
+/// Tracks the stage a policy is in, given temperature readings.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ThermalLimiter {
+    policy: ThermalPolicy,
+    /// Number of stages entered.
+    stage: usize,
+}
+
+impl ThermalLimiter {
+    pub fn new(policy: ThermalPolicy) -> Self {
+        ThermalLimiter { policy, stage: 0 }
+    }
+    /// The current limit for the current stage, in amps.
+    pub fn limit_amps(&self) -> i32 {
+        match self.stage {
+            0 => self.policy.base_limit_amps,
+            stage => self.policy.stages[stage - 1].limit_amps,
+        }
+    }
+    /// Moves between stages for a temperature reading.
+    /// Returns the new limit if it changed.
+    pub fn update(&mut self, temperature: f64) -> Option<i32> {
+        let stages = &self.policy.stages;
+        let mut stage = self.stage;
+        while stage < stages.len() && temperature >= stages[stage].temperature {
+            stage += 1;
+        }
+        while stage > 0 && temperature < stages[stage - 1].temperature - self.policy.hysteresis {
+            stage -= 1;
+        }
+        if stage == self.stage {
+            return None;
+        }
+        self.stage = stage;
+        Some(self.limit_amps())
+    }
+}
+
+/**
+ * A scheduler task which applies a [`ThermalPolicy`] to a Talon SRX's
+ * continuous current limit.  The task stops when the guard is dropped.  It
+ * follows the Talon to a new device if it is rebound with `rebind`, applying
+ * the current limit there too.
+ *
+ * The base limit is applied when the guard starts.  Current limiting must be
+ * enabled separately, with `enable_current_limit`.
+ *
+ * [`ThermalPolicy`]: struct.ThermalPolicy.html
+ */
+#[derive(Debug)]
+pub struct ThermalGuard<'a> {
+    limit_amps: Arc<AtomicI32>,
+    _task: TaskHandle,
+    /// Ties the guard to the Talon it limits.
+    _talon: PhantomData<&'a TalonSRX>,
+}
+
+impl<'a> ThermalGuard<'a> {
+    pub fn start(talon: &'a TalonSRX, policy: ThermalPolicy) -> Self {
+        let limit_amps = Arc::new(AtomicI32::new(0));
+        let state = shared_state(talon);
+        let task = {
+            let applied = Arc::clone(&limit_amps);
+            let period = policy.period;
+            let mut limiter = ThermalLimiter::new(policy);
+            let mut pending = None;
+            // The handle the limit was last sent through, as an address so the task stays `Send`.
+            let mut limited = 0;
+            scheduler::schedule_named("ctre-thermal", period, move || {
+                // The CCI functions lock internally, so they are safe to call from any thread.
+                let talon = state.handle();
+                if talon as usize != limited {
+                    // A new device, after `rebind`, needs the limit too.
+                    pending = Some(limiter.limit_amps());
+                    limited = talon as usize;
+                }
+                let mut temperature = 0.;
+                let code = unsafe { c_MotController_GetTemperature(talon, &mut temperature) };
+                if code == ErrorCode::OK {
+                    pending = limiter.update(temperature).or(pending);
+                }
+                if let Some(amps) = pending {
+                    // Retry next period if the config doesn't go through.
+                    let code =
+                        unsafe { c_MotController_ConfigContinuousCurrentLimit(talon, amps, 0) };
+                    if code == ErrorCode::OK {
+                        applied.store(amps, Ordering::Relaxed);
+                        pending = None;
+                    }
+                }
+            })
+        };
+        ThermalGuard {
+            limit_amps,
+            _task: task,
+            _talon: PhantomData,
+        }
+    }
+    /// The continuous current limit last applied, in amps, or 0 before the first.
+    pub fn limit_amps(&self) -> i32 {
+        self.limit_amps.load(Ordering::Relaxed)
+    }
+}
+
+#[test]
+fn limiter_steps_with_hysteresis() {
+    let mut limiter = ThermalLimiter::new(ThermalPolicy::default());
+    assert_eq!(limiter.update(40.), None);
+    assert_eq!(limiter.update(76.), Some(20));
+    assert_eq!(limiter.update(72.), None);
+    assert_eq!(limiter.update(69.), Some(30));
+    assert_eq!(limiter.update(56.), None);
+    assert_eq!(limiter.update(54.), Some(40));
+}
+
+#[test]
+fn guard_applies_base_limit() {
+    use motor_control::BaseMotorController;
+    use std::thread;
+    use DeviceId;
+    use ParamEnum;
+    let talon = TalonSRX::new(DeviceId::new(22));
+    let policy = ThermalPolicy {
+        base_limit_amps: 35,
+        period: Duration::from_millis(1),
+        ..ThermalPolicy::default()
+    };
+    let guard = ThermalGuard::start(&talon, policy);
+    thread::sleep(Duration::from_millis(20));
+    assert_eq!(guard.limit_amps(), 35);
+    drop(guard);
+    let limit = talon.config_get_parameter(ParamEnum::ContinuousCurrentLimitAmps, 0, 0);
+    assert_eq!(limit, Ok(35.));
+}
@@ -0,0 +1,215 @@
+//! Detecting the robot tipping over, and ramping the drivetrain gently until it recovers.
+//!
+//! Hard acceleration is the usual way a tall robot tips.  [`TipGuard`] watches
+//! a Pigeon's pitch and roll and, while the robot is tipping, gives the drive
+//! controllers a slower ramp so the driver can't make it worse.
+//!
+//! [`TipGuard`]: struct.TipGuard.html
+
+use std::fmt;
+
+use motor_control::BaseMotorController;
+use ramp::{RampDevice, RampProfile};
+use sensors::pigeon::PigeonIMU;
+use Result;
+
+/// When the robot counts as tipping, and what to do about it.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TipConfig {
+    /// Pitch or roll, in degrees, beyond which the robot is tipping.  Defaults to 15.
+    pub max_tilt: f64,
+    /**
+     * Rate, in degrees per second, at which a tilt growing past `rate_tilt`
+     * counts as tipping, before it reaches `max_tilt`.  Defaults to 40.
+     */
+    pub max_tilt_rate: f64,
+    /// Tilt, in degrees, above which `max_tilt_rate` applies.  Defaults to 5.
+    pub rate_tilt: f64,
+    /// Pitch and roll, in degrees, below which the robot has recovered.  Defaults to 3.
+    pub recovered_tilt: f64,
+    /// Ramps while tipping.  Defaults to 1 second to full output, open and closed loop.
+    pub tipping_ramp: RampProfile,
+    /// Ramps once recovered.  Defaults to none.
+    pub normal_ramp: RampProfile,
+    /// Timeout for configuring the ramps, in ms.  Defaults to 0, so updates don't block.
+    pub timeout_ms: i32,
+}
+
+impl Default for TipConfig {
+    fn default() -> Self {
+        TipConfig {
+            max_tilt: 15.,
+            max_tilt_rate: 40.,
+            rate_tilt: 5.,
+            recovered_tilt: 3.,
+            tipping_ramp: RampProfile::new(1., 1.),
+            normal_ramp: RampProfile::default(),
+            timeout_ms: 0,
+        }
+    }
+}
+
+impl TipConfig {
+    /// Whether a tilt of `angle` degrees, changing at `rate` degrees per second, is tipping.
+    fn is_tipping(&self, angle: f64, rate: f64) -> bool {
+        let growing = angle.signum() * rate;
+        angle.abs() >= self.max_tilt
+            || (angle.abs() >= self.rate_tilt && growing >= self.max_tilt_rate)
+    }
+    /**
+     * Whether the robot is tipping after a reading of `[pitch, roll]` and their
+     * rates, given whether it was tipping before.
+     */
+    fn update(&self, tipping: bool, angles: [f64; 2], rates: [f64; 2]) -> bool {
+        if self.is_tipping(angles[0], rates[0]) || self.is_tipping(angles[1], rates[1]) {
+            true
+        } else if tipping {
+            angles
+                .iter()
+                .any(|angle| angle.abs() >= self.recovered_tilt)
+        } else {
+            false
+        }
+    }
+}
+
+/**
+ * Watches a Pigeon for the robot tipping, and slows the drivetrain's ramps
+ * while it is.
+ *
+ * Call [`update`] periodically, e.g. once per robot loop.  The ramps are only
+ * configured when the robot starts tipping or recovers, and are retried on the
+ * next update if configuring them fails.
+ *
+ * Pitch and roll are about the Pigeon's own axes, so mount it flat.
+ *
+ * ```no_run
+ * # use ctre::DeviceId;
+ * # use ctre::motor_control::{BaseMotorController, TalonSRX};
+ * # use ctre::sensors::pigeon::PigeonIMU;
+ * # use ctre::tip::{TipConfig, TipGuard};
+ * # fn main() -> ctre::Result<()> {
+ * let (left, right) = (TalonSRX::new(DeviceId::new(1)), TalonSRX::new(DeviceId::new(2)));
+ * let pigeon = PigeonIMU::new(DeviceId::new(0));
+ * let mut guard = TipGuard::new(&pigeon, TipConfig::default());
+ * guard.add(&left).add(&right);
+ * loop {
+ *     if guard.update()? {
+ *         // Maybe also lower the elevator.
+ *     }
+ * #   break;
+ * }
+ * # Ok(())
+ * # }
+ * ```
+ *
+ * [`update`]: #method.update
+ */
+pub struct TipGuard<'a> {
+    pigeon: &'a PigeonIMU,
+    motors: Vec<&'a dyn RampDevice>,
+    config: TipConfig,
+    tipping: bool,
+    /// Whether the ramps for the current state still need to be configured.
+    pending: bool,
+}
+
+impl<'a> TipGuard<'a> {
+    pub fn new(pigeon: &'a PigeonIMU, config: TipConfig) -> Self {
+        TipGuard {
+            pigeon,
+            motors: Vec::new(),
+            config,
+            tipping: false,
+            pending: false,
+        }
+    }
+    /// Adds a drive controller.  It gets the normal ramp on the next update.
+    pub fn add<M: BaseMotorController>(&mut self, motor: &'a M) -> &mut Self {
+        self.motors.push(motor);
+        self.pending = true;
+        self
+    }
+    /// Whether the robot was tipping at the last update.
+    pub fn is_tipping(&self) -> bool {
+        self.tipping
+    }
+    /**
+     * Reads the Pigeon, returning whether the robot is tipping, and switches
+     * the ramps if that changed.
+     *
+     * If configuring a controller fails, the rest are still configured and the
+     * first error is returned.
+     */
+    pub fn update(&mut self) -> Result<bool> {
+        let [_, pitch, roll] = self.pigeon.get_yaw_pitch_roll()?;
+        let [roll_rate, pitch_rate, _] = self.pigeon.get_raw_gyro()?;
+        self.update_with([pitch, roll], [pitch_rate, roll_rate])
+    }
+
+    fn update_with(&mut self, angles: [f64; 2], rates: [f64; 2]) -> Result<bool> {
+        let tipping = self.config.update(self.tipping, angles, rates);
+        if tipping != self.tipping {
+            self.tipping = tipping;
+            self.pending = true;
+        }
+        if self.pending {
+            let ramp = if tipping {
+                &self.config.tipping_ramp
+            } else {
+                &self.config.normal_ramp
+            };
+            let mut result = Ok(());
+            for motor in &self.motors {
+                result = result.and(motor.apply_ramp(ramp, self.config.timeout_ms));
+            }
+            result?;
+            self.pending = false;
+        }
+        Ok(tipping)
+    }
+}
+
+impl<'a> fmt::Debug for TipGuard<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("TipGuard")
+            .field("motors", &self.motors.len())
+            .field("config", &self.config)
+            .field("tipping", &self.tipping)
+            .finish()
+    }
+}
+
+#[test]
+fn tipping_has_hysteresis() {
+    let config = TipConfig::default();
+    // A fast swing near level is a bump, not a tip.
+    assert!(!config.update(false, [2., 0.], [80., 0.]));
+    assert!(config.update(false, [0., -6.], [0., -50.]));
+    // Falling back towards level quickly isn't tipping.
+    assert!(!config.update(false, [8., 0.], [-50., 0.]));
+    assert!(config.update(false, [16., 0.], [0., 0.]));
+    assert!(config.update(true, [4., 0.], [-10., 0.]));
+    assert!(!config.update(true, [2., 1.], [-10., 0.]));
+}
+
+#[test]
+fn guard_switches_ramps() {
+    use motor_control::TalonSRX;
+    use DeviceId;
+    use ParamEnum;
+    let talon = TalonSRX::new(DeviceId::new(30));
+    let pigeon = PigeonIMU::new(DeviceId::new(19));
+    let mut guard = TipGuard::new(&pigeon, TipConfig::default());
+    guard.add(&talon);
+    let ramp = || {
+        talon
+            .config_get_parameter(ParamEnum::OpenloopRamp, 0, 0)
+            .unwrap()
+    };
+    assert!(guard.update_with([20., 0.], [0., 0.]).unwrap());
+    assert_eq!(ramp(), 1.);
+    // The simulated Pigeon is level.
+    assert!(!guard.update().unwrap());
+    assert_eq!(ramp(), 0.);
+}
@@ -0,0 +1,110 @@
+//! [`uom`] quantities for values with physical units.
+//!
+//! Sensor positions and velocities are in native units that depend on the
+//! sensor, so converting them to angles takes the sensor's [`SensorUnits`].
+//!
+//! [`uom`]: https://docs.rs/uom
+//! [`SensorUnits`]: ../sensors/units/struct.SensorUnits.html
+
+use std::convert::TryInto;
+
+use uom::si::angle::revolution;
+use uom::si::angular_velocity::revolution_per_second;
+use uom::si::electric_current::ampere;
+use uom::si::electric_potential::volt;
+use uom::si::f64::{Angle, AngularVelocity, ElectricCurrent, ElectricPotential};
+use uom::si::f64::{ThermodynamicTemperature, Time};
+use uom::si::thermodynamic_temperature::degree_celsius;
+use uom::si::time::millisecond;
+
+use canifier::CANifier;
+use motor_control::{BaseMotorController, PidLoop, TalonSRX};
+use sensors::pigeon::PigeonIMU;
+use sensors::units::SensorUnits;
+use Result;
+
+/// `uom` versions of the unit-bearing [`BaseMotorController`] methods.
+///
+/// [`BaseMotorController`]: ../motor_control/trait.BaseMotorController.html
+pub trait MotorControllerUnits: BaseMotorController {
+    fn bus_voltage(&self) -> Result<ElectricPotential> {
+        self.get_bus_voltage().map(ElectricPotential::new::<volt>)
+    }
+    fn motor_output_voltage(&self) -> Result<ElectricPotential> {
+        self.get_motor_output_voltage()
+            .map(ElectricPotential::new::<volt>)
+    }
+    fn output_current(&self) -> Result<ElectricCurrent> {
+        self.get_output_current()
+            .map(ElectricCurrent::new::<ampere>)
+    }
+    fn temperature(&self) -> Result<ThermodynamicTemperature> {
+        self.get_temperature()
+            .map(ThermodynamicTemperature::new::<degree_celsius>)
+    }
+    /// The selected sensor position as an angle of the sensor.
+    fn selected_sensor_angle(
+        &self,
+        pid_idx: impl TryInto<PidLoop>,
+        units: SensorUnits,
+    ) -> Result<Angle> {
+        let pid_idx = cci_index!(self, selected_sensor_angle, pid_idx: PidLoop)? as i32;
+        self.get_selected_sensor_position(pid_idx)
+            .map(|position| Angle::new::<revolution>(units.units_to_rotations(position)))
+    }
+    /// The selected sensor velocity as an angular velocity of the sensor.
+    fn selected_sensor_angular_velocity(
+        &self,
+        pid_idx: impl TryInto<PidLoop>,
+        units: SensorUnits,
+    ) -> Result<AngularVelocity> {
+        let pid_idx = cci_index!(self, selected_sensor_angular_velocity, pid_idx: PidLoop)? as i32;
+        self.get_selected_sensor_velocity(pid_idx).map(|velocity| {
+            AngularVelocity::new::<revolution_per_second>(units.velocity_to_rps(velocity))
+        })
+    }
+    /// Configures the voltage that full output is scaled to when voltage compensation is enabled.
+    fn config_voltage_comp_saturation_to(
+        &self,
+        voltage: ElectricPotential,
+        timeout_ms: i32,
+    ) -> Result<()> {
+        self.config_voltage_comp_saturation(voltage.get::<volt>(), timeout_ms)
+    }
+}
+
+impl<T: BaseMotorController> MotorControllerUnits for T {}
+
+/// Current limits take whole amps and milliseconds; quantities are rounded.
+impl TalonSRX {
+    pub fn config_peak_current_limit_to(
+        &self,
+        current: ElectricCurrent,
+        timeout_ms: i32,
+    ) -> Result<()> {
+        self.config_peak_current_limit(current.get::<ampere>().round() as i32, timeout_ms)
+    }
+    pub fn config_peak_current_duration_to(&self, duration: Time, timeout_ms: i32) -> Result<()> {
+        self.config_peak_current_duration(duration.get::<millisecond>().round() as i32, timeout_ms)
+    }
+    pub fn config_continuous_current_limit_to(
+        &self,
+        current: ElectricCurrent,
+        timeout_ms: i32,
+    ) -> Result<()> {
+        self.config_continuous_current_limit(current.get::<ampere>().round() as i32, timeout_ms)
+    }
+}
+
+impl CANifier {
+    pub fn bus_voltage(&self) -> Result<ElectricPotential> {
+        self.get_bus_voltage().map(ElectricPotential::new::<volt>)
+    }
+}
+
+impl PigeonIMU {
+    pub fn temperature(&self) -> Result<ThermodynamicTemperature> {
+        self.get_temp()
+            .map(ThermodynamicTemperature::new::<degree_celsius>)
+    }
+}
@@ -0,0 +1,99 @@
+//! WPILib-style motor controller interface.
+//!
+//! This mirrors WPILib's `SpeedController` so CTRE motor controllers can be used
+//! by drive code written against that interface.  As with CTRE's `WPI_TalonSRX`,
+//! the controller is wrapped so the last set speed and inversion can be reported
+//! back, since the CCI provides no way to read them.
+
+use std::cell::Cell;
+use std::ops::Deref;
+
+use id::DeviceId;
+use motor_control::{BaseMotorController, ControlMode, DemandType, TalonSRX, VictorSPX};
+
+/// Interface for speed controlling devices, as in WPILib.
+pub trait SpeedController {
+    /// Common interface for setting the speed of a speed controller.
+    ///
+    /// * `speed` - The speed to set.  Value should be between -1.0 and 1.0.
+    fn set(&self, speed: f64);
+    /// Common interface for getting the current set speed of a speed controller.
+    fn get(&self) -> f64;
+    /// Common interface for inverting direction of a speed controller.
+    fn set_inverted(&self, is_inverted: bool);
+    /// Common interface for returning the inversion state of a speed controller.
+    fn get_inverted(&self) -> bool;
+    /// Common interface for disabling a motor.
+    fn disable(&self);
+    /// Common interface to stop the motor until `set` is called again.
+    fn stop_motor(&self);
+    /// Write out the PID value as seen in the PIDOutput base object.
+    fn pid_write(&self, output: f64) {
+        self.set(output)
+    }
+}
+
+/// A motor controller usable as a WPILib `SpeedController`.
+///
+/// Derefs to the wrapped controller for everything else.
+#[derive(Debug)]
+pub struct WpiMotorController<M> {
+    inner: M,
+    speed: Cell<f64>,
+    inverted: Cell<bool>,
+}
+
+/// A Talon SRX usable as a WPILib `SpeedController`.
+pub type WpiTalonSRX = WpiMotorController<TalonSRX>;
+/// A Victor SPX usable as a WPILib `SpeedController`.
+pub type WpiVictorSPX = WpiMotorController<VictorSPX>;
+
+impl<M: BaseMotorController> WpiMotorController<M> {
+    pub fn new(device_number: DeviceId) -> Self {
+        Self::from(M::new(device_number))
+    }
+    pub fn into_inner(self) -> M {
+        self.inner
+    }
+}
+
+impl<M: BaseMotorController> From<M> for WpiMotorController<M> {
+    fn from(inner: M) -> Self {
+        WpiMotorController {
+            inner,
+            speed: Cell::new(0.0),
+            inverted: Cell::new(false),
+        }
+    }
+}
+
+impl<M> Deref for WpiMotorController<M> {
+    type Target = M;
+    fn deref(&self) -> &M {
+        &self.inner
+    }
+}
+
+impl<M: BaseMotorController> SpeedController for WpiMotorController<M> {
+    fn set(&self, speed: f64) {
+        self.speed.set(speed);
+        self.inner
+            .set(ControlMode::PercentOutput, speed, DemandType::Neutral, 0.0);
+    }
+    fn get(&self) -> f64 {
+        self.speed.get()
+    }
+    fn set_inverted(&self, is_inverted: bool) {
+        self.inverted.set(is_inverted);
+        self.inner.set_inverted(is_inverted);
+    }
+    fn get_inverted(&self) -> bool {
+        self.inverted.get()
+    }
+    fn disable(&self) {
+        self.inner.neutral_output();
+    }
+    fn stop_motor(&self) {
+        self.inner.neutral_output();
+    }
+}
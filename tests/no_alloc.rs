@@ -0,0 +1,81 @@
+//! Checks that the calls made every robot loop don't allocate.
+
+extern crate ctre;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use ctre::control::{PercentOutputRequest, VelocityRequest};
+use ctre::motor_control::{BaseMotorController, ControlMode, DemandType, Slot, TalonSRX};
+use ctre::DeviceId;
+
+/// Counts the current thread's allocations, so other test threads don't interfere.
+struct CountingAlloc;
+
+thread_local!(static ALLOCATIONS: Cell<usize> = const { Cell::new(0) });
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOC: CountingAlloc = CountingAlloc;
+
+fn allocations<F: FnMut()>(mut f: F) -> usize {
+    // Warm up first, so lazily created state isn't counted.
+    f();
+    let before = ALLOCATIONS.with(Cell::get);
+    for _ in 0..100 {
+        f();
+    }
+    ALLOCATIONS.with(Cell::get) - before
+}
+
+#[test]
+fn control_calls_dont_allocate() {
+    let talon = TalonSRX::new(DeviceId::new(1));
+    let velocity = VelocityRequest::new(1000.).with_slot(Slot::S1).with_ff(0.1);
+    assert_eq!(
+        allocations(|| {
+            talon.set(ControlMode::PercentOutput, 0.5, DemandType::Neutral, 0.);
+            talon.apply(&PercentOutputRequest::new(0.25)).unwrap();
+            talon.apply(&velocity).unwrap();
+        }),
+        0
+    );
+}
+
+#[test]
+fn getters_dont_allocate() {
+    let talon = TalonSRX::new(DeviceId::new(2));
+    let mut errors = Vec::with_capacity(16);
+    assert_eq!(
+        allocations(|| {
+            talon.get_selected_sensor_position(0).unwrap();
+            talon.get_selected_sensor_velocity(0).unwrap();
+            talon.get_motor_output_percent().unwrap();
+            talon.get_closed_loop_error(0).unwrap();
+            talon.get_faults().unwrap();
+            talon.recent_errors_into(&mut errors);
+        }),
+        0
+    );
+}
+
+#[test]
+fn errors_dont_allocate() {
+    let talon = TalonSRX::new(DeviceId::new(3));
+    // The error log is allocated up front, so recording an error only fills it.
+    assert_eq!(
+        allocations(|| {
+            talon.normalize_feedback_to(8192., 0, 0).unwrap_err();
+        }),
+        0
+    );
+}
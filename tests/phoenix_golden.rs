@@ -0,0 +1,74 @@
+//! Checks the demand arithmetic against Phoenix 5's C++ implementation.
+//!
+//! Expected values were worked through the C++ (`BaseMotorController::Set`,
+//! `::Follow` and the feedback coefficient's range check) for each input, so
+//! a port that drifts from Phoenix fails here rather than on a robot.
+
+extern crate ctre;
+
+use ctre::demand::{current_demand, feedback_coefficient, follower_demand, follower_id24};
+
+const TALON_5: i32 = 0x0204_0005;
+const VICTOR_3: i32 = 0x0104_0003;
+const TALON_62: i32 = 0x0204_003E;
+
+#[test]
+fn follower_ids() {
+    let golden = [
+        (TALON_5, 0x02_0405),
+        (VICTOR_3, 0x01_0403),
+        (TALON_62, 0x02_043E),
+        (0x0204_0000, 0x02_0400),
+    ];
+    for &(base_id, id24) in &golden {
+        assert_eq!(follower_id24(base_id), id24, "base id {:#x}", base_id);
+    }
+}
+
+#[test]
+fn follower_demands() {
+    let golden = [
+        // A device number is combined with the follower's own class.
+        (TALON_5, 7., 132_103.),
+        (VICTOR_3, 62., 66_622.),
+        (VICTOR_3, 0., 66_560.),
+        // Fractions of a device number are truncated.
+        (TALON_5, 7.9, 132_103.),
+        // Anything else is already an id24, e.g. a Victor following a Talon.
+        (VICTOR_3, 132_101., 132_101.),
+        (TALON_5, 63., 63.),
+    ];
+    for &(base_id, demand0, sent) in &golden {
+        assert_eq!(
+            follower_demand(base_id, demand0),
+            sent,
+            "demand {}",
+            demand0
+        );
+    }
+}
+
+#[test]
+fn current_milliamps() {
+    let golden = [
+        (1.5, 1500),
+        (-2.25, -2250),
+        (0.0015, 1),
+        (-0.0015, -1),
+        (40., 40_000),
+        (0., 0),
+    ];
+    for &(amps, milliamps) in &golden {
+        assert_eq!(current_demand(amps), milliamps, "{} A", amps);
+    }
+}
+
+#[test]
+fn feedback_coefficients() {
+    assert_eq!(feedback_coefficient(4096., 360.), Some(0.087_890_625));
+    assert_eq!(feedback_coefficient(4096., 4096.), Some(1.));
+    assert_eq!(feedback_coefficient(1024., 360.), Some(0.351_562_5));
+    assert_eq!(feedback_coefficient(1024., 4096.), None);
+    assert_eq!(feedback_coefficient(4096., 0.), None);
+    assert_eq!(feedback_coefficient(4096., -360.), None);
+}